@@ -0,0 +1,181 @@
+use bevy::prelude::*;
+use bevy_ggrs::{GGRSPlugin, SessionType};
+use bevy_rapier2d::prelude::*;
+use bytemuck::{Pod, Zeroable};
+use ggrs::{Config, InputStatus, PlayerHandle, PlayerType, SessionBuilder};
+
+const FPS: usize = 60;
+
+const INPUT_LEFT: u8 = 1 << 0;
+const INPUT_RIGHT: u8 = 1 << 1;
+const INPUT_JUMP: u8 = 1 << 2;
+
+struct GgrsConfig;
+
+impl Config for GgrsConfig {
+    type Input = PlayerInput;
+    type State = u8;
+    type Address = String;
+}
+
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Pod, Zeroable)]
+struct PlayerInput {
+    buttons: u8,
+}
+
+#[derive(Component)]
+struct Player {
+    handle: PlayerHandle,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // A sync-test session simulates every frame twice, rolling back to check that the
+    // checksums of the physics world are the same.
+    let session = SessionBuilder::<GgrsConfig>::new()
+        .with_num_players(2)
+        .with_check_distance(2)
+        .add_player(PlayerType::Local, 0)?
+        .add_player(PlayerType::Local, 1)?
+        .start_synctest_session()?;
+
+    let mut app = App::new();
+
+    // The physics stages are run by the rollback schedule, once per GGRS frame. The game logic
+    // moving the players is added before them.
+    let mut schedule = RapierPhysicsPlugin::<NoUserData>::rollback_schedule();
+    schedule.add_stage_before(
+        PhysicsStages::SyncBackend,
+        "move_players",
+        SystemStage::parallel().with_system(move_players),
+    );
+
+    GGRSPlugin::<GgrsConfig>::new()
+        .with_update_frequency(FPS)
+        .with_input_system(read_input)
+        .register_physics_rollback_types()
+        .with_rollback_schedule(schedule)
+        .build(&mut app);
+
+    app.insert_resource(ClearColor(Color::rgb(
+        0xF9 as f32 / 255.0,
+        0xF9 as f32 / 255.0,
+        0xFF as f32 / 255.0,
+    )))
+    .insert_resource(Msaa::default())
+    // The physics must advance by exactly one GGRS frame per simulation step.
+    .insert_resource(RapierConfiguration {
+        timestep_mode: TimestepMode::Fixed {
+            dt: 1.0 / FPS as f32,
+            substeps: 1,
+        },
+        ..default()
+    })
+    .insert_resource(session)
+    .insert_resource(SessionType::SyncTestSession)
+    .add_plugins(DefaultPlugins)
+    .add_plugin(
+        RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0).with_default_system_setup(false),
+    )
+    .add_stage_before(
+        CoreStage::Last,
+        PhysicsStages::DetectDespawn,
+        SystemStage::parallel().with_system_set(RapierPhysicsPlugin::<NoUserData>::get_systems(
+            PhysicsStages::DetectDespawn,
+        )),
+    )
+    .add_plugin(RapierDebugRenderPlugin::default())
+    .add_startup_system(setup_graphics)
+    .add_startup_system(setup_physics)
+    .run();
+
+    Ok(())
+}
+
+fn read_input(handle: In<PlayerHandle>, keyboard_input: Res<Input<KeyCode>>) -> PlayerInput {
+    let (left, right, jump) = if handle.0 == 0 {
+        (KeyCode::A, KeyCode::D, KeyCode::W)
+    } else {
+        (KeyCode::Left, KeyCode::Right, KeyCode::Up)
+    };
+
+    let mut buttons = 0;
+    if keyboard_input.pressed(left) {
+        buttons |= INPUT_LEFT;
+    }
+    if keyboard_input.pressed(right) {
+        buttons |= INPUT_RIGHT;
+    }
+    if keyboard_input.pressed(jump) {
+        buttons |= INPUT_JUMP;
+    }
+
+    PlayerInput { buttons }
+}
+
+fn move_players(
+    inputs: Res<Vec<(PlayerInput, InputStatus)>>,
+    mut players: Query<(&Player, &mut ExternalImpulse)>,
+) {
+    for (player, mut impulse) in players.iter_mut() {
+        let buttons = inputs[player.handle].0.buttons;
+        let mut direction = Vec2::ZERO;
+
+        if buttons & INPUT_LEFT != 0 {
+            direction.x -= 1.0;
+        }
+        if buttons & INPUT_RIGHT != 0 {
+            direction.x += 1.0;
+        }
+        if buttons & INPUT_JUMP != 0 {
+            direction.y += 2.0;
+        }
+
+        impulse.impulse = direction * 2.0;
+    }
+}
+
+fn setup_graphics(mut commands: Commands) {
+    commands.spawn_bundle(Camera2dBundle {
+        transform: Transform::from_xyz(0.0, 200.0, 0.0),
+        ..default()
+    });
+}
+
+fn setup_physics(mut commands: Commands) {
+    /*
+     * Ground
+     */
+    // NOTE: the ground is a fixed rigid-body because a collider without rigid-body is moved to
+    //       its transform one step after its creation, which isn’t rolled back.
+    commands
+        .spawn_bundle(TransformBundle::from(Transform::from_xyz(0.0, -100.0, 0.0)))
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(500.0, 10.0));
+
+    /*
+     * Players
+     */
+    for handle in 0..2 {
+        let x = if handle == 0 { -100.0 } else { 100.0 };
+
+        commands
+            .spawn_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(20.0))
+            .insert(ExternalImpulse::default())
+            .insert(Player { handle });
+    }
+
+    /*
+     * Boxes pushed around by the players
+     */
+    for i in 0..10 {
+        let x = (i as f32 - 4.5) * 45.0;
+
+        commands
+            .spawn_bundle(TransformBundle::from(Transform::from_xyz(x, 100.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::cuboid(20.0, 20.0));
+    }
+}
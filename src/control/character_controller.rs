@@ -1,11 +1,21 @@
 use crate::geometry::{Collider, Toi};
 use crate::math::{Real, Rot, Vect};
-use bevy::prelude::*;
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
 
 use crate::plugin::RapierContext;
 pub use rapier::control::CharacterAutostep;
 pub use rapier::control::CharacterLength;
-use rapier::prelude::{ColliderSet, InteractionGroups, QueryFilterFlags};
+use rapier::parry::bounding_volume::BoundingVolume;
+use rapier::parry::query::{DefaultQueryDispatcher, PersistentQueryDispatcher};
+use rapier::prelude::{
+    ColliderHandle, ColliderSet, ContactManifold, InteractionGroups, Isometry, QueryFilter,
+    QueryFilterFlags, QueryPipeline, Ray, RigidBodyHandle, RigidBodySet, Shape, Vector,
+};
+use std::collections::{HashMap, HashSet};
 
 /// A collision between the character and its environment during its movement.
 #[derive(Copy, Clone, PartialEq, Debug)]
@@ -75,6 +85,10 @@ pub struct MoveShapeOptions {
     pub min_slope_slide_angle: Real,
     /// Should the character apply forces to dynamic bodies in its path?
     pub apply_impulse_to_dynamic_bodies: bool,
+    /// How the character pushes the dynamic bodies in its path.
+    ///
+    /// This field isn’t used if `Self::apply_impulse_to_dynamic_bodies` is set to `false`.
+    pub pushing: CharacterPushing,
     /// Should the character be automatically snapped to the ground if the distance between
     /// the ground and its feed are smaller than the specified threshold?
     pub snap_to_ground: Option<CharacterLength>,
@@ -91,23 +105,454 @@ impl Default for MoveShapeOptions {
             max_slope_climb_angle: def.max_slope_climb_angle,
             min_slope_slide_angle: def.min_slope_slide_angle,
             apply_impulse_to_dynamic_bodies: true,
+            pushing: CharacterPushing::default(),
             snap_to_ground: def.snap_to_ground,
         }
     }
 }
 
+/// How a character pushes the dynamic rigid-bodies in its path.
+///
+/// The impulse applied to a pushed body depends on the ratio between its mass and the mass of
+/// the character (e.g. `KinematicCharacterController::custom_mass`): a light crate is shoved at
+/// the speed of the character, while a heavy car is barely nudged.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterPushing {
+    /// The maximum force the character applies to each pushed rigid-body.
+    pub max_force: Real,
+    /// If set, only the colliders with collision groups compatible with these groups are pushed.
+    pub groups: Option<InteractionGroups>,
+}
+
+impl Default for CharacterPushing {
+    fn default() -> Self {
+        Self {
+            max_force: Real::MAX,
+            groups: None,
+        }
+    }
+}
+
+impl CharacterPushing {
+    /// Applies the impulses of the collisions of a character movement to the dynamic bodies
+    /// touching the character, and returns the sum of these impulses.
+    ///
+    /// This is the same as `rapier::control::KinematicCharacterController::solve_character_collision_impulses`,
+    /// with the force limit and group filter of `self`, except that each body is only pushed
+    /// by the first collision of the movement touching it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn solve_character_collision_impulses(
+        &self,
+        controller: &rapier::control::KinematicCharacterController,
+        dt: Real,
+        bodies: &mut RigidBodySet,
+        colliders: &ColliderSet,
+        queries: &QueryPipeline,
+        character_shape: &dyn Shape,
+        character_mass: Real,
+        collisions: &[rapier::control::CharacterCollision],
+        filter: QueryFilter,
+    ) -> Vector<Real> {
+        let extents = character_shape.compute_local_aabb().extents();
+        let up_extent = extents.dot(&controller.up);
+        let prediction = match controller.offset {
+            CharacterLength::Absolute(offset) => offset,
+            CharacterLength::Relative(offset) => offset * up_extent,
+        } * 1.1;
+
+        let dispatcher = DefaultQueryDispatcher;
+        let mut manifolds: Vec<ContactManifold> = vec![];
+        let mut pushed: HashSet<RigidBodyHandle> = HashSet::new();
+        let mut total_impulse = Vector::zeros();
+
+        for collision in collisions {
+            let movement_to_transfer = *collision.toi.normal1
+                * collision.translation_remaining.dot(&collision.toi.normal1);
+            let character_aabb = character_shape
+                .compute_aabb(&collision.character_pos)
+                .loosened(prediction);
+            manifolds.clear();
+
+            queries.colliders_with_aabb_intersecting_aabb(&character_aabb, |handle| {
+                if let Some(collider) = colliders.get(*handle) {
+                    let pushable = self
+                        .groups
+                        .map(|groups| groups.test(collider.collision_groups()))
+                        .unwrap_or(true);
+                    if let Some(parent) = collider.parent().filter(|_| pushable) {
+                        if !pushed.contains(&parent)
+                            && filter.test(bodies, *handle, collider)
+                            && bodies.get(parent).map(|rb| rb.is_dynamic()) == Some(true)
+                        {
+                            let pos12 = collision.character_pos.inv_mul(collider.position());
+                            let prev_manifolds_len = manifolds.len();
+                            let _ = dispatcher.contact_manifolds(
+                                &pos12,
+                                character_shape,
+                                collider.shape(),
+                                prediction,
+                                &mut manifolds,
+                                &mut None,
+                            );
+
+                            for m in &mut manifolds[prev_manifolds_len..] {
+                                m.data.rigid_body2 = Some(parent);
+                                m.data.normal = collision.character_pos * m.local_n1;
+                            }
+                        }
+                    }
+                }
+                true
+            });
+
+            let velocity_to_transfer = movement_to_transfer / dt;
+            let mut budgets: HashMap<RigidBodyHandle, Real> = HashMap::new();
+
+            for manifold in &manifolds {
+                let body_handle = match manifold.data.rigid_body2 {
+                    Some(handle) => handle,
+                    None => continue,
+                };
+                let body = match bodies.get_mut(body_handle) {
+                    Some(body) => body,
+                    None => continue,
+                };
+                let budget = budgets.entry(body_handle).or_insert(self.max_force * dt);
+
+                for pt in &manifold.points {
+                    if pt.dist <= prediction {
+                        let body_mass = body.mass();
+                        let contact_point = body.position() * pt.local_p2;
+                        let delta_vel_per_contact = (velocity_to_transfer
+                            - body.velocity_at_point(&contact_point))
+                        .dot(&manifold.data.normal);
+                        let mass_ratio = body_mass * character_mass / (body_mass + character_mass);
+                        let impulse = (delta_vel_per_contact.max(0.0) * mass_ratio).min(*budget);
+
+                        if impulse > 0.0 {
+                            *budget -= impulse;
+                            body.apply_impulse_at_point(
+                                manifold.data.normal * impulse,
+                                contact_point,
+                                true,
+                            );
+                            total_impulse += manifold.data.normal * impulse;
+                        }
+                    }
+                }
+            }
+
+            pushed.extend(budgets.into_keys());
+        }
+
+        total_impulse
+    }
+}
+
+/// The configuration of the ledge detection of a [`KinematicCharacterController`].
+///
+/// When the character moves horizontally toward a wall, the top of the wall is searched in front
+/// of the character, and reported as a [`CharacterLedge`] if it can be grabbed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterLedgeDetection {
+    /// The maximum horizontal distance between the character and the wall.
+    pub reach: Real,
+    /// The minimum height of the ledge, relative to the top of the character’s shape.
+    pub min_height: Real,
+    /// The maximum height of the ledge, relative to the top of the character’s shape.
+    pub max_height: Real,
+}
+
+impl Default for CharacterLedgeDetection {
+    fn default() -> Self {
+        Self {
+            reach: 0.5,
+            min_height: -0.3,
+            max_height: 0.3,
+        }
+    }
+}
+
+impl CharacterLedgeDetection {
+    /// Searches for a ledge in front of a character moving in the horizontal `direction`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn detect(
+        &self,
+        physics_scale: Real,
+        controller: &rapier::control::KinematicCharacterController,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        queries: &QueryPipeline,
+        character_shape: &dyn Shape,
+        character_pos: &Isometry<Real>,
+        direction: &Vector<Real>,
+        filter: QueryFilter,
+    ) -> Option<CharacterLedge> {
+        let up = *controller.up;
+        let aabb = character_shape.compute_aabb(character_pos);
+        let center = aabb.center();
+        let half_extents = aabb.half_extents();
+        let top = up.dot(&center.coords) + half_extents.dot(&up.abs());
+        let side_extent = half_extents.dot(&direction.abs());
+        let (reach, min_height, max_height) = (
+            self.reach / physics_scale,
+            self.min_height / physics_scale,
+            self.max_height / physics_scale,
+        );
+
+        // Find the wall at the lowest height of the ledge.
+        let wall_origin = center + up * (top + min_height - up.dot(&center.coords));
+        let (_, wall_hit) = queries.cast_ray_and_get_normal(
+            bodies,
+            colliders,
+            &Ray::new(wall_origin, *direction),
+            side_extent + reach,
+            true,
+            filter,
+        )?;
+
+        // Find the top of the wall, slightly behind its surface.
+        let probe_depth = side_extent * 0.25;
+        let top_origin =
+            wall_origin + direction * (wall_hit.toi + probe_depth) + up * (max_height - min_height);
+        let (handle, top_hit) = queries.cast_ray_and_get_normal(
+            bodies,
+            colliders,
+            &Ray::new(top_origin, -up),
+            max_height - min_height,
+            true,
+            filter,
+        )?;
+
+        if top_hit.toi <= 0.0 || top_hit.normal.dot(&up) < controller.max_slope_climb_angle.cos() {
+            // The wall is too high, or its top is too steep.
+            return None;
+        }
+
+        let grab_point =
+            wall_origin + direction * wall_hit.toi + up * (max_height - min_height - top_hit.toi);
+
+        Some(CharacterLedge {
+            entity: RapierContext::collider_entity_with_set(colliders, handle)?,
+            grab_point: (grab_point.coords * physics_scale).into(),
+            top_normal: top_hit.normal.into(),
+            wall_normal: wall_hit.normal.into(),
+        })
+    }
+}
+
+/// A ledge found in front of a character, see [`CharacterLedgeDetection`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterLedge {
+    /// The entity of the collider of the top of the ledge.
+    pub entity: Entity,
+    /// The point at the edge of the ledge, on the surface of the wall.
+    pub grab_point: Vect,
+    /// The normal of the top surface of the ledge.
+    pub top_normal: Vect,
+    /// The normal of the wall below the ledge.
+    pub wall_normal: Vect,
+}
+
+/// The configuration of the wall detection of a [`KinematicCharacterController`].
+///
+/// After each movement, the shape of the character is cast toward its left and its right to
+/// find the nearby walls, e.g. for wall-running or wall-jumping. In 2D, the left and the right are
+/// the two directions orthogonal to `up`. In 3D, they are relative to the horizontal direction of
+/// the movement of the character, and no wall is detected if it doesn’t move horizontally.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterWallDetection {
+    /// The maximum distance between the character and the walls it detects.
+    pub distance: Real,
+}
+
+impl Default for CharacterWallDetection {
+    fn default() -> Self {
+        Self { distance: 0.5 }
+    }
+}
+
+impl CharacterWallDetection {
+    /// Searches for walls on the left and the right of a character, given the horizontal
+    /// direction of its movement.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn detect(
+        &self,
+        physics_scale: Real,
+        controller: &rapier::control::KinematicCharacterController,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        queries: &QueryPipeline,
+        character_shape: &dyn Shape,
+        character_pos: &Isometry<Real>,
+        direction: Option<Vector<Real>>,
+        filter: QueryFilter,
+    ) -> (Option<CharacterWall>, Option<CharacterWall>) {
+        let up = *controller.up;
+        #[cfg(feature = "dim2")]
+        let right = {
+            let _ = direction;
+            Some(Vector::new(up.y, -up.x))
+        };
+        #[cfg(feature = "dim3")]
+        let right = direction.map(|direction| direction.cross(&up));
+
+        let max_wall_cos = controller.max_slope_climb_angle.cos();
+        let probe = |side: Vector<Real>| {
+            let (handle, toi) = queries.cast_shape(
+                bodies,
+                colliders,
+                character_pos,
+                &side,
+                character_shape,
+                self.distance / physics_scale,
+                false,
+                filter,
+            )?;
+            let normal = toi.normal1.into_inner();
+            if normal.dot(&up).abs() >= max_wall_cos {
+                // This is a ground or a ceiling.
+                return None;
+            }
+
+            Some(CharacterWall {
+                entity: RapierContext::collider_entity_with_set(colliders, handle)?,
+                normal: normal.into(),
+                distance: toi.toi * physics_scale,
+            })
+        };
+
+        match right {
+            Some(right) => (probe(-right), probe(right)),
+            None => (None, None),
+        }
+    }
+}
+
+/// A wall found next to a character, see [`CharacterWallDetection`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterWall {
+    /// The entity of the collider of the wall.
+    pub entity: Entity,
+    /// The normal of the wall, pointing toward the character.
+    pub normal: Vect,
+    /// The distance between the character and the wall.
+    pub distance: Real,
+}
+
+/// How a [`KinematicCharacterController`] behaves on slopes steeper than its
+/// `max_slope_climb_angle`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum SteepSlopeBehavior {
+    /// Steep slopes can’t be climbed, and the character only slides down the part of its own
+    /// movement pushing it into the slope.
+    #[default]
+    Block,
+    /// Steep slopes are treated like any other ground: they can be climbed, and the character
+    /// doesn’t slide down.
+    Allow,
+    /// Steep slopes can’t be climbed, and the character slides down with the given acceleration
+    /// while it stands on one.
+    ///
+    /// The velocity of the slide is reported by
+    /// `KinematicCharacterControllerOutput::slide_velocity`, and is reset when the character
+    /// leaves the steep slope.
+    Slide {
+        /// The acceleration of the character sliding down the slope.
+        acceleration: Real,
+    },
+}
+
+/// How much of the motion of the ground a [`KinematicCharacterController`] standing on it inherits.
+///
+/// Each factor is usually between `0.0` (the motion is ignored) and `1.0` (the character follows
+/// the ground exactly).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PlatformInheritance {
+    /// The fraction of the motion of the ground orthogonal to the `up` vector that is inherited.
+    pub horizontal: Real,
+    /// The fraction of the motion of the ground along the `up` vector that is inherited.
+    pub vertical: Real,
+    /// The fraction of the rotation of the ground around the `up` vector that is applied to the
+    /// rotation of the character.
+    pub angular: Real,
+}
+
+impl PlatformInheritance {
+    /// The character follows the motion of the ground exactly.
+    pub const FULL: Self = Self {
+        horizontal: 1.0,
+        vertical: 1.0,
+        angular: 1.0,
+    };
+    /// The character ignores the motion of the ground.
+    pub const NONE: Self = Self {
+        horizontal: 0.0,
+        vertical: 0.0,
+        angular: 0.0,
+    };
+}
+
+impl Default for PlatformInheritance {
+    fn default() -> Self {
+        Self::FULL
+    }
+}
+
+/// The built-in vertical movement of a [`KinematicCharacterController`], integrating gravity
+/// and jumps.
+///
+/// The resulting vertical velocity is stored in
+/// [`KinematicCharacterControllerOutput::vertical_velocity`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CharacterVerticalMovement {
+    /// The gravity accelerating the character along `-up`.
+    ///
+    /// If `None`, the component of `RapierConfiguration::gravity` along `-up` is used.
+    pub gravity: Option<Real>,
+    /// The maximum falling speed of the character.
+    pub terminal_velocity: Real,
+    /// The upward velocity given to the character when it jumps.
+    pub jump_speed: Real,
+    /// The time (in seconds) after leaving the ground during which the character can still jump.
+    pub coyote_time: Real,
+}
+
+impl Default for CharacterVerticalMovement {
+    fn default() -> Self {
+        Self {
+            gravity: None,
+            terminal_velocity: Real::MAX,
+            jump_speed: 5.0,
+            coyote_time: 0.1,
+        }
+    }
+}
+
 /// A character controller for kinematic bodies and free-standing colliders.
-#[derive(Clone, Debug, Component)] // TODO: Reflect
+//
+// NOTE: the custom shape and Rapier’s character settings and query filters aren’t reflectable,
+//       so this component is reflected as an opaque value.
+#[derive(Clone, Debug, Component, Reflect, FromReflect)]
+#[reflect_value(Component)]
 pub struct KinematicCharacterController {
     /// The translations we desire the character to move by if it doesn’t meet any obstacle.
     pub translation: Option<Vect>,
     /// The shape, and its position, to be used instead of the shape of the collider attached to
     /// the same entity is this `KinematicCharacterController`.
+    ///
+    /// If this is `None` and the rigid-body of the character has several colliders (or its
+    /// collider is attached to another entity, e.g. a child), all the colliders of the rigid-body
+    /// that aren’t sensors are merged into a compound shape and moved as a whole. Sensors, like
+    /// weapon hitboxes, never affect the movement of the character.
     pub custom_shape: Option<(Collider, Vect, Rot)>,
     /// The mass to be used for impulse of dynamic bodies. This replaces the mass of the rigid-body
     /// potentially associated to the collider attached to the same entity as this
     /// `KinematicCharacterController`.
     ///
+    /// This is the "virtual mass" of the character, compared to the mass of the bodies it pushes
+    /// (see [`CharacterPushing`]).
+    ///
     /// This field isn’t used if `Self::apply_impulse_to_dynamic_bodies` is set to `false`.
     pub custom_mass: Option<Real>,
     /// The direction that goes "up". Used to determine where the floor is, and the floor’s angle.
@@ -127,17 +572,76 @@ pub struct KinematicCharacterController {
     /// The minimum angle (radians) between the floor’s normal and the `up` vector before the
     /// character starts to slide down automatically.
     pub min_slope_slide_angle: Real,
+    /// How the character behaves on slopes steeper than `Self::max_slope_climb_angle`.
+    pub steep_slope_behavior: SteepSlopeBehavior,
     /// Should the character apply forces to dynamic bodies in its path?
     pub apply_impulse_to_dynamic_bodies: bool,
+    /// How the character pushes the dynamic bodies in its path.
+    ///
+    /// This field isn’t used if `Self::apply_impulse_to_dynamic_bodies` is set to `false`.
+    pub pushing: CharacterPushing,
     /// Should the character be automatically snapped to the ground if the distance between
     /// the ground and its feed are smaller than the specified threshold?
     pub snap_to_ground: Option<CharacterLength>,
+    /// If set, a character that was grounded before its movement is snapped down to the ground
+    /// below it, up to this distance, if it would leave the ground otherwise.
+    ///
+    /// Unlike `Self::snap_to_ground`, this doesn’t depend on the contacts at the starting position
+    /// of the character, so it keeps the character on the ground when it walks down stairs or
+    /// slopes. The character isn’t snapped while it moves up (e.g. after a jump).
+    pub snap_to_ground_while_descending: Option<CharacterLength>,
     /// Flags for filtering-out some categories of entities from the environment seen by the
     /// character controller.
     pub filter_flags: QueryFilterFlags,
     /// Groups for filtering-out some colliders from the environment seen by the character
     /// controller.
     pub filter_groups: Option<InteractionGroups>,
+    /// How much of the motion of the ground collider the character stands on is added to its
+    /// movement.
+    ///
+    /// The motion of the ground during the last simulation step is applied the next time the
+    /// character is moved, i.e., when `Self::translation` is set. Only the motion of grounds
+    /// attached to a non-fixed rigid-body is inherited.
+    pub platform_inheritance: PlatformInheritance,
+    /// If set, gravity and jumps are integrated automatically and added to `Self::translation`.
+    ///
+    /// The character is then moved every frame, even if `Self::translation` is `None`.
+    pub vertical_movement: Option<CharacterVerticalMovement>,
+    /// Should the character jump the next time it is moved?
+    ///
+    /// This is only used by `Self::vertical_movement`, and is reset after each movement. The
+    /// jump only happens if the character is grounded, or left the ground less than
+    /// `CharacterVerticalMovement::coyote_time` ago without jumping.
+    pub jump: bool,
+    /// Should the character drop through the one-way platform it stands on the next time it is
+    /// moved?
+    ///
+    /// The platform (see [`OneWayPlatform`](crate::geometry::OneWayPlatform)) is then ignored
+    /// until the character leaves it from below. This is reset after each movement.
+    pub drop_through: bool,
+    /// If set, ledges are searched in front of the character when it moves horizontally, and
+    /// reported by `KinematicCharacterControllerOutput::ledge`.
+    pub ledge_detection: Option<CharacterLedgeDetection>,
+    /// If set, walls are searched on the sides of the character after each movement, and
+    /// reported by `KinematicCharacterControllerOutput::left_wall` and
+    /// `KinematicCharacterControllerOutput::right_wall`.
+    pub wall_detection: Option<CharacterWallDetection>,
+    /// The minimum fraction of the height of the character submerged in a
+    /// [`FluidVolume`](crate::geometry::FluidVolume) for the character to swim.
+    ///
+    /// While swimming, the character isn’t snapped to the ground and can’t jump, and the gravity
+    /// of `Self::vertical_movement` is countered by the buoyancy and drag of the fluid. The
+    /// translation along `up` is applied as-is, letting the character swim in any direction.
+    pub min_swimming_submersion: Real,
+    /// The desired total height of the capsule of the character, from the bottom of its lower cap
+    /// to the top of its upper cap, before the scale of its transform.
+    ///
+    /// When set, the capsule of `Self::custom_shape`, or else of the collider attached to the same
+    /// entity, is resized to this height, keeping its lowest point in place (e.g. for crouching).
+    /// The capsule is only enlarged if it doesn’t overlap with any collider after its resize,
+    /// otherwise the resize is retried every frame, and
+    /// `KinematicCharacterControllerOutput::resize_blocked` is set to `true`.
+    pub target_height: Option<Real>,
 }
 
 impl KinematicCharacterController {
@@ -151,13 +655,18 @@ impl KinematicCharacterController {
             include_dynamic_bodies: autostep.include_dynamic_bodies,
         });
 
+        let (max_slope_climb_angle, min_slope_slide_angle) = match self.steep_slope_behavior {
+            SteepSlopeBehavior::Allow => (std::f32::consts::FRAC_PI_2, std::f32::consts::FRAC_PI_2),
+            _ => (self.max_slope_climb_angle, self.min_slope_slide_angle),
+        };
+
         Some(rapier::control::KinematicCharacterController {
             up: self.up.try_into().ok()?,
             offset: self.offset.map_absolute(|x| x / physics_scale),
             slide: self.slide,
             autostep,
-            max_slope_climb_angle: self.max_slope_climb_angle,
-            min_slope_slide_angle: self.min_slope_slide_angle,
+            max_slope_climb_angle,
+            min_slope_slide_angle,
             snap_to_ground: self
                 .snap_to_ground
                 .map(|x| x.map_absolute(|x| x / physics_scale)),
@@ -178,10 +687,21 @@ impl Default for KinematicCharacterController {
             autostep: def.autostep,
             max_slope_climb_angle: def.max_slope_climb_angle,
             min_slope_slide_angle: def.min_slope_slide_angle,
+            steep_slope_behavior: SteepSlopeBehavior::default(),
             apply_impulse_to_dynamic_bodies: true,
+            pushing: CharacterPushing::default(),
             snap_to_ground: def.snap_to_ground,
+            snap_to_ground_while_descending: None,
             filter_flags: QueryFilterFlags::default(),
             filter_groups: None,
+            platform_inheritance: PlatformInheritance::default(),
+            vertical_movement: None,
+            jump: false,
+            drop_through: false,
+            ledge_detection: None,
+            wall_detection: None,
+            min_swimming_submersion: 0.5,
+            target_height: None,
         }
     }
 }
@@ -191,7 +711,8 @@ impl Default for KinematicCharacterController {
 /// This component is automatically added after the first execution of a character control
 /// based on the `KinematicCharacterController` component with its
 /// `KinematicCharacterController::translation` set to a value other than `None`.
-#[derive(Clone, PartialEq, Debug, Default, Component)]
+#[derive(Clone, PartialEq, Debug, Default, Component, Reflect, FromReflect)]
+#[reflect_value(Component, MapEntities, PartialEq)]
 pub struct KinematicCharacterControllerOutput {
     /// Indicates whether the shape is grounded after its kinematic movement.
     pub grounded: bool,
@@ -199,8 +720,121 @@ pub struct KinematicCharacterControllerOutput {
     pub desired_translation: Vect,
     /// The translation calculated by the last character control step taking obstacles into account.
     pub effective_translation: Vect,
+    /// The angle (radians) of the rotation around `up` inherited from the ground during the last
+    /// character control step (see `KinematicCharacterController::platform_inheritance`).
+    pub inherited_angle: Real,
     /// Collisions between the character and obstacles found in its path.
     pub collisions: Vec<CharacterCollision>,
+    /// The entity of the collider the character stands on, if it is grounded.
+    pub ground: Option<Entity>,
+    /// The normal of the ground the character stands on, or its `up` vector if it isn’t grounded.
+    pub ground_normal: Vect,
+    /// The angle (radians) between the normal of the ground the character stands on and its `up`
+    /// vector, or zero if it isn’t grounded.
+    pub slope_angle: Real,
+    /// Whether the character stands on a slope it can’t climb, see
+    /// `KinematicCharacterController::steep_slope_behavior`.
+    pub on_steep_slope: bool,
+    /// The velocity of the character sliding down a steep slope, with
+    /// [`SteepSlopeBehavior::Slide`].
+    pub slide_velocity: Vect,
+    /// The velocity of the character along its `up` vector, integrated by
+    /// `KinematicCharacterController::vertical_movement`.
+    ///
+    /// It can be modified to give a vertical impulse to the character (e.g., a bounce pad).
+    pub vertical_velocity: Real,
+    /// The ledge found in front of the character during its last movement, if
+    /// `KinematicCharacterController::ledge_detection` is set.
+    pub ledge: Option<CharacterLedge>,
+    /// The wall found on the left of the character during its last movement, if
+    /// `KinematicCharacterController::wall_detection` is set.
+    pub left_wall: Option<CharacterWall>,
+    /// The wall found on the right of the character during its last movement, if
+    /// `KinematicCharacterController::wall_detection` is set.
+    pub right_wall: Option<CharacterWall>,
+    /// The fraction of the height of the character submerged in a
+    /// [`FluidVolume`](crate::geometry::FluidVolume) before its last movement.
+    pub submersion: Real,
+    /// Whether the character was swimming during its last movement, see
+    /// `KinematicCharacterController::min_swimming_submersion`.
+    pub swimming: bool,
+    /// The sum of the impulses applied by the character to the dynamic bodies it pushed during its
+    /// last movement.
+    ///
+    /// The opposite of this impulse is the reaction of the pushed bodies. It isn’t applied to the
+    /// kinematic character, but can be used to slow it down or knock it back.
+    pub push_impulse: Vect,
+    /// Whether the capsule of the character can’t be enlarged to
+    /// `KinematicCharacterController::target_height` because it would overlap with a collider.
+    pub resize_blocked: bool,
+    // The time since the character last stood on the ground, or `Real::MAX` after a jump.
+    pub(crate) airborne_time: Real,
+    // The sorted colliders hit or stood on during the last movement.
+    pub(crate) touching: Vec<Entity>,
+    // The ground collider and its position when the character was last moved.
+    pub(crate) ground_position: Option<(ColliderHandle, Isometry<Real>)>,
+    // The one-way platforms the character is dropping through.
+    pub(crate) dropped_platforms: Vec<ColliderHandle>,
+}
+
+impl MapEntities for KinematicCharacterControllerOutput {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        let map = |entity: &mut Entity| {
+            if let Ok(mapped) = entity_map.get(*entity) {
+                *entity = mapped;
+            }
+        };
+
+        self.collisions
+            .iter_mut()
+            .for_each(|collision| map(&mut collision.entity));
+        self.ground.iter_mut().for_each(map);
+        self.ledge
+            .iter_mut()
+            .for_each(|ledge| map(&mut ledge.entity));
+        self.left_wall
+            .iter_mut()
+            .chain(self.right_wall.iter_mut())
+            .for_each(|wall| map(&mut wall.entity));
+        self.touching.iter_mut().for_each(map);
+        self.touching.sort();
+        Ok(())
+    }
+}
+
+/// If the `TimestepMode::Interpolated` mode is set and this component is present, the `Transform`
+/// of a [`KinematicCharacterController`] is automatically interpolated between its last two
+/// positions computed by the controller.
+///
+/// This component must be added to the entity moved by the controller, i.e., the entity of the
+/// rigid-body of the character if it has one. The character is then only moved on the frames where
+/// the physics simulation advances, and its `KinematicCharacterController::translation` is kept
+/// until then. Its `Transform` is restored to the end of the interpolation before it is moved, so
+/// the controller never starts from an interpolated position.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct CharacterInterpolation {
+    /// The starting point of the interpolation.
+    pub start: Option<Transform>,
+    /// The end point of the interpolation.
+    pub end: Option<Transform>,
+    // The interpolated transform last written, to detect changes made by the user.
+    pub(crate) current: Option<Transform>,
+}
+
+impl CharacterInterpolation {
+    /// Interpolates between the start and end transforms with `t` in the range `[0..1]`.
+    pub fn lerp_slerp(&self, t: f32) -> Option<Transform> {
+        if let (Some(start), Some(end)) = (self.start, self.end) {
+            Some(Transform {
+                translation: start.translation.lerp(end.translation, t),
+                rotation: start.rotation.slerp(end.rotation, t),
+                scale: end.scale,
+            })
+        } else {
+            None
+        }
+    }
 }
 
 /// The allowed movement computed by `RapierContext::move_shape`.
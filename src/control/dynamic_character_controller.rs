@@ -0,0 +1,103 @@
+use crate::math::{Real, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
+use rapier::prelude::{InteractionGroups, QueryFilterFlags};
+
+/// A character controller driving a dynamic rigid-body by changing its velocity.
+///
+/// Unlike the [`KinematicCharacterController`](crate::control::KinematicCharacterController),
+/// the character is moved by the physics solver: it has momentum, pushes the dynamic bodies in
+/// its path depending on their mass, and is pushed by them. This component must be added to an
+/// entity with a dynamic [`RigidBody`](crate::dynamics::RigidBody) and a collider (usually a
+/// capsule). Its rotations should be locked with
+/// [`LockedAxes::ROTATION_LOCKED`](crate::dynamics::LockedAxes::ROTATION_LOCKED) to keep it
+/// upright, and its collider should have no [`Friction`](crate::geometry::Friction) (with the
+/// `Min` combine rule) so it doesn’t slow down the character below its desired velocity.
+///
+/// Before each simulation step, the ground is searched right below the character. The velocity
+/// of the character orthogonal to `up` (or along the ground, if the character is grounded) is
+/// then accelerated toward `Self::desired_velocity`.
+//
+// NOTE: Rapier’s query filters aren’t reflectable, so this component is reflected as an opaque
+//       value.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect_value(Component, PartialEq)]
+pub struct DynamicCharacterController {
+    /// The velocity the character should reach, orthogonal to `up`.
+    ///
+    /// When the character is grounded, this velocity is projected on the ground, so the
+    /// character follows slopes instead of running into them or flying off them.
+    pub desired_velocity: Vect,
+    /// The direction that goes "up". Used to determine where the floor is, and the floor’s angle.
+    pub up: Vect,
+    /// The maximum acceleration of the character toward `Self::desired_velocity` while grounded.
+    pub max_acceleration: Real,
+    /// The maximum acceleration of the character toward `Self::desired_velocity` while it isn’t
+    /// grounded.
+    pub air_acceleration: Real,
+    /// The maximum angle (radians) between the floor’s normal and the `up` vector for the
+    /// character to be grounded.
+    ///
+    /// The character slides down steeper slopes, and can’t accelerate or jump on them.
+    pub max_ground_angle: Real,
+    /// The maximum distance between the collider of the character and the ground for the
+    /// character to be grounded.
+    pub ground_distance: Real,
+    /// The upward velocity given to the character when it jumps.
+    pub jump_speed: Real,
+    /// Should the character jump before the next simulation step?
+    ///
+    /// This is reset before each simulation step. The jump only happens if the character is
+    /// grounded.
+    pub jump: bool,
+    /// Flags for filtering-out some categories of entities from the ground detection.
+    pub filter_flags: QueryFilterFlags,
+    /// Groups for filtering-out some colliders from the ground detection.
+    pub filter_groups: Option<InteractionGroups>,
+}
+
+impl Default for DynamicCharacterController {
+    fn default() -> Self {
+        Self {
+            desired_velocity: Vect::ZERO,
+            up: Vect::Y,
+            max_acceleration: 50.0,
+            air_acceleration: 10.0,
+            max_ground_angle: std::f32::consts::FRAC_PI_4,
+            ground_distance: 0.05,
+            jump_speed: 5.0,
+            jump: false,
+            filter_flags: QueryFilterFlags::default(),
+            filter_groups: None,
+        }
+    }
+}
+
+/// The state of a [`DynamicCharacterController`].
+///
+/// This component is automatically added after the first update of a
+/// `DynamicCharacterController`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct DynamicCharacterControllerOutput {
+    /// Indicates whether the character stands on a ground that isn’t too steep.
+    pub grounded: bool,
+    /// The entity of the collider the character stands on, if it is grounded.
+    pub ground: Option<Entity>,
+    /// The normal of the ground the character stands on, or `up` if it isn’t grounded.
+    pub ground_normal: Vect,
+}
+
+impl MapEntities for DynamicCharacterControllerOutput {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Some(ground) = &mut self.ground {
+            if let Ok(mapped) = entity_map.get(*ground) {
+                *ground = mapped;
+            }
+        }
+        Ok(())
+    }
+}
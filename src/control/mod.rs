@@ -1,6 +1,13 @@
 pub use self::character_controller::{
-    CharacterAutostep, CharacterCollision, CharacterLength, KinematicCharacterController,
-    KinematicCharacterControllerOutput, MoveShapeOptions, MoveShapeOutput,
+    CharacterAutostep, CharacterCollision, CharacterInterpolation, CharacterLedge,
+    CharacterLedgeDetection, CharacterLength, CharacterPushing, CharacterVerticalMovement,
+    CharacterWall, CharacterWallDetection, KinematicCharacterController,
+    KinematicCharacterControllerOutput, MoveShapeOptions, MoveShapeOutput, PlatformInheritance,
+    SteepSlopeBehavior,
+};
+pub use self::dynamic_character_controller::{
+    DynamicCharacterController, DynamicCharacterControllerOutput,
 };
 
 mod character_controller;
+mod dynamic_character_controller;
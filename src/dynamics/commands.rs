@@ -0,0 +1,168 @@
+use crate::dynamics::{RigidBody, Velocity};
+use crate::plugin::RapierContext;
+use bevy::ecs::system::{Command, EntityCommands};
+use bevy::prelude::*;
+use rapier::prelude::{RigidBodyHandle, RigidBodyType};
+
+/// Extension trait for switching the type of a rigid-body at runtime.
+///
+/// Changing the value of the [`RigidBody`] component directly works too, but these
+/// helpers also take care of the velocity of the rigid-body and of waking up the
+/// rigid-bodies touching it or attached to it by a joint.
+pub trait RigidBodyCommandsExt {
+    /// Turns the rigid-body into a [`RigidBody::Dynamic`], keeping its current velocity.
+    fn make_dynamic(&mut self) -> &mut Self;
+    /// Turns the rigid-body into a [`RigidBody::Fixed`], stopping it.
+    fn make_fixed(&mut self) -> &mut Self;
+    /// Turns the rigid-body into a [`RigidBody::KinematicPositionBased`].
+    ///
+    /// The rigid-body stays at its current position until it is moved through its transform.
+    fn make_kinematic(&mut self) -> &mut Self;
+    /// Turns the rigid-body into a [`RigidBody::KinematicVelocityBased`], keeping its
+    /// current velocity.
+    fn make_kinematic_velocity_based(&mut self) -> &mut Self;
+}
+
+impl<'w, 's, 'a> RigidBodyCommandsExt for EntityCommands<'w, 's, 'a> {
+    fn make_dynamic(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(SetRigidBodyType {
+            entity,
+            rigid_body: RigidBody::Dynamic,
+        });
+        self
+    }
+
+    fn make_fixed(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(SetRigidBodyType {
+            entity,
+            rigid_body: RigidBody::Fixed,
+        });
+        self
+    }
+
+    fn make_kinematic(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(SetRigidBodyType {
+            entity,
+            rigid_body: RigidBody::KinematicPositionBased,
+        });
+        self
+    }
+
+    fn make_kinematic_velocity_based(&mut self) -> &mut Self {
+        let entity = self.id();
+        self.commands().add(SetRigidBodyType {
+            entity,
+            rigid_body: RigidBody::KinematicVelocityBased,
+        });
+        self
+    }
+}
+
+/// A command switching the type of a rigid-body, see [`RigidBodyCommandsExt`].
+pub struct SetRigidBodyType {
+    /// The entity of the rigid-body.
+    pub entity: Entity,
+    /// The new type of the rigid-body.
+    pub rigid_body: RigidBody,
+}
+
+impl Command for SetRigidBodyType {
+    fn write(self, world: &mut World) {
+        let mut new_velocity = None;
+
+        if let Some(mut context) = world.get_resource_mut::<RapierContext>() {
+            let context = &mut *context;
+            let scale = context.physics_scale();
+
+            if let Some(handle) = context.entity2body.get(&self.entity).copied() {
+                let neighbors = rigid_body_neighbors(context, handle);
+
+                if let Some(rb) = context.bodies.get_mut(handle) {
+                    let body_type = self.rigid_body.into();
+                    let linvel = *rb.linvel();
+                    #[cfg(feature = "dim2")]
+                    let angvel = rb.angvel();
+                    #[cfg(feature = "dim3")]
+                    let angvel = *rb.angvel();
+
+                    rb.set_body_type(body_type);
+                    match body_type {
+                        RigidBodyType::Dynamic | RigidBodyType::KinematicVelocityBased => {
+                            rb.set_linvel(linvel, false);
+                            rb.set_angvel(angvel, false);
+                        }
+                        RigidBodyType::KinematicPositionBased => {
+                            // Don’t move toward an outdated target.
+                            let position = *rb.position();
+                            rb.set_next_kinematic_position(position);
+                            rb.set_linvel(Default::default(), false);
+                            rb.set_angvel(Default::default(), false);
+                        }
+                        RigidBodyType::Fixed => {
+                            rb.set_linvel(Default::default(), false);
+                            rb.set_angvel(Default::default(), false);
+                        }
+                    }
+                    rb.wake_up(true);
+
+                    new_velocity = Some(Velocity {
+                        linvel: (rb.linvel() * scale).into(),
+                        #[cfg(feature = "dim2")]
+                        angvel: rb.angvel(),
+                        #[cfg(feature = "dim3")]
+                        angvel: (*rb.angvel()).into(),
+                    });
+                }
+
+                for neighbor in neighbors {
+                    if let Some(rb) = context.bodies.get_mut(neighbor) {
+                        if rb.is_dynamic() {
+                            rb.wake_up(true);
+                        }
+                    }
+                }
+            }
+        }
+
+        // The entity may have been despawned before the command was applied.
+        let mut entity = match world.get_entity_mut(self.entity) {
+            Some(entity) => entity,
+            None => return,
+        };
+        entity.insert(self.rigid_body);
+
+        if let Some(new_velocity) = new_velocity {
+            if let Some(mut velocity) = entity.get_mut::<Velocity>() {
+                *velocity = new_velocity;
+            }
+        }
+    }
+}
+
+// The rigid-bodies touching the given rigid-body, or attached to it by a joint.
+fn rigid_body_neighbors(context: &RapierContext, handle: RigidBodyHandle) -> Vec<RigidBodyHandle> {
+    let mut neighbors = vec![];
+
+    if let Some(rb) = context.bodies.get(handle) {
+        for collider in rb.colliders() {
+            for pair in context.narrow_phase.contacts_with(*collider) {
+                let other = if pair.collider1 == *collider {
+                    pair.collider2
+                } else {
+                    pair.collider1
+                };
+                neighbors.extend(context.colliders.get(other).and_then(|co| co.parent()));
+            }
+        }
+    }
+
+    for (rb1, rb2, ..) in context.impulse_joints.attached_joints(handle) {
+        neighbors.push(if rb1 == handle { rb2 } else { rb1 });
+    }
+
+    neighbors.extend(context.multibody_joints.attached_bodies(handle));
+    neighbors
+}
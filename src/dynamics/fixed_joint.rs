@@ -1,8 +1,12 @@
-use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::dynamics::{GenericJoint, GenericJointBuilder, JointSoftness};
 use crate::math::{Rot, Vect};
+use bevy::prelude::GlobalTransform;
+use bevy::reflect::{FromReflect, Reflect};
 use rapier::dynamics::JointAxesMask;
 
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 #[repr(transparent)]
 /// A fixed joint, locks all relative motion between two bodies.
 pub struct FixedJoint {
@@ -34,6 +38,20 @@ impl FixedJoint {
         self
     }
 
+    /// The softness of the locked axes of this joint, if they aren’t locked rigidly.
+    #[must_use]
+    pub fn softness(&self) -> Option<JointSoftness> {
+        self.data.softness()
+    }
+
+    /// Sets the softness of the locked axes of this joint.
+    ///
+    /// If `None`, the locked axes are locked rigidly.
+    pub fn set_softness(&mut self, softness: Option<JointSoftness>) -> &mut Self {
+        self.data.set_softness(softness);
+        self
+    }
+
     /// The joint’s basis, expressed in the first rigid-body’s local-space.
     #[must_use]
     pub fn local_basis1(&self) -> Rot {
@@ -90,7 +108,9 @@ impl From<FixedJoint> for GenericJoint {
 }
 
 /// Create fixed joints using the builder pattern.
-#[derive(Copy, Clone, Debug, PartialEq, Default)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Default, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 pub struct FixedJointBuilder(FixedJoint);
 
 impl FixedJointBuilder {
@@ -99,6 +119,26 @@ impl FixedJointBuilder {
         Self(FixedJoint::new())
     }
 
+    /// Creates a new builder for fixed joints, attaching the rigid-bodies at the given
+    /// world-space anchor in their current relative pose.
+    ///
+    /// The local anchors and bases are computed from the current transforms of both
+    /// rigid-bodies.
+    pub fn connect_in_place(
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+    ) -> Self {
+        let data = GenericJointBuilder::connect_in_place(
+            JointAxesMask::LOCKED_FIXED_AXES,
+            transform1,
+            transform2,
+            world_anchor,
+            Rot::default(),
+        );
+        Self(FixedJoint { data: data.build() })
+    }
+
     /// Sets the joint’s basis, expressed in the first rigid-body’s local-space.
     #[must_use]
     pub fn local_basis1(mut self, local_basis: Rot) -> Self {
@@ -127,6 +167,13 @@ impl FixedJointBuilder {
         self
     }
 
+    /// Makes the locked axes of this joint soft.
+    #[must_use]
+    pub fn softness(mut self, softness: JointSoftness) -> Self {
+        self.0.set_softness(Some(softness));
+        self
+    }
+
     /// Build the fixed joint.
     #[must_use]
     pub fn build(self) -> FixedJoint {
@@ -1,21 +1,32 @@
-use crate::dynamics::{FixedJoint, PrismaticJoint, RevoluteJoint};
+use crate::dynamics::{
+    FixedJoint, JointSoftness, PrismaticJoint, RevoluteJoint, RopeJoint, SpringJoint,
+};
 use crate::math::{Real, Rot, Vect};
+use bevy::{prelude::*, reflect::FromReflect};
 use rapier::dynamics::{
     GenericJoint as RapierGenericJoint, JointAxesMask, JointAxis, JointLimits, JointMotor,
     MotorModel,
 };
-use rapier::math::DIM;
+use rapier::math::{Matrix, Rotation, Vector, DIM, SPATIAL_DIM};
 
 #[cfg(feature = "dim3")]
 use crate::dynamics::SphericalJoint;
+#[cfg(feature = "serde-serialize")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
 
 /// The description of any joint.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq, Default)]
-#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Default, Reflect, FromReflect)]
+#[cfg_attr(
+    feature = "serde-serialize",
+    reflect_value(Debug, PartialEq, Serialize, Deserialize)
+)]
+#[cfg_attr(not(feature = "serde-serialize"), reflect_value(Debug, PartialEq))]
 pub struct GenericJoint {
     /// The raw Rapier description of the joint.
     pub raw: RapierGenericJoint,
+    /// The softness of the locked axes of the joint, if they aren’t locked rigidly.
+    pub softness: Option<JointSoftness>,
 }
 
 impl GenericJoint {
@@ -36,8 +47,95 @@ impl GenericJoint {
             motor.target_pos /= physics_scale;
         }
 
+        // NOTE: soft axes are emulated by acceleration-based motors pulling the locked axes
+        //       back to their locked position. These motors are independent from the physics scale.
+        if let Some(softness) = self.softness {
+            let (stiffness, damping) = softness.stiffness_and_damping();
+
+            for i in 0..SPATIAL_DIM {
+                let axis = JointAxesMask::from_bits_truncate(1 << i);
+                if self.raw.locked_axes.contains(axis) {
+                    self.raw.locked_axes.remove(axis);
+                    self.raw.motor_axes.insert(axis);
+                    self.raw.motors[i] = JointMotor {
+                        stiffness,
+                        damping,
+                        model: MotorModel::AccelerationBased,
+                        ..JointMotor::default()
+                    };
+                }
+            }
+        }
+
         self.raw
     }
+
+    /// Converts a Rapier joint into this joint, the inverse of [`Self::into_rapier`].
+    ///
+    /// The motors emulating soft axes are kept as motors.
+    pub fn from_rapier(mut raw: RapierGenericJoint, physics_scale: Real) -> Self {
+        raw.local_frame1.translation.vector *= physics_scale;
+        raw.local_frame2.translation.vector *= physics_scale;
+
+        for limit in &mut raw.limits[0..DIM] {
+            limit.min *= physics_scale;
+            limit.max *= physics_scale;
+        }
+
+        for motor in &mut raw.motors[0..DIM] {
+            motor.target_vel *= physics_scale;
+            motor.target_pos *= physics_scale;
+        }
+
+        Self {
+            raw,
+            softness: None,
+        }
+    }
+
+    /// Mirrors this joint across a plane with the given normal, e.g. to build the right arm of
+    /// a rig from its left arm.
+    ///
+    /// The anchors and bases of the joint are reflected across the plane, assuming both
+    /// rigid-bodies are mirrored with the same plane, like with
+    /// [`MirrorPlane::mirror_transform`](crate::dynamics::MirrorPlane::mirror_transform).
+    /// To keep the bases right-handed, their local `Y` axis is flipped too. As a result, the
+    /// coordinates along the linear `Y` axis and around the angular axes other than `AngY`
+    /// change sign, and their limits and motor targets are negated.
+    #[must_use]
+    pub fn mirrored(&self, normal: Vect) -> Self {
+        let mut result = *self;
+        let normal: Vector<Real> = normal.normalize().into();
+        let reflection = Matrix::identity() - normal * normal.transpose() * 2.0;
+        let mut flip = Matrix::identity();
+        flip[(1, 1)] = -1.0;
+
+        for frame in [&mut result.raw.local_frame1, &mut result.raw.local_frame2] {
+            let rotation = reflection * frame.rotation.to_rotation_matrix().into_inner() * flip;
+            frame.translation.vector = reflection * frame.translation.vector;
+            frame.rotation =
+                Rotation::from_rotation_matrix(&na::Rotation::from_matrix_unchecked(rotation));
+        }
+
+        #[cfg(feature = "dim2")]
+        let flipped_axes = [JointAxis::Y, JointAxis::AngX];
+        #[cfg(feature = "dim3")]
+        let flipped_axes = [JointAxis::Y, JointAxis::AngX, JointAxis::AngZ];
+
+        for axis in flipped_axes {
+            let i = axis as usize;
+            let limits = &mut result.raw.limits[i];
+            let (min, max) = (limits.min, limits.max);
+            limits.min = -max;
+            limits.max = -min;
+
+            let motor = &mut result.raw.motors[i];
+            motor.target_pos = -motor.target_pos;
+            motor.target_vel = -motor.target_vel;
+        }
+
+        result
+    }
 }
 
 /*
@@ -51,6 +149,7 @@ impl GenericJoint {
     pub fn new(locked_axes: JointAxesMask) -> Self {
         Self {
             raw: RapierGenericJoint::new(locked_axes),
+            softness: None,
         }
     }
 
@@ -168,6 +267,20 @@ impl GenericJoint {
         self
     }
 
+    /// The softness of the locked axes of this joint, if they aren’t locked rigidly.
+    #[must_use]
+    pub fn softness(&self) -> Option<JointSoftness> {
+        self.softness
+    }
+
+    /// Sets the softness of the locked axes of this joint.
+    ///
+    /// If `None`, the locked axes are locked rigidly.
+    pub fn set_softness(&mut self, softness: Option<JointSoftness>) -> &mut Self {
+        self.softness = softness;
+        self
+    }
+
     /// The joint limits along the specified axis.
     #[must_use]
     pub fn limits(&self, axis: JointAxis) -> Option<&JointLimits<Real>> {
@@ -291,6 +404,72 @@ impl GenericJoint {
         JointAxesMask::LOCKED_PRISMATIC_AXES
     );
 
+    /// Converts the joint to a rope joint, if it is one.
+    ///
+    /// A joint is considered to be a rope joint if it locks no axis, limits only its
+    /// `JointAxis::X` axis, and motorizes no other axis.
+    #[must_use]
+    pub fn as_rope(&self) -> Option<&RopeJoint> {
+        if self.is_rope() {
+            // SAFETY: this is OK because the target joint type is
+            //         a `repr(transparent)` newtype of `Joint`.
+            Some(unsafe { std::mem::transmute::<&GenericJoint, &RopeJoint>(self) })
+        } else {
+            None
+        }
+    }
+
+    /// Converts the joint to a mutable rope joint, if it is one.
+    #[must_use]
+    pub fn as_rope_mut(&mut self) -> Option<&mut RopeJoint> {
+        if self.is_rope() {
+            // SAFETY: this is OK because the target joint type is
+            //         a `repr(transparent)` newtype of `Joint`.
+            Some(unsafe { std::mem::transmute::<&mut GenericJoint, &mut RopeJoint>(self) })
+        } else {
+            None
+        }
+    }
+
+    fn is_rope(&self) -> bool {
+        self.raw.locked_axes.is_empty()
+            && self.raw.limit_axes == JointAxesMask::X
+            && JointAxesMask::X.contains(self.raw.motor_axes)
+    }
+
+    /// Converts the joint to a spring joint, if it is one.
+    ///
+    /// A joint is considered to be a spring joint if it locks and limits no axis, and
+    /// motorizes only its `JointAxis::X` axis.
+    #[must_use]
+    pub fn as_spring(&self) -> Option<&SpringJoint> {
+        if self.is_spring() {
+            // SAFETY: this is OK because the target joint type is
+            //         a `repr(transparent)` newtype of `Joint`.
+            Some(unsafe { std::mem::transmute::<&GenericJoint, &SpringJoint>(self) })
+        } else {
+            None
+        }
+    }
+
+    /// Converts the joint to a mutable spring joint, if it is one.
+    #[must_use]
+    pub fn as_spring_mut(&mut self) -> Option<&mut SpringJoint> {
+        if self.is_spring() {
+            // SAFETY: this is OK because the target joint type is
+            //         a `repr(transparent)` newtype of `Joint`.
+            Some(unsafe { std::mem::transmute::<&mut GenericJoint, &mut SpringJoint>(self) })
+        } else {
+            None
+        }
+    }
+
+    fn is_spring(&self) -> bool {
+        self.raw.locked_axes.is_empty()
+            && self.raw.limit_axes.is_empty()
+            && self.raw.motor_axes == JointAxesMask::X
+    }
+
     #[cfg(feature = "dim3")]
     joint_conversion_methods!(
         as_spherical,
@@ -300,9 +479,154 @@ impl GenericJoint {
     );
 }
 
+/// How a single degree of freedom of a [`GenericJoint`] is constrained.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Reflect, FromReflect)]
+pub enum JointAxisMotion {
+    /// The bodies can move freely along this axis.
+    #[default]
+    Free,
+    /// The bodies can’t move relative to each other along this axis.
+    Locked,
+    /// The bodies can move along this axis, within the given bounds.
+    Limited {
+        /// The lower bound of the relative translation or angle along this axis.
+        min: Real,
+        /// The upper bound of the relative translation or angle along this axis.
+        max: Real,
+    },
+}
+
+/// The motor driving a single degree of freedom of a [`GenericJoint`].
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+pub struct JointAxisMotor {
+    /// The relative translation or angle the motor is trying to reach.
+    pub target_pos: Real,
+    /// The relative velocity the motor is trying to reach.
+    pub target_vel: Real,
+    /// The stiffness of the motor, pulling toward `target_pos`.
+    pub stiffness: Real,
+    /// The damping of the motor, pulling toward `target_vel`.
+    pub damping: Real,
+    /// The maximum force the motor can deliver.
+    pub max_force: Real,
+}
+
+impl Default for JointAxisMotor {
+    fn default() -> Self {
+        Self {
+            target_pos: 0.0,
+            target_vel: 0.0,
+            stiffness: 0.0,
+            damping: 0.0,
+            max_force: Real::MAX,
+        }
+    }
+}
+
+/// The description of a single degree of freedom of a [`GenericJoint`].
+///
+/// This is a reflectable alternative to setting the joint’s axes masks directly.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+pub struct JointAxisDescription {
+    /// How the degree of freedom is constrained.
+    pub motion: JointAxisMotion,
+    /// The motor driving the degree of freedom, if any.
+    pub motor: Option<JointAxisMotor>,
+}
+
+impl JointAxisDescription {
+    /// A degree of freedom without any constraint.
+    pub const FREE: Self = Self {
+        motion: JointAxisMotion::Free,
+        motor: None,
+    };
+    /// A locked degree of freedom.
+    pub const LOCKED: Self = Self {
+        motion: JointAxisMotion::Locked,
+        motor: None,
+    };
+
+    /// A degree of freedom limited within `[min, max]`.
+    #[must_use]
+    pub fn limited(min: Real, max: Real) -> Self {
+        Self {
+            motion: JointAxisMotion::Limited { min, max },
+            motor: None,
+        }
+    }
+
+    /// Drives this degree of freedom with the given motor.
+    #[must_use]
+    pub fn with_motor(mut self, motor: JointAxisMotor) -> Self {
+        self.motor = Some(motor);
+        self
+    }
+}
+
+impl GenericJoint {
+    /// Describes how the specified degree of freedom is constrained and motorized.
+    #[must_use]
+    pub fn axis(&self, axis: JointAxis) -> JointAxisDescription {
+        let mask = JointAxesMask::from(axis);
+        let motion = if self.raw.locked_axes.contains(mask) {
+            JointAxisMotion::Locked
+        } else if let Some(limits) = self.raw.limits(axis) {
+            JointAxisMotion::Limited {
+                min: limits.min,
+                max: limits.max,
+            }
+        } else {
+            JointAxisMotion::Free
+        };
+        let motor = self.raw.motor(axis).map(|motor| JointAxisMotor {
+            target_pos: motor.target_pos,
+            target_vel: motor.target_vel,
+            stiffness: motor.stiffness,
+            damping: motor.damping,
+            max_force: motor.max_force,
+        });
+
+        JointAxisDescription { motion, motor }
+    }
+
+    /// Sets how the specified degree of freedom is constrained and motorized, replacing
+    /// any previous lock, limits, or motor along this axis.
+    pub fn set_axis(&mut self, axis: JointAxis, description: JointAxisDescription) -> &mut Self {
+        let mask = JointAxesMask::from(axis);
+        self.raw.locked_axes.remove(mask);
+        self.raw.limit_axes.remove(mask);
+        self.raw.motor_axes.remove(mask);
+
+        match description.motion {
+            JointAxisMotion::Free => {}
+            JointAxisMotion::Locked => {
+                self.raw.lock_axes(mask);
+            }
+            JointAxisMotion::Limited { min, max } => {
+                self.raw.set_limits(axis, [min, max]);
+            }
+        }
+
+        if let Some(motor) = description.motor {
+            self.raw.set_motor(
+                axis,
+                motor.target_pos,
+                motor.target_vel,
+                motor.stiffness,
+                motor.damping,
+            );
+            self.raw.set_motor_max_force(axis, motor.max_force);
+        }
+
+        self
+    }
+}
+
 /// Create generic joints using the builder pattern.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Reflect, FromReflect)]
 pub struct GenericJointBuilder(GenericJoint);
 
 impl GenericJointBuilder {
@@ -312,6 +636,43 @@ impl GenericJointBuilder {
         Self(GenericJoint::new(locked_axes))
     }
 
+    /// Creates a new generic joint builder, with its local frames computed so that the joint
+    /// is located at the given world-space anchor and basis for the current transforms of
+    /// both rigid-bodies.
+    ///
+    /// The scale of the transforms is ignored, like it is for rigid-bodies.
+    #[must_use]
+    pub fn connect_in_place(
+        locked_axes: JointAxesMask,
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+        world_basis: Rot,
+    ) -> Self {
+        #[cfg(feature = "dim2")]
+        let (world_anchor, world_basis) =
+            (world_anchor.extend(0.0), Quat::from_rotation_z(world_basis));
+
+        // Expresses the world-space joint frame in the local-space of a rigid-body.
+        let local_frame = |transform: &GlobalTransform| -> (Rot, Vect) {
+            let (_, rotation, translation) = transform.to_scale_rotation_translation();
+            let basis = rotation.inverse() * world_basis;
+            let anchor = rotation.inverse() * (world_anchor - translation);
+            #[cfg(feature = "dim2")]
+            return (basis.to_euler(EulerRot::ZYX).0, anchor.truncate());
+            #[cfg(feature = "dim3")]
+            return (basis, anchor);
+        };
+        let (basis1, anchor1) = local_frame(transform1);
+        let (basis2, anchor2) = local_frame(transform2);
+
+        Self::new(locked_axes)
+            .local_basis1(basis1)
+            .local_basis2(basis2)
+            .local_anchor1(anchor1)
+            .local_anchor2(anchor2)
+    }
+
     /// Sets the degrees of freedom locked by the joint.
     #[must_use]
     pub fn locked_axes(mut self, axes: JointAxesMask) -> Self {
@@ -319,6 +680,26 @@ impl GenericJointBuilder {
         self
     }
 
+    /// Locks the specified degree of freedom, removing its limits and motor.
+    #[must_use]
+    pub fn lock(self, axis: JointAxis) -> Self {
+        self.axis(axis, JointAxisDescription::LOCKED)
+    }
+
+    /// Frees the specified degree of freedom, removing its lock, limits, and motor.
+    #[must_use]
+    pub fn free(self, axis: JointAxis) -> Self {
+        self.axis(axis, JointAxisDescription::FREE)
+    }
+
+    /// Sets how the specified degree of freedom is constrained and motorized, replacing
+    /// any previous lock, limits, or motor along this axis.
+    #[must_use]
+    pub fn axis(mut self, axis: JointAxis, description: JointAxisDescription) -> Self {
+        self.0.set_axis(axis, description);
+        self
+    }
+
     /// Sets the joint’s frame, expressed in the first rigid-body’s local-space.
     #[must_use]
     pub fn local_basis1(mut self, local_basis: Rot) -> Self {
@@ -405,7 +786,7 @@ impl GenericJointBuilder {
 
     /// Configure both the target angle and target velocity of the motor.
     #[must_use]
-    pub fn set_motor(
+    pub fn motor(
         mut self,
         axis: JointAxis,
         target_pos: Real,
@@ -418,6 +799,20 @@ impl GenericJointBuilder {
         self
     }
 
+    /// Configure both the target angle and target velocity of the motor.
+    #[must_use]
+    #[deprecated(note = "Use `.motor` instead.")]
+    pub fn set_motor(
+        self,
+        axis: JointAxis,
+        target_pos: Real,
+        target_vel: Real,
+        stiffness: Real,
+        damping: Real,
+    ) -> Self {
+        self.motor(axis, target_pos, target_vel, stiffness, damping)
+    }
+
     /// Sets the maximum force the motor can deliver along the specified axis.
     #[must_use]
     pub fn motor_max_force(mut self, axis: JointAxis, max_force: Real) -> Self {
@@ -425,6 +820,13 @@ impl GenericJointBuilder {
         self
     }
 
+    /// Makes the locked axes of this joint soft.
+    #[must_use]
+    pub fn softness(mut self, softness: JointSoftness) -> Self {
+        self.0.set_softness(Some(softness));
+        self
+    }
+
     /// Builds the generic joint.
     #[must_use]
     pub fn build(self) -> GenericJoint {
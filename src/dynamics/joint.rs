@@ -1,6 +1,14 @@
 use crate::dynamics::GenericJoint;
-use bevy::prelude::*;
-use rapier::dynamics::{ImpulseJointHandle, MultibodyJointHandle};
+use crate::math::{Real, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
+use rapier::dynamics::{ImpulseJointHandle, JointAxis, MultibodyJointHandle};
+
+#[cfg(feature = "serde-serialize")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
 
 /// The handle of an impulse joint added to the physics scene.
 #[derive(Copy, Clone, Debug, Component)]
@@ -10,10 +18,35 @@ pub struct RapierImpulseJointHandle(pub ImpulseJointHandle);
 #[derive(Copy, Clone, Debug, Component)]
 pub struct RapierMultibodyJointHandle(pub MultibodyJointHandle);
 
+/// The first endpoint of an [`ImpulseJoint`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Reflect, FromReflect)]
+pub enum JointParent {
+    /// The world, i.e., a fixed rigid-body managed by the plugin.
+    World,
+    /// The rigid-body attached to this entity.
+    Entity(Entity),
+}
+
+impl JointParent {
+    /// The entity of the rigid-body, or `None` for the world.
+    pub fn entity(&self) -> Option<Entity> {
+        match self {
+            Self::World => None,
+            Self::Entity(entity) => Some(*entity),
+        }
+    }
+}
+
+impl From<Entity> for JointParent {
+    fn from(entity: Entity) -> Self {
+        Self::Entity(entity)
+    }
+}
+
 /// An impulse-based joint attached to two entities.
 ///
 /// The first end-point of the joint is the rigid-body attached to
-/// `ImpulseJoint::parent`. The second endpoint of the joint is the
+/// `ImpulseJoint::parent`, or the world. The second endpoint of the joint is the
 /// rigid-body attached to the entity (or the parent of the entity)
 /// containing this `ImpulseJoint` component.
 ///
@@ -21,22 +54,69 @@ pub struct RapierMultibodyJointHandle(pub MultibodyJointHandle);
 /// joints can be added in the children of the entity containing that
 /// rigid-body (this is similar to the technique used to attach multiple
 /// colliders to the same rigid-body).
-#[derive(Copy, Clone, Debug, PartialEq, Component)]
+///
+/// A joint created with [`ImpulseJoint::anchored_to_world`] has no first rigid-body,
+/// and pins the rigid-body to a fixed point of the world instead.
+///
+/// Changes made to `ImpulseJoint::data` (e.g. to its motors, limits, or anchors,
+/// through `GenericJoint::as_revolute_mut`) are applied to the physics joint automatically,
+/// without recreating it, and waking up the attached rigid-bodies. The joint can be
+/// toggled on and off with the [`JointEnabled`] component.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
 pub struct ImpulseJoint {
-    /// The entity containing the rigid-body used as the first endpoint of this joint.
-    pub parent: Entity,
+    /// The entity containing the rigid-body used as the first endpoint of this joint, or the
+    /// world.
+    pub parent: JointParent,
     /// The joint’s description.
     pub data: GenericJoint,
 }
 
 impl ImpulseJoint {
     /// Initializes an impulse-based joint from its first endpoint and the joint description.
-    pub fn new(parent: Entity, data: impl Into<GenericJoint>) -> Self {
+    pub fn new(parent: impl Into<JointParent>, data: impl Into<GenericJoint>) -> Self {
         Self {
-            parent,
+            parent: parent.into(),
             data: data.into(),
         }
     }
+
+    /// Initializes an impulse-based joint attached to the world, i.e., to a fixed rigid-body
+    /// managed by the plugin, instead of to the rigid-body of another entity.
+    ///
+    /// The first anchor and basis of the joint description are expressed in world-space.
+    pub fn anchored_to_world(data: impl Into<GenericJoint>) -> Self {
+        Self::new(JointParent::World, data)
+    }
+
+    /// Is this joint attached to the world rather than to the rigid-body of another entity?
+    pub fn is_anchored_to_world(&self) -> bool {
+        self.parent == JointParent::World
+    }
+}
+
+// NOTE: `FromWorld` is needed to register `ImpulseJoint` as a reflected component. The placeholder
+//       parent is replaced when the component is deserialized.
+impl FromWorld for ImpulseJoint {
+    fn from_world(_world: &mut World) -> Self {
+        Self {
+            parent: JointParent::Entity(Entity::from_raw(u32::MAX)),
+            data: GenericJoint::default(),
+        }
+    }
+}
+
+impl MapEntities for ImpulseJoint {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        // The parent may be outside of the mapped entities (e.g. a static body of the world
+        // a scene is spawned in), in which case it is kept as is.
+        if let JointParent::Entity(parent) = &mut self.parent {
+            if let Ok(mapped) = entity_map.get(*parent) {
+                *parent = mapped;
+            }
+        }
+        Ok(())
+    }
 }
 
 /// An joint based on generalized coordinates, attached to two entities.
@@ -48,12 +128,27 @@ impl ImpulseJoint {
 /// Note that a set of multibody joints cannot form closed loops (for example a necklace).
 /// If a closed loop is detected, the last joint that closes the loop is ignored, and an
 /// error is printed to `stderr` (using `log::error!`).
-#[derive(Copy, Clone, Debug, PartialEq, Component)]
+///
+/// Changes made to `MultibodyJoint::data` (e.g. to its motors, through
+/// `GenericJoint::as_revolute_mut`), `damping`, or `friction` are applied to the physics
+/// joint automatically, waking up the attached rigid-bodies.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
 pub struct MultibodyJoint {
     /// The entity containing the rigid-body used as the first endpoint of this joint.
     pub parent: Entity,
     /// The joint’s description.
     pub data: GenericJoint,
+    /// The viscous damping applied to the velocity of each free axis of the joint.
+    ///
+    /// If `None`, Rapier’s default damping is used: `0.1` on the free angular axes, and
+    /// no damping on the free linear axes.
+    pub damping: Option<Real>,
+    /// The dry friction of each free axis of the joint, i.e., the maximum force (or torque for
+    /// angular axes) resisting its motion.
+    ///
+    /// The friction isn’t applied to the axes with a motor.
+    pub friction: Real,
 }
 
 impl MultibodyJoint {
@@ -63,6 +158,397 @@ impl MultibodyJoint {
         Self {
             parent,
             data: data.into(),
+            damping: None,
+            friction: 0.0,
+        }
+    }
+
+    /// Sets the viscous damping applied to the velocity of each free axis of the joint.
+    #[must_use]
+    pub fn with_damping(mut self, damping: Real) -> Self {
+        self.damping = Some(damping);
+        self
+    }
+
+    /// Sets the dry friction of each free axis of the joint.
+    #[must_use]
+    pub fn with_friction(mut self, friction: Real) -> Self {
+        self.friction = friction;
+        self
+    }
+}
+
+impl FromWorld for MultibodyJoint {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(Entity::from_raw(u32::MAX), GenericJoint::default())
+    }
+}
+
+impl MapEntities for MultibodyJoint {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(parent) = entity_map.get(self.parent) {
+            self.parent = parent;
+        }
+        Ok(())
+    }
+}
+
+/// A reference to an entity by its `Name`, used by a [`PendingJoint`].
+#[derive(Clone, Debug, PartialEq, Eq, Reflect, FromReflect)]
+pub enum EntityPathOrName {
+    /// The entity with the given name.
+    Name(String),
+    /// The entity at the end of the given path of names, each entity of the path being a
+    /// child of the previous one (e.g. `["LeftArm", "Hand"]`).
+    ///
+    /// The first entity of the path doesn’t need to be at the root of the scene.
+    Path(Vec<String>),
+}
+
+impl Default for EntityPathOrName {
+    fn default() -> Self {
+        EntityPathOrName::Name(String::new())
+    }
+}
+
+impl From<&str> for EntityPathOrName {
+    /// Parses a name, or a path of names separated by `/` (e.g. `"LeftArm/Hand"`).
+    fn from(path: &str) -> Self {
+        if path.contains('/') {
+            EntityPathOrName::Path(path.split('/').map(str::to_string).collect())
+        } else {
+            EntityPathOrName::Name(path.to_string())
+        }
+    }
+}
+
+/// A joint whose first endpoint is referred to by name, for prefabs whose entities don’t
+/// exist until they are spawned (e.g. scenes loaded from a file, or GLTF models).
+///
+/// The `other` entity is searched among the entities of the same hierarchy as the entity
+/// containing this component, i.e., all the descendants of its topmost ancestor, which is the
+/// entity of the `SceneBundle` for spawned scenes. Once it is found, this component is replaced
+/// by an [`ImpulseJoint`] (or a [`MultibodyJoint`] if `multibody` is `true`) whose `parent`
+/// is the `other` entity. Until then, this component is kept as is. If several entities match,
+/// the first one found is used.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct PendingJoint {
+    /// The entity containing the rigid-body used as the first endpoint of the joint.
+    pub other: EntityPathOrName,
+    /// The joint’s description.
+    pub data: GenericJoint,
+    /// Whether a [`MultibodyJoint`] is created instead of an [`ImpulseJoint`].
+    pub multibody: bool,
+}
+
+impl PendingJoint {
+    /// Initializes an impulse joint from the name of its first endpoint and the joint description.
+    pub fn new(other: impl Into<EntityPathOrName>, data: impl Into<GenericJoint>) -> Self {
+        Self {
+            other: other.into(),
+            data: data.into(),
+            multibody: false,
+        }
+    }
+
+    /// Creates a [`MultibodyJoint`] instead of an [`ImpulseJoint`] once resolved.
+    #[must_use]
+    pub fn multibody(mut self) -> Self {
+        self.multibody = true;
+        self
+    }
+}
+
+/// The softness of the locked axes of a joint.
+///
+/// Instead of being locked rigidly, each locked axis of a soft joint is pulled back toward its
+/// locked position like a damped spring, which is useful for deliberately squishy attachments
+/// (e.g. a trailer hitch or a soft-mounted camera). The spring is independent from the mass
+/// of the attached rigid-bodies.
+///
+/// The softness can be set on the joint description with `GenericJoint::set_softness` or the
+/// joint builders. When this component is added to an entity with an `ImpulseJoint`, it overrides
+/// the softness of the joint, and changes to it are applied to the joint automatically. Removing
+/// this component makes the joint rigid again.
+///
+/// This component has no effect on `MultibodyJoint`s, whose softness must be set on their
+/// description before they are created.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct JointSoftness {
+    /// The natural frequency of the spring, in Hertz. Lower values make the joint softer.
+    pub natural_frequency: Real,
+    /// The damping ratio of the spring.
+    ///
+    /// A ratio of `1.0` is critically damped, lower values make the joint oscillate.
+    pub damping_ratio: Real,
+}
+
+impl JointSoftness {
+    /// Creates a softness from the natural frequency (in Hertz) and the damping ratio of the spring.
+    pub fn new(natural_frequency: Real, damping_ratio: Real) -> Self {
+        Self {
+            natural_frequency,
+            damping_ratio,
+        }
+    }
+
+    /// The stiffness and damping of the acceleration-based spring with this softness.
+    pub fn stiffness_and_damping(&self) -> (Real, Real) {
+        let angular_frequency = 2.0 * std::f32::consts::PI * self.natural_frequency;
+        (
+            angular_frequency * angular_frequency,
+            2.0 * self.damping_ratio * angular_frequency,
+        )
+    }
+}
+
+impl Default for JointSoftness {
+    fn default() -> Self {
+        Self::new(5.0, 1.0)
+    }
+}
+
+/// Drives an axis of the `ImpulseJoint` of this entity toward a target position, following
+/// a trapezoidal velocity profile.
+///
+/// The motor accelerates by `max_acceleration` until it reaches `max_velocity`, then
+/// decelerates so that it stops at `target_pos` without overshooting it. Changing `target_pos`
+/// makes the motor ramp smoothly toward the new target. This is well suited to animate physical
+/// elevators or platforms.
+///
+/// This overrides the motor configured on `axis` by the joint description, and removing this
+/// component restores it. This has no effect on `MultibodyJoint`s.
+//
+// NOTE: Rapier’s `JointAxis` isn’t reflectable, so this component is reflected as an opaque value.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    reflect_value(Component, PartialEq, Serialize, Deserialize)
+)]
+#[cfg_attr(not(feature = "serde-serialize"), reflect_value(Component, PartialEq))]
+pub struct RampedJointMotor {
+    /// The axis of the joint driven by the motor.
+    pub axis: JointAxis,
+    /// The position the motor is moving toward.
+    pub target_pos: Real,
+    /// The maximum velocity of the motor.
+    pub max_velocity: Real,
+    /// The maximum acceleration and deceleration of the motor.
+    pub max_acceleration: Real,
+    /// How strongly the velocity of the joint follows the velocity profile.
+    pub damping: Real,
+    /// The maximum force the motor can deliver.
+    pub max_force: Real,
+}
+
+impl RampedJointMotor {
+    /// Creates a motor driving `axis` toward `target_pos` with the given velocity and acceleration limits.
+    pub fn new(
+        axis: JointAxis,
+        target_pos: Real,
+        max_velocity: Real,
+        max_acceleration: Real,
+    ) -> Self {
+        Self {
+            axis,
+            target_pos,
+            max_velocity,
+            max_acceleration,
+            damping: 100.0,
+            max_force: Real::MAX,
+        }
+    }
+}
+
+impl FromWorld for RampedJointMotor {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(JointAxis::X, 0.0, 0.0, 0.0)
+    }
+}
+
+/// Runtime-tunable parameters of a [`SpringJoint`](crate::dynamics::SpringJoint).
+///
+/// When added to an entity with an `ImpulseJoint` describing a spring joint, changes to
+/// this component are applied to the joint automatically. It is ignored for other joints.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct JointSpring {
+    /// The distance between the anchors the spring is trying to reach.
+    pub rest_length: Real,
+    /// The stiffness of the spring.
+    pub stiffness: Real,
+    /// The damping of the spring.
+    pub damping: Real,
+}
+
+impl Default for JointSpring {
+    fn default() -> Self {
+        Self {
+            rest_length: 0.0,
+            stiffness: 1.0,
+            damping: 0.0,
+        }
+    }
+}
+
+/// What happens to the entity of a [`BreakableJoint`] once its joint breaks.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Reflect, FromReflect)]
+pub enum JointBreakAction {
+    /// The `ImpulseJoint` and `BreakableJoint` components are removed from the entity.
+    #[default]
+    RemoveJoint,
+    /// The entity is despawned, along with its children.
+    Despawn,
+}
+
+/// Breaks the `ImpulseJoint` of this entity once the force or torque it applies to keep
+/// the rigid-bodies together exceeds a threshold.
+///
+/// A `JointBroken` event is sent when the joint breaks, whichever the `action` is.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct BreakableJoint {
+    /// The maximum force the joint can apply before it breaks.
+    pub break_force: Real,
+    /// The maximum torque the joint can apply before it breaks.
+    pub break_torque: Real,
+    /// What happens to the entity once the joint breaks.
+    pub action: JointBreakAction,
+}
+
+impl BreakableJoint {
+    /// A joint that breaks when the force or torque it applies exceeds the given thresholds.
+    pub fn new(break_force: Real, break_torque: Real) -> Self {
+        Self {
+            break_force,
+            break_torque,
+            action: JointBreakAction::RemoveJoint,
+        }
+    }
+}
+
+impl Default for BreakableJoint {
+    fn default() -> Self {
+        Self::new(Real::INFINITY, Real::INFINITY)
+    }
+}
+
+/// Enables or disables the `ImpulseJoint` of this entity, without removing it.
+///
+/// A disabled joint doesn’t constrain the relative motion of its rigid-bodies, but keeps
+/// its handle and description so that it can be re-enabled cheaply (e.g. to activate a
+/// ragdoll, or to unlock a door). Removing this component re-enables the joint.
+///
+/// This has no effect on `MultibodyJoint`s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct JointEnabled(pub bool);
+
+impl Default for JointEnabled {
+    fn default() -> Self {
+        Self(true)
+    }
+}
+
+/// The minimum velocity at which the `ImpulseJoint` of this entity must hit one of its limits
+/// to emit a [`JointLimitReached`](crate::pipeline::JointLimitReached) event.
+///
+/// Joints without this component never emit these events. The threshold is a linear velocity
+/// for linear axes, and an angular velocity for angular axes.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct JointLimitEventThreshold(pub Real);
+
+/// The forces applied by the `ImpulseJoint` of this entity during the last simulation step.
+///
+/// This component is filled automatically after each step; modifying it has no effect.
+/// This has no effect on `MultibodyJoint`s.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct ReadJointForces {
+    /// The world-space force applied by the joint on the second rigid-body.
+    pub force: Vect,
+    /// The world-space torque applied by the joint on the second rigid-body.
+    #[cfg(feature = "dim2")]
+    pub torque: Real,
+    /// The world-space torque applied by the joint on the second rigid-body.
+    #[cfg(feature = "dim3")]
+    pub torque: Vect,
+    /// The force applied along each linear axis of the joint’s frame, including its limits and motors.
+    pub local_force: Vect,
+    /// The torque applied along each angular axis of the joint’s frame, including its limits and motors.
+    #[cfg(feature = "dim2")]
+    pub local_torque: Real,
+    /// The torque applied along each angular axis of the joint’s frame, including its limits and motors.
+    #[cfg(feature = "dim3")]
+    pub local_torque: Vect,
+}
+
+/// Couples the motions of two `ImpulseJoint`s, e.g. to model gears or a rack and pinion.
+///
+/// The relative velocity of `joint2` along its free axis is constrained to be `ratio` times
+/// the relative velocity of `joint1` along its free axis. The free axis of a joint is the `X`
+/// axis of its local frames: it is the translation axis of prismatic joints, and the rotation
+/// axis of revolute joints.
+///
+/// This component can be attached to any entity, and `joint1` and `joint2` are the entities
+/// with the coupled `ImpulseJoint`s. The coupling is enforced on the velocities before each
+/// simulation step, so a small positional drift can accumulate over time.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct JointCoupling {
+    /// The entity with the driving joint.
+    pub joint1: Entity,
+    /// The entity with the driven joint.
+    pub joint2: Entity,
+    /// The ratio between the velocity of `joint2` and the velocity of `joint1`.
+    ///
+    /// A negative ratio makes the joints move in opposite directions, like two meshing gears.
+    pub ratio: Real,
+}
+
+impl JointCoupling {
+    /// Couples two joints so that the velocity of `joint2` is `ratio` times the velocity of `joint1`.
+    pub fn new(joint1: Entity, joint2: Entity, ratio: Real) -> Self {
+        Self {
+            joint1,
+            joint2,
+            ratio,
+        }
+    }
+
+    /// Couples the revolute joint of two gears, with the given number of teeth.
+    ///
+    /// The gears are meshing, so they rotate in opposite directions.
+    pub fn gear(joint1: Entity, teeth1: u32, joint2: Entity, teeth2: u32) -> Self {
+        Self::new(joint1, joint2, -(teeth1 as Real) / (teeth2 as Real))
+    }
+
+    /// Couples the revolute joint of a pinion with the prismatic joint of a rack, so that the rack
+    /// moves by `pinion_radius` for each radian the pinion turns.
+    pub fn rack_and_pinion(pinion: Entity, rack: Entity, pinion_radius: Real) -> Self {
+        Self::new(pinion, rack, pinion_radius)
+    }
+}
+
+impl FromWorld for JointCoupling {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(Entity::from_raw(u32::MAX), Entity::from_raw(u32::MAX), 1.0)
+    }
+}
+
+impl MapEntities for JointCoupling {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(joint1) = entity_map.get(self.joint1) {
+            self.joint1 = joint1;
+        }
+        if let Ok(joint2) = entity_map.get(self.joint2) {
+            self.joint2 = joint2;
         }
+        Ok(())
     }
 }
@@ -0,0 +1,232 @@
+use crate::dynamics::{
+    Ccd, Damping, GravityScale, ImpulseJoint, JointParent, MultibodyJoint, RigidBody,
+};
+use crate::geometry::{Collider, CollisionGroups, Friction, Restitution, Sensor, SolverGroups};
+use crate::math::Vect;
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// A plane across which rigs of rigid-bodies and joints are mirrored.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MirrorPlane {
+    /// A point on the plane.
+    pub point: Vect,
+    /// The unit normal of the plane.
+    pub normal: Vect,
+}
+
+impl MirrorPlane {
+    /// Creates a plane going through `point`, with the given normal.
+    pub fn new(point: Vect, normal: Vect) -> Self {
+        Self {
+            point,
+            normal: normal.normalize(),
+        }
+    }
+
+    /// Reflects a point across this plane.
+    pub fn mirror_point(&self, point: Vect) -> Vect {
+        point - self.normal * (2.0 * (point - self.point).dot(self.normal))
+    }
+
+    /// Reflects a vector (e.g. a direction) across this plane.
+    pub fn mirror_vector(&self, vector: Vect) -> Vect {
+        vector - self.normal * (2.0 * vector.dot(self.normal))
+    }
+
+    /// Mirrors a world-space transform across this plane.
+    ///
+    /// A reflection can’t be represented by a rotation, so the local-space of the mirrored
+    /// transform is reflected too, across the plane with the same normal going through its
+    /// origin: the local point `p` of the original transform ends up at the local point
+    /// `mirror_vector(p)` of the mirrored transform. Shapes symmetric with respect to this
+    /// local plane (e.g. balls, or cuboids and capsules when the normal is a coordinate axis)
+    /// can therefore be reused as is.
+    pub fn mirror_transform(&self, transform: &Transform) -> Transform {
+        #[cfg(feature = "dim2")]
+        let (point, normal) = (self.point.extend(0.0), self.normal.extend(0.0));
+        #[cfg(feature = "dim3")]
+        let (point, normal) = (self.point, self.normal);
+
+        // The conjugate of a rotation by a reflection rotates by the opposite angle around
+        // the reflected axis.
+        let rotation = transform.rotation;
+        let axis = Vec3::new(rotation.x, rotation.y, rotation.z);
+        let axis = axis - normal * (2.0 * axis.dot(normal));
+
+        Transform {
+            translation: transform.translation
+                - normal * (2.0 * (transform.translation - point).dot(normal)),
+            rotation: Quat::from_xyzw(-axis.x, -axis.y, -axis.z, rotation.w),
+            scale: transform.scale,
+        }
+    }
+}
+
+/// Extension trait for spawning mirrored copies of rigs of rigid-bodies and joints.
+pub trait MirrorRigCommandsExt {
+    /// Spawns a copy of the given entities mirrored across `plane`, and returns the entities
+    /// of the copies, in the same order.
+    ///
+    /// If `rename` is set, its first string is replaced by the second one in the names of the
+    /// copies (e.g. `Some(("Left", "Right"))`). See [`SpawnMirroredRig`] for the components
+    /// that are copied.
+    fn spawn_mirrored_rig(
+        &mut self,
+        entities: &[Entity],
+        plane: MirrorPlane,
+        rename: Option<(&str, &str)>,
+    ) -> Vec<Entity>;
+}
+
+impl<'w, 's> MirrorRigCommandsExt for Commands<'w, 's> {
+    fn spawn_mirrored_rig(
+        &mut self,
+        entities: &[Entity],
+        plane: MirrorPlane,
+        rename: Option<(&str, &str)>,
+    ) -> Vec<Entity> {
+        let mirrored: Vec<_> = entities.iter().map(|_| self.spawn().id()).collect();
+        self.add(SpawnMirroredRig {
+            entities: entities.to_vec(),
+            mirrored: mirrored.clone(),
+            plane,
+            rename: rename.map(|(from, to)| (from.to_string(), to.to_string())),
+        });
+        mirrored
+    }
+}
+
+/// A command filling entities with the mirrored copy of a rig, see [`MirrorRigCommandsExt`].
+///
+/// Each entity of the rig is given a mirrored `Transform` (see [`MirrorPlane::mirror_transform`])
+/// and a copy of its `Name`, [`RigidBody`], [`Collider`], [`Sensor`], [`Friction`],
+/// [`Restitution`], [`CollisionGroups`], [`SolverGroups`], [`Damping`], [`GravityScale`],
+/// and [`Ccd`] components. Its [`ImpulseJoint`] and [`MultibodyJoint`] are mirrored with
+/// [`GenericJoint::mirrored`](crate::dynamics::GenericJoint::mirrored).
+///
+/// The joints and the `Parent` of the copies refer to the copies of the entities of the rig,
+/// and to the same entities otherwise (e.g. to attach both arms of a ragdoll to the same torso).
+/// The `GlobalTransform` of the entities of the rig and of their parents must be up to date.
+pub struct SpawnMirroredRig {
+    /// The entities of the rig.
+    pub entities: Vec<Entity>,
+    /// The entities receiving the mirrored copies, in the same order as `entities`.
+    pub mirrored: Vec<Entity>,
+    /// The plane across which the rig is mirrored.
+    pub plane: MirrorPlane,
+    /// If set, the first string is replaced by the second one in the names of the copies
+    /// (e.g. `("Left", "Right")`).
+    pub rename: Option<(String, String)>,
+}
+
+impl Command for SpawnMirroredRig {
+    fn write(self, world: &mut World) {
+        let mapping: HashMap<Entity, Entity> = self
+            .entities
+            .iter()
+            .copied()
+            .zip(self.mirrored.iter().copied())
+            .collect();
+        let global_transform = |world: &World, entity| {
+            world
+                .get::<GlobalTransform>(entity)
+                .copied()
+                .unwrap_or_default()
+        };
+        let mirrored_globals: HashMap<Entity, GlobalTransform> = mapping
+            .iter()
+            .map(|(entity, mirrored)| {
+                let transform = global_transform(world, *entity).compute_transform();
+                (*mirrored, self.plane.mirror_transform(&transform).into())
+            })
+            .collect();
+
+        for (entity, mirrored) in self.entities.iter().zip(self.mirrored.iter()) {
+            let (entity, mirrored) = (*entity, *mirrored);
+            let global = mirrored_globals[&mirrored];
+            let parent = world.get::<Parent>(entity).map(|parent| {
+                let parent = parent.get();
+                mapping.get(&parent).copied().unwrap_or(parent)
+            });
+            let parent_global = parent
+                .map(|parent| {
+                    mirrored_globals
+                        .get(&parent)
+                        .copied()
+                        .unwrap_or_else(|| global_transform(world, parent))
+                })
+                .unwrap_or_default();
+            let transform = Transform::from_matrix(
+                parent_global.compute_matrix().inverse() * global.compute_matrix(),
+            );
+
+            let name = world.get::<Name>(entity).map(|name| match &self.rename {
+                Some((from, to)) => Name::new(name.as_str().replace(from.as_str(), to.as_str())),
+                None => name.clone(),
+            });
+            let impulse_joint = world.get::<ImpulseJoint>(entity).map(|joint| {
+                let mut data = joint.data.mirrored(self.plane.normal);
+                if joint.is_anchored_to_world() {
+                    data.set_local_anchor1(self.plane.mirror_point(joint.data.local_anchor1()));
+                }
+                ImpulseJoint {
+                    parent: match joint.parent {
+                        JointParent::Entity(parent) => {
+                            JointParent::Entity(mapping.get(&parent).copied().unwrap_or(parent))
+                        }
+                        JointParent::World => JointParent::World,
+                    },
+                    data,
+                }
+            });
+            let multibody_joint = world.get::<MultibodyJoint>(entity).map(|joint| {
+                let mut joint = *joint;
+                joint.parent = mapping.get(&joint.parent).copied().unwrap_or(joint.parent);
+                joint.data = joint.data.mirrored(self.plane.normal);
+                joint
+            });
+
+            world.entity_mut(mirrored).insert_bundle(TransformBundle {
+                local: transform,
+                global,
+            });
+            copy_components(world, entity, mirrored);
+
+            let mut mirrored_entity = world.entity_mut(mirrored);
+            if let Some(name) = name {
+                mirrored_entity.insert(name);
+            }
+            if let Some(joint) = impulse_joint {
+                mirrored_entity.insert(joint);
+            }
+            if let Some(joint) = multibody_joint {
+                mirrored_entity.insert(joint);
+            }
+            if let Some(parent) = parent {
+                world.entity_mut(parent).push_children(&[mirrored]);
+            }
+        }
+    }
+}
+
+fn copy_component<T: Component + Clone>(world: &mut World, from: Entity, to: Entity) {
+    if let Some(component) = world.get::<T>(from).cloned() {
+        world.entity_mut(to).insert(component);
+    }
+}
+
+// The components that are invariant by reflection.
+fn copy_components(world: &mut World, from: Entity, to: Entity) {
+    copy_component::<RigidBody>(world, from, to);
+    copy_component::<Collider>(world, from, to);
+    copy_component::<Sensor>(world, from, to);
+    copy_component::<Friction>(world, from, to);
+    copy_component::<Restitution>(world, from, to);
+    copy_component::<CollisionGroups>(world, from, to);
+    copy_component::<SolverGroups>(world, from, to);
+    copy_component::<Damping>(world, from, to);
+    copy_component::<GravityScale>(world, from, to);
+    copy_component::<Ccd>(world, from, to);
+}
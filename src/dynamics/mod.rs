@@ -1,25 +1,47 @@
+pub use self::commands::*;
 pub use self::generic_joint::*;
 pub use self::joint::*;
+pub use self::mirror::*;
+pub use self::multibody_chain::*;
+pub use self::replication::*;
 pub use self::rigid_body::*;
 
 pub use self::fixed_joint::*;
 pub use self::prismatic_joint::*;
+pub use self::pulley_joint::*;
 pub use self::revolute_joint::*;
+pub use self::rope_joint::*;
+pub use self::spring_joint::*;
 
 use bevy::reflect::{FromReflect, Reflect};
 use rapier::dynamics::CoefficientCombineRule as RapierCoefficientCombineRule;
 
+#[cfg(feature = "dim3")]
+pub use self::ragdoll::*;
+#[cfg(feature = "dim3")]
+pub use self::rope::*;
 #[cfg(feature = "dim3")]
 pub use self::spherical_joint::*;
 
+mod commands;
 mod generic_joint;
 mod joint;
+mod mirror;
+mod multibody_chain;
+mod replication;
 mod rigid_body;
 
 mod fixed_joint;
 mod prismatic_joint;
+mod pulley_joint;
 mod revolute_joint;
+mod rope_joint;
+mod spring_joint;
 
+#[cfg(feature = "dim3")]
+mod ragdoll;
+#[cfg(feature = "dim3")]
+mod rope;
 #[cfg(feature = "dim3")]
 mod spherical_joint;
 
@@ -49,6 +71,18 @@ impl Default for CoefficientCombineRule {
     }
 }
 
+impl CoefficientCombineRule {
+    /// Combines two coefficients using the rule `max(rule1 as usize, rule2 as usize)`.
+    pub fn combine(coeff1: f32, rule1: Self, coeff2: f32, rule2: Self) -> f32 {
+        match (rule1 as usize).max(rule2 as usize) {
+            0 => (coeff1 + coeff2) / 2.0,
+            1 => coeff1.min(coeff2),
+            2 => coeff1 * coeff2,
+            _ => coeff1.max(coeff2),
+        }
+    }
+}
+
 impl From<CoefficientCombineRule> for RapierCoefficientCombineRule {
     fn from(combine_rule: CoefficientCombineRule) -> RapierCoefficientCombineRule {
         match combine_rule {
@@ -59,3 +93,14 @@ impl From<CoefficientCombineRule> for RapierCoefficientCombineRule {
         }
     }
 }
+
+impl From<RapierCoefficientCombineRule> for CoefficientCombineRule {
+    fn from(combine_rule: RapierCoefficientCombineRule) -> CoefficientCombineRule {
+        match combine_rule {
+            RapierCoefficientCombineRule::Average => CoefficientCombineRule::Average,
+            RapierCoefficientCombineRule::Min => CoefficientCombineRule::Min,
+            RapierCoefficientCombineRule::Multiply => CoefficientCombineRule::Multiply,
+            RapierCoefficientCombineRule::Max => CoefficientCombineRule::Max,
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::dynamics::{GenericJoint, MultibodyJoint, RigidBody};
+use crate::geometry::Collider;
+use bevy::ecs::system::EntityCommands;
+use bevy::prelude::*;
+
+type LinkCallback = Box<dyn FnMut(usize, &mut EntityCommands)>;
+
+/// Spawns a chain of rigid-bodies linked by multibody joints (e.g. a rope, or a robot arm).
+///
+/// Each link is spawned with a dynamic [`RigidBody`], the chain’s [`Collider`], and a
+/// [`MultibodyJoint`] attaching it to the previous link. The first link is attached to the
+/// entity given to [`MultibodyChainBuilder::attached_to`] if any, otherwise it is the root of
+/// the multibody. Trees can be built by attaching other chains to the links of a chain.
+///
+/// The links are placed so that the joint anchors coincide, starting from the chain’s
+/// transform and following its orientation.
+pub struct MultibodyChainBuilder {
+    links: usize,
+    collider: Collider,
+    joint: GenericJoint,
+    transform: Transform,
+    parent: Option<Entity>,
+    with_link: Option<LinkCallback>,
+}
+
+impl MultibodyChainBuilder {
+    /// Creates a builder for a chain of `links` rigid-bodies with the given collider, each
+    /// attached to the previous one with the given joint.
+    pub fn new(links: usize, collider: Collider, joint: impl Into<GenericJoint>) -> Self {
+        Self {
+            links,
+            collider,
+            joint: joint.into(),
+            transform: Transform::identity(),
+            parent: None,
+            with_link: None,
+        }
+    }
+
+    /// Sets the transform of the first link of the chain.
+    #[must_use]
+    pub fn transform(mut self, transform: Transform) -> Self {
+        self.transform = transform;
+        self
+    }
+
+    /// Attaches the first link of the chain to the rigid-body of the given entity, using
+    /// the chain’s joint.
+    ///
+    /// Note that the transform of the chain isn’t derived from the transform of this entity.
+    #[must_use]
+    pub fn attached_to(mut self, parent: Entity) -> Self {
+        self.parent = Some(parent);
+        self
+    }
+
+    /// Sets a closure called with the index and the entity commands of each link, after its
+    /// components were inserted.
+    ///
+    /// This can be used to customize some links, e.g. to give a different shape to the last
+    /// one, or to make the root of the chain a [`RigidBody::Fixed`].
+    #[must_use]
+    pub fn with_link(
+        mut self,
+        with_link: impl FnMut(usize, &mut EntityCommands) + 'static,
+    ) -> Self {
+        self.with_link = Some(Box::new(with_link));
+        self
+    }
+
+    /// Spawns the links of the chain, and returns their entities, from the first to the last.
+    pub fn spawn(mut self, commands: &mut Commands) -> Vec<Entity> {
+        #[cfg(feature = "dim2")]
+        let offset = (self.joint.local_anchor1() - self.joint.local_anchor2()).extend(0.0);
+        #[cfg(feature = "dim3")]
+        let offset = self.joint.local_anchor1() - self.joint.local_anchor2();
+
+        let mut entities = Vec::with_capacity(self.links);
+        let mut transform = self.transform;
+        let mut parent = self.parent;
+
+        for i in 0..self.links {
+            if i > 0 {
+                transform.translation += transform.rotation * offset;
+            }
+
+            let mut link = commands.spawn();
+            link.insert_bundle(TransformBundle::from(transform))
+                .insert(RigidBody::Dynamic)
+                .insert(self.collider.clone());
+
+            if let Some(parent) = parent {
+                link.insert(MultibodyJoint::new(parent, self.joint));
+            }
+
+            if let Some(with_link) = &mut self.with_link {
+                with_link(i, &mut link);
+            }
+
+            let entity = link.id();
+            entities.push(entity);
+            parent = Some(entity);
+        }
+
+        entities
+    }
+}
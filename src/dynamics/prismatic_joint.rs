@@ -1,9 +1,14 @@
-use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::dynamics::{GenericJoint, GenericJointBuilder, JointSoftness};
 use crate::math::{Real, Vect};
+use bevy::prelude::GlobalTransform;
+#[cfg(feature = "dim3")]
+use bevy::prelude::Quat;
+use bevy::reflect::{FromReflect, Reflect};
 use rapier::dynamics::{JointAxesMask, JointAxis, JointLimits, JointMotor, MotorModel};
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 #[repr(transparent)]
 /// A prismatic joint, locks all relative motion between two bodies except for translation along the joint’s principal axis.
 pub struct PrismaticJoint {
@@ -38,6 +43,20 @@ impl PrismaticJoint {
         self
     }
 
+    /// The softness of the locked axes of this joint, if they aren’t locked rigidly.
+    #[must_use]
+    pub fn softness(&self) -> Option<JointSoftness> {
+        self.data.softness()
+    }
+
+    /// Sets the softness of the locked axes of this joint.
+    ///
+    /// If `None`, the locked axes are locked rigidly.
+    pub fn set_softness(&mut self, softness: Option<JointSoftness>) -> &mut Self {
+        self.data.set_softness(softness);
+        self
+    }
+
     /// The joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(&self) -> Vect {
@@ -159,7 +178,8 @@ impl From<PrismaticJoint> for GenericJoint {
 ///
 /// A prismatic joint locks all relative motion except for translations along the joint’s principal axis.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 pub struct PrismaticJointBuilder(PrismaticJoint);
 
 impl PrismaticJointBuilder {
@@ -170,6 +190,32 @@ impl PrismaticJointBuilder {
         Self(PrismaticJoint::new(axis))
     }
 
+    /// Creates a new builder for prismatic joints, attaching the rigid-bodies at the given
+    /// world-space anchor in their current relative pose, and allowing only relative
+    /// translations along the given world-space axis.
+    ///
+    /// The local anchors and axes are computed from the current transforms of both
+    /// rigid-bodies.
+    pub fn connect_in_place(
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+        world_axis: Vect,
+    ) -> Self {
+        #[cfg(feature = "dim2")]
+        let world_basis = world_axis.y.atan2(world_axis.x);
+        #[cfg(feature = "dim3")]
+        let world_basis = Quat::from_rotation_arc(Vect::X, world_axis.normalize());
+        let data = GenericJointBuilder::connect_in_place(
+            JointAxesMask::LOCKED_PRISMATIC_AXES,
+            transform1,
+            transform2,
+            world_anchor,
+            world_basis,
+        );
+        Self(PrismaticJoint { data: data.build() })
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
@@ -221,7 +267,7 @@ impl PrismaticJointBuilder {
 
     /// Configure both the target angle and target velocity of the motor.
     #[must_use]
-    pub fn set_motor(
+    pub fn motor(
         mut self,
         target_pos: Real,
         target_vel: Real,
@@ -232,6 +278,19 @@ impl PrismaticJointBuilder {
         self
     }
 
+    /// Configure both the target angle and target velocity of the motor.
+    #[must_use]
+    #[deprecated(note = "Use `.motor` instead.")]
+    pub fn set_motor(
+        self,
+        target_pos: Real,
+        target_vel: Real,
+        stiffness: Real,
+        damping: Real,
+    ) -> Self {
+        self.motor(target_pos, target_vel, stiffness, damping)
+    }
+
     /// Sets the maximum force the motor can deliver.
     #[must_use]
     pub fn motor_max_force(mut self, max_force: Real) -> Self {
@@ -246,6 +305,13 @@ impl PrismaticJointBuilder {
         self
     }
 
+    /// Makes the locked axes of this joint soft.
+    #[must_use]
+    pub fn softness(mut self, softness: JointSoftness) -> Self {
+        self.0.set_softness(Some(softness));
+        self
+    }
+
     /// Builds the prismatic joint.
     #[must_use]
     pub fn build(self) -> PrismaticJoint {
@@ -0,0 +1,125 @@
+use crate::math::{Real, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
+
+/// A pulley joint, connecting two rigid-bodies with a rope running over two fixed pulleys.
+///
+/// The rope goes from the anchor of the first rigid-body to the first world-space ground anchor,
+/// then from the second ground anchor to the anchor of the second rigid-body. The joint keeps
+/// `length1 + ratio * length2` below the total length of the rope, where `length1` and `length2`
+/// are the lengths of each side of the rope. The rope can only pull: the bodies are free to move
+/// while it is slack.
+///
+/// This component must be attached to the second rigid-body, and `parent` is the entity of
+/// the first rigid-body. The pulley is enforced on the velocities before each simulation step.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct PulleyJoint {
+    /// The entity of the first rigid-body attached to this joint.
+    pub parent: Entity,
+    /// The world-space anchor the rope of the first rigid-body runs over.
+    pub ground_anchor1: Vect,
+    /// The world-space anchor the rope of the second rigid-body runs over.
+    pub ground_anchor2: Vect,
+    /// The rope’s anchor, expressed in the local-space of the first rigid-body.
+    pub local_anchor1: Vect,
+    /// The rope’s anchor, expressed in the local-space of the second rigid-body.
+    pub local_anchor2: Vect,
+    /// The total length of the rope, `length1 + ratio * length2`.
+    pub length: Real,
+    /// The ratio of the pulley, i.e., how much longer the first side of the rope becomes when
+    /// the second side is shortened by one unit.
+    pub ratio: Real,
+}
+
+impl PulleyJoint {
+    /// Creates a pulley joint attaching the entity it is added to, to the `parent` entity.
+    pub fn new(parent: Entity, data: PulleyJointBuilder) -> Self {
+        Self {
+            parent,
+            ground_anchor1: data.ground_anchor1,
+            ground_anchor2: data.ground_anchor2,
+            local_anchor1: data.local_anchor1,
+            local_anchor2: data.local_anchor2,
+            length: data.length,
+            ratio: data.ratio,
+        }
+    }
+}
+
+impl FromWorld for PulleyJoint {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(
+            Entity::from_raw(u32::MAX),
+            PulleyJointBuilder::new(Vect::ZERO, Vect::ZERO, 0.0),
+        )
+    }
+}
+
+impl MapEntities for PulleyJoint {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(parent) = entity_map.get(self.parent) {
+            self.parent = parent;
+        }
+        Ok(())
+    }
+}
+
+/// Create pulley joints using the builder pattern.
+///
+/// The built joint is added to an entity with [`PulleyJoint::new`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PulleyJointBuilder {
+    ground_anchor1: Vect,
+    ground_anchor2: Vect,
+    local_anchor1: Vect,
+    local_anchor2: Vect,
+    length: Real,
+    ratio: Real,
+}
+
+impl PulleyJointBuilder {
+    /// Creates a new builder for pulley joints, running over the given world-space ground
+    /// anchors with a rope of the given total length, and with a ratio of 1.
+    pub fn new(ground_anchor1: Vect, ground_anchor2: Vect, length: Real) -> Self {
+        Self {
+            ground_anchor1,
+            ground_anchor2,
+            local_anchor1: Vect::ZERO,
+            local_anchor2: Vect::ZERO,
+            length,
+            ratio: 1.0,
+        }
+    }
+
+    /// Sets the rope’s anchor, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
+        self.local_anchor1 = anchor1;
+        self
+    }
+
+    /// Sets the rope’s anchor, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_anchor2(mut self, anchor2: Vect) -> Self {
+        self.local_anchor2 = anchor2;
+        self
+    }
+
+    /// Sets the total length of the rope, `length1 + ratio * length2`.
+    #[must_use]
+    pub fn length(mut self, length: Real) -> Self {
+        self.length = length;
+        self
+    }
+
+    /// Sets the ratio of the pulley.
+    #[must_use]
+    pub fn ratio(mut self, ratio: Real) -> Self {
+        self.ratio = ratio;
+        self
+    }
+}
@@ -0,0 +1,185 @@
+use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::math::{Real, Rot, Vect};
+use bevy::{prelude::*, reflect::FromReflect};
+use rapier::dynamics::{JointAxesMask, JointAxis};
+use std::collections::HashMap;
+
+/// Whether the bones of a ragdoll follow their animation or are simulated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Reflect, FromReflect)]
+pub enum RagdollMode {
+    /// The bones are kinematic, and follow the transforms set by the animation.
+    #[default]
+    Kinematic,
+    /// The bones are dynamic, and driven by the physics simulation.
+    ///
+    /// The animation of these bones should be stopped, otherwise it will fight against
+    /// the transforms written by the physics.
+    Dynamic,
+}
+
+/// The joint attaching a ragdoll bone to its closest ancestor bone.
+///
+/// The joint’s frame is aligned with the bone, its `X` axis pointing toward the end of
+/// the bone. All the angles are relative to the pose of the skeleton when the ragdoll
+/// is created.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RagdollJoint {
+    /// A ball-and-socket joint.
+    Spherical {
+        /// The maximum angle between the bone and its rest direction.
+        swing_limit: Real,
+        /// The maximum rotation angle of the bone around its own axis.
+        twist_limit: Real,
+    },
+    /// A hinge joint (e.g. for elbows and knees).
+    Revolute {
+        /// The rotation axis, expressed in the local-space of the bone.
+        axis: Vect,
+        /// The minimum and maximum rotation angles around the axis.
+        limits: [Real; 2],
+    },
+    /// The bone is rigidly attached to its parent bone.
+    Fixed,
+}
+
+impl RagdollJoint {
+    /// Builds the joint attaching a bone to its parent bone.
+    ///
+    /// The `rest_rotation` and `anchor1` are the pose of the bone relative to its parent bone,
+    /// and `bone_dir` is the direction of the bone expressed in its local-space.
+    pub(crate) fn build(self, rest_rotation: Rot, anchor1: Vect, bone_dir: Vect) -> GenericJoint {
+        let (locked_axes, frame2) = match self {
+            RagdollJoint::Spherical { .. } => (
+                JointAxesMask::LOCKED_SPHERICAL_AXES,
+                Rot::from_rotation_arc(Vect::X, bone_dir),
+            ),
+            RagdollJoint::Revolute { axis, .. } => (
+                JointAxesMask::LOCKED_REVOLUTE_AXES,
+                Rot::from_rotation_arc(Vect::X, axis.normalize()),
+            ),
+            RagdollJoint::Fixed => (JointAxesMask::LOCKED_FIXED_AXES, Rot::IDENTITY),
+        };
+
+        let mut builder = GenericJointBuilder::new(locked_axes)
+            .local_anchor1(anchor1)
+            .local_basis1(rest_rotation * frame2)
+            .local_basis2(frame2);
+
+        match self {
+            RagdollJoint::Spherical {
+                swing_limit,
+                twist_limit,
+            } => {
+                builder = builder
+                    .limits(JointAxis::AngX, [-twist_limit, twist_limit])
+                    .limits(JointAxis::AngY, [-swing_limit, swing_limit])
+                    .limits(JointAxis::AngZ, [-swing_limit, swing_limit]);
+            }
+            RagdollJoint::Revolute { limits, .. } => {
+                builder = builder.limits(JointAxis::AngX, limits);
+            }
+            RagdollJoint::Fixed => {}
+        }
+
+        let mut joint = builder.build();
+        joint.set_contacts_enabled(false);
+        joint
+    }
+}
+
+impl Default for RagdollJoint {
+    fn default() -> Self {
+        RagdollJoint::Spherical {
+            swing_limit: std::f32::consts::FRAC_PI_4,
+            twist_limit: std::f32::consts::FRAC_PI_8,
+        }
+    }
+}
+
+/// The configuration of a single bone of a [`Ragdoll`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct RagdollBoneConfig {
+    /// The radius of the capsule collider of the bone.
+    pub radius: Real,
+    /// The length of the capsule collider of the bone.
+    ///
+    /// If `None`, the capsule extends to the first child bone, or is a ball if the bone
+    /// has no child bone.
+    pub length: Option<Real>,
+    /// The joint attaching the bone to its closest ancestor bone, if any.
+    pub joint: RagdollJoint,
+}
+
+impl RagdollBoneConfig {
+    /// A bone with a capsule of the given radius, and a spherical joint.
+    pub fn new(radius: Real) -> Self {
+        Self {
+            radius,
+            length: None,
+            joint: RagdollJoint::default(),
+        }
+    }
+
+    /// Sets the length of the capsule collider of the bone.
+    #[must_use]
+    pub fn length(mut self, length: Real) -> Self {
+        self.length = Some(length);
+        self
+    }
+
+    /// Sets the joint attaching the bone to its closest ancestor bone.
+    #[must_use]
+    pub fn joint(mut self, joint: RagdollJoint) -> Self {
+        self.joint = joint;
+        self
+    }
+}
+
+/// Turns the skeleton of a `SkinnedMesh` into a ragdoll.
+///
+/// When this component is added to an entity with a `SkinnedMesh`, each of its bones with
+/// a `Name` matching a key of `bones` is given a [`RigidBody`](crate::dynamics::RigidBody),
+/// a capsule [`Collider`](crate::geometry::Collider), a [`RagdollBone`], and an
+/// [`ImpulseJoint`](crate::dynamics::ImpulseJoint) attaching it to its closest ancestor bone
+/// that is part of the ragdoll. Bones without configuration are left untouched.
+///
+/// Changing the `mode` of this component changes the mode of all its bones. The mode of
+/// a single bone can be changed afterward through its [`RagdollBone`] component.
+//
+// NOTE: the bones are stored in a `std` hash map, which isn’t reflectable, so this component
+//       is reflected as an opaque value.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect_value(Component, PartialEq)]
+pub struct Ragdoll {
+    /// The configuration of each bone, by bone name.
+    pub bones: HashMap<String, RagdollBoneConfig>,
+    /// Whether the bones follow their animation or are simulated.
+    pub mode: RagdollMode,
+}
+
+impl Ragdoll {
+    /// Creates a ragdoll without any bone.
+    pub fn new(mode: RagdollMode) -> Self {
+        Self {
+            bones: HashMap::new(),
+            mode,
+        }
+    }
+
+    /// Adds a bone to the ragdoll.
+    #[must_use]
+    pub fn with_bone(mut self, name: impl Into<String>, config: RagdollBoneConfig) -> Self {
+        self.bones.insert(name.into(), config);
+        self
+    }
+}
+
+/// A bone of a [`Ragdoll`].
+///
+/// Changes to its `mode` are applied to the `RigidBody` of the bone automatically.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct RagdollBone {
+    /// Whether the bone follows its animation or is simulated.
+    pub mode: RagdollMode,
+}
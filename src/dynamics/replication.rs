@@ -0,0 +1,343 @@
+use crate::dynamics::Velocity;
+use crate::math::{Real, Rot, Vect};
+use bevy::prelude::*;
+use bevy::reflect::FromReflect;
+
+#[cfg(feature = "dim2")]
+const DIM: usize = 2;
+#[cfg(feature = "dim3")]
+const DIM: usize = 3;
+#[cfg(feature = "dim2")]
+const ROT_DIM: usize = 1;
+#[cfg(feature = "dim3")]
+const ROT_DIM: usize = 4;
+#[cfg(feature = "dim2")]
+const ANG_DIM: usize = 1;
+#[cfg(feature = "dim3")]
+const ANG_DIM: usize = 3;
+
+/// The state of a rigid-body replicated over the network, e.g. from a server to its clients.
+///
+/// It is read from the physics world with
+/// [`RapierContext::body_state`](crate::plugin::RapierContext::body_state), quantized with
+/// [`Self::quantize`] to be sent, and applied to the rigid-body of a client with
+/// [`ReplicatedBody::receive`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodyState {
+    /// The translation of the rigid-body.
+    pub translation: Vect,
+    /// The rotation of the rigid-body.
+    pub rotation: Rot,
+    /// The velocity of the rigid-body.
+    pub velocity: Velocity,
+    /// Whether the rigid-body is sleeping.
+    pub sleeping: bool,
+}
+
+impl BodyState {
+    /// Quantizes this state with the given precision.
+    pub fn quantize(&self, quantization: &StateQuantization) -> QuantizedBodyState {
+        let quantize = |value: Real, step: Real| (value / step).round() as i32;
+
+        #[cfg(feature = "dim2")]
+        let rotation = {
+            let angle = (self.rotation + std::f32::consts::PI).rem_euclid(std::f32::consts::TAU)
+                - std::f32::consts::PI;
+            [(angle / std::f32::consts::PI * i16::MAX as Real).round() as i16]
+        };
+        #[cfg(feature = "dim3")]
+        let rotation = {
+            // `q` and `-q` are the same rotation: send the one with a positive `w`.
+            let rotation = self.rotation.normalize();
+            let rotation = if rotation.w < 0.0 {
+                -rotation
+            } else {
+                rotation
+            };
+            rotation
+                .to_array()
+                .map(|value| (value * i16::MAX as Real).round() as i16)
+        };
+        #[cfg(feature = "dim2")]
+        let angvel = [quantize(
+            self.velocity.angvel,
+            quantization.angular_velocity,
+        )];
+        #[cfg(feature = "dim3")]
+        let angvel = self
+            .velocity
+            .angvel
+            .to_array()
+            .map(|value| quantize(value, quantization.angular_velocity));
+
+        QuantizedBodyState {
+            translation: self
+                .translation
+                .to_array()
+                .map(|value| quantize(value, quantization.position)),
+            rotation,
+            linvel: self
+                .velocity
+                .linvel
+                .to_array()
+                .map(|value| quantize(value, quantization.velocity)),
+            angvel,
+            sleeping: self.sleeping,
+        }
+    }
+}
+
+/// The precision of the values of a [`QuantizedBodyState`].
+///
+/// Each value is rounded to a multiple of its precision. The rotations are always quantized to
+/// 16 bits per component.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct StateQuantization {
+    /// The precision of the translations.
+    pub position: Real,
+    /// The precision of the linear velocities.
+    pub velocity: Real,
+    /// The precision of the angular velocities, in radians per second.
+    pub angular_velocity: Real,
+}
+
+impl Default for StateQuantization {
+    fn default() -> Self {
+        Self {
+            position: 0.001,
+            velocity: 0.001,
+            angular_velocity: 0.001,
+        }
+    }
+}
+
+/// A [`BodyState`] quantized to integers, to be sent over the network.
+///
+/// It is sent either whole, e.g. with `serde`, or as its difference with a state the receiver
+/// already knows with [`Self::write_delta`], which takes a few bytes for a body moving slowly
+/// and a single byte for a body at rest.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct QuantizedBodyState {
+    /// The quantized translation.
+    pub translation: [i32; DIM],
+    /// The quantized rotation: an angle in 2D, the components `[x, y, z, w]` of a quaternion in 3D.
+    pub rotation: [i16; ROT_DIM],
+    /// The quantized linear velocity.
+    pub linvel: [i32; DIM],
+    /// The quantized angular velocity.
+    pub angvel: [i32; ANG_DIM],
+    /// Whether the rigid-body is sleeping.
+    pub sleeping: bool,
+}
+
+const TRANSLATION_CHANGED: u8 = 1;
+const ROTATION_CHANGED: u8 = 1 << 1;
+const LINVEL_CHANGED: u8 = 1 << 2;
+const ANGVEL_CHANGED: u8 = 1 << 3;
+const SLEEPING: u8 = 1 << 4;
+
+impl QuantizedBodyState {
+    /// The state these quantized values approximate.
+    pub fn dequantize(&self, quantization: &StateQuantization) -> BodyState {
+        let dequantize = |value: i32, step: Real| value as Real * step;
+
+        #[cfg(feature = "dim2")]
+        let rotation = self.rotation[0] as Real / i16::MAX as Real * std::f32::consts::PI;
+        #[cfg(feature = "dim3")]
+        let rotation = Rot::from_array(self.rotation.map(|value| value as Real / i16::MAX as Real))
+            .normalize();
+        #[cfg(feature = "dim2")]
+        let angvel = dequantize(self.angvel[0], quantization.angular_velocity);
+        #[cfg(feature = "dim3")]
+        let angvel = Vect::from_array(
+            self.angvel
+                .map(|value| dequantize(value, quantization.angular_velocity)),
+        );
+
+        BodyState {
+            translation: Vect::from_array(
+                self.translation
+                    .map(|value| dequantize(value, quantization.position)),
+            ),
+            rotation,
+            velocity: Velocity {
+                linvel: Vect::from_array(
+                    self.linvel
+                        .map(|value| dequantize(value, quantization.velocity)),
+                ),
+                angvel,
+            },
+            sleeping: self.sleeping,
+        }
+    }
+
+    /// Appends to `out` the difference between this state and `baseline`, a state the receiver
+    /// already knows, e.g. the last one it acknowledged.
+    ///
+    /// The first byte flags the groups of values which changed. Then, the difference of each
+    /// value of these groups is written as a variable-length integer. The
+    /// [`QuantizedBodyState::default`] can be used as baseline to send a whole state.
+    pub fn write_delta(&self, baseline: &Self, out: &mut Vec<u8>) {
+        let mut flags = if self.sleeping { SLEEPING } else { 0 };
+        if self.translation != baseline.translation {
+            flags |= TRANSLATION_CHANGED;
+        }
+        if self.rotation != baseline.rotation {
+            flags |= ROTATION_CHANGED;
+        }
+        if self.linvel != baseline.linvel {
+            flags |= LINVEL_CHANGED;
+        }
+        if self.angvel != baseline.angvel {
+            flags |= ANGVEL_CHANGED;
+        }
+        out.push(flags);
+
+        let mut write = |value: i32, baseline: i32| {
+            // Zigzag-encoding, so that small negative differences are small integers too.
+            let diff = value.wrapping_sub(baseline);
+            let mut bits = ((diff << 1) ^ (diff >> 31)) as u32;
+            while bits >= 0x80 {
+                out.push(bits as u8 | 0x80);
+                bits >>= 7;
+            }
+            out.push(bits as u8);
+        };
+
+        if flags & TRANSLATION_CHANGED != 0 {
+            for (value, baseline) in self.translation.iter().zip(baseline.translation) {
+                write(*value, baseline);
+            }
+        }
+        if flags & ROTATION_CHANGED != 0 {
+            for (value, baseline) in self.rotation.iter().zip(baseline.rotation) {
+                write(*value as i32, baseline as i32);
+            }
+        }
+        if flags & LINVEL_CHANGED != 0 {
+            for (value, baseline) in self.linvel.iter().zip(baseline.linvel) {
+                write(*value, baseline);
+            }
+        }
+        if flags & ANGVEL_CHANGED != 0 {
+            for (value, baseline) in self.angvel.iter().zip(baseline.angvel) {
+                write(*value, baseline);
+            }
+        }
+    }
+
+    /// Reads, from the start of `bytes`, a difference written by [`Self::write_delta`] with the
+    /// same `baseline`, and advances `bytes` past it.
+    ///
+    /// Returns `None` if the bytes are truncated or malformed.
+    pub fn read_delta(baseline: &Self, bytes: &mut &[u8]) -> Option<Self> {
+        let (flags, rest) = bytes.split_first()?;
+        let flags = *flags;
+        let mut rest = rest;
+
+        let mut read = |baseline: i32| -> Option<i32> {
+            let mut bits = 0u32;
+            for shift in (0..35).step_by(7) {
+                let (byte, tail) = rest.split_first()?;
+                rest = tail;
+                bits |= ((byte & 0x7f) as u32).checked_shl(shift)?;
+                if byte & 0x80 == 0 {
+                    let diff = (bits >> 1) as i32 ^ -((bits & 1) as i32);
+                    return Some(baseline.wrapping_add(diff));
+                }
+            }
+            None
+        };
+
+        let mut state = *baseline;
+        if flags & TRANSLATION_CHANGED != 0 {
+            for value in &mut state.translation {
+                *value = read(*value)?;
+            }
+        }
+        if flags & ROTATION_CHANGED != 0 {
+            for value in &mut state.rotation {
+                *value = read(*value as i32)? as i16;
+            }
+        }
+        if flags & LINVEL_CHANGED != 0 {
+            for value in &mut state.linvel {
+                *value = read(*value)?;
+            }
+        }
+        if flags & ANGVEL_CHANGED != 0 {
+            for value in &mut state.angvel {
+                *value = read(*value)?;
+            }
+        }
+        state.sleeping = flags & SLEEPING != 0;
+
+        *bytes = rest;
+        Some(state)
+    }
+}
+
+/// Component applying to a rigid-body the [`BodyState`]s received from the network.
+///
+/// When a state is received with [`Self::receive`], the [`Velocity`] and [`Sleeping`] components
+/// of the rigid-body, if any, are set to the received ones. Its transform is moved toward the
+/// received position, extrapolated with the received velocity, by a fraction `correction` of
+/// the remaining error at each update, which hides the small corrections of the client
+/// prediction. With a `correction` of `1.0`, the transform is set to the received position
+/// immediately.
+///
+/// [`Sleeping`]: crate::dynamics::Sleeping
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct ReplicatedBody {
+    /// The fraction of the error between the transform and the received position corrected at
+    /// each update, between `0.0` (excluded) and `1.0`.
+    pub correction: Real,
+    /// The distance below which the transform is snapped to the received position, ending the
+    /// correction.
+    pub snap_distance: Real,
+    #[reflect(ignore)]
+    pub(crate) target: Option<ReplicationTarget>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct ReplicationTarget {
+    pub state: BodyState,
+    pub received: bool,
+    pub elapsed: Real,
+}
+
+impl Default for ReplicatedBody {
+    fn default() -> Self {
+        Self {
+            correction: 0.2,
+            snap_distance: 0.001,
+            target: None,
+        }
+    }
+}
+
+impl ReplicatedBody {
+    /// A component applying the received states with the given correction.
+    pub fn new(correction: Real) -> Self {
+        Self {
+            correction,
+            ..Default::default()
+        }
+    }
+
+    /// Applies a state received from the network during the next updates.
+    pub fn receive(&mut self, state: BodyState) {
+        self.target = Some(ReplicationTarget {
+            state,
+            received: true,
+            elapsed: 0.0,
+        });
+    }
+
+    /// Whether the transform is still being moved toward the last received position.
+    pub fn is_correcting(&self) -> bool {
+        self.target.is_some()
+    }
+}
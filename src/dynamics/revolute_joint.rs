@@ -1,9 +1,14 @@
-use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::dynamics::{GenericJoint, GenericJointBuilder, JointSoftness};
 use crate::math::{Real, Vect};
+use bevy::prelude::GlobalTransform;
+#[cfg(feature = "dim3")]
+use bevy::prelude::Quat;
+use bevy::reflect::{FromReflect, Reflect};
 use rapier::dynamics::{JointAxesMask, JointAxis, JointLimits, JointMotor, MotorModel};
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 #[repr(transparent)]
 /// A revolute joint, locks all relative motion except for rotation along the joint’s principal axis.
 pub struct RevoluteJoint {
@@ -53,6 +58,20 @@ impl RevoluteJoint {
         self
     }
 
+    /// The softness of the locked axes of this joint, if they aren’t locked rigidly.
+    #[must_use]
+    pub fn softness(&self) -> Option<JointSoftness> {
+        self.data.softness()
+    }
+
+    /// Sets the softness of the locked axes of this joint.
+    ///
+    /// If `None`, the locked axes are locked rigidly.
+    pub fn set_softness(&mut self, softness: Option<JointSoftness>) -> &mut Self {
+        self.data.set_softness(softness);
+        self
+    }
+
     /// The joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(&self) -> Vect {
@@ -150,7 +169,8 @@ impl From<RevoluteJoint> for GenericJoint {
 ///
 /// A revolute joint locks all relative motion except for rotations along the joint’s principal axis.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 pub struct RevoluteJointBuilder(RevoluteJoint);
 
 #[cfg(feature = "dim2")]
@@ -175,6 +195,49 @@ impl RevoluteJointBuilder {
         Self(RevoluteJoint::new(axis))
     }
 
+    /// Creates a new builder for revolute joints, attaching the rigid-bodies at the given
+    /// world-space anchor in their current relative pose.
+    ///
+    /// The local anchors are computed from the current transforms of both rigid-bodies.
+    #[cfg(feature = "dim2")]
+    pub fn connect_in_place(
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+    ) -> Self {
+        let data = GenericJointBuilder::connect_in_place(
+            JointAxesMask::LOCKED_REVOLUTE_AXES,
+            transform1,
+            transform2,
+            world_anchor,
+            0.0,
+        );
+        Self(RevoluteJoint { data: data.build() })
+    }
+
+    /// Creates a new builder for revolute joints, attaching the rigid-bodies at the given
+    /// world-space anchor in their current relative pose, and allowing only relative
+    /// rotations along the given world-space axis.
+    ///
+    /// The local anchors and axes are computed from the current transforms of both
+    /// rigid-bodies.
+    #[cfg(feature = "dim3")]
+    pub fn connect_in_place(
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+        world_axis: Vect,
+    ) -> Self {
+        let data = GenericJointBuilder::connect_in_place(
+            JointAxesMask::LOCKED_REVOLUTE_AXES,
+            transform1,
+            transform2,
+            world_anchor,
+            Quat::from_rotation_arc(Vect::X, world_axis.normalize()),
+        );
+        Self(RevoluteJoint { data: data.build() })
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
@@ -237,6 +300,13 @@ impl RevoluteJointBuilder {
         self
     }
 
+    /// Makes the locked axes of this joint soft.
+    #[must_use]
+    pub fn softness(mut self, softness: JointSoftness) -> Self {
+        self.0.set_softness(Some(softness));
+        self
+    }
+
     /// Builds the revolute joint.
     #[must_use]
     pub fn build(self) -> RevoluteJoint {
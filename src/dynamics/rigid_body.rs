@@ -1,9 +1,17 @@
 use crate::math::Vect;
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+#[cfg(feature = "serde-serialize")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
 use bevy::{prelude::*, reflect::FromReflect};
 use rapier::prelude::{
-    Isometry, LockedAxes as RapierLockedAxes, RigidBodyActivation, RigidBodyHandle, RigidBodyType,
+    ColliderHandle, Isometry, LockedAxes as RapierLockedAxes, Real, RigidBodyActivation,
+    RigidBodyHandle, RigidBodyType,
 };
 use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::Duration;
 
 /// The Rapier handle of a rigid-body that was inserted to the physics scene.
 #[derive(Copy, Clone, Debug, Component)]
@@ -50,6 +58,17 @@ impl From<RigidBody> for RigidBodyType {
     }
 }
 
+impl From<RigidBodyType> for RigidBody {
+    fn from(rigid_body: RigidBodyType) -> RigidBody {
+        match rigid_body {
+            RigidBodyType::Dynamic => RigidBody::Dynamic,
+            RigidBodyType::Fixed => RigidBody::Fixed,
+            RigidBodyType::KinematicPositionBased => RigidBody::KinematicPositionBased,
+            RigidBodyType::KinematicVelocityBased => RigidBody::KinematicVelocityBased,
+        }
+    }
+}
+
 /// The velocity of a rigid-body.
 ///
 /// Use this component to control and/or read the velocity of a dynamic or kinematic rigid-body.
@@ -378,6 +397,242 @@ impl Default for GravityScale {
     }
 }
 
+/// Gravity pulling a rigid-body toward another entity (e.g. a planet) instead of
+/// following the global gravity vector.
+///
+/// While this component is attached, the global `RapierConfiguration::gravity` no longer
+/// affects the rigid-body. Instead, the rigid-body is accelerated by `strength` toward the
+/// translation of the `GlobalTransform` of the `center` entity, which may move freely. The
+/// rigid-body is woken up when the `center` entity moves.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct RadialGravity {
+    /// The entity the rigid-body is attracted to.
+    pub center: Entity,
+    /// The magnitude of the acceleration toward the `center` entity.
+    pub strength: Real,
+}
+
+impl RadialGravity {
+    /// Attracts the rigid-body toward `center` with an acceleration of magnitude `strength`.
+    pub fn new(center: Entity, strength: Real) -> Self {
+        Self { center, strength }
+    }
+}
+
+impl FromWorld for RadialGravity {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(Entity::from_raw(u32::MAX), 0.0)
+    }
+}
+
+impl MapEntities for RadialGravity {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(center) = entity_map.get(self.center) {
+            self.center = center;
+        }
+        Ok(())
+    }
+}
+
+/// Smooths the velocity of a kinematic position-based rigid-body over several simulation steps.
+///
+/// The velocity of a kinematic position-based rigid-body is normally deduced from the
+/// displacement toward the next position it is moved to. If these positions are sampled
+/// from a jittery source (e.g. an animation), the resulting velocity is spiky and so are
+/// the contacts with other bodies. With this component, the velocity used by contacts and
+/// written back to the `Velocity` component is computed from the displacement averaged over
+/// the last `window` simulation steps instead. The rigid-body still reaches each of its target
+/// positions.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct KinematicVelocityEstimation {
+    /// The number of simulation steps the displacement is averaged over.
+    ///
+    /// A value of `1` (or `0`) disables the smoothing.
+    pub window: usize,
+}
+
+impl Default for KinematicVelocityEstimation {
+    fn default() -> Self {
+        Self { window: 4 }
+    }
+}
+
+/// Welds the rigid-body of this entity onto the rigid-body of another entity.
+///
+/// The colliders of the welded rigid-body are moved to the rigid-body of the given entity,
+/// keeping their current relative transform, so that both rigid-bodies move as a single
+/// compound rigid-body. This is cheaper and stiffer than a fixed joint. The transform and
+/// velocity of the welded entity keep being updated to follow the compound. Removing this
+/// component splits the rigid-bodies again.
+///
+/// Welds can be chained: if the given entity is itself welded, the colliders are moved to the
+/// compound it is part of. The colliders added to the welded entity later on are moved to the
+/// compound too.
+///
+/// While welded, the rigid-body of this entity is not simulated: joints attached to it
+/// behave as if they were attached to a kinematic rigid-body.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct Weld(pub Entity);
+
+impl FromWorld for Weld {
+    fn from_world(_world: &mut World) -> Self {
+        Self(Entity::from_raw(u32::MAX))
+    }
+}
+
+impl MapEntities for Weld {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(target) = entity_map.get(self.0) {
+            self.0 = target;
+        }
+        Ok(())
+    }
+}
+
+// The bookkeeping of a rigid-body welded by a `Weld` component.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub(crate) struct WeldedBody {
+    // The entity given to the `Weld`.
+    #[cfg_attr(
+        feature = "serde-serialize",
+        serde(with = "crate::plugin::snapshot::entity_bits")
+    )]
+    pub target: Entity,
+    // The rigid-body of the compound the colliders were welded onto, which is the one of
+    // `target` unless `target` is itself welded.
+    pub target_handle: RigidBodyHandle,
+    // The position of the welded rigid-body relative to `target_handle`.
+    pub relative_pos: Isometry<Real>,
+    // The colliders of the welded rigid-body moved to `target_handle`.
+    pub colliders: Vec<ColliderHandle>,
+    // The type of the welded rigid-body before it was welded.
+    pub body_type: RigidBodyType,
+}
+
+/// What happens to an entity with a [`DespawnWhenSleeping`] once its rigid-body
+/// has been asleep long enough.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Reflect, FromReflect)]
+pub enum SleepTimeoutAction {
+    /// The entity is despawned, along with its children.
+    #[default]
+    Despawn,
+    /// The entity is kept, and only a `SleepTimeoutEvent` is sent.
+    SendEvent,
+}
+
+/// Despawns a rigid-body (typically some debris) once it has been sleeping for a while.
+///
+/// A `SleepTimeoutEvent` is sent when the rigid-body has been sleeping for `after`,
+/// whichever the `action` is. The timer is reset whenever the rigid-body wakes up.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct DespawnWhenSleeping {
+    /// How long the rigid-body must have been sleeping before `action` is taken.
+    pub after: Duration,
+    /// What happens to the entity when the rigid-body has been sleeping for `after`.
+    pub action: SleepTimeoutAction,
+    /// For how long the rigid-body has been sleeping so far.
+    pub asleep_for: Duration,
+}
+
+impl Default for DespawnWhenSleeping {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(5))
+    }
+}
+
+impl DespawnWhenSleeping {
+    /// Despawns the entity once its rigid-body has been sleeping for `after`.
+    pub fn new(after: Duration) -> Self {
+        Self {
+            after,
+            action: SleepTimeoutAction::Despawn,
+            asleep_for: Duration::ZERO,
+        }
+    }
+
+    /// Only sends a `SleepTimeoutEvent` once the rigid-body has been sleeping for `after`.
+    pub fn send_event(after: Duration) -> Self {
+        Self {
+            action: SleepTimeoutAction::SendEvent,
+            ..Self::new(after)
+        }
+    }
+}
+
+/// Tracks the entity a rigid-body stands on in its [`StandingOn`] component, and adds the
+/// rigid-body to the [`Carrying`] component of that entity.
+///
+/// The rigid-body starts standing on an entity when the normal of their contact is within
+/// `max_slope_angle` of the up direction (opposite to the gravity). It keeps standing on it
+/// until the normal exceeds `release_slope_angle`, or the contact has been lost for more than
+/// `release_delay`, so that it doesn’t flicker on bumpy or moving supports.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct TrackStanding {
+    /// The maximum angle (radians) between the contact normal and the up direction for the
+    /// rigid-body to start standing on an entity.
+    pub max_slope_angle: Real,
+    /// The angle (radians) between the contact normal and the up direction beyond which the
+    /// rigid-body stops standing on an entity. Should be `>= max_slope_angle`.
+    pub release_slope_angle: Real,
+    /// The time, in seconds, the contact with an entity can be lost before the rigid-body stops
+    /// standing on it.
+    pub release_delay: Real,
+}
+
+impl Default for TrackStanding {
+    fn default() -> Self {
+        Self {
+            max_slope_angle: std::f32::consts::FRAC_PI_4,
+            release_slope_angle: std::f32::consts::FRAC_PI_3,
+            release_delay: 0.1,
+        }
+    }
+}
+
+/// The entity a rigid-body with [`TrackStanding`] stands on: the rigid-body of the supporting
+/// collider, or the collider itself if it isn’t attached to a rigid-body.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct StandingOn(pub Entity);
+
+impl FromWorld for StandingOn {
+    fn from_world(_world: &mut World) -> Self {
+        Self(Entity::from_raw(u32::MAX))
+    }
+}
+
+impl MapEntities for StandingOn {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(support) = entity_map.get(self.0) {
+            self.0 = support;
+        }
+        Ok(())
+    }
+}
+
+/// The rigid-bodies with [`TrackStanding`] standing on this entity, sorted.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct Carrying(pub Vec<Entity>);
+
+impl MapEntities for Carrying {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for carried in &mut self.0 {
+            if let Ok(mapped) = entity_map.get(*carried) {
+                *carried = mapped;
+            }
+        }
+        self.0.sort();
+        Ok(())
+    }
+}
+
 /// Information used for Continuous-Collision-Detection.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Component, Reflect, FromReflect)]
 #[reflect(Component, PartialEq)]
@@ -476,7 +731,15 @@ impl Default for Damping {
 /// If the `TimestepMode::Interpolated` mode is set and this component is present,
 /// the associated rigid-body will have its position automatically interpolated
 /// between the last two rigid-body positions set by the physics engine.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Component)]
+//
+// NOTE: the isometries aren’t reflectable, so this component is reflected as an opaque value.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    reflect_value(Component, PartialEq, Serialize, Deserialize)
+)]
+#[cfg_attr(not(feature = "serde-serialize"), reflect_value(Component, PartialEq))]
 pub struct TransformInterpolation {
     /// The starting point of the interpolation.
     pub start: Option<Isometry<f32>>,
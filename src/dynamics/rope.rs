@@ -0,0 +1,277 @@
+use crate::dynamics::{GenericJoint, SphericalJointBuilder};
+use crate::geometry::Collider;
+use crate::math::{Real, Rot, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::prelude::*;
+use bevy::reflect::FromReflect;
+use bevy::render::mesh::{Indices, PrimitiveTopology};
+
+/// An endpoint of a [`Rope`] attached to a rigid-body.
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+pub struct RopeAttachment {
+    /// The entity of the rigid-body the rope is attached to.
+    pub entity: Entity,
+    /// The attachment point, expressed in the local-space of the rigid-body.
+    pub local_anchor: Vect,
+}
+
+impl RopeAttachment {
+    /// Attaches the rope to the given point of the rigid-body of `entity`.
+    pub fn new(entity: Entity, local_anchor: Vect) -> Self {
+        Self {
+            entity,
+            local_anchor,
+        }
+    }
+
+    fn map_entity(&mut self, entity_map: &EntityMap) {
+        if let Ok(entity) = entity_map.get(self.entity) {
+            self.entity = entity;
+        }
+    }
+}
+
+/// A rope, simulated as a chain of capsule segments attached by spherical joints.
+///
+/// When this component is added to an entity, its segments are spawned as separate entities,
+/// each with a dynamic [`RigidBody`](crate::dynamics::RigidBody), a capsule
+/// [`Collider`](crate::geometry::Collider), and a [`RopeSegment`] component. The rope starts
+/// at the position of this entity and hangs along its local `-Y` axis. The entities of the
+/// segments are then listed by the [`RopeSegments`] component inserted on this entity.
+///
+/// The component can be modified afterward:
+/// - Changing `start` or `end` attaches or detaches the endpoints of the rope.
+/// - Changing `length` resizes all the segments, which can be used to winch the rope in or out.
+///   Large changes at once will make the rope jump, so it should be changed progressively.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct Rope {
+    /// The number of segments of the rope.
+    ///
+    /// Changing this after the rope was spawned has no effect.
+    pub segments: usize,
+    /// The rest length of the rope.
+    pub length: Real,
+    /// The radius of the rope.
+    pub radius: Real,
+    /// Whether the segments are attached by multibody joints instead of impulse joints.
+    ///
+    /// Multibody joints don’t stretch, but are slower to simulate for long ropes. Changing this
+    /// after the rope was spawned has no effect.
+    pub multibody: bool,
+    /// The rigid-body the first end of the rope is attached to, if any.
+    pub start: Option<RopeAttachment>,
+    /// The rigid-body the last end of the rope is attached to, if any.
+    pub end: Option<RopeAttachment>,
+}
+
+impl Rope {
+    /// Creates a free rope made of `segments` impulse-jointed segments.
+    pub fn new(segments: usize, length: Real, radius: Real) -> Self {
+        Self {
+            segments,
+            length,
+            radius,
+            multibody: false,
+            start: None,
+            end: None,
+        }
+    }
+
+    /// Attaches the segments of the rope with multibody joints.
+    #[must_use]
+    pub fn multibody(mut self) -> Self {
+        self.multibody = true;
+        self
+    }
+
+    /// Attaches the first end of the rope to the given point of the rigid-body of `entity`.
+    #[must_use]
+    pub fn attach_start(mut self, entity: Entity, local_anchor: Vect) -> Self {
+        self.start = Some(RopeAttachment::new(entity, local_anchor));
+        self
+    }
+
+    /// Attaches the last end of the rope to the given point of the rigid-body of `entity`.
+    #[must_use]
+    pub fn attach_end(mut self, entity: Entity, local_anchor: Vect) -> Self {
+        self.end = Some(RopeAttachment::new(entity, local_anchor));
+        self
+    }
+
+    /// The rest length of each segment of the rope.
+    pub fn segment_length(&self) -> Real {
+        self.length / self.segments.max(1) as Real
+    }
+
+    /// The collider of each segment of the rope.
+    pub(crate) fn segment_collider(&self) -> Collider {
+        let half_height = (self.segment_length() / 2.0 - self.radius).max(0.0);
+        Collider::capsule_y(half_height, self.radius)
+    }
+
+    /// The joint attaching a segment to the previous one.
+    pub(crate) fn segment_joint(&self) -> GenericJoint {
+        let half_length = self.segment_length() / 2.0;
+        let mut joint: GenericJoint = SphericalJointBuilder::new()
+            .local_anchor1(Vect::Y * -half_length)
+            .local_anchor2(Vect::Y * half_length)
+            .into();
+        joint.set_contacts_enabled(false);
+        joint
+    }
+
+    /// The joint attaching the first (if `start` is `true`) or last segment to its attachment.
+    pub(crate) fn attachment_joint(&self, attachment: RopeAttachment, start: bool) -> GenericJoint {
+        let half_length = self.segment_length() / 2.0;
+        let anchor2 = if start { Vect::Y } else { -Vect::Y } * half_length;
+        let mut joint: GenericJoint = SphericalJointBuilder::new()
+            .local_anchor1(attachment.local_anchor)
+            .local_anchor2(anchor2)
+            .into();
+        joint.set_contacts_enabled(false);
+        joint
+    }
+}
+
+impl FromWorld for Rope {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(1, 1.0, 0.1)
+    }
+}
+
+impl MapEntities for Rope {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for attachment in self.start.iter_mut().chain(self.end.iter_mut()) {
+            attachment.map_entity(entity_map);
+        }
+        Ok(())
+    }
+}
+
+/// The entities spawned for a [`Rope`].
+///
+/// This component is inserted automatically on the entity of the rope.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct RopeSegments {
+    /// The segments of the rope, from its first end to its last end.
+    pub segments: Vec<Entity>,
+    // The state of the rope the segments currently match.
+    pub(crate) length: Real,
+    pub(crate) start: Option<(RopeAttachment, Entity)>,
+    pub(crate) end: Option<(RopeAttachment, Entity)>,
+}
+
+impl MapEntities for RopeSegments {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for segment in &mut self.segments {
+            if let Ok(mapped) = entity_map.get(*segment) {
+                *segment = mapped;
+            }
+        }
+        for (attachment, segment) in self.start.iter_mut().chain(self.end.iter_mut()) {
+            attachment.map_entity(entity_map);
+            if let Ok(mapped) = entity_map.get(*segment) {
+                *segment = mapped;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A segment of a [`Rope`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct RopeSegment {
+    /// The entity of the rope.
+    pub rope: Entity,
+    /// The index of this segment, starting from the first end of the rope.
+    pub index: usize,
+}
+
+impl FromWorld for RopeSegment {
+    fn from_world(_world: &mut World) -> Self {
+        Self {
+            rope: Entity::from_raw(u32::MAX),
+            index: 0,
+        }
+    }
+}
+
+impl MapEntities for RopeSegment {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        if let Ok(rope) = entity_map.get(self.rope) {
+            self.rope = rope;
+        }
+        Ok(())
+    }
+}
+
+/// Generates a tube following the segments of the [`Rope`] of this entity into its
+/// `Handle<Mesh>`.
+///
+/// The mesh is regenerated after each simulation step, and is expressed in the local-space
+/// of the rope entity, so it can be rendered with a `PbrBundle` on the rope entity.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct RopeMesh {
+    /// The number of sides of the tube.
+    pub sides: usize,
+}
+
+impl Default for RopeMesh {
+    fn default() -> Self {
+        Self { sides: 8 }
+    }
+}
+
+impl RopeMesh {
+    /// Builds a tube of the given radius going through the given points.
+    ///
+    /// The ends of the tube are left open.
+    pub fn mesh(&self, points: &[Vect], radius: Real) -> Mesh {
+        let sides = self.sides.max(3);
+        let mut positions = Vec::with_capacity(points.len() * (sides + 1));
+        let mut normals = Vec::with_capacity(points.len() * (sides + 1));
+        let mut uvs = Vec::with_capacity(points.len() * (sides + 1));
+        let mut indices = Vec::with_capacity(points.len().saturating_sub(1) * sides * 6);
+        let mut distance = 0.0;
+
+        for (i, point) in points.iter().enumerate() {
+            let prev = points[i.saturating_sub(1)];
+            let next = points[(i + 1).min(points.len() - 1)];
+            let tangent = (next - prev).try_normalize().unwrap_or(Vect::Y);
+            let rotation = Rot::from_rotation_arc(Vect::Y, tangent);
+            distance += (*point - prev).length();
+
+            for j in 0..=sides {
+                let angle = j as Real / sides as Real * std::f32::consts::TAU;
+                let normal = rotation * Vect::new(angle.cos(), 0.0, angle.sin());
+                positions.push((*point + normal * radius).to_array());
+                normals.push(normal.to_array());
+                uvs.push([j as Real / sides as Real, distance]);
+            }
+        }
+
+        for i in 1..points.len() {
+            for j in 0..sides {
+                let a = ((i - 1) * (sides + 1) + j) as u32;
+                let b = a + 1;
+                let c = a + (sides + 1) as u32;
+                let d = c + 1;
+                indices.extend([a, c, b, b, c, d]);
+            }
+        }
+
+        let mut mesh = Mesh::new(PrimitiveTopology::TriangleList);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, positions);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_NORMAL, normals);
+        mesh.insert_attribute(Mesh::ATTRIBUTE_UV_0, uvs);
+        mesh.set_indices(Some(Indices::U32(indices)));
+        mesh
+    }
+}
@@ -0,0 +1,167 @@
+use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::math::{Real, Vect};
+use bevy::reflect::{FromReflect, Reflect};
+use rapier::dynamics::{JointAxesMask, JointAxis};
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+#[repr(transparent)]
+/// A rope joint, limits the maximum distance between the anchors of two bodies.
+///
+/// The bodies can move freely while the rope is slack. Once taut, the rope prevents
+/// them from moving further apart, or pulls them back like a spring if the rope has a
+/// spring configured. Rope joints are only supported as an `ImpulseJoint`.
+pub struct RopeJoint {
+    data: GenericJoint,
+}
+
+impl RopeJoint {
+    /// Creates a new rope joint limiting the distance between the anchors to `max_length`.
+    #[must_use]
+    pub fn new(max_length: Real) -> Self {
+        let data = GenericJointBuilder::new(JointAxesMask::empty())
+            .limits(JointAxis::X, [0.0, max_length])
+            .build();
+        Self { data }
+    }
+
+    /// The underlying generic joint.
+    pub fn data(&self) -> &GenericJoint {
+        &self.data
+    }
+
+    /// Are contacts between the attached rigid-bodies enabled?
+    pub fn contacts_enabled(&self) -> bool {
+        self.data.contacts_enabled()
+    }
+
+    /// Sets whether contacts between the attached rigid-bodies are enabled.
+    pub fn set_contacts_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.data.set_contacts_enabled(enabled);
+        self
+    }
+
+    /// The joint’s anchor, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_anchor1(&self) -> Vect {
+        self.data.local_anchor1()
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
+    pub fn set_local_anchor1(&mut self, anchor1: Vect) -> &mut Self {
+        self.data.set_local_anchor1(anchor1);
+        self
+    }
+
+    /// The joint’s anchor, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_anchor2(&self) -> Vect {
+        self.data.local_anchor2()
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the second rigid-body.
+    pub fn set_local_anchor2(&mut self, anchor2: Vect) -> &mut Self {
+        self.data.set_local_anchor2(anchor2);
+        self
+    }
+
+    /// The maximum distance between the anchors before the rope becomes taut.
+    #[must_use]
+    pub fn max_length(&self) -> Real {
+        self.data.raw.limits[JointAxis::X as usize].max
+    }
+
+    /// Sets the maximum distance between the anchors before the rope becomes taut.
+    pub fn set_max_length(&mut self, max_length: Real) -> &mut Self {
+        let i = JointAxis::X as usize;
+        self.data.raw.limits[i].max = max_length;
+        self.data.raw.motors[i].target_pos = max_length;
+        self
+    }
+
+    /// The `(stiffness, damping)` of the spring pulling the anchors back together
+    /// when the rope is stretched, if any.
+    #[must_use]
+    pub fn spring(&self) -> Option<(Real, Real)> {
+        self.data
+            .motor(JointAxis::X)
+            .map(|motor| (motor.stiffness, motor.damping))
+    }
+
+    /// Makes the rope elastic: once stretched beyond its maximum length, the anchors
+    /// are pulled back together by a spring instead of being stopped abruptly.
+    pub fn set_spring(&mut self, stiffness: Real, damping: Real) -> &mut Self {
+        let max_length = self.max_length();
+        self.data
+            .set_motor_position(JointAxis::X, max_length, stiffness, damping);
+        self
+    }
+
+    /// Makes the rope inextensible again, after a call to `set_spring`.
+    pub fn remove_spring(&mut self) -> &mut Self {
+        self.data.raw.motor_axes.remove(JointAxesMask::X);
+        self
+    }
+}
+
+impl From<RopeJoint> for GenericJoint {
+    fn from(joint: RopeJoint) -> GenericJoint {
+        joint.data
+    }
+}
+
+/// Create rope joints using the builder pattern.
+///
+/// A rope joint limits the maximum distance between the anchors of two bodies.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+pub struct RopeJointBuilder(RopeJoint);
+
+impl RopeJointBuilder {
+    /// Creates a new builder for rope joints with the given maximum length.
+    pub fn new(max_length: Real) -> Self {
+        Self(RopeJoint::new(max_length))
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
+        self.0.set_local_anchor1(anchor1);
+        self
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_anchor2(mut self, anchor2: Vect) -> Self {
+        self.0.set_local_anchor2(anchor2);
+        self
+    }
+
+    /// Sets the maximum distance between the anchors before the rope becomes taut.
+    #[must_use]
+    pub fn max_length(mut self, max_length: Real) -> Self {
+        self.0.set_max_length(max_length);
+        self
+    }
+
+    /// Makes the rope elastic, with a spring of the given stiffness and damping.
+    #[must_use]
+    pub fn spring(mut self, stiffness: Real, damping: Real) -> Self {
+        self.0.set_spring(stiffness, damping);
+        self
+    }
+
+    /// Builds the rope joint.
+    #[must_use]
+    pub fn build(self) -> RopeJoint {
+        self.0
+    }
+}
+
+impl From<RopeJointBuilder> for GenericJoint {
+    fn from(joint: RopeJointBuilder) -> GenericJoint {
+        joint.0.into()
+    }
+}
@@ -1,9 +1,12 @@
-use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::dynamics::{GenericJoint, GenericJointBuilder, JointSoftness};
 use crate::math::{Real, Vect};
+use bevy::prelude::{GlobalTransform, Quat};
+use bevy::reflect::{FromReflect, Reflect};
 use rapier::dynamics::{JointAxesMask, JointAxis, JointLimits, JointMotor, MotorModel};
 
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 #[repr(transparent)]
 /// A spherical joint, locks all relative translations between two bodies.
 pub struct SphericalJoint {
@@ -39,6 +42,20 @@ impl SphericalJoint {
         self
     }
 
+    /// The softness of the locked axes of this joint, if they aren’t locked rigidly.
+    #[must_use]
+    pub fn softness(&self) -> Option<JointSoftness> {
+        self.data.softness()
+    }
+
+    /// Sets the softness of the locked axes of this joint.
+    ///
+    /// If `None`, the locked axes are locked rigidly.
+    pub fn set_softness(&mut self, softness: Option<JointSoftness>) -> &mut Self {
+        self.data.set_softness(softness);
+        self
+    }
+
     /// The joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(&self) -> Vect {
@@ -140,7 +157,8 @@ impl From<SphericalJoint> for GenericJoint {
 
 /// Create spherical joints using the builder pattern.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
 pub struct SphericalJointBuilder(SphericalJoint);
 
 impl Default for SphericalJointBuilder {
@@ -155,6 +173,25 @@ impl SphericalJointBuilder {
         Self(SphericalJoint::new())
     }
 
+    /// Creates a new builder for spherical joints, attaching the rigid-bodies at the given
+    /// world-space anchor.
+    ///
+    /// The local anchors are computed from the current transforms of both rigid-bodies.
+    pub fn connect_in_place(
+        transform1: &GlobalTransform,
+        transform2: &GlobalTransform,
+        world_anchor: Vect,
+    ) -> Self {
+        let data = GenericJointBuilder::connect_in_place(
+            JointAxesMask::LOCKED_SPHERICAL_AXES,
+            transform1,
+            transform2,
+            world_anchor,
+            Quat::IDENTITY,
+        );
+        Self(SphericalJoint { data: data.build() })
+    }
+
     /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
     #[must_use]
     pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
@@ -226,6 +263,13 @@ impl SphericalJointBuilder {
         self
     }
 
+    /// Makes the locked axes of this joint soft.
+    #[must_use]
+    pub fn softness(mut self, softness: JointSoftness) -> Self {
+        self.0.set_softness(Some(softness));
+        self
+    }
+
     /// Builds the spherical joint.
     #[must_use]
     pub fn build(self) -> SphericalJoint {
@@ -0,0 +1,174 @@
+use crate::dynamics::{GenericJoint, GenericJointBuilder};
+use crate::math::{Real, Vect};
+use bevy::reflect::{FromReflect, Reflect};
+use rapier::dynamics::{JointAxesMask, JointAxis};
+
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+#[repr(transparent)]
+/// A spring joint, pulls or pushes the anchors of two bodies toward a rest distance.
+///
+/// Unlike a [`RopeJoint`](crate::dynamics::RopeJoint) with a spring, the spring joint
+/// also pushes the anchors apart when they get closer than the rest length. Spring joints
+/// are only supported as an `ImpulseJoint`. Their stiffness and damping can be tuned at
+/// runtime with the [`JointSpring`](crate::dynamics::JointSpring) component.
+pub struct SpringJoint {
+    data: GenericJoint,
+}
+
+impl SpringJoint {
+    /// Creates a new spring joint with the given rest length, stiffness, and damping.
+    #[must_use]
+    pub fn new(rest_length: Real, stiffness: Real, damping: Real) -> Self {
+        let data = GenericJointBuilder::new(JointAxesMask::empty())
+            .motor_position(JointAxis::X, rest_length, stiffness, damping)
+            .build();
+        Self { data }
+    }
+
+    /// The underlying generic joint.
+    pub fn data(&self) -> &GenericJoint {
+        &self.data
+    }
+
+    /// Are contacts between the attached rigid-bodies enabled?
+    pub fn contacts_enabled(&self) -> bool {
+        self.data.contacts_enabled()
+    }
+
+    /// Sets whether contacts between the attached rigid-bodies are enabled.
+    pub fn set_contacts_enabled(&mut self, enabled: bool) -> &mut Self {
+        self.data.set_contacts_enabled(enabled);
+        self
+    }
+
+    /// The joint’s anchor, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_anchor1(&self) -> Vect {
+        self.data.local_anchor1()
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
+    pub fn set_local_anchor1(&mut self, anchor1: Vect) -> &mut Self {
+        self.data.set_local_anchor1(anchor1);
+        self
+    }
+
+    /// The joint’s anchor, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_anchor2(&self) -> Vect {
+        self.data.local_anchor2()
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the second rigid-body.
+    pub fn set_local_anchor2(&mut self, anchor2: Vect) -> &mut Self {
+        self.data.set_local_anchor2(anchor2);
+        self
+    }
+
+    /// The distance between the anchors the spring is trying to reach.
+    #[must_use]
+    pub fn rest_length(&self) -> Real {
+        self.data.raw.motors[JointAxis::X as usize].target_pos
+    }
+
+    /// Sets the distance between the anchors the spring is trying to reach.
+    pub fn set_rest_length(&mut self, rest_length: Real) -> &mut Self {
+        self.data.raw.motors[JointAxis::X as usize].target_pos = rest_length;
+        self
+    }
+
+    /// The stiffness of the spring.
+    #[must_use]
+    pub fn stiffness(&self) -> Real {
+        self.data.raw.motors[JointAxis::X as usize].stiffness
+    }
+
+    /// Sets the stiffness of the spring.
+    pub fn set_stiffness(&mut self, stiffness: Real) -> &mut Self {
+        self.data.raw.motors[JointAxis::X as usize].stiffness = stiffness;
+        self
+    }
+
+    /// The damping of the spring.
+    #[must_use]
+    pub fn damping(&self) -> Real {
+        self.data.raw.motors[JointAxis::X as usize].damping
+    }
+
+    /// Sets the damping of the spring.
+    pub fn set_damping(&mut self, damping: Real) -> &mut Self {
+        self.data.raw.motors[JointAxis::X as usize].damping = damping;
+        self
+    }
+}
+
+impl From<SpringJoint> for GenericJoint {
+    fn from(joint: SpringJoint) -> GenericJoint {
+        joint.data
+    }
+}
+
+/// Create spring joints using the builder pattern.
+///
+/// A spring joint pulls or pushes the anchors of two bodies toward a rest distance.
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[derive(Copy, Clone, Debug, PartialEq, Reflect, FromReflect)]
+#[reflect(PartialEq)]
+pub struct SpringJointBuilder(SpringJoint);
+
+impl SpringJointBuilder {
+    /// Creates a new builder for spring joints with the given rest length, stiffness,
+    /// and damping.
+    pub fn new(rest_length: Real, stiffness: Real, damping: Real) -> Self {
+        Self(SpringJoint::new(rest_length, stiffness, damping))
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the first rigid-body.
+    #[must_use]
+    pub fn local_anchor1(mut self, anchor1: Vect) -> Self {
+        self.0.set_local_anchor1(anchor1);
+        self
+    }
+
+    /// Sets the joint’s anchor, expressed in the local-space of the second rigid-body.
+    #[must_use]
+    pub fn local_anchor2(mut self, anchor2: Vect) -> Self {
+        self.0.set_local_anchor2(anchor2);
+        self
+    }
+
+    /// Sets the distance between the anchors the spring is trying to reach.
+    #[must_use]
+    pub fn rest_length(mut self, rest_length: Real) -> Self {
+        self.0.set_rest_length(rest_length);
+        self
+    }
+
+    /// Sets the stiffness of the spring.
+    #[must_use]
+    pub fn stiffness(mut self, stiffness: Real) -> Self {
+        self.0.set_stiffness(stiffness);
+        self
+    }
+
+    /// Sets the damping of the spring.
+    #[must_use]
+    pub fn damping(mut self, damping: Real) -> Self {
+        self.0.set_damping(damping);
+        self
+    }
+
+    /// Builds the spring joint.
+    #[must_use]
+    pub fn build(self) -> SpringJoint {
+        self.0
+    }
+}
+
+impl From<SpringJointBuilder> for GenericJoint {
+    fn from(joint: SpringJointBuilder) -> GenericJoint {
+        joint.0.into()
+    }
+}
@@ -2,16 +2,21 @@ use std::fmt;
 
 #[cfg(feature = "dim3")]
 use crate::geometry::VHACDParameters;
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
 use bevy::prelude::*;
 use bevy::reflect::FromReflect;
-#[cfg(feature = "dim3")]
-use bevy::utils::HashMap;
-use bevy::utils::HashSet;
+use bevy::utils::{HashMap, HashSet};
 use rapier::geometry::Shape;
 use rapier::prelude::{ColliderHandle, InteractionGroups, SharedShape};
 
+#[cfg(feature = "serde-serialize")]
+use bevy::reflect::{ReflectDeserialize, ReflectSerialize};
+
 use crate::dynamics::{CoefficientCombineRule, MassProperties};
-use crate::math::Vect;
+use crate::math::{Real, Vect};
 
 /// The Rapier handle of a collider that was inserted to the physics scene.
 #[derive(Copy, Clone, Debug, Component)]
@@ -19,7 +24,7 @@ pub struct RapierColliderHandle(pub ColliderHandle);
 
 /// A component which will be replaced by the specified collider type after the referenced mesh become available.
 #[cfg(feature = "dim3")]
-#[derive(Component, Debug, Clone, Reflect)]
+#[derive(Component, Debug, Clone, Reflect, FromReflect)]
 #[reflect(Component)]
 pub struct AsyncCollider {
     /// Mesh handle to use for collider generation.
@@ -40,7 +45,8 @@ impl Default for AsyncCollider {
 
 /// A component which will be replaced the specified collider types on children with meshes after the referenced scene become available.
 #[cfg(feature = "dim3")]
-#[derive(Component, Debug, Clone)]
+#[derive(Component, Debug, Clone, Default, Reflect, FromReflect)]
+#[reflect(Component)]
 pub struct AsyncSceneCollider {
     /// Scene handle to use for colliders generation.
     pub handle: Handle<Scene>,
@@ -54,7 +60,7 @@ pub struct AsyncSceneCollider {
 
 /// Shape type based on a Bevy mesh asset.
 #[cfg(feature = "dim3")]
-#[derive(Debug, Clone, Reflect)]
+#[derive(Debug, Clone, Reflect, FromReflect)]
 pub enum ComputedColliderShape {
     /// Triangle-mesh.
     TriMesh,
@@ -64,7 +70,15 @@ pub enum ComputedColliderShape {
 
 /// A geometric entity that can be attached to a body so it can be affected by contacts
 /// and intersection queries.
-#[derive(Component, Clone)] // TODO: Reflect
+//
+// NOTE: the shapes aren’t reflectable, so this component is reflected as an opaque value.
+#[derive(Component, Clone, Reflect, FromReflect)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde-serialize",
+    reflect_value(Component, Serialize, Deserialize)
+)]
+#[cfg_attr(not(feature = "serde-serialize"), reflect_value(Component))]
 pub struct Collider {
     /// The raw shape from Rapier.
     pub raw: SharedShape,
@@ -96,6 +110,7 @@ impl fmt::Debug for Collider {
 
 /// Overwrites the default application of [`GlobalTransform::scale`] to collider shapes.
 #[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
 pub enum ColliderScale {
     /// This scale will be multiplied with the scale in the [`GlobalTransform`] component
     /// before being applied to the collider.
@@ -104,6 +119,12 @@ pub enum ColliderScale {
     Absolute(Vect),
 }
 
+impl Default for ColliderScale {
+    fn default() -> Self {
+        ColliderScale::Relative(Vect::ONE)
+    }
+}
+
 /// Indicates whether or not the collider is a sensor.
 #[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
 #[reflect(Component, PartialEq)]
@@ -211,6 +232,79 @@ impl Default for Restitution {
     }
 }
 
+/// The rolling resistance affecting a collider.
+///
+/// While in contact, the rolling of the collider is slowed down by a torque proportional
+/// to the contact force, so that balls and wheels eventually stop rolling instead of coasting
+/// forever. If this component is not attached to the collider entity, the one attached to the
+/// entity of its rigid-body is used, if any.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct RollingFriction {
+    /// The rolling resistance coefficient of a collider.
+    ///
+    /// This is a length: the lever arm of the contact force resisting the rolling.
+    /// It is typically a small fraction of the radius of the rolling shape.
+    /// Should be `>= 0`.
+    pub coefficient: f32,
+    /// The rule applied to combine the rolling resistance coefficients of two colliders in contact.
+    pub combine_rule: CoefficientCombineRule,
+}
+
+impl RollingFriction {
+    /// Creates a `RollingFriction` component from the given coefficient, and using the default
+    /// `CoefficientCombineRule::Average` coefficient combine rule.
+    pub const fn new(coefficient: f32) -> Self {
+        Self {
+            coefficient,
+            combine_rule: CoefficientCombineRule::Average,
+        }
+    }
+}
+
+impl Default for RollingFriction {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
+/// The spin friction affecting a collider.
+///
+/// While in contact, the rotation of the collider around the contact normal (e.g. a spinning
+/// top) is slowed down by a torque proportional to the contact force. If this component is not
+/// attached to the collider entity, the one attached to the entity of its rigid-body is used, if any.
+#[cfg(feature = "dim3")]
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct SpinFriction {
+    /// The spin friction coefficient of a collider.
+    ///
+    /// This is a length: the lever arm of the contact force resisting the spinning.
+    /// Should be `>= 0`.
+    pub coefficient: f32,
+    /// The rule applied to combine the spin friction coefficients of two colliders in contact.
+    pub combine_rule: CoefficientCombineRule,
+}
+
+#[cfg(feature = "dim3")]
+impl SpinFriction {
+    /// Creates a `SpinFriction` component from the given coefficient, and using the default
+    /// `CoefficientCombineRule::Average` coefficient combine rule.
+    pub const fn new(coefficient: f32) -> Self {
+        Self {
+            coefficient,
+            combine_rule: CoefficientCombineRule::Average,
+        }
+    }
+}
+
+#[cfg(feature = "dim3")]
+impl Default for SpinFriction {
+    fn default() -> Self {
+        Self::new(0.0)
+    }
+}
+
 bitflags::bitflags! {
     #[derive(Component, Reflect, FromReflect)]
     #[reflect(Component, Hash, PartialEq)]
@@ -449,13 +543,38 @@ bitflags::bitflags! {
         /// If set, Rapier will call `EventHandler::handle_contact_force_event`
         /// whenever relevant for this collider.
         const CONTACT_FORCE_EVENTS = 0b0010;
+        /// If set together with `COLLISION_EVENTS`, the `CollisionEventDetails` of the
+        /// `CollisionEvent::Started` events involving this collider include a snapshot of
+        /// their first contact manifold.
+        const COLLISION_EVENT_CONTACTS = 0b0100;
+        /// If set, a `CcdImpact` event is sent when the continuous collision detection of the
+        /// rigid-body of this collider stops it at an impact.
+        const CCD_IMPACTS = 0b1000;
     }
 }
 
 impl From<ActiveEvents> for rapier::pipeline::ActiveEvents {
     fn from(active_events: ActiveEvents) -> rapier::pipeline::ActiveEvents {
-        rapier::pipeline::ActiveEvents::from_bits(active_events.bits)
-            .expect("Internal error: invalid active events conversion.")
+        // `COLLISION_EVENT_CONTACTS` and `CCD_IMPACTS` are handled by bevy_rapier, not Rapier.
+        rapier::pipeline::ActiveEvents::from_bits_truncate(active_events.bits)
+    }
+}
+
+/// Restricts the collision events reported for a collider to the colliders of specific
+/// collision groups.
+///
+/// A collider with `ActiveEvents::COLLISION_EVENTS` and this component only reports the
+/// collision events with colliders member of at least one of these groups, according to their
+/// [`CollisionGroups::memberships`] (e.g. a player only interested in touching pickups and
+/// hazards, and not the ground). The events are still reported if the other collider has
+/// `ActiveEvents::COLLISION_EVENTS` and accepts this collider.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct CollisionEventFilter(pub Group);
+
+impl Default for CollisionEventFilter {
+    fn default() -> Self {
+        Self(Group::ALL)
     }
 }
 
@@ -470,10 +589,190 @@ impl Default for ContactForceEventThreshold {
     }
 }
 
+/// Enables the `ImpactEvent`s of a collider, to play sounds or spawn particles when it hits
+/// something.
+///
+/// Impacts are detected from the contact forces, so the collider also needs the
+/// `ActiveEvents::CONTACT_FORCE_EVENTS` flag. An impact is reported when the contact impulse and
+/// the speed at which the colliders approach each other along the contact normal exceed the
+/// thresholds, and no impact was reported for the same pair of colliders during the `cooldown`.
+/// This way, a box sliding or resting on the floor doesn’t report impacts continuously.
+///
+/// If this component is not attached to the collider entity, the one attached to the entity of
+/// its rigid-body is used, if any. If both colliders have impact events enabled, the smallest
+/// thresholds and the longest cooldown apply.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct ImpactEvents {
+    /// The contact impulse magnitude beyond which an impact is reported.
+    pub min_impulse: Real,
+    /// The approach speed, along the contact normal, beyond which an impact is reported.
+    pub min_relative_speed: Real,
+    /// The duration, in seconds, during which no other impact is reported for the same pair of
+    /// colliders after an impact.
+    pub cooldown: f32,
+}
+
+impl Default for ImpactEvents {
+    fn default() -> Self {
+        Self {
+            min_impulse: 0.0,
+            min_relative_speed: 1.0,
+            cooldown: 0.1,
+        }
+    }
+}
+
+/// An identifier of the material of a collider (e.g. wood or metal), reported by the
+/// `ImpactEvent`s to pick the sounds and particles of the impacts.
+///
+/// If this component is not attached to the collider entity, the one attached to the entity of
+/// its rigid-body is used, if any, and the default material `0` otherwise.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct ImpactMaterial(pub u32);
+
+/// Makes a collider a one-way platform, which can only be hit from one side.
+///
+/// Character controllers pass through one-way platforms, except when they move toward the
+/// platform from the side its `direction` points to (e.g. when they land on it from above).
+/// A character standing on a one-way platform can drop through it with
+/// `KinematicCharacterController::drop_through`.
+///
+/// Rigid-bodies aren’t affected by this component, unless the contacts of the platform are
+/// modified with [`ContactModificationContextView::update_as_one_way_platform`](crate::pipeline::ContactModificationContextView::update_as_one_way_platform)
+/// by the physics hooks, e.g. the [`ContactModifiers`](crate::pipeline::ContactModifiers) hooks.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct OneWayPlatform {
+    /// The side of the platform that can be hit, in the local-space of the collider.
+    pub direction: Vect,
+    /// The maximum angle (radians) between the contact normal and `direction` for the contacts
+    /// with rigid-bodies to be kept.
+    pub allowed_angle: Real,
+}
+
+impl Default for OneWayPlatform {
+    fn default() -> Self {
+        Self {
+            direction: Vect::Y,
+            allowed_angle: 0.1,
+        }
+    }
+}
+
+/// Makes a collider pass-through from one side for all the rigid-bodies.
+///
+/// The rigid-bodies moving through the collider along `allowed_direction` (e.g. balls thrown up
+/// through a platform) pass through it, and those coming from the other side collide with it.
+/// A rigid-body that started passing through keeps passing through until it no longer touches
+/// the collider. The direction is expressed in the local-space of the collider.
+///
+/// Unlike [`OneWayPlatform`], this doesn’t require any physics hooks: the contacts are filtered
+/// by the plugin before the user-defined physics hooks are run.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct OneWayCollider {
+    /// The direction along which rigid-bodies can pass through the collider, in the
+    /// local-space of the collider.
+    pub allowed_direction: Vect,
+    /// The maximum angle (radians) between the contact normal and `allowed_direction` for the
+    /// contacts to be kept.
+    pub tolerance: Real,
+}
+
+impl Default for OneWayCollider {
+    fn default() -> Self {
+        Self {
+            allowed_direction: Vect::Y,
+            tolerance: 0.1,
+        }
+    }
+}
+
+impl From<OneWayCollider> for OneWayPlatform {
+    fn from(collider: OneWayCollider) -> Self {
+        Self {
+            direction: collider.allowed_direction,
+            allowed_angle: collider.tolerance,
+        }
+    }
+}
+
+/// The velocity of the surface of a collider, e.g. to simulate a conveyor belt.
+///
+/// The colliders in contact with this collider are dragged along by friction, as if its surface
+/// was moving. The velocity is expressed in the local-space of the collider, and only its
+/// component tangent to the contacts is taken into account.
+///
+/// This is applied by the [`ContactModifiers`](crate::pipeline::ContactModifiers) physics hooks,
+/// to colliders with the `ActiveHooks::MODIFY_SOLVER_CONTACTS` flag.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct SurfaceVelocity(pub Vect);
+
+/// Overrides the friction coefficient of the contacts between a collider and specific colliders.
+///
+/// This replaces the coefficient computed from the [`Friction`] of the two colliders, e.g. to
+/// make a character slide on ice without making the ice slippery for everything else.
+/// If both colliders override the friction of their contacts, the override of the first
+/// collider of the contact pair is used.
+///
+/// This is applied by the [`ContactModifiers`](crate::pipeline::ContactModifiers) physics hooks,
+/// to colliders with the `ActiveHooks::MODIFY_SOLVER_CONTACTS` flag.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, MapEntities, PartialEq)]
+pub struct FrictionOverrides(pub HashMap<Entity, Real>);
+
+impl FrictionOverrides {
+    /// Overrides the friction coefficient of the contacts with the given collider.
+    pub fn with(mut self, collider: Entity, friction: Real) -> Self {
+        self.0.insert(collider, friction);
+        self
+    }
+}
+
+impl MapEntities for FrictionOverrides {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        self.0 = self
+            .0
+            .drain()
+            .map(|(collider, friction)| (entity_map.get(collider).unwrap_or(collider), friction))
+            .collect();
+        Ok(())
+    }
+}
+
+/// Makes a collider a volume of fluid (e.g. water) in which character controllers swim.
+///
+/// This component should be attached to a [`Sensor`] collider. Character controllers pass through
+/// fluid volumes, and swim when a large enough fraction of their shape is submerged (see
+/// `KinematicCharacterController::min_swimming_submersion`).
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct FluidVolume {
+    /// The density of the fluid, relative to the density of the characters swimming in it.
+    ///
+    /// The buoyancy of a swimming character is its gravity, multiplied by this density and by its
+    /// submerged fraction. Characters float if this is greater than 1.
+    pub density: Real,
+    /// The rate (per second) at which the vertical velocity of a swimming character is damped.
+    pub drag: Real,
+}
+
+impl Default for FluidVolume {
+    fn default() -> Self {
+        Self {
+            density: 1.0,
+            drag: 2.0,
+        }
+    }
+}
+
 /// Component which will be filled (if present) with a list of entities with which the current
 /// entity is currently in contact.
 #[derive(Component, Default, Reflect, FromReflect)]
-#[reflect(Component)]
+#[reflect(Component, MapEntities)]
 pub struct CollidingEntities(pub(crate) HashSet<Entity>);
 
 impl CollidingEntities {
@@ -500,3 +799,105 @@ impl CollidingEntities {
         self.0.iter().copied()
     }
 }
+
+impl MapEntities for CollidingEntities {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_entity_set(&mut self.0, entity_map);
+        Ok(())
+    }
+}
+
+/// Opts a [`Sensor`] collider in to the tracking of the colliders intersecting it.
+///
+/// The [`SensorIntersections`] component is automatically added to the entity.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct TrackIntersections;
+
+/// The entities of the colliders intersecting a sensor collider with the [`TrackIntersections`]
+/// component.
+///
+/// This is updated after each simulation step from the intersections detected by the narrow-phase,
+/// so it doesn’t require the sensor to have [`ActiveEvents::COLLISION_EVENTS`].
+#[derive(Clone, Debug, Component, Default, PartialEq, Eq, Reflect, FromReflect)]
+#[reflect(Component, MapEntities)]
+pub struct SensorIntersections(pub(crate) HashSet<Entity>);
+
+impl SensorIntersections {
+    /// Returns the number of entities intersecting the sensor.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if no entity intersects the sensor.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if the specified entity intersects the sensor.
+    #[must_use]
+    pub fn contains(&self, entity: Entity) -> bool {
+        self.0.contains(&entity)
+    }
+
+    /// An iterator visiting all the entities intersecting the sensor in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+impl MapEntities for SensorIntersections {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        map_entity_set(&mut self.0, entity_map);
+        Ok(())
+    }
+}
+
+/// Turns a [`Sensor`] collider into a trigger zone (e.g. a checkpoint, a capture zone, or a
+/// pressure plate), reporting `TriggerEvent`s for the colliders entering, staying in, and
+/// exiting it.
+///
+/// Like [`TrackIntersections`], this relies on the intersections detected by the narrow-phase,
+/// so it doesn’t require the sensor to have [`ActiveEvents::COLLISION_EVENTS`].
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct TriggerZone {
+    /// Only the colliders member of at least one of these groups, according to their
+    /// [`CollisionGroups::memberships`], trigger the zone.
+    pub filter: Group,
+    /// The time, in seconds, a collider must stay inside the zone before it is considered
+    /// entered.
+    ///
+    /// A collider leaving the zone before this time doesn’t report any event.
+    pub dwell_time: f32,
+}
+
+impl TriggerZone {
+    /// A trigger zone requiring the colliders to stay inside for `dwell_time` seconds before
+    /// they are considered entered.
+    pub fn with_dwell_time(dwell_time: f32) -> Self {
+        Self {
+            dwell_time,
+            ..default()
+        }
+    }
+}
+
+impl Default for TriggerZone {
+    fn default() -> Self {
+        Self {
+            filter: Group::ALL,
+            dwell_time: 0.0,
+        }
+    }
+}
+
+// Maps the entities of a set, keeping the entities outside of the map as is.
+fn map_entity_set(entities: &mut HashSet<Entity>, entity_map: &EntityMap) {
+    *entities = entities
+        .drain()
+        .map(|entity| entity_map.get(entity).unwrap_or(entity))
+        .collect();
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypeUuid};
+
+use crate::geometry::Collider;
+
+/// A collider shape stored as an asset.
+///
+/// The shapes which are expensive to compute, like convex decompositions, can be computed
+/// offline and written to a `.collider` file with [`Self::to_bytes`]. With the
+/// `serde-serialize` feature, these files are loaded by the `AssetServer`, and attached to
+/// entities with the [`AsyncColliderFromAsset`] component.
+#[derive(Clone, TypeUuid)]
+#[uuid = "72f61a42-af21-4051-b108-4a2e669be618"]
+pub struct ColliderAsset {
+    /// The collider.
+    pub collider: Collider,
+}
+
+impl From<Collider> for ColliderAsset {
+    fn from(collider: Collider) -> Self {
+        Self { collider }
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl ColliderAsset {
+    /// Serializes the unscaled shape of the collider to the content of a `.collider` file.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(&self.collider.unscaled)
+    }
+
+    /// Deserializes the content of a `.collider` file written with [`Self::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        let shape: rapier::prelude::SharedShape = bincode::deserialize(bytes)?;
+        Ok(Collider::from(shape).into())
+    }
+}
+
+/// The loader of the [`ColliderAsset`]s, from `.collider` files.
+#[cfg(feature = "serde-serialize")]
+#[derive(Default)]
+pub struct ColliderAssetLoader;
+
+#[cfg(feature = "serde-serialize")]
+impl bevy::asset::AssetLoader for ColliderAssetLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset = ColliderAsset::from_bytes(bytes)?;
+            load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["collider"]
+    }
+}
+
+/// A component which will be replaced by the collider of a [`ColliderAsset`] once it is loaded.
+#[derive(Component, Debug, Clone, Default, Reflect, FromReflect)]
+#[reflect(Component)]
+pub struct AsyncColliderFromAsset(pub Handle<ColliderAsset>);
@@ -518,18 +518,8 @@ impl Collider {
     /// with a non-uniform scale results in an ellipse which isn’t supported),
     /// the shape is approximated by a convex polygon/convex polyhedron using
     /// `num_subdivisions` subdivisions.
-    pub fn set_scale(&mut self, mut scale: Vect, num_subdivisions: u32) {
-        /// We restrict the scaling increment to 1.0e-4, to avoid numerical jitter
-        /// due to the extraction of scaling factor from the GlobalTransform matrix.
-        fn snap_value(new: &mut f32) {
-            const PRECISION: f32 = 1.0e4;
-            *new = (*new * PRECISION).round() / PRECISION;
-        }
-
-        snap_value(&mut scale.x);
-        snap_value(&mut scale.y);
-        #[cfg(feature = "dim3")]
-        snap_value(&mut scale.z);
+    pub fn set_scale(&mut self, scale: Vect, num_subdivisions: u32) {
+        let scale = Self::snap_scale(scale);
 
         if scale == self.scale {
             // Nothing to do.
@@ -554,6 +544,23 @@ impl Collider {
         }
     }
 
+    /// The scale actually applied by [`Self::set_scale`].
+    ///
+    /// We restrict the scaling increment to 1.0e-4, to avoid numerical jitter
+    /// due to the extraction of scaling factor from the GlobalTransform matrix.
+    pub(crate) fn snap_scale(mut scale: Vect) -> Vect {
+        fn snap_value(new: &mut f32) {
+            const PRECISION: f32 = 1.0e4;
+            *new = (*new * PRECISION).round() / PRECISION;
+        }
+
+        snap_value(&mut scale.x);
+        snap_value(&mut scale.y);
+        #[cfg(feature = "dim3")]
+        snap_value(&mut scale.z);
+        scale
+    }
+
     /// Projects a point on `self`, unless the projection lies further than the given max distance.
     ///
     /// The point is assumed to be expressed in the local-space of `self`.
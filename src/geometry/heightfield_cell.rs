@@ -0,0 +1,59 @@
+use crate::math::Real;
+use rapier::math::Point;
+use rapier::parry::shape::Shape;
+
+/// The cell of a heightfield collider touched by a scene query.
+///
+/// See [`RapierContext::heightfield_cell`](crate::plugin::RapierContext::heightfield_cell).
+#[cfg(feature = "dim2")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HeightfieldCell {
+    /// The index of the cell, i.e., of the segment of the heightfield.
+    pub index: usize,
+    /// The indices, in the heights given to [`Collider::heightfield`](crate::geometry::Collider::heightfield),
+    /// of the left and right ends of the cell.
+    pub heights: [usize; 2],
+}
+
+/// The cell of a heightfield collider touched by a scene query.
+///
+/// See [`RapierContext::heightfield_cell`](crate::plugin::RapierContext::heightfield_cell).
+#[cfg(feature = "dim3")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HeightfieldCell {
+    /// The row of the cell, along the local `z` axis of the heightfield.
+    pub row: usize,
+    /// The column of the cell, along the local `x` axis of the heightfield.
+    pub column: usize,
+    /// The indices, in the column-major heights given to
+    /// [`Collider::heightfield`](crate::geometry::Collider::heightfield), of the four corners
+    /// of the cell, in the order `(row, column)`, `(row + 1, column)`, `(row, column + 1)`,
+    /// `(row + 1, column + 1)`.
+    pub heights: [usize; 4],
+}
+
+impl HeightfieldCell {
+    /// The cell of the heightfield `shape` containing the vertical projection of the given point.
+    #[cfg(feature = "dim2")]
+    pub(crate) fn at_point(shape: &dyn Shape, point: &Point<Real>) -> Option<Self> {
+        let index = shape.as_heightfield()?.cell_at_point(point)?;
+        Some(Self {
+            index,
+            heights: [index, index + 1],
+        })
+    }
+
+    /// The cell of the heightfield `shape` containing the vertical projection of the given point.
+    #[cfg(feature = "dim3")]
+    pub(crate) fn at_point(shape: &dyn Shape, point: &Point<Real>) -> Option<Self> {
+        let heightfield = shape.as_heightfield()?;
+        let (row, column) = heightfield.cell_at_point(point)?;
+        let num_rows = heightfield.nrows() + 1;
+        let corner = column * num_rows + row;
+        Some(Self {
+            row,
+            column,
+            heights: [corner, corner + 1, corner + num_rows, corner + num_rows + 1],
+        })
+    }
+}
@@ -1,5 +1,15 @@
 pub use self::collider::*;
+#[cfg(feature = "serde-serialize")]
+pub use self::collider_asset::ColliderAssetLoader;
+pub use self::collider_asset::{AsyncColliderFromAsset, ColliderAsset};
+pub use self::heightfield_cell::HeightfieldCell;
+pub use self::physics_material::{
+    AppliedMaterial, MaterialPairResponse, PhysicsMaterial, PhysicsMaterials,
+};
 pub use self::shape_views::ColliderView;
+pub use self::static_world::{StaticWorld, StaticWorldBuilder};
+pub use self::terrain::{TerrainChunk, TerrainFocus, TerrainStreamer};
+pub use self::triangle_hit::TriangleHit;
 pub use rapier::geometry::InteractionGroups;
 pub use rapier::geometry::SolverFlags;
 pub use rapier::parry::query::TOIStatus;
@@ -7,12 +17,20 @@ pub use rapier::parry::shape::TriMeshFlags;
 pub use rapier::parry::transformation::{vhacd::VHACDParameters, voxelization::FillMode};
 
 use crate::math::{Real, Vect};
+use bevy::prelude::Entity;
+use bevy::render::primitives::Aabb;
 use rapier::prelude::FeatureId;
 
 mod collider;
+mod collider_asset;
 mod collider_impl;
+mod heightfield_cell;
+mod physics_material;
 /// Wrappers around Rapier shapes to access their properties.
 pub mod shape_views;
+mod static_world;
+mod terrain;
+mod triangle_hit;
 
 /// Result of the projection of a point on a shape.
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -78,6 +96,142 @@ impl RayIntersection {
     }
 }
 
+/// The closest points between two colliders.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ClosestPoints {
+    /// The two colliders are intersecting.
+    Intersecting,
+    /// The two colliders are non-intersecting but closer than the given maximum distance. Contains
+    /// the closest points on the first and the second collider, in world-space.
+    WithinMargin(Vect, Vect),
+    /// The two colliders are further apart than the given maximum distance.
+    Disjoint,
+}
+
+impl ClosestPoints {
+    pub(crate) fn from_rapier(
+        physics_scale: Real,
+        raw: rapier::parry::query::ClosestPoints,
+    ) -> Self {
+        match raw {
+            rapier::parry::query::ClosestPoints::Intersecting => Self::Intersecting,
+            rapier::parry::query::ClosestPoints::WithinMargin(p1, p2) => {
+                Self::WithinMargin((p1 * physics_scale).into(), (p2 * physics_scale).into())
+            }
+            rapier::parry::query::ClosestPoints::Disjoint => Self::Disjoint,
+        }
+    }
+}
+
+/// An axis-aligned region of the physics world, in Bevy units.
+///
+/// This can be built from a Bevy `Aabb`, a `(min, max)` pair of points, or, in 2D with the
+/// `debug-render` feature, a Bevy sprite `Rect`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Region {
+    /// The corner of the region with the smallest coordinates.
+    pub min: Vect,
+    /// The corner of the region with the largest coordinates.
+    pub max: Vect,
+}
+
+impl Region {
+    /// A region from its two corners, given in any order.
+    pub fn new(a: Vect, b: Vect) -> Self {
+        Self {
+            min: a.min(b),
+            max: a.max(b),
+        }
+    }
+
+    /// A region from its center and its half extents.
+    pub fn from_center_half_extents(center: Vect, half_extents: Vect) -> Self {
+        Self::new(center - half_extents, center + half_extents)
+    }
+
+    pub(crate) fn to_rapier(self, physics_scale: Real) -> rapier::prelude::Aabb {
+        rapier::prelude::Aabb {
+            mins: (self.min / physics_scale).into(),
+            maxs: (self.max / physics_scale).into(),
+        }
+    }
+}
+
+impl From<(Vect, Vect)> for Region {
+    fn from((a, b): (Vect, Vect)) -> Self {
+        Self::new(a, b)
+    }
+}
+
+impl From<Aabb> for Region {
+    fn from(aabb: Aabb) -> Self {
+        #[cfg(feature = "dim2")]
+        return Self::new(aabb.min().truncate(), aabb.max().truncate());
+        #[cfg(feature = "dim3")]
+        return Self::new(aabb.min().into(), aabb.max().into());
+    }
+}
+
+#[cfg(all(feature = "dim2", feature = "debug-render"))]
+impl From<bevy::sprite::Rect> for Region {
+    fn from(rect: bevy::sprite::Rect) -> Self {
+        Self::new(rect.min, rect.max)
+    }
+}
+
+/// The result of a line-of-sight test between two entities.
+///
+/// See [`RapierContext::line_of_sight`](crate::plugin::RapierContext::line_of_sight).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LineOfSight {
+    /// Nothing blocks the view between the two entities.
+    Clear,
+    /// The view between the two entities is blocked by the collider attached to this entity.
+    Blocked(Entity),
+}
+
+impl LineOfSight {
+    /// Whether nothing blocks the view between the two entities.
+    pub fn is_clear(&self) -> bool {
+        *self == LineOfSight::Clear
+    }
+}
+
+/// A cone (or circular sector, in 2D) of vision, used to find the entities seen by a viewer.
+///
+/// See [`RapierContext::visible_entities`](crate::plugin::RapierContext::visible_entities).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VisionCone {
+    /// The point the viewer looks from.
+    pub origin: Vect,
+    /// The direction the viewer looks at.
+    pub direction: Vect,
+    /// The maximum angle (radians) between `direction` and the direction of a visible entity.
+    pub half_angle: Real,
+    /// The maximum distance of a visible entity.
+    pub range: Real,
+}
+
+/// The first collider blocking a shape swept along a path.
+///
+/// See [`RapierContext::cast_shape_along_path`](crate::plugin::RapierContext::cast_shape_along_path).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PathHit {
+    /// The entity of the collider blocking the path.
+    pub entity: Entity,
+    /// The index of the segment of the path where the shape is blocked, i.e., the shape is
+    /// blocked between the points `segment` and `segment + 1` of the path.
+    pub segment: usize,
+    /// The distance traveled by the shape along the path before being blocked.
+    pub distance: Real,
+    /// The position of the shape when it is blocked.
+    pub position: Vect,
+    /// The result of the shape cast along the blocked segment. Its time-of-impact is the distance
+    /// traveled along this segment, and its witness and normal 1 refer to the blocking collider,
+    /// in world-space.
+    pub toi: Toi,
+}
+
 /// The result of a time-of-impact (TOI) computation.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Toi {
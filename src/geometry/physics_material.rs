@@ -0,0 +1,127 @@
+use bevy::asset::HandleId;
+use bevy::prelude::*;
+use bevy::reflect::{FromReflect, TypeUuid};
+use bevy::utils::HashMap;
+
+use crate::math::Real;
+
+/// A physics material, identifying the colliders whose contacts are tuned by the
+/// [`PhysicsMaterials`] table (e.g. ice, rubber, or metal).
+#[derive(Clone, Debug, Default, PartialEq, Eq, TypeUuid)]
+#[uuid = "38d8dead-0322-402c-85bb-01f52849b4d8"]
+pub struct PhysicsMaterial {
+    /// The name of the material, for debugging purpose.
+    pub name: String,
+}
+
+impl PhysicsMaterial {
+    /// Creates a physics material with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self { name: name.into() }
+    }
+}
+
+/// The physics material of a collider.
+///
+/// If this component is not attached to the collider entity, the one attached to the entity of
+/// its rigid-body is used, if any.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Component, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
+pub struct AppliedMaterial(pub Handle<PhysicsMaterial>);
+
+/// The overrides of the collision response between two physics materials.
+///
+/// The coefficients left to `None` are computed from the colliders, as if they had no material.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MaterialPairResponse {
+    /// The friction coefficient of the contacts.
+    pub friction: Option<Real>,
+    /// The restitution coefficient of the contacts.
+    pub restitution: Option<Real>,
+    /// The rolling resistance coefficient of the contacts (see `RollingFriction`).
+    pub rolling_friction: Option<Real>,
+}
+
+impl MaterialPairResponse {
+    /// Overrides the friction coefficient of the contacts.
+    pub fn with_friction(mut self, friction: Real) -> Self {
+        self.friction = Some(friction);
+        self
+    }
+
+    /// Overrides the restitution coefficient of the contacts.
+    pub fn with_restitution(mut self, restitution: Real) -> Self {
+        self.restitution = Some(restitution);
+        self
+    }
+
+    /// Overrides the rolling resistance coefficient of the contacts.
+    pub fn with_rolling_friction(mut self, rolling_friction: Real) -> Self {
+        self.rolling_friction = Some(rolling_friction);
+        self
+    }
+}
+
+/// Resource mapping pairs of [`PhysicsMaterial`]s to the overrides of their collision response.
+///
+/// The friction and restitution overrides are applied by the
+/// [`ContactModifiers`](crate::pipeline::ContactModifiers) physics hooks, to the colliders with
+/// an [`AppliedMaterial`] and the `ActiveHooks::MODIFY_SOLVER_CONTACTS` flag. The rolling
+/// resistance overrides are applied along with the `RollingFriction` components.
+///
+/// The order of the two materials of a pair doesn’t matter.
+#[derive(Clone, Debug, Default)]
+pub struct PhysicsMaterials {
+    pairs: HashMap<(HandleId, HandleId), MaterialPairResponse>,
+}
+
+impl PhysicsMaterials {
+    fn pair_key(material1: HandleId, material2: HandleId) -> (HandleId, HandleId) {
+        if material1 <= material2 {
+            (material1, material2)
+        } else {
+            (material2, material1)
+        }
+    }
+
+    /// Sets the collision response between two materials, returning the previous one.
+    pub fn insert(
+        &mut self,
+        material1: &Handle<PhysicsMaterial>,
+        material2: &Handle<PhysicsMaterial>,
+        response: MaterialPairResponse,
+    ) -> Option<MaterialPairResponse> {
+        self.pairs
+            .insert(Self::pair_key(material1.id, material2.id), response)
+    }
+
+    /// Sets the collision response between two materials, and returns `self`.
+    pub fn with(
+        mut self,
+        material1: &Handle<PhysicsMaterial>,
+        material2: &Handle<PhysicsMaterial>,
+        response: MaterialPairResponse,
+    ) -> Self {
+        self.insert(material1, material2, response);
+        self
+    }
+
+    /// The collision response between two materials.
+    pub fn get(
+        &self,
+        material1: &Handle<PhysicsMaterial>,
+        material2: &Handle<PhysicsMaterial>,
+    ) -> Option<&MaterialPairResponse> {
+        self.pairs.get(&Self::pair_key(material1.id, material2.id))
+    }
+
+    /// Removes the collision response between two materials.
+    pub fn remove(
+        &mut self,
+        material1: &Handle<PhysicsMaterial>,
+        material2: &Handle<PhysicsMaterial>,
+    ) -> Option<MaterialPairResponse> {
+        self.pairs
+            .remove(&Self::pair_key(material1.id, material2.id))
+    }
+}
@@ -0,0 +1,235 @@
+use crate::geometry::Collider;
+use crate::math::Real;
+use crate::utils;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use rapier::prelude::{Isometry, SharedShape, TypedShape};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
+
+#[cfg(feature = "dim2")]
+type ChunkCell = [i32; 2];
+#[cfg(feature = "dim3")]
+type ChunkCell = [i32; 3];
+
+type BuiltColliders = Vec<(Transform, Collider)>;
+
+/// Merges a large amount of static geometry (e.g. the props of a level) into a few colliders,
+/// instead of spawning an entity with a collider for each shape.
+///
+/// The shapes of each chunk of a grid, or of the whole world if no chunk size is set, are
+/// merged into a compound collider, and the triangles of the triangle-meshes into a single
+/// triangle-mesh collider. The polylines and heightfields can’t be merged, and keep their own
+/// collider. Smaller chunks make the colliders cheaper to update when only a part of the world
+/// is touched, but increase their number.
+///
+/// The colliders are built with [`Self::build`], or spawned with [`Self::spawn`], which builds
+/// them on the `AsyncComputeTaskPool` set up by Bevy’s `CorePlugin` without blocking the app.
+#[derive(Clone)]
+pub struct StaticWorldBuilder {
+    shapes: Vec<(Isometry<Real>, SharedShape)>,
+    chunk_size: Option<Real>,
+    scaled_shape_subdivision: u32,
+}
+
+impl Default for StaticWorldBuilder {
+    fn default() -> Self {
+        Self {
+            shapes: Vec::new(),
+            chunk_size: None,
+            scaled_shape_subdivision: 10,
+        }
+    }
+}
+
+impl StaticWorldBuilder {
+    /// An empty builder merging all its shapes together.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the shapes by chunks of the given size, according to their position.
+    pub fn chunk_size(mut self, chunk_size: Real) -> Self {
+        self.chunk_size = Some(chunk_size);
+        self
+    }
+
+    /// Sets the number of subdivisions of the shapes approximated by a convex polygon or
+    /// polyhedron once scaled, like `RapierConfiguration::scaled_shape_subdivision`.
+    pub fn scaled_shape_subdivision(mut self, scaled_shape_subdivision: u32) -> Self {
+        self.scaled_shape_subdivision = scaled_shape_subdivision;
+        self
+    }
+
+    /// Adds a collider with the given transform, e.g. the transform of a prop.
+    ///
+    /// The collider is scaled by the scale of the transform, on top of its own scale, like a
+    /// collider attached to an entity with this transform.
+    pub fn add(&mut self, transform: Transform, collider: &Collider) -> &mut Self {
+        #[cfg(feature = "dim2")]
+        let scale = transform.scale.truncate();
+        #[cfg(feature = "dim3")]
+        let scale = transform.scale;
+
+        let mut collider = collider.clone();
+        collider.set_scale(collider.scale() * scale, self.scaled_shape_subdivision);
+        self.shapes
+            .push((utils::transform_to_iso(&transform, 1.0), collider.raw));
+        self
+    }
+
+    /// The number of added colliders.
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    /// Whether no collider was added.
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// Merges the added colliders, returning the merged colliders with their transform.
+    pub fn build(&self) -> BuiltColliders {
+        let mut chunks: BTreeMap<ChunkCell, Vec<_>> = BTreeMap::new();
+        for (pose, shape) in &self.shapes {
+            chunks
+                .entry(self.cell(pose))
+                .or_default()
+                .push((*pose, shape));
+        }
+
+        let mut colliders = Vec::new();
+        for (cell, shapes) in chunks {
+            let origin = self.origin(cell);
+            let mut parts = Vec::new();
+            let mut vertices = Vec::new();
+            let mut indices = Vec::new();
+
+            for (mut pose, shape) in shapes {
+                pose.translation.vector -= origin.translation.vector;
+                match shape.as_typed_shape() {
+                    TypedShape::Compound(compound) => {
+                        parts.extend(
+                            compound
+                                .shapes()
+                                .iter()
+                                .map(|(part_pose, part)| (pose * part_pose, part.clone())),
+                        );
+                    }
+                    TypedShape::TriMesh(trimesh) => {
+                        let base = vertices.len() as u32;
+                        vertices.extend(trimesh.vertices().iter().map(|v| pose * v));
+                        indices.extend(
+                            trimesh
+                                .indices()
+                                .iter()
+                                .map(|[a, b, c]| [a + base, b + base, c + base]),
+                        );
+                    }
+                    TypedShape::Polyline(_)
+                    | TypedShape::HeightField(_)
+                    | TypedShape::Custom(_) => {
+                        pose.translation.vector += origin.translation.vector;
+                        colliders.push((
+                            utils::iso_to_transform(&pose, 1.0),
+                            Collider::from(shape.clone()),
+                        ));
+                    }
+                    _ => parts.push((pose, shape.clone())),
+                }
+            }
+
+            let transform = utils::iso_to_transform(&origin, 1.0);
+            if !parts.is_empty() {
+                colliders.push((transform, SharedShape::compound(parts).into()));
+            }
+            if !indices.is_empty() {
+                colliders.push((transform, SharedShape::trimesh(vertices, indices).into()));
+            }
+        }
+
+        colliders
+    }
+
+    /// Spawns an entity with a [`StaticWorld`] component, and builds the colliders in the
+    /// background. Once they are built, they are spawned as children of this entity.
+    pub fn spawn(self, commands: &mut Commands) -> Entity {
+        let (sender, receiver) = mpsc::channel();
+        AsyncComputeTaskPool::get()
+            .spawn(async move {
+                let _ = sender.send(self.build());
+            })
+            .detach();
+
+        commands
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(StaticWorld {
+                pending: Some(Mutex::new(receiver)),
+                colliders: Vec::new(),
+            })
+            .id()
+    }
+
+    fn cell(&self, pose: &Isometry<Real>) -> ChunkCell {
+        let chunk_size = match self.chunk_size {
+            Some(chunk_size) => chunk_size,
+            None => return ChunkCell::default(),
+        };
+        let mut cell = ChunkCell::default();
+        for (i, coord) in cell.iter_mut().enumerate() {
+            *coord = (pose.translation.vector[i] / chunk_size).floor() as i32;
+        }
+        cell
+    }
+
+    fn origin(&self, cell: ChunkCell) -> Isometry<Real> {
+        let mut origin = Isometry::identity();
+        if let Some(chunk_size) = self.chunk_size {
+            for (i, coord) in cell.iter().enumerate() {
+                origin.translation.vector[i] = *coord as Real * chunk_size;
+            }
+        }
+        origin
+    }
+}
+
+/// A component spawning the colliders built in the background by
+/// [`StaticWorldBuilder::spawn`] as children of its entity.
+#[derive(Component)]
+pub struct StaticWorld {
+    pending: Option<Mutex<Receiver<BuiltColliders>>>,
+    colliders: Vec<Entity>,
+}
+
+impl StaticWorld {
+    /// Whether the colliders are built and spawned.
+    pub fn is_built(&self) -> bool {
+        self.pending.is_none()
+    }
+
+    /// The entities of the spawned colliders.
+    pub fn colliders(&self) -> &[Entity] {
+        &self.colliders
+    }
+
+    /// The colliders built since the last call, if any.
+    pub(crate) fn take_built(&mut self) -> Option<BuiltColliders> {
+        let pending = self.pending.as_mut()?.get_mut().unwrap();
+        let built = match pending.try_recv() {
+            Ok(built) => built,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => {
+                error!("Unable to build the colliders of a static world.");
+                Vec::new()
+            }
+        };
+        self.pending = None;
+        Some(built)
+    }
+
+    pub(crate) fn set_colliders(&mut self, colliders: Vec<Entity>) {
+        self.colliders = colliders;
+    }
+}
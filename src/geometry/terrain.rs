@@ -0,0 +1,235 @@
+use crate::geometry::Collider;
+use crate::math::{Real, Vect};
+#[cfg(feature = "dim3")]
+use bevy::math::Vec3Swizzles;
+use bevy::prelude::*;
+use bevy::tasks::AsyncComputeTaskPool;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+/// The coordinates of a chunk of the horizontal grid of a [`TerrainStreamer`].
+#[cfg(feature = "dim2")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TerrainChunk {
+    /// The index of the chunk along the `x` axis.
+    pub x: i32,
+}
+
+/// The coordinates of a chunk of the horizontal grid of a [`TerrainStreamer`].
+#[cfg(feature = "dim3")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TerrainChunk {
+    /// The index of the chunk along the `x` axis.
+    pub x: i32,
+    /// The index of the chunk along the `z` axis.
+    pub z: i32,
+}
+
+impl TerrainChunk {
+    /// The center of the chunk, at a height of zero, in the local-space of the terrain.
+    #[cfg(feature = "dim2")]
+    pub fn center(&self, chunk_size: Real) -> Vect {
+        Vect::new((self.x as Real + 0.5) * chunk_size, 0.0)
+    }
+
+    /// The center of the chunk, at a height of zero, in the local-space of the terrain.
+    #[cfg(feature = "dim3")]
+    pub fn center(&self, chunk_size: Real) -> Vect {
+        Vect::new(
+            (self.x as Real + 0.5) * chunk_size,
+            0.0,
+            (self.z as Real + 0.5) * chunk_size,
+        )
+    }
+
+    /// The horizontal distance between the point and the closest point of the chunk.
+    #[cfg(feature = "dim2")]
+    fn distance(&self, chunk_size: Real, point: Vect) -> Real {
+        let min = self.x as Real * chunk_size;
+        (point.x - point.x.clamp(min, min + chunk_size)).abs()
+    }
+
+    /// The horizontal distance between the point and the closest point of the chunk.
+    #[cfg(feature = "dim3")]
+    fn distance(&self, chunk_size: Real, point: Vect) -> Real {
+        let min = Vec2::new(self.x as Real, self.z as Real) * chunk_size;
+        let point = point.xz();
+        point.distance(point.clamp(min, min + chunk_size))
+    }
+
+    /// The chunks closer than `radius` to the point.
+    #[cfg(feature = "dim2")]
+    fn around(chunk_size: Real, point: Vect, radius: Real) -> impl Iterator<Item = Self> {
+        let min = ((point.x - radius) / chunk_size).floor() as i32;
+        let max = ((point.x + radius) / chunk_size).floor() as i32;
+        (min..=max)
+            .map(|x| Self { x })
+            .filter(move |chunk| chunk.distance(chunk_size, point) <= radius)
+    }
+
+    /// The chunks closer than `radius` to the point.
+    #[cfg(feature = "dim3")]
+    fn around(chunk_size: Real, point: Vect, radius: Real) -> impl Iterator<Item = Self> {
+        let min = ((point.xz() - radius) / chunk_size).floor();
+        let max = ((point.xz() + radius) / chunk_size).floor();
+        (min.x as i32..=max.x as i32)
+            .flat_map(move |x| (min.y as i32..=max.y as i32).map(move |z| Self { x, z }))
+            .filter(move |chunk| chunk.distance(chunk_size, point) <= radius)
+    }
+}
+
+/// Marks an entity, e.g. a player or a camera, around which the colliders of the
+/// [`TerrainStreamer`]s are loaded.
+#[derive(Copy, Clone, Debug, Default, Component, Reflect)]
+#[reflect(Component)]
+pub struct TerrainFocus;
+
+type ChunkGenerator = dyn Fn(TerrainChunk) -> Option<Collider> + Send + Sync;
+
+/// A component streaming the colliders of an infinite terrain, by chunks of a horizontal grid,
+/// around the entities with a [`TerrainFocus`].
+///
+/// The collider of a chunk, e.g. a heightfield or a triangle-mesh, is built by the generator
+/// on the `AsyncComputeTaskPool` set up by Bevy’s `CorePlugin`, then spawned as a child of the
+/// terrain entity once built, with a transform placing its origin at the
+/// [`TerrainChunk::center`]. A heightfield scaled by the chunk size thus covers exactly its
+/// chunk. The generator can return `None` for a chunk
+/// without collider, e.g. under the sea.
+///
+/// The chunks closer than the load radius to a focus are loaded, and are despawned once they
+/// are farther than the unload radius from all the focuses. This hysteresis keeps a focus going
+/// back and forth across the edge of a chunk from reloading it again and again.
+#[derive(Component)]
+pub struct TerrainStreamer {
+    chunk_size: Real,
+    load_radius: Real,
+    unload_radius: Real,
+    generator: Arc<ChunkGenerator>,
+    chunks: HashMap<TerrainChunk, Option<Entity>>,
+    pending: HashSet<TerrainChunk>,
+    sender: Mutex<Sender<(TerrainChunk, Option<Collider>)>>,
+    receiver: Mutex<Receiver<(TerrainChunk, Option<Collider>)>>,
+}
+
+impl TerrainStreamer {
+    /// A terrain loading the chunks of the given size closer than `load_radius` to a focus.
+    ///
+    /// The chunks are unloaded once farther than `load_radius + chunk_size` from all the
+    /// focuses, see [`Self::with_unload_radius`].
+    pub fn new(
+        chunk_size: Real,
+        load_radius: Real,
+        generator: impl Fn(TerrainChunk) -> Option<Collider> + Send + Sync + 'static,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            chunk_size,
+            load_radius,
+            unload_radius: load_radius + chunk_size,
+            generator: Arc::new(generator),
+            chunks: HashMap::new(),
+            pending: HashSet::new(),
+            sender: Mutex::new(sender),
+            receiver: Mutex::new(receiver),
+        }
+    }
+
+    /// Sets the distance from all the focuses beyond which the chunks are unloaded. It is
+    /// clamped to be at least the load radius.
+    pub fn with_unload_radius(mut self, unload_radius: Real) -> Self {
+        self.unload_radius = unload_radius.max(self.load_radius);
+        self
+    }
+
+    /// The size of the chunks.
+    pub fn chunk_size(&self) -> Real {
+        self.chunk_size
+    }
+
+    /// The distance to a focus below which the chunks are loaded.
+    pub fn load_radius(&self) -> Real {
+        self.load_radius
+    }
+
+    /// The distance from all the focuses beyond which the chunks are unloaded.
+    pub fn unload_radius(&self) -> Real {
+        self.unload_radius
+    }
+
+    /// The loaded chunks, with the entity of their collider if the generator built one.
+    pub fn loaded_chunks(&self) -> impl Iterator<Item = (TerrainChunk, Option<Entity>)> + '_ {
+        self.chunks.iter().map(|(chunk, entity)| (*chunk, *entity))
+    }
+
+    /// The chunks whose collider is being built in the background.
+    pub fn pending_chunks(&self) -> impl Iterator<Item = TerrainChunk> + '_ {
+        self.pending.iter().copied()
+    }
+
+    /// Whether the chunk is loaded.
+    pub fn is_loaded(&self, chunk: TerrainChunk) -> bool {
+        self.chunks.contains_key(&chunk)
+    }
+
+    /// Spawns the colliders of the chunks built since the last update, then starts building
+    /// the chunks close to the focuses, and despawns the ones far from them.
+    ///
+    /// The `focuses` are given in the local-space of the terrain.
+    pub(crate) fn stream(&mut self, commands: &mut Commands, terrain: Entity, focuses: &[Vect]) {
+        for (chunk, collider) in self.receiver.get_mut().unwrap().try_iter() {
+            // The chunk was unloaded while being built.
+            if !self.pending.remove(&chunk) {
+                continue;
+            }
+
+            let center = chunk.center(self.chunk_size);
+            #[cfg(feature = "dim2")]
+            let center = center.extend(0.0);
+            let entity = collider.map(|collider| {
+                let transform = Transform::from_translation(center);
+                let entity = commands
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(transform))
+                    .insert(collider)
+                    .id();
+                commands.entity(terrain).add_child(entity);
+                entity
+            });
+            self.chunks.insert(chunk, entity);
+        }
+
+        let (chunk_size, unload_radius) = (self.chunk_size, self.unload_radius);
+        let is_far = |chunk: &TerrainChunk| {
+            focuses
+                .iter()
+                .all(|focus| chunk.distance(chunk_size, *focus) > unload_radius)
+        };
+        self.pending.retain(|chunk| !is_far(chunk));
+        self.chunks.retain(|chunk, entity| {
+            if !is_far(chunk) {
+                return true;
+            }
+            if let Some(entity) = entity {
+                commands.entity(*entity).despawn_recursive();
+            }
+            false
+        });
+
+        let pool = AsyncComputeTaskPool::get();
+        for focus in focuses {
+            for chunk in TerrainChunk::around(self.chunk_size, *focus, self.load_radius) {
+                if self.chunks.contains_key(&chunk) || !self.pending.insert(chunk) {
+                    continue;
+                }
+
+                let generator = self.generator.clone();
+                let sender = self.sender.get_mut().unwrap().clone();
+                pool.spawn(async move {
+                    let _ = sender.send((chunk, generator(chunk)));
+                })
+                .detach();
+            }
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use crate::math::Real;
+use rapier::math::Point;
+use rapier::parry::query::{PointQuery, PointQueryWithLocation};
+use rapier::parry::shape::{FeatureId, Shape, Triangle};
+
+/// The triangle of a triangle mesh or heightfield collider touched by a scene query.
+///
+/// See [`RapierContext::triangle_hit`](crate::plugin::RapierContext::triangle_hit) and
+/// [`RapierContext::closest_triangle`](crate::plugin::RapierContext::closest_triangle).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TriangleHit {
+    /// The index of the triangle.
+    ///
+    /// For a collider built from a Bevy mesh, or with [`Collider::trimesh`](crate::geometry::Collider::trimesh),
+    /// this is the index of the triangle in the mesh indices: its vertices are given by the
+    /// indices `3 * triangle`, `3 * triangle + 1` and `3 * triangle + 2`. For a heightfield,
+    /// this is the index of the triangle (in 3D) or segment (in 2D) of the heightfield.
+    pub triangle: u32,
+    /// The barycentric coordinates of the point on the triangle, i.e., the weights of each of its
+    /// three vertices. These can be used to interpolate the UVs or colors of the vertices.
+    ///
+    /// For the segments of a 2D heightfield, the third coordinate is always zero.
+    pub barycentric: [Real; 3],
+    /// Whether the back face of the triangle was touched.
+    ///
+    /// In 2D, this is only set for the segments of heightfields, since triangles have no back
+    /// face.
+    pub back_face: bool,
+}
+
+impl TriangleHit {
+    /// The triangle identified by a feature reported by a ray cast on `shape`.
+    pub(crate) fn from_feature(
+        shape: &dyn Shape,
+        point: &Point<Real>,
+        feature: FeatureId,
+    ) -> Option<Self> {
+        let fid = match feature {
+            FeatureId::Face(fid) => fid,
+            _ => return None,
+        };
+
+        if let Some(trimesh) = shape.as_trimesh() {
+            // Back faces are identified by an offset of the number of triangles.
+            let num_triangles = trimesh.num_triangles() as u32;
+            let triangle = fid % num_triangles;
+            #[cfg(feature = "dim2")]
+            let back_face = false;
+            #[cfg(feature = "dim3")]
+            let back_face = fid >= num_triangles;
+            return Some(Self::on_triangle(
+                &trimesh.triangle(triangle),
+                point,
+                triangle,
+                back_face,
+            ));
+        }
+
+        #[cfg(feature = "dim2")]
+        if let Some(heightfield) = shape.as_heightfield() {
+            let num_cells = heightfield.num_cells() as u32;
+            let segment = fid % num_cells;
+            return heightfield.segment_at(segment as usize).map(|seg| {
+                let [a, b] = seg
+                    .project_local_point_and_get_location(point, false)
+                    .1
+                    .barycentric_coordinates();
+                Self {
+                    triangle: segment,
+                    barycentric: [a, b, 0.0],
+                    back_face: fid >= num_cells,
+                }
+            });
+        }
+
+        #[cfg(feature = "dim3")]
+        if let Some(heightfield) = shape.as_heightfield() {
+            let num_triangles = (heightfield.nrows() * heightfield.ncols() * 2) as u32;
+            let triangle = fid % num_triangles;
+            return heightfield
+                .triangle_at_id(triangle)
+                .map(|tri| Self::on_triangle(&tri, point, triangle, fid >= num_triangles));
+        }
+
+        None
+    }
+
+    /// The triangle of `shape` closest to the given point.
+    pub(crate) fn closest(shape: &dyn Shape, point: &Point<Real>) -> Option<Self> {
+        if let Some(trimesh) = shape.as_trimesh() {
+            let (_, (triangle, _)) = trimesh.project_local_point_and_get_location(point, false);
+            return Some(Self::closest_on_triangle(
+                &trimesh.triangle(triangle),
+                point,
+                triangle,
+            ));
+        }
+
+        #[cfg(feature = "dim2")]
+        if let Some(heightfield) = shape.as_heightfield() {
+            let segment = heightfield.cell_at_point(point)?;
+            return Self::from_feature(shape, point, FeatureId::Face(segment as u32));
+        }
+
+        #[cfg(feature = "dim3")]
+        if let Some(heightfield) = shape.as_heightfield() {
+            // The point can only be projected on one of the two triangles of its cell.
+            let (i, j) = heightfield.closest_cell_at_point(point);
+            let left = (j * heightfield.nrows() + i) as u32;
+            let right = left + (heightfield.nrows() * heightfield.ncols()) as u32;
+            return [left, right]
+                .into_iter()
+                .filter_map(|id| heightfield.triangle_at_id(id).map(|tri| (id, tri)))
+                .map(|(id, tri)| (tri.distance_to_local_point(point, true), id, tri))
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, id, tri)| Self::closest_on_triangle(&tri, point, id));
+        }
+
+        None
+    }
+
+    fn closest_on_triangle(tri: &Triangle, point: &Point<Real>, triangle: u32) -> Self {
+        let projection = tri.project_local_point(point, true);
+        #[cfg(feature = "dim2")]
+        let back_face = false;
+        #[cfg(feature = "dim3")]
+        let back_face = tri
+            .normal()
+            .map(|n| n.dot(&(point - projection.point)) < 0.0)
+            .unwrap_or(false);
+        Self::on_triangle(tri, &projection.point, triangle, back_face)
+    }
+
+    fn on_triangle(tri: &Triangle, point: &Point<Real>, triangle: u32, back_face: bool) -> Self {
+        let v0 = tri.b - tri.a;
+        let v1 = tri.c - tri.a;
+        let v2 = point - tri.a;
+        let d00 = v0.dot(&v0);
+        let d01 = v0.dot(&v1);
+        let d11 = v1.dot(&v1);
+        let d20 = v2.dot(&v0);
+        let d21 = v2.dot(&v1);
+        let denom = d00 * d11 - d01 * d01;
+
+        // Degenerate triangles are reduced to their first vertex.
+        let barycentric = if denom != 0.0 {
+            let v = (d11 * d20 - d01 * d21) / denom;
+            let w = (d00 * d21 - d01 * d20) / denom;
+            [1.0 - v - w, v, w]
+        } else {
+            [1.0, 0.0, 0.0]
+        };
+
+        Self {
+            triangle,
+            barycentric,
+            back_face,
+        }
+    }
+}
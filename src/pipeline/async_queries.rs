@@ -0,0 +1,191 @@
+use crate::geometry::{Collider, RayIntersection, Toi};
+use crate::math::{Real, Rot, Vect};
+use bevy::prelude::*;
+use rapier::prelude::{InteractionGroups, QueryFilterFlags};
+use std::collections::VecDeque;
+
+/// The identifier of a scene query enqueued in the [`AsyncQueryQueue`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct AsyncQueryId(pub u64);
+
+/// The geometry of a scene query enqueued in the [`AsyncQueryQueue`].
+#[derive(Clone, Debug)]
+pub enum AsyncQueryKind {
+    /// Find the closest intersection between a ray and the colliders.
+    ///
+    /// See [`RapierContext::cast_ray_and_get_normal`](crate::plugin::RapierContext::cast_ray_and_get_normal).
+    Ray {
+        /// The starting point of the ray.
+        origin: Vect,
+        /// The direction of the ray.
+        direction: Vect,
+        /// The maximum time-of-impact of the hit.
+        max_toi: Real,
+        /// Whether a hit at time 0.0 is reported if the ray starts inside of a collider.
+        solid: bool,
+    },
+    /// Find the first collider hit by a shape moving at a constant linear velocity.
+    ///
+    /// See [`RapierContext::cast_shape`](crate::plugin::RapierContext::cast_shape).
+    Shape {
+        /// The shape to cast.
+        shape: Collider,
+        /// The initial position of the shape.
+        position: Vect,
+        /// The rotation of the shape.
+        rotation: Rot,
+        /// The velocity of the shape.
+        velocity: Vect,
+        /// The maximum time-of-impact of the hit.
+        max_toi: Real,
+    },
+}
+
+/// A scene query enqueued in the [`AsyncQueryQueue`].
+#[derive(Clone, Debug)]
+pub struct AsyncQuery {
+    /// The entity the result of the query is reported to, in [`AsyncQueryResult::requester`].
+    pub requester: Entity,
+    /// The geometry of the query.
+    pub kind: AsyncQueryKind,
+    /// Flags for filtering-out some categories of entities from the query.
+    pub filter_flags: QueryFilterFlags,
+    /// Groups for filtering-out some colliders from the query.
+    pub filter_groups: Option<InteractionGroups>,
+    /// The collider of this entity is excluded from the query.
+    pub exclude_collider: Option<Entity>,
+    /// The colliders attached to the rigid-body of this entity are excluded from the query.
+    pub exclude_rigid_body: Option<Entity>,
+}
+
+impl AsyncQuery {
+    /// A ray cast reported to `requester`, without any filter.
+    pub fn ray(
+        requester: Entity,
+        origin: Vect,
+        direction: Vect,
+        max_toi: Real,
+        solid: bool,
+    ) -> Self {
+        Self::new(
+            requester,
+            AsyncQueryKind::Ray {
+                origin,
+                direction,
+                max_toi,
+                solid,
+            },
+        )
+    }
+
+    /// A shape cast reported to `requester`, without any filter.
+    pub fn shape(
+        requester: Entity,
+        shape: Collider,
+        position: Vect,
+        rotation: Rot,
+        velocity: Vect,
+        max_toi: Real,
+    ) -> Self {
+        Self::new(
+            requester,
+            AsyncQueryKind::Shape {
+                shape,
+                position,
+                rotation,
+                velocity,
+                max_toi,
+            },
+        )
+    }
+
+    fn new(requester: Entity, kind: AsyncQueryKind) -> Self {
+        Self {
+            requester,
+            kind,
+            filter_flags: QueryFilterFlags::default(),
+            filter_groups: None,
+            exclude_collider: None,
+            exclude_rigid_body: None,
+        }
+    }
+
+    /// Excludes the collider and the rigid-body of `entity` from this query.
+    pub fn excluding(mut self, entity: Entity) -> Self {
+        self.exclude_collider = Some(entity);
+        self.exclude_rigid_body = Some(entity);
+        self
+    }
+}
+
+/// The hit of a scene query resolved from the [`AsyncQueryQueue`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum AsyncQueryHit {
+    /// The result of an [`AsyncQueryKind::Ray`].
+    Ray(Option<(Entity, RayIntersection)>),
+    /// The result of an [`AsyncQueryKind::Shape`].
+    Shape(Option<(Entity, Toi)>),
+}
+
+/// Event emitted when a scene query of the [`AsyncQueryQueue`] is resolved.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AsyncQueryResult {
+    /// The identifier returned when the query was enqueued.
+    pub id: AsyncQueryId,
+    /// The entity the query was enqueued for.
+    pub requester: Entity,
+    /// The hit of the query.
+    pub hit: AsyncQueryHit,
+}
+
+/// A queue of scene queries resolved over several frames.
+///
+/// Systems enqueue ray and shape casts with [`Self::push`], and up to [`Self::budget`] of them are
+/// resolved after each simulation step, in the order they were enqueued. Their results are
+/// reported by [`AsyncQueryResult`] events. This amortizes the cost of a large number of queries
+/// (e.g. the perception of a crowd of agents) over several frames.
+#[derive(Clone, Debug)]
+pub struct AsyncQueryQueue {
+    /// The maximum number of queries resolved each frame.
+    pub budget: usize,
+    /// Whether the queries of a frame are resolved in parallel on the `ComputeTaskPool`.
+    pub parallel: bool,
+    pub(crate) pending: VecDeque<(AsyncQueryId, AsyncQuery)>,
+    next_id: u64,
+}
+
+impl Default for AsyncQueryQueue {
+    fn default() -> Self {
+        Self {
+            budget: 64,
+            parallel: false,
+            pending: VecDeque::new(),
+            next_id: 0,
+        }
+    }
+}
+
+impl AsyncQueryQueue {
+    /// Enqueues a scene query, and returns the identifier of its future [`AsyncQueryResult`].
+    pub fn push(&mut self, query: AsyncQuery) -> AsyncQueryId {
+        let id = AsyncQueryId(self.next_id);
+        self.next_id += 1;
+        self.pending.push_back((id, query));
+        id
+    }
+
+    /// The number of queries not resolved yet.
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Whether all the queries have been resolved.
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Removes all the queries not resolved yet.
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
@@ -0,0 +1,164 @@
+use crate::pipeline::CollisionEvent;
+use crate::plugin::RapierContext;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+/// Plugin routing the [`CollisionEvent`]s between the entities with an `A` component and the
+/// entities with a `B` component to [`CollisionStarted<A, B>`] and [`CollisionStopped<A, B>`]
+/// events.
+///
+/// A collider is matched if it has the marker component, or if the rigid-body it is attached to
+/// has it. The typed events report the entities with the marker components, always in the
+/// `A`, `B` order, whichever order the colliders are reported by the physics engine.
+///
+/// ```ignore
+/// app.add_plugin(CollisionEventRouter::<Player, Hazard>::default())
+///     .add_system(hurt_players);
+///
+/// fn hurt_players(mut events: EventReader<CollisionStarted<Player, Hazard>>) {
+///     for event in events.iter() {
+///         info!("Player {:?} touched hazard {:?}", event.a, event.b);
+///     }
+/// }
+/// ```
+///
+/// The colliders must have the `ActiveEvents::COLLISION_EVENTS` flag for the physics engine to
+/// report their collision events. A [`CollisionStopped<A, B>`] event is reported for each
+/// [`CollisionStarted<A, B>`] event, even if the colliders were despawned.
+pub struct CollisionEventRouter<A, B>(PhantomData<fn() -> (A, B)>);
+
+impl<A, B> Default for CollisionEventRouter<A, B> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<A: Component, B: Component> Plugin for CollisionEventRouter<A, B> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CollisionStarted<A, B>>()
+            .add_event::<CollisionStopped<A, B>>()
+            .add_system_to_stage(CoreStage::PostUpdate, route_collision_events::<A, B>);
+    }
+}
+
+macro_rules! typed_collision_event {
+    ($name: ident, $doc: literal) => {
+        #[doc = $doc]
+        pub struct $name<A, B> {
+            /// The entity with the `A` component: a collider, or the rigid-body it is attached to.
+            pub a: Entity,
+            /// The entity with the `B` component: a collider, or the rigid-body it is attached to.
+            pub b: Entity,
+            marker: PhantomData<fn() -> (A, B)>,
+        }
+
+        impl<A, B> $name<A, B> {
+            /// Creates a new event between `a`, with the `A` component, and `b`, with the `B`
+            /// component.
+            pub fn new(a: Entity, b: Entity) -> Self {
+                Self {
+                    a,
+                    b,
+                    marker: PhantomData,
+                }
+            }
+        }
+
+        impl<A, B> Copy for $name<A, B> {}
+
+        impl<A, B> Clone for $name<A, B> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<A, B> PartialEq for $name<A, B> {
+            fn eq(&self, other: &Self) -> bool {
+                (self.a, self.b) == (other.a, other.b)
+            }
+        }
+
+        impl<A, B> Eq for $name<A, B> {}
+
+        impl<A, B> fmt::Debug for $name<A, B> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("a", &self.a)
+                    .field("b", &self.b)
+                    .finish()
+            }
+        }
+    };
+}
+
+typed_collision_event!(
+    CollisionStarted,
+    "Event occurring when an entity with an `A` component starts colliding with an entity with a `B` component.\n\nSee [`CollisionEventRouter`]."
+);
+typed_collision_event!(
+    CollisionStopped,
+    "Event occurring when an entity with an `A` component stops colliding with an entity with a `B` component.\n\nSee [`CollisionEventRouter`]."
+);
+
+/// System responsible for routing the collision events of the colliders with `A` and `B` marker
+/// components, see [`CollisionEventRouter`].
+pub fn route_collision_events<A: Component, B: Component>(
+    context: Res<RapierContext>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut started_events: EventWriter<CollisionStarted<A, B>>,
+    mut stopped_events: EventWriter<CollisionStopped<A, B>>,
+    markers_a: Query<(), With<A>>,
+    markers_b: Query<(), With<B>>,
+    // The marked entities of the collider pairs reported as started, so they are reported as
+    // stopped even if the colliders or their marker components were removed in the meantime.
+    mut started_pairs: Local<HashMap<(Entity, Entity), (Entity, Entity)>>,
+) {
+    let pair_key = |collider1: Entity, collider2: Entity| {
+        if collider1 <= collider2 {
+            (collider1, collider2)
+        } else {
+            (collider2, collider1)
+        }
+    };
+
+    for event in collision_events.iter() {
+        match *event {
+            CollisionEvent::Started(collider1, collider2, ..) => {
+                let matched = [(collider1, collider2), (collider2, collider1)]
+                    .into_iter()
+                    .find_map(|(collider_a, collider_b)| {
+                        let a = marked_entity(&context, collider_a, &markers_a)?;
+                        let b = marked_entity(&context, collider_b, &markers_b)?;
+                        Some((a, b))
+                    });
+
+                if let Some((a, b)) = matched {
+                    started_pairs.insert(pair_key(collider1, collider2), (a, b));
+                    started_events.send(CollisionStarted::new(a, b));
+                }
+            }
+            CollisionEvent::Stopped(collider1, collider2, ..) => {
+                if let Some((a, b)) = started_pairs.remove(&pair_key(collider1, collider2)) {
+                    stopped_events.send(CollisionStopped::new(a, b));
+                }
+            }
+        }
+    }
+}
+
+/// The collider, or the rigid-body it is attached to, with the `T` marker component.
+fn marked_entity<T: Component>(
+    context: &RapierContext,
+    collider: Entity,
+    markers: &Query<(), With<T>>,
+) -> Option<Entity> {
+    if markers.contains(collider) {
+        Some(collider)
+    } else {
+        context
+            .collider_parent(collider)
+            .filter(|body| markers.contains(*body))
+    }
+}
@@ -0,0 +1,134 @@
+use crate::pipeline::CollisionEvent;
+use crate::plugin::RapierContext;
+use bevy::prelude::*;
+use std::fmt;
+
+type CollisionCallback = Box<dyn FnMut(&mut Commands, ObservedCollision) + Send + Sync>;
+
+/// A collision event observed by a [`CollisionObserver`].
+#[derive(Copy, Clone, Debug)]
+pub struct ObservedCollision<'a> {
+    /// The entity with the `CollisionObserver`: a collider, or the rigid-body it is attached to.
+    pub observer: Entity,
+    /// The collider the observer collided with.
+    pub other: Entity,
+    /// The observed collision event.
+    pub event: &'a CollisionEvent,
+}
+
+/// Callbacks invoked right after each simulation step with the collision events of this entity.
+///
+/// The callbacks are invoked with the events of the colliders attached to this entity, or of
+/// the collider this entity is. This is an alternative to matching the entities of all the
+/// [`CollisionEvent`]s in a system, for entity-specific logic like buttons and pickups:
+///
+/// ```ignore
+/// commands
+///     .spawn()
+///     .insert(Collider::ball(0.5))
+///     .insert(Sensor)
+///     .insert(CollisionObserver::on_started(|commands, collision| {
+///         commands.entity(collision.observer).despawn();
+///     }));
+/// ```
+///
+/// The colliders must have the `ActiveEvents::COLLISION_EVENTS` flag for the physics engine to
+/// report their collision events.
+#[derive(Component, Default)]
+pub struct CollisionObserver {
+    on_started: Option<CollisionCallback>,
+    on_stopped: Option<CollisionCallback>,
+}
+
+impl CollisionObserver {
+    /// An observer invoking `callback` when this entity starts colliding with a collider.
+    pub fn on_started(
+        callback: impl FnMut(&mut Commands, ObservedCollision) + Send + Sync + 'static,
+    ) -> Self {
+        Self::default().with_on_started(callback)
+    }
+
+    /// An observer invoking `callback` when this entity stops colliding with a collider.
+    pub fn on_stopped(
+        callback: impl FnMut(&mut Commands, ObservedCollision) + Send + Sync + 'static,
+    ) -> Self {
+        Self::default().with_on_stopped(callback)
+    }
+
+    /// Sets the callback invoked when this entity starts colliding with a collider.
+    pub fn with_on_started(
+        mut self,
+        callback: impl FnMut(&mut Commands, ObservedCollision) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_started = Some(Box::new(callback));
+        self
+    }
+
+    /// Sets the callback invoked when this entity stops colliding with a collider.
+    pub fn with_on_stopped(
+        mut self,
+        callback: impl FnMut(&mut Commands, ObservedCollision) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_stopped = Some(Box::new(callback));
+        self
+    }
+}
+
+impl fmt::Debug for CollisionObserver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CollisionObserver")
+            .field("on_started", &self.on_started.is_some())
+            .field("on_stopped", &self.on_stopped.is_some())
+            .finish()
+    }
+}
+
+/// System responsible for invoking the callbacks of the [`CollisionObserver`]s with the collision
+/// events of the last simulation step.
+pub fn run_collision_observers(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    mut collision_events: EventReader<CollisionEvent>,
+    mut observers: Query<&mut CollisionObserver>,
+) {
+    if observers.is_empty() {
+        // Skip the events, so they aren’t observed by observers added later on.
+        collision_events.clear();
+        return;
+    }
+
+    for event in collision_events.iter() {
+        let (collider1, collider2) = match *event {
+            CollisionEvent::Started(collider1, collider2, ..)
+            | CollisionEvent::Stopped(collider1, collider2, ..) => (collider1, collider2),
+        };
+
+        for (collider, other) in [(collider1, collider2), (collider2, collider1)] {
+            let body = context
+                .collider_parent(collider)
+                .filter(|body| *body != collider);
+
+            for observer in std::iter::once(collider).chain(body) {
+                let mut callbacks = match observers.get_mut(observer) {
+                    Ok(callbacks) => callbacks,
+                    Err(_) => continue,
+                };
+                let callback = match event {
+                    CollisionEvent::Started(..) => callbacks.on_started.as_mut(),
+                    CollisionEvent::Stopped(..) => callbacks.on_stopped.as_mut(),
+                };
+
+                if let Some(callback) = callback {
+                    callback(
+                        &mut commands,
+                        ObservedCollision {
+                            observer,
+                            other,
+                            event,
+                        },
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use crate::math::{Real, Vect};
+use crate::plugin::RapierContext;
+use bevy::prelude::Entity;
+use bevy::utils::HashMap;
+
+/// An entity touching another one, as reported by the [`ContactReport`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct TouchingEntity {
+    /// The touching collider.
+    pub entity: Entity,
+    /// The sum of the normal impulses applied by the contacts between both colliders during the
+    /// last simulation step.
+    pub normal_impulse: Real,
+    /// The average contact normal, pointing from the collider the contacts are reported for
+    /// toward the touching collider.
+    ///
+    /// The normals of the contacts are weighted by their impulses, unless no impulse was applied.
+    pub normal: Vect,
+}
+
+/// Resource listing, for each collider, the colliders it touches and the contacts between them.
+///
+/// This is a pull-based alternative to the contact events, for systems interested in the
+/// contacts of specific entities (is the player being crushed, what is the total load on this
+/// bridge, etc.)
+///
+/// This resource is opt-in: insert it into the app to have it rebuilt after each simulation step.
+/// Only the colliders with active contacts are listed.
+#[derive(Clone, Debug, Default)]
+pub struct ContactReport {
+    contacts: HashMap<Entity, Vec<TouchingEntity>>,
+}
+
+impl ContactReport {
+    /// The colliders touching the given collider.
+    pub fn contacts(&self, entity: Entity) -> &[TouchingEntity] {
+        self.contacts
+            .get(&entity)
+            .map(|contacts| &contacts[..])
+            .unwrap_or(&[])
+    }
+
+    /// The sum of the normal impulses applied to the given collider by all the colliders
+    /// touching it during the last simulation step.
+    pub fn total_normal_impulse(&self, entity: Entity) -> Real {
+        self.contacts(entity)
+            .iter()
+            .map(|touching| touching.normal_impulse)
+            .sum()
+    }
+
+    /// An iterator visiting all the colliders with active contacts, in arbitrary order.
+    pub fn iter(&self) -> impl Iterator<Item = (Entity, &[TouchingEntity])> {
+        self.contacts
+            .iter()
+            .map(|(entity, contacts)| (*entity, &contacts[..]))
+    }
+
+    /// Rebuilds the report from the contact pairs of the last simulation step.
+    pub fn rebuild(&mut self, context: &RapierContext) {
+        self.contacts.clear();
+
+        for pair in context.contact_pairs() {
+            if !pair.has_any_active_contacts() {
+                continue;
+            }
+
+            let mut normal_impulse = 0.0;
+            let mut weighted_normal = Vect::ZERO;
+            let mut normal = Vect::ZERO;
+
+            for manifold in pair.manifolds() {
+                let manifold_impulse: Real = manifold.points().map(|point| point.impulse()).sum();
+                normal_impulse += manifold_impulse;
+                weighted_normal += manifold.normal() * manifold_impulse;
+                normal += manifold.normal();
+            }
+
+            let normal = if normal_impulse > 0.0 {
+                weighted_normal.normalize_or_zero()
+            } else {
+                normal.normalize_or_zero()
+            };
+
+            self.contacts
+                .entry(pair.collider1())
+                .or_default()
+                .push(TouchingEntity {
+                    entity: pair.collider2(),
+                    normal_impulse,
+                    normal,
+                });
+            self.contacts
+                .entry(pair.collider2())
+                .or_default()
+                .push(TouchingEntity {
+                    entity: pair.collider1(),
+                    normal_impulse,
+                    normal: -normal,
+                });
+        }
+    }
+}
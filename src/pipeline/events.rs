@@ -1,15 +1,26 @@
+use crate::control::CharacterCollision;
+use crate::geometry::Group;
 use crate::math::{Real, Vect};
-use bevy::prelude::{Entity, EventWriter};
-use rapier::dynamics::RigidBodySet;
+use crate::plugin::ContactManifoldData;
+use bevy::prelude::{Entity, EventWriter, Transform};
+use rapier::dynamics::{
+    IslandManager, JointAxis, RigidBodyHandle, RigidBodySet, RigidBodyVelocity,
+};
 use rapier::geometry::{
-    ColliderHandle, ColliderSet, CollisionEvent as RapierCollisionEvent, CollisionEventFlags,
-    ContactForceEvent as RapierContactForceEvent, ContactPair,
+    Collider, ColliderHandle, ColliderSet, CollisionEvent as RapierCollisionEvent,
+    CollisionEventFlags, ContactForceEvent as RapierContactForceEvent, ContactPair,
 };
-use rapier::pipeline::EventHandler;
-use std::collections::HashMap;
+use rapier::math::{Point, Vector};
+use rapier::pipeline::{ActiveEvents, EventHandler};
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::RwLock;
 
 /// Events occurring when two colliders start or stop colliding
+///
+/// The events are reported in the order of the simulation steps, even when several steps are run
+/// in a single frame, so a contact starting and stopping during these steps reports both events.
+/// The step of each event is given by its [`CollisionEventDetails`].
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum CollisionEvent {
     /// Event occurring when two colliders start colliding
@@ -18,9 +29,115 @@ pub enum CollisionEvent {
     Stopped(Entity, Entity, CollisionEventFlags),
 }
 
+/// Events occurring when a collider enters or exits a sensor.
+///
+/// These are reported alongside the [`CollisionEvent`]s with the `CollisionEventFlags::SENSOR`
+/// flag, with the sensor first. If both colliders are sensors, an event is reported for each of
+/// them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SensorEvent {
+    /// Event occurring when a collider starts intersecting a sensor.
+    Entered {
+        /// The sensor collider.
+        sensor: Entity,
+        /// The collider entering the sensor.
+        other: Entity,
+    },
+    /// Event occurring when a collider stops intersecting a sensor.
+    Exited {
+        /// The sensor collider.
+        sensor: Entity,
+        /// The collider exiting the sensor.
+        other: Entity,
+    },
+}
+
+/// Event occurring when continuous collision detection stops a rigid-body at an impact
+/// during a simulation step.
+///
+/// This is reported during the step the impact happens in, while the contact itself is only
+/// reported by the next step, so fast projectiles can react at the exact impact location.
+///
+/// Only the rigid-bodies with a collider with `ActiveEvents::CCD_IMPACTS` report their impacts.
+/// With the `parallel` feature, the continuous collision detection of Rapier 0.16 never stops
+/// the rigid-bodies, so no impact is reported.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct CcdImpact {
+    /// The collider of the rigid-body with continuous collision detection enabled.
+    pub entity: Entity,
+    /// The collider it hit.
+    pub other: Entity,
+    /// The time of impact, in seconds since the beginning of the simulation step.
+    pub toi: Real,
+    /// The impact point, on the surface of `other`, in world-space.
+    pub point: Vect,
+    /// The outward normal of `other` at the impact point, in world-space.
+    pub normal: Vect,
+}
+
+/// Events occurring when a collider enters, stays in, or exits a `TriggerZone`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TriggerEvent {
+    /// Event occurring when a collider has been inside the zone for its `dwell_time`.
+    Entered {
+        /// The trigger zone.
+        zone: Entity,
+        /// The collider inside the zone.
+        entity: Entity,
+    },
+    /// Event occurring after each simulation step while an entered collider stays inside the zone.
+    Staying {
+        /// The trigger zone.
+        zone: Entity,
+        /// The collider inside the zone.
+        entity: Entity,
+        /// The time, in seconds, the collider has been inside the zone, dwell time included.
+        time_inside: f32,
+    },
+    /// Event occurring when an entered collider leaves the zone, or when the zone or the
+    /// collider are removed.
+    Exited {
+        /// The trigger zone.
+        zone: Entity,
+        /// The collider that was inside the zone.
+        entity: Entity,
+        /// The time, in seconds, the collider has been inside the zone, dwell time included.
+        time_inside: f32,
+    },
+}
+
+/// Details about a [`CollisionEvent`], sent alongside each of them, in the same order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionEventDetails {
+    /// The collision event.
+    pub event: CollisionEvent,
+    /// The index of the simulation step the event occurred in, see
+    /// [`RapierContext::step_index`](crate::plugin::RapierContext::step_index).
+    pub step: u64,
+    /// A snapshot of the contact between the two colliders, for a `CollisionEvent::Started`
+    /// involving a collider with `ActiveEvents::COLLISION_EVENT_CONTACTS` which is not a sensor.
+    pub contact: Option<CollisionContact>,
+}
+
+/// A snapshot of the contact between two colliders, when they start colliding.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CollisionContact {
+    /// The first contact manifold between the two colliders.
+    pub manifold: ContactManifoldData,
+    /// The penetration depth of the deepest contact point, zero if the colliders aren’t
+    /// penetrating.
+    pub penetration: Real,
+    /// The velocity of the rigid-body of the second collider relative to the rigid-body of the
+    /// first collider, at the deepest contact point.
+    pub relative_velocity: Vect,
+}
+
 /// Event occurring when the sum of the magnitudes of the contact forces
 /// between two colliders exceed a threshold.
-#[derive(Copy, Clone, Debug, PartialEq)]
+///
+/// The threshold is given by the [`ContactForceEventThresholds`] of the context if
+/// one applies to the two colliders, and by their `ContactForceEventThreshold` otherwise.
+#[derive(Clone, Debug, PartialEq)]
 pub struct ContactForceEvent {
     /// The first collider involved in the contact.
     pub collider1: Entity,
@@ -38,8 +155,373 @@ pub struct ContactForceEvent {
     pub max_force_direction: Vect,
     /// The magnitude of the largest force at a contact point of this contact pair.
     pub max_force_magnitude: Real,
+    /// The world-space contact point with the largest force.
+    pub max_force_point: Vect,
+    /// The sum of the magnitudes of the contact impulses applied between the two colliders
+    /// during the simulation step.
+    ///
+    /// This is `self.total_force_magnitude` multiplied by the length of the simulation step.
+    pub total_impulse_magnitude: Real,
+    /// The world-space points of all the contacts between the two colliders.
+    pub contact_points: Vec<Vect>,
+    /// The velocity of the rigid-body of `collider2` relative to the rigid-body of `collider1`,
+    /// at `max_force_point`, before the simulation step.
+    ///
+    /// This is the speed of the impact, before it was resolved by the contact forces.
+    pub relative_velocity: Vect,
+}
+
+/// Event occurring when two colliders hit each other, with one of them having `ImpactEvents`
+/// enabled.
+///
+/// These events are meant to drive sounds and particles, see `ImpactEvents` for how they are
+/// debounced.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ImpactEvent {
+    /// The two colliders hitting each other.
+    pub entities: [Entity; 2],
+    /// The world-space point of the impact, where the contact force is the strongest.
+    pub point: Vect,
+    /// The world-space (unit) normal of the impact, pointing from the first collider toward the
+    /// second one.
+    pub normal: Vect,
+    /// The sum of the magnitudes of the contact impulses of the impact.
+    pub impulse: Real,
+    /// The speed at which the colliders approach each other along the normal of the impact.
+    pub relative_speed: Real,
+    /// The `ImpactMaterial`s of the two colliders.
+    pub material_pair: [u32; 2],
+}
+
+/// Contact force event thresholds for specific pairs of colliders, overriding their
+/// `ContactForceEventThreshold`.
+///
+/// These are set in [`RapierContext::contact_force_event_thresholds`](crate::plugin::RapierContext::contact_force_event_thresholds).
+/// A threshold set for a pair of entities takes precedence over the thresholds set for pairs of
+/// collision groups, and the smallest threshold of the matching pairs of collision groups is used.
+///
+/// Like `ContactForceEventThreshold`, these thresholds only apply if at least one of the two
+/// colliders has `ActiveEvents::CONTACT_FORCE_EVENTS` enabled.
+#[derive(Clone, Debug, Default)]
+pub struct ContactForceEventThresholds {
+    pairs: HashMap<(Entity, Entity), Real>,
+    groups: Vec<(Group, Group, Real)>,
+}
+
+impl ContactForceEventThresholds {
+    fn pair_key(collider1: Entity, collider2: Entity) -> (Entity, Entity) {
+        if collider1 <= collider2 {
+            (collider1, collider2)
+        } else {
+            (collider2, collider1)
+        }
+    }
+
+    /// Sets the contact force event threshold between two colliders.
+    pub fn set_pair(&mut self, collider1: Entity, collider2: Entity, threshold: Real) {
+        self.pairs
+            .insert(Self::pair_key(collider1, collider2), threshold);
+    }
+
+    /// Removes the contact force event threshold between two colliders.
+    pub fn remove_pair(&mut self, collider1: Entity, collider2: Entity) -> Option<Real> {
+        self.pairs.remove(&Self::pair_key(collider1, collider2))
+    }
+
+    /// The contact force event threshold set between two colliders.
+    pub fn pair(&self, collider1: Entity, collider2: Entity) -> Option<Real> {
+        self.pairs
+            .get(&Self::pair_key(collider1, collider2))
+            .copied()
+    }
+
+    /// Sets the contact force event threshold between the colliders member of `groups1` and the
+    /// colliders member of `groups2`, according to their `CollisionGroups::memberships`.
+    pub fn set_groups(&mut self, groups1: Group, groups2: Group, threshold: Real) {
+        self.remove_groups(groups1, groups2);
+        self.groups.push((groups1, groups2, threshold));
+    }
+
+    /// Removes the contact force event threshold between two collision groups.
+    pub fn remove_groups(&mut self, groups1: Group, groups2: Group) -> Option<Real> {
+        let i = self.groups.iter().position(|(g1, g2, _)| {
+            (*g1, *g2) == (groups1, groups2) || (*g1, *g2) == (groups2, groups1)
+        })?;
+        Some(self.groups.remove(i).2)
+    }
+
+    /// The contact force event threshold set between two collision groups.
+    pub fn groups(&self, groups1: Group, groups2: Group) -> Option<Real> {
+        self.groups
+            .iter()
+            .find(|(g1, g2, _)| {
+                (*g1, *g2) == (groups1, groups2) || (*g1, *g2) == (groups2, groups1)
+            })
+            .map(|(_, _, threshold)| *threshold)
+    }
+
+    /// Whether no threshold is set.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty() && self.groups.is_empty()
+    }
+
+    /// Removes all the thresholds.
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+        self.groups.clear();
+    }
+
+    /// The threshold applying to a pair of colliders, given their collision group memberships.
+    pub(crate) fn threshold(
+        &self,
+        collider1: Entity,
+        memberships1: Group,
+        collider2: Entity,
+        memberships2: Group,
+    ) -> Option<Real> {
+        self.pair(collider1, collider2).or_else(|| {
+            self.groups
+                .iter()
+                .filter(|(g1, g2, _)| {
+                    (g1.intersects(memberships1) && g2.intersects(memberships2))
+                        || (g1.intersects(memberships2) && g2.intersects(memberships1))
+                })
+                .map(|(_, _, threshold)| *threshold)
+                .reduce(Real::min)
+        })
+    }
+
+    /// The smallest threshold that may apply to the pairs involving the given collider.
+    pub(crate) fn min_threshold(&self, collider: Entity, memberships: Group) -> Option<Real> {
+        let pairs = self
+            .pairs
+            .iter()
+            .filter(|((e1, e2), _)| *e1 == collider || *e2 == collider)
+            .map(|(_, threshold)| *threshold);
+        let groups = self
+            .groups
+            .iter()
+            .filter(|(g1, g2, _)| g1.intersects(memberships) || g2.intersects(memberships))
+            .map(|(_, _, threshold)| *threshold);
+        pairs.chain(groups).reduce(Real::min)
+    }
+}
+
+/// Event occurring when a rigid-body with a `DespawnWhenSleeping` component
+/// has been sleeping for long enough.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SleepTimeoutEvent {
+    /// The entity of the rigid-body.
+    ///
+    /// If the action of its `DespawnWhenSleeping` is `SleepTimeoutAction::Despawn`,
+    /// this entity no longer exists when the event is read.
+    pub entity: Entity,
+}
+
+/// Events occurring when the simulation islands change.
+///
+/// A simulation island is a group of dynamic rigid-bodies connected by active contacts or
+/// joints, which are woken up and put to sleep together. The islands are compared after each
+/// simulation step if the [`IslandTracker`] resource exists, and the rigid-bodies of each event
+/// are sorted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IslandEvent {
+    /// Event occurring when a new island appears, because a rigid-body was added or an
+    /// island was split.
+    Created {
+        /// The entities of the rigid-bodies of the island.
+        bodies: Vec<Entity>,
+    },
+    /// Event occurring when several islands are connected into a single one.
+    Merged {
+        /// The entities of the rigid-bodies of the resulting island.
+        bodies: Vec<Entity>,
+    },
+    /// Event occurring when all the rigid-bodies of an island fall asleep.
+    Sleeping {
+        /// The entities of the rigid-bodies of the island.
+        bodies: Vec<Entity>,
+    },
+}
+
+/// Resource tracking the simulation islands, to send the [`IslandEvent`]s.
+///
+/// This resource is opt-in: insert it into the app, e.g. with
+/// `app.init_resource::<IslandTracker>()`, to have the islands compared after each simulation
+/// step. Finding the islands visits all the dynamic rigid-bodies and active contacts.
+#[derive(Clone, Debug, Default)]
+pub struct IslandTracker {
+    pub(crate) next_id: u32,
+    pub(crate) island_of: HashMap<Entity, u32>,
+    pub(crate) sleeping: HashSet<u32>,
+}
+
+/// Event occurring when the rigid-body of an entity is removed from the physics world, because
+/// the entity was despawned or its `RigidBody` component was removed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodyRemoved {
+    /// The entity of the rigid-body. It may no longer exist when the event is read.
+    pub entity: Entity,
+    /// The last known world-space pose of the rigid-body.
+    pub transform: Transform,
+}
+
+/// Event occurring when the collider of an entity is removed from the physics world, because
+/// the entity was despawned or its `Collider` component was removed.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ColliderRemoved {
+    /// The entity of the collider. It may no longer exist when the event is read.
+    pub entity: Entity,
+    /// The last known world-space pose of the collider.
+    pub transform: Transform,
+}
+
+/// Event occurring when the joint of an entity is removed from the physics world, because
+/// the entity was despawned or its `ImpulseJoint` or `MultibodyJoint` component was removed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct JointRemoved {
+    /// The entity of the joint. It may no longer exist when the event is read.
+    pub entity: Entity,
+}
+
+/// The non-finite value of a rigid-body reported by a `PhysicsInvalidState` event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidStateField {
+    /// The position of the rigid-body.
+    Position,
+    /// The rotation of the rigid-body.
+    Rotation,
+    /// The linear velocity of the rigid-body.
+    LinearVelocity,
+    /// The angular velocity of the rigid-body.
+    AngularVelocity,
+}
+
+/// The likely cause of a `PhysicsInvalidState`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidStateCause {
+    /// The dynamic rigid-body has a zero, negative, or non-finite mass or angular inertia.
+    ZeroMass,
+    /// A collider attached to the rigid-body has a non-finite or empty shape (e.g. a ball with a
+    /// zero radius).
+    DegenerateCollider,
+    /// The `ExternalForce` or `ExternalImpulse` of the rigid-body is non-finite or exceeds
+    /// `InvalidStateDetection::max_force`.
+    HugeForce,
+    /// No likely cause was found.
+    Unknown,
+}
+
+/// Event occurring when a value of a rigid-body is no longer finite after a simulation step.
+///
+/// These events are sent when the `InvalidStateDetection` resource exists.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PhysicsInvalidState {
+    /// The entity of the rigid-body.
+    pub entity: Entity,
+    /// The non-finite value.
+    pub field: InvalidStateField,
+    /// The likely cause of the non-finite value.
+    pub cause: InvalidStateCause,
+}
+
+/// Event occurring when a rigid-body leaves the `PhysicsWorldBounds`, or
+/// when its position or velocity is no longer finite.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct OutOfBounds(pub Entity);
+
+/// Event occurring when the `ImpulseJoint` of an entity with a `BreakableJoint` breaks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointBroken {
+    /// The entity of the joint.
+    ///
+    /// If the action of its `BreakableJoint` is `JointBreakAction::Despawn`,
+    /// this entity no longer exists when the event is read.
+    pub entity: Entity,
+    /// The world-space linear impulse applied by the joint on the second rigid-body,
+    /// during the step it broke.
+    pub impulse: Vect,
+    /// The world-space angular impulse applied by the joint on the second rigid-body,
+    /// during the step it broke.
+    #[cfg(feature = "dim2")]
+    pub torque_impulse: Real,
+    /// The world-space angular impulse applied by the joint on the second rigid-body,
+    /// during the step it broke.
+    #[cfg(feature = "dim3")]
+    pub torque_impulse: Vect,
 }
 
+/// The side of a joint limit.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum JointLimitSide {
+    /// The lower bound of the limit.
+    Min,
+    /// The upper bound of the limit.
+    Max,
+}
+
+/// Event occurring when the `ImpulseJoint` of an entity with a `JointLimitEventThreshold`
+/// hits one of its limits.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct JointLimitReached {
+    /// The entity of the joint.
+    pub entity: Entity,
+    /// The limited axis of the joint.
+    pub axis: JointAxis,
+    /// The bound of the limit that was reached.
+    pub side: JointLimitSide,
+    /// The relative velocity along `axis` when the limit was hit.
+    ///
+    /// This is a linear velocity for linear axes, and an angular velocity for angular axes.
+    /// It is always positive.
+    pub velocity: Real,
+}
+
+/// The kind of surface hit by a `KinematicCharacterController`, depending on the angle between
+/// its normal and the `up` vector of the character.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum CharacterSurface {
+    /// A surface the character can stand on, i.e., no steeper than
+    /// `KinematicCharacterController::max_slope_climb_angle`.
+    Ground,
+    /// A surface facing downward, no steeper than
+    /// `KinematicCharacterController::max_slope_climb_angle` when upside-down.
+    Ceiling,
+    /// Any other surface.
+    Wall,
+}
+
+/// Events occurring when a `KinematicCharacterController` moves against colliders.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CharacterCollisionEvent {
+    /// Event occurring when the character hits a collider during its movement.
+    Hit {
+        /// The entity of the character.
+        character: Entity,
+        /// The collision between the character and the collider.
+        collision: CharacterCollision,
+        /// The kind of surface hit by the character.
+        surface: CharacterSurface,
+    },
+    /// Event occurring when the character starts touching a collider, i.e., the collider is hit or
+    /// stood on during a movement, but was not during the previous one.
+    Started(Entity, Entity),
+    /// Event occurring when the character stops touching a collider.
+    Stopped(Entity, Entity),
+}
+
+/// Event occurring when a `KinematicCharacterController` lands on the ground (`true`),
+/// or leaves it (`false`).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct GroundedChanged(pub Entity, pub bool);
+
+/// Event occurring when a `KinematicCharacterController` starts standing on a slope it can’t
+/// climb (`true`), or stops (`false`).
+///
+/// See `KinematicCharacterController::steep_slope_behavior`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SteepSlopeChanged(pub Entity, pub bool);
+
 // TODO: it may be more efficient to use crossbeam channel.
 // However crossbeam channels cause a Segfault (I have not
 // investigated how to reproduce this exactly to open an
@@ -50,7 +532,32 @@ pub(crate) struct EventQueue<'a> {
     // since the last physics step.
     pub deleted_colliders: &'a HashMap<ColliderHandle, Entity>,
     pub collision_events: RwLock<EventWriter<'a, 'a, CollisionEvent>>,
+    // The index of the current simulation step.
+    pub step_index: AtomicU64,
+    // The collision events of the current simulation step, sent at the end of the step.
+    pub step_collision_events: RwLock<Vec<CollisionEventDetails>>,
+    // The details of the collision events sent, waiting to be sent once the simulation is
+    // complete.
+    pub collision_event_details: RwLock<Vec<CollisionEventDetails>>,
     pub contact_force_events: RwLock<EventWriter<'a, 'a, ContactForceEvent>>,
+    // The sensor events, sent once the simulation step is complete.
+    pub sensor_events: RwLock<Vec<SensorEvent>>,
+    // The handles of the sensors that have been removed since the last physics update.
+    pub deleted_sensors: &'a HashSet<ColliderHandle>,
+    // The colliders with `ActiveEvents::COLLISION_EVENT_CONTACTS`.
+    pub collision_event_contacts: &'a HashSet<ColliderHandle>,
+    pub collision_event_filters: &'a HashMap<ColliderHandle, Group>,
+    pub contact_force_event_thresholds: &'a ContactForceEventThresholds,
+    // The original contact force event thresholds of the colliders with a threshold lowered
+    // during the simulation step, so Rapier reports the contact pairs
+    // `contact_force_event_thresholds` apply to.
+    pub original_contact_force_event_thresholds: HashMap<ColliderHandle, Real>,
+    // Whether any collider has contact force events enabled, so the velocities of the
+    // rigid-bodies are needed by the contact force events.
+    pub record_body_velocities: bool,
+    // The velocities and centers of mass of the moving rigid-bodies, before the current substep.
+    pub body_velocities: RwLock<HashMap<RigidBodyHandle, (RigidBodyVelocity, Point<Real>)>>,
+    pub physics_scale: Real,
 }
 
 impl<'a> EventQueue<'a> {
@@ -61,31 +568,238 @@ impl<'a> EventQueue<'a> {
             .or_else(|| self.deleted_colliders.get(&handle).copied())
             .expect("Internal error: entity not found for collision event.")
     }
+
+    fn is_sensor(&self, colliders: &ColliderSet, handle: ColliderHandle) -> bool {
+        colliders
+            .get(handle)
+            .map(|co| co.is_sensor())
+            .unwrap_or_else(|| self.deleted_sensors.contains(&handle))
+    }
+
+    // Whether one of the colliders reports collision events with the other one, according to
+    // their `CollisionEventFilter`s. The events involving a deleted collider are always reported,
+    // since its filter and collision groups are no longer known.
+    fn reports_collision_events(
+        &self,
+        colliders: &ColliderSet,
+        h1: ColliderHandle,
+        h2: ColliderHandle,
+    ) -> bool {
+        if self.collision_event_filters.is_empty() {
+            return true;
+        }
+
+        let (co1, co2) = match (colliders.get(h1), colliders.get(h2)) {
+            (Some(co1), Some(co2)) => (co1, co2),
+            _ => return true,
+        };
+
+        [(h1, co1, co2), (h2, co2, co1)]
+            .into_iter()
+            .any(|(handle, co, other)| {
+                if !co
+                    .active_events()
+                    .contains(rapier::pipeline::ActiveEvents::COLLISION_EVENTS)
+                {
+                    return false;
+                }
+
+                match self.collision_event_filters.get(&handle) {
+                    Some(filter) => {
+                        let memberships = other.collision_groups().memberships.bits();
+                        filter.bits() & memberships != 0
+                    }
+                    None => true,
+                }
+            })
+    }
+
+    fn push_sensor_events(
+        &self,
+        colliders: &ColliderSet,
+        (h1, e1): (ColliderHandle, Entity),
+        (h2, e2): (ColliderHandle, Entity),
+        started: bool,
+    ) {
+        if let Ok(mut events) = self.sensor_events.write() {
+            for (sensor_handle, sensor, other) in [(h1, e1, e2), (h2, e2, e1)] {
+                if self.is_sensor(colliders, sensor_handle) {
+                    events.push(if started {
+                        SensorEvent::Entered { sensor, other }
+                    } else {
+                        SensorEvent::Exited { sensor, other }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Prepares the queue for the simulation step `step_index`.
+    pub fn begin_step(&self, step_index: u64, islands: &IslandManager, bodies: &RigidBodySet) {
+        self.step_index.store(step_index, Ordering::Relaxed);
+        self.record_body_velocities(islands, bodies);
+    }
+
+    /// Sends the collision events of the simulation step, once it is complete.
+    pub fn end_step(&self) {
+        if let (Ok(mut step_events), Ok(mut events), Ok(mut details)) = (
+            self.step_collision_events.write(),
+            self.collision_events.write(),
+            self.collision_event_details.write(),
+        ) {
+            events.send_batch(step_events.iter().map(|details| details.event));
+            details.append(&mut step_events);
+        }
+    }
+
+    /// Records the velocities of the moving rigid-bodies, before a simulation substep.
+    fn record_body_velocities(&self, islands: &IslandManager, bodies: &RigidBodySet) {
+        if !self.record_body_velocities {
+            return;
+        }
+
+        if let Ok(mut velocities) = self.body_velocities.write() {
+            velocities.clear();
+            let moving_bodies = islands
+                .active_dynamic_bodies()
+                .iter()
+                .chain(islands.active_kinematic_bodies());
+            for handle in moving_bodies {
+                if let Some(body) = bodies.get(*handle) {
+                    #[cfg(feature = "dim2")]
+                    let angvel = body.angvel();
+                    #[cfg(feature = "dim3")]
+                    let angvel = *body.angvel();
+                    let velocity = RigidBodyVelocity {
+                        linvel: *body.linvel(),
+                        angvel,
+                    };
+                    let world_com = body.position() * body.mass_properties().local_com;
+                    velocities.insert(*handle, (velocity, world_com));
+                }
+            }
+        }
+    }
+
+    fn collision_contact(
+        &self,
+        bodies: &RigidBodySet,
+        colliders: &ColliderSet,
+        contact_pair: &ContactPair,
+    ) -> Option<CollisionContact> {
+        let co1 = colliders.get(contact_pair.collider1)?;
+        let co2 = colliders.get(contact_pair.collider2)?;
+        let manifold = contact_pair
+            .manifolds
+            .iter()
+            .find(|manifold| !manifold.points.is_empty())?;
+        let deepest = manifold.find_deepest_contact()?;
+        let pos1 = match manifold.subshape_pos1 {
+            Some(subshape_pos1) => co1.position() * subshape_pos1,
+            None => *co1.position(),
+        };
+        let pos2 = match manifold.subshape_pos2 {
+            Some(subshape_pos2) => co2.position() * subshape_pos2,
+            None => *co2.position(),
+        };
+
+        let point = pos1 * deepest.local_p1 + manifold.data.normal * deepest.dist / 2.0;
+        let velocity_at_point = |collider: &Collider| {
+            collider
+                .parent()
+                .and_then(|handle| bodies.get(handle))
+                .map(|body| body.velocity_at_point(&point))
+                .unwrap_or_else(Vector::zeros)
+        };
+        let relative_velocity = velocity_at_point(co2) - velocity_at_point(co1);
+
+        Some(CollisionContact {
+            manifold: ContactManifoldData::from_rapier(
+                manifold,
+                &pos1,
+                &pos2,
+                bodies,
+                self.physics_scale,
+            ),
+            penetration: (-deepest.dist).max(0.0) * self.physics_scale,
+            relative_velocity: Vect::from(relative_velocity) * self.physics_scale,
+        })
+    }
+
+    fn contact_force_event_threshold(&self, handle: ColliderHandle, collider: &Collider) -> Real {
+        if collider
+            .active_events()
+            .contains(ActiveEvents::CONTACT_FORCE_EVENTS)
+        {
+            self.original_contact_force_event_thresholds
+                .get(&handle)
+                .copied()
+                .unwrap_or_else(|| collider.contact_force_event_threshold())
+        } else {
+            Real::MAX
+        }
+    }
+
+    fn velocity_at_point(&self, collider: &Collider, point: &Point<Real>) -> Vector<Real> {
+        collider
+            .parent()
+            .and_then(|handle| {
+                let velocities = self.body_velocities.read().ok()?;
+                let (velocity, world_com) = velocities.get(&handle)?;
+                Some(velocity.velocity_at_point(point, world_com))
+            })
+            .unwrap_or_else(Vector::zeros)
+    }
 }
 
 impl<'a> EventHandler for EventQueue<'a> {
     fn handle_collision_event(
         &self,
-        _bodies: &RigidBodySet,
+        bodies: &RigidBodySet,
         colliders: &ColliderSet,
         event: RapierCollisionEvent,
-        _: Option<&ContactPair>,
+        contact_pair: Option<&ContactPair>,
     ) {
+        if !self.reports_collision_events(colliders, event.collider1(), event.collider2()) {
+            return;
+        }
+
+        let step = self.step_index.load(Ordering::Relaxed);
         let event = match event {
             RapierCollisionEvent::Started(h1, h2, flags) => {
                 let e1 = self.collider2entity(colliders, h1);
                 let e2 = self.collider2entity(colliders, h2);
-                CollisionEvent::Started(e1, e2, flags)
+                let contact = contact_pair
+                    .filter(|_| {
+                        self.collision_event_contacts.contains(&h1)
+                            || self.collision_event_contacts.contains(&h2)
+                    })
+                    .and_then(|pair| self.collision_contact(bodies, colliders, pair));
+                if flags.contains(CollisionEventFlags::SENSOR) {
+                    self.push_sensor_events(colliders, (h1, e1), (h2, e2), true);
+                }
+                CollisionEventDetails {
+                    event: CollisionEvent::Started(e1, e2, flags),
+                    step,
+                    contact,
+                }
             }
             RapierCollisionEvent::Stopped(h1, h2, flags) => {
                 let e1 = self.collider2entity(colliders, h1);
                 let e2 = self.collider2entity(colliders, h2);
-                CollisionEvent::Stopped(e1, e2, flags)
+                if flags.contains(CollisionEventFlags::SENSOR) {
+                    self.push_sensor_events(colliders, (h1, e1), (h2, e2), false);
+                }
+                CollisionEventDetails {
+                    event: CollisionEvent::Stopped(e1, e2, flags),
+                    step,
+                    contact: None,
+                }
             }
         };
 
-        if let Ok(mut events) = self.collision_events.write() {
-            events.send(event)
+        if let Ok(mut events) = self.step_collision_events.write() {
+            events.push(event)
         }
     }
 
@@ -97,15 +811,78 @@ impl<'a> EventHandler for EventQueue<'a> {
         contact_pair: &ContactPair,
         total_force_magnitude: Real,
     ) {
+        let (co1, co2) = match (
+            colliders.get(contact_pair.collider1),
+            colliders.get(contact_pair.collider2),
+        ) {
+            (Some(co1), Some(co2)) => (co1, co2),
+            _ => return,
+        };
+        let collider1 = self.collider2entity(colliders, contact_pair.collider1);
+        let collider2 = self.collider2entity(colliders, contact_pair.collider2);
+
+        let threshold = self
+            .contact_force_event_thresholds
+            .threshold(
+                collider1,
+                Group::from_bits_truncate(co1.collision_groups().memberships.bits()),
+                collider2,
+                Group::from_bits_truncate(co2.collision_groups().memberships.bits()),
+            )
+            .unwrap_or_else(|| {
+                self.contact_force_event_threshold(contact_pair.collider1, co1)
+                    .min(self.contact_force_event_threshold(contact_pair.collider2, co2))
+            });
+
+        // NOTE: the strict inequality matches the one used by Rapier.
+        if total_force_magnitude <= threshold {
+            return;
+        }
+
         let rapier_event =
             RapierContactForceEvent::from_contact_pair(dt, contact_pair, total_force_magnitude);
+
+        let mut contact_points = vec![];
+        let mut max_force_point = None;
+        let mut max_impulse = -1.0;
+        for manifold in &contact_pair.manifolds {
+            let pos1 = match manifold.subshape_pos1 {
+                Some(subshape_pos1) => co1.position() * subshape_pos1,
+                None => *co1.position(),
+            };
+
+            for contact in &manifold.points {
+                if contact.data.impulse > max_impulse {
+                    max_impulse = contact.data.impulse;
+                    max_force_point =
+                        Some(pos1 * contact.local_p1 + manifold.data.normal * contact.dist / 2.0);
+                }
+            }
+
+            contact_points.extend(
+                manifold
+                    .data
+                    .solver_contacts
+                    .iter()
+                    .map(|contact| Vect::from(contact.point.coords) * self.physics_scale),
+            );
+        }
+
+        let max_force_point = max_force_point.unwrap_or_else(|| co1.position() * Point::origin());
+        let relative_velocity = self.velocity_at_point(co2, &max_force_point)
+            - self.velocity_at_point(co1, &max_force_point);
+
         let event = ContactForceEvent {
-            collider1: self.collider2entity(colliders, rapier_event.collider1),
-            collider2: self.collider2entity(colliders, rapier_event.collider2),
+            collider1,
+            collider2,
             total_force: rapier_event.total_force.into(),
             total_force_magnitude: rapier_event.total_force_magnitude,
             max_force_direction: rapier_event.max_force_direction.into(),
             max_force_magnitude: rapier_event.max_force_magnitude,
+            max_force_point: Vect::from(max_force_point.coords) * self.physics_scale,
+            total_impulse_magnitude: total_force_magnitude * dt,
+            contact_points,
+            relative_velocity: Vect::from(relative_velocity) * self.physics_scale,
         };
 
         if let Ok(mut events) = self.contact_force_events.write() {
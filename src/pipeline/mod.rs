@@ -1,12 +1,35 @@
+pub use self::async_queries::{
+    AsyncQuery, AsyncQueryHit, AsyncQueryId, AsyncQueryKind, AsyncQueryQueue, AsyncQueryResult,
+};
+pub use self::collision_event_router::{
+    route_collision_events, CollisionEventRouter, CollisionStarted, CollisionStopped,
+};
+pub use self::collision_observer::{run_collision_observers, CollisionObserver, ObservedCollision};
+pub use self::contact_report::{ContactReport, TouchingEntity};
 pub(crate) use self::events::EventQueue;
-pub use self::events::{CollisionEvent, ContactForceEvent};
+pub use self::events::{
+    BodyRemoved, CcdImpact, CharacterCollisionEvent, CharacterSurface, ColliderRemoved,
+    CollisionContact, CollisionEvent, CollisionEventDetails, ContactForceEvent,
+    ContactForceEventThresholds, GroundedChanged, ImpactEvent, InvalidStateCause,
+    InvalidStateField, IslandEvent, IslandTracker, JointBroken, JointLimitReached, JointLimitSide,
+    JointRemoved, OutOfBounds, PhysicsInvalidState, SensorEvent, SleepTimeoutEvent,
+    SteepSlopeChanged, TriggerEvent,
+};
 pub(crate) use self::physics_hooks::PhysicsHooksWithQueryInstance;
 pub use self::physics_hooks::{
-    ContactModificationContextView, PairFilterContextView, PhysicsHooksWithQuery,
-    PhysicsHooksWithQueryResource,
+    ContactModificationContextView, ContactModifiers, ContactModifiersData, PairFilterContextView,
+    PhysicsHooksWithQuery, PhysicsHooksWithQueryResource,
 };
-pub use query_filter::{InteractionGroups, QueryFilter, QueryFilterFlags};
+pub use query_filter::{EntitySet, InteractionGroups, QueryFilter, QueryFilterFlags};
+pub use ray_caster::{RayCastMode, RayCaster, RayHits};
+pub use shape_caster::{ShapeCastMode, ShapeCaster, ShapeHits};
 
+mod async_queries;
+mod collision_event_router;
+mod collision_observer;
+mod contact_report;
 mod events;
 mod physics_hooks;
 mod query_filter;
+mod ray_caster;
+mod shape_caster;
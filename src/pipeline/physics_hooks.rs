@@ -1,6 +1,12 @@
+use crate::geometry::{
+    AppliedMaterial, FrictionOverrides, OneWayCollider, OneWayPlatform, PhysicsMaterials,
+    SurfaceVelocity,
+};
+use crate::math::{Real, Vect};
 use bevy::ecs::query::WorldQuery;
 use bevy::prelude::*;
 use rapier::geometry::SolverFlags;
+use rapier::math::Vector;
 use rapier::pipeline::{ContactModificationContext, PairFilterContext, PhysicsHooks};
 
 /// Read-only access to the properties of a collision pair filter context.
@@ -45,6 +51,8 @@ impl<'a> PairFilterContextView<'a> {
 pub struct ContactModificationContextView<'a, 'b> {
     /// The raw context from Rapier.
     pub raw: &'a mut ContactModificationContext<'b>,
+    pub(crate) physics_scale: Real,
+    pub(crate) physics_materials: Option<&'a PhysicsMaterials>,
 }
 
 impl<'a, 'b> ContactModificationContextView<'a, 'b> {
@@ -77,6 +85,72 @@ impl<'a, 'b> ContactModificationContextView<'a, 'b> {
             Entity::from_bits(co2.user_data as u64)
         })
     }
+
+    /// The [`PhysicsMaterials`] resource, if it exists.
+    pub fn physics_materials(&self) -> Option<&PhysicsMaterials> {
+        self.physics_materials
+    }
+
+    /// Updates the contacts to emulate the given one-way platform, attached to the first collider
+    /// if `platform_is_collider1` is `true`, or to the second collider otherwise.
+    ///
+    /// This must be called by `PhysicsHooksWithQuery::modify_solver_contacts` at each timestep,
+    /// for each contact manifold involving the platform, whose collider must have the
+    /// `ActiveHooks::MODIFY_SOLVER_CONTACTS` flag.
+    pub fn update_as_one_way_platform(
+        &mut self,
+        platform: &OneWayPlatform,
+        platform_is_collider1: bool,
+    ) {
+        let direction: Vector<Real> = platform.direction.into();
+        let allowed_local_n1 = if platform_is_collider1 {
+            direction
+        } else {
+            // Express the direction of the platform in the local-space of the first collider.
+            let co1 = &self.raw.colliders[self.raw.collider1];
+            let co2 = &self.raw.colliders[self.raw.collider2];
+            -co1.rotation()
+                .inverse_transform_vector(&(co2.rotation() * direction))
+        };
+        self.raw
+            .update_as_oneway_platform(&allowed_local_n1, platform.allowed_angle);
+    }
+
+    /// Sets the friction coefficient of all the contacts of this manifold.
+    pub fn set_friction(&mut self, friction: Real) {
+        for contact in self.raw.solver_contacts.iter_mut() {
+            contact.friction = friction;
+        }
+    }
+
+    /// Sets the restitution coefficient of all the contacts of this manifold.
+    pub fn set_restitution(&mut self, restitution: Real) {
+        for contact in self.raw.solver_contacts.iter_mut() {
+            contact.restitution = restitution;
+        }
+    }
+
+    /// Adds the given world-space velocity to the surface of the first collider if
+    /// `surface_is_collider1` is `true`, or of the second collider otherwise.
+    ///
+    /// The other collider is dragged along by friction, as if this surface was moving.
+    /// Only the component of the velocity tangent to the contacts is taken into account.
+    pub fn add_surface_velocity(&mut self, velocity: Vect, surface_is_collider1: bool) {
+        let velocity: Vector<Real> = (velocity / self.physics_scale).into();
+        let normal = *self.raw.normal;
+        let tangent_velocity = velocity - normal * normal.dot(&velocity);
+        // The solver drives the velocity of the first collider, relative to the second one,
+        // toward the opposite of the tangent velocity.
+        let tangent_velocity = if surface_is_collider1 {
+            tangent_velocity
+        } else {
+            -tangent_velocity
+        };
+
+        for contact in self.raw.solver_contacts.iter_mut() {
+            contact.tangent_velocity += tangent_velocity;
+        }
+    }
 }
 
 /// User-defined functions called by the physics engines during one timestep in order to customize its behavior.
@@ -200,6 +274,122 @@ where
     }
 }
 
+/// The components of the colliders read by the [`ContactModifiers`] physics hooks.
+pub type ContactModifiersData = (
+    Option<&'static OneWayPlatform>,
+    Option<&'static SurfaceVelocity>,
+    Option<&'static FrictionOverrides>,
+    Option<&'static AppliedMaterial>,
+);
+
+/// Physics hooks modifying the contacts of the colliders with a [`OneWayPlatform`],
+/// [`SurfaceVelocity`], [`FrictionOverrides`] or [`AppliedMaterial`] component.
+///
+/// The friction and restitution of the contacts between two colliders with an [`AppliedMaterial`]
+/// are overridden by the response to their pair of materials in the [`PhysicsMaterials`]
+/// resource, if any. The [`FrictionOverrides`] take precedence over this response.
+///
+/// The colliders with these components must have the `ActiveHooks::MODIFY_SOLVER_CONTACTS` flag.
+/// These hooks are set up with:
+///
+/// ```ignore
+/// app.add_plugin(RapierPhysicsPlugin::<ContactModifiersData>::default())
+///     .insert_resource(PhysicsHooksWithQueryResource(Box::new(ContactModifiers)));
+/// ```
+///
+/// Custom physics hooks can apply the same modifications, along with their own, by querying
+/// [`ContactModifiersData`] as part of their user data and calling [`ContactModifiers::apply`].
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ContactModifiers;
+
+impl ContactModifiers {
+    /// Applies the contact modifications described by the components of the two colliders
+    /// involved in the contact.
+    pub fn apply(
+        context: &mut ContactModificationContextView,
+        (platform1, surface1, frictions1, material1): (
+            Option<&OneWayPlatform>,
+            Option<&SurfaceVelocity>,
+            Option<&FrictionOverrides>,
+            Option<&AppliedMaterial>,
+        ),
+        (platform2, surface2, frictions2, material2): (
+            Option<&OneWayPlatform>,
+            Option<&SurfaceVelocity>,
+            Option<&FrictionOverrides>,
+            Option<&AppliedMaterial>,
+        ),
+    ) {
+        let response = match (context.physics_materials, material1, material2) {
+            (Some(materials), Some(material1), Some(material2)) => {
+                materials.get(&material1.0, &material2.0).copied()
+            }
+            _ => None,
+        };
+        if let Some(response) = response {
+            if let Some(friction) = response.friction {
+                context.set_friction(friction);
+            }
+            if let Some(restitution) = response.restitution {
+                context.set_restitution(restitution);
+            }
+        }
+
+        let collider1 = context.collider1();
+        let collider2 = context.collider2();
+        let friction = frictions1
+            .and_then(|frictions| frictions.0.get(&collider2))
+            .or_else(|| frictions2.and_then(|frictions| frictions.0.get(&collider1)));
+        if let Some(friction) = friction {
+            context.set_friction(*friction);
+        }
+
+        if let Some(surface) = surface1 {
+            let rotation = context.raw.colliders[context.raw.collider1].rotation();
+            let velocity: Vector<Real> = surface.0.into();
+            context.add_surface_velocity((rotation * velocity).into(), true);
+        }
+        if let Some(surface) = surface2 {
+            let rotation = context.raw.colliders[context.raw.collider2].rotation();
+            let velocity: Vector<Real> = surface.0.into();
+            context.add_surface_velocity((rotation * velocity).into(), false);
+        }
+
+        if let Some(platform) = platform1 {
+            context.update_as_one_way_platform(platform, true);
+        }
+        if let Some(platform) = platform2 {
+            context.update_as_one_way_platform(platform, false);
+        }
+    }
+}
+
+impl PhysicsHooksWithQuery<ContactModifiersData> for ContactModifiers {
+    fn modify_solver_contacts(
+        &self,
+        mut context: ContactModificationContextView,
+        user_data: &Query<ContactModifiersData>,
+    ) {
+        let none = (None, None, None, None);
+        let mut data1 = user_data.get(context.collider1()).unwrap_or(none);
+        let mut data2 = user_data.get(context.collider2()).unwrap_or(none);
+
+        // The colliders without material use the one of their rigid-body.
+        let body_material = |body: Option<Entity>| {
+            body.and_then(|body| user_data.get(body).ok())
+                .and_then(|(.., material)| material)
+        };
+        if data1.3.is_none() {
+            data1.3 = body_material(context.rigid_body1());
+        }
+        if data2.3.is_none() {
+            data2.3 = body_material(context.rigid_body2());
+        }
+
+        Self::apply(&mut context, data1, data2);
+    }
+}
+
 /// Resource containing the user-defined physics hooks.
 pub struct PhysicsHooksWithQueryResource<UserData: WorldQuery>(
     pub Box<dyn PhysicsHooksWithQuery<UserData>>,
@@ -208,7 +398,10 @@ pub struct PhysicsHooksWithQueryResource<UserData: WorldQuery>(
 pub(crate) struct PhysicsHooksWithQueryInstance<'world, 'state, 'b, UserData: WorldQuery> {
     // pub commands: Commands<'world, 'state>,
     pub user_data: Query<'world, 'state, UserData>,
+    pub one_way_colliders: Query<'world, 'state, &'static OneWayCollider>,
     pub hooks: &'b dyn PhysicsHooksWithQuery<UserData>,
+    pub physics_scale: Real,
+    pub physics_materials: Option<&'b PhysicsMaterials>,
 }
 
 impl<UserData: WorldQuery> PhysicsHooks for PhysicsHooksWithQueryInstance<'_, '_, '_, UserData> {
@@ -225,7 +418,24 @@ impl<UserData: WorldQuery> PhysicsHooks for PhysicsHooksWithQueryInstance<'_, '_
     }
 
     fn modify_solver_contacts(&self, context: &mut ContactModificationContext) {
-        let context_view = ContactModificationContextView { raw: context };
+        let mut context_view = ContactModificationContextView {
+            raw: context,
+            physics_scale: self.physics_scale,
+            physics_materials: self.physics_materials,
+        };
+
+        if !self.one_way_colliders.is_empty() {
+            let colliders = [
+                (context_view.collider1(), true),
+                (context_view.collider2(), false),
+            ];
+            for (collider, is_collider1) in colliders {
+                if let Ok(one_way) = self.one_way_colliders.get(collider) {
+                    context_view.update_as_one_way_platform(&(*one_way).into(), is_collider1);
+                }
+            }
+        }
+
         self.hooks
             .modify_solver_contacts(context_view, &self.user_data)
     }
@@ -1,8 +1,25 @@
-use bevy::prelude::Entity;
+use bevy::ecs::query::WorldQuery;
+use bevy::prelude::{Entity, Query};
 
 pub use rapier::geometry::InteractionGroups;
 pub use rapier::pipeline::QueryFilterFlags;
 
+/// A set of entities used to filter the colliders taken into account by a scene query.
+///
+/// This is implemented for Bevy queries: for example, a `Query<(), (With<Enemy>, Without<Dead>)>`
+/// only includes the colliders attached to an entity with an `Enemy` component and no `Dead`
+/// component.
+pub trait EntitySet {
+    /// Does this set contain the given entity?
+    fn contains(&self, entity: Entity) -> bool;
+}
+
+impl<'w, 's, Q: WorldQuery, F: WorldQuery> EntitySet for Query<'w, 's, Q, F> {
+    fn contains(&self, entity: Entity) -> bool {
+        Query::contains(self, entity)
+    }
+}
+
 /// A filter tha describes what collider should be included or excluded from a scene query.
 #[derive(Copy, Clone, Default)]
 pub struct QueryFilter<'a> {
@@ -18,6 +35,8 @@ pub struct QueryFilter<'a> {
     pub exclude_rigid_body: Option<Entity>,
     /// If set, any collider for which this closure returns false.
     pub predicate: Option<&'a dyn Fn(Entity) -> bool>,
+    /// If set, any collider attached to an entity not in this set will be excluded by the query.
+    pub entities: Option<&'a dyn EntitySet>,
 }
 
 impl<'a> From<QueryFilterFlags> for QueryFilter<'a> {
@@ -111,4 +130,24 @@ impl<'a> QueryFilter<'a> {
         self.predicate = Some(predicate);
         self
     }
+
+    /// Only include the colliders attached to an entity matching the given query, e.g.,
+    /// `Query<(), With<Enemy>>`.
+    pub fn with_query(mut self, query: &'a impl EntitySet) -> Self {
+        self.entities = Some(query);
+        self
+    }
+
+    /// Does this filter have a per-entity test, i.e., a predicate or an entity set?
+    pub(crate) fn has_entity_test(&self) -> bool {
+        self.predicate.is_some() || self.entities.is_some()
+    }
+
+    /// Tests the entity of a collider against the predicate and the entity set of this filter.
+    pub(crate) fn test_entity(&self, entity: Entity) -> bool {
+        self.entities
+            .map(|set| set.contains(entity))
+            .unwrap_or(true)
+            && self.predicate.map(|f| f(entity)).unwrap_or(true)
+    }
 }
@@ -0,0 +1,94 @@
+use crate::geometry::RayIntersection;
+use crate::math::{Real, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
+use rapier::prelude::{InteractionGroups, QueryFilterFlags};
+
+/// The hits reported by a [`RayCaster`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum RayCastMode {
+    /// Only the closest hit is reported.
+    #[default]
+    First,
+    /// All the hits are reported, sorted by increasing time-of-impact.
+    All {
+        /// If set, only this number of closest hits are reported.
+        max_hits: Option<usize>,
+    },
+}
+
+/// A ray cast from the entity of this component after each simulation step.
+///
+/// The ray follows the position of the rigid-body attached to the entity, or its
+/// `GlobalTransform` if it has no rigid-body. The collider and the rigid-body attached to the
+/// entity are excluded from the ray cast. Its hits are written to the [`RayHits`] component, which
+/// is automatically added to the entity.
+//
+// NOTE: Rapier’s query filters aren’t reflectable, so this component is reflected as an opaque
+//       value.
+#[derive(Copy, Clone, Debug, PartialEq, Component, Reflect, FromReflect)]
+#[reflect_value(Component, PartialEq)]
+pub struct RayCaster {
+    /// The origin of the ray, relative to the entity.
+    pub origin: Vect,
+    /// The direction of the ray, relative to the entity.
+    pub direction: Vect,
+    /// The maximum time-of-impact of the hits. This limits the length of the ray to
+    /// `direction.length() * max_toi`.
+    pub max_toi: Real,
+    /// If `true`, a hit at time 0.0 is reported if the ray starts inside of a collider. If `false`,
+    /// the ray hits the boundary of the collider instead.
+    pub solid: bool,
+    /// The hits reported by this ray cast.
+    pub mode: RayCastMode,
+    /// Flags for filtering-out some categories of entities from the ray cast.
+    pub filter_flags: QueryFilterFlags,
+    /// Groups for filtering-out some colliders from the ray cast.
+    pub filter_groups: Option<InteractionGroups>,
+}
+
+impl Default for RayCaster {
+    fn default() -> Self {
+        Self {
+            origin: Vect::ZERO,
+            #[cfg(feature = "dim2")]
+            direction: Vect::X,
+            #[cfg(feature = "dim3")]
+            direction: -Vect::Z,
+            max_toi: Real::MAX,
+            solid: true,
+            mode: RayCastMode::First,
+            filter_flags: QueryFilterFlags::default(),
+            filter_groups: None,
+        }
+    }
+}
+
+/// The hits of a [`RayCaster`] during the last simulation step, sorted by increasing
+/// time-of-impact.
+///
+/// The intersection points and normals are expressed in world-space.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect_value(Component, MapEntities, PartialEq)]
+pub struct RayHits(pub Vec<(Entity, RayIntersection)>);
+
+impl RayHits {
+    /// The closest hit, if any.
+    pub fn first(&self) -> Option<&(Entity, RayIntersection)> {
+        self.0.first()
+    }
+}
+
+impl MapEntities for RayHits {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for (entity, _) in &mut self.0 {
+            if let Ok(mapped) = entity_map.get(*entity) {
+                *entity = mapped;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,101 @@
+use crate::geometry::{Collider, Toi};
+use crate::math::{Real, Rot, Vect};
+use bevy::ecs::{
+    entity::{EntityMap, MapEntities, MapEntitiesError},
+    reflect::ReflectMapEntities,
+};
+use bevy::{prelude::*, reflect::FromReflect};
+use rapier::prelude::{InteractionGroups, QueryFilterFlags};
+
+/// The hits reported by a [`ShapeCaster`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ShapeCastMode {
+    /// Only the closest hit is reported.
+    #[default]
+    First,
+    /// All the hits are reported, sorted by increasing time-of-impact.
+    All {
+        /// If set, only this number of closest hits are reported.
+        max_hits: Option<usize>,
+    },
+}
+
+/// A shape cast from the entity of this component after each simulation step.
+///
+/// The shape follows the position of the rigid-body attached to the entity, or its
+/// `GlobalTransform` if it has no rigid-body. The collider and the rigid-body attached to the
+/// entity are excluded from the shape cast. Its hits are written to the [`ShapeHits`] component,
+/// which is automatically added to the entity.
+//
+// NOTE: the shape and Rapier’s query filters aren’t reflectable, so this component is reflected
+//       as an opaque value.
+#[derive(Clone, Debug, Component, Reflect, FromReflect)]
+#[reflect_value(Component)]
+pub struct ShapeCaster {
+    /// The shape to cast.
+    pub shape: Collider,
+    /// The initial translation of the shape, relative to the entity.
+    pub offset: Vect,
+    /// The rotation of the shape, relative to the entity.
+    pub rotation: Rot,
+    /// The direction of the cast, relative to the entity.
+    pub direction: Vect,
+    /// The maximum distance traveled by the shape.
+    pub max_distance: Real,
+    /// The hits reported by this shape cast.
+    pub mode: ShapeCastMode,
+    /// Flags for filtering-out some categories of entities from the shape cast.
+    pub filter_flags: QueryFilterFlags,
+    /// Groups for filtering-out some colliders from the shape cast.
+    pub filter_groups: Option<InteractionGroups>,
+}
+
+impl ShapeCaster {
+    /// Creates a shape caster casting `shape` from the entity along `direction`, up to
+    /// `max_distance`.
+    pub fn new(shape: Collider, direction: Vect, max_distance: Real) -> Self {
+        Self {
+            shape,
+            offset: Vect::ZERO,
+            rotation: Rot::default(),
+            direction,
+            max_distance,
+            mode: ShapeCastMode::First,
+            filter_flags: QueryFilterFlags::default(),
+            filter_groups: None,
+        }
+    }
+}
+
+impl FromWorld for ShapeCaster {
+    fn from_world(_world: &mut World) -> Self {
+        Self::new(Collider::ball(0.5), Vect::ZERO, 0.0)
+    }
+}
+
+/// The hits of a [`ShapeCaster`] during the last simulation step, sorted by increasing
+/// time-of-impact.
+///
+/// The time-of-impact of each hit is the distance traveled by the shape. The witness and normal 1
+/// refer to the collider hit, and are expressed in world-space.
+#[derive(Clone, Debug, Default, PartialEq, Component, Reflect, FromReflect)]
+#[reflect_value(Component, MapEntities, PartialEq)]
+pub struct ShapeHits(pub Vec<(Entity, Toi)>);
+
+impl ShapeHits {
+    /// The closest hit, if any.
+    pub fn first(&self) -> Option<&(Entity, Toi)> {
+        self.0.first()
+    }
+}
+
+impl MapEntities for ShapeHits {
+    fn map_entities(&mut self, entity_map: &EntityMap) -> Result<(), MapEntitiesError> {
+        for (entity, _) in &mut self.0 {
+            if let Ok(mapped) = entity_map.get(*entity) {
+                *entity = mapped;
+            }
+        }
+        Ok(())
+    }
+}
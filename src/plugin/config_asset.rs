@@ -0,0 +1,239 @@
+use crate::math::{Real, Vect};
+use crate::plugin::configuration::{QueryPipelineUpdate, TimestepMode};
+use crate::plugin::RapierConfiguration;
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use rapier::prelude::IntegrationParameters;
+
+/// A physics configuration loaded from a `.physics.ron` file, with the `serde-serialize` feature.
+///
+/// It is applied to the [`RapierConfiguration`], the integration parameters of the
+/// `RapierContext` and the debug-renderer when the [`ActivePhysicsConfig`] is loaded, and again
+/// each time its file is modified if the `AssetServer` watches for changes. This allows tuning
+/// the simulation while the app runs. Only the values present in the file are applied:
+///
+/// ```ron
+/// (
+///     gravity: Some((0.0, -9.81, 0.0)),
+///     timestep_mode: Some(Fixed(dt: 0.016666668, substeps: 2)),
+///     integration_parameters: (
+///         erp: Some(0.6),
+///         max_velocity_iterations: Some(8),
+///     ),
+///     debug_render: (
+///         contacts: Some(true),
+///     ),
+/// )
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, TypeUuid, Serialize, Deserialize)]
+#[uuid = "5a3c0e44-0dbb-4d8d-9b4e-9d5f3c8f04c1"]
+#[serde(default)]
+pub struct PhysicsConfigAsset {
+    /// See [`RapierConfiguration::gravity`].
+    pub gravity: Option<Vect>,
+    /// See [`RapierConfiguration::physics_pipeline_active`].
+    pub physics_pipeline_active: Option<bool>,
+    /// See [`RapierConfiguration::query_pipeline_active`].
+    pub query_pipeline_active: Option<bool>,
+    /// See [`RapierConfiguration::query_pipeline_update`].
+    pub query_pipeline_update: Option<QueryPipelineUpdate>,
+    /// See [`RapierConfiguration::timestep_mode`].
+    pub timestep_mode: Option<TimestepMode>,
+    /// See [`RapierConfiguration::scaled_shape_subdivision`].
+    pub scaled_shape_subdivision: Option<u32>,
+    /// See [`RapierConfiguration::force_update_from_transform_changes`].
+    pub force_update_from_transform_changes: Option<bool>,
+    /// The integration parameters of the simulation.
+    pub integration_parameters: IntegrationParametersConfig,
+    /// The settings of the debug-renderer, applied with the `debug-render` feature.
+    pub debug_render: DebugRenderConfig,
+}
+
+/// The [`IntegrationParameters`] set by a [`PhysicsConfigAsset`].
+///
+/// The timestep is set by the [`TimestepMode`] instead.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+#[allow(missing_docs)] // See the fields of the same name of `IntegrationParameters`.
+pub struct IntegrationParametersConfig {
+    pub min_ccd_dt: Option<Real>,
+    pub erp: Option<Real>,
+    pub damping_ratio: Option<Real>,
+    pub joint_erp: Option<Real>,
+    pub joint_damping_ratio: Option<Real>,
+    pub allowed_linear_error: Option<Real>,
+    pub max_penetration_correction: Option<Real>,
+    pub prediction_distance: Option<Real>,
+    pub max_velocity_iterations: Option<usize>,
+    pub max_velocity_friction_iterations: Option<usize>,
+    pub max_stabilization_iterations: Option<usize>,
+    pub interleave_restitution_and_friction_resolution: Option<bool>,
+    pub min_island_size: Option<usize>,
+    pub max_ccd_substeps: Option<usize>,
+}
+
+/// The settings of the debug-renderer set by a [`PhysicsConfigAsset`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DebugRenderConfig {
+    /// Whether the debug-rendering is enabled.
+    pub enabled: Option<bool>,
+    /// Whether the collider shapes are rendered.
+    pub collider_shapes: Option<bool>,
+    /// Whether the local axes of the rigid-bodies are rendered.
+    pub rigid_body_axes: Option<bool>,
+    /// Whether the multibody joints are rendered.
+    pub multibody_joints: Option<bool>,
+    /// Whether the impulse joints are rendered.
+    pub impulse_joints: Option<bool>,
+    /// Whether the solver contacts are rendered.
+    pub solver_contacts: Option<bool>,
+    /// Whether the geometric contacts are rendered.
+    pub contacts: Option<bool>,
+    /// Whether the AABBs of the colliders are rendered.
+    pub collider_aabbs: Option<bool>,
+}
+
+impl PhysicsConfigAsset {
+    /// Applies the values of this configuration to the physics configuration and integration
+    /// parameters.
+    pub fn apply(
+        &self,
+        config: &mut RapierConfiguration,
+        integration_parameters: &mut IntegrationParameters,
+    ) {
+        fn set<T: Copy>(target: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+
+        set(&mut config.gravity, self.gravity);
+        set(
+            &mut config.physics_pipeline_active,
+            self.physics_pipeline_active,
+        );
+        set(
+            &mut config.query_pipeline_active,
+            self.query_pipeline_active,
+        );
+        set(
+            &mut config.query_pipeline_update,
+            self.query_pipeline_update,
+        );
+        set(&mut config.timestep_mode, self.timestep_mode);
+        set(
+            &mut config.scaled_shape_subdivision,
+            self.scaled_shape_subdivision,
+        );
+        set(
+            &mut config.force_update_from_transform_changes,
+            self.force_update_from_transform_changes,
+        );
+
+        let params = &self.integration_parameters;
+        set(&mut integration_parameters.min_ccd_dt, params.min_ccd_dt);
+        set(&mut integration_parameters.erp, params.erp);
+        set(
+            &mut integration_parameters.damping_ratio,
+            params.damping_ratio,
+        );
+        set(&mut integration_parameters.joint_erp, params.joint_erp);
+        set(
+            &mut integration_parameters.joint_damping_ratio,
+            params.joint_damping_ratio,
+        );
+        set(
+            &mut integration_parameters.allowed_linear_error,
+            params.allowed_linear_error,
+        );
+        set(
+            &mut integration_parameters.max_penetration_correction,
+            params.max_penetration_correction,
+        );
+        set(
+            &mut integration_parameters.prediction_distance,
+            params.prediction_distance,
+        );
+        set(
+            &mut integration_parameters.max_velocity_iterations,
+            params.max_velocity_iterations,
+        );
+        set(
+            &mut integration_parameters.max_velocity_friction_iterations,
+            params.max_velocity_friction_iterations,
+        );
+        set(
+            &mut integration_parameters.max_stabilization_iterations,
+            params.max_stabilization_iterations,
+        );
+        set(
+            &mut integration_parameters.interleave_restitution_and_friction_resolution,
+            params.interleave_restitution_and_friction_resolution,
+        );
+        set(
+            &mut integration_parameters.min_island_size,
+            params.min_island_size,
+        );
+        set(
+            &mut integration_parameters.max_ccd_substeps,
+            params.max_ccd_substeps,
+        );
+    }
+
+    /// Applies the debug-render settings of this configuration.
+    #[cfg(feature = "debug-render")]
+    pub fn apply_debug_render(&self, debug_render: &mut crate::render::DebugRenderContext) {
+        use rapier::pipeline::DebugRenderMode;
+
+        let settings = &self.debug_render;
+        if let Some(enabled) = settings.enabled {
+            debug_render.enabled = enabled;
+        }
+
+        let mode = &mut debug_render.pipeline.mode;
+        for (flag, value) in [
+            (DebugRenderMode::COLLIDER_SHAPES, settings.collider_shapes),
+            (DebugRenderMode::RIGID_BODY_AXES, settings.rigid_body_axes),
+            (DebugRenderMode::MULTIBODY_JOINTS, settings.multibody_joints),
+            (DebugRenderMode::IMPULSE_JOINTS, settings.impulse_joints),
+            (DebugRenderMode::SOLVER_CONTACTS, settings.solver_contacts),
+            (DebugRenderMode::CONTACTS, settings.contacts),
+            (DebugRenderMode::COLLIDER_AABBS, settings.collider_aabbs),
+        ] {
+            if let Some(value) = value {
+                mode.set(flag, value);
+            }
+        }
+    }
+}
+
+/// The loader of the [`PhysicsConfigAsset`]s, from `.physics.ron` files.
+#[derive(Default)]
+pub struct PhysicsConfigLoader;
+
+impl bevy::asset::AssetLoader for PhysicsConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut bevy::asset::LoadContext,
+    ) -> bevy::asset::BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let asset: PhysicsConfigAsset = ron::de::from_bytes(bytes)?;
+            load_context.set_default_asset(bevy::asset::LoadedAsset::new(asset));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["physics.ron"]
+    }
+}
+
+/// Resource selecting the [`PhysicsConfigAsset`] applied to the physics resources.
+///
+/// ```ignore
+/// commands.insert_resource(ActivePhysicsConfig(asset_server.load("config.physics.ron")));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ActivePhysicsConfig(pub Handle<PhysicsConfigAsset>);
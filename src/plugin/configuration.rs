@@ -1,4 +1,5 @@
-use crate::math::Vect;
+use crate::math::{Real, Vect};
+use bevy::render::primitives::Aabb;
 
 /// Difference between simulation and rendering time
 #[derive(Default)]
@@ -9,6 +10,7 @@ pub struct SimulationToRenderTime {
 
 /// The different ways of adjusting the timestep length.
 #[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
 pub enum TimestepMode {
     /// Use a fixed timestep: the physics simulation will be advanced by the fixed value
     /// `dt` seconds at each Bevy tick by performing `substeps` of length `dt / substeps`.
@@ -47,6 +49,24 @@ pub enum TimestepMode {
     },
 }
 
+/// When the query pipeline, used by the scene queries, is updated.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum QueryPipelineUpdate {
+    /// The query pipeline is updated after each simulation step.
+    #[default]
+    AfterStep,
+    /// The query pipeline is updated after each simulation step, and before it, once the
+    /// colliders created or modified since the last simulation step have been synchronized
+    /// with Rapier.
+    ///
+    /// This lets the systems running between [`PhysicsStages::SyncBackend`](crate::plugin::PhysicsStages::SyncBackend)
+    /// and the simulation step, as well as the physics hooks, find these colliders.
+    AfterSyncAndStep,
+    /// The query pipeline is only updated by [`RapierContext::update_query_pipeline_now`](crate::plugin::RapierContext::update_query_pipeline_now).
+    Manual,
+}
+
 #[derive(Copy, Clone, Debug)]
 /// A resource for specifying configuration information for the physics simulation
 pub struct RapierConfiguration {
@@ -55,7 +75,13 @@ pub struct RapierConfiguration {
     /// Specifies if the physics simulation is active and update the physics world.
     pub physics_pipeline_active: bool,
     /// Specifies if the query pipeline is active and update the query pipeline.
+    ///
+    /// If this is `false`, the query pipeline is updated only by
+    /// [`RapierContext::update_query_pipeline_now`](crate::plugin::RapierContext::update_query_pipeline_now),
+    /// whatever `Self::query_pipeline_update` is.
     pub query_pipeline_active: bool,
+    /// Specifies when the query pipeline is updated.
+    pub query_pipeline_update: QueryPipelineUpdate,
     /// Specifies the way the timestep length should be adjusted at each frame.
     pub timestep_mode: TimestepMode,
     /// Specifies the number of subdivisions along each axes a shape should be subdivided
@@ -79,6 +105,7 @@ impl Default for RapierConfiguration {
             gravity: Vect::Y * -9.81,
             physics_pipeline_active: true,
             query_pipeline_active: true,
+            query_pipeline_update: QueryPipelineUpdate::AfterStep,
             timestep_mode: TimestepMode::Variable {
                 max_dt: 1.0 / 60.0,
                 time_scale: 1.0,
@@ -89,3 +116,66 @@ impl Default for RapierConfiguration {
         }
     }
 }
+
+/// What happens to a rigid-body that left the [`PhysicsWorldBounds`].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum OutOfBoundsAction {
+    /// The rigid-body entity is despawned, along with its children.
+    #[default]
+    Despawn,
+    /// The rigid-body is moved back to the closest point inside of the bounds, and loses
+    /// the part of its velocity pointing outside. A rigid-body with a non-finite position
+    /// or velocity is moved to the center of the bounds and stopped.
+    Clamp,
+    /// The rigid-body is left untouched, and only an `OutOfBounds` event is sent. It isn’t sent
+    /// again until the rigid-body comes back inside the bounds and leaves them again.
+    SendEvent,
+}
+
+/// A resource defining the region of space rigid-bodies are expected to remain in.
+///
+/// Rigid-bodies that escape these bounds, or end up with a non-finite position or velocity,
+/// are handled according to `action`. An `OutOfBounds` event is sent for each of them,
+/// whichever the `action` is. No bounds are enforced if this resource doesn’t exist.
+#[derive(Clone, Debug)]
+pub struct PhysicsWorldBounds {
+    /// The bounds, in Bevy’s world-space. The `z` coordinate is ignored in 2D.
+    pub aabb: Aabb,
+    /// What happens to the rigid-bodies leaving the bounds.
+    pub action: OutOfBoundsAction,
+}
+
+impl PhysicsWorldBounds {
+    /// Bounds despawning the rigid-bodies leaving the given `aabb`.
+    pub fn new(aabb: Aabb) -> Self {
+        Self {
+            aabb,
+            action: OutOfBoundsAction::Despawn,
+        }
+    }
+}
+
+/// A resource enabling the detection of the rigid-bodies with a non-finite position, rotation, or
+/// velocity after each simulation step.
+///
+/// A `PhysicsInvalidState` event is sent for each non-finite value, with the likely cause of the
+/// problem. This resource is inserted by the physics plugin in debug builds; insert it manually
+/// to enable the detection in release builds, or remove it to disable it.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct InvalidStateDetection {
+    /// If `true`, the invalid rigid-bodies are moved back to their last valid position, stopped,
+    /// and made fixed, so they don’t vanish and can be inspected.
+    pub freeze_bodies: bool,
+    /// The magnitude of the `ExternalForce` or `ExternalImpulse` beyond which it is considered
+    /// the likely cause of an invalid state.
+    pub max_force: Real,
+}
+
+impl Default for InvalidStateDetection {
+    fn default() -> Self {
+        Self {
+            freeze_bodies: false,
+            max_force: 1.0e12,
+        }
+    }
+}
@@ -0,0 +1,157 @@
+use crate::plugin::RapierContext;
+use bevy::prelude::Entity;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+type PairData = HashMap<TypeId, Box<dyn Any + Send + Sync>>;
+
+/// User data attached to the contact pairs between colliders (e.g. the damage accumulated by two
+/// grinding colliders, or the number of decals spawned at their contact).
+///
+/// The data of a pair lives as long as the two colliders are in contact (or intersecting, if one
+/// of them is a sensor): it is removed automatically after the simulation step they stop touching.
+/// Data attached to two colliders that aren’t touching is removed after the next simulation step.
+///
+/// Each pair can hold one value of each type. The order of the two colliders doesn’t matter.
+#[derive(Default)]
+pub struct ContactPairStorage {
+    pairs: HashMap<(Entity, Entity), PairData>,
+}
+
+impl ContactPairStorage {
+    fn pair_key(collider1: Entity, collider2: Entity) -> (Entity, Entity) {
+        if collider1 <= collider2 {
+            (collider1, collider2)
+        } else {
+            (collider2, collider1)
+        }
+    }
+
+    /// Attaches a value to the contact pair between two colliders, returning the value of the
+    /// same type previously attached to it.
+    pub fn insert<T: Any + Send + Sync>(
+        &mut self,
+        collider1: Entity,
+        collider2: Entity,
+        value: T,
+    ) -> Option<T> {
+        self.pairs
+            .entry(Self::pair_key(collider1, collider2))
+            .or_default()
+            .insert(TypeId::of::<T>(), Box::new(value))
+            .and_then(|previous| previous.downcast().ok())
+            .map(|previous| *previous)
+    }
+
+    /// The value of type `T` attached to the contact pair between two colliders.
+    pub fn get<T: Any + Send + Sync>(&self, collider1: Entity, collider2: Entity) -> Option<&T> {
+        self.pairs
+            .get(&Self::pair_key(collider1, collider2))?
+            .get(&TypeId::of::<T>())?
+            .downcast_ref()
+    }
+
+    /// A mutable reference to the value of type `T` attached to the contact pair between two
+    /// colliders.
+    pub fn get_mut<T: Any + Send + Sync>(
+        &mut self,
+        collider1: Entity,
+        collider2: Entity,
+    ) -> Option<&mut T> {
+        self.pairs
+            .get_mut(&Self::pair_key(collider1, collider2))?
+            .get_mut(&TypeId::of::<T>())?
+            .downcast_mut()
+    }
+
+    /// A mutable reference to the value of type `T` attached to the contact pair between two
+    /// colliders, attaching the value returned by `default` first if there is none.
+    pub fn get_or_insert_with<T: Any + Send + Sync>(
+        &mut self,
+        collider1: Entity,
+        collider2: Entity,
+        default: impl FnOnce() -> T,
+    ) -> &mut T {
+        self.pairs
+            .entry(Self::pair_key(collider1, collider2))
+            .or_default()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(default()))
+            .downcast_mut()
+            .expect("Internal error: contact pair data of the wrong type.")
+    }
+
+    /// Removes the value of type `T` attached to the contact pair between two colliders.
+    pub fn remove<T: Any + Send + Sync>(
+        &mut self,
+        collider1: Entity,
+        collider2: Entity,
+    ) -> Option<T> {
+        let key = Self::pair_key(collider1, collider2);
+        let data = self.pairs.get_mut(&key)?;
+        let value = data.remove(&TypeId::of::<T>())?;
+
+        if data.is_empty() {
+            self.pairs.remove(&key);
+        }
+
+        value.downcast().ok().map(|value| *value)
+    }
+
+    /// Removes all the values attached to the contact pair between two colliders.
+    pub fn remove_pair(&mut self, collider1: Entity, collider2: Entity) {
+        self.pairs.remove(&Self::pair_key(collider1, collider2));
+    }
+
+    /// Returns `true` if any value is attached to the contact pair between two colliders.
+    pub fn contains_pair(&self, collider1: Entity, collider2: Entity) -> bool {
+        self.pairs
+            .contains_key(&Self::pair_key(collider1, collider2))
+    }
+
+    /// An iterator visiting all the contact pairs with a value of type `T` attached, in
+    /// arbitrary order.
+    pub fn iter<T: Any + Send + Sync>(&self) -> impl Iterator<Item = (Entity, Entity, &T)> {
+        self.pairs
+            .iter()
+            .filter_map(|((collider1, collider2), data)| {
+                let value = data.get(&TypeId::of::<T>())?.downcast_ref()?;
+                Some((*collider1, *collider2, value))
+            })
+    }
+
+    /// The number of contact pairs with values attached.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns `true` if no value is attached to any contact pair.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+
+    /// Removes all the values attached to the contact pairs.
+    pub fn clear(&mut self) {
+        self.pairs.clear();
+    }
+}
+
+impl RapierContext {
+    /// Removes the values of the [`ContactPairStorage`] attached to the colliders that are no
+    /// longer in contact.
+    pub(crate) fn remove_ended_contact_pair_data(&mut self) {
+        if self.contact_pair_storage.is_empty() {
+            return;
+        }
+
+        let mut storage = std::mem::take(&mut self.contact_pair_storage);
+        storage.pairs.retain(|(collider1, collider2), _| {
+            let in_contact = self
+                .contact_pair(*collider1, *collider2)
+                .map(|pair| pair.raw.has_any_active_contact)
+                .unwrap_or(false);
+            in_contact || self.intersection_pair(*collider1, *collider2) == Some(true)
+        });
+        self.contact_pair_storage = storage;
+    }
+}
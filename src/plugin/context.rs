@@ -1,27 +1,43 @@
 use bevy::time::Time;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::AtomicU64;
 use std::sync::RwLock;
 
 use rapier::prelude::{
-    Aabb as RapierAabb, BroadPhase, CCDSolver, ColliderHandle, ColliderSet, EventHandler,
-    FeatureId, ImpulseJointHandle, ImpulseJointSet, IntegrationParameters, IslandManager,
-    MultibodyJointHandle, MultibodyJointSet, NarrowPhase, PhysicsHooks, PhysicsPipeline,
-    QueryFilter as RapierQueryFilter, QueryPipeline, Ray, Real, RigidBodyHandle, RigidBodySet,
+    Aabb as RapierAabb, ActiveHooks, BroadPhase, CCDSolver, ColliderHandle, ColliderSet,
+    EventHandler, FeatureId, GenericJoint, ImpulseJointHandle, ImpulseJointSet,
+    IntegrationParameters, IslandManager, Isometry, JointAxesMask, JointAxis, MultibodyJointHandle,
+    MultibodyJointSet, NarrowPhase, PairFilterContext, PhysicsHooks, PhysicsPipeline, Point,
+    QueryFilter as RapierQueryFilter, QueryPipeline, QueryPipelineMode, Ray, Real,
+    RigidBodyBuilder, RigidBodyHandle, RigidBodyPosition, RigidBodySet, RigidBodyType, Rotation,
+    Shape, SolverFlags, Translation, Vector,
 };
 
-use crate::geometry::{Collider, PointProjection, RayIntersection, Toi};
+use crate::geometry::{
+    ClosestPoints, Collider, FluidVolume, Group, HeightfieldCell, LineOfSight, PathHit,
+    PointProjection, RayIntersection, Region, Toi, TriangleHit, VisionCone,
+};
 use crate::math::{Rot, Vect};
-use crate::pipeline::{CollisionEvent, ContactForceEvent, EventQueue, QueryFilter};
-use bevy::prelude::{Entity, EventWriter, GlobalTransform, Query};
+use crate::pipeline::{
+    CcdImpact, CollisionEvent, CollisionEventDetails, ContactForceEvent,
+    ContactForceEventThresholds, EventQueue, QueryFilter, SensorEvent,
+};
+use bevy::prelude::{Camera, Entity, EventWriter, GlobalTransform, Mat4, Query, Vec2};
 use bevy::render::primitives::Aabb;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice};
 
-use crate::control::{CharacterCollision, MoveShapeOptions, MoveShapeOutput};
-use crate::dynamics::TransformInterpolation;
-use crate::plugin::configuration::{SimulationToRenderTime, TimestepMode};
+use crate::control::{
+    CharacterCollision, KinematicCharacterController, KinematicCharacterControllerOutput,
+    MoveShapeOptions, MoveShapeOutput, SteepSlopeBehavior,
+};
+use crate::dynamics::{BodyState, TransformInterpolation, Velocity, WeldedBody};
+use crate::plugin::configuration::{RapierConfiguration, SimulationToRenderTime, TimestepMode};
+use crate::plugin::rolling_friction::RollingResistances;
+use crate::plugin::{ContactPairStorage, PhysicsSnapshot};
 use crate::prelude::RapierRigidBodyHandle;
-#[cfg(feature = "dim2")]
-use bevy::math::Vec3Swizzles;
-use rapier::control::CharacterAutostep;
+use rapier::control::{CharacterAutostep, CharacterLength};
+#[cfg(feature = "debug-stream")]
+use {crate::plugin::DebugStreamFrame, rapier::pipeline::DebugRenderPipeline};
 
 /// The Rapier context, containing all the state of the physics engine.
 #[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
@@ -52,11 +68,40 @@ pub struct RapierContext {
     /// The integration parameters, controlling various low-level coefficient of the simulation.
     pub integration_parameters: IntegrationParameters,
     pub(crate) physics_scale: Real,
+    // The number of simulation steps run so far.
+    pub(crate) step_index: u64,
+    // The timestep length of the last simulation step, i.e. of the last substep, needed to turn
+    // the impulses applied during this step into forces.
+    pub(crate) last_substep_dt: Real,
+    // The fixed rigid-body the joints anchored to the world are attached to.
+    pub(crate) world_body: Option<RigidBodyHandle>,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     pub(crate) event_handler: Option<Box<dyn EventHandler>>,
     // For transform change detection.
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     pub(crate) last_body_transform_set: HashMap<RigidBodyHandle, GlobalTransform>,
+    // The forces added to rigid-bodies by `RadialGravity`. Needed to subtract them again,
+    // because Rapier accumulates user forces until they are reset.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) radial_gravity_forces: HashMap<RigidBodyHandle, Vect>,
+    // The last target positions of the kinematic rigid-bodies with a
+    // `KinematicVelocityEstimation`, oldest first.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) kinematic_position_histories: HashMap<RigidBodyHandle, VecDeque<Isometry<Real>>>,
+    // The windows of the `KinematicVelocityEstimation`s of the kinematic rigid-bodies.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) kinematic_velocity_estimations: HashMap<RigidBodyHandle, usize>,
+    // The rigid-bodies welded onto another one by a `Weld`.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) welded_bodies: HashMap<Entity, WeldedBody>,
+    // The joints of the `RopeJoint`s and `SpringJoint`s, with the description of their component,
+    // aimed at their second anchor before each simulation step.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) distance_joints: HashMap<ImpulseJointHandle, GenericJoint>,
+    // The components slowing down the rotation of the rigid-bodies in contact, applied after
+    // each simulation step.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) rolling_resistances: RollingResistances,
     // NOTE: these maps are needed to handle despawning.
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     pub(crate) entity2body: HashMap<Entity, RigidBodyHandle>,
@@ -70,8 +115,57 @@ pub struct RapierContext {
     // physics update, to the entity they was attached to.
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     pub(crate) deleted_colliders: HashMap<ColliderHandle, Entity>,
+    // The handles of the sensors among the deleted colliders.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) deleted_sensors: HashSet<ColliderHandle>,
+    // The details of the collision events of the last simulation step, waiting to be sent.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) collision_event_details: Vec<CollisionEventDetails>,
+    // The sensor events of the last simulation step, waiting to be sent.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) sensor_events: Vec<SensorEvent>,
+    // The impacts resolved by CCD during the last simulation step, waiting to be sent.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) ccd_impacts: Vec<CcdImpact>,
+    // The pipeline of the `DebugStreamRecorder`, lent during the simulation steps of the frames
+    // it is recording.
+    #[cfg(feature = "debug-stream")]
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) debug_stream_pipeline: Option<DebugRenderPipeline>,
+    // The debug stream frames recorded after each simulation step, waiting to be written.
+    #[cfg(feature = "debug-stream")]
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) debug_stream_frames: Vec<DebugStreamFrame>,
     #[cfg_attr(feature = "serde-serialize", serde(skip))]
     pub(crate) character_collisions_collector: Vec<rapier::control::CharacterCollision>,
+    // The one-way platforms and fluid volumes seen by the character controllers, updated with
+    // the character controllers.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) one_way_platforms: HashMap<ColliderHandle, Vector<Real>>,
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) fluid_volumes: HashMap<ColliderHandle, FluidVolume>,
+    // The colliders with `ActiveEvents::COLLISION_EVENT_CONTACTS`.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) collision_event_contacts: HashSet<ColliderHandle>,
+    // The colliders with `ActiveEvents::CCD_IMPACTS`.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) ccd_impact_colliders: HashSet<ColliderHandle>,
+    // The query pipeline finding the colliders hit by the rigid-bodies stopped by CCD, updated
+    // only after the steps with such an impact.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) ccd_query_pipeline: QueryPipeline,
+    // The colliders with a `CollisionEventFilter`.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) collision_event_filters: HashMap<ColliderHandle, Group>,
+    // Whether a snapshot was restored since the last `PhysicsStages::SyncBackend`.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub(crate) restored: bool,
+    /// The contact force event thresholds of specific pairs of colliders.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub contact_force_event_thresholds: ContactForceEventThresholds,
+    /// The user data attached to the contact pairs between colliders.
+    #[cfg_attr(feature = "serde-serialize", serde(skip))]
+    pub contact_pair_storage: ContactPairStorage,
 }
 
 impl Default for RapierContext {
@@ -89,14 +183,40 @@ impl Default for RapierContext {
             query_pipeline: QueryPipeline::new(),
             integration_parameters: IntegrationParameters::default(),
             physics_scale: 1.0,
+            step_index: 0,
+            last_substep_dt: IntegrationParameters::default().dt,
+            world_body: None,
             event_handler: None,
             last_body_transform_set: HashMap::new(),
+            radial_gravity_forces: HashMap::new(),
+            kinematic_position_histories: HashMap::new(),
+            kinematic_velocity_estimations: HashMap::new(),
+            welded_bodies: HashMap::new(),
+            distance_joints: HashMap::new(),
+            rolling_resistances: RollingResistances::default(),
             entity2body: HashMap::new(),
             entity2collider: HashMap::new(),
             entity2impulse_joint: HashMap::new(),
             entity2multibody_joint: HashMap::new(),
             deleted_colliders: HashMap::new(),
+            deleted_sensors: HashSet::new(),
+            collision_event_details: vec![],
+            sensor_events: vec![],
+            ccd_impacts: vec![],
+            #[cfg(feature = "debug-stream")]
+            debug_stream_pipeline: None,
+            #[cfg(feature = "debug-stream")]
+            debug_stream_frames: vec![],
             character_collisions_collector: vec![],
+            one_way_platforms: HashMap::new(),
+            fluid_volumes: HashMap::new(),
+            collision_event_contacts: HashSet::new(),
+            ccd_impact_colliders: HashSet::new(),
+            ccd_query_pipeline: QueryPipeline::new(),
+            collision_event_filters: HashMap::new(),
+            restored: false,
+            contact_force_event_thresholds: ContactForceEventThresholds::default(),
+            contact_pair_storage: ContactPairStorage::default(),
         }
     }
 }
@@ -109,6 +229,37 @@ impl RapierContext {
         self.physics_scale
     }
 
+    /// The index of the next simulation step, which is the number of simulation steps run so
+    /// far, substeps included.
+    ///
+    /// The [`CollisionEventDetails`] give the index of the step their event occurred in.
+    pub fn step_index(&self) -> u64 {
+        self.step_index
+    }
+
+    /// The user data of the fixed rigid-body the joints anchored to the world are attached to,
+    /// which no entity converts to.
+    pub(crate) const WORLD_BODY_USER_DATA: u128 = u128::MAX;
+
+    /// The fixed rigid-body the joints anchored to the world are attached to.
+    ///
+    /// It is created the first time it is needed.
+    pub(crate) fn world_body(&mut self) -> RigidBodyHandle {
+        if let Some(handle) = self
+            .world_body
+            .filter(|handle| self.bodies.contains(*handle))
+        {
+            return handle;
+        }
+
+        let body = RigidBodyBuilder::fixed()
+            .user_data(Self::WORLD_BODY_USER_DATA)
+            .build();
+        let handle = self.bodies.insert(body);
+        self.world_body = Some(handle);
+        handle
+    }
+
     /// If the collider attached to `entity` is attached to a rigid-body, this
     /// returns the `Entity` containing that rigid-body.
     pub fn collider_parent(&self, entity: Entity) -> Option<Entity> {
@@ -141,6 +292,27 @@ impl RapierContext {
             .map(|c| Entity::from_bits(c.user_data as u64))
     }
 
+    /// The state of the rigid-body attached to the given entity, to replicate it over the
+    /// network.
+    pub fn body_state(&self, entity: Entity) -> Option<BodyState> {
+        let rb = self.bodies.get(*self.entity2body.get(&entity)?)?;
+        Some(BodyState {
+            translation: (rb.translation() * self.physics_scale).into(),
+            #[cfg(feature = "dim2")]
+            rotation: rb.rotation().angle(),
+            #[cfg(feature = "dim3")]
+            rotation: (*rb.rotation()).into(),
+            velocity: Velocity {
+                linvel: (rb.linvel() * self.physics_scale).into(),
+                #[cfg(feature = "dim2")]
+                angvel: rb.angvel(),
+                #[cfg(feature = "dim3")]
+                angvel: (*rb.angvel()).into(),
+            },
+            sleeping: rb.is_sleeping(),
+        })
+    }
+
     fn with_query_filter<T>(
         &self,
         filter: QueryFilter,
@@ -174,10 +346,10 @@ impl RapierContext {
             predicate: None,
         };
 
-        if let Some(predicate) = filter.predicate {
+        if filter.has_entity_test() {
             let wrapped_predicate = |h: ColliderHandle, _: &rapier::geometry::Collider| {
                 Self::collider_entity_with_set(colliders, h)
-                    .map(predicate)
+                    .map(|e| filter.test_entity(e))
                     .unwrap_or(false)
             };
             rapier_filter.predicate = Some(&wrapped_predicate);
@@ -201,17 +373,75 @@ impl RapierContext {
             Query<(&RapierRigidBodyHandle, &mut TransformInterpolation)>,
         >,
     ) {
+        let mut record_body_velocities = false;
+        let mut original_thresholds = HashMap::new();
+        if events.is_some() && self.event_handler.is_none() {
+            let mut lowered_thresholds = vec![];
+            for (handle, co) in self.colliders.iter() {
+                if !co
+                    .active_events()
+                    .contains(rapier::pipeline::ActiveEvents::CONTACT_FORCE_EVENTS)
+                {
+                    continue;
+                }
+
+                record_body_velocities = true;
+                let entity = Entity::from_bits(co.user_data as u64);
+                let memberships =
+                    Group::from_bits_truncate(co.collision_groups().memberships.bits());
+                if let Some(threshold) = self
+                    .contact_force_event_thresholds
+                    .min_threshold(entity, memberships)
+                {
+                    if threshold < co.contact_force_event_threshold() {
+                        original_thresholds.insert(handle, co.contact_force_event_threshold());
+                        lowered_thresholds.push((handle, threshold));
+                    }
+                }
+            }
+
+            // Lower the thresholds of the colliders so Rapier reports the contact pairs the
+            // per-pair thresholds apply to. They are checked again by the event queue.
+            for (handle, threshold) in lowered_thresholds {
+                if let Some(co) = self.colliders.get_mut(handle) {
+                    co.set_contact_force_event_threshold(threshold);
+                }
+            }
+        }
+
+        // The event queue borrows these while the context is stepped, so they are moved out of
+        // the context until the end of the simulation.
+        let event_handler = self.event_handler.take();
+        let deleted_colliders = std::mem::take(&mut self.deleted_colliders);
+        let deleted_sensors = std::mem::take(&mut self.deleted_sensors);
+        let collision_event_contacts = std::mem::take(&mut self.collision_event_contacts);
+        let collision_event_filters = std::mem::take(&mut self.collision_event_filters);
+        let contact_force_event_thresholds =
+            std::mem::take(&mut self.contact_force_event_thresholds);
+
         let event_queue = events.map(|(ce, fe)| EventQueue {
-            deleted_colliders: &self.deleted_colliders,
+            deleted_colliders: &deleted_colliders,
             collision_events: RwLock::new(ce),
+            step_index: AtomicU64::new(self.step_index),
+            step_collision_events: RwLock::new(vec![]),
+            collision_event_details: RwLock::new(vec![]),
             contact_force_events: RwLock::new(fe),
+            sensor_events: RwLock::new(vec![]),
+            deleted_sensors: &deleted_sensors,
+            collision_event_contacts: &collision_event_contacts,
+            collision_event_filters: &collision_event_filters,
+            contact_force_event_thresholds: &contact_force_event_thresholds,
+            original_contact_force_event_thresholds: original_thresholds,
+            record_body_velocities,
+            body_velocities: RwLock::new(HashMap::new()),
+            physics_scale: self.physics_scale,
         });
 
-        let events = self
-            .event_handler
+        let events = event_handler
             .as_deref()
             .or_else(|| event_queue.as_ref().map(|q| q as &dyn EventHandler))
             .unwrap_or(&() as &dyn EventHandler);
+        let gravity = (gravity / self.physics_scale).into();
 
         match timestep_mode {
             TimestepMode::Interpolated {
@@ -242,19 +472,12 @@ impl RapierContext {
                     substep_integration_parameters.dt = dt / (substeps as Real) * time_scale;
 
                     for _ in 0..substeps {
-                        self.pipeline.step(
-                            &(gravity / self.physics_scale).into(),
+                        self.substep(
                             &substep_integration_parameters,
-                            &mut self.islands,
-                            &mut self.broad_phase,
-                            &mut self.narrow_phase,
-                            &mut self.bodies,
-                            &mut self.colliders,
-                            &mut self.impulse_joints,
-                            &mut self.multibody_joints,
-                            &mut self.ccd_solver,
+                            &gravity,
                             hooks,
                             events,
+                            event_queue.as_ref(),
                         );
                     }
 
@@ -271,19 +494,12 @@ impl RapierContext {
                     (time.delta_seconds() * time_scale).min(max_dt) / (substeps as Real);
 
                 for _ in 0..substeps {
-                    self.pipeline.step(
-                        &(gravity / self.physics_scale).into(),
+                    self.substep(
                         &substep_integration_parameters,
-                        &mut self.islands,
-                        &mut self.broad_phase,
-                        &mut self.narrow_phase,
-                        &mut self.bodies,
-                        &mut self.colliders,
-                        &mut self.impulse_joints,
-                        &mut self.multibody_joints,
-                        &mut self.ccd_solver,
+                        &gravity,
                         hooks,
                         events,
+                        event_queue.as_ref(),
                     );
                 }
             }
@@ -292,23 +508,400 @@ impl RapierContext {
                 substep_integration_parameters.dt = dt / (substeps as Real);
 
                 for _ in 0..substeps {
-                    self.pipeline.step(
-                        &(gravity / self.physics_scale).into(),
+                    self.substep(
                         &substep_integration_parameters,
-                        &mut self.islands,
-                        &mut self.broad_phase,
-                        &mut self.narrow_phase,
-                        &mut self.bodies,
-                        &mut self.colliders,
-                        &mut self.impulse_joints,
-                        &mut self.multibody_joints,
-                        &mut self.ccd_solver,
+                        &gravity,
                         hooks,
                         events,
+                        event_queue.as_ref(),
+                    );
+                }
+            }
+        }
+
+        if let Some(queue) = event_queue {
+            if let Ok(details) = queue.collision_event_details.into_inner() {
+                self.collision_event_details.extend(details);
+            }
+            if let Ok(sensor_events) = queue.sensor_events.into_inner() {
+                self.sensor_events.extend(sensor_events);
+            }
+            for (handle, threshold) in queue.original_contact_force_event_thresholds {
+                if let Some(co) = self.colliders.get_mut(handle) {
+                    co.set_contact_force_event_threshold(threshold);
+                }
+            }
+        }
+
+        self.event_handler = event_handler;
+        self.deleted_colliders = deleted_colliders;
+        self.deleted_sensors = deleted_sensors;
+        self.collision_event_contacts = collision_event_contacts;
+        self.collision_event_filters = collision_event_filters;
+        self.contact_force_event_thresholds = contact_force_event_thresholds;
+
+        self.remove_ended_contact_pair_data();
+    }
+
+    // Runs a single simulation step with the given integration parameters, along with the parts
+    // of the simulation handled by the plugin instead of Rapier.
+    fn substep(
+        &mut self,
+        integration_parameters: &IntegrationParameters,
+        gravity: &Vector<Real>,
+        hooks: &dyn PhysicsHooks,
+        events: &dyn EventHandler,
+        event_queue: Option<&EventQueue>,
+    ) {
+        Self::aim_distance_joints(
+            &self.distance_joints,
+            &mut self.impulse_joints,
+            &mut self.islands,
+            &mut self.bodies,
+            self.integration_parameters.allowed_linear_error,
+        );
+        let kinematic_targets = Self::smooth_kinematic_velocities(
+            &self.kinematic_velocity_estimations,
+            &mut self.kinematic_position_histories,
+            &mut self.bodies,
+        );
+        let ccd_bodies = self.ccd_body_positions();
+        if let Some(queue) = event_queue {
+            queue.begin_step(self.step_index, &self.islands, &self.bodies);
+        }
+        self.pipeline.step(
+            gravity,
+            integration_parameters,
+            &mut self.islands,
+            &mut self.broad_phase,
+            &mut self.narrow_phase,
+            &mut self.bodies,
+            &mut self.colliders,
+            &mut self.impulse_joints,
+            &mut self.multibody_joints,
+            &mut self.ccd_solver,
+            hooks,
+            events,
+        );
+        if let Some(queue) = event_queue {
+            queue.end_step();
+        }
+        self.rolling_resistances.apply(
+            &self.narrow_phase,
+            &self.colliders,
+            &mut self.bodies,
+            self.physics_scale,
+        );
+        Self::move_to_kinematic_targets(&kinematic_targets, &mut self.bodies);
+        if !ccd_bodies.is_empty() {
+            let ccd_impacts =
+                self.ccd_impacts_of_step(&ccd_bodies, integration_parameters.dt, hooks);
+            self.ccd_impacts.extend(ccd_impacts);
+        }
+        self.step_index += 1;
+        self.last_substep_dt = integration_parameters.dt;
+
+        #[cfg(feature = "debug-stream")]
+        if let Some(mut pipeline) = self.debug_stream_pipeline.take() {
+            let frame = DebugStreamFrame::record(self, &mut pipeline);
+            self.debug_stream_frames.push(frame);
+            self.debug_stream_pipeline = Some(pipeline);
+        }
+    }
+
+    // Aims the `X` axis of the distance joints at their second anchor, and enables their limit
+    // or motor for the current distance between their anchors. Only these parts of the Rapier
+    // joints are modified, so the changes made to the other parts (e.g. by a `RampedJointMotor`)
+    // are kept.
+    fn aim_distance_joints(
+        distance_joints: &HashMap<ImpulseJointHandle, GenericJoint>,
+        impulse_joints: &mut ImpulseJointSet,
+        islands: &mut IslandManager,
+        bodies: &mut RigidBodySet,
+        allowed_linear_error: Real,
+    ) {
+        for (handle, data) in distance_joints {
+            let raw_joint = match impulse_joints.get_mut(*handle) {
+                Some(raw_joint) => raw_joint,
+                None => continue,
+            };
+            let (body1, body2) = (raw_joint.body1, raw_joint.body2);
+            let (pos1, pos2) = match (bodies.get(body1), bodies.get(body2)) {
+                (Some(rb1), Some(rb2)) => (*rb1.position(), *rb2.position()),
+                _ => continue,
+            };
+
+            let anchor1 = pos1 * Point::from(data.local_frame1.translation.vector);
+            let anchor2 = pos2 * Point::from(data.local_frame2.translation.vector);
+            let local_dir = pos1.rotation.inverse() * (anchor2 - anchor1);
+            let length = local_dir.norm();
+
+            if length > 1.0e-6 {
+                #[cfg(feature = "dim2")]
+                {
+                    raw_joint.data.local_frame1.rotation =
+                        Rotation::rotation_between(&Vector::x(), &local_dir);
+                }
+                #[cfg(feature = "dim3")]
+                {
+                    raw_joint.data.local_frame1.rotation =
+                        Rotation::rotation_between(&Vector::x(), &local_dir).unwrap_or_else(|| {
+                            Rotation::from_axis_angle(&Vector::y_axis(), std::f32::consts::PI)
+                        });
+                }
+            }
+
+            // The spring of an elastic rope replaces its limit, and only pulls once
+            // the rope is stretched.
+            let i = JointAxis::X as usize;
+            let mut limited = data.limit_axes.contains(JointAxesMask::X);
+            let mut motorized = data.motor_axes.contains(JointAxesMask::X);
+            if limited && motorized {
+                limited = false;
+                motorized = length > data.motors[i].target_pos;
+            }
+            raw_joint.data.limit_axes.set(JointAxesMask::X, limited);
+            raw_joint.data.motor_axes.set(JointAxesMask::X, motorized);
+            raw_joint.data.limits[i] = data.limits[i];
+            raw_joint.data.motors[i] = data.motors[i];
+
+            // Rapier doesn’t account for the motor when putting the bodies to sleep,
+            // so keep them awake until the spring reaches its rest length.
+            if motorized && (length - data.motors[i].target_pos).abs() > allowed_linear_error {
+                islands.wake_up(bodies, body1, true);
+                islands.wake_up(bodies, body2, true);
+            }
+        }
+    }
+
+    // Points the next position of the kinematic position-based rigid-bodies with a
+    // `KinematicVelocityEstimation` along their displacement averaged over the last steps, so that
+    // the velocity Rapier deduces from it is the smoothed one. Returns the actual targets of these
+    // rigid-bodies, which they are moved to after the step.
+    fn smooth_kinematic_velocities(
+        estimations: &HashMap<RigidBodyHandle, usize>,
+        histories: &mut HashMap<RigidBodyHandle, VecDeque<Isometry<Real>>>,
+        bodies: &mut RigidBodySet,
+    ) -> Vec<(RigidBodyHandle, Isometry<Real>)> {
+        let mut targets = vec![];
+
+        for (handle, window) in estimations {
+            let rb = match bodies.get_mut(*handle) {
+                Some(rb) if rb.body_type() == RigidBodyType::KinematicPositionBased => rb,
+                _ => {
+                    let _ = histories.remove(handle);
+                    continue;
+                }
+            };
+
+            let position = *rb.position();
+            let target = *rb.next_position();
+            let history = histories
+                .entry(*handle)
+                .or_insert_with(|| [position].into_iter().collect());
+            history.push_back(target);
+            while history.len() > (*window).max(1) + 1 {
+                history.pop_front();
+            }
+
+            // The displacement per step, averaged over the whole window.
+            let local_com = rb.mass_properties().local_com;
+            let window_pos = RigidBodyPosition {
+                position: history[0],
+                next_position: target,
+            };
+            let mean =
+                window_pos.interpolate_velocity(1.0 / (history.len() - 1) as Real, &local_com);
+
+            // Apply this displacement around the current center of mass.
+            let displacement = Isometry::new(mean.linvel, mean.angvel);
+            let shift = Translation::from((position * local_com).coords);
+            rb.set_next_kinematic_position(shift * displacement * shift.inverse() * position);
+            targets.push((*handle, target));
+        }
+
+        targets
+    }
+
+    // Moves the kinematic rigid-bodies with a smoothed velocity to their actual target, without
+    // changing their velocity.
+    fn move_to_kinematic_targets(
+        targets: &[(RigidBodyHandle, Isometry<Real>)],
+        bodies: &mut RigidBodySet,
+    ) {
+        for (handle, target) in targets {
+            if let Some(rb) = bodies.get_mut(*handle) {
+                rb.set_position(*target, false);
+            }
+        }
+    }
+
+    // The positions, before the next simulation step, of the awake dynamic rigid-bodies with
+    // CCD enabled and a collider with `ActiveEvents::CCD_IMPACTS`, sorted by handle so the
+    // impacts are reported in a deterministic order.
+    fn ccd_body_positions(&self) -> Vec<(RigidBodyHandle, Isometry<Real>)> {
+        let mut handles: Vec<_> = self
+            .ccd_impact_colliders
+            .iter()
+            .filter_map(|handle| self.colliders.get(*handle)?.parent())
+            .collect();
+        handles.sort_by_key(|handle| handle.into_raw_parts());
+        handles.dedup();
+
+        handles
+            .into_iter()
+            .filter_map(|handle| {
+                let rb = self.bodies.get(handle)?;
+                (rb.is_dynamic() && rb.is_ccd_enabled() && !rb.is_sleeping())
+                    .then(|| (handle, *rb.position()))
+            })
+            .collect()
+    }
+
+    // Rapier doesn’t report the impacts its CCD solver resolved, only their effect: the motion
+    // of the rigid-bodies it stopped is cut short. The collider hit by each of these bodies is
+    // then found with a time-of-impact query between its position before the step and the
+    // colliders crossed by its unclamped motion, which could have been in contact with it.
+    fn ccd_impacts_of_step(
+        &mut self,
+        ccd_bodies: &[(RigidBodyHandle, Isometry<Real>)],
+        dt: Real,
+        hooks: &dyn PhysicsHooks,
+    ) -> Vec<CcdImpact> {
+        // The rigid-bodies whose motion was clamped by CCD.
+        let clamped_bodies: Vec<_> = ccd_bodies
+            .iter()
+            .filter(|(handle, start_position)| {
+                let rb = match self.bodies.get(*handle) {
+                    Some(rb) if rb.is_ccd_active() => rb,
+                    _ => return false,
+                };
+                let local_com = rb.mass_properties().local_com;
+                let traveled = (rb.position() * local_com - start_position * local_com).norm();
+                traveled < rb.linvel().norm() * dt * 0.99
+            })
+            .collect();
+        if clamped_bodies.is_empty() {
+            return vec![];
+        }
+
+        self.ccd_query_pipeline.update_with_mode(
+            &self.islands,
+            &self.bodies,
+            &self.colliders,
+            QueryPipelineMode::CurrentPosition,
+        );
+
+        let mut impacts = vec![];
+
+        for (handle, start_position) in clamped_bodies {
+            let rb = &self.bodies[*handle];
+            let motion = rb.linvel() * dt;
+            let mut earliest: Option<CcdImpact> = None;
+
+            for collider_handle in rb.colliders() {
+                let co = &self.colliders[*collider_handle];
+                if co.is_sensor() {
+                    continue;
+                }
+
+                let start = start_position * co.position_wrt_parent().copied().unwrap_or_default();
+                let end = Isometry::from_parts(
+                    (start.translation.vector + motion).into(),
+                    start.rotation,
+                );
+                let swept_aabb = co.shape().compute_swept_aabb(&start, &end);
+                let mut candidates = vec![];
+                self.ccd_query_pipeline
+                    .colliders_with_aabb_intersecting_aabb(&swept_aabb, |other_handle| {
+                        candidates.push(*other_handle);
+                        true
+                    });
+
+                for other_handle in candidates {
+                    let other = &self.colliders[other_handle];
+                    if other.is_sensor()
+                        || other.parent() == Some(*handle)
+                        || !co.collision_groups().test(other.collision_groups())
+                        || !co.solver_groups().test(other.solver_groups())
+                    {
+                        continue;
+                    }
+
+                    // The contacts filtered out by the physics hooks don’t stop the body.
+                    if (co.active_hooks() | other.active_hooks())
+                        .contains(ActiveHooks::FILTER_CONTACT_PAIRS)
+                    {
+                        let context = PairFilterContext {
+                            bodies: &self.bodies,
+                            colliders: &self.colliders,
+                            collider1: *collider_handle,
+                            collider2: other_handle,
+                            rigid_body1: Some(*handle),
+                            rigid_body2: other.parent(),
+                        };
+                        match hooks.filter_contact_pair(&context) {
+                            Some(flags) if flags.contains(SolverFlags::COMPUTE_IMPULSES) => {}
+                            _ => continue,
+                        }
+                    }
+
+                    // The other collider moved during the step too: cast from where it started.
+                    let other_linvel = other
+                        .parent()
+                        .and_then(|parent| self.bodies.get(parent))
+                        .map(|parent| *parent.linvel())
+                        .unwrap_or_else(Vector::zeros);
+                    let other_start = Isometry::from_parts(
+                        (other.position().translation.vector - other_linvel * dt).into(),
+                        other.position().rotation,
                     );
+
+                    let toi = match rapier::parry::query::time_of_impact(
+                        &other_start,
+                        &other_linvel,
+                        other.shape(),
+                        &start,
+                        rb.linvel(),
+                        co.shape(),
+                        dt,
+                        true,
+                    ) {
+                        Ok(Some(toi)) => toi,
+                        _ => continue,
+                    };
+
+                    if earliest.map(|e| toi.toi < e.toi).unwrap_or(true) {
+                        let (entity, other_entity) = match (
+                            self.collider_entity(*collider_handle),
+                            self.collider_entity(other_handle),
+                        ) {
+                            (Some(entity), Some(other_entity)) => (entity, other_entity),
+                            _ => continue,
+                        };
+                        let other_position = Isometry::from_parts(
+                            (other_start.translation.vector + other_linvel * toi.toi).into(),
+                            other_start.rotation,
+                        );
+
+                        earliest = Some(CcdImpact {
+                            entity,
+                            other: other_entity,
+                            toi: toi.toi,
+                            point: (other_position * toi.witness1).coords.into(),
+                            normal: (other_position * toi.normal1).into_inner().into(),
+                        });
+                    }
                 }
             }
+
+            if let Some(mut impact) = earliest {
+                impact.point *= self.physics_scale;
+                impacts.push(impact);
+            }
         }
+
+        impacts
     }
 
     /// This method makes sure tha the rigid-body positions have been propagated to
@@ -325,6 +918,138 @@ impl RapierContext {
             .update(&self.islands, &self.bodies, &self.colliders);
     }
 
+    /// Updates the query pipeline right away, after propagating the positions of the rigid-bodies
+    /// to their attached colliders.
+    ///
+    /// This makes the scene queries take into account the latest changes made to the colliders
+    /// and rigid-bodies synchronized with Rapier, without waiting for the next simulation step.
+    /// This is the only way the query pipeline is updated with
+    /// [`QueryPipelineUpdate::Manual`](crate::plugin::QueryPipelineUpdate::Manual).
+    pub fn update_query_pipeline_now(&mut self) {
+        self.propagate_modified_body_positions_to_colliders();
+        self.update_query_pipeline();
+    }
+
+    /// The checksum of the positions and velocities of the rigid-bodies, equal to the
+    /// [`PhysicsSnapshot::checksum`] of a snapshot taken now, without copying the physics world.
+    pub fn checksum(&self) -> u64 {
+        crate::plugin::snapshot::checksum(self.step_index, &self.bodies)
+    }
+
+    /// Takes a snapshot of the whole physics world, to restore it later with [`Self::restore`].
+    pub fn snapshot(&self) -> PhysicsSnapshot {
+        PhysicsSnapshot {
+            islands: self.islands.clone(),
+            broad_phase: self.broad_phase.clone(),
+            narrow_phase: self.narrow_phase.clone(),
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            impulse_joints: self.impulse_joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+            ccd_solver: self.ccd_solver.clone(),
+            integration_parameters: self.integration_parameters,
+            step_index: self.step_index,
+            world_body: self.world_body,
+            impulse_joint_entities: self
+                .entity2impulse_joint
+                .iter()
+                .map(|(entity, handle)| (*entity, *handle))
+                .collect(),
+            multibody_joint_entities: self
+                .entity2multibody_joint
+                .iter()
+                .map(|(entity, handle)| (*entity, *handle))
+                .collect(),
+            welded_bodies: self
+                .welded_bodies
+                .iter()
+                .map(|(entity, welded)| (*entity, welded.clone()))
+                .collect(),
+        }
+    }
+
+    /// Restores the physics world from a snapshot taken with [`Self::snapshot`].
+    ///
+    /// The rigid-bodies and colliders are associated again with the entities they were attached
+    /// to when the snapshot was taken, using their user data, and the handle components of
+    /// these entities are updated during the next [`PhysicsStages::SyncBackend`]. Then, the
+    /// rigid-bodies, colliders and joints of the entities that no longer exist are removed,
+    /// while the entities with physics components but missing from the snapshot get new ones.
+    ///
+    /// [`PhysicsStages::SyncBackend`]: crate::plugin::PhysicsStages::SyncBackend
+    pub fn restore(&mut self, snapshot: PhysicsSnapshot) {
+        // The state attached to the handles of the current world, keyed by entity to find the
+        // handles of the same entities in the snapshot.
+        let body_entities: HashMap<_, _> = self.entity2body.iter().map(|(e, h)| (*h, *e)).collect();
+        let collider_entities: HashMap<_, _> =
+            self.entity2collider.iter().map(|(e, h)| (*h, *e)).collect();
+        let transforms = by_entity(self.last_body_transform_set.drain(), &body_entities);
+        let radial_gravity_forces = by_entity(self.radial_gravity_forces.drain(), &body_entities);
+        let histories = by_entity(self.kinematic_position_histories.drain(), &body_entities);
+        let contacts = by_entity(
+            self.collision_event_contacts.drain().map(|h| (h, ())),
+            &collider_entities,
+        );
+        let ccd_impact_colliders = by_entity(
+            self.ccd_impact_colliders.drain().map(|h| (h, ())),
+            &collider_entities,
+        );
+        let filters = by_entity(self.collision_event_filters.drain(), &collider_entities);
+
+        self.islands = snapshot.islands;
+        self.broad_phase = snapshot.broad_phase;
+        self.narrow_phase = snapshot.narrow_phase;
+        self.bodies = snapshot.bodies;
+        self.colliders = snapshot.colliders;
+        self.impulse_joints = snapshot.impulse_joints;
+        self.multibody_joints = snapshot.multibody_joints;
+        self.ccd_solver = snapshot.ccd_solver;
+        self.integration_parameters = snapshot.integration_parameters;
+        self.step_index = snapshot.step_index;
+        self.world_body = snapshot.world_body;
+
+        let world_body = self.world_body;
+        self.entity2body = self
+            .bodies
+            .iter()
+            .filter(|(handle, _)| Some(*handle) != world_body)
+            .map(|(handle, rb)| (Entity::from_bits(rb.user_data as u64), handle))
+            .collect();
+        self.entity2collider = self
+            .colliders
+            .iter()
+            .map(|(handle, co)| (Entity::from_bits(co.user_data as u64), handle))
+            .collect();
+        self.entity2impulse_joint = snapshot.impulse_joint_entities.into_iter().collect();
+        self.entity2multibody_joint = snapshot.multibody_joint_entities.into_iter().collect();
+        self.welded_bodies = snapshot.welded_bodies.into_iter().collect();
+
+        self.last_body_transform_set = by_handle(transforms, &self.entity2body).collect();
+        self.radial_gravity_forces = by_handle(radial_gravity_forces, &self.entity2body).collect();
+        self.kinematic_position_histories = by_handle(histories, &self.entity2body).collect();
+        self.collision_event_contacts = by_handle(contacts, &self.entity2collider)
+            .map(|(h, _)| h)
+            .collect();
+        self.ccd_impact_colliders = by_handle(ccd_impact_colliders, &self.entity2collider)
+            .map(|(h, _)| h)
+            .collect();
+        self.collision_event_filters = by_handle(filters, &self.entity2collider).collect();
+
+        // Transient state of the replaced world.
+        self.deleted_colliders.clear();
+        self.deleted_sensors.clear();
+        self.collision_event_details.clear();
+        self.sensor_events.clear();
+        self.ccd_impacts.clear();
+        #[cfg(feature = "debug-stream")]
+        self.debug_stream_frames.clear();
+        self.one_way_platforms.clear();
+        self.fluid_volumes.clear();
+
+        self.update_query_pipeline();
+        self.restored = true;
+    }
+
     /// The map from entities to rigid-body handles.
     pub fn entity2body(&self) -> &HashMap<Entity, RigidBodyHandle> {
         &self.entity2body
@@ -433,18 +1158,17 @@ impl RapierContext {
                 );
 
                 if options.apply_impulse_to_dynamic_bodies {
-                    for collision in &*collisions {
-                        controller.solve_character_collision_impulses(
-                            dt,
-                            bodies,
-                            colliders,
-                            query_pipeline,
-                            (&scaled_shape).into(),
-                            shape_mass,
-                            collision,
-                            filter,
-                        )
-                    }
+                    options.pushing.solve_character_collision_impulses(
+                        &controller,
+                        dt,
+                        bodies,
+                        colliders,
+                        query_pipeline,
+                        (&scaled_shape).into(),
+                        shape_mass,
+                        collisions,
+                        filter,
+                    );
                 }
 
                 result
@@ -457,6 +1181,472 @@ impl RapierContext {
         }
     }
 
+    /// Moves a character exactly like the [`KinematicCharacterController`] system does, without
+    /// modifying any component.
+    ///
+    /// The result only depends on the state of this context and on the given parameters, so this
+    /// can be called outside of the built-in system, e.g., to resimulate the movements of a
+    /// character during the prediction and reconciliation of rollback netcode. The caller is
+    /// responsible for applying the returned output to the character: adding its
+    /// `effective_translation` (and rotating it by its `inherited_angle` around `up`), and giving
+    /// it back as `previous_output` for the next movement. The one-way platforms and fluid volumes
+    /// seen by the character are the ones known at the last update of the character controllers.
+    ///
+    /// The dynamic rigid-bodies in the way of the character are only pushed if
+    /// `push_dynamic_bodies` is `true` (and `KinematicCharacterController::apply_impulse_to_dynamic_bodies`
+    /// is set), in which case the pushing impulses are applied to the rigid-bodies of this
+    /// context, and their sum is reported by the output’s `push_impulse`. Otherwise, the
+    /// rigid-bodies of this context are left untouched.
+    ///
+    /// Returns `None` if the `up` vector of the controller is zero.
+    ///
+    /// # Parameters
+    /// * `controller`: the character controller. Its `translation` is ignored, but its `jump` and
+    ///   `drop_through` flags are applied.
+    /// * `translation`: the desired translation of the character.
+    /// * `shape`: the shape of the character.
+    /// * `shape_translation`: the initial position of the shape.
+    /// * `shape_rotation`: the rotation of the shape.
+    /// * `previous_output`: the output of the previous movement of the character, if any.
+    /// * `config`: the configuration of the physics, giving the gravity used by
+    ///   `KinematicCharacterController::vertical_movement` (if it doesn’t have a custom gravity)
+    ///   and the subdivisions of the scaled shape of the character.
+    /// * `dt`: the timestep used by `KinematicCharacterController::vertical_movement`.
+    /// * `filter`: indicates what collider or rigid-body needs to be ignored by the obstacle detection.
+    /// * `push_dynamic_bodies`: whether the impulses pushing the dynamic bodies in the way of the
+    ///   character are applied to them.
+    ///
+    /// The mass of the character used to push dynamic bodies is
+    /// `KinematicCharacterController::custom_mass` (or zero if it isn’t set).
+    #[allow(clippy::too_many_arguments)]
+    pub fn move_character(
+        &mut self,
+        controller: &KinematicCharacterController,
+        translation: Vect,
+        shape: &Collider,
+        shape_translation: Vect,
+        shape_rotation: Rot,
+        previous_output: Option<&KinematicCharacterControllerOutput>,
+        config: &RapierConfiguration,
+        dt: Real,
+        filter: QueryFilter,
+        push_dynamic_bodies: bool,
+    ) -> Option<KinematicCharacterControllerOutput> {
+        let physics_scale = self.physics_scale;
+        let raw_controller = controller.to_raw(physics_scale)?;
+        let mut scaled_shape = shape.clone();
+        scaled_shape.set_scale(shape.scale / physics_scale, config.scaled_shape_subdivision);
+
+        let predicate = filter.has_entity_test().then_some(
+            move |_: ColliderHandle, collider: &rapier::geometry::Collider| {
+                filter.test_entity(Entity::from_bits(collider.user_data as u64))
+            },
+        );
+        let raw_filter = RapierQueryFilter {
+            flags: filter.flags,
+            groups: filter.groups,
+            exclude_collider: filter
+                .exclude_collider
+                .and_then(|c| self.entity2collider.get(&c).copied()),
+            exclude_rigid_body: filter
+                .exclude_rigid_body
+                .and_then(|b| self.entity2body.get(&b).copied()),
+            predicate: predicate
+                .as_ref()
+                .map(|p| p as &dyn Fn(ColliderHandle, &rapier::geometry::Collider) -> bool),
+        };
+
+        Some(self.move_character_raw(
+            controller,
+            raw_controller,
+            translation,
+            &*scaled_shape.raw,
+            (shape_translation / physics_scale, shape_rotation).into(),
+            controller.custom_mass.unwrap_or(0.0),
+            previous_output,
+            config.gravity,
+            dt,
+            raw_filter,
+            push_dynamic_bodies && controller.apply_impulse_to_dynamic_bodies,
+        ))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn move_character_raw(
+        &mut self,
+        controller: &KinematicCharacterController,
+        mut raw_controller: rapier::control::KinematicCharacterController,
+        mut translation: Vect,
+        character_shape: &dyn Shape,
+        character_pos: Isometry<Real>,
+        character_mass: Real,
+        output: Option<&KinematicCharacterControllerOutput>,
+        gravity: Vect,
+        dt: Real,
+        filter: RapierQueryFilter,
+        push_dynamic_bodies: bool,
+    ) -> KinematicCharacterControllerOutput {
+        let physics_scale = self.physics_scale;
+        let one_way_platforms = &self.one_way_platforms;
+        let fluid_volumes = &self.fluid_volumes;
+        let up = *raw_controller.up;
+        let up_vect: Vect = up.into();
+
+        // Measure the submersion of the character along a vertical ray through its center,
+        // from its top down to the surface of the fluid volumes it overlaps.
+        let character_aabb = character_shape.compute_aabb(&character_pos);
+        let height = character_aabb.extents().dot(&up.abs());
+        let character_top = character_aabb.center() + up * (height / 2.0);
+        let (submersion, fluid) = fluid_volumes
+            .iter()
+            .filter_map(|(handle, fluid)| {
+                let collider = self.colliders.get(*handle)?;
+                rapier::parry::query::intersection_test(
+                    &character_pos,
+                    character_shape,
+                    collider.position(),
+                    collider.shape(),
+                )
+                .ok()
+                .filter(|intersecting| *intersecting)?;
+                let depth = collider.shape().cast_ray(
+                    collider.position(),
+                    &Ray::new(character_top, -up),
+                    height,
+                    true,
+                )?;
+                Some(((height - depth) / height, fluid))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(submersion, fluid)| (submersion, Some(fluid)))
+            .unwrap_or((0.0, None));
+        let swimming_fluid =
+            fluid.filter(|_| submersion > 0.0 && submersion >= controller.min_swimming_submersion);
+        if swimming_fluid.is_some() {
+            raw_controller.snap_to_ground = None;
+        }
+
+        let grounded = output.map(|output| output.grounded).unwrap_or(false);
+        let mut vertical_velocity = output.map(|output| output.vertical_velocity).unwrap_or(0.0);
+        let mut airborne_time = output
+            .map(|output| output.airborne_time)
+            .unwrap_or(Real::MAX);
+        if let Some(vertical) = controller.vertical_movement {
+            let gravity = vertical.gravity.unwrap_or_else(|| -gravity.dot(up_vect));
+            if controller.jump && swimming_fluid.is_none() && airborne_time <= vertical.coyote_time
+            {
+                vertical_velocity = vertical.jump_speed;
+                airborne_time = Real::MAX;
+            } else if grounded && vertical_velocity < 0.0 {
+                vertical_velocity = 0.0;
+            }
+
+            if let Some(fluid) = swimming_fluid {
+                let buoyancy = gravity * fluid.density * submersion;
+                vertical_velocity =
+                    (vertical_velocity + (buoyancy - gravity) * dt) / (1.0 + fluid.drag * dt);
+            } else {
+                vertical_velocity =
+                    (vertical_velocity - gravity * dt).max(-vertical.terminal_velocity);
+            }
+            translation += up_vect * vertical_velocity * dt;
+        }
+
+        // Slide down the steep slope the character stands on.
+        let mut slide_velocity = Vect::ZERO;
+        if let SteepSlopeBehavior::Slide { acceleration } = controller.steep_slope_behavior {
+            if let Some(output) = output.filter(|output| output.on_steep_slope) {
+                let normal = output.ground_normal;
+                let downhill = (normal * normal.dot(up_vect) - up_vect).normalize_or_zero();
+                slide_velocity = output.slide_velocity + downhill * acceleration * dt;
+                translation += slide_velocity * dt;
+            }
+        }
+
+        // Inherit the motion of the ground since the character was last moved.
+        let inheritance = controller.platform_inheritance;
+        let mut platform_translation = Vector::zeros();
+        let mut platform_angle = 0.0;
+        if let Some((ground, ground_pos)) = output
+            .and_then(|output| output.ground_position)
+            .and_then(|(handle, pos)| Some((self.colliders.get(handle)?, pos)))
+            // Colliders without a moving rigid-body are only moved by teleportation.
+            .filter(|(ground, _)| {
+                ground
+                    .parent()
+                    .and_then(|h| self.bodies.get(h))
+                    .map(|rb| !rb.is_fixed())
+                    .unwrap_or(false)
+            })
+        {
+            let delta = ground.position() * ground_pos.inverse();
+            let character_point = Point::from(character_pos.translation.vector);
+            let displacement = delta * character_point - character_point;
+            let vertical = up * displacement.dot(&up);
+            platform_translation = (displacement - vertical) * inheritance.horizontal
+                + vertical * inheritance.vertical;
+
+            #[cfg(feature = "dim2")]
+            {
+                platform_angle = delta.rotation.angle() * inheritance.angular;
+            }
+            #[cfg(feature = "dim3")]
+            {
+                let twist = delta.rotation.imag().dot(&up);
+                platform_angle = 2.0 * twist.atan2(delta.rotation.w) * inheritance.angular;
+            }
+        }
+
+        let up_extent = character_shape.compute_local_aabb().extents().dot(&up);
+        let eval_length = |length: CharacterLength| match length {
+            CharacterLength::Absolute(length) => length,
+            CharacterLength::Relative(length) => length * up_extent,
+        };
+        let offset = eval_length(raw_controller.offset);
+        let desired_translation = Vector::from(translation / physics_scale) + platform_translation;
+
+        // One-way platforms are only solid for characters moving toward their solid side,
+        // and that aren’t already inside of them.
+        let mut dropped_platforms = output
+            .map(|output| output.dropped_platforms.clone())
+            .unwrap_or_default();
+        if controller.drop_through {
+            if let Some((ground, _)) = output.and_then(|output| output.ground_position) {
+                if one_way_platforms.contains_key(&ground) && !dropped_platforms.contains(&ground) {
+                    dropped_platforms.push(ground);
+                }
+            }
+        }
+        let is_inside_platform = |pos: &Isometry<Real>, collider: &rapier::geometry::Collider| {
+            rapier::parry::query::contact(
+                pos,
+                character_shape,
+                collider.position(),
+                collider.shape(),
+                0.0,
+            )
+            .ok()
+            .flatten()
+            .map(|contact| contact.dist < -offset)
+            .unwrap_or(false)
+        };
+        let user_predicate = filter.predicate;
+        let one_way_predicate = |handle: ColliderHandle, collider: &rapier::geometry::Collider| {
+            if let Some(predicate) = user_predicate {
+                if !predicate(handle, collider) {
+                    return false;
+                }
+            }
+
+            // Characters swim through fluid volumes.
+            if fluid_volumes.contains_key(&handle) {
+                return false;
+            }
+
+            match one_way_platforms.get(&handle) {
+                Some(direction) => {
+                    !dropped_platforms.contains(&handle)
+                        && desired_translation.dot(&(collider.rotation() * direction)) <= 0.0
+                        && !is_inside_platform(&character_pos, collider)
+                }
+                None => true,
+            }
+        };
+        let mut filter = filter;
+        if !one_way_platforms.is_empty() || !fluid_volumes.is_empty() {
+            filter.predicate = Some(&one_way_predicate);
+        }
+
+        let collisions = &mut self.character_collisions_collector;
+        collisions.clear();
+
+        // The motion of the ground is already inherited above, so the built-in (velocity-based)
+        // motion of the character along kinematic bodies is disabled with a zero timestep.
+        let mut movement = raw_controller.move_shape(
+            0.0,
+            &self.bodies,
+            &self.colliders,
+            &self.query_pipeline,
+            character_shape,
+            &character_pos,
+            desired_translation,
+            filter,
+            |c| collisions.push(c),
+        );
+
+        // Keep the character on the ground when it walks down stairs or slopes.
+        if let Some(snap_distance) = controller.snap_to_ground_while_descending {
+            let snap_distance = eval_length(snap_distance.map_absolute(|x| x / physics_scale));
+            if grounded
+                && swimming_fluid.is_none()
+                && !movement.grounded
+                && vertical_velocity <= 0.0
+                && movement.translation.dot(&up) <= 1.0e-5
+            {
+                if let Some((_, toi)) = self.query_pipeline.cast_shape(
+                    &self.bodies,
+                    &self.colliders,
+                    &(Translation::from(movement.translation) * character_pos),
+                    &-up,
+                    character_shape,
+                    snap_distance + offset,
+                    false,
+                    filter,
+                ) {
+                    movement.translation -= up * (toi.toi - offset).max(0.0);
+                    movement.grounded = true;
+                }
+            }
+        }
+
+        let ground_hit = if movement.grounded {
+            self.query_pipeline.cast_shape(
+                &self.bodies,
+                &self.colliders,
+                &(Translation::from(movement.translation) * character_pos),
+                &-up,
+                character_shape,
+                offset * 2.0,
+                false,
+                filter,
+            )
+        } else {
+            None
+        };
+        let ground = ground_hit
+            .and_then(|(handle, _)| Some((handle, *self.colliders.get(handle)?.position())));
+
+        // Stop dropping through the platforms the character left from below.
+        let new_character_pos = Translation::from(movement.translation) * character_pos;
+        let dropped_platforms: Vec<_> = dropped_platforms
+            .iter()
+            .copied()
+            .filter(
+                |handle| match (self.colliders.get(*handle), one_way_platforms.get(handle)) {
+                    (Some(collider), Some(direction)) => {
+                        let direction = collider.rotation() * direction;
+                        is_inside_platform(&new_character_pos, collider)
+                            || (new_character_pos.translation.vector - collider.translation())
+                                .dot(&direction)
+                                >= 0.0
+                    }
+                    _ => false,
+                },
+            )
+            .collect();
+        let ground_normal: Vect = ground_hit
+            .map(|(_, toi)| toi.normal1.into_inner())
+            .unwrap_or(up)
+            .into();
+        let slope_angle = if movement.grounded {
+            ground_normal.dot(up_vect).clamp(-1.0, 1.0).acos()
+        } else {
+            0.0
+        };
+        let on_steep_slope = slope_angle > raw_controller.max_slope_climb_angle;
+
+        let horizontal_translation = desired_translation - up * desired_translation.dot(&up);
+        let ledge = controller.ledge_detection.and_then(|ledge_detection| {
+            ledge_detection.detect(
+                physics_scale,
+                &raw_controller,
+                &self.bodies,
+                &self.colliders,
+                &self.query_pipeline,
+                character_shape,
+                &new_character_pos,
+                &horizontal_translation.try_normalize(1.0e-5)?,
+                filter,
+            )
+        });
+
+        let (left_wall, right_wall) = controller
+            .wall_detection
+            .map(|wall_detection| {
+                wall_detection.detect(
+                    physics_scale,
+                    &raw_controller,
+                    &self.bodies,
+                    &self.colliders,
+                    &self.query_pipeline,
+                    character_shape,
+                    &new_character_pos,
+                    horizontal_translation.try_normalize(1.0e-5),
+                    filter,
+                )
+            })
+            .unwrap_or_default();
+
+        let push_impulse = if push_dynamic_bodies {
+            controller.pushing.solve_character_collision_impulses(
+                &raw_controller,
+                self.integration_parameters.dt,
+                &mut self.bodies,
+                &self.colliders,
+                &self.query_pipeline,
+                character_shape,
+                character_mass,
+                collisions,
+                filter,
+            )
+        } else {
+            Vector::zeros()
+        };
+
+        if controller.vertical_movement.is_some() {
+            let effective_vertical = movement.translation.dot(&up) * physics_scale;
+            if movement.grounded && vertical_velocity <= 0.0 {
+                vertical_velocity = 0.0;
+                airborne_time = 0.0;
+            } else {
+                // Stop jumping when hitting a ceiling.
+                if vertical_velocity > 0.0 && effective_vertical < vertical_velocity * dt * 0.5 {
+                    vertical_velocity = 0.0;
+                }
+                airborne_time += dt;
+            }
+        }
+
+        let ground_entity = ground.and_then(|(handle, _)| self.collider_entity(handle));
+        let collisions: Vec<_> = self
+            .character_collisions_collector
+            .iter()
+            .filter_map(|c| CharacterCollision::from_raw(self, c))
+            .collect();
+        let mut touching: Vec<_> = collisions
+            .iter()
+            .map(|collision| collision.entity)
+            .chain(ground_entity)
+            .collect();
+        touching.sort();
+        touching.dedup();
+
+        KinematicCharacterControllerOutput {
+            desired_translation: translation, // Already takes the physics_scale into account.
+            effective_translation: (movement.translation * physics_scale).into(),
+            inherited_angle: platform_angle,
+            grounded: movement.grounded,
+            collisions,
+            ground: ground_entity,
+            ground_normal,
+            slope_angle,
+            on_steep_slope,
+            slide_velocity,
+            ledge,
+            left_wall,
+            right_wall,
+            submersion,
+            swimming: swimming_fluid.is_some(),
+            ground_position: ground,
+            dropped_platforms,
+            vertical_velocity,
+            push_impulse: (push_impulse * physics_scale).into(),
+            airborne_time,
+            touching,
+            resize_blocked: output.map(|output| output.resize_blocked).unwrap_or(false),
+        }
+    }
+
     /// Find the closest intersection between a ray and a set of collider.
     ///
     /// # Parameters
@@ -534,6 +1724,190 @@ impl RapierContext {
             .map(|e| (e, RayIntersection::from_rapier(result, ray_origin, ray_dir)))
     }
 
+    /// Find the closest intersection between each ray of a batch and a set of collider.
+    ///
+    /// The rays are cast in parallel on the [`ComputeTaskPool`] set up by Bevy’s `CorePlugin`,
+    /// unless the `filter` has a predicate or an entity set (which aren’t thread-safe), in which
+    /// case they are cast sequentially.
+    ///
+    /// # Parameters
+    /// * `rays`: the origin and the direction of each ray to cast.
+    /// * `max_toi`: the maximum time-of-impact that can be reported by each cast.
+    /// * `solid`: if this is `true` an impact at time 0.0 (i.e. at the ray origin) is returned if
+    ///   it starts inside of a shape. If this `false` then the ray will hit the shape's boundary
+    ///   even if its starts inside of it.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    ///
+    /// Returns the closest intersection of each ray, in the same order as `rays`.
+    pub fn cast_rays_batch(
+        &self,
+        rays: &[(Vect, Vect)],
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Vec<Option<(Entity, RayIntersection)>> {
+        if filter.has_entity_test() {
+            return rays
+                .iter()
+                .map(|(origin, dir)| {
+                    self.cast_ray_and_get_normal(*origin, *dir, max_toi, solid, filter)
+                })
+                .collect();
+        }
+
+        let (flags, groups) = (filter.flags, filter.groups);
+        let (exclude_collider, exclude_rigid_body) =
+            (filter.exclude_collider, filter.exclude_rigid_body);
+        let task_pool = ComputeTaskPool::get();
+        rays.par_splat_map(task_pool, None, |rays| {
+            let filter = QueryFilter {
+                flags,
+                groups,
+                exclude_collider,
+                exclude_rigid_body,
+                predicate: None,
+                entities: None,
+            };
+            rays.iter()
+                .map(|(origin, dir)| {
+                    self.cast_ray_and_get_normal(*origin, *dir, max_toi, solid, filter)
+                })
+                .collect::<Vec<_>>()
+        })
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    /// Checks whether anything blocks the view between two entities.
+    ///
+    /// A ray is cast between the anchor points of the two entities, ignoring the colliders attached
+    /// to the entities themselves, as well as the colliders attached to their rigid-bodies.
+    ///
+    /// # Parameters
+    /// * `from` - The entity looking at `to`.
+    /// * `from_anchor` - The point `from` looks from (e.g. its eyes), relative to the rigid-body
+    ///   or collider of `from`.
+    /// * `to` - The entity being looked at.
+    /// * `to_anchor` - The point looked at on `to`, relative to the rigid-body or collider of `to`.
+    /// * `occluder_filter`: set of rules used to determine which collider can block the view.
+    ///
+    /// Returns `None` if one of the entities has neither a rigid-body nor a collider.
+    pub fn line_of_sight(
+        &self,
+        from: Entity,
+        from_anchor: Vect,
+        to: Entity,
+        to_anchor: Vect,
+        occluder_filter: QueryFilter,
+    ) -> Option<LineOfSight> {
+        let start = self.anchor_position(from, from_anchor)?;
+        let end = self.anchor_position(to, to_anchor)?;
+
+        let user_predicate = occluder_filter.predicate;
+        let predicate = |entity: Entity| {
+            let parent = self.collider_parent(entity);
+            entity != from
+                && entity != to
+                && parent != Some(from)
+                && parent != Some(to)
+                && user_predicate
+                    .map(|predicate| predicate(entity))
+                    .unwrap_or(true)
+        };
+        let filter = QueryFilter {
+            predicate: Some(&predicate),
+            ..occluder_filter
+        };
+
+        Some(match self.cast_ray(start, end - start, 1.0, true, filter) {
+            Some((entity, _)) => LineOfSight::Blocked(entity),
+            None => LineOfSight::Clear,
+        })
+    }
+
+    /// Find the entities visible from a vision cone, sorted by increasing distance.
+    ///
+    /// A collider is visible if its center is within the range and the angle of the cone, and no
+    /// other collider blocks the ray cast from the origin of the cone to its center. The colliders
+    /// attached to the viewer should be excluded by both filters, so they don’t block its view.
+    ///
+    /// # Parameters
+    /// * `cone` - The origin, direction, angle and range of the view.
+    /// * `filter`: set of rules used to determine which collider can be seen.
+    /// * `occluder_filter`: set of rules used to determine which collider can block the view.
+    ///
+    /// Returns the entity of each visible collider with the distance to its center.
+    pub fn visible_entities(
+        &self,
+        cone: VisionCone,
+        filter: QueryFilter,
+        occluder_filter: QueryFilter,
+    ) -> Vec<(Entity, Real)> {
+        let mut candidates = vec![];
+        self.intersections_with_shape(
+            cone.origin,
+            Rot::default(),
+            &Collider::ball(cone.range),
+            filter,
+            |entity| {
+                candidates.push(entity);
+                true
+            },
+        );
+
+        let user_predicate = occluder_filter.predicate;
+        let mut visible: Vec<_> = candidates
+            .into_iter()
+            .filter_map(|entity| {
+                let collider = self.colliders.get(*self.entity2collider.get(&entity)?)?;
+                let center = Vect::from(*collider.translation()) * self.physics_scale;
+                let to_center = center - cone.origin;
+                let distance = to_center.length();
+
+                if distance > cone.range
+                    || (distance > 0.0 && cone.direction.angle_between(to_center) > cone.half_angle)
+                {
+                    return None;
+                }
+
+                let parent = self.collider_parent(entity);
+                let predicate = |occluder: Entity| {
+                    occluder != entity
+                        && (parent.is_none() || self.collider_parent(occluder) != parent)
+                        && user_predicate
+                            .map(|predicate| predicate(occluder))
+                            .unwrap_or(true)
+                };
+                let occluder_filter = QueryFilter {
+                    predicate: Some(&predicate),
+                    ..occluder_filter
+                };
+
+                self.cast_ray(cone.origin, to_center, 1.0, true, occluder_filter)
+                    .is_none()
+                    .then_some((entity, distance))
+            })
+            .collect();
+
+        visible.sort_by(|a, b| a.1.total_cmp(&b.1));
+        visible
+    }
+
+    /// The world-space position of a point given relative to the rigid-body, or else the collider,
+    /// of an entity.
+    fn anchor_position(&self, entity: Entity, anchor: Vect) -> Option<Vect> {
+        let position = match self.entity2body.get(&entity) {
+            Some(handle) => self.bodies.get(*handle)?.position(),
+            None => self
+                .colliders
+                .get(*self.entity2collider.get(&entity)?)?
+                .position(),
+        };
+        let point = position * Point::from(anchor / self.physics_scale);
+        Some(Vect::from(point) * self.physics_scale)
+    }
+
     /// Find the all intersections between a ray and a set of collider and passes them to a callback.
     ///
     /// # Parameters
@@ -581,6 +1955,104 @@ impl RapierContext {
         });
     }
 
+    /// Find all the intersections between a ray and a set of collider, sorted by increasing
+    /// time-of-impact.
+    ///
+    /// # Parameters
+    /// * `ray_origin`: the starting point of the ray to cast.
+    /// * `ray_dir`: the direction of the ray to cast.
+    /// * `max_toi`: the maximum time-of-impact that can be reported by this cast. This effectively
+    ///   limits the length of the ray to `ray.dir.norm() * max_toi`. Use `Real::MAX` for an unbounded ray.
+    /// * `solid`: if this is `true` an impact at time 0.0 (i.e. at the ray origin) is returned if
+    ///   it starts inside of a shape. If this `false` then the ray will hit the shape's boundary
+    ///   even if its starts inside of it.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    /// * `max_hits`: if set, only this number of closest intersections are returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn cast_ray_all(
+        &self,
+        ray_origin: Vect,
+        ray_dir: Vect,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+        max_hits: Option<usize>,
+    ) -> Vec<(Entity, RayIntersection)> {
+        let mut hits = vec![];
+        self.intersections_with_ray(ray_origin, ray_dir, max_toi, solid, filter, |e, inter| {
+            hits.push((e, inter));
+            true
+        });
+
+        hits.sort_by(|a, b| a.1.toi.total_cmp(&b.1.toi));
+        if let Some(max_hits) = max_hits {
+            hits.truncate(max_hits);
+        }
+        hits
+    }
+
+    /// Find the collider under a position on the screen, e.g., to pick the entity under the cursor.
+    ///
+    /// In 3D, a ray is cast from the camera through the given position. In 2D, the collider
+    /// containing the point under the given position is returned, with a zero normal.
+    ///
+    /// # Parameters
+    /// * `camera`: the camera rendering the screen.
+    /// * `camera_transform`: the global transform of the camera.
+    /// * `cursor_pos`: the position in the viewport of the camera, in logical pixels from its
+    ///   bottom-left corner (e.g., `Window::cursor_position` if the camera renders to the whole
+    ///   window).
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    pub fn cast_ray_from_screen(
+        &self,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cursor_pos: Vec2,
+        filter: QueryFilter,
+    ) -> Option<(Entity, RayIntersection)> {
+        let viewport_size = camera.logical_viewport_size()?;
+        let ndc = cursor_pos * 2.0 / viewport_size - Vec2::ONE;
+        let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+        self.cast_ray_from_ndc(ndc_to_world, ndc, filter)
+    }
+
+    pub(crate) fn cast_ray_from_ndc(
+        &self,
+        ndc_to_world: Mat4,
+        ndc: Vec2,
+        filter: QueryFilter,
+    ) -> Option<(Entity, RayIntersection)> {
+        // With a reversed depth, the near plane is at `z = 1`.
+        let near = ndc_to_world.project_point3(ndc.extend(1.0));
+
+        #[cfg(feature = "dim2")]
+        {
+            let point = near.truncate();
+            let mut hit = None;
+            self.intersections_with_point(point, filter, |e| {
+                hit = Some(e);
+                false
+            });
+
+            hit.map(|e| {
+                let intersection = RayIntersection {
+                    toi: 0.0,
+                    point,
+                    normal: Vect::ZERO,
+                    feature: FeatureId::Unknown,
+                };
+                (e, intersection)
+            })
+        }
+
+        #[cfg(feature = "dim3")]
+        {
+            let far = ndc_to_world.project_point3(ndc.extend(Real::EPSILON));
+            let dir = (far - near).try_normalize()?;
+            self.cast_ray_and_get_normal(near, dir, Real::MAX, true, filter)
+        }
+    }
+
     /// Gets the handle of up to one collider intersecting the given shape.
     ///
     /// # Parameters
@@ -643,6 +2115,229 @@ impl RapierContext {
             .map(|e| (e, PointProjection::from_rapier(self.physics_scale, result)))
     }
 
+    /// Find the `k` colliders closest to a point, sorted by increasing distance.
+    ///
+    /// The colliders are searched in a region growing around the point, so only the colliders
+    /// close to the point are tested.
+    ///
+    /// # Parameters
+    /// * `point` - The point the distances are computed from.
+    /// * `k` - The maximum number of colliders to return.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    ///
+    /// Returns the entity of each collider, its distance to the point, and the point of the
+    /// collider closest to `point`. The distance is zero and the closest point is `point` itself
+    /// if the point is inside of the collider.
+    pub fn nearest_colliders(
+        &self,
+        point: Vect,
+        k: usize,
+        filter: QueryFilter,
+    ) -> Vec<(Entity, Real, Vect)> {
+        let scaled_point: Point<Real> = (point / self.physics_scale).into();
+        let mut nearest = vec![];
+
+        if k == 0 {
+            return nearest;
+        }
+
+        self.with_query_filter(filter, |filter| {
+            let mut half_extent: Real = 1.0;
+
+            loop {
+                let aabb = RapierAabb::from_half_extents(scaled_point, Vector::repeat(half_extent));
+                let mut num_visited = 0;
+                nearest.clear();
+
+                self.query_pipeline
+                    .colliders_with_aabb_intersecting_aabb(&aabb, |handle| {
+                        num_visited += 1;
+                        if let Some(collider) = self.colliders.get(*handle) {
+                            if filter.test(&self.bodies, *handle, collider) {
+                                let projection = collider.shape().project_point(
+                                    collider.position(),
+                                    &scaled_point,
+                                    true,
+                                );
+                                let distance = (projection.point - scaled_point).norm();
+                                if let Some(entity) = self.collider_entity(*handle) {
+                                    nearest.push((entity, distance, projection.point.into()));
+                                }
+                            }
+                        }
+                        true
+                    });
+
+                nearest.sort_by(|a, b| a.1.total_cmp(&b.1));
+                nearest.truncate(k);
+
+                // Any collider closer than the k-th collider found intersects the region, so the
+                // result is exact once the k-th collider is inside of the region.
+                let complete = nearest.len() == k && nearest[k - 1].1 <= half_extent;
+                if complete || num_visited >= self.colliders.len() || half_extent >= Real::MAX / 4.0
+                {
+                    break;
+                }
+
+                half_extent *= 2.0;
+            }
+        });
+
+        nearest
+            .into_iter()
+            .map(|(entity, distance, point)| {
+                (
+                    entity,
+                    distance * self.physics_scale,
+                    point * self.physics_scale,
+                )
+            })
+            .collect()
+    }
+
+    /// Computes the distance between a point and the collider attached to the given entity.
+    ///
+    /// Returns zero if the point is inside of the collider, and `None` if the entity has no
+    /// collider.
+    pub fn distance_to_collider(&self, point: Vect, collider: Entity) -> Option<Real> {
+        let collider = self.colliders.get(*self.entity2collider.get(&collider)?)?;
+        let distance = collider.shape().distance_to_point(
+            collider.position(),
+            &(point / self.physics_scale).into(),
+            true,
+        );
+        Some(distance * self.physics_scale)
+    }
+
+    /// Computes the distance between a shape and the collider attached to the given entity.
+    ///
+    /// Returns zero if they intersect, and `None` if the entity has no collider or the distance
+    /// between these shapes isn’t supported.
+    pub fn shape_distance_to_collider(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape: &Collider,
+        collider: Entity,
+    ) -> Option<Real> {
+        let collider = self.colliders.get(*self.entity2collider.get(&collider)?)?;
+        let scaled_transform = (shape_pos / self.physics_scale, shape_rot).into();
+        let mut scaled_shape = shape.clone();
+        // TODO: how to set a good number of subdivisions, we don’t have access to the
+        //       RapierConfiguration::scaled_shape_subdivision here.
+        scaled_shape.set_scale(shape.scale / self.physics_scale, 20);
+
+        let distance = rapier::parry::query::distance(
+            &scaled_transform,
+            &*scaled_shape.raw,
+            collider.position(),
+            collider.shape(),
+        )
+        .ok()?;
+        Some(distance * self.physics_scale)
+    }
+
+    /// Computes the distance between the colliders attached to two entities.
+    ///
+    /// Returns zero if they intersect, and `None` if one of the entities has no collider or the
+    /// distance between their shapes isn’t supported.
+    pub fn distance_between(&self, collider1: Entity, collider2: Entity) -> Option<Real> {
+        let collider1 = self.colliders.get(*self.entity2collider.get(&collider1)?)?;
+        let collider2 = self.colliders.get(*self.entity2collider.get(&collider2)?)?;
+        let distance = rapier::parry::query::distance(
+            collider1.position(),
+            collider1.shape(),
+            collider2.position(),
+            collider2.shape(),
+        )
+        .ok()?;
+        Some(distance * self.physics_scale)
+    }
+
+    /// Computes the closest points between the colliders attached to two entities.
+    ///
+    /// Returns `None` if one of the entities has no collider or the closest points between their
+    /// shapes aren’t supported.
+    ///
+    /// # Parameters
+    /// * `collider1` - The entity of the first collider.
+    /// * `collider2` - The entity of the second collider.
+    /// * `max_dist` - The colliders further apart than this distance are reported as
+    ///   `ClosestPoints::Disjoint`.
+    pub fn closest_points_between(
+        &self,
+        collider1: Entity,
+        collider2: Entity,
+        max_dist: Real,
+    ) -> Option<ClosestPoints> {
+        let collider1 = self.colliders.get(*self.entity2collider.get(&collider1)?)?;
+        let collider2 = self.colliders.get(*self.entity2collider.get(&collider2)?)?;
+        let closest_points = rapier::parry::query::closest_points(
+            collider1.position(),
+            collider1.shape(),
+            collider2.position(),
+            collider2.shape(),
+            max_dist / self.physics_scale,
+        )
+        .ok()?;
+        Some(ClosestPoints::from_rapier(
+            self.physics_scale,
+            closest_points,
+        ))
+    }
+
+    /// The triangle hit by a ray on the triangle mesh or heightfield attached to the given entity.
+    ///
+    /// This maps the feature of a ray hit back to the triangle of the source mesh, e.g., to look
+    /// up the UVs or the vertex colors of the mesh at the hit. Returns `None` if the entity has no
+    /// triangle mesh or heightfield collider.
+    ///
+    /// # Parameters
+    /// * `collider` - The entity of the collider hit by the ray.
+    /// * `point` - The world-space intersection point, see [`RayIntersection::point`].
+    /// * `feature` - The feature hit by the ray, see [`RayIntersection::feature`].
+    pub fn triangle_hit(
+        &self,
+        collider: Entity,
+        point: Vect,
+        feature: FeatureId,
+    ) -> Option<TriangleHit> {
+        let collider = self.colliders.get(*self.entity2collider.get(&collider)?)?;
+        let local_point = collider
+            .position()
+            .inverse_transform_point(&(point / self.physics_scale).into());
+        TriangleHit::from_feature(collider.shape(), &local_point, feature)
+    }
+
+    /// The triangle closest to a point on the triangle mesh or heightfield attached to the given
+    /// entity.
+    ///
+    /// This is useful to find the triangle hit by a shape cast, from its world-space witness point
+    /// (see [`Toi::witness1`]). Returns `None` if the entity has no triangle mesh or heightfield
+    /// collider.
+    pub fn closest_triangle(&self, collider: Entity, point: Vect) -> Option<TriangleHit> {
+        let collider = self.colliders.get(*self.entity2collider.get(&collider)?)?;
+        let local_point = collider
+            .position()
+            .inverse_transform_point(&(point / self.physics_scale).into());
+        TriangleHit::closest(collider.shape(), &local_point)
+    }
+
+    /// The cell of the heightfield attached to the given entity, containing the vertical
+    /// projection of a point.
+    ///
+    /// This maps the world-space point of a ray hit (see [`RayIntersection::point`]) or of a
+    /// shape cast hit (see [`Toi::witness1`]) back to the heights of the heightfield, e.g., to
+    /// deform the terrain at the hit. Returns `None` if the entity has no heightfield collider,
+    /// or if the point is outside of the heightfield bounds.
+    pub fn heightfield_cell(&self, collider: Entity, point: Vect) -> Option<HeightfieldCell> {
+        let collider = self.colliders.get(*self.entity2collider.get(&collider)?)?;
+        let local_point = collider
+            .position()
+            .inverse_transform_point(&(point / self.physics_scale).into());
+        HeightfieldCell::at_point(collider.shape(), &local_point)
+    }
+
     /// Find all the colliders containing the given point.
     ///
     /// # Parameters
@@ -713,16 +2408,7 @@ impl RapierContext {
         aabb: Aabb,
         mut callback: impl FnMut(Entity) -> bool,
     ) {
-        #[cfg(feature = "dim2")]
-        let scaled_aabb = RapierAabb {
-            mins: (aabb.min().xy() / self.physics_scale).into(),
-            maxs: (aabb.max().xy() / self.physics_scale).into(),
-        };
-        #[cfg(feature = "dim3")]
-        let scaled_aabb = RapierAabb {
-            mins: (aabb.min() / self.physics_scale).into(),
-            maxs: (aabb.max() / self.physics_scale).into(),
-        };
+        let scaled_aabb = Region::from(aabb).to_rapier(self.physics_scale);
         #[allow(clippy::redundant_closure)]
         // False-positive, we can't move callback, closure becomes `FnOnce`
         let callback = |h: &ColliderHandle| {
@@ -734,6 +2420,39 @@ impl RapierContext {
             .colliders_with_aabb_intersecting_aabb(&scaled_aabb, callback);
     }
 
+    /// Finds the entities of all the colliders with an AABB intersecting the given region.
+    ///
+    /// This only runs the broad-phase test on the colliders’ AABBs, so it is cheap but the
+    /// reported colliders don’t necessarily intersect the region.
+    ///
+    /// # Parameters
+    /// * `region`: the region to test, in Bevy units. This can be a Bevy `Aabb`, a `(min, max)`
+    ///   pair of points, or any other type convertible to a [`Region`].
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene
+    ///   query.
+    pub fn colliders_with_aabb_intersecting(
+        &self,
+        region: impl Into<Region>,
+        filter: QueryFilter,
+    ) -> impl ExactSizeIterator<Item = Entity> {
+        let scaled_aabb = region.into().to_rapier(self.physics_scale);
+
+        let mut entities = vec![];
+        self.with_query_filter(filter, |filter| {
+            self.query_pipeline
+                .colliders_with_aabb_intersecting_aabb(&scaled_aabb, |handle| {
+                    if let Some(collider) = self.colliders.get(*handle) {
+                        if filter.test(&self.bodies, *handle, collider) {
+                            entities.extend(self.collider_entity(*handle));
+                        }
+                    }
+                    true
+                });
+        });
+
+        entities.into_iter()
+    }
+
     /// Casts a shape at a constant linear velocity and retrieve the first collider it hits.
     ///
     /// This is similar to ray-casting except that we are casting a whole shape instead of just a
@@ -780,6 +2499,141 @@ impl RapierContext {
             .map(|e| (e, Toi::from_rapier(self.physics_scale, result)))
     }
 
+    /// Sweeps a shape along a path and retrieve the first collider blocking it.
+    ///
+    /// The shape is cast along each segment of the path in turn, keeping the same rotation, so
+    /// the corners of the path are covered by the casts of both of their segments. Returns `None`
+    /// if the shape can travel the whole path, or if the path has less than two points.
+    ///
+    /// # Parameters
+    /// * `path` - The successive positions of the shape along the path.
+    /// * `shape_rot` - The rotation of the shape along the path.
+    /// * `shape` - The shape to sweep.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    pub fn cast_shape_along_path(
+        &self,
+        path: &[Vect],
+        shape_rot: Rot,
+        shape: &Collider,
+        filter: QueryFilter,
+    ) -> Option<PathHit> {
+        let mut scaled_shape = shape.clone();
+        // TODO: how to set a good number of subdivisions, we don’t have access to the
+        //       RapierConfiguration::scaled_shape_subdivision here.
+        scaled_shape.set_scale(shape.scale / self.physics_scale, 20);
+
+        self.with_query_filter(filter, |filter| {
+            let mut distance = 0.0;
+
+            for (segment, points) in path.windows(2).enumerate() {
+                let (start, end) = (points[0], points[1]);
+                let length = start.distance(end);
+                if length == 0.0 {
+                    continue;
+                }
+
+                let scaled_transform = (start / self.physics_scale, shape_rot).into();
+                let scaled_vel = (end - start) / length / self.physics_scale;
+
+                if let Some((h, result)) = self.query_pipeline.cast_shape(
+                    &self.bodies,
+                    &self.colliders,
+                    &scaled_transform,
+                    &scaled_vel.into(),
+                    &*scaled_shape.raw,
+                    length,
+                    true,
+                    filter,
+                ) {
+                    let toi = Toi::from_rapier(self.physics_scale, result);
+                    return self.collider_entity(h).map(|entity| PathHit {
+                        entity,
+                        segment,
+                        distance: distance + toi.toi,
+                        position: start + (end - start) * (toi.toi / length),
+                        toi,
+                    });
+                }
+
+                distance += length;
+            }
+
+            None
+        })
+    }
+
+    /// Casts a shape at a constant linear velocity and retrieve all the colliders it hits, sorted
+    /// by increasing time-of-impact.
+    ///
+    /// In the resulting `TOI`s, witness and normal 1 refer to the world collider, and are in world
+    /// space.
+    ///
+    /// # Parameters
+    /// * `shape_pos` - The initial position of the shape to cast.
+    /// * `shape_vel` - The constant velocity of the shape to cast (i.e. the cast direction).
+    /// * `shape` - The shape to cast.
+    /// * `max_toi` - The maximum time-of-impact that can be reported by this cast. This effectively
+    ///   limits the distance traveled by the shape to `shapeVel.norm() * maxToi`.
+    /// * `filter`: set of rules used to determine which collider is taken into account by this scene query.
+    /// * `max_hits`: if set, only this number of closest hits are returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn cast_shape_all(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape_vel: Vect,
+        shape: &Collider,
+        max_toi: Real,
+        filter: QueryFilter,
+        max_hits: Option<usize>,
+    ) -> Vec<(Entity, Toi)> {
+        let scaled_transform: Isometry<Real> = (shape_pos / self.physics_scale, shape_rot).into();
+        let scaled_vel: Vector<Real> = (shape_vel / self.physics_scale).into();
+        let mut scaled_shape = shape.clone();
+        // TODO: how to set a good number of subdivisions, we don’t have access to the
+        //       RapierConfiguration::scaled_shape_subdivision here.
+        scaled_shape.set_scale(shape.scale / self.physics_scale, 20);
+
+        let end_transform = Translation::from(scaled_vel * max_toi) * scaled_transform;
+        let swept_aabb = scaled_shape
+            .raw
+            .compute_swept_aabb(&scaled_transform, &end_transform);
+
+        let mut hits = vec![];
+        self.with_query_filter(filter, |filter| {
+            self.query_pipeline
+                .colliders_with_aabb_intersecting_aabb(&swept_aabb, |handle| {
+                    let collider = match self.colliders.get(*handle) {
+                        Some(collider) if filter.test(&self.bodies, *handle, collider) => collider,
+                        _ => return true,
+                    };
+
+                    if let Ok(Some(toi)) = rapier::parry::query::time_of_impact(
+                        collider.position(),
+                        &Vector::zeros(),
+                        collider.shape(),
+                        &scaled_transform,
+                        &scaled_vel,
+                        &*scaled_shape.raw,
+                        max_toi,
+                        true,
+                    ) {
+                        if let Some(entity) = self.collider_entity(*handle) {
+                            let toi = toi.transform1_by(collider.position());
+                            hits.push((entity, Toi::from_rapier(self.physics_scale, toi)));
+                        }
+                    }
+                    true
+                });
+        });
+
+        hits.sort_by(|a, b| a.1.toi.total_cmp(&b.1.toi));
+        if let Some(max_hits) = max_hits {
+            hits.truncate(max_hits);
+        }
+        hits
+    }
+
     /* TODO: we need to wrap the NonlinearRigidMotion somehow.
      *
     /// Casts a shape with an arbitrary continuous motion and retrieve the first collider it hits.
@@ -870,3 +2724,23 @@ impl RapierContext {
         });
     }
 }
+
+// Keys the values attached to handles by the entities of these handles.
+fn by_entity<H: std::hash::Hash + Eq, T>(
+    values: impl Iterator<Item = (H, T)>,
+    entities: &HashMap<H, Entity>,
+) -> Vec<(Entity, T)> {
+    values
+        .filter_map(|(handle, value)| Some((*entities.get(&handle)?, value)))
+        .collect()
+}
+
+// Keys the values attached to entities by the handles of these entities.
+fn by_handle<'a, H: Copy, T: 'a>(
+    values: Vec<(Entity, T)>,
+    handles: &'a HashMap<Entity, H>,
+) -> impl Iterator<Item = (H, T)> + 'a {
+    values
+        .into_iter()
+        .filter_map(|(entity, value)| Some((*handles.get(&entity)?, value)))
+}
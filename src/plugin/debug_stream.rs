@@ -0,0 +1,278 @@
+use crate::math::Vect;
+use crate::plugin::{systems, RapierContext};
+use crate::utils;
+use bevy::prelude::*;
+use rapier::math::{Point, Real};
+use rapier::pipeline::{
+    DebugRenderBackend, DebugRenderMode, DebugRenderObject, DebugRenderPipeline, DebugRenderStyle,
+};
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// A line of the debug-render of the physics world, recorded in a [`DebugStreamFrame`].
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DebugStreamLine {
+    /// The first point of the line.
+    pub a: Vect,
+    /// The second point of the line.
+    pub b: Vect,
+    /// The RGBA color of the line.
+    pub color: [u8; 4],
+}
+
+/// The pose of a rigid-body, recorded in a [`DebugStreamFrame`].
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DebugStreamBody {
+    /// The entity of the rigid-body on the recording app.
+    #[serde(with = "crate::plugin::snapshot::entity_bits")]
+    pub entity: Entity,
+    /// The translation of the rigid-body.
+    pub translation: Vec3,
+    /// The rotation of the rigid-body.
+    pub rotation: Quat,
+}
+
+impl DebugStreamBody {
+    /// The transform of the rigid-body.
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: self.rotation,
+            ..Default::default()
+        }
+    }
+}
+
+/// The debug-render lines and rigid-body poses of the physics world after a simulation step,
+/// recorded by the [`DebugStreamRecorderPlugin`].
+///
+/// The positions are multiplied by the physics scale, like the transforms of the entities.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DebugStreamFrame {
+    /// The `RapierContext::step_index` after the simulation step.
+    pub step: u64,
+    /// The lines of the debug-render.
+    pub lines: Vec<DebugStreamLine>,
+    /// The poses of the rigid-bodies.
+    pub bodies: Vec<DebugStreamBody>,
+}
+
+impl DebugStreamFrame {
+    /// Records the debug-render lines and rigid-body poses of a physics world.
+    pub fn record(context: &RapierContext, pipeline: &mut DebugRenderPipeline) -> Self {
+        let scale = context.physics_scale;
+        let mut backend = DebugStreamBackend {
+            physics_scale: scale,
+            lines: Vec::new(),
+        };
+
+        let unscaled_style = pipeline.style;
+        pipeline.style.rigid_body_axes_length /= scale;
+        pipeline.render(
+            &mut backend,
+            &context.bodies,
+            &context.colliders,
+            &context.impulse_joints,
+            &context.multibody_joints,
+            &context.narrow_phase,
+        );
+        pipeline.style = unscaled_style;
+
+        let mut bodies: Vec<_> = context
+            .entity2body
+            .iter()
+            .filter_map(|(entity, handle)| {
+                let transform =
+                    utils::iso_to_transform(context.bodies.get(*handle)?.position(), scale);
+                Some(DebugStreamBody {
+                    entity: *entity,
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                })
+            })
+            .collect();
+        bodies.sort_by_key(|body| body.entity);
+
+        Self {
+            step: context.step_index,
+            lines: backend.lines,
+            bodies,
+        }
+    }
+
+    /// The pose of the rigid-body of the given entity of the recording app.
+    pub fn body(&self, entity: Entity) -> Option<&DebugStreamBody> {
+        let index = self
+            .bodies
+            .binary_search_by_key(&entity, |body| body.entity)
+            .ok()?;
+        Some(&self.bodies[index])
+    }
+}
+
+struct DebugStreamBackend {
+    physics_scale: Real,
+    lines: Vec<DebugStreamLine>,
+}
+
+impl DebugRenderBackend for DebugStreamBackend {
+    fn draw_line(
+        &mut self,
+        _object: DebugRenderObject,
+        a: Point<Real>,
+        b: Point<Real>,
+        color: [f32; 4],
+    ) {
+        let color = Color::hsla(color[0], color[1], color[2], color[3]).as_rgba_u32();
+        self.lines.push(DebugStreamLine {
+            a: (a.coords * self.physics_scale).into(),
+            b: (b.coords * self.physics_scale).into(),
+            color: color.to_le_bytes(),
+        });
+    }
+}
+
+/// Writes [`DebugStreamFrame`]s to a file or a socket.
+///
+/// Each frame is written with `bincode`, after its length as a little-endian `u32`. Any
+/// [`Write`] can be used, e.g. a `TcpStream` to stream the frames to a viewer, or the adapter
+/// of a WebSocket.
+pub struct DebugStreamWriter {
+    writer: Box<dyn Write + Send + Sync>,
+}
+
+impl DebugStreamWriter {
+    /// A stream writing the frames to the given writer.
+    pub fn new(writer: impl Write + Send + Sync + 'static) -> Self {
+        Self {
+            writer: Box::new(writer),
+        }
+    }
+
+    /// A stream writing the frames to a new file.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(io::BufWriter::new(std::fs::File::create(path)?)))
+    }
+
+    /// Writes a frame, and flushes the writer for it to be read right away.
+    pub fn write_frame(&mut self, frame: &DebugStreamFrame) -> io::Result<()> {
+        let bytes =
+            bincode::serialize(frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let len = u32::try_from(bytes.len())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        self.writer.write_all(&bytes)?;
+        self.writer.flush()
+    }
+}
+
+/// Reads the [`DebugStreamFrame`]s written by a [`DebugStreamWriter`].
+pub struct DebugStreamReader {
+    reader: Box<dyn Read + Send>,
+}
+
+impl DebugStreamReader {
+    /// A stream reading the frames from the given reader.
+    pub fn new(reader: impl Read + Send + 'static) -> Self {
+        Self {
+            reader: Box::new(reader),
+        }
+    }
+
+    /// A stream reading the frames from a file.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::new(io::BufReader::new(std::fs::File::open(path)?)))
+    }
+
+    /// Reads the next frame, or `None` at the end of the stream.
+    pub fn read_frame(&mut self) -> io::Result<Option<DebugStreamFrame>> {
+        let mut len = [0; 4];
+        match self.reader.read_exact(&mut len) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            Err(e) => return Err(e),
+        }
+        let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+        self.reader.read_exact(&mut bytes)?;
+        bincode::deserialize(&bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl Iterator for DebugStreamReader {
+    type Item = io::Result<DebugStreamFrame>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_frame().transpose()
+    }
+}
+
+/// Plugin recording the debug-render of the physics world into a stream after each simulation
+/// step, with the `debug-stream` feature.
+///
+/// This doesn’t need a window nor a renderer, so a headless server can stream its physics
+/// world, e.g. to a file or a socket, to be visualized by a client app with the
+/// `DebugStreamViewerPlugin`. The recording is started by giving a [`DebugStreamWriter`] to the
+/// [`DebugStreamRecorder`] resource.
+///
+/// The frames of all the simulation steps of a frame, e.g. with substeps, are recorded during
+/// the step and written during [`CoreStage::PostUpdate`].
+#[derive(Default)]
+pub struct DebugStreamRecorderPlugin {
+    /// Control some aspects of the render coloring.
+    pub style: DebugRenderStyle,
+    /// Flags to select what part of physics scene is recorded (by default everything is
+    /// recorded).
+    pub mode: DebugRenderMode,
+}
+
+impl Plugin for DebugStreamRecorderPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(DebugStreamRecorder {
+            writer: None,
+            pipeline: DebugRenderPipeline::new(self.style, self.mode),
+        })
+        .add_system_to_stage(CoreStage::PostUpdate, systems::record_debug_stream);
+    }
+}
+
+/// Resource controlling the recording of the [`DebugStreamRecorderPlugin`].
+pub struct DebugStreamRecorder {
+    writer: Option<DebugStreamWriter>,
+    /// Pipeline generating the recorded lines. Access `pipeline.mode` and `pipeline.style`
+    /// to modify the set of recorded elements, and modify the default coloring rules.
+    pub pipeline: DebugRenderPipeline,
+}
+
+impl DebugStreamRecorder {
+    /// Starts recording into the given stream, replacing the current one.
+    pub fn start(&mut self, writer: DebugStreamWriter) {
+        self.writer = Some(writer);
+    }
+
+    /// Stops recording, returning the stream if any.
+    pub fn stop(&mut self) -> Option<DebugStreamWriter> {
+        self.writer.take()
+    }
+
+    /// Whether a recording is started.
+    pub fn is_recording(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes the frames recorded since the last update. The recording stops if a frame can’t
+    /// be written.
+    pub(crate) fn write_frames(&mut self, frames: impl IntoIterator<Item = DebugStreamFrame>) {
+        for frame in frames {
+            let writer = match &mut self.writer {
+                Some(writer) => writer,
+                None => return,
+            };
+            if let Err(err) = writer.write_frame(&frame) {
+                error!("Unable to write the physics debug stream: {}", err);
+                self.writer = None;
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::plugin::systems;
+use bevy::prelude::*;
+use std::collections::VecDeque;
+
+/// Plugin recording the checksum of the physics world after each simulation step, for
+/// deterministic lockstep games.
+///
+/// The checksums of the last steps are kept in the [`LockstepChecksums`] resource. The peers
+/// of a lockstep game exchange them, e.g. with their inputs, and compare them with
+/// [`LockstepChecksums::matches`] or [`LockstepChecksums::first_divergence`] to find the first
+/// step at which their simulations diverged. The checksum is [`RapierContext::checksum`], which
+/// is the same on all the platforms where the simulation is deterministic (see the
+/// `enhanced-determinism` feature).
+///
+/// The checksums are recorded during [`CoreStage::PostUpdate`], so this plugin must be added
+/// with a `RapierPhysicsPlugin` simulating at most one step per frame, e.g. with a
+/// `TimestepMode::Fixed` of a single substep.
+///
+/// [`RapierContext::checksum`]: crate::plugin::RapierContext::checksum
+pub struct LockstepChecksumPlugin {
+    /// The number of checksums kept in the [`LockstepChecksums`].
+    pub capacity: usize,
+}
+
+impl Default for LockstepChecksumPlugin {
+    fn default() -> Self {
+        Self { capacity: 128 }
+    }
+}
+
+impl LockstepChecksumPlugin {
+    /// A plugin keeping the checksums of the last `capacity` steps.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self { capacity }
+    }
+}
+
+impl Plugin for LockstepChecksumPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(LockstepChecksums::new(self.capacity))
+            .add_system_to_stage(CoreStage::PostUpdate, systems::record_lockstep_checksum);
+    }
+}
+
+/// Resource holding the checksums of the physics world after the last simulation steps,
+/// recorded by the [`LockstepChecksumPlugin`].
+///
+/// The steps are identified by the number of simulation steps done before the checksum, i.e. the
+/// `RapierContext::step_index` after the step.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LockstepChecksums {
+    capacity: usize,
+    checksums: VecDeque<(u64, u64)>,
+}
+
+impl LockstepChecksums {
+    /// An empty buffer keeping the checksums of the last `capacity` steps.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            checksums: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// The number of checksums kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// The checksum of the physics world after the given step, if it is still kept.
+    pub fn get(&self, step: u64) -> Option<u64> {
+        let index = self
+            .checksums
+            .binary_search_by_key(&step, |(step, _)| *step)
+            .ok()?;
+        Some(self.checksums[index].1)
+    }
+
+    /// The last step and its checksum.
+    pub fn latest(&self) -> Option<(u64, u64)> {
+        self.checksums.back().copied()
+    }
+
+    /// The steps and checksums kept, from the oldest to the latest.
+    pub fn iter(&self) -> impl Iterator<Item = (u64, u64)> + '_ {
+        self.checksums.iter().copied()
+    }
+
+    /// Whether the checksum of a peer for the given step matches the local one, or `None` if the
+    /// local checksum of this step is not known.
+    pub fn matches(&self, step: u64, checksum: u64) -> Option<bool> {
+        self.get(step).map(|local| local == checksum)
+    }
+
+    /// The first step, known by both peers, at which the checksums of a peer differ from the
+    /// local ones.
+    pub fn first_divergence(&self, remote: impl IntoIterator<Item = (u64, u64)>) -> Option<u64> {
+        remote
+            .into_iter()
+            .filter(|(step, checksum)| self.matches(*step, *checksum) == Some(false))
+            .map(|(step, _)| step)
+            .min()
+    }
+
+    /// Removes all the checksums, e.g. when a new game starts.
+    pub fn clear(&mut self) {
+        self.checksums.clear();
+    }
+
+    pub(crate) fn push(&mut self, step: u64, checksum: u64) {
+        // A restored physics world steps again through the restored steps, whose checksums are
+        // replaced to keep the steps sorted.
+        while matches!(self.checksums.back(), Some((last, _)) if *last >= step) {
+            self.checksums.pop_back();
+        }
+        if self.checksums.len() == self.capacity {
+            self.checksums.pop_front();
+        }
+        if self.capacity > 0 {
+            self.checksums.push_back((step, checksum));
+        }
+    }
+}
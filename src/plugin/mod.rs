@@ -1,13 +1,64 @@
-pub use self::configuration::{RapierConfiguration, SimulationToRenderTime, TimestepMode};
+#[cfg(feature = "serde-serialize")]
+pub use self::config_asset::{
+    ActivePhysicsConfig, DebugRenderConfig, IntegrationParametersConfig, PhysicsConfigAsset,
+    PhysicsConfigLoader,
+};
+pub use self::configuration::{
+    InvalidStateDetection, OutOfBoundsAction, PhysicsWorldBounds, QueryPipelineUpdate,
+    RapierConfiguration, SimulationToRenderTime, TimestepMode,
+};
+pub use self::contact_pair_storage::ContactPairStorage;
 pub use self::context::RapierContext;
+#[cfg(feature = "debug-stream")]
+pub use self::debug_stream::{
+    DebugStreamBody, DebugStreamFrame, DebugStreamLine, DebugStreamReader, DebugStreamRecorder,
+    DebugStreamRecorderPlugin, DebugStreamWriter,
+};
+pub use self::lockstep::{LockstepChecksumPlugin, LockstepChecksums};
+pub use self::narrow_phase::{ContactManifoldData, ContactPairData, ContactPointData};
 pub use self::plugin::{NoUserData, PhysicsStages, RapierPhysicsPlugin};
+pub use self::prefab::{PhysicsPrefab, PhysicsPrefabBundle, PhysicsPrefabInstance};
+pub use self::query::RapierQuery;
+pub use self::raw_world::{RawRapierWorld, RawWorldEntities};
+pub use self::recorder::{
+    PhysicsInput, PhysicsPlayback, PhysicsRecorder, PhysicsRecording, RecordedFrame,
+};
+pub use self::saveable::SaveablePhysics;
+pub use self::snapshot::{
+    BodyDivergence, ContactPairDivergence, DiffThresholds, PhysicsSnapshot, SnapshotDiff,
+};
+#[cfg(feature = "serde-serialize")]
+pub use self::snapshot_format::{
+    SnapshotError, SnapshotHeader, SnapshotMigration, SnapshotMigrations, SNAPSHOT_FORMAT_VERSION,
+    SNAPSHOT_MAGIC,
+};
+
+#[cfg(feature = "ggrs")]
+pub use self::rollback::{PhysicsRollbackState, RapierRollbackExt};
 
 #[allow(clippy::type_complexity)]
 #[allow(clippy::too_many_arguments)]
 pub mod systems;
 
+#[cfg(feature = "serde-serialize")]
+mod config_asset;
 mod configuration;
+mod contact_pair_storage;
 mod context;
+#[cfg(feature = "debug-stream")]
+mod debug_stream;
+mod lockstep;
 mod narrow_phase;
 #[allow(clippy::module_inception)]
 mod plugin;
+mod prefab;
+mod query;
+mod raw_world;
+mod recorder;
+#[cfg(feature = "ggrs")]
+mod rollback;
+mod rolling_friction;
+mod saveable;
+pub(crate) mod snapshot;
+#[cfg(feature = "serde-serialize")]
+mod snapshot_format;
@@ -1,7 +1,9 @@
 use crate::math::{Real, Vect};
 use crate::plugin::RapierContext;
 use bevy::prelude::*;
+use rapier::dynamics::RigidBodySet;
 use rapier::geometry::{Contact, ContactManifold, ContactPair, SolverContact, SolverFlags};
+use rapier::math::Isometry;
 
 impl RapierContext {
     /// All the contacts involving the non-sensor collider attached to the given entity.
@@ -53,10 +55,53 @@ impl RapierContext {
             .map(|raw| ContactPairView { context: self, raw })
     }
 
+    /// The contacts between two specific colliders, expressed in world-space and in Bevy units.
+    ///
+    /// Unlike [`Self::contact_pair`], this copies the contact manifolds so they can be kept
+    /// around, with the contact points and impulses scaled back from the physics scale. Returns
+    /// `None` if the colliders don’t exist or if there is no active contact between them.
+    pub fn contact_pair_data(
+        &self,
+        collider1: Entity,
+        collider2: Entity,
+    ) -> Option<ContactPairData> {
+        let h1 = self.entity2collider.get(&collider1)?;
+        let h2 = self.entity2collider.get(&collider2)?;
+        let pair = self.narrow_phase.contact_pair(*h1, *h2)?;
+
+        if !pair.has_any_active_contact {
+            return None;
+        }
+
+        let pos1 = self.colliders.get(pair.collider1)?.position();
+        let pos2 = self.colliders.get(pair.collider2)?.position();
+
+        let manifolds = pair
+            .manifolds
+            .iter()
+            .map(|manifold| {
+                ContactManifoldData::from_rapier(
+                    manifold,
+                    pos1,
+                    pos2,
+                    &self.bodies,
+                    self.physics_scale,
+                )
+            })
+            .collect();
+
+        Some(ContactPairData {
+            collider1: self.collider_entity(pair.collider1)?,
+            collider2: self.collider_entity(pair.collider2)?,
+            manifolds,
+        })
+    }
+
     /// The intersection pair involving two specific colliders (at least one being a sensor).
     ///
     /// If this returns `None` or `Some(false)`, then there is no intersection between the two colliders.
     /// If this returns `Some(true)`, then there may be an intersection between the two colliders.
+    /// This is how the overlap of a sensor with another collider is checked.
     pub fn intersection_pair(&self, collider1: Entity, collider2: Entity) -> Option<bool> {
         let h1 = self.entity2collider.get(&collider1)?;
         let h2 = self.entity2collider.get(&collider2)?;
@@ -352,3 +397,99 @@ impl<'a> ContactPairView<'a> {
         })
     }
 }
+
+/// The contacts between two colliders, see [`RapierContext::contact_pair_data`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactPairData {
+    /// The first collider involved in the contacts.
+    pub collider1: Entity,
+    /// The second collider involved in the contacts.
+    pub collider2: Entity,
+    /// The contact manifolds between the two colliders.
+    pub manifolds: Vec<ContactManifoldData>,
+}
+
+impl ContactPairData {
+    /// Finds the contact with the smallest distance (i.e. the largest penetration depth), and the
+    /// contact manifold it is part of.
+    pub fn find_deepest_contact(&self) -> Option<(&ContactManifoldData, &ContactPointData)> {
+        self.manifolds
+            .iter()
+            .flat_map(|manifold| manifold.points.iter().map(move |point| (manifold, point)))
+            .min_by(|a, b| a.1.dist.total_cmp(&b.1.dist))
+    }
+}
+
+/// A set of contacts sharing the same normal, see [`RapierContext::contact_pair_data`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ContactManifoldData {
+    /// The rigid-body attached to the first collider, if any.
+    pub rigid_body1: Option<Entity>,
+    /// The rigid-body attached to the second collider, if any.
+    pub rigid_body2: Option<Entity>,
+    /// The world-space contact normal, pointing from the first collider to the second one.
+    pub normal: Vect,
+    /// The contact points of this manifold.
+    pub points: Vec<ContactPointData>,
+}
+
+impl ContactManifoldData {
+    pub(crate) fn from_rapier(
+        manifold: &ContactManifold,
+        pos1: &Isometry<Real>,
+        pos2: &Isometry<Real>,
+        bodies: &RigidBodySet,
+        scale: Real,
+    ) -> Self {
+        let body_entity = |handle| {
+            bodies
+                .get(handle)
+                .map(|rb| Entity::from_bits(rb.user_data as u64))
+        };
+
+        Self {
+            rigid_body1: manifold.data.rigid_body1.and_then(body_entity),
+            rigid_body2: manifold.data.rigid_body2.and_then(body_entity),
+            normal: manifold.data.normal.into(),
+            points: manifold
+                .points
+                .iter()
+                .map(|contact| ContactPointData {
+                    point1: Vect::from((pos1 * contact.local_p1).coords) * scale,
+                    point2: Vect::from((pos2 * contact.local_p2).coords) * scale,
+                    dist: contact.dist * scale,
+                    impulse: contact.data.impulse * scale,
+                    #[cfg(feature = "dim2")]
+                    tangent_impulse: contact.data.tangent_impulse * scale,
+                    #[cfg(feature = "dim3")]
+                    tangent_impulse: [
+                        contact.data.tangent_impulse.x * scale,
+                        contact.data.tangent_impulse.y * scale,
+                    ],
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A single contact point, see [`RapierContext::contact_pair_data`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ContactPointData {
+    /// The world-space contact point on the first collider.
+    pub point1: Vect,
+    /// The world-space contact point on the second collider.
+    pub point2: Vect,
+    /// The distance between the two contact points, negative if the colliders are penetrating.
+    pub dist: Real,
+    /// The impulse, along the contact normal, applied by this contact to the first collider's
+    /// rigid-body during the last timestep.
+    pub impulse: Real,
+    /// The friction impulse along the vector orthonormal to the contact normal, applied to the
+    /// first collider's rigid-body during the last timestep.
+    #[cfg(feature = "dim2")]
+    pub tangent_impulse: Real,
+    /// The friction impulse along the vectors orthonormal to the contact normal, applied to the
+    /// first collider's rigid-body during the last timestep.
+    #[cfg(feature = "dim3")]
+    pub tangent_impulse: [Real; 2],
+}
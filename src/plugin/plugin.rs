@@ -1,6 +1,18 @@
-use crate::pipeline::{CollisionEvent, ContactForceEvent, PhysicsHooksWithQueryResource};
-use crate::plugin::configuration::SimulationToRenderTime;
-use crate::plugin::{systems, RapierConfiguration, RapierContext};
+use crate::pipeline::{
+    AsyncQueryQueue, AsyncQueryResult, BodyRemoved, CcdImpact, CharacterCollisionEvent,
+    ColliderRemoved, CollisionEvent, CollisionEventDetails, ContactForceEvent, GroundedChanged,
+    ImpactEvent, IslandEvent, JointBroken, JointLimitReached, JointRemoved, OutOfBounds,
+    PhysicsHooksWithQueryResource, PhysicsInvalidState, SensorEvent, SleepTimeoutEvent,
+    SteepSlopeChanged, TriggerEvent,
+};
+use crate::plugin::configuration::{InvalidStateDetection, SimulationToRenderTime};
+#[cfg(feature = "ggrs")]
+use crate::plugin::PhysicsRollbackState;
+use crate::plugin::{
+    systems, PhysicsRecorder, RapierConfiguration, RapierContext, SaveablePhysics,
+};
+#[cfg(feature = "serde-serialize")]
+use crate::plugin::{PhysicsConfigAsset, PhysicsConfigLoader};
 use crate::prelude::*;
 use bevy::ecs::{event::Events, query::WorldQuery};
 use bevy::prelude::*;
@@ -59,6 +71,30 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> RapierPhysicsPlugin<P
         match stage {
             PhysicsStages::SyncBackend => {
                 let systems = SystemSet::new()
+                    .with_system(
+                        Events::<CharacterCollisionEvent>::update_system
+                            .before(systems::update_character_controls),
+                    )
+                    .with_system(
+                        Events::<GroundedChanged>::update_system
+                            .before(systems::update_character_controls),
+                    )
+                    .with_system(
+                        Events::<SteepSlopeChanged>::update_system
+                            .before(systems::update_character_controls),
+                    )
+                    .with_system(
+                        systems::restore_character_transforms
+                            .before(systems::resize_character_controllers),
+                    )
+                    .with_system(
+                        systems::resize_character_controllers
+                            .before(systems::update_character_controls),
+                    )
+                    .with_system(
+                        systems::apply_replicated_bodies
+                            .before(bevy::transform::transform_propagate_system),
+                    )
                     .with_system(systems::update_character_controls) // Run the character controller befor ethe manual transform propagation.
                     .with_system(
                         bevy::transform::transform_propagate_system
@@ -68,16 +104,45 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> RapierPhysicsPlugin<P
                         systems::init_async_colliders
                             .after(bevy::transform::transform_propagate_system),
                     )
+                    .with_system(
+                        systems::init_colliders_from_assets
+                            .after(bevy::transform::transform_propagate_system),
+                    )
+                    // The colliders spawned with commands are initialized at the next update.
+                    .with_system(systems::spawn_static_worlds.before(systems::init_colliders))
+                    .with_system(
+                        systems::stream_terrains
+                            .after(bevy::transform::transform_propagate_system)
+                            .before(systems::init_colliders),
+                    )
                     .with_system(systems::apply_scale.after(systems::init_async_colliders))
                     .with_system(systems::apply_collider_user_changes.after(systems::apply_scale))
                     .with_system(
                         systems::apply_rigid_body_user_changes
                             .after(systems::apply_collider_user_changes),
                     )
+                    .with_system(
+                        systems::estimate_kinematic_velocities
+                            .after(systems::apply_rigid_body_user_changes),
+                    )
+                    .with_system(
+                        systems::update_dynamic_character_controls
+                            .after(systems::apply_rigid_body_user_changes),
+                    )
+                    .with_system(
+                        systems::apply_joint_springs.before(systems::apply_joint_user_changes),
+                    )
+                    .with_system(
+                        systems::apply_joint_softness.before(systems::apply_joint_user_changes),
+                    )
                     .with_system(
                         systems::apply_joint_user_changes
                             .after(systems::apply_rigid_body_user_changes),
                     )
+                    .with_system(
+                        systems::sync_restored_snapshot
+                            .before(systems::apply_collider_user_changes),
+                    )
                     .with_system(
                         systems::init_rigid_bodies.after(systems::apply_joint_user_changes),
                     )
@@ -86,20 +151,183 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> RapierPhysicsPlugin<P
                             .after(systems::init_rigid_bodies)
                             .after(systems::init_async_colliders),
                     )
+                    .with_system(systems::resolve_pending_joints.before(systems::init_joints))
+                    .with_system(
+                        systems::update_collision_event_filters.after(systems::init_colliders),
+                    )
+                    .with_system(systems::update_one_way_colliders.after(systems::init_colliders))
                     .with_system(systems::init_joints.after(systems::init_colliders))
+                    .with_system(systems::apply_welds.after(systems::init_joints))
+                    .with_system(systems::update_distance_joints.after(systems::init_joints))
+                    .with_system(systems::update_ramped_joint_motors.after(systems::init_joints))
                     .with_system(
                         systems::apply_initial_rigid_body_impulses.after(systems::init_colliders),
                     )
                     .with_system(
-                        systems::sync_removals
-                            .after(systems::init_joints)
+                        systems::apply_radial_gravity
                             .after(systems::apply_initial_rigid_body_impulses),
+                    )
+                    .with_system(
+                        systems::sync_removals
+                            .after(systems::apply_welds)
+                            .after(systems::apply_radial_gravity),
                     );
 
+                #[cfg(feature = "dim3")]
+                {
+                    systems
+                        .with_system(
+                            systems::init_async_scene_colliders
+                                .before(systems::init_async_colliders),
+                        )
+                        .with_system(systems::init_ragdolls.before(systems::init_rigid_bodies))
+                        .with_system(systems::apply_ragdoll_modes)
+                        .with_system(
+                            systems::apply_ragdoll_bone_modes
+                                .after(systems::apply_ragdoll_modes)
+                                .before(systems::apply_rigid_body_user_changes),
+                        )
+                        .with_system(systems::init_ropes.before(systems::init_rigid_bodies))
+                        .with_system(
+                            systems::apply_rope_changes
+                                .before(systems::apply_collider_user_changes),
+                        )
+                }
+                #[cfg(not(feature = "dim3"))]
+                {
+                    systems
+                }
+            }
+            PhysicsStages::StepSimulation => {
+                let systems = SystemSet::new()
+                    .with_system(
+                        systems::update_query_pipeline_after_sync
+                            .before(systems::apply_joint_couplings),
+                    )
+                    .with_system(
+                        systems::apply_joint_couplings
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        systems::solve_pulley_joints
+                            .after(systems::apply_joint_couplings)
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(systems::step_simulation::<PhysicsHooksData>)
+                    .with_system(
+                        systems::record_physics_inputs
+                            .after(systems::update_query_pipeline_after_sync)
+                            .after(systems::apply_joint_couplings)
+                            .after(systems::solve_pulley_joints)
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        systems::record_physics_step
+                            .after(systems::step_simulation::<PhysicsHooksData>)
+                            .before(run_collision_observers),
+                    )
+                    .with_system(
+                        systems::update_rolling_friction
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        run_collision_observers.after(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        Events::<CollisionEvent>::update_system
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        Events::<ContactForceEvent>::update_system
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        Events::<CollisionEventDetails>::update_system
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        Events::<SensorEvent>::update_system
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                    .with_system(
+                        Events::<CcdImpact>::update_system
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    );
+
+                #[cfg(feature = "debug-stream")]
+                {
+                    systems.with_system(
+                        systems::prepare_debug_stream
+                            .before(systems::step_simulation::<PhysicsHooksData>),
+                    )
+                }
+                #[cfg(not(feature = "debug-stream"))]
+                {
+                    systems
+                }
+            }
+            PhysicsStages::Writeback => {
+                let systems = SystemSet::new()
+                    .with_system(systems::update_colliding_entities)
+                    .with_system(systems::update_sensor_intersections)
+                    .with_system(systems::update_contact_report)
+                    .with_system(systems::update_standing_on)
+                    .with_system(
+                        Events::<TriggerEvent>::update_system.before(systems::update_trigger_zones),
+                    )
+                    .with_system(systems::update_trigger_zones)
+                    .with_system(
+                        Events::<OutOfBounds>::update_system.before(systems::apply_world_bounds),
+                    )
+                    .with_system(
+                        Events::<PhysicsInvalidState>::update_system
+                            .before(systems::detect_invalid_states),
+                    )
+                    .with_system(systems::detect_invalid_states.before(systems::apply_world_bounds))
+                    .with_system(systems::apply_world_bounds)
+                    .with_system(systems::sync_welded_bodies)
+                    .with_system(
+                        systems::writeback_rigid_bodies
+                            .after(systems::apply_world_bounds)
+                            .after(systems::sync_welded_bodies),
+                    )
+                    .with_system(
+                        systems::interpolate_character_transforms
+                            .after(systems::writeback_rigid_bodies),
+                    )
+                    .with_system(systems::update_ray_casters)
+                    .with_system(systems::update_shape_casters)
+                    .with_system(
+                        Events::<AsyncQueryResult>::update_system
+                            .before(systems::resolve_async_queries),
+                    )
+                    .with_system(systems::resolve_async_queries)
+                    .with_system(
+                        Events::<SleepTimeoutEvent>::update_system
+                            .before(systems::despawn_sleeping_bodies),
+                    )
+                    .with_system(systems::despawn_sleeping_bodies)
+                    .with_system(
+                        Events::<IslandEvent>::update_system.before(systems::emit_island_events),
+                    )
+                    .with_system(systems::emit_island_events)
+                    .with_system(Events::<JointBroken>::update_system.before(systems::break_joints))
+                    .with_system(systems::writeback_joint_forces)
+                    .with_system(systems::break_joints)
+                    .with_system(
+                        Events::<JointLimitReached>::update_system
+                            .before(systems::emit_joint_limit_events),
+                    )
+                    .with_system(systems::emit_joint_limit_events)
+                    .with_system(
+                        Events::<ImpactEvent>::update_system.before(systems::emit_impact_events),
+                    )
+                    .with_system(systems::emit_impact_events);
+
                 #[cfg(feature = "dim3")]
                 {
                     systems.with_system(
-                        systems::init_async_scene_colliders.before(systems::init_async_colliders),
+                        systems::update_rope_meshes.after(systems::writeback_rigid_bodies),
                     )
                 }
                 #[cfg(not(feature = "dim3"))]
@@ -107,20 +335,13 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> RapierPhysicsPlugin<P
                     systems
                 }
             }
-            PhysicsStages::StepSimulation => SystemSet::new()
-                .with_system(systems::step_simulation::<PhysicsHooksData>)
+            PhysicsStages::DetectDespawn => SystemSet::new()
+                .with_system(Events::<BodyRemoved>::update_system.before(systems::sync_removals))
                 .with_system(
-                    Events::<CollisionEvent>::update_system
-                        .before(systems::step_simulation::<PhysicsHooksData>),
+                    Events::<ColliderRemoved>::update_system.before(systems::sync_removals),
                 )
-                .with_system(
-                    Events::<ContactForceEvent>::update_system
-                        .before(systems::step_simulation::<PhysicsHooksData>),
-                ),
-            PhysicsStages::Writeback => SystemSet::new()
-                .with_system(systems::update_colliding_entities)
-                .with_system(systems::writeback_rigid_bodies),
-            PhysicsStages::DetectDespawn => SystemSet::new().with_system(systems::sync_removals),
+                .with_system(Events::<JointRemoved>::update_system.before(systems::sync_removals))
+                .with_system(systems::sync_removals),
         }
     }
 }
@@ -168,6 +389,20 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> Plugin
             .register_type::<Velocity>()
             .register_type::<AdditionalMassProperties>()
             .register_type::<MassProperties>()
+            .register_type::<ImpulseJoint>()
+            .register_type::<MultibodyJoint>()
+            .register_type::<GenericJoint>()
+            .register_type::<PendingJoint>()
+            .register_type::<EntityPathOrName>()
+            .register_type::<JointSpring>()
+            .register_type::<JointSoftness>()
+            .register_type::<BreakableJoint>()
+            .register_type::<JointEnabled>()
+            .register_type::<ReadJointForces>()
+            .register_type::<JointLimitEventThreshold>()
+            .register_type::<JointAxisMotion>()
+            .register_type::<JointAxisMotor>()
+            .register_type::<JointAxisDescription>()
             .register_type::<LockedAxes>()
             .register_type::<ExternalForce>()
             .register_type::<ExternalImpulse>()
@@ -176,16 +411,88 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> Plugin
             .register_type::<Dominance>()
             .register_type::<Ccd>()
             .register_type::<GravityScale>()
+            .register_type::<KinematicVelocityEstimation>()
+            .register_type::<DespawnWhenSleeping>()
             .register_type::<CollidingEntities>()
+            .register_type::<TrackIntersections>()
+            .register_type::<SensorIntersections>()
+            .register_type::<TriggerZone>()
             .register_type::<Sensor>()
             .register_type::<Friction>()
             .register_type::<Restitution>()
+            .register_type::<RollingFriction>()
             .register_type::<CollisionGroups>()
             .register_type::<SolverGroups>()
-            .register_type::<ContactForceEventThreshold>();
+            .register_type::<ContactForceEventThreshold>()
+            .register_type::<ImpactEvents>()
+            .register_type::<ImpactMaterial>()
+            .register_type::<OneWayPlatform>()
+            .register_type::<SurfaceVelocity>()
+            .register_type::<FrictionOverrides>()
+            .register_type::<FluidVolume>()
+            .register_type::<AppliedMaterial>()
+            .register_type::<CollisionEventFilter>()
+            .register_type::<TrackStanding>()
+            .register_type::<OneWayCollider>()
+            .register_type::<Collider>()
+            .register_type::<AsyncColliderFromAsset>()
+            .register_type::<TerrainFocus>()
+            .register_type::<ColliderScale>()
+            .register_type::<ColliderMassProperties>()
+            .register_type::<ReadMassProperties>()
+            .register_type::<ActiveCollisionTypes>()
+            .register_type::<ActiveEvents>()
+            .register_type::<ActiveHooks>()
+            .register_type::<Group>()
+            .register_type::<TransformInterpolation>()
+            .register_type::<RadialGravity>()
+            .register_type::<ReplicatedBody>()
+            .register_type::<Weld>()
+            .register_type::<StandingOn>()
+            .register_type::<Carrying>()
+            .register_type::<RampedJointMotor>()
+            .register_type::<JointCoupling>()
+            .register_type::<PulleyJoint>()
+            .register_type::<KinematicCharacterController>()
+            .register_type::<KinematicCharacterControllerOutput>()
+            .register_type::<CharacterInterpolation>()
+            .register_type::<DynamicCharacterController>()
+            .register_type::<DynamicCharacterControllerOutput>()
+            .register_type::<RayCaster>()
+            .register_type::<RayHits>()
+            .register_type::<ShapeCaster>()
+            .register_type::<ShapeHits>();
 
         #[cfg(feature = "dim3")]
-        app.register_type::<AsyncCollider>();
+        app.register_type::<AsyncCollider>()
+            .register_type::<AsyncSceneCollider>()
+            .register_type::<ComputedColliderShape>()
+            .register_type::<SpinFriction>()
+            .register_type::<Ragdoll>()
+            .register_type::<RagdollBone>()
+            .register_type::<Rope>()
+            .register_type::<RopeSegments>()
+            .register_type::<RopeSegment>()
+            .register_type::<RopeMesh>();
+
+        // The physics materials are assets, which need the `AssetPlugin`.
+        if app.world.contains_resource::<AssetServer>() {
+            app.add_asset::<PhysicsMaterial>()
+                .add_asset::<ColliderAsset>();
+            #[cfg(feature = "serde-serialize")]
+            app.init_asset_loader::<ColliderAssetLoader>()
+                .add_asset::<PhysicsConfigAsset>()
+                .init_asset_loader::<PhysicsConfigLoader>()
+                .add_system_to_stage(CoreStage::PreUpdate, systems::apply_physics_config);
+        }
+
+        // The physics prefabs are scenes, which need the `ScenePlugin`.
+        if app.world.contains_resource::<SceneSpawner>() {
+            app.add_system_to_stage(
+                CoreStage::PreUpdate,
+                systems::spawn_physics_prefabs.exclusive_system().at_end(),
+            );
+        }
 
         // Insert all of our required resources. Don’t overwrite
         // the `RapierConfiguration` if it already exists.
@@ -193,13 +500,42 @@ impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> Plugin
             app.insert_resource(RapierConfiguration::default());
         }
 
+        #[cfg(debug_assertions)]
+        if app.world.get_resource::<InvalidStateDetection>().is_none() {
+            app.insert_resource(InvalidStateDetection::default());
+        }
+
         app.insert_resource(SimulationToRenderTime::default())
             .insert_resource(RapierContext {
                 physics_scale: self.physics_scale,
                 ..Default::default()
             })
             .insert_resource(Events::<CollisionEvent>::default())
-            .insert_resource(Events::<ContactForceEvent>::default());
+            .insert_resource(Events::<ContactForceEvent>::default())
+            .insert_resource(Events::<CollisionEventDetails>::default())
+            .insert_resource(Events::<SensorEvent>::default())
+            .insert_resource(Events::<CcdImpact>::default())
+            .insert_resource(Events::<ImpactEvent>::default())
+            .insert_resource(Events::<BodyRemoved>::default())
+            .insert_resource(Events::<ColliderRemoved>::default())
+            .insert_resource(Events::<JointRemoved>::default())
+            .insert_resource(Events::<TriggerEvent>::default())
+            .insert_resource(Events::<PhysicsInvalidState>::default())
+            .insert_resource(Events::<SleepTimeoutEvent>::default())
+            .insert_resource(Events::<IslandEvent>::default())
+            .insert_resource(Events::<OutOfBounds>::default())
+            .insert_resource(Events::<JointBroken>::default())
+            .insert_resource(Events::<JointLimitReached>::default())
+            .insert_resource(Events::<CharacterCollisionEvent>::default())
+            .insert_resource(Events::<GroundedChanged>::default())
+            .insert_resource(Events::<SteepSlopeChanged>::default())
+            .insert_resource(Events::<AsyncQueryResult>::default())
+            .init_resource::<AsyncQueryQueue>()
+            .init_resource::<PhysicsRecorder>()
+            .init_resource::<SaveablePhysics>();
+
+        #[cfg(feature = "ggrs")]
+        app.init_resource::<PhysicsRollbackState>();
 
         // Add each stage as necessary
         if self.default_system_setup {
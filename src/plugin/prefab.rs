@@ -0,0 +1,44 @@
+use bevy::prelude::*;
+
+/// A component spawning a scene with physics components as children of its entity, once the
+/// scene is loaded.
+///
+/// Unlike a `SceneBundle`, whose entities are spawned over several stages, the whole scene is
+/// spawned at once at the end of [`CoreStage::PreUpdate`], and the `GlobalTransform` of its
+/// entities are set from their transforms right away. The rigid-bodies, colliders and joints of
+/// the scene are then created together during the next physics stages, at their final position,
+/// with the joints attached to the rigid-bodies of the same scene.
+///
+/// Once the scene is spawned, this component is replaced by a [`PhysicsPrefabInstance`]. The
+/// `ScenePlugin` must be added before the `RapierPhysicsPlugin`.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PhysicsPrefab(pub Handle<Scene>);
+
+/// The components of an entity spawning a [`PhysicsPrefab`].
+#[derive(Bundle, Clone, Debug, Default)]
+pub struct PhysicsPrefabBundle {
+    /// The scene to spawn.
+    pub prefab: PhysicsPrefab,
+    /// The transform of the scene.
+    pub transform: Transform,
+    /// The global transform of the scene.
+    pub global_transform: GlobalTransform,
+}
+
+impl PhysicsPrefabBundle {
+    /// The components spawning the given scene with the given transform.
+    pub fn new(scene: Handle<Scene>, transform: Transform) -> Self {
+        Self {
+            prefab: PhysicsPrefab(scene),
+            transform,
+            global_transform: GlobalTransform::from(transform),
+        }
+    }
+}
+
+/// A component listing the entities spawned by a [`PhysicsPrefab`].
+#[derive(Component, Clone, Debug, Default)]
+pub struct PhysicsPrefabInstance {
+    /// The spawned entities.
+    pub entities: Vec<Entity>,
+}
@@ -0,0 +1,334 @@
+use crate::geometry::{
+    Collider, LineOfSight, PathHit, PointProjection, RayIntersection, Region, Toi, VisionCone,
+};
+use crate::math::{Real, Rot, Vect};
+use crate::pipeline::QueryFilter;
+use crate::plugin::RapierContext;
+use bevy::ecs::query::WorldQuery;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::primitives::Aabb;
+use rapier::prelude::FeatureId;
+
+/// A system parameter for running scene queries on the physics world.
+///
+/// All the positions and distances are expressed in Bevy units, and all the results are
+/// entities. The query filter `F` (e.g. `With<Enemy>` or `Without<Ghost>`) restricts the colliders
+/// that can be reported by the scene queries, in addition to the [`QueryFilter`] given to each
+/// method. With the default `()`, all the colliders can be reported.
+#[derive(SystemParam)]
+pub struct RapierQuery<'w, 's, F: WorldQuery + 'static = ()> {
+    context: Res<'w, RapierContext>,
+    colliders: Query<'w, 's, (), F>,
+}
+
+impl<'w, 's, F: WorldQuery + 'static> RapierQuery<'w, 's, F> {
+    /// The physics context the scene queries are run on.
+    pub fn context(&self) -> &RapierContext {
+        &self.context
+    }
+
+    /// Combines the query filter `F` with the predicate of `filter`.
+    fn with_filter<T>(&self, filter: QueryFilter, f: impl FnOnce(QueryFilter) -> T) -> T {
+        let user_predicate = filter.predicate;
+        let predicate = |entity: Entity| {
+            self.colliders.contains(entity)
+                && user_predicate
+                    .map(|predicate| predicate(entity))
+                    .unwrap_or(true)
+        };
+
+        f(QueryFilter {
+            predicate: Some(&predicate),
+            ..filter
+        })
+    }
+
+    /// Find the closest intersection between a ray and a set of collider.
+    ///
+    /// See [`RapierContext::cast_ray`].
+    pub fn cast_ray(
+        &self,
+        ray_origin: Vect,
+        ray_dir: Vect,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(Entity, Real)> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_ray(ray_origin, ray_dir, max_toi, solid, filter)
+        })
+    }
+
+    /// Find the closest intersection between a ray and a set of collider.
+    ///
+    /// See [`RapierContext::cast_ray_and_get_normal`].
+    pub fn cast_ray_and_get_normal(
+        &self,
+        ray_origin: Vect,
+        ray_dir: Vect,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(Entity, RayIntersection)> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_ray_and_get_normal(ray_origin, ray_dir, max_toi, solid, filter)
+        })
+    }
+
+    /// Checks whether anything blocks the view between two entities.
+    ///
+    /// See [`RapierContext::line_of_sight`].
+    pub fn line_of_sight(
+        &self,
+        from: Entity,
+        from_anchor: Vect,
+        to: Entity,
+        to_anchor: Vect,
+        occluder_filter: QueryFilter,
+    ) -> Option<LineOfSight> {
+        self.with_filter(occluder_filter, |filter| {
+            self.context
+                .line_of_sight(from, from_anchor, to, to_anchor, filter)
+        })
+    }
+
+    /// Find the entities visible from a vision cone, sorted by increasing distance.
+    ///
+    /// See [`RapierContext::visible_entities`].
+    pub fn visible_entities(
+        &self,
+        cone: VisionCone,
+        filter: QueryFilter,
+        occluder_filter: QueryFilter,
+    ) -> Vec<(Entity, Real)> {
+        self.with_filter(filter, |filter| {
+            self.context.visible_entities(cone, filter, occluder_filter)
+        })
+    }
+
+    /// Find the all intersections between a ray and a set of collider and passes them to a callback.
+    ///
+    /// See [`RapierContext::intersections_with_ray`].
+    pub fn intersections_with_ray(
+        &self,
+        ray_origin: Vect,
+        ray_dir: Vect,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+        callback: impl FnMut(Entity, RayIntersection) -> bool,
+    ) {
+        self.with_filter(filter, |filter| {
+            self.context
+                .intersections_with_ray(ray_origin, ray_dir, max_toi, solid, filter, callback)
+        })
+    }
+
+    /// Find all the intersections between a ray and a set of collider, sorted by increasing
+    /// time-of-impact.
+    ///
+    /// See [`RapierContext::cast_ray_all`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn cast_ray_all(
+        &self,
+        ray_origin: Vect,
+        ray_dir: Vect,
+        max_toi: Real,
+        solid: bool,
+        filter: QueryFilter,
+        max_hits: Option<usize>,
+    ) -> Vec<(Entity, RayIntersection)> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_ray_all(ray_origin, ray_dir, max_toi, solid, filter, max_hits)
+        })
+    }
+
+    /// Find the collider under a position on the screen, e.g., to pick the entity under the cursor.
+    ///
+    /// See [`RapierContext::cast_ray_from_screen`].
+    pub fn cast_ray_from_screen(
+        &self,
+        camera: &Camera,
+        camera_transform: &GlobalTransform,
+        cursor_pos: Vec2,
+        filter: QueryFilter,
+    ) -> Option<(Entity, RayIntersection)> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_ray_from_screen(camera, camera_transform, cursor_pos, filter)
+        })
+    }
+
+    /// Gets the entity of up to one collider intersecting the given shape.
+    ///
+    /// See [`RapierContext::intersection_with_shape`].
+    pub fn intersection_with_shape(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape: &Collider,
+        filter: QueryFilter,
+    ) -> Option<Entity> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .intersection_with_shape(shape_pos, shape_rot, shape, filter)
+        })
+    }
+
+    /// Retrieve all the colliders intersecting the given shape.
+    ///
+    /// See [`RapierContext::intersections_with_shape`].
+    pub fn intersections_with_shape(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape: &Collider,
+        filter: QueryFilter,
+        callback: impl FnMut(Entity) -> bool,
+    ) {
+        self.with_filter(filter, |filter| {
+            self.context
+                .intersections_with_shape(shape_pos, shape_rot, shape, filter, callback)
+        })
+    }
+
+    /// Casts a shape at a constant linear velocity and retrieve the first collider it hits.
+    ///
+    /// See [`RapierContext::cast_shape`].
+    pub fn cast_shape(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape_vel: Vect,
+        shape: &Collider,
+        max_toi: Real,
+        filter: QueryFilter,
+    ) -> Option<(Entity, Toi)> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_shape(shape_pos, shape_rot, shape_vel, shape, max_toi, filter)
+        })
+    }
+
+    /// Casts a shape at a constant linear velocity and retrieve all the colliders it hits, sorted
+    /// by increasing time-of-impact.
+    ///
+    /// See [`RapierContext::cast_shape_all`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn cast_shape_all(
+        &self,
+        shape_pos: Vect,
+        shape_rot: Rot,
+        shape_vel: Vect,
+        shape: &Collider,
+        max_toi: Real,
+        filter: QueryFilter,
+        max_hits: Option<usize>,
+    ) -> Vec<(Entity, Toi)> {
+        self.with_filter(filter, |filter| {
+            self.context.cast_shape_all(
+                shape_pos, shape_rot, shape_vel, shape, max_toi, filter, max_hits,
+            )
+        })
+    }
+
+    /// Sweeps a shape along a path and retrieve the first collider blocking it.
+    ///
+    /// See [`RapierContext::cast_shape_along_path`].
+    pub fn cast_shape_along_path(
+        &self,
+        path: &[Vect],
+        shape_rot: Rot,
+        shape: &Collider,
+        filter: QueryFilter,
+    ) -> Option<PathHit> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .cast_shape_along_path(path, shape_rot, shape, filter)
+        })
+    }
+
+    /// Find the projection of a point on the closest collider.
+    ///
+    /// See [`RapierContext::project_point`].
+    pub fn project_point(
+        &self,
+        point: Vect,
+        solid: bool,
+        filter: QueryFilter,
+    ) -> Option<(Entity, PointProjection)> {
+        self.with_filter(filter, |filter| {
+            self.context.project_point(point, solid, filter)
+        })
+    }
+
+    /// Find the projection of a point on the closest collider, and the feature it is projected on.
+    ///
+    /// See [`RapierContext::project_point_and_get_feature`].
+    pub fn project_point_and_get_feature(
+        &self,
+        point: Vect,
+        filter: QueryFilter,
+    ) -> Option<(Entity, PointProjection, FeatureId)> {
+        self.with_filter(filter, |filter| {
+            self.context.project_point_and_get_feature(point, filter)
+        })
+    }
+
+    /// Find the `k` colliders closest to a point, sorted by increasing distance.
+    ///
+    /// See [`RapierContext::nearest_colliders`].
+    pub fn nearest_colliders(
+        &self,
+        point: Vect,
+        k: usize,
+        filter: QueryFilter,
+    ) -> Vec<(Entity, Real, Vect)> {
+        self.with_filter(filter, |filter| {
+            self.context.nearest_colliders(point, k, filter)
+        })
+    }
+
+    /// Find all the colliders containing the given point.
+    ///
+    /// See [`RapierContext::intersections_with_point`].
+    pub fn intersections_with_point(
+        &self,
+        point: Vect,
+        filter: QueryFilter,
+        callback: impl FnMut(Entity) -> bool,
+    ) {
+        self.with_filter(filter, |filter| {
+            self.context
+                .intersections_with_point(point, filter, callback)
+        })
+    }
+
+    /// Finds the entities of all the colliders with an AABB intersecting the given region.
+    ///
+    /// See [`RapierContext::colliders_with_aabb_intersecting`].
+    pub fn colliders_with_aabb_intersecting(
+        &self,
+        region: impl Into<Region>,
+        filter: QueryFilter,
+    ) -> impl ExactSizeIterator<Item = Entity> {
+        self.with_filter(filter, |filter| {
+            self.context
+                .colliders_with_aabb_intersecting(region, filter)
+        })
+    }
+
+    /// Finds all entities of all the colliders with an Aabb intersecting the given Aabb.
+    ///
+    /// See [`RapierContext::colliders_with_aabb_intersecting_aabb`].
+    pub fn intersections_with_aabb(&self, aabb: Aabb, mut callback: impl FnMut(Entity) -> bool) {
+        self.context
+            .colliders_with_aabb_intersecting_aabb(aabb, |entity| {
+                !self.colliders.contains(entity) || callback(entity)
+            })
+    }
+}
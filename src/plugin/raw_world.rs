@@ -0,0 +1,276 @@
+use crate::dynamics::{
+    AdditionalMassProperties, Ccd, Damping, Dominance, GenericJoint, GravityScale, ImpulseJoint,
+    JointParent, LockedAxes, MassProperties, MultibodyJoint, RigidBody, Sleeping, Velocity,
+};
+use crate::geometry::{
+    ActiveCollisionTypes, ActiveEvents, ActiveHooks, Collider, ColliderMassProperties,
+    CollisionGroups, ContactForceEventThreshold, Friction, Group, Restitution, Sensor,
+    SolverGroups,
+};
+use crate::math::{Real, Vect};
+use crate::plugin::{RapierConfiguration, RapierContext};
+use crate::utils;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rapier::prelude::{
+    ColliderHandle, ColliderSet, ImpulseJointSet, IntegrationParameters, MultibodyJointSet,
+    RigidBodyHandle, RigidBodySet,
+};
+
+/// A physics world made of plain Rapier objects, to exchange worlds with Rapier itself, e.g. its
+/// testbed, or with another engine using Rapier.
+///
+/// It is exported from the [`RapierContext`] with [`RapierContext::export_raw_world`], and
+/// imported as new entities with [`Self::spawn`]. With the `serde-serialize` feature,
+/// [`Self::to_bytes`] and [`Self::from_bytes`] read and write it with `bincode`, as the tuple
+/// `(gravity, integration_parameters, bodies, colliders, impulse_joints, multibody_joints)` of
+/// the serialized Rapier objects, which makes sharing a reproduction case with the upstream
+/// project easy.
+///
+/// All the values are in physics units, i.e. they are not multiplied by the physics scale.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct RawRapierWorld {
+    /// The gravity of the world.
+    pub gravity: rapier::math::Vector<Real>,
+    /// The integration parameters of the simulation.
+    pub integration_parameters: IntegrationParameters,
+    /// The rigid-bodies.
+    pub bodies: RigidBodySet,
+    /// The colliders.
+    pub colliders: ColliderSet,
+    /// The impulse joints.
+    pub impulse_joints: ImpulseJointSet,
+    /// The multibody joints.
+    pub multibody_joints: MultibodyJointSet,
+}
+
+/// The entities spawned by [`RawRapierWorld::spawn`] for the rigid-bodies and colliders of the
+/// raw world.
+#[derive(Clone, Debug, Default)]
+pub struct RawWorldEntities {
+    /// The entity of each rigid-body.
+    pub bodies: HashMap<RigidBodyHandle, Entity>,
+    /// The entity of each collider.
+    pub colliders: HashMap<ColliderHandle, Entity>,
+}
+
+impl RapierContext {
+    /// Exports the rigid-bodies, colliders and joints of the physics world as plain Rapier
+    /// objects, with the gravity of the given configuration.
+    ///
+    /// The user data of the exported objects still contains the bits of their entities.
+    pub fn export_raw_world(&self, config: &RapierConfiguration) -> RawRapierWorld {
+        RawRapierWorld {
+            gravity: (config.gravity / self.physics_scale).into(),
+            integration_parameters: self.integration_parameters,
+            bodies: self.bodies.clone(),
+            colliders: self.colliders.clone(),
+            impulse_joints: self.impulse_joints.clone(),
+            multibody_joints: self.multibody_joints.clone(),
+        }
+    }
+}
+
+impl RawRapierWorld {
+    /// Spawns an entity with the physics components of each rigid-body, collider and joint of
+    /// this world.
+    ///
+    /// The colliders are spawned as children of the entities of their rigid-bodies, and the
+    /// impulse joints as children of the entities of their second rigid-body. The positions
+    /// and lengths are multiplied by the `physics_scale` of the plugin. The gravity and the
+    /// integration parameters of this world are not applied: copy them to the
+    /// [`RapierConfiguration`] and the [`RapierContext`] if needed.
+    ///
+    /// The mass-properties added to a rigid-body with colliders, the forces applied to the
+    /// rigid-bodies, and the contacts are not imported.
+    pub fn spawn(&self, commands: &mut Commands, physics_scale: Real) -> RawWorldEntities {
+        let mut entities = RawWorldEntities::default();
+        let mut world_body = None;
+
+        for (handle, rb) in self.bodies.iter() {
+            // The body a `RapierContext` attaches the joints anchored to the world to.
+            if rb.user_data == RapierContext::WORLD_BODY_USER_DATA
+                && rb.is_fixed()
+                && rb.colliders().is_empty()
+            {
+                world_body = Some(handle);
+                continue;
+            }
+
+            #[cfg(feature = "dim2")]
+            let (angvel, locked_axes) = {
+                let mut locked_axes = LockedAxes::empty();
+                locked_axes.set(LockedAxes::TRANSLATION_LOCKED, rb.is_translation_locked());
+                locked_axes.set(LockedAxes::ROTATION_LOCKED, rb.is_rotation_locked());
+                (rb.angvel(), locked_axes)
+            };
+            #[cfg(feature = "dim3")]
+            let (angvel, locked_axes) = {
+                let mut locked_axes = LockedAxes::empty();
+                let [x, y, z] = rb.is_rotation_locked();
+                locked_axes.set(LockedAxes::TRANSLATION_LOCKED, rb.is_translation_locked());
+                locked_axes.set(LockedAxes::ROTATION_LOCKED_X, x);
+                locked_axes.set(LockedAxes::ROTATION_LOCKED_Y, y);
+                locked_axes.set(LockedAxes::ROTATION_LOCKED_Z, z);
+                ((*rb.angvel()).into(), locked_axes)
+            };
+
+            let activation = rb.activation();
+            let mut entity = commands.spawn();
+            entity
+                .insert_bundle(TransformBundle::from(utils::iso_to_transform(
+                    rb.position(),
+                    physics_scale,
+                )))
+                .insert(RigidBody::from(rb.body_type()))
+                .insert(Velocity {
+                    linvel: (rb.linvel() * physics_scale).into(),
+                    angvel,
+                })
+                .insert(locked_axes)
+                .insert(Damping {
+                    linear_damping: rb.linear_damping(),
+                    angular_damping: rb.angular_damping(),
+                })
+                .insert(GravityScale(rb.gravity_scale()))
+                .insert(Ccd {
+                    enabled: rb.is_ccd_enabled(),
+                })
+                .insert(Dominance {
+                    groups: rb.dominance_group(),
+                })
+                .insert(Sleeping {
+                    linear_threshold: activation.linear_threshold,
+                    angular_threshold: activation.angular_threshold,
+                    sleeping: rb.is_sleeping(),
+                });
+            // The mass-properties of a rigid-body without collider are its additional ones.
+            if rb.colliders().is_empty() && rb.mass() > 0.0 {
+                entity.insert(AdditionalMassProperties::MassProperties(
+                    MassProperties::from_rapier(*rb.mass_properties(), physics_scale),
+                ));
+            }
+            entities.bodies.insert(handle, entity.id());
+        }
+
+        for (handle, co) in self.colliders.iter() {
+            let parent = co.parent().and_then(|parent| entities.bodies.get(&parent));
+            let position = match parent {
+                Some(_) => co.position_wrt_parent().copied().unwrap_or_default(),
+                None => *co.position(),
+            };
+
+            let mut collider = Collider::from(co.shared_shape().clone());
+            if physics_scale != 1.0 {
+                // The scale of the collider is overwritten by the scale of its transform: scale
+                // its unscaled shape instead.
+                collider.set_scale(Vect::splat(physics_scale), 10);
+                collider = Collider::from(collider.raw);
+            }
+
+            let mut entity = commands.spawn();
+            entity
+                .insert_bundle(TransformBundle::from(utils::iso_to_transform(
+                    &position,
+                    physics_scale,
+                )))
+                .insert(collider)
+                .insert(ColliderMassProperties::Density(co.density()))
+                .insert(Friction {
+                    coefficient: co.friction(),
+                    combine_rule: co.friction_combine_rule().into(),
+                })
+                .insert(Restitution {
+                    coefficient: co.restitution(),
+                    combine_rule: co.restitution_combine_rule().into(),
+                })
+                .insert(CollisionGroups::new(
+                    group(co.collision_groups().memberships),
+                    group(co.collision_groups().filter),
+                ))
+                .insert(SolverGroups::new(
+                    group(co.solver_groups().memberships),
+                    group(co.solver_groups().filter),
+                ))
+                .insert(ActiveEvents::from_bits_truncate(co.active_events().bits()))
+                .insert(ActiveHooks::from_bits_truncate(co.active_hooks().bits()))
+                .insert(ActiveCollisionTypes::from_bits_truncate(
+                    co.active_collision_types().bits(),
+                ))
+                .insert(ContactForceEventThreshold(
+                    co.contact_force_event_threshold(),
+                ));
+            if co.is_sensor() {
+                entity.insert(Sensor);
+            }
+            let entity = entity.id();
+            if let Some(parent) = parent {
+                commands.entity(*parent).add_child(entity);
+            }
+            entities.colliders.insert(handle, entity);
+        }
+
+        let joint_parent = |handle| {
+            if Some(handle) == world_body {
+                Some(JointParent::World)
+            } else {
+                entities
+                    .bodies
+                    .get(&handle)
+                    .copied()
+                    .map(JointParent::Entity)
+            }
+        };
+        let mut joints = vec![];
+        for (_, joint) in self.impulse_joints.iter() {
+            let mut data = GenericJoint::from_rapier(joint.data, physics_scale);
+            let (mut parent, mut body) = (joint_parent(joint.body1), joint_parent(joint.body2));
+            if body == Some(JointParent::World) {
+                // Only the first body of a joint can be the world.
+                std::mem::swap(&mut parent, &mut body);
+                std::mem::swap(&mut data.raw.local_frame1, &mut data.raw.local_frame2);
+            }
+            if let (Some(parent), Some(JointParent::Entity(body))) = (parent, body) {
+                joints.push((body, ImpulseJoint::new(parent, data)));
+            }
+        }
+        for (body, joint) in joints {
+            let child = commands.spawn().insert(joint).id();
+            commands.entity(body).add_child(child);
+        }
+
+        for (_, multibody, link) in self.multibody_joints.iter() {
+            let parent = link
+                .parent_id()
+                .and_then(|id| multibody.link(id))
+                .and_then(|parent| entities.bodies.get(&parent.rigid_body_handle()));
+            let body = entities.bodies.get(&link.rigid_body_handle());
+            if let (Some(parent), Some(body)) = (parent, body) {
+                let data = GenericJoint::from_rapier(link.joint.data, physics_scale);
+                commands
+                    .entity(*body)
+                    .insert(MultibodyJoint::new(*parent, data));
+            }
+        }
+
+        entities
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+impl RawRapierWorld {
+    /// Serializes this world with `bincode`.
+    pub fn to_bytes(&self) -> bincode::Result<Vec<u8>> {
+        bincode::serialize(self)
+    }
+
+    /// Deserializes a world serialized with [`Self::to_bytes`], or by plain Rapier.
+    pub fn from_bytes(bytes: &[u8]) -> bincode::Result<Self> {
+        bincode::deserialize(bytes)
+    }
+}
+
+fn group(group: rapier::geometry::Group) -> Group {
+    Group::from_bits_truncate(group.bits())
+}
@@ -0,0 +1,630 @@
+use crate::math::Vect;
+use crate::plugin::configuration::{SimulationToRenderTime, TimestepMode};
+use crate::plugin::{PhysicsSnapshot, RapierContext};
+use bevy::prelude::Entity;
+use bevy::time::Time;
+use rapier::prelude::{AngVector, Collider, Isometry, Real, RigidBody, RigidBodyType, Vector};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// A change made to the physics world between two simulation steps, recorded by the
+/// [`PhysicsRecorder`].
+///
+/// The values are in physics units, i.e. the lengths are divided by the physics scale.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub enum PhysicsInput {
+    /// The [`ExternalForce`](crate::dynamics::ExternalForce) of a rigid-body changed.
+    Force {
+        /// The entity of the rigid-body.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The new force.
+        force: Vector<Real>,
+        /// The new torque.
+        torque: AngVector<Real>,
+    },
+    /// The velocities of a rigid-body changed, e.g. because of an
+    /// [`ExternalImpulse`](crate::dynamics::ExternalImpulse) or of its
+    /// [`Velocity`](crate::dynamics::Velocity). The resulting velocities are recorded rather than
+    /// the impulses, so that they are restored exactly.
+    Impulse {
+        /// The entity of the rigid-body.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The new linear velocity.
+        linvel: Vector<Real>,
+        /// The new angular velocity.
+        angvel: AngVector<Real>,
+    },
+    /// A rigid-body, or a collider without rigid-body, was moved, e.g. by changing its transform.
+    Move {
+        /// The entity of the rigid-body or collider.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The new position.
+        position: Isometry<Real>,
+    },
+    /// The next position of a kinematic position-based rigid-body changed, e.g. when it is moved
+    /// by a [`KinematicCharacterController`](crate::control::KinematicCharacterController).
+    KinematicTarget {
+        /// The entity of the rigid-body.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The position the rigid-body reaches at the end of the next step.
+        next_position: Isometry<Real>,
+    },
+    /// A rigid-body was added.
+    SpawnRigidBody {
+        /// The entity of the rigid-body.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The new rigid-body, without its colliders.
+        rigid_body: RigidBody,
+    },
+    /// A collider was added.
+    SpawnCollider {
+        /// The entity of the collider.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+        /// The entity of the rigid-body the collider is attached to, if any.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::optional_entity_bits")
+        )]
+        parent: Option<Entity>,
+        /// The new collider.
+        collider: Collider,
+    },
+    /// A rigid-body was removed. Its colliders are removed by their own
+    /// [`PhysicsInput::DespawnCollider`].
+    DespawnRigidBody {
+        /// The entity of the rigid-body.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+    },
+    /// A collider was removed.
+    DespawnCollider {
+        /// The entity of the collider.
+        #[cfg_attr(
+            feature = "serde-serialize",
+            serde(with = "crate::plugin::snapshot::entity_bits")
+        )]
+        entity: Entity,
+    },
+}
+
+/// The inputs of one update of the physics world, recorded by the [`PhysicsRecorder`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct RecordedFrame {
+    /// The gravity of the [`RapierConfiguration`](crate::plugin::RapierConfiguration).
+    pub gravity: Vect,
+    /// The timestep mode of the [`RapierConfiguration`](crate::plugin::RapierConfiguration).
+    pub timestep_mode: TimestepMode,
+    /// The time elapsed since the previous update.
+    pub delta: Duration,
+    /// The changes made to the physics world before the simulation steps of this update, in the
+    /// order they are replayed.
+    pub inputs: Vec<PhysicsInput>,
+}
+
+/// A recording of the physics simulation, made by the [`PhysicsRecorder`].
+///
+/// It starts with a snapshot of the physics world, followed by the inputs of each update. With
+/// the `serde-serialize` feature, it can be written to a `.replay` file with [`Self::save`], e.g.
+/// by a player reporting a bug, and read back with [`Self::load`] to replay it with
+/// [`Self::play`].
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct PhysicsRecording {
+    /// The physics world when the recording started.
+    pub snapshot: PhysicsSnapshot,
+    /// The inputs of each update recorded since then.
+    pub frames: Vec<RecordedFrame>,
+    pub(crate) physics_scale: Real,
+    pub(crate) sim_to_render_time: f32,
+}
+
+impl PhysicsRecording {
+    /// Starts replaying this recording in a new physics world, restored from its snapshot.
+    pub fn play(&self) -> PhysicsPlayback {
+        PhysicsPlayback::new(self)
+    }
+
+    /// Writes this recording to a `.replay` file.
+    #[cfg(feature = "serde-serialize")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        bincode::serialize_into(file, self)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a recording from a `.replay` file written with [`Self::save`].
+    #[cfg(feature = "serde-serialize")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::io::BufReader::new(std::fs::File::open(path)?);
+        bincode::deserialize_from(file)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+}
+
+/// The replay of a [`PhysicsRecording`], in a physics world separate from the one of the app.
+///
+/// Each call to [`Self::step`] applies the inputs of the next recorded update, then advances
+/// the simulation like this update did. Since the simulation is deterministic, the physics world
+/// goes through the same states as the recorded one, and can be inspected with
+/// [`Self::context`] after each step to find where a bug happens.
+pub struct PhysicsPlayback {
+    context: RapierContext,
+    frames: Vec<RecordedFrame>,
+    frame: usize,
+    time: Time,
+    sim_to_render_time: SimulationToRenderTime,
+}
+
+impl PhysicsPlayback {
+    fn new(recording: &PhysicsRecording) -> Self {
+        let mut context = RapierContext {
+            physics_scale: recording.physics_scale,
+            ..Default::default()
+        };
+        context.restore(recording.snapshot.clone());
+        let mut time = Time::default();
+        time.update_with_instant(Instant::now());
+
+        Self {
+            context,
+            frames: recording.frames.clone(),
+            frame: 0,
+            time,
+            sim_to_render_time: SimulationToRenderTime {
+                diff: recording.sim_to_render_time,
+            },
+        }
+    }
+
+    /// The physics world being replayed.
+    pub fn context(&self) -> &RapierContext {
+        &self.context
+    }
+
+    /// The index of the next update to replay.
+    pub fn frame(&self) -> usize {
+        self.frame
+    }
+
+    /// Whether all the recorded updates have been replayed.
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.frames.len()
+    }
+
+    /// Replays the next recorded update, returning `false` if there isn’t any left.
+    pub fn step(&mut self) -> bool {
+        let frame = match self.frames.get(self.frame) {
+            Some(frame) => frame,
+            None => return false,
+        };
+
+        apply_inputs(&mut self.context, &frame.inputs);
+
+        let now = self.time.last_update().unwrap_or(self.time.startup()) + frame.delta;
+        self.time.update_with_instant(now);
+        self.context.step_simulation(
+            frame.gravity,
+            frame.timestep_mode,
+            None,
+            &(),
+            &self.time,
+            &mut self.sim_to_render_time,
+            None,
+        );
+        self.context.update_query_pipeline();
+        self.context.deleted_colliders.clear();
+        self.context.deleted_sensors.clear();
+        self.context.collision_event_details.clear();
+        self.context.sensor_events.clear();
+        self.context.ccd_impacts.clear();
+
+        self.frame += 1;
+        true
+    }
+
+    /// Replays all the remaining recorded updates.
+    pub fn run_to_end(&mut self) {
+        while self.step() {}
+    }
+}
+
+#[derive(Copy, Clone)]
+struct RecordedBody {
+    position: Isometry<Real>,
+    linvel: Vector<Real>,
+    angvel: AngVector<Real>,
+}
+
+#[derive(Copy, Clone)]
+struct RecordedCollider {
+    // Only set for the colliders without rigid-body.
+    position: Option<Isometry<Real>>,
+}
+
+/// A resource recording the inputs of the physics simulation, to replay them later from the
+/// same initial state.
+///
+/// Once started with [`Self::start`], the recorder takes a snapshot of the physics world after
+/// the next simulation step. Then, before each step, it records the changes made to the
+/// physics world since the previous one: the [`ExternalForce`](crate::dynamics::ExternalForce)s,
+/// the velocity changes (e.g. impulses), the moved rigid-bodies and colliders (e.g. by
+/// character controllers), and the added and removed rigid-bodies and colliders. The recording
+/// is retrieved with [`Self::stop`], and replayed with [`PhysicsRecording::play`].
+///
+/// The joints added or removed, the changes to the properties of the rigid-bodies and colliders
+/// (e.g. their shape or mass), the forces added by other components than `ExternalForce` (e.g.
+/// `RadialGravity`), and the physics hooks aren’t recorded, so a simulation relying on them
+/// won’t be replayed identically.
+#[derive(Default)]
+pub struct PhysicsRecorder {
+    starting: bool,
+    recording: Option<PhysicsRecording>,
+    pending_inputs: Vec<PhysicsInput>,
+    bodies: HashMap<Entity, RecordedBody>,
+    colliders: HashMap<Entity, RecordedCollider>,
+}
+
+impl PhysicsRecorder {
+    /// Starts a new recording after the next simulation step, discarding the current one.
+    pub fn start(&mut self) {
+        *self = Self {
+            starting: true,
+            ..Default::default()
+        };
+    }
+
+    /// Stops recording, returning the recording if any.
+    pub fn stop(&mut self) -> Option<PhysicsRecording> {
+        let recording = self.recording.take();
+        *self = Self::default();
+        recording
+    }
+
+    /// Whether a recording is started.
+    pub fn is_recording(&self) -> bool {
+        self.starting || self.recording.is_some()
+    }
+
+    /// The current recording, if any.
+    pub fn recording(&self) -> Option<&PhysicsRecording> {
+        self.recording.as_ref()
+    }
+
+    /// Records the changes made to the physics world since the last simulation step.
+    pub(crate) fn record_inputs(
+        &mut self,
+        context: &RapierContext,
+        forces: impl Iterator<Item = (Entity, Vector<Real>, AngVector<Real>)>,
+    ) {
+        if self.recording.is_none() {
+            return;
+        }
+
+        // The entities are sorted for a stable recording, and the added objects by handle, for
+        // the replay to give them the same handles in most cases.
+        let mut despawned_bodies: Vec<_> = self
+            .bodies
+            .keys()
+            .filter(|entity| !context.entity2body.contains_key(entity))
+            .copied()
+            .collect();
+        despawned_bodies.sort();
+        let mut despawned_colliders: Vec<_> = self
+            .colliders
+            .keys()
+            .filter(|entity| !context.entity2collider.contains_key(entity))
+            .copied()
+            .collect();
+        despawned_colliders.sort();
+        let mut spawned_bodies: Vec<_> = context
+            .entity2body
+            .iter()
+            .filter(|(entity, _)| !self.bodies.contains_key(entity))
+            .map(|(entity, handle)| (*handle, *entity))
+            .collect();
+        spawned_bodies.sort_by_key(|(handle, _)| handle.into_raw_parts());
+        let mut spawned_colliders: Vec<_> = context
+            .entity2collider
+            .iter()
+            .filter(|(entity, _)| !self.colliders.contains_key(entity))
+            .map(|(entity, handle)| (*handle, *entity))
+            .collect();
+        spawned_colliders.sort_by_key(|(handle, _)| handle.into_raw_parts());
+
+        let inputs = &mut self.pending_inputs;
+        inputs.extend(
+            despawned_bodies
+                .into_iter()
+                .map(|entity| PhysicsInput::DespawnRigidBody { entity }),
+        );
+        inputs.extend(
+            despawned_colliders
+                .into_iter()
+                .map(|entity| PhysicsInput::DespawnCollider { entity }),
+        );
+        inputs.extend(spawned_bodies.into_iter().map(|(handle, entity)| {
+            PhysicsInput::SpawnRigidBody {
+                entity,
+                rigid_body: context.bodies[handle].clone(),
+            }
+        }));
+        inputs.extend(spawned_colliders.into_iter().map(|(handle, entity)| {
+            let collider = &context.colliders[handle];
+            PhysicsInput::SpawnCollider {
+                entity,
+                parent: collider
+                    .parent()
+                    .and_then(|parent| context.rigid_body_entity(parent)),
+                collider: collider.clone(),
+            }
+        }));
+
+        let mut forces: Vec<_> = forces
+            .filter(|(entity, ..)| context.entity2body.contains_key(entity))
+            .collect();
+        forces.sort_by_key(|(entity, ..)| *entity);
+        inputs.extend(
+            forces
+                .into_iter()
+                .map(|(entity, force, torque)| PhysicsInput::Force {
+                    entity,
+                    force,
+                    torque,
+                }),
+        );
+
+        let mut bodies: Vec<_> = self.bodies.iter().collect();
+        bodies.sort_by_key(|(entity, _)| **entity);
+        for (entity, recorded) in bodies {
+            let rb = match context
+                .entity2body
+                .get(entity)
+                .and_then(|handle| context.bodies.get(*handle))
+            {
+                Some(rb) => rb,
+                None => continue,
+            };
+
+            if *rb.position() != recorded.position {
+                inputs.push(PhysicsInput::Move {
+                    entity: *entity,
+                    position: *rb.position(),
+                });
+            }
+            if rb.body_type() == RigidBodyType::KinematicPositionBased
+                && rb.next_position() != rb.position()
+            {
+                inputs.push(PhysicsInput::KinematicTarget {
+                    entity: *entity,
+                    next_position: *rb.next_position(),
+                });
+            }
+            if *rb.linvel() != recorded.linvel || body_angvel(rb) != recorded.angvel {
+                inputs.push(PhysicsInput::Impulse {
+                    entity: *entity,
+                    linvel: *rb.linvel(),
+                    angvel: body_angvel(rb),
+                });
+            }
+        }
+
+        let mut colliders: Vec<_> = self.colliders.iter().collect();
+        colliders.sort_by_key(|(entity, _)| **entity);
+        for (entity, recorded) in colliders {
+            let co = match context
+                .entity2collider
+                .get(entity)
+                .and_then(|handle| context.colliders.get(*handle))
+            {
+                Some(co) => co,
+                None => continue,
+            };
+
+            if co.parent().is_none() && Some(*co.position()) != recorded.position {
+                inputs.push(PhysicsInput::Move {
+                    entity: *entity,
+                    position: *co.position(),
+                });
+            }
+        }
+
+        self.record_state(context);
+    }
+
+    /// Ends the frame recorded after a simulation step, or starts the recording.
+    pub(crate) fn record_step(
+        &mut self,
+        context: &RapierContext,
+        gravity: Vect,
+        timestep_mode: TimestepMode,
+        delta: Duration,
+        sim_to_render_time: &SimulationToRenderTime,
+    ) {
+        if self.starting {
+            self.starting = false;
+            self.recording = Some(PhysicsRecording {
+                snapshot: context.snapshot(),
+                frames: vec![],
+                physics_scale: context.physics_scale,
+                sim_to_render_time: sim_to_render_time.diff,
+            });
+        } else if let Some(recording) = &mut self.recording {
+            recording.frames.push(RecordedFrame {
+                gravity,
+                timestep_mode,
+                delta,
+                inputs: std::mem::take(&mut self.pending_inputs),
+            });
+        } else {
+            return;
+        }
+
+        self.record_state(context);
+    }
+
+    // The state of the physics world the next changes are detected against.
+    fn record_state(&mut self, context: &RapierContext) {
+        self.bodies = context
+            .entity2body
+            .iter()
+            .filter_map(|(entity, handle)| {
+                let rb = context.bodies.get(*handle)?;
+                let recorded = RecordedBody {
+                    position: *rb.position(),
+                    linvel: *rb.linvel(),
+                    angvel: body_angvel(rb),
+                };
+                Some((*entity, recorded))
+            })
+            .collect();
+        self.colliders = context
+            .entity2collider
+            .iter()
+            .filter_map(|(entity, handle)| {
+                let co = context.colliders.get(*handle)?;
+                let recorded = RecordedCollider {
+                    position: co.parent().is_none().then(|| *co.position()),
+                };
+                Some((*entity, recorded))
+            })
+            .collect();
+    }
+}
+
+// Applies recorded inputs to a physics world, like the systems of the `SyncBackend` stage do.
+fn apply_inputs(context: &mut RapierContext, inputs: &[PhysicsInput]) {
+    let context = &mut *context;
+
+    for input in inputs {
+        match input {
+            PhysicsInput::Force {
+                entity,
+                force,
+                torque,
+            } => {
+                if let Some(rb) = body_mut(context, *entity) {
+                    rb.reset_forces(true);
+                    rb.reset_torques(true);
+                    rb.add_force(*force, true);
+                    rb.add_torque(*torque, true);
+                }
+            }
+            PhysicsInput::Impulse {
+                entity,
+                linvel,
+                angvel,
+            } => {
+                if let Some(rb) = body_mut(context, *entity) {
+                    rb.set_linvel(*linvel, true);
+                    rb.set_angvel(*angvel, true);
+                }
+            }
+            PhysicsInput::Move { entity, position } => {
+                if let Some(rb) = body_mut(context, *entity) {
+                    rb.set_position(*position, true);
+                } else if let Some(co) = context
+                    .entity2collider
+                    .get(entity)
+                    .and_then(|handle| context.colliders.get_mut(*handle))
+                {
+                    co.set_position(*position);
+                }
+            }
+            PhysicsInput::KinematicTarget {
+                entity,
+                next_position,
+            } => {
+                if let Some(rb) = body_mut(context, *entity) {
+                    rb.set_next_kinematic_position(*next_position);
+                }
+            }
+            PhysicsInput::SpawnRigidBody { entity, rigid_body } => {
+                let handle = context.bodies.insert(rigid_body.clone());
+                context.entity2body.insert(*entity, handle);
+            }
+            PhysicsInput::SpawnCollider {
+                entity,
+                parent,
+                collider,
+            } => {
+                let parent = parent.and_then(|parent| context.entity2body.get(&parent).copied());
+                let handle = match parent {
+                    Some(parent) => context.colliders.insert_with_parent(
+                        collider.clone(),
+                        parent,
+                        &mut context.bodies,
+                    ),
+                    None => context.colliders.insert(collider.clone()),
+                };
+                context.entity2collider.insert(*entity, handle);
+            }
+            PhysicsInput::DespawnRigidBody { entity } => {
+                if let Some(handle) = context.entity2body.remove(entity) {
+                    // Like `sync_removals`, the colliders are removed separately.
+                    context.bodies.remove(
+                        handle,
+                        &mut context.islands,
+                        &mut context.colliders,
+                        &mut context.impulse_joints,
+                        &mut context.multibody_joints,
+                        false,
+                    );
+                }
+            }
+            PhysicsInput::DespawnCollider { entity } => {
+                if let Some(handle) = context.entity2collider.remove(entity) {
+                    context.colliders.remove(
+                        handle,
+                        &mut context.islands,
+                        &mut context.bodies,
+                        true,
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "dim2")]
+fn body_angvel(rb: &RigidBody) -> AngVector<Real> {
+    rb.angvel()
+}
+
+#[cfg(feature = "dim3")]
+fn body_angvel(rb: &RigidBody) -> AngVector<Real> {
+    *rb.angvel()
+}
+
+fn body_mut(context: &mut RapierContext, entity: Entity) -> Option<&mut RigidBody> {
+    let handle = context.entity2body.get(&entity)?;
+    context.bodies.get_mut(*handle)
+}
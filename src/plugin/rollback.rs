@@ -0,0 +1,138 @@
+use crate::plugin::{systems, PhysicsSnapshot, PhysicsStages, RapierContext, RapierPhysicsPlugin};
+use bevy::ecs::query::WorldQuery;
+use bevy::prelude::*;
+use bevy_ggrs::GGRSPlugin;
+use ggrs::Config;
+use std::hash::{Hash, Hasher};
+
+/// The state of the physics world saved and loaded by [GGRS](https://github.com/gschup/ggrs)
+/// during rollbacks, with the `ggrs` feature.
+///
+/// This resource holds a [`PhysicsSnapshot`] of the physics world, taken after each simulation
+/// step. It is registered as a rollback type of the `GGRSPlugin` with
+/// [`RapierRollbackExt::register_physics_rollback_types`]. When GGRS loads an older state, the
+/// physics world is restored from its snapshot before the next simulation step, and the entities
+/// are associated again with their rigid-bodies, colliders and joints. The checksum GGRS uses to
+/// detect desyncs is [`PhysicsSnapshot::checksum`].
+///
+/// The physics must be simulated by the rollback schedule of GGRS, with exactly one simulation
+/// step per GGRS frame:
+/// - The `RapierPhysicsPlugin` must be added with
+///   [`with_default_system_setup(false)`](RapierPhysicsPlugin::with_default_system_setup), and
+///   the rollback schedule built from [`RapierPhysicsPlugin::rollback_schedule`]. The
+///   [`PhysicsStages::DetectDespawn`] stage must still be added to the app.
+/// - The `RapierConfiguration::timestep_mode` must be `TimestepMode::Fixed` with a `dt` of
+///   `1.0 / fps` and a single substep, where `fps` is the update frequency of the `GGRSPlugin`.
+///
+/// The `Transform` and `Velocity` of the rigid-bodies don’t need to be rolled back: the velocities
+/// are written back from the restored physics world when it is loaded, and the transforms after
+/// the next simulation step. They can still be registered if the systems of the rollback schedule
+/// read the transforms before the physics stages. The other physics components must only be
+/// modified by the systems of the rollback schedule, and the colliders should be attached to
+/// rigid-bodies, a collider without rigid-body being moved to its transform one step after its
+/// creation.
+///
+/// It is inserted as a resource by the `RapierPhysicsPlugin`, and saved and loaded by `bevy_ggrs`
+/// as a rollback resource. It only derives `Component` because `bevy_ggrs` requires all its
+/// rollback types, resources included, to implement it.
+#[derive(Clone, Component, Reflect)]
+#[reflect_value(Hash)]
+pub struct PhysicsRollbackState {
+    pub(crate) snapshot: PhysicsSnapshot,
+    pub(crate) checksum: u64,
+}
+
+impl PhysicsRollbackState {
+    /// The snapshot of the physics world taken after the last simulation step.
+    pub fn snapshot(&self) -> &PhysicsSnapshot {
+        &self.snapshot
+    }
+
+    /// The checksum of the snapshot.
+    pub fn checksum(&self) -> u64 {
+        self.checksum
+    }
+}
+
+impl From<PhysicsSnapshot> for PhysicsRollbackState {
+    fn from(snapshot: PhysicsSnapshot) -> Self {
+        Self {
+            checksum: snapshot.checksum(),
+            snapshot,
+        }
+    }
+}
+
+impl Default for PhysicsRollbackState {
+    /// The state of an empty physics world, before its first simulation step.
+    fn default() -> Self {
+        RapierContext::default().snapshot().into()
+    }
+}
+
+impl Hash for PhysicsRollbackState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.checksum.hash(state);
+    }
+}
+
+/// Registers the physics as rollback state of a `GGRSPlugin`.
+pub trait RapierRollbackExt {
+    /// Registers [`PhysicsRollbackState`] as a rollback type.
+    #[must_use]
+    fn register_physics_rollback_types(self) -> Self;
+}
+
+impl<T: Config + Send + Sync> RapierRollbackExt for GGRSPlugin<T> {
+    fn register_physics_rollback_types(self) -> Self {
+        self.register_rollback_type::<PhysicsRollbackState>()
+    }
+}
+
+impl<PhysicsHooksData: 'static + WorldQuery + Send + Sync> RapierPhysicsPlugin<PhysicsHooksData> {
+    /// A schedule running the [`PhysicsStages::SyncBackend`], [`PhysicsStages::StepSimulation`]
+    /// and [`PhysicsStages::Writeback`] stages, to be used as the rollback schedule of a
+    /// `GGRSPlugin`.
+    ///
+    /// The physics world is restored from the loaded [`PhysicsRollbackState`] by a first stage,
+    /// and saved into it at the end of the `Writeback` stage. The stages of the game logic can be
+    /// added before or after the physics stages.
+    pub fn rollback_schedule() -> Schedule {
+        Schedule::default()
+            .with_stage(
+                LoadRollbackStage,
+                SystemStage::parallel()
+                    .with_system(systems::load_rollback_state)
+                    .with_system(
+                        systems::sync_restored_snapshot.after(systems::load_rollback_state),
+                    ),
+            )
+            .with_stage_after(
+                LoadRollbackStage,
+                PhysicsStages::SyncBackend,
+                SystemStage::parallel()
+                    .with_system_set(Self::get_systems(PhysicsStages::SyncBackend)),
+            )
+            .with_stage_after(
+                PhysicsStages::SyncBackend,
+                PhysicsStages::StepSimulation,
+                SystemStage::parallel()
+                    .with_system_set(Self::get_systems(PhysicsStages::StepSimulation)),
+            )
+            .with_stage_after(
+                PhysicsStages::StepSimulation,
+                PhysicsStages::Writeback,
+                SystemStage::parallel()
+                    .with_system_set(Self::get_systems(PhysicsStages::Writeback))
+                    .with_system(
+                        systems::save_rollback_state.after(systems::writeback_rigid_bodies),
+                    ),
+            )
+    }
+}
+
+// The stage restoring the physics world, separate from `PhysicsStages::SyncBackend` so that the
+// handle components updated with commands are up-to-date before the new rigid-bodies and
+// colliders are initialized.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, StageLabel)]
+struct LoadRollbackStage;
@@ -0,0 +1,215 @@
+use crate::dynamics::CoefficientCombineRule;
+#[cfg(feature = "dim3")]
+use crate::geometry::SpinFriction;
+use crate::geometry::{PhysicsMaterial, PhysicsMaterials, RollingFriction};
+use crate::math::Real;
+use bevy::prelude::{Entity, Handle};
+use rapier::prelude::{ColliderHandle, ColliderSet, NarrowPhase, RigidBodyHandle, RigidBodySet};
+use std::collections::HashMap;
+
+#[cfg(feature = "dim3")]
+use rapier::prelude::Vector;
+
+/// The components slowing down the rotation of the rigid-bodies in contact, gathered from the
+/// ECS by the `update_rolling_friction` system.
+///
+/// Rapier’s contact modification cannot express torques, so the resistance is applied after
+/// each simulation step, as angular impulses proportional to the normal impulses computed by
+/// this step.
+#[derive(Default)]
+pub(crate) struct RollingResistances {
+    pub rolling_frictions: HashMap<Entity, RollingFriction>,
+    #[cfg(feature = "dim3")]
+    pub spin_frictions: HashMap<Entity, SpinFriction>,
+    pub materials: HashMap<Entity, Handle<PhysicsMaterial>>,
+    pub physics_materials: Option<PhysicsMaterials>,
+}
+
+impl RollingResistances {
+    fn is_empty(&self) -> bool {
+        #[cfg(feature = "dim2")]
+        let no_friction = self.rolling_frictions.is_empty();
+        #[cfg(feature = "dim3")]
+        let no_friction = self.rolling_frictions.is_empty() && self.spin_frictions.is_empty();
+
+        no_friction && (self.physics_materials.is_none() || self.materials.is_empty())
+    }
+
+    /// Applies the rolling resistance of the contact pairs of the last simulation step to their
+    /// rigid-bodies.
+    pub fn apply(
+        &self,
+        narrow_phase: &NarrowPhase,
+        colliders: &ColliderSet,
+        bodies: &mut RigidBodySet,
+        physics_scale: Real,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        // The component of a collider, falling back to the one of its rigid-body.
+        fn collider_component<'a, T>(
+            colliders: &ColliderSet,
+            bodies: &RigidBodySet,
+            components: &'a HashMap<Entity, T>,
+            handle: ColliderHandle,
+        ) -> Option<&'a T> {
+            let co = colliders.get(handle);
+            co.and_then(|co| components.get(&Entity::from_bits(co.user_data as u64)))
+                .or_else(|| {
+                    co.and_then(|co| co.parent())
+                        .and_then(|h| bodies.get(h))
+                        .and_then(|rb| components.get(&Entity::from_bits(rb.user_data as u64)))
+                })
+        }
+
+        // Combines the coefficients of two colliders, falling back to the ones of their rigid-bodies.
+        fn combined_coefficient<T>(
+            colliders: &ColliderSet,
+            bodies: &RigidBodySet,
+            components: &HashMap<Entity, T>,
+            handles: [ColliderHandle; 2],
+            get: impl Fn(&T) -> (f32, CoefficientCombineRule),
+        ) -> f32 {
+            let [(c1, r1), (c2, r2)] = handles.map(|handle| {
+                collider_component(colliders, bodies, components, handle)
+                    .map(&get)
+                    .unwrap_or((0.0, CoefficientCombineRule::Average))
+            });
+            CoefficientCombineRule::combine(c1, r1, c2, r2)
+        }
+
+        let mut angular_impulses = vec![];
+
+        for pair in narrow_phase.contact_pairs() {
+            if !pair.has_any_active_contact {
+                continue;
+            }
+
+            let handles = [pair.collider1, pair.collider2];
+            let material_rolling = self.physics_materials.as_ref().and_then(|materials| {
+                let material1 = collider_component(colliders, bodies, &self.materials, handles[0])?;
+                let material2 = collider_component(colliders, bodies, &self.materials, handles[1])?;
+                materials.get(material1, material2)?.rolling_friction
+            });
+            let rolling = match material_rolling {
+                Some(rolling) => rolling / physics_scale,
+                None => {
+                    combined_coefficient(colliders, bodies, &self.rolling_frictions, handles, |f| {
+                        (f.coefficient, f.combine_rule)
+                    }) / physics_scale
+                }
+            };
+            #[cfg(feature = "dim3")]
+            let spin =
+                combined_coefficient(colliders, bodies, &self.spin_frictions, handles, |f| {
+                    (f.coefficient, f.combine_rule)
+                }) / physics_scale;
+
+            for manifold in &pair.manifolds {
+                let normal_impulse: Real = manifold.points.iter().map(|pt| pt.data.impulse).sum();
+                if normal_impulse <= 0.0 {
+                    continue;
+                }
+
+                let rb1 = manifold.data.rigid_body1;
+                let rb2 = manifold.data.rigid_body2;
+                #[cfg(feature = "dim2")]
+                let angvel = |h: Option<RigidBodyHandle>| {
+                    h.and_then(|h| bodies.get(h))
+                        .map(|rb| rb.angvel())
+                        .unwrap_or_default()
+                };
+                #[cfg(feature = "dim3")]
+                let angvel = |h: Option<RigidBodyHandle>| {
+                    h.and_then(|h| bodies.get(h))
+                        .map(|rb| *rb.angvel())
+                        .unwrap_or_default()
+                };
+
+                for (handle, other) in [(rb1, rb2), (rb2, rb1)] {
+                    let rb = match handle.and_then(|h| bodies.get(h)) {
+                        Some(rb) if rb.is_dynamic() => rb,
+                        _ => continue,
+                    };
+                    let inv_inertia_sqrt =
+                        rb.mass_properties().world_inv_inertia_sqrt(rb.rotation());
+                    let relative_angvel = angvel(handle) - angvel(other);
+
+                    #[cfg(feature = "dim2")]
+                    let impulse =
+                        opposing_angular_impulse(relative_angvel, rolling * normal_impulse, |j| {
+                            inv_inertia_sqrt * inv_inertia_sqrt * j
+                        });
+                    #[cfg(feature = "dim3")]
+                    let impulse = {
+                        let normal = manifold.data.normal;
+                        let spin_angvel = normal * normal.dot(&relative_angvel);
+                        let inv_inertia =
+                            |j: Vector<Real>| inv_inertia_sqrt * (inv_inertia_sqrt * j);
+                        opposing_angular_impulse(
+                            relative_angvel - spin_angvel,
+                            rolling * normal_impulse,
+                            inv_inertia,
+                        ) + opposing_angular_impulse(
+                            spin_angvel,
+                            spin * normal_impulse,
+                            inv_inertia,
+                        )
+                    };
+
+                    angular_impulses.push((handle.unwrap(), impulse));
+                }
+            }
+        }
+
+        for (handle, impulse) in angular_impulses {
+            if let Some(rb) = bodies.get_mut(handle) {
+                rb.apply_torque_impulse(impulse, false);
+            }
+        }
+    }
+}
+
+/// The angular impulse of magnitude `max_impulse` opposing `angvel`, reduced so that it
+/// stops the rotation at most instead of reversing it.
+#[cfg(feature = "dim2")]
+fn opposing_angular_impulse(
+    angvel: Real,
+    max_impulse: Real,
+    inv_inertia: impl Fn(Real) -> Real,
+) -> Real {
+    let impulse = -angvel.signum() * max_impulse;
+    let delta = inv_inertia(impulse).abs();
+
+    if delta > angvel.abs() {
+        impulse * angvel.abs() / delta
+    } else {
+        impulse
+    }
+}
+
+/// The angular impulse of magnitude `max_impulse` opposing `angvel`, reduced so that it
+/// stops the rotation at most instead of reversing it.
+#[cfg(feature = "dim3")]
+fn opposing_angular_impulse(
+    angvel: Vector<Real>,
+    max_impulse: Real,
+    inv_inertia: impl Fn(Vector<Real>) -> Vector<Real>,
+) -> Vector<Real> {
+    let speed = angvel.norm();
+    if speed <= Real::EPSILON {
+        return Vector::zeros();
+    }
+
+    let dir = angvel / speed;
+    let impulse = -dir * max_impulse;
+    let delta = -inv_inertia(impulse).dot(&dir);
+
+    if delta > speed {
+        impulse * speed / delta
+    } else {
+        impulse
+    }
+}
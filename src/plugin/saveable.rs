@@ -0,0 +1,184 @@
+use crate::dynamics::{
+    AdditionalMassProperties, Ccd, Damping, Dominance, ExternalForce, ExternalImpulse,
+    GravityScale, ImpulseJoint, LockedAxes, MultibodyJoint, RampedJointMotor, RigidBody, Sleeping,
+    TransformInterpolation, Velocity,
+};
+use crate::geometry::{
+    ActiveCollisionTypes, ActiveEvents, ActiveHooks, Collider, ColliderMassProperties,
+    ColliderScale, CollisionGroups, ContactForceEventThreshold, Friction, Restitution, Sensor,
+    SolverGroups,
+};
+use crate::plugin::{systems, RapierContext};
+use bevy::ecs::reflect::ReflectComponent;
+use bevy::prelude::*;
+use bevy::reflect::TypeRegistryArc;
+use bevy::scene::{DynamicEntity, DynamicScene};
+use bevy::utils::HashSet;
+use std::any::TypeId;
+
+/// The components to save to reconstruct the physics simulation, for the save crates
+/// persisting the reflected components of the entities instead of a [`PhysicsSnapshot`].
+///
+/// This resource is inserted by the `RapierPhysicsPlugin` with the transforms, the hierarchy,
+/// and the physics components describing the rigid-bodies, colliders and joints. The Rapier
+/// handles are not saved: the rigid-bodies, colliders and joints are created again from the
+/// loaded components. A save crate selecting the saved components by type can register
+/// [`Self::type_ids`], or the scene to save can be extracted with [`Self::extract_scene`].
+///
+/// The velocity and the sleeping state of a rigid-body are only saved by the [`Velocity`] and
+/// [`Sleeping`] components if the entity has them, which the physics then keeps up-to-date.
+/// [`Self::extract_scene`] reads them from the physics world otherwise. The forces of a
+/// rigid-body are only saved by its [`ExternalForce`] component.
+///
+/// [`PhysicsSnapshot`]: crate::plugin::PhysicsSnapshot
+#[derive(Clone, Debug)]
+pub struct SaveablePhysics {
+    types: HashSet<TypeId>,
+}
+
+impl Default for SaveablePhysics {
+    fn default() -> Self {
+        let mut saveable = Self {
+            types: HashSet::default(),
+        };
+        saveable
+            .register::<Transform>()
+            .register::<GlobalTransform>()
+            .register::<Parent>()
+            .register::<Children>()
+            .register::<RigidBody>()
+            .register::<Velocity>()
+            .register::<AdditionalMassProperties>()
+            .register::<LockedAxes>()
+            .register::<ExternalForce>()
+            .register::<ExternalImpulse>()
+            .register::<Sleeping>()
+            .register::<Damping>()
+            .register::<Dominance>()
+            .register::<Ccd>()
+            .register::<GravityScale>()
+            .register::<TransformInterpolation>()
+            .register::<ImpulseJoint>()
+            .register::<MultibodyJoint>()
+            .register::<RampedJointMotor>()
+            .register::<Collider>()
+            .register::<ColliderScale>()
+            .register::<ColliderMassProperties>()
+            .register::<Sensor>()
+            .register::<Friction>()
+            .register::<Restitution>()
+            .register::<CollisionGroups>()
+            .register::<SolverGroups>()
+            .register::<ActiveCollisionTypes>()
+            .register::<ActiveEvents>()
+            .register::<ActiveHooks>()
+            .register::<ContactForceEventThreshold>();
+        saveable
+    }
+}
+
+impl SaveablePhysics {
+    /// Adds a component to the saved components.
+    pub fn register<T: Component>(&mut self) -> &mut Self {
+        self.types.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Removes a component from the saved components.
+    pub fn unregister<T: Component>(&mut self) -> &mut Self {
+        self.types.remove(&TypeId::of::<T>());
+        self
+    }
+
+    /// Is this component saved?
+    pub fn contains<T: Component>(&self) -> bool {
+        self.types.contains(&TypeId::of::<T>())
+    }
+
+    /// The type ids of the saved components.
+    pub fn type_ids(&self) -> impl Iterator<Item = TypeId> + '_ {
+        self.types.iter().copied()
+    }
+
+    /// Extracts the saved components of all the entities of the world into a scene.
+    ///
+    /// Only the components registered in the `TypeRegistry` with `#[reflect(Component)]` are
+    /// extracted. The [`Velocity`] and [`Sleeping`] of the rigid-bodies
+    /// without these components are read from the [`RapierContext`], if the world has one.
+    pub fn extract_scene(&self, world: &World) -> DynamicScene {
+        let type_registry = world.resource::<TypeRegistryArc>().read();
+        let context = world.get_resource::<RapierContext>();
+        let mut scene = DynamicScene::default();
+
+        for archetype in world.archetypes().iter() {
+            let saved: Vec<_> = archetype
+                .components()
+                .filter_map(|component_id| {
+                    let type_id = world.components().get_info(component_id)?.type_id()?;
+                    if !self.types.contains(&type_id) {
+                        return None;
+                    }
+                    type_registry
+                        .get(type_id)?
+                        .data::<ReflectComponent>()
+                        .cloned()
+                })
+                .collect();
+            if saved.is_empty() {
+                continue;
+            }
+
+            for entity in archetype.entities() {
+                let mut components: Vec<_> = saved
+                    .iter()
+                    .filter_map(|reflect| reflect.reflect(world, *entity))
+                    .map(|component| component.clone_value())
+                    .collect();
+                if let Some(context) = context {
+                    components.extend(self.simulation_state(world, context, *entity));
+                }
+                scene.entities.push(DynamicEntity {
+                    entity: entity.id(),
+                    components,
+                });
+            }
+        }
+
+        scene
+    }
+
+    /// The saved components of the state of the rigid-body of an entity only known by the
+    /// physics world.
+    fn simulation_state(
+        &self,
+        world: &World,
+        context: &RapierContext,
+        entity: Entity,
+    ) -> Vec<Box<dyn Reflect>> {
+        let mut components: Vec<Box<dyn Reflect>> = Vec::new();
+        let handle = match context.entity2body.get(&entity) {
+            Some(handle) => *handle,
+            None => return components,
+        };
+        let rb = match context.bodies.get(handle) {
+            Some(rb) if !rb.is_fixed() => rb,
+            _ => return components,
+        };
+        let scale = context.physics_scale;
+
+        if self.contains::<Velocity>() && world.get::<Velocity>(entity).is_none() {
+            components.push(Box::new(systems::body_velocity(rb, scale)));
+        }
+
+        if self.contains::<Sleeping>() && world.get::<Sleeping>(entity).is_none() {
+            let activation = rb.activation();
+            components.push(Box::new(Sleeping {
+                linear_threshold: activation.linear_threshold,
+                angular_threshold: activation.angular_threshold,
+                sleeping: rb.is_sleeping(),
+            }));
+        }
+
+        components
+    }
+}
@@ -0,0 +1,393 @@
+use crate::dynamics::WeldedBody;
+use crate::math::Real;
+use bevy::prelude::Entity;
+use bevy::utils::HashMap;
+use rapier::prelude::{
+    BroadPhase, CCDSolver, ColliderSet, ImpulseJointHandle, ImpulseJointSet, IntegrationParameters,
+    IslandManager, MultibodyJointHandle, MultibodyJointSet, NarrowPhase, RigidBodyHandle,
+    RigidBodySet,
+};
+use std::fmt;
+
+/// A complete copy of the state of the physics world, taken with
+/// [`RapierContext::snapshot`](crate::plugin::RapierContext::snapshot) and restored with
+/// [`RapierContext::restore`](crate::plugin::RapierContext::restore).
+///
+/// Besides the rigid-bodies, colliders and joints, it includes the islands, the broad-phase,
+/// and the contact manifolds of the narrow-phase with the impulses the solver uses to
+/// warm-start the next step, so a restored simulation continues like the original one.
+/// With the `serde-serialize` feature, it can be serialized, e.g. to save a game or to send an
+/// authoritative state to a client.
+#[derive(Clone)]
+#[cfg_attr(feature = "serde-serialize", derive(Serialize, Deserialize))]
+pub struct PhysicsSnapshot {
+    /// The island manager.
+    pub islands: IslandManager,
+    /// The broad-phase.
+    pub broad_phase: BroadPhase,
+    /// The narrow-phase, with the contact and intersection graphs.
+    pub narrow_phase: NarrowPhase,
+    /// The rigid-bodies.
+    pub bodies: RigidBodySet,
+    /// The colliders.
+    pub colliders: ColliderSet,
+    /// The impulse joints.
+    pub impulse_joints: ImpulseJointSet,
+    /// The multibody joints.
+    pub multibody_joints: MultibodyJointSet,
+    /// The continuous collision detection solver.
+    pub ccd_solver: CCDSolver,
+    /// The integration parameters.
+    pub integration_parameters: IntegrationParameters,
+    pub(crate) step_index: u64,
+    pub(crate) world_body: Option<RigidBodyHandle>,
+    // The joints don’t have user data to find their entity with.
+    #[cfg_attr(feature = "serde-serialize", serde(with = "entity_keys"))]
+    pub(crate) impulse_joint_entities: Vec<(Entity, ImpulseJointHandle)>,
+    #[cfg_attr(feature = "serde-serialize", serde(with = "entity_keys"))]
+    pub(crate) multibody_joint_entities: Vec<(Entity, MultibodyJointHandle)>,
+    #[cfg_attr(feature = "serde-serialize", serde(with = "entity_keys"))]
+    pub(crate) welded_bodies: Vec<(Entity, WeldedBody)>,
+}
+
+impl PhysicsSnapshot {
+    /// The index of the next simulation step when the snapshot was taken.
+    pub fn step_index(&self) -> u64 {
+        self.step_index
+    }
+
+    /// A checksum of the positions and velocities of the rigid-bodies, to check that two peers
+    /// simulating the same world are still in sync.
+    ///
+    /// The checksum is computed from the exact bits of these values with the FNV-1a hash, so it
+    /// is the same on all the platforms where the simulation is deterministic (see the
+    /// `enhanced-determinism` feature).
+    pub fn checksum(&self) -> u64 {
+        checksum(self.step_index, &self.bodies)
+    }
+
+    /// The divergences between this snapshot and `other`, e.g. the same step simulated on
+    /// another machine, beyond the given thresholds.
+    ///
+    /// The rigid-bodies and colliders are matched by entity. The returned [`SnapshotDiff`] can be
+    /// printed as a report. Use [`DiffThresholds::default`] to report all the differences,
+    /// even of a single bit.
+    pub fn diff(&self, other: &Self, thresholds: &DiffThresholds) -> SnapshotDiff {
+        let mut diff = SnapshotDiff {
+            step_indices: (self.step_index, other.step_index),
+            ..Default::default()
+        };
+
+        let other_bodies: HashMap<_, _> = other
+            .bodies
+            .iter()
+            .map(|(_, body)| (Entity::from_bits(body.user_data as u64), body))
+            .collect();
+        let mut matched = 0;
+        for (_, body) in self.bodies.iter() {
+            let entity = Entity::from_bits(body.user_data as u64);
+            let other_body = match other_bodies.get(&entity) {
+                Some(other_body) => other_body,
+                None => {
+                    diff.only_in_self.push(entity);
+                    continue;
+                }
+            };
+            matched += 1;
+
+            let (position, other_position) = (body.position(), other_body.position());
+            let divergence = BodyDivergence {
+                entity,
+                translation: (position.translation.vector - other_position.translation.vector)
+                    .norm(),
+                rotation: position.rotation.angle_to(&other_position.rotation),
+                linvel: (body.linvel() - other_body.linvel()).norm(),
+                #[cfg(feature = "dim2")]
+                angvel: (body.angvel() - other_body.angvel()).abs(),
+                #[cfg(feature = "dim3")]
+                angvel: (body.angvel() - other_body.angvel()).norm(),
+            };
+
+            // A difference of a bit may be rounded away by the deltas: compare the values too.
+            let diverged = position != other_position
+                || body.linvel() != other_body.linvel()
+                || body.angvel() != other_body.angvel();
+            let exceeds_thresholds = divergence.translation > thresholds.translation
+                || divergence.rotation > thresholds.rotation
+                || divergence.linvel > thresholds.linvel
+                || divergence.angvel > thresholds.angvel;
+            if diverged && (exceeds_thresholds || *thresholds == DiffThresholds::default()) {
+                diff.bodies.push(divergence);
+            }
+        }
+
+        if matched != other_bodies.len() {
+            let bodies: HashMap<_, _> = self
+                .bodies
+                .iter()
+                .map(|(_, body)| (Entity::from_bits(body.user_data as u64), ()))
+                .collect();
+            diff.only_in_other.extend(
+                other_bodies
+                    .keys()
+                    .filter(|entity| !bodies.contains_key(entity)),
+            );
+        }
+
+        let pairs = self.contact_counts();
+        let other_pairs = other.contact_counts();
+        for (colliders, contacts) in &pairs {
+            let other_contacts = other_pairs.get(colliders).copied().unwrap_or(0);
+            if *contacts != other_contacts {
+                diff.contact_pairs.push(ContactPairDivergence {
+                    collider1: colliders.0,
+                    collider2: colliders.1,
+                    contacts: (*contacts, other_contacts),
+                });
+            }
+        }
+        for (colliders, other_contacts) in &other_pairs {
+            if *other_contacts != 0 && !pairs.contains_key(colliders) {
+                diff.contact_pairs.push(ContactPairDivergence {
+                    collider1: colliders.0,
+                    collider2: colliders.1,
+                    contacts: (0, *other_contacts),
+                });
+            }
+        }
+
+        // Sort the divergences so that the reports of two machines can be compared too.
+        diff.bodies.sort_by_key(|divergence| divergence.entity);
+        diff.only_in_self.sort();
+        diff.only_in_other.sort();
+        diff.contact_pairs
+            .sort_by_key(|divergence| (divergence.collider1, divergence.collider2));
+        diff
+    }
+
+    // The number of solver contacts of each contact pair, by the entities of their colliders.
+    fn contact_counts(&self) -> HashMap<(Entity, Entity), usize> {
+        let entity = |handle| {
+            self.colliders
+                .get(handle)
+                .map(|collider| Entity::from_bits(collider.user_data as u64))
+        };
+
+        self.narrow_phase
+            .contact_pairs()
+            .filter_map(|pair| {
+                let (collider1, collider2) = (entity(pair.collider1)?, entity(pair.collider2)?);
+                let contacts = pair
+                    .manifolds
+                    .iter()
+                    .map(|manifold| manifold.data.solver_contacts.len())
+                    .sum();
+                let colliders = if collider1 <= collider2 {
+                    (collider1, collider2)
+                } else {
+                    (collider2, collider1)
+                };
+                Some((colliders, contacts))
+            })
+            .collect()
+    }
+}
+
+// The checksum of `PhysicsSnapshot::checksum`, computed without copying the rigid-bodies.
+pub(crate) fn checksum(step_index: u64, bodies: &RigidBodySet) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325u64;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ *byte as u64).wrapping_mul(0x0100_0000_01b3);
+        }
+    };
+
+    write(&step_index.to_le_bytes());
+    for (handle, body) in bodies.iter() {
+        let (index, generation) = handle.into_raw_parts();
+        write(&index.to_le_bytes());
+        write(&generation.to_le_bytes());
+        for value in body
+            .position()
+            .to_homogeneous()
+            .iter()
+            .chain(body.linvel().iter())
+        {
+            write(&value.to_bits().to_le_bytes());
+        }
+        #[cfg(feature = "dim2")]
+        write(&body.angvel().to_bits().to_le_bytes());
+        #[cfg(feature = "dim3")]
+        body.angvel()
+            .iter()
+            .for_each(|value| write(&value.to_bits().to_le_bytes()));
+    }
+
+    hash
+}
+
+/// The differences below which the positions and velocities of two snapshots compared with
+/// [`PhysicsSnapshot::diff`] are considered equal.
+///
+/// With the default thresholds, all zero, any difference is reported.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct DiffThresholds {
+    /// The distance between the translations of a rigid-body.
+    pub translation: Real,
+    /// The angle, in radians, between the rotations of a rigid-body.
+    pub rotation: Real,
+    /// The norm of the difference between the linear velocities of a rigid-body.
+    pub linvel: Real,
+    /// The norm of the difference between the angular velocities of a rigid-body.
+    pub angvel: Real,
+}
+
+/// The divergences between two snapshots, computed with [`PhysicsSnapshot::diff`].
+///
+/// It is displayed as a report listing each divergence on its own line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SnapshotDiff {
+    /// The step indices of the two snapshots.
+    pub step_indices: (u64, u64),
+    /// The rigid-bodies whose position or velocity diverged.
+    pub bodies: Vec<BodyDivergence>,
+    /// The entities of the rigid-bodies only present in the first snapshot.
+    pub only_in_self: Vec<Entity>,
+    /// The entities of the rigid-bodies only present in the second snapshot.
+    pub only_in_other: Vec<Entity>,
+    /// The contact pairs with a different number of contacts.
+    pub contact_pairs: Vec<ContactPairDivergence>,
+}
+
+/// The divergence of a rigid-body between two snapshots.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BodyDivergence {
+    /// The entity of the rigid-body.
+    pub entity: Entity,
+    /// The distance between its translations.
+    pub translation: Real,
+    /// The angle, in radians, between its rotations.
+    pub rotation: Real,
+    /// The norm of the difference between its linear velocities.
+    pub linvel: Real,
+    /// The norm of the difference between its angular velocities.
+    pub angvel: Real,
+}
+
+/// A contact pair with a different number of solver contacts in two snapshots.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ContactPairDivergence {
+    /// The entity of the first collider.
+    pub collider1: Entity,
+    /// The entity of the second collider.
+    pub collider2: Entity,
+    /// The number of contacts in each snapshot, `0` if the pair doesn’t exist.
+    pub contacts: (usize, usize),
+}
+
+impl SnapshotDiff {
+    /// Whether no divergence was found.
+    pub fn is_empty(&self) -> bool {
+        self.step_indices.0 == self.step_indices.1
+            && self.bodies.is_empty()
+            && self.only_in_self.is_empty()
+            && self.only_in_other.is_empty()
+            && self.contact_pairs.is_empty()
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no divergence at step {}", self.step_indices.0);
+        }
+
+        writeln!(
+            f,
+            "divergences between steps {} and {}:",
+            self.step_indices.0, self.step_indices.1
+        )?;
+        for body in &self.bodies {
+            writeln!(
+                f,
+                "  body {:?}: translation {:e}, rotation {:e}, linvel {:e}, angvel {:e}",
+                body.entity, body.translation, body.rotation, body.linvel, body.angvel
+            )?;
+        }
+        for entity in &self.only_in_self {
+            writeln!(f, "  body {:?}: only in the first snapshot", entity)?;
+        }
+        for entity in &self.only_in_other {
+            writeln!(f, "  body {:?}: only in the second snapshot", entity)?;
+        }
+        for pair in &self.contact_pairs {
+            writeln!(
+                f,
+                "  contact pair ({:?}, {:?}): {} contacts vs. {}",
+                pair.collider1, pair.collider2, pair.contacts.0, pair.contacts.1
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// Serializes the entities as their bits, Bevy’s entities not being serializable.
+#[cfg(feature = "serde-serialize")]
+pub(crate) mod entity_bits {
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(entity: &Entity, serializer: S) -> Result<S::Ok, S::Error> {
+        entity.to_bits().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Entity, D::Error> {
+        u64::deserialize(deserializer).map(Entity::from_bits)
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+pub(crate) mod optional_entity_bits {
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        entity: &Option<Entity>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        entity.map(Entity::to_bits).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Entity>, D::Error> {
+        Option::<u64>::deserialize(deserializer).map(|bits| bits.map(Entity::from_bits))
+    }
+}
+
+#[cfg(feature = "serde-serialize")]
+mod entity_keys {
+    use bevy::prelude::Entity;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer, T: Serialize>(
+        values: &[(Entity, T)],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(
+            values
+                .iter()
+                .map(|(entity, value)| (entity.to_bits(), value)),
+        )
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, T: Deserialize<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<(Entity, T)>, D::Error> {
+        let values: Vec<(u64, T)> = Vec::deserialize(deserializer)?;
+        Ok(values
+            .into_iter()
+            .map(|(bits, value)| (Entity::from_bits(bits), value))
+            .collect())
+    }
+}
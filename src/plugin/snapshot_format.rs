@@ -0,0 +1,194 @@
+use crate::plugin::PhysicsSnapshot;
+use bevy::utils::HashMap;
+use std::fmt;
+
+/// The bytes starting the snapshots written with [`PhysicsSnapshot::to_bytes`].
+pub const SNAPSHOT_MAGIC: [u8; 4] = *b"BRPS";
+
+/// The version of the format of the snapshots written with [`PhysicsSnapshot::to_bytes`].
+///
+/// It is increased each time the serialized snapshot changes, e.g. when Rapier is upgraded, and
+/// a migration from the previous version is added to the default [`SnapshotMigrations`] when
+/// possible.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+#[cfg(feature = "dim2")]
+const DIMENSION: u8 = 2;
+#[cfg(feature = "dim3")]
+const DIMENSION: u8 = 3;
+
+/// The header written before the serialized snapshot by [`PhysicsSnapshot::to_bytes`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotHeader {
+    /// The version of the format of the snapshot.
+    pub format_version: u32,
+    /// The version of `bevy_rapier` which wrote the snapshot.
+    pub crate_version: String,
+    /// The dimension of the physics world, `2` or `3`.
+    pub dimension: u8,
+}
+
+impl SnapshotHeader {
+    /// The header of the snapshots written by this version of `bevy_rapier`.
+    pub fn current() -> Self {
+        Self {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            dimension: DIMENSION,
+        }
+    }
+}
+
+/// A function converting the serialized snapshot of a version of the format into the one of the
+/// next version.
+pub type SnapshotMigration = fn(Vec<u8>) -> Result<Vec<u8>, SnapshotError>;
+
+/// The migrations applied by [`PhysicsSnapshot::from_bytes_with_migrations`] to load the
+/// snapshots written with older versions of the format.
+///
+/// The default migrations are the ones shipped with `bevy_rapier`. Custom ones can be added,
+/// e.g. to load the snapshots of a fork.
+#[derive(Clone, Debug, Default)]
+pub struct SnapshotMigrations {
+    migrations: HashMap<u32, SnapshotMigration>,
+}
+
+impl SnapshotMigrations {
+    /// Sets the migration converting the snapshots of the format `from_version` into the format
+    /// `from_version + 1`.
+    pub fn with_migration(mut self, from_version: u32, migration: SnapshotMigration) -> Self {
+        self.migrations.insert(from_version, migration);
+        self
+    }
+
+    /// Converts the serialized snapshot of the format `version` to the current format.
+    pub fn migrate(&self, mut version: u32, mut bytes: Vec<u8>) -> Result<Vec<u8>, SnapshotError> {
+        while version < SNAPSHOT_FORMAT_VERSION {
+            let migration = self
+                .migrations
+                .get(&version)
+                .ok_or(SnapshotError::MissingMigration { version })?;
+            bytes = migration(bytes)?;
+            version += 1;
+        }
+        Ok(bytes)
+    }
+}
+
+/// The errors of [`PhysicsSnapshot::from_bytes`].
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The bytes don’t start with the [`SNAPSHOT_MAGIC`]: they are not a snapshot written with
+    /// [`PhysicsSnapshot::to_bytes`].
+    NotASnapshot,
+    /// The snapshot was taken in a world of another dimension.
+    WrongDimension {
+        /// The dimension of the world of the snapshot.
+        dimension: u8,
+    },
+    /// The snapshot was written with a newer version of the format.
+    UnsupportedVersion {
+        /// The version of the format of the snapshot.
+        version: u32,
+    },
+    /// No migration converts the snapshots of this version of the format to the next one.
+    MissingMigration {
+        /// The version of the format without migration.
+        version: u32,
+    },
+    /// The header or the snapshot could not be (de)serialized.
+    Serialization(bincode::Error),
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotASnapshot => write!(f, "not a physics snapshot"),
+            Self::WrongDimension { dimension } => write!(
+                f,
+                "the snapshot was taken in {}D, it can’t be loaded in {}D",
+                dimension, DIMENSION
+            ),
+            Self::UnsupportedVersion { version } => write!(
+                f,
+                "the snapshot format {} is newer than the supported format {}",
+                version, SNAPSHOT_FORMAT_VERSION
+            ),
+            Self::MissingMigration { version } => write!(
+                f,
+                "no migration from the snapshot format {} to the format {}",
+                version,
+                version + 1
+            ),
+            Self::Serialization(err) => write!(f, "invalid snapshot: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Serialization(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<bincode::Error> for SnapshotError {
+    fn from(err: bincode::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+impl PhysicsSnapshot {
+    /// Serializes this snapshot with `bincode`, after the [`SNAPSHOT_MAGIC`] and the
+    /// [`SnapshotHeader`] identifying its format.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut bytes = SNAPSHOT_MAGIC.to_vec();
+        bincode::serialize_into(&mut bytes, &SnapshotHeader::current())?;
+        bincode::serialize_into(&mut bytes, self)?;
+        Ok(bytes)
+    }
+
+    /// Reads the header of a snapshot written with [`Self::to_bytes`], e.g. to show the version
+    /// of a save, and returns it with the serialized snapshot which follows it.
+    pub fn read_header(bytes: &[u8]) -> Result<(SnapshotHeader, &[u8]), SnapshotError> {
+        let mut bytes = bytes
+            .strip_prefix(&SNAPSHOT_MAGIC[..])
+            .ok_or(SnapshotError::NotASnapshot)?;
+        let header = bincode::deserialize_from(&mut bytes)?;
+        Ok((header, bytes))
+    }
+
+    /// Deserializes a snapshot written with [`Self::to_bytes`], converting the snapshots of the
+    /// older versions of the format with the default [`SnapshotMigrations`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SnapshotError> {
+        Self::from_bytes_with_migrations(bytes, &SnapshotMigrations::default())
+    }
+
+    /// Deserializes a snapshot written with [`Self::to_bytes`], converting the snapshots of the
+    /// older versions of the format with the given migrations.
+    pub fn from_bytes_with_migrations(
+        bytes: &[u8],
+        migrations: &SnapshotMigrations,
+    ) -> Result<Self, SnapshotError> {
+        let (header, payload) = Self::read_header(bytes)?;
+        if header.dimension != DIMENSION {
+            return Err(SnapshotError::WrongDimension {
+                dimension: header.dimension,
+            });
+        }
+        if header.format_version > SNAPSHOT_FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion {
+                version: header.format_version,
+            });
+        }
+
+        if header.format_version == SNAPSHOT_FORMAT_VERSION {
+            Ok(bincode::deserialize(payload)?)
+        } else {
+            let payload = migrations.migrate(header.format_version, payload.to_vec())?;
+            Ok(bincode::deserialize(&payload)?)
+        }
+    }
+}
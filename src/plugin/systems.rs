@@ -1,34 +1,74 @@
 //! Systems responsible for interfacing our Bevy components with the Rapier physics engine.
 
 use crate::dynamics::{
-    AdditionalMassProperties, Ccd, Damping, Dominance, ExternalForce, ExternalImpulse,
-    GravityScale, ImpulseJoint, LockedAxes, MassProperties, MultibodyJoint,
-    RapierImpulseJointHandle, RapierMultibodyJointHandle, RapierRigidBodyHandle,
-    ReadMassProperties, RigidBody, Sleeping, TransformInterpolation, Velocity,
+    AdditionalMassProperties, BreakableJoint, Carrying, Ccd, Damping, DespawnWhenSleeping,
+    Dominance, EntityPathOrName, ExternalForce, ExternalImpulse, GravityScale, ImpulseJoint,
+    JointBreakAction, JointCoupling, JointEnabled, JointLimitEventThreshold, JointParent,
+    JointSoftness, JointSpring, KinematicVelocityEstimation, LockedAxes, MassProperties,
+    MultibodyJoint, PendingJoint, PulleyJoint, RadialGravity, RampedJointMotor,
+    RapierImpulseJointHandle, RapierMultibodyJointHandle, RapierRigidBodyHandle, ReadJointForces,
+    ReadMassProperties, ReplicatedBody, RigidBody, SleepTimeoutAction, Sleeping, StandingOn,
+    TrackStanding, TransformInterpolation, Velocity, Weld, WeldedBody,
 };
 use crate::geometry::{
-    ActiveCollisionTypes, ActiveEvents, ActiveHooks, Collider, ColliderMassProperties,
-    ColliderScale, CollisionGroups, ContactForceEventThreshold, Friction, RapierColliderHandle,
-    Restitution, Sensor, SolverGroups,
+    ActiveCollisionTypes, ActiveEvents, ActiveHooks, AppliedMaterial, AsyncColliderFromAsset,
+    Collider, ColliderAsset, ColliderMassProperties, ColliderScale, ColliderView,
+    CollisionEventFilter, CollisionGroups, ContactForceEventThreshold, FluidVolume, Friction,
+    ImpactEvents, ImpactMaterial, OneWayCollider, OneWayPlatform, PhysicsMaterials,
+    RapierColliderHandle, Restitution, RollingFriction, Sensor, SensorIntersections, SolverGroups,
+    StaticWorld, TerrainFocus, TerrainStreamer, TrackIntersections, TriggerZone,
 };
+use crate::math::{Real, Vect};
 use crate::pipeline::{
-    CollisionEvent, ContactForceEvent, PhysicsHooksWithQueryInstance, PhysicsHooksWithQueryResource,
+    AsyncQuery, AsyncQueryHit, AsyncQueryId, AsyncQueryKind, AsyncQueryQueue, AsyncQueryResult,
+    BodyRemoved, CcdImpact, CharacterCollisionEvent, CharacterSurface, ColliderRemoved,
+    CollisionEvent, CollisionEventDetails, ContactForceEvent, ContactReport, GroundedChanged,
+    ImpactEvent, InvalidStateCause, InvalidStateField, IslandEvent, IslandTracker, JointBroken,
+    JointLimitReached, JointLimitSide, JointRemoved, OutOfBounds, PhysicsHooksWithQueryInstance,
+    PhysicsHooksWithQueryResource, PhysicsInvalidState, RayCastMode, RayCaster, RayHits,
+    SensorEvent, ShapeCastMode, ShapeCaster, ShapeHits, SleepTimeoutEvent, SteepSlopeChanged,
+    TriggerEvent,
+};
+use crate::plugin::configuration::{
+    InvalidStateDetection, OutOfBoundsAction, PhysicsWorldBounds, QueryPipelineUpdate,
+    SimulationToRenderTime, TimestepMode,
+};
+#[cfg(feature = "debug-stream")]
+use crate::plugin::DebugStreamRecorder;
+#[cfg(feature = "serde-serialize")]
+use crate::plugin::{ActivePhysicsConfig, PhysicsConfigAsset};
+use crate::plugin::{
+    LockstepChecksums, PhysicsPrefab, PhysicsPrefabInstance, PhysicsRecorder, RapierConfiguration,
+    RapierContext,
 };
-use crate::plugin::configuration::{SimulationToRenderTime, TimestepMode};
-use crate::plugin::{RapierConfiguration, RapierContext};
 use crate::prelude::{
-    CollidingEntities, KinematicCharacterController, KinematicCharacterControllerOutput,
+    CollidingEntities, DynamicCharacterController, DynamicCharacterControllerOutput,
+    KinematicCharacterController, KinematicCharacterControllerOutput,
 };
 use crate::utils;
+use bevy::ecs::entity::Entities;
 use bevy::ecs::query::WorldQuery;
 use bevy::prelude::*;
+use bevy::scene::SceneSpawner;
+use bevy::tasks::{ComputeTaskPool, ParallelSlice, ParallelSliceMut};
 use rapier::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
 
 #[cfg(feature = "dim3")]
-use crate::prelude::{AsyncCollider, AsyncSceneCollider};
+use crate::dynamics::{
+    Ragdoll, RagdollBone, RagdollBoneConfig, RagdollMode, Rope, RopeAttachment, RopeMesh,
+    RopeSegment, RopeSegments,
+};
+#[cfg(feature = "dim3")]
+use crate::prelude::{AsyncCollider, AsyncSceneCollider, SpinFriction};
+#[cfg(feature = "dim3")]
+use bevy::render::mesh::skinning::SkinnedMesh;
 
-use crate::control::CharacterCollision;
+#[cfg(feature = "ggrs")]
+use crate::plugin::PhysicsRollbackState;
+
+use crate::control::{CharacterInterpolation, CharacterLength};
 use crate::utils::transform_to_iso;
 #[cfg(feature = "dim2")]
 use bevy::math::Vec3Swizzles;
@@ -108,7 +148,9 @@ pub fn apply_scale(
             None => transform.compute_transform().scale,
         };
 
-        if shape.scale != effective_scale {
+        // NOTE: the collider is only modified if its scale changes, so that moving an entity
+        //       doesn’t trigger bevy’s change tracking of its collider.
+        if shape.scale != Collider::snap_scale(effective_scale) {
             shape.set_scale(effective_scale, config.scaled_shape_subdivision);
         }
     }
@@ -171,6 +213,17 @@ pub fn apply_collider_user_changes(
         if let Some(co) = context.colliders.get_mut(handle.0) {
             co.set_active_events((*active_events).into())
         }
+
+        if active_events.contains(ActiveEvents::COLLISION_EVENT_CONTACTS) {
+            context.collision_event_contacts.insert(handle.0);
+        } else {
+            context.collision_event_contacts.remove(&handle.0);
+        }
+        if active_events.contains(ActiveEvents::CCD_IMPACTS) {
+            context.ccd_impact_colliders.insert(handle.0);
+        } else {
+            context.ccd_impact_colliders.remove(&handle.0);
+        }
     }
 
     for (handle, active_hooks) in changed_active_hooks.iter() {
@@ -314,47 +367,54 @@ pub fn apply_rigid_body_user_changes(
             }
         };
 
-    for (handle, global_transform, mut interpolation) in changed_transforms.iter_mut() {
-        if let Some(interpolation) = interpolation.as_deref_mut() {
-            // Reset the interpolation so we don’t overwrite
-            // the user’s input.
-            interpolation.start = None;
-            interpolation.end = None;
-        }
+    // The new positions of the moved rigid-bodies are computed by batches in parallel, then set
+    // in the order of their handles for the simulation to stay deterministic.
+    let mut changed_transforms: Vec<_> = changed_transforms.iter_mut().collect();
+    let mut moved_bodies: Vec<_> = {
+        let context = &*context;
+        changed_transforms
+            .par_chunk_map_mut(ComputeTaskPool::get(), PARALLEL_BATCH_SIZE, |bodies| {
+                let mut moved_bodies = Vec::new();
+                for (handle, global_transform, interpolation) in bodies {
+                    if let Some(interpolation) = interpolation.as_deref_mut() {
+                        // Reset the interpolation so we don’t overwrite
+                        // the user’s input.
+                        interpolation.start = None;
+                        interpolation.end = None;
+                    }
 
-        if let Some(rb) = context.bodies.get_mut(handle.0) {
+                    if context.bodies.contains(handle.0)
+                        && transform_changed(
+                            &handle.0,
+                            global_transform,
+                            &context.last_body_transform_set,
+                        )
+                    {
+                        let position =
+                            utils::transform_to_iso(&global_transform.compute_transform(), scale);
+                        moved_bodies.push((handle.0, **global_transform, position));
+                    }
+                }
+                moved_bodies
+            })
+            .into_iter()
+            .flatten()
+            .collect()
+    };
+    moved_bodies.sort_by_key(|(handle, ..)| handle.into_raw_parts());
+    for (handle, global_transform, position) in moved_bodies {
+        if let Some(rb) = context.bodies.get_mut(handle) {
             match rb.body_type() {
                 RigidBodyType::KinematicPositionBased => {
-                    if transform_changed(
-                        &handle.0,
-                        global_transform,
-                        &context.last_body_transform_set,
-                    ) {
-                        rb.set_next_kinematic_position(utils::transform_to_iso(
-                            &global_transform.compute_transform(),
-                            scale,
-                        ));
-                        context
-                            .last_body_transform_set
-                            .insert(handle.0, *global_transform);
-                    }
+                    rb.set_next_kinematic_position(position);
                 }
                 _ => {
-                    if transform_changed(
-                        &handle.0,
-                        global_transform,
-                        &context.last_body_transform_set,
-                    ) {
-                        rb.set_position(
-                            utils::transform_to_iso(&global_transform.compute_transform(), scale),
-                            true,
-                        );
-                        context
-                            .last_body_transform_set
-                            .insert(handle.0, *global_transform);
-                    }
+                    rb.set_position(position, true);
                 }
             }
+            context
+                .last_body_transform_set
+                .insert(handle, global_transform);
         }
     }
 
@@ -392,6 +452,8 @@ pub fn apply_rigid_body_user_changes(
             rb.add_force((forces.force / scale).into(), true);
             #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
             rb.add_torque(forces.torque.into(), true);
+            // The reset also removed the force applied by `RadialGravity`.
+            context.radial_gravity_forces.remove(&handle.0);
         }
     }
 
@@ -430,13 +492,193 @@ pub fn apply_rigid_body_user_changes(
     }
 }
 
+/// System responsible for smoothing the velocity of kinematic position-based rigid-bodies
+/// with a [`KinematicVelocityEstimation`].
+///
+/// Rapier deduces the velocity of these rigid-bodies from the displacement between their
+/// current and next positions. Before each simulation step, their next position is set along
+/// the displacement averaged over the last steps, and they are moved to the target set by the
+/// user after the step. Their contacts and `Velocity` thus use the smoothed velocity, while they
+/// still reach each of their targets.
+pub fn estimate_kinematic_velocities(
+    mut context: ResMut<RapierContext>,
+    estimations: Query<(&RapierRigidBodyHandle, &KinematicVelocityEstimation)>,
+) {
+    let context = &mut *context;
+
+    context.kinematic_velocity_estimations.clear();
+    context.kinematic_velocity_estimations.extend(
+        estimations
+            .iter()
+            .map(|(handle, estimation)| (handle.0, estimation.window)),
+    );
+
+    let estimations = &context.kinematic_velocity_estimations;
+    context
+        .kinematic_position_histories
+        .retain(|handle, _| estimations.contains_key(handle));
+}
+
+/// System responsible for moving the rigid-bodies with a [`ReplicatedBody`] toward the states
+/// they received.
+pub fn apply_replicated_bodies(
+    time: Res<Time>,
+    mut bodies: Query<(
+        &mut ReplicatedBody,
+        &mut Transform,
+        Option<&mut Velocity>,
+        Option<&mut Sleeping>,
+    )>,
+) {
+    for (mut replicated, mut transform, velocity, sleeping) in bodies.iter_mut() {
+        let mut target = match replicated.target {
+            Some(target) => target,
+            None => continue,
+        };
+
+        if target.received {
+            if let Some(mut velocity) = velocity {
+                *velocity = target.state.velocity;
+            }
+            if let Some(mut sleeping) = sleeping {
+                if sleeping.sleeping != target.state.sleeping {
+                    sleeping.sleeping = target.state.sleeping;
+                }
+            }
+            target.received = false;
+        } else {
+            target.elapsed += time.delta_seconds();
+        }
+
+        // The received position, extrapolated to the current time.
+        let translation = target.state.translation + target.state.velocity.linvel * target.elapsed;
+        #[cfg(feature = "dim2")]
+        let (error, rotation) = (
+            translation - transform.translation.truncate(),
+            Quat::from_rotation_z(target.state.rotation),
+        );
+        #[cfg(feature = "dim3")]
+        let (error, rotation) = (translation - transform.translation, target.state.rotation);
+
+        if replicated.correction >= 1.0 || error.length() <= replicated.snap_distance {
+            #[cfg(feature = "dim2")]
+            {
+                transform.translation.x = translation.x;
+                transform.translation.y = translation.y;
+            }
+            #[cfg(feature = "dim3")]
+            {
+                transform.translation = translation;
+            }
+            transform.rotation = rotation;
+            replicated.target = None;
+        } else {
+            let correction = replicated.correction.max(0.0);
+            #[cfg(feature = "dim2")]
+            {
+                transform.translation += error.extend(0.0) * correction;
+            }
+            #[cfg(feature = "dim3")]
+            {
+                transform.translation += error * correction;
+            }
+            transform.rotation = transform.rotation.slerp(rotation, correction);
+            replicated.target = Some(target);
+        }
+    }
+}
+
+/// The velocity of a rigid-body, as written back to its [`Velocity`] component.
+pub(crate) fn body_velocity(rb: &rapier::dynamics::RigidBody, scale: Real) -> Velocity {
+    Velocity {
+        linvel: (rb.linvel() * scale).into(),
+        #[cfg(feature = "dim3")]
+        angvel: (*rb.angvel()).into(),
+        #[cfg(feature = "dim2")]
+        angvel: rb.angvel(),
+    }
+}
+
+/// Converts the description of an impulse joint into a Rapier joint, freeing all its
+/// axes if it is disabled by its [`JointEnabled`] component.
+fn impulse_joint_data(
+    joint: &ImpulseJoint,
+    enabled: Option<&JointEnabled>,
+    scale: Real,
+) -> rapier::dynamics::GenericJoint {
+    let mut data = joint.data.into_rapier(scale);
+    if enabled.map(|enabled| !enabled.0).unwrap_or(false) {
+        data.locked_axes = JointAxesMask::empty();
+        data.limit_axes = JointAxesMask::empty();
+        data.motor_axes = JointAxesMask::empty();
+    }
+    data
+}
+
+/// Converts the description of a multibody joint into a Rapier joint, emulating its dry
+/// friction with velocity motors on its free axes without a motor.
+fn multibody_joint_data(joint: &MultibodyJoint, scale: Real) -> rapier::dynamics::GenericJoint {
+    let mut data = joint.data.into_rapier(scale);
+    if joint.friction > 0.0 {
+        for i in 0..SPATIAL_DIM {
+            let axis = JointAxesMask::from_bits_truncate(1 << i);
+            if !data.locked_axes.contains(axis) && !data.motor_axes.contains(axis) {
+                let unit_scale = if i < DIM { scale } else { 1.0 };
+                data.motor_axes.insert(axis);
+                data.motors[i] = JointMotor {
+                    damping: Real::MAX,
+                    max_force: joint.friction / unit_scale,
+                    ..JointMotor::default()
+                };
+            }
+        }
+    }
+    data
+}
+
+/// Sets the damping of the free axes of a multibody joint in its multibody.
+fn set_multibody_joint_damping(
+    multibody_joints: &mut MultibodyJointSet,
+    handle: MultibodyJointHandle,
+    joint: &MultibodyJoint,
+) {
+    if let Some((multibody, link_id)) = multibody_joints.get_mut(handle) {
+        // NOTE: the free axes of the links are laid out one after the other, in the same order
+        //       as the links.
+        let mut dof: usize = multibody
+            .links()
+            .take(link_id)
+            .map(|link| link.joint().ndofs())
+            .sum();
+        let locked_axes = match multibody.link(link_id) {
+            Some(link) => link.joint().data.locked_axes,
+            None => return,
+        };
+
+        for i in 0..SPATIAL_DIM {
+            if !locked_axes.contains(JointAxesMask::from_bits_truncate(1 << i)) {
+                // Rapier’s default damping only applies to the angular axes.
+                let default_damping = if i < DIM { 0.0 } else { 0.1 };
+                multibody.damping_mut()[dof] = joint.damping.unwrap_or(default_damping);
+                dof += 1;
+            }
+        }
+    }
+}
+
 /// System responsible for applying changes the user made to a joint component.
 pub fn apply_joint_user_changes(
     mut context: ResMut<RapierContext>,
     changed_impulse_joints: Query<
-        (&RapierImpulseJointHandle, &ImpulseJoint),
-        Changed<ImpulseJoint>,
+        (
+            &RapierImpulseJointHandle,
+            &ImpulseJoint,
+            Option<&JointEnabled>,
+        ),
+        Or<(Changed<ImpulseJoint>, Changed<JointEnabled>)>,
     >,
+    impulse_joints: Query<(&RapierImpulseJointHandle, &ImpulseJoint)>,
+    removed_joint_enabled: RemovedComponents<JointEnabled>,
     changed_multibody_joints: Query<
         (&RapierMultibodyJointHandle, &MultibodyJoint),
         Changed<MultibodyJoint>,
@@ -446,9 +688,26 @@ pub fn apply_joint_user_changes(
 
     // TODO: right now, we only support propagating changes made to the joint data.
     //       Re-parenting the joint isn’t supported yet.
-    for (handle, changed_joint) in changed_impulse_joints.iter() {
+    let context = &mut *context;
+    let mut to_wake_up = vec![];
+
+    let re_enabled_impulse_joints = removed_joint_enabled
+        .iter()
+        .filter_map(|entity| impulse_joints.get(entity).ok())
+        .map(|(handle, joint)| (handle, joint, None));
+
+    for (handle, changed_joint, enabled) in changed_impulse_joints
+        .iter()
+        .chain(re_enabled_impulse_joints)
+    {
         if let Some(joint) = context.impulse_joints.get_mut(handle.0) {
-            joint.data = changed_joint.data.into_rapier(scale);
+            // NOTE: the joint is modified in-place, keeping its impulses for warm-starting.
+            joint.data = impulse_joint_data(changed_joint, enabled, scale);
+            if enabled.map(|enabled| !enabled.0).unwrap_or(false) {
+                joint.impulses.fill(0.0);
+            }
+            // Wake up the rigid-bodies so that changes like new motor targets take effect.
+            to_wake_up.extend([joint.body1, joint.body2]);
         }
     }
 
@@ -456,14 +715,193 @@ pub fn apply_joint_user_changes(
         // TODO: not sure this will always work properly, e.g., if the number of Dofs is changed.
         if let Some((mb, link_id)) = context.multibody_joints.get_mut(handle.0) {
             if let Some(link) = mb.link_mut(link_id) {
-                link.joint.data = changed_joint.data.into_rapier(scale);
+                link.joint.data = multibody_joint_data(changed_joint, scale);
+                to_wake_up.push(link.rigid_body_handle());
+            }
+        }
+        set_multibody_joint_damping(&mut context.multibody_joints, handle.0, changed_joint);
+    }
+
+    for handle in to_wake_up {
+        context.islands.wake_up(&mut context.bodies, handle, true);
+    }
+}
+
+/// System responsible for applying the [`JointSpring`] parameters to the spring joints.
+pub fn apply_joint_springs(
+    mut joints: Query<(&JointSpring, &mut ImpulseJoint), Changed<JointSpring>>,
+) {
+    for (params, mut joint) in joints.iter_mut() {
+        if let Some(spring) = joint.data.as_spring_mut() {
+            spring
+                .set_rest_length(params.rest_length)
+                .set_stiffness(params.stiffness)
+                .set_damping(params.damping);
+        }
+    }
+}
+
+/// System responsible for applying the [`JointSoftness`] components to the impulse joints.
+pub fn apply_joint_softness(
+    changed_softness: Query<(Entity, &JointSoftness), Changed<JointSoftness>>,
+    removed_softness: RemovedComponents<JointSoftness>,
+    mut joints: Query<&mut ImpulseJoint>,
+) {
+    let changed = changed_softness
+        .iter()
+        .map(|(entity, softness)| (entity, Some(*softness)));
+    let removed = removed_softness.iter().map(|entity| (entity, None));
+
+    for (entity, softness) in changed.chain(removed) {
+        if let Ok(mut joint) = joints.get_mut(entity) {
+            // NOTE: we write the new value only if there was an
+            //       actual change, in order to not trigger bevy’s
+            //       change tracking when the values didn’t change.
+            if joint.data.softness() != softness {
+                joint.data.set_softness(softness);
+            }
+        }
+    }
+}
+
+/// System responsible for updating the Rapier joints of the [`RopeJoint`](crate::dynamics::RopeJoint)s
+/// and [`SpringJoint`](crate::dynamics::SpringJoint)s.
+///
+/// These joints are simulated by a joint limiting or motorizing the translation along its `X`
+/// axis. This axis is aimed at the second anchor before each simulation step (substeps
+/// included), so that the limit or motor applies to the distance between both anchors.
+pub fn update_distance_joints(
+    mut context: ResMut<RapierContext>,
+    joints: Query<(Entity, &ImpulseJoint, Option<&JointEnabled>)>,
+) {
+    let context = &mut *context;
+    let scale = context.physics_scale;
+    context.distance_joints.clear();
+
+    for (entity, joint, enabled) in joints.iter() {
+        if enabled.map(|enabled| !enabled.0).unwrap_or(false) {
+            continue;
+        }
+
+        if joint.data.as_rope().is_none() && joint.data.as_spring().is_none() {
+            continue;
+        }
+
+        // NOTE: use `entity2impulse_joint` rather than the `RapierImpulseJointHandle` so that
+        //       the joints created by `init_joints` are aimed before their first step.
+        if let Some(handle) = context.entity2impulse_joint.get(&entity) {
+            context
+                .distance_joints
+                .insert(*handle, joint.data.into_rapier(scale));
+        }
+    }
+}
+
+/// System responsible for driving the joint axes of the [`RampedJointMotor`]s along their
+/// velocity profile.
+///
+/// The axis is driven by a velocity motor, whose target velocity is updated before each step.
+pub fn update_ramped_joint_motors(
+    mut context: ResMut<RapierContext>,
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    motors: Query<(Entity, &RampedJointMotor, Option<&JointEnabled>), With<ImpulseJoint>>,
+    removed_motors: RemovedComponents<RampedJointMotor>,
+    joints: Query<(&ImpulseJoint, Option<&JointEnabled>)>,
+    mut velocities: Local<HashMap<Entity, Real>>,
+) {
+    let context = &mut *context;
+    let scale = context.physics_scale;
+    let dt = simulation_step_dt(config.timestep_mode, &time);
+
+    for entity in removed_motors.iter() {
+        velocities.remove(&entity);
+
+        if let (Ok((joint, enabled)), Some(handle)) = (
+            joints.get(entity),
+            context.entity2impulse_joint.get(&entity),
+        ) {
+            if let Some(raw_joint) = context.impulse_joints.get_mut(*handle) {
+                raw_joint.data = impulse_joint_data(joint, enabled, scale);
             }
         }
     }
+
+    velocities.retain(|entity, _| motors.contains(*entity));
+
+    for (entity, motor, enabled) in motors.iter() {
+        if enabled.map(|enabled| !enabled.0).unwrap_or(false) {
+            continue;
+        }
+
+        // NOTE: use `entity2impulse_joint` rather than the `RapierImpulseJointHandle` so that
+        //       the joints created by `init_joints` are driven from their first step.
+        let handle = match context.entity2impulse_joint.get(&entity) {
+            Some(handle) => *handle,
+            None => continue,
+        };
+        let i = motor.axis as usize;
+        let (position, velocity) = match (
+            impulse_joint_axis_positions(context, handle),
+            impulse_joint_axis_velocities(context, handle),
+        ) {
+            (Some(positions), Some(velocities)) => (positions[i], velocities[i]),
+            _ => continue,
+        };
+
+        // Move toward the target as fast as possible while still being able to stop
+        // on it, without exceeding the maximum acceleration. The velocity is applied for
+        // a whole step before the deceleration starts, hence the `max_change` terms.
+        let remaining = motor.target_pos - position;
+        let previous_velocity = *velocities.entry(entity).or_insert(velocity);
+        let max_change = motor.max_acceleration * dt;
+        let stopping_velocity =
+            ((max_change * max_change + 2.0 * motor.max_acceleration * remaining.abs()).sqrt()
+                - max_change)
+                .min(motor.max_velocity)
+                .copysign(remaining);
+        let target_vel = stopping_velocity.clamp(
+            previous_velocity - max_change,
+            previous_velocity + max_change,
+        );
+        velocities.insert(entity, target_vel);
+
+        let raw_joint = match context.impulse_joints.get_mut(handle) {
+            Some(raw_joint) => raw_joint,
+            None => continue,
+        };
+        let unit_scale = if i < DIM { scale } else { 1.0 };
+        let axis_mask = JointAxesMask::from_bits_truncate(1 << i);
+        raw_joint.data.motor_axes.insert(axis_mask);
+        raw_joint.data.motors[i] = JointMotor {
+            target_vel: target_vel / unit_scale,
+            target_pos: 0.0,
+            stiffness: 0.0,
+            damping: motor.damping,
+            max_force: motor.max_force / unit_scale,
+            model: MotorModel::AccelerationBased,
+            ..raw_joint.data.motors[i]
+        };
+
+        // Rapier doesn’t account for the motor when putting the bodies to sleep,
+        // so keep them awake until the motor stops on its target.
+        if target_vel != 0.0
+            || remaining.abs() / unit_scale > context.integration_parameters.allowed_linear_error
+        {
+            let (body1, body2) = (raw_joint.body1, raw_joint.body2);
+            context.islands.wake_up(&mut context.bodies, body1, true);
+            context.islands.wake_up(&mut context.bodies, body2, true);
+        }
+    }
 }
 
+/// The number of entities processed by each task of the parallel systems.
+const PARALLEL_BATCH_SIZE: usize = 1024;
+
 /// System responsible for writing the result of the last simulation step into our `bevy_rapier`
 /// components and the [`GlobalTransform`] component.
+///
+/// The entities are written back in parallel, by batches of rigid-bodies.
 pub fn writeback_rigid_bodies(
     mut context: ResMut<RapierContext>,
     config: Res<RapierConfiguration>,
@@ -471,1200 +909,12120 @@ pub fn writeback_rigid_bodies(
     global_transforms: Query<&GlobalTransform>,
     mut writeback: Query<RigidBodyWritebackComponents>,
 ) {
-    let context = &mut *context;
+    if !config.physics_pipeline_active {
+        return;
+    }
+
+    // The rigid-bodies are written back by batches in parallel. Each batch returns the global
+    // transforms it set, added to the `last_body_transform_set` once all the batches are done.
     let scale = context.physics_scale;
+    let mut bodies: Vec<_> = writeback.iter_mut().collect();
+    let transforms_set = {
+        let context = &*context;
+        bodies.par_chunk_map_mut(ComputeTaskPool::get(), PARALLEL_BATCH_SIZE, |bodies| {
+            let mut transforms_set = Vec::new();
+            for (entity, parent, transform, interpolation, velocity, sleeping) in bodies {
+                // TODO: do this the other way round: iterate through Rapier’s RigidBodySet on the active bodies,
+                // and update the components accordingly. That way, we don’t have to iterate through the entities that weren’t changed
+                // by physics (for example because they are sleeping).
+                let handle = match context.entity2body.get(entity) {
+                    Some(handle) => *handle,
+                    None => continue,
+                };
+                let rb = match context.bodies.get(handle) {
+                    Some(rb) => rb,
+                    None => continue,
+                };
+                let mut interpolated_pos = utils::iso_to_transform(rb.position(), scale);
 
-    if config.physics_pipeline_active {
-        for (entity, parent, transform, mut interpolation, mut velocity, mut sleeping) in
-            writeback.iter_mut()
-        {
-            // TODO: do this the other way round: iterate through Rapier’s RigidBodySet on the active bodies,
-            // and update the components accordingly. That way, we don’t have to iterate through the entities that weren’t changed
-            // by physics (for example because they are sleeping).
-            if let Some(handle) = context.entity2body.get(&entity).copied() {
-                if let Some(rb) = context.bodies.get(handle) {
-                    let mut interpolated_pos = utils::iso_to_transform(rb.position(), scale);
-
-                    if let TimestepMode::Interpolated { dt, .. } = config.timestep_mode {
-                        if let Some(interpolation) = interpolation.as_deref_mut() {
-                            if interpolation.end.is_none() {
-                                interpolation.end = Some(*rb.position());
-                            }
+                if let TimestepMode::Interpolated { dt, .. } = config.timestep_mode {
+                    if let Some(interpolation) = interpolation.as_deref_mut() {
+                        if interpolation.end.is_none() {
+                            interpolation.end = Some(*rb.position());
+                        }
 
-                            if let Some(interpolated) =
-                                interpolation.lerp_slerp((dt + sim_to_render_time.diff) / dt)
-                            {
-                                interpolated_pos = utils::iso_to_transform(&interpolated, scale);
-                            }
+                        if let Some(interpolated) =
+                            interpolation.lerp_slerp((dt + sim_to_render_time.diff) / dt)
+                        {
+                            interpolated_pos = utils::iso_to_transform(&interpolated, scale);
                         }
                     }
+                }
 
-                    if let Some(mut transform) = transform {
-                        // NOTE: we query the parent’s global transform here, which is a bit
-                        //       unfortunate (performance-wise). An alternative would be to
-                        //       deduce the parent’s global transform from the current entity’s
-                        //       global transform. However, this makes it nearly impossible
-                        //       (because of rounding errors) to predict the exact next value this
-                        //       entity’s global transform will get after the next transform
-                        //       propagation, which breaks our transform modification detection
-                        //       that we do to detect if the user’s transform has to be written
-                        //       into the rigid-body.
-                        if let Some(parent_global_transform) =
-                            parent.and_then(|p| global_transforms.get(**p).ok())
+                if let Some(transform) = transform {
+                    // NOTE: we query the parent’s global transform here, which is a bit
+                    //       unfortunate (performance-wise). An alternative would be to
+                    //       deduce the parent’s global transform from the current entity’s
+                    //       global transform. However, this makes it nearly impossible
+                    //       (because of rounding errors) to predict the exact next value this
+                    //       entity’s global transform will get after the next transform
+                    //       propagation, which breaks our transform modification detection
+                    //       that we do to detect if the user’s transform has to be written
+                    //       into the rigid-body. The rigid-bodies without parent, which are
+                    //       the most common, don’t need this lookup.
+                    if let Some(parent_global_transform) =
+                        (*parent).and_then(|p| global_transforms.get(**p).ok())
+                    {
+                        // We need to compute the new local transform such that:
+                        // curr_parent_global_transform * new_transform = interpolated_pos
+                        // new_transform = curr_parent_global_transform.inverse() * interpolated_pos
+                        let (_, inverse_parent_rotation, inverse_parent_translation) =
+                            parent_global_transform
+                                .affine()
+                                .inverse()
+                                .to_scale_rotation_translation();
+                        let new_rotation = inverse_parent_rotation * interpolated_pos.rotation;
+                        #[allow(unused_mut)]
+                        let mut new_translation = inverse_parent_rotation
+                            * interpolated_pos.translation
+                            + inverse_parent_translation;
+
+                        // In 2D, preserve the transform `z` component that may have been set by the user
+                        #[cfg(feature = "dim2")]
                         {
-                            // In 2D, preserve the transform `z` component that may have been set by the user
-                            #[cfg(feature = "dim2")]
-                            let prev_z = transform.translation.z;
-
-                            // We need to compute the new local transform such that:
-                            // curr_parent_global_transform * new_transform = interpolated_pos
-                            // new_transform = curr_parent_global_transform.inverse() * interpolated_pos
-                            let (_, inverse_parent_rotation, inverse_parent_translation) =
-                                parent_global_transform
-                                    .affine()
-                                    .inverse()
-                                    .to_scale_rotation_translation();
-                            transform.rotation =
-                                inverse_parent_rotation * interpolated_pos.rotation;
-                            transform.translation = inverse_parent_rotation
-                                * interpolated_pos.translation
-                                + inverse_parent_translation;
-
-                            #[cfg(feature = "dim2")]
-                            {
-                                transform.translation.z = prev_z;
-                            }
+                            new_translation.z = transform.translation.z;
+                        }
 
-                            // NOTE: we need to compute the result of the next transform propagation
-                            //       to make sure that our change detection for transforms is exact
-                            //       despite rounding errors.
-                            let new_global_transform =
-                                parent_global_transform.mul_transform(*transform);
-
-                            context
-                                .last_body_transform_set
-                                .insert(handle, new_global_transform);
-                        } else {
-                            // In 2D, preserve the transform `z` component that may have been set by the user
-                            #[cfg(feature = "dim2")]
-                            {
-                                interpolated_pos.translation.z = transform.translation.z;
-                            }
+                        // NOTE: we write the new value only if there was an
+                        //       actual change, in order to not trigger bevy’s
+                        //       change tracking when the values didn’t change
+                        //       (for example because the rigid-body is sleeping).
+                        if transform.rotation != new_rotation
+                            || transform.translation != new_translation
+                        {
+                            transform.rotation = new_rotation;
+                            transform.translation = new_translation;
+                        }
 
-                            transform.rotation = interpolated_pos.rotation;
-                            transform.translation = interpolated_pos.translation;
+                        // NOTE: we need to compute the result of the next transform propagation
+                        //       to make sure that our change detection for transforms is exact
+                        //       despite rounding errors.
+                        let new_global_transform =
+                            parent_global_transform.mul_transform(**transform);
 
-                            context
-                                .last_body_transform_set
-                                .insert(handle, GlobalTransform::from(interpolated_pos));
+                        transforms_set.push((handle, new_global_transform));
+                    } else {
+                        // In 2D, preserve the transform `z` component that may have been set by the user
+                        #[cfg(feature = "dim2")]
+                        {
+                            interpolated_pos.translation.z = transform.translation.z;
                         }
-                    }
-
-                    if let Some(velocity) = &mut velocity {
-                        let new_vel = Velocity {
-                            linvel: (rb.linvel() * scale).into(),
-                            #[cfg(feature = "dim3")]
-                            angvel: (*rb.angvel()).into(),
-                            #[cfg(feature = "dim2")]
-                            angvel: rb.angvel(),
-                        };
 
                         // NOTE: we write the new value only if there was an
                         //       actual change, in order to not trigger bevy’s
-                        //       change tracking when the values didn’t change.
-                        if **velocity != new_vel {
-                            **velocity = new_vel;
+                        //       change tracking when the values didn’t change
+                        //       (for example because the rigid-body is sleeping).
+                        if transform.rotation != interpolated_pos.rotation
+                            || transform.translation != interpolated_pos.translation
+                        {
+                            transform.rotation = interpolated_pos.rotation;
+                            transform.translation = interpolated_pos.translation;
                         }
+
+                        transforms_set.push((handle, GlobalTransform::from(interpolated_pos)));
+                    }
+                }
+
+                if let Some(velocity) = velocity {
+                    let new_vel = body_velocity(rb, scale);
+
+                    // NOTE: we write the new value only if there was an
+                    //       actual change, in order to not trigger bevy’s
+                    //       change tracking when the values didn’t change.
+                    if **velocity != new_vel {
+                        **velocity = new_vel;
                     }
+                }
 
-                    if let Some(sleeping) = &mut sleeping {
-                        // NOTE: we write the new value only if there was an
-                        //       actual change, in order to not trigger bevy’s
-                        //       change tracking when the values didn’t change.
-                        if sleeping.sleeping != rb.is_sleeping() {
-                            sleeping.sleeping = rb.is_sleeping();
-                        }
+                if let Some(sleeping) = sleeping {
+                    // NOTE: we write the new value only if there was an
+                    //       actual change, in order to not trigger bevy’s
+                    //       change tracking when the values didn’t change.
+                    if sleeping.sleeping != rb.is_sleeping() {
+                        sleeping.sleeping = rb.is_sleeping();
                     }
                 }
             }
-        }
-    }
+            transforms_set
+        })
+    };
+
+    context
+        .last_body_transform_set
+        .extend(transforms_set.into_iter().flatten());
 }
 
-/// System responsible for advancing the physics simulation, and updating the internal state
-/// for scene queries.
-pub fn step_simulation<PhysicsHooksData: 'static + WorldQuery + Send + Sync>(
-    mut context: ResMut<RapierContext>,
-    config: Res<RapierConfiguration>,
-    hooks: Res<PhysicsHooksWithQueryResource<PhysicsHooksData>>,
-    (time, mut sim_to_render_time): (Res<Time>, ResMut<SimulationToRenderTime>),
-    collision_events: EventWriter<CollisionEvent>,
-    contact_force_events: EventWriter<ContactForceEvent>,
-    hooks_data: Query<PhysicsHooksData>,
-    interpolation_query: Query<(&RapierRigidBodyHandle, &mut TransformInterpolation)>,
+/// System responsible for despawning the rigid-bodies with a [`DespawnWhenSleeping`]
+/// that have been sleeping for long enough.
+pub fn despawn_sleeping_bodies(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    time: Res<Time>,
+    mut sleep_timeout_events: EventWriter<SleepTimeoutEvent>,
+    mut despawn_when_sleeping: Query<(Entity, &RapierRigidBodyHandle, &mut DespawnWhenSleeping)>,
 ) {
-    let context = &mut *context;
+    for (entity, handle, mut despawn) in despawn_when_sleeping.iter_mut() {
+        let sleeping = context
+            .bodies
+            .get(handle.0)
+            .map(|rb| rb.is_sleeping())
+            .unwrap_or(false);
+
+        if !sleeping {
+            // NOTE: we write the new value only if there was an
+            //       actual change, in order to not trigger bevy’s
+            //       change tracking when the values didn’t change.
+            if despawn.asleep_for != Duration::ZERO {
+                despawn.asleep_for = Duration::ZERO;
+            }
+            continue;
+        }
 
-    if config.physics_pipeline_active {
-        let hooks_instance = PhysicsHooksWithQueryInstance {
-            user_data: hooks_data,
-            hooks: &*hooks.0,
-        };
+        let was_timed_out = despawn.asleep_for >= despawn.after;
+        despawn.asleep_for += time.delta();
 
-        context.step_simulation(
-            config.gravity,
-            config.timestep_mode,
-            Some((collision_events, contact_force_events)),
-            &hooks_instance,
-            &time,
-            &mut sim_to_render_time,
-            Some(interpolation_query),
-        );
-        context.deleted_colliders.clear();
-    } else {
-        context.propagate_modified_body_positions_to_colliders();
-    }
+        if !was_timed_out && despawn.asleep_for >= despawn.after {
+            sleep_timeout_events.send(SleepTimeoutEvent { entity });
 
-    if config.query_pipeline_active {
-        context.update_query_pipeline();
+            if despawn.action == SleepTimeoutAction::Despawn {
+                commands.entity(entity).despawn_recursive();
+            }
+        }
     }
 }
 
-/// NOTE: This currently does nothing in 2D.
-#[cfg(feature = "dim2")]
-pub fn init_async_colliders() {}
-
-/// System responsible for creating `Collider` components from `AsyncCollider` components if the
-/// corresponding mesh has become available.
-#[cfg(feature = "dim3")]
-pub fn init_async_colliders(
-    mut commands: Commands,
-    meshes: Res<Assets<Mesh>>,
-    async_colliders: Query<(Entity, &AsyncCollider)>,
+/// System responsible for sending the [`IslandEvent`]s, by comparing the simulation islands
+/// of the dynamic rigid-bodies with the ones found after the previous simulation step, if the
+/// [`IslandTracker`] resource exists.
+pub fn emit_island_events(
+    context: Res<RapierContext>,
+    mut island_events: EventWriter<IslandEvent>,
+    tracker: Option<ResMut<IslandTracker>>,
 ) {
-    for (entity, async_collider) in async_colliders.iter() {
-        if let Some(mesh) = meshes.get(&async_collider.handle) {
-            match Collider::from_bevy_mesh(mesh, &async_collider.shape) {
-                Some(collider) => {
-                    commands
-                        .entity(entity)
-                        .insert(collider)
-                        .remove::<AsyncCollider>();
-                }
-                None => error!("Unable to generate collider from mesh {:?}", mesh),
+    let mut tracker = match tracker {
+        Some(tracker) => tracker,
+        None => return,
+    };
+    let mut island_of = HashMap::new();
+    let mut assigned = HashSet::new();
+    let mut sleeping = HashSet::new();
+
+    for (handles, bodies) in simulation_islands(&context) {
+        let mut previous: Vec<u32> = bodies
+            .iter()
+            .filter_map(|entity| tracker.island_of.get(entity).copied())
+            .collect();
+        previous.sort_unstable();
+        previous.dedup();
+
+        // When an island is split, only its first part keeps its identifier.
+        let kept = previous.iter().copied().find(|id| !assigned.contains(id));
+        let id = match kept {
+            Some(id) => id,
+            None => {
+                tracker.next_id += 1;
+                tracker.next_id
+            }
+        };
+        assigned.insert(id);
+
+        let asleep = handles
+            .iter()
+            .all(|h| context.bodies.get(*h).map(|rb| rb.is_sleeping()) == Some(true));
+
+        if kept.is_none() {
+            island_events.send(IslandEvent::Created {
+                bodies: bodies.clone(),
+            });
+        } else if previous.len() > 1 {
+            island_events.send(IslandEvent::Merged {
+                bodies: bodies.clone(),
+            });
+        }
+
+        if asleep {
+            if !tracker.sleeping.contains(&id) {
+                island_events.send(IslandEvent::Sleeping {
+                    bodies: bodies.clone(),
+                });
             }
+            sleeping.insert(id);
         }
+
+        island_of.extend(bodies.into_iter().map(|entity| (entity, id)));
     }
+
+    tracker.island_of = island_of;
+    tracker.sleeping = sleeping;
 }
 
-/// System responsible for creating `Collider` components from `AsyncSceneCollider` components if the
-/// corresponding scene has become available.
-#[cfg(feature = "dim3")]
-pub fn init_async_scene_colliders(
-    mut commands: Commands,
-    meshes: Res<Assets<Mesh>>,
-    scenes: Res<Assets<Scene>>,
-    async_colliders: Query<(Entity, &AsyncSceneCollider)>,
-    children: Query<&Children>,
-    mesh_handles: Query<(&Name, &Handle<Mesh>)>,
-) {
-    for (entity, async_collider) in async_colliders.iter() {
-        if scenes.get(&async_collider.handle).is_some() {
-            traverse_descendants(entity, &children, &mut |child| {
-                if let Ok((name, handle)) = mesh_handles.get(child) {
-                    let shape = async_collider
-                        .named_shapes
-                        .get(name.as_str())
-                        .unwrap_or(&async_collider.shape);
-                    if let Some(shape) = shape {
-                        let mesh = meshes.get(handle).unwrap(); // NOTE: Mesh is already loaded
-                        match Collider::from_bevy_mesh(mesh, shape) {
-                            Some(collider) => {
-                                commands.entity(child).insert(collider);
-                            }
-                            None => error!(
-                                "Unable to generate collider from mesh {:?} with name {}",
-                                mesh, name
-                            ),
-                        }
+/// Groups the dynamic rigid-bodies connected by active contacts or joints, with their
+/// entities sorted.
+///
+/// Rapier 0.16 doesn’t expose the islands of its `IslandManager`, which only contains the
+/// awake rigid-bodies anyway, so they are found again with a disjoint-set forest.
+fn simulation_islands(context: &RapierContext) -> Vec<(Vec<RigidBodyHandle>, Vec<Entity>)> {
+    // The parent and the rank of each rigid-body in the forest.
+    type Parents = HashMap<RigidBodyHandle, (RigidBodyHandle, u32)>;
+
+    fn root(parents: &mut Parents, mut h: RigidBodyHandle) -> RigidBodyHandle {
+        loop {
+            let parent = parents[&h].0;
+            if parent == h {
+                return h;
+            }
+            // Path halving, without recursing on long chains of rigid-bodies.
+            let grandparent = parents[&parent].0;
+            parents.get_mut(&h).unwrap().0 = grandparent;
+            h = grandparent;
+        }
+    }
+
+    fn union(parents: &mut Parents, h1: Option<RigidBodyHandle>, h2: Option<RigidBodyHandle>) {
+        if let (Some(h1), Some(h2)) = (h1, h2) {
+            if parents.contains_key(&h1) && parents.contains_key(&h2) {
+                let (r1, r2) = (root(parents, h1), root(parents, h2));
+                if r1 == r2 {
+                    return;
+                }
+                let (rank1, rank2) = (parents[&r1].1, parents[&r2].1);
+                if rank1 < rank2 {
+                    parents.get_mut(&r1).unwrap().0 = r2;
+                } else {
+                    parents.get_mut(&r2).unwrap().0 = r1;
+                    if rank1 == rank2 {
+                        parents.get_mut(&r1).unwrap().1 += 1;
                     }
                 }
-            });
+            }
+        }
+    }
 
-            commands.entity(entity).remove::<AsyncSceneCollider>();
+    let mut parents: Parents = context
+        .bodies
+        .iter()
+        .filter(|(_, rb)| rb.is_dynamic())
+        .map(|(h, _)| (h, (h, 0)))
+        .collect();
+
+    for pair in context.narrow_phase.contact_pairs() {
+        if pair.has_any_active_contact {
+            let parent = |h| context.colliders.get(h).and_then(|co| co.parent());
+            union(&mut parents, parent(pair.collider1), parent(pair.collider2));
+        }
+    }
+
+    for (_, joint) in context.impulse_joints.iter() {
+        union(&mut parents, Some(joint.body1), Some(joint.body2));
+    }
+
+    let mut multibody_roots = HashMap::new();
+    for h in parents.keys().copied().collect::<Vec<_>>() {
+        if let Some(link) = context.multibody_joints.rigid_body_link(h) {
+            let first = *multibody_roots.entry(link.multibody).or_insert(h);
+            union(&mut parents, Some(h), Some(first));
         }
     }
+
+    let mut islands: HashMap<RigidBodyHandle, Vec<RigidBodyHandle>> = HashMap::new();
+    for h in parents.keys().copied().collect::<Vec<_>>() {
+        let root = root(&mut parents, h);
+        islands.entry(root).or_default().push(h);
+    }
+
+    let mut islands: Vec<_> = islands
+        .into_values()
+        .map(|handles| {
+            let mut bodies: Vec<Entity> = handles
+                .iter()
+                .filter_map(|h| context.rigid_body_entity(*h))
+                .collect();
+            bodies.sort();
+            (handles, bodies)
+        })
+        .collect();
+    islands.sort_by(|a, b| a.1.cmp(&b.1));
+    islands
 }
 
-/// Iterates over all descendants of the `entity` and applies `f`.
-#[cfg(feature = "dim3")]
-fn traverse_descendants(entity: Entity, children: &Query<&Children>, f: &mut impl FnMut(Entity)) {
-    if let Ok(entity_children) = children.get(entity) {
-        for child in entity_children.iter().copied() {
-            f(child);
-            traverse_descendants(child, children, f);
+/// Computes the forces applied by an impulse joint during the last simulation step.
+fn impulse_joint_forces(
+    context: &RapierContext,
+    handle: ImpulseJointHandle,
+) -> Option<ReadJointForces> {
+    let scale = context.physics_scale;
+    let inv_dt = 1.0 / context.last_substep_dt;
+    let joint = context.impulse_joints.get(handle)?;
+    let rb1 = context.bodies.get(joint.body1)?;
+
+    // Rapier stores the impulses of the locked axes, of the limits and of the motors
+    // separately, so they are summed along each axis.
+    let data = &joint.data;
+    let mut impulses = SpacialVector::<Real>::zeros();
+    for i in 0..SPATIAL_DIM {
+        let axis = JointAxesMask::from_bits_truncate(1 << i);
+        if data.locked_axes.contains(axis) {
+            impulses[i] += joint.impulses[i];
+        }
+        if data.limit_axes.contains(axis) {
+            impulses[i] += data.limits[i].impulse;
+        }
+        if data.motor_axes.contains(axis) {
+            impulses[i] += data.motors[i].impulse;
+        }
+    }
+
+    // The impulses are the ones applied on the first rigid-body, expressed in the
+    // joint’s frame attached to it.
+    let basis = rb1.position().rotation * data.local_frame1.rotation;
+    let local_force = -impulses.fixed_rows::<DIM>(0).into_owned() * scale * inv_dt;
+    #[cfg(feature = "dim2")]
+    let local_torque = -impulses[DIM] * inv_dt;
+    #[cfg(feature = "dim3")]
+    let local_torque = -impulses.fixed_rows::<DIM>(DIM).into_owned() * inv_dt;
+
+    #[cfg(feature = "dim2")]
+    let torque = local_torque;
+    #[cfg(feature = "dim3")]
+    let torque = basis * local_torque;
+
+    #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
+    Some(ReadJointForces {
+        force: (basis * local_force).into(),
+        torque: torque.into(),
+        local_force: local_force.into(),
+        local_torque: local_torque.into(),
+    })
+}
+
+/// System responsible for writing the forces applied by the joints into the
+/// [`ReadJointForces`] components.
+pub fn writeback_joint_forces(
+    context: Res<RapierContext>,
+    mut joint_forces: Query<(&RapierImpulseJointHandle, &mut ReadJointForces)>,
+) {
+    for (handle, mut forces) in joint_forces.iter_mut() {
+        if let Some(new_forces) = impulse_joint_forces(&context, handle.0) {
+            // NOTE: we write the new value only if there was an
+            //       actual change, in order to not trigger bevy’s
+            //       change tracking when the values didn’t change.
+            if *forces != new_forces {
+                *forces = new_forces;
+            }
         }
     }
 }
 
-/// System responsible for creating new Rapier colliders from the related `bevy_rapier` components.
-pub fn init_colliders(
+/// System responsible for breaking the joints of the [`BreakableJoint`]s applying a force
+/// or torque above their thresholds during the last simulation step.
+pub fn break_joints(
     mut commands: Commands,
-    config: Res<RapierConfiguration>,
-    mut context: ResMut<RapierContext>,
-    colliders: Query<ColliderComponents, Without<RapierColliderHandle>>,
-    mut rigid_body_mprops: Query<&mut ReadMassProperties>,
-    parent_query: Query<(&Parent, Option<&Transform>)>,
+    context: Res<RapierContext>,
+    mut joint_broken_events: EventWriter<JointBroken>,
+    breakable_joints: Query<(Entity, &RapierImpulseJointHandle, &BreakableJoint)>,
 ) {
-    let context = &mut *context;
-    let physics_scale = context.physics_scale;
+    let dt = context.last_substep_dt;
 
-    for (
-        entity,
-        shape,
-        sensor,
-        mprops,
-        active_events,
-        active_hooks,
-        active_collision_types,
-        friction,
-        restitution,
-        collision_groups,
-        solver_groups,
-        contact_force_event_threshold,
-    ) in colliders.iter()
-    {
-        let mut scaled_shape = shape.clone();
-        scaled_shape.set_scale(shape.scale / physics_scale, config.scaled_shape_subdivision);
-        let mut builder = ColliderBuilder::new(scaled_shape.raw.clone());
+    for (entity, handle, breakable) in breakable_joints.iter() {
+        let forces = match impulse_joint_forces(&context, handle.0) {
+            Some(forces) => forces,
+            None => continue,
+        };
 
-        builder = builder.sensor(sensor.is_some());
+        #[cfg(feature = "dim2")]
+        let torque = forces.torque.abs();
+        #[cfg(feature = "dim3")]
+        let torque = forces.torque.length();
 
-        if let Some(mprops) = mprops {
-            builder = match mprops {
-                ColliderMassProperties::Density(density) => builder.density(*density),
-                ColliderMassProperties::Mass(mass) => builder.mass(*mass),
-                ColliderMassProperties::MassProperties(mprops) => {
-                    builder.mass_properties(mprops.into_rapier(physics_scale))
-                }
-            };
+        if forces.force.length() <= breakable.break_force && torque <= breakable.break_torque {
+            continue;
         }
 
-        if let Some(active_events) = active_events {
-            builder = builder.active_events((*active_events).into());
-        }
+        joint_broken_events.send(JointBroken {
+            entity,
+            impulse: forces.force * dt,
+            torque_impulse: forces.torque * dt,
+        });
 
-        if let Some(active_hooks) = active_hooks {
-            builder = builder.active_hooks((*active_hooks).into());
+        match breakable.action {
+            JointBreakAction::RemoveJoint => {
+                commands
+                    .entity(entity)
+                    .remove::<ImpulseJoint>()
+                    .remove::<BreakableJoint>();
+            }
+            JointBreakAction::Despawn => {
+                commands.entity(entity).despawn_recursive();
+            }
         }
+    }
+}
 
-        if let Some(active_collision_types) = active_collision_types {
-            builder = builder.active_collision_types((*active_collision_types).into());
-        }
+/// The relative velocity of the second rigid-body of an impulse joint, along each axis of
+/// the joint’s frame attached to the first rigid-body.
+fn impulse_joint_axis_velocities(
+    context: &RapierContext,
+    handle: ImpulseJointHandle,
+) -> Option<SpacialVector<Real>> {
+    let joint = context.impulse_joints.get(handle)?;
+    let rb1 = context.bodies.get(joint.body1)?;
+    let rb2 = context.bodies.get(joint.body2)?;
+    let basis = rb1.position().rotation * joint.data.local_frame1.rotation;
+    let anchor2 = rb2.position() * Point::from(joint.data.local_frame2.translation.vector);
+
+    let linvel = basis.inverse_transform_vector(
+        &(rb2.velocity_at_point(&anchor2) - rb1.velocity_at_point(&anchor2)),
+    ) * context.physics_scale;
+    #[cfg(feature = "dim2")]
+    let angvel = rb2.angvel() - rb1.angvel();
+    #[cfg(feature = "dim3")]
+    let angvel = basis.inverse_transform_vector(&(rb2.angvel() - rb1.angvel()));
 
-        if let Some(friction) = friction {
-            builder = builder
-                .friction(friction.coefficient)
-                .friction_combine_rule(friction.combine_rule.into());
-        }
+    let mut velocities = SpacialVector::zeros();
+    velocities.fixed_rows_mut::<DIM>(0).copy_from(&linvel);
+    #[cfg(feature = "dim2")]
+    {
+        velocities[DIM] = angvel;
+    }
+    #[cfg(feature = "dim3")]
+    velocities.fixed_rows_mut::<DIM>(DIM).copy_from(&angvel);
+    Some(velocities)
+}
 
-        if let Some(restitution) = restitution {
-            builder = builder
-                .restitution(restitution.coefficient)
-                .restitution_combine_rule(restitution.combine_rule.into());
+/// The position of the second rigid-body of an impulse joint, along each axis of the joint’s
+/// frame attached to the first rigid-body.
+///
+/// The angular positions are only exact when the joint rotates around a single axis.
+fn impulse_joint_axis_positions(
+    context: &RapierContext,
+    handle: ImpulseJointHandle,
+) -> Option<SpacialVector<Real>> {
+    let joint = context.impulse_joints.get(handle)?;
+    let frame1 = context.bodies.get(joint.body1)?.position() * joint.data.local_frame1;
+    let frame2 = context.bodies.get(joint.body2)?.position() * joint.data.local_frame2;
+    let relative = frame1.inverse() * frame2;
+
+    let mut positions = SpacialVector::zeros();
+    positions
+        .fixed_rows_mut::<DIM>(0)
+        .copy_from(&(relative.translation.vector * context.physics_scale));
+    #[cfg(feature = "dim2")]
+    {
+        positions[DIM] = relative.rotation.angle();
+    }
+    #[cfg(feature = "dim3")]
+    {
+        let rotation = relative.rotation.into_inner();
+        let rotation = if rotation.w < 0.0 {
+            -rotation
+        } else {
+            rotation
+        };
+        for i in 0..DIM {
+            positions[DIM + i] = 2.0 * rotation.imag()[i].atan2(rotation.w);
         }
+    }
+    Some(positions)
+}
 
-        if let Some(collision_groups) = collision_groups {
-            builder = builder.collision_groups((*collision_groups).into());
-        }
+/// System responsible for emitting the [`ImpactEvent`]s of the colliders with [`ImpactEvents`],
+/// from the contact force events of the last simulation step.
+pub fn emit_impact_events(
+    context: Res<RapierContext>,
+    time: Res<Time>,
+    mut contact_force_events: EventReader<ContactForceEvent>,
+    mut impact_events: EventWriter<ImpactEvent>,
+    impact_settings: Query<&ImpactEvents>,
+    materials: Query<&ImpactMaterial>,
+    // The time of the last impact of each pair of colliders, still in its cooldown.
+    mut last_impacts: Local<HashMap<(Entity, Entity), (f64, f32)>>,
+) {
+    let now = time.seconds_since_startup();
+    last_impacts.retain(|_, (impact_time, cooldown)| now - *impact_time < *cooldown as f64);
+
+    // The component of the collider, or of the rigid-body it is attached to.
+    fn collider_component<'a, T: Component>(
+        context: &RapierContext,
+        query: &'a Query<&T>,
+        entity: Entity,
+    ) -> Option<&'a T> {
+        query.get(entity).ok().or_else(|| {
+            context
+                .collider_parent(entity)
+                .and_then(|body| query.get(body).ok())
+        })
+    }
 
-        if let Some(solver_groups) = solver_groups {
-            builder = builder.solver_groups((*solver_groups).into());
-        }
+    for event in contact_force_events.iter() {
+        let settings = [event.collider1, event.collider2]
+            .map(|entity| collider_component(&context, &impact_settings, entity).copied());
+        let settings = match settings {
+            [Some(s1), Some(s2)] => ImpactEvents {
+                min_impulse: s1.min_impulse.min(s2.min_impulse),
+                min_relative_speed: s1.min_relative_speed.min(s2.min_relative_speed),
+                cooldown: s1.cooldown.max(s2.cooldown),
+            },
+            [Some(s), None] | [None, Some(s)] => s,
+            [None, None] => continue,
+        };
 
-        if let Some(threshold) = contact_force_event_threshold {
-            builder = builder.contact_force_event_threshold(threshold.0);
+        let pair = if event.collider1 <= event.collider2 {
+            (event.collider1, event.collider2)
+        } else {
+            (event.collider2, event.collider1)
+        };
+        let relative_speed = -event.relative_velocity.dot(event.max_force_direction);
+
+        if event.total_impulse_magnitude < settings.min_impulse
+            || relative_speed < settings.min_relative_speed
+            || last_impacts.contains_key(&pair)
+        {
+            continue;
         }
 
-        let mut body_entity = entity;
-        let mut body_handle = context.entity2body.get(&body_entity).copied();
-        let mut child_transform = Transform::default();
-        while body_handle.is_none() {
-            if let Ok((parent_entity, transform)) = parent_query.get(body_entity) {
-                if let Some(transform) = transform {
-                    child_transform = *transform * child_transform;
-                }
-                body_entity = parent_entity.get();
-            } else {
-                break;
+        last_impacts.insert(pair, (now, settings.cooldown));
+        impact_events.send(ImpactEvent {
+            entities: [event.collider1, event.collider2],
+            point: event.max_force_point,
+            normal: event.max_force_direction,
+            impulse: event.total_impulse_magnitude,
+            relative_speed,
+            material_pair: [event.collider1, event.collider2].map(|entity| {
+                collider_component(&context, &materials, entity)
+                    .map(|material| material.0)
+                    .unwrap_or_default()
+            }),
+        });
+    }
+}
+
+/// System responsible for emitting a [`JointLimitReached`] event whenever the joint of an
+/// entity with a [`JointLimitEventThreshold`] hits one of its limits.
+///
+/// A limit is hit when Rapier starts applying an impulse to enforce it. The velocity of the
+/// hit is the relative velocity along the limited axis before the step.
+pub fn emit_joint_limit_events(
+    context: Res<RapierContext>,
+    mut joint_limit_events: EventWriter<JointLimitReached>,
+    joints: Query<(Entity, &RapierImpulseJointHandle, &JointLimitEventThreshold)>,
+    mut previous_states: Local<HashMap<Entity, (JointAxesMask, SpacialVector<Real>)>>,
+) {
+    #[cfg(feature = "dim2")]
+    const AXES: [JointAxis; SPATIAL_DIM] = [JointAxis::X, JointAxis::Y, JointAxis::AngX];
+    #[cfg(feature = "dim3")]
+    const AXES: [JointAxis; SPATIAL_DIM] = [
+        JointAxis::X,
+        JointAxis::Y,
+        JointAxis::Z,
+        JointAxis::AngX,
+        JointAxis::AngY,
+        JointAxis::AngZ,
+    ];
+
+    let mut states = HashMap::new();
+
+    for (entity, handle, threshold) in joints.iter() {
+        let joint = match context.impulse_joints.get(handle.0) {
+            Some(joint) => joint,
+            None => continue,
+        };
+        let velocities = match impulse_joint_axis_velocities(&context, handle.0) {
+            Some(velocities) => velocities,
+            None => continue,
+        };
+
+        let mut active_limits = JointAxesMask::empty();
+
+        for (i, axis) in AXES.into_iter().enumerate() {
+            let axis_mask = JointAxesMask::from_bits_truncate(1 << i);
+            let impulse = joint.data.limits[i].impulse;
+
+            if !joint.data.limit_axes.contains(axis_mask) || impulse == 0.0 {
+                continue;
             }
 
-            body_handle = context.entity2body.get(&body_entity).copied();
-        }
+            active_limits |= axis_mask;
 
-        builder = builder.position(utils::transform_to_iso(&child_transform, physics_scale));
-        builder = builder.user_data(entity.to_bits() as u128);
+            let (was_active, previous_velocity) = match previous_states.get(&entity) {
+                Some((mask, velocities)) => (mask.contains(axis_mask), velocities[i]),
+                None => (false, 0.0),
+            };
 
-        let handle = if let Some(body_handle) = body_handle {
-            let handle =
-                context
-                    .colliders
-                    .insert_with_parent(builder, body_handle, &mut context.bodies);
-            if let Ok(mut mprops) = rigid_body_mprops.get_mut(body_entity) {
-                // Inserting the collider changed the rigid-body’s mass properties.
-                // Read them back from the engine.
-                if let Some(parent_body) = context.bodies.get(body_handle) {
-                    mprops.0 =
-                        MassProperties::from_rapier(*parent_body.mass_properties(), physics_scale);
-                }
+            if was_active {
+                continue;
             }
-            handle
-        } else {
-            context.colliders.insert(builder)
-        };
 
-        commands.entity(entity).insert(RapierColliderHandle(handle));
-        context.entity2collider.insert(entity, handle);
+            let (side, velocity) = if impulse > 0.0 {
+                (JointLimitSide::Max, previous_velocity)
+            } else {
+                (JointLimitSide::Min, -previous_velocity)
+            };
+
+            if velocity >= threshold.0 {
+                joint_limit_events.send(JointLimitReached {
+                    entity,
+                    axis,
+                    side,
+                    velocity,
+                });
+            }
+        }
+
+        states.insert(entity, (active_limits, velocities));
     }
+
+    *previous_states = states;
 }
 
-/// System responsible for creating new Rapier rigid-bodies from the related `bevy_rapier` components.
-pub fn init_rigid_bodies(
+/// System responsible for handling the rigid-bodies that left the [`PhysicsWorldBounds`].
+pub fn apply_world_bounds(
     mut commands: Commands,
     mut context: ResMut<RapierContext>,
-    rigid_bodies: Query<RigidBodyComponents, Without<RapierRigidBodyHandle>>,
+    bounds: Option<Res<PhysicsWorldBounds>>,
+    mut out_of_bounds_events: EventWriter<OutOfBounds>,
+    rigid_bodies: Query<(Entity, &RapierRigidBodyHandle)>,
+    mut reported: Local<HashSet<Entity>>,
 ) {
-    let physics_scale = context.physics_scale;
+    let bounds = match bounds {
+        Some(bounds) => bounds,
+        None => {
+            reported.clear();
+            return;
+        }
+    };
+    reported.retain(|entity| rigid_bodies.contains(*entity));
+    let scale = context.physics_scale;
 
-    for (
-        entity,
-        rb,
-        transform,
-        vel,
-        additional_mass_props,
-        _mass_props,
-        locked_axes,
-        force,
-        gravity_scale,
-        ccd,
-        dominance,
-        sleep,
-        damping,
-    ) in rigid_bodies.iter()
-    {
-        let mut builder = RigidBodyBuilder::new((*rb).into());
-        if let Some(transform) = transform {
-            builder = builder.position(utils::transform_to_iso(
-                &transform.compute_transform(),
-                physics_scale,
-            ));
+    #[cfg(feature = "dim2")]
+    let (mins, maxs) = (
+        bounds.aabb.min().xy() / scale,
+        bounds.aabb.max().xy() / scale,
+    );
+    #[cfg(feature = "dim3")]
+    let (mins, maxs) = (
+        Vect::from(bounds.aabb.min()) / scale,
+        Vect::from(bounds.aabb.max()) / scale,
+    );
+
+    for (entity, handle) in rigid_bodies.iter() {
+        let rb = match context.bodies.get_mut(handle.0) {
+            Some(rb) if !rb.is_fixed() => rb,
+            _ => continue,
+        };
+
+        let translation: Vect = (*rb.translation()).into();
+        let linvel: Vect = (*rb.linvel()).into();
+        let is_finite = translation.is_finite() && linvel.is_finite();
+
+        if is_finite && translation.cmpge(mins).all() && translation.cmple(maxs).all() {
+            reported.remove(&entity);
+            continue;
         }
 
-        #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
-        if let Some(vel) = vel {
-            builder = builder
-                .linvel((vel.linvel / physics_scale).into())
-                .angvel(vel.angvel.into());
+        // The rigid-bodies left untouched are reported once, until they are back in the bounds.
+        if bounds.action == OutOfBoundsAction::SendEvent && !reported.insert(entity) {
+            continue;
         }
 
-        if let Some(locked_axes) = locked_axes {
-            builder = builder.locked_axes((*locked_axes).into())
+        out_of_bounds_events.send(OutOfBounds(entity));
+
+        match bounds.action {
+            OutOfBoundsAction::Despawn => commands.entity(entity).despawn_recursive(),
+            OutOfBoundsAction::Clamp => {
+                if is_finite {
+                    // Remove the part of the velocity pointing outside of the bounds.
+                    let linvel =
+                        Vect::select(translation.cmplt(mins), linvel.max(Vect::ZERO), linvel);
+                    let linvel =
+                        Vect::select(translation.cmpgt(maxs), linvel.min(Vect::ZERO), linvel);
+                    rb.set_translation(translation.clamp(mins, maxs).into(), true);
+                    rb.set_linvel(linvel.into(), true);
+                } else {
+                    let mut position = Isometry::identity();
+                    position.translation.vector = ((mins + maxs) / 2.0).into();
+                    rb.set_position(position, true);
+                    rb.set_linvel(Vect::ZERO.into(), true);
+                    #[cfg(feature = "dim2")]
+                    rb.set_angvel(0.0, true);
+                    #[cfg(feature = "dim3")]
+                    rb.set_angvel(Vect::ZERO.into(), true);
+                }
+            }
+            OutOfBoundsAction::SendEvent => {}
         }
+    }
+}
 
-        if let Some(gravity_scale) = gravity_scale {
-            builder = builder.gravity_scale(gravity_scale.0);
-        }
+/// The likely cause of the non-finite state of a rigid-body.
+fn invalid_state_cause(
+    context: &RapierContext,
+    rb: &rapier::dynamics::RigidBody,
+    detection: &InvalidStateDetection,
+    force: Option<&ExternalForce>,
+    impulse: Option<&ExternalImpulse>,
+) -> InvalidStateCause {
+    #[cfg(feature = "dim2")]
+    let (force, impulse) = (
+        force.map(|f| (f.force, Vect::splat(f.torque))),
+        impulse.map(|i| (i.impulse, Vect::splat(i.torque_impulse))),
+    );
+    #[cfg(feature = "dim3")]
+    let (force, impulse) = (
+        force.map(|f| (f.force, f.torque)),
+        impulse.map(|i| (i.impulse, i.torque_impulse)),
+    );
+    let huge_force = force.into_iter().chain(impulse).any(|(linear, angular)| {
+        !linear.is_finite()
+            || !angular.is_finite()
+            || linear.length() > detection.max_force
+            || angular.length() > detection.max_force
+    });
+    if huge_force {
+        return InvalidStateCause::HugeForce;
+    }
 
-        if let Some(ccd) = ccd {
-            builder = builder.ccd_enabled(ccd.enabled)
-        }
+    let mprops = rb.mass_properties();
+    #[cfg(feature = "dim2")]
+    let inv_inertia_sqrt = [mprops.inv_principal_inertia_sqrt];
+    #[cfg(feature = "dim3")]
+    let inv_inertia_sqrt: [Real; 3] = mprops.inv_principal_inertia_sqrt.into();
+    let inertia_is_finite = inv_inertia_sqrt.iter().all(|x| x.is_finite() && *x >= 0.0);
+    let mass_is_valid = rb.mass() > 0.0 && rb.mass().is_finite();
+    if rb.is_dynamic() && (!mass_is_valid || !inertia_is_finite) {
+        return InvalidStateCause::ZeroMass;
+    }
 
-        if let Some(dominance) = dominance {
-            builder = builder.dominance_group(dominance.groups)
+    let degenerate_collider = rb.colliders().iter().any(|handle| {
+        let extents = match context.colliders.get(*handle) {
+            Some(co) => co.shape().compute_local_aabb().extents(),
+            None => return true,
+        };
+        !extents.iter().all(|x| x.is_finite()) || extents.iter().all(|x| *x <= 0.0)
+    });
+    if degenerate_collider {
+        return InvalidStateCause::DegenerateCollider;
+    }
+
+    InvalidStateCause::Unknown
+}
+
+/// System responsible for detecting the rigid-bodies with a non-finite position, rotation, or
+/// velocity after each simulation step, if the [`InvalidStateDetection`] resource exists.
+pub fn detect_invalid_states(
+    mut commands: Commands,
+    mut context: ResMut<RapierContext>,
+    detection: Option<Res<InvalidStateDetection>>,
+    mut invalid_state_events: EventWriter<PhysicsInvalidState>,
+    rigid_bodies: Query<(
+        Entity,
+        &RapierRigidBodyHandle,
+        Option<&ExternalForce>,
+        Option<&ExternalImpulse>,
+    )>,
+    // The last valid positions of the rigid-bodies, to move back the frozen rigid-bodies.
+    mut valid_positions: Local<HashMap<RigidBodyHandle, Isometry<Real>>>,
+) {
+    let detection = match detection {
+        Some(detection) => detection,
+        None => {
+            valid_positions.clear();
+            return;
         }
+    };
+    let context = &mut *context;
 
-        if let Some(sleep) = sleep {
-            builder = builder.sleeping(sleep.sleeping);
+    for (entity, handle, force, impulse) in rigid_bodies.iter() {
+        let rb = match context.bodies.get(handle.0) {
+            Some(rb) if !rb.is_fixed() => rb,
+            _ => continue,
+        };
+
+        let transform = utils::iso_to_transform(rb.position(), 1.0);
+        #[cfg(feature = "dim2")]
+        let angvel_is_finite = rb.angvel().is_finite();
+        #[cfg(feature = "dim3")]
+        let angvel_is_finite = Vect::from(*rb.angvel()).is_finite();
+        let invalid_fields = [
+            (
+                InvalidStateField::Position,
+                transform.translation.is_finite(),
+            ),
+            (InvalidStateField::Rotation, transform.rotation.is_finite()),
+            (
+                InvalidStateField::LinearVelocity,
+                Vect::from(*rb.linvel()).is_finite(),
+            ),
+            (InvalidStateField::AngularVelocity, angvel_is_finite),
+        ]
+        .into_iter()
+        .filter(|(_, is_finite)| !is_finite)
+        .map(|(field, _)| field);
+
+        let mut is_valid = true;
+        for field in invalid_fields {
+            is_valid = false;
+            invalid_state_events.send(PhysicsInvalidState {
+                entity,
+                field,
+                cause: invalid_state_cause(context, rb, &detection, force, impulse),
+            });
         }
 
-        if let Some(damping) = damping {
-            builder = builder
-                .linear_damping(damping.linear_damping)
-                .angular_damping(damping.angular_damping);
+        if is_valid {
+            valid_positions.insert(handle.0, *rb.position());
+            continue;
         }
 
-        if let Some(mprops) = additional_mass_props {
-            builder = match mprops {
-                AdditionalMassProperties::MassProperties(mprops) => {
-                    builder.additional_mass_properties(mprops.into_rapier(physics_scale))
-                }
-                AdditionalMassProperties::Mass(mass) => builder.additional_mass(*mass),
-            };
+        if detection.freeze_bodies {
+            let position = valid_positions
+                .get(&handle.0)
+                .copied()
+                .unwrap_or_else(Isometry::identity);
+            if let Some(rb) = context.bodies.get_mut(handle.0) {
+                rb.set_position(position, false);
+                rb.set_linvel(Vector::zeros(), false);
+                #[cfg(feature = "dim2")]
+                rb.set_angvel(0.0, false);
+                #[cfg(feature = "dim3")]
+                rb.set_angvel(Vector::zeros(), false);
+                rb.reset_forces(false);
+                rb.reset_torques(false);
+                rb.set_body_type(RigidBodyType::Fixed);
+            }
+            commands
+                .entity(entity)
+                .insert(RigidBody::Fixed)
+                .remove::<ExternalForce>()
+                .remove::<ExternalImpulse>();
         }
+    }
+}
 
-        builder = builder.user_data(entity.to_bits() as u128);
+/// The velocity Jacobian of the free axis of an impulse joint, for each of its rigid-bodies.
+///
+/// Also returns the factor converting the Rapier velocity along this axis to Bevy units.
+fn coupled_axis_jacobian(
+    context: &RapierContext,
+    handle: ImpulseJointHandle,
+) -> Option<([(RigidBodyHandle, Vector<Real>, AngVector<Real>); 2], Real)> {
+    let joint = context.impulse_joints.get(handle)?;
+    let rb1 = context.bodies.get(joint.body1)?;
+    let rb2 = context.bodies.get(joint.body2)?;
+    let frame1 = rb1.position() * joint.data.local_frame1;
+    let axis = frame1 * Vector::x();
+
+    if !joint.data.locked_axes.contains(JointAxesMask::X) {
+        // The relative velocity of the anchor of the second body along the axis.
+        let anchor = rb2.position() * Point::from(joint.data.local_frame2.translation.vector);
+        let angular_part = |rb: &rapier::dynamics::RigidBody| {
+            let arm = anchor - rb.position() * rb.mass_properties().local_com;
+            #[cfg(feature = "dim2")]
+            return arm.perp(&axis);
+            #[cfg(feature = "dim3")]
+            return arm.cross(&axis);
+        };
+        Some((
+            [
+                (joint.body1, -axis, -angular_part(rb1)),
+                (joint.body2, axis, angular_part(rb2)),
+            ],
+            context.physics_scale,
+        ))
+    } else if !joint.data.locked_axes.contains(JointAxesMask::ANG_X) {
+        #[cfg(feature = "dim2")]
+        let axis = 1.0;
+        Some((
+            [
+                (joint.body1, Vector::zeros(), -axis),
+                (joint.body2, Vector::zeros(), axis),
+            ],
+            1.0,
+        ))
+    } else {
+        None
+    }
+}
 
-        let mut rb = builder.build();
+/// System responsible for enforcing the [`JointCoupling`]s, by applying velocity impulses
+/// to the rigid-bodies attached to the coupled joints.
+pub fn apply_joint_couplings(mut context: ResMut<RapierContext>, couplings: Query<&JointCoupling>) {
+    let context = &mut *context;
+    let mut constraints = vec![];
 
-        #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
-        if let Some(force) = force {
-            rb.add_force((force.force / physics_scale).into(), false);
-            rb.add_torque(force.torque.into(), false);
-        }
+    for coupling in couplings.iter() {
+        let jacobians = [coupling.joint1, coupling.joint2].map(|entity| {
+            context
+                .entity2impulse_joint
+                .get(&entity)
+                .and_then(|handle| coupled_axis_jacobian(context, *handle))
+        });
+        let (jacobian1, unit1, jacobian2, unit2) = match jacobians {
+            [Some((jacobian1, unit1)), Some((jacobian2, unit2))] => {
+                (jacobian1, unit1, jacobian2, unit2)
+            }
+            _ => continue,
+        };
 
-        // NOTE: we can’t apply impulses yet at this point because
-        //       the rigid-body’s mass isn’t up-to-date yet (its
-        //       attached colliders, if any, haven’t been created yet).
+        // The constraint is `v2 - ratio * v1 = 0`, with velocities expressed in Bevy units.
+        let ratio = coupling.ratio * unit1 / unit2;
+        let mut rows: Vec<(RigidBodyHandle, Vector<Real>, AngVector<Real>)> = vec![];
+        let rows1 = jacobian1
+            .into_iter()
+            .map(|(handle, lin, ang)| (handle, -lin * ratio, -ang * ratio));
+
+        for (handle, lin, ang) in jacobian2.into_iter().chain(rows1) {
+            match context.bodies.get(handle) {
+                Some(rb) if rb.is_dynamic() => {}
+                _ => continue,
+            }
 
-        if let Some(sleep) = sleep {
-            let activation = rb.activation_mut();
-            activation.linear_threshold = sleep.linear_threshold;
-            activation.angular_threshold = sleep.angular_threshold;
+            if let Some(row) = rows.iter_mut().find(|row| row.0 == handle) {
+                row.1 += lin;
+                row.2 += ang;
+            } else {
+                rows.push((handle, lin, ang));
+            }
         }
 
-        let handle = context.bodies.insert(rb);
-        commands
-            .entity(entity)
-            .insert(RapierRigidBodyHandle(handle));
-        context.entity2body.insert(entity, handle);
+        let mut inv_effective_mass = 0.0;
 
-        if let Some(transform) = transform {
-            context.last_body_transform_set.insert(handle, *transform);
+        for (handle, lin, ang) in &rows {
+            let rb = &context.bodies[*handle];
+            let inv_inertia_sqrt = rb.mass_properties().world_inv_inertia_sqrt(rb.rotation());
+            #[cfg(feature = "dim2")]
+            let angular_part = (inv_inertia_sqrt * *ang).powi(2);
+            #[cfg(feature = "dim3")]
+            let angular_part = (inv_inertia_sqrt * *ang).norm_squared();
+            inv_effective_mass += lin.norm_squared() * rb.mass_properties().inv_mass + angular_part;
+        }
+
+        if inv_effective_mass > 0.0 {
+            constraints.push((rows, inv_effective_mass));
         }
     }
-}
 
-/// This applies the initial impulse given to a rigid-body when it is created.
-///
-/// This cannot be done inside `init_rigid_bodies` because impulses require the rigid-body
-/// mass to be available, which it was not because colliders were not created yet. As a
-/// result, we run this system after the collider creation.
-pub fn apply_initial_rigid_body_impulses(
-    mut context: ResMut<RapierContext>,
-    // We can’t use RapierRigidBodyHandle yet because its creation command hasn’t been
-    // executed yet.
-    mut init_impulses: Query<(Entity, &mut ExternalImpulse), Without<RapierRigidBodyHandle>>,
-) {
-    let context = &mut *context;
-    let scale = context.physics_scale;
+    // Couplings sharing rigid-bodies (e.g. gear trains) are solved iteratively.
+    for _ in 0..context.integration_parameters.max_velocity_iterations {
+        for (rows, inv_effective_mass) in &constraints {
+            let mut velocity_error = 0.0;
 
-    for (entity, mut impulse) in init_impulses.iter_mut() {
-        let bodies = &mut context.bodies;
-        if let Some(rb) = context
-            .entity2body
-            .get(&entity)
-            .and_then(|h| bodies.get_mut(*h))
-        {
-            // Make sure the mass-properties are computed.
-            rb.recompute_mass_properties_from_colliders(&context.colliders);
-            // Apply the impulse.
-            rb.apply_impulse((impulse.impulse / scale).into(), false);
+            for (handle, lin, ang) in rows {
+                let rb = &context.bodies[*handle];
+                #[cfg(feature = "dim2")]
+                {
+                    velocity_error += lin.dot(rb.linvel()) + ang * rb.angvel();
+                }
+                #[cfg(feature = "dim3")]
+                {
+                    velocity_error += lin.dot(rb.linvel()) + ang.dot(rb.angvel());
+                }
+            }
 
-            #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
-            rb.apply_torque_impulse(impulse.torque_impulse.into(), false);
+            if velocity_error == 0.0 {
+                continue;
+            }
 
-            impulse.reset();
+            let impulse = -velocity_error / inv_effective_mass;
+
+            for (handle, lin, ang) in rows {
+                let rb = &mut context.bodies[*handle];
+                let wake_up = rb.is_sleeping();
+                rb.apply_impulse(lin * impulse, wake_up);
+                rb.apply_torque_impulse(ang * impulse, wake_up);
+            }
         }
     }
 }
 
-/// System responsible for creating new Rapier joints from the related `bevy_rapier` components.
-pub fn init_joints(
-    mut commands: Commands,
+/// The duration of the next simulation step, ignoring substeps.
+fn simulation_step_dt(timestep_mode: TimestepMode, time: &Time) -> Real {
+    match timestep_mode {
+        TimestepMode::Fixed { dt, .. } => dt,
+        TimestepMode::Variable {
+            max_dt, time_scale, ..
+        } => (time.delta_seconds() * time_scale).min(max_dt),
+        TimestepMode::Interpolated { dt, time_scale, .. } => dt * time_scale,
+    }
+}
+
+/// System responsible for enforcing the [`PulleyJoint`]s, by applying velocity impulses
+/// to the rigid-bodies pulled by a taut rope.
+pub fn solve_pulley_joints(
     mut context: ResMut<RapierContext>,
-    impulse_joints: Query<(Entity, &ImpulseJoint), Without<RapierImpulseJointHandle>>,
-    multibody_joints: Query<(Entity, &MultibodyJoint), Without<RapierMultibodyJointHandle>>,
-    parent_query: Query<&Parent>,
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    pulleys: Query<(Entity, &PulleyJoint)>,
 ) {
+    let dt = simulation_step_dt(config.timestep_mode, &time);
+    if pulleys.is_empty() || dt <= 0.0 {
+        return;
+    }
+
     let context = &mut *context;
     let scale = context.physics_scale;
+    let erp = context.integration_parameters.erp;
+    let mut constraints = vec![];
+
+    for (entity, pulley) in pulleys.iter() {
+        let handles = match (
+            context.entity2body.get(&pulley.parent),
+            context.entity2body.get(&entity),
+        ) {
+            (Some(handle1), Some(handle2)) => [*handle1, *handle2],
+            _ => continue,
+        };
 
-    for (entity, joint) in impulse_joints.iter() {
-        let mut target = None;
-        let mut body_entity = entity;
-        while target.is_none() {
-            target = context.entity2body.get(&body_entity).copied();
-            if let Ok(parent_entity) = parent_query.get(body_entity) {
-                body_entity = parent_entity.get();
-            } else {
-                break;
+        let sides = [
+            (handles[0], pulley.ground_anchor1, pulley.local_anchor1, 1.0),
+            (
+                handles[1],
+                pulley.ground_anchor2,
+                pulley.local_anchor2,
+                pulley.ratio,
+            ),
+        ];
+        let mut rope_length = 0.0;
+        let mut rows = vec![];
+        let mut inv_effective_mass = 0.0;
+
+        for (handle, ground_anchor, local_anchor, factor) in sides {
+            let rb = match context.bodies.get(handle) {
+                Some(rb) => rb,
+                None => continue,
+            };
+            let ground_anchor: Point<Real> = (ground_anchor / scale).into();
+            let local_anchor: Point<Real> = (local_anchor / scale).into();
+            let anchor = rb.position() * local_anchor;
+            let (dir, length) = match UnitVector::try_new_and_get(anchor - ground_anchor, 1.0e-6) {
+                Some((dir, length)) => (dir.into_inner(), length),
+                None => (Vector::zeros(), 0.0),
+            };
+            rope_length += factor * length;
+
+            if !rb.is_dynamic() {
+                continue;
             }
+
+            let arm = anchor - rb.position() * rb.mass_properties().local_com;
+            let lin = dir * factor;
+            #[cfg(feature = "dim2")]
+            let ang = arm.perp(&lin);
+            #[cfg(feature = "dim3")]
+            let ang = arm.cross(&lin);
+            let inv_inertia_sqrt = rb.mass_properties().world_inv_inertia_sqrt(rb.rotation());
+            #[cfg(feature = "dim2")]
+            let angular_part = (inv_inertia_sqrt * ang).powi(2);
+            #[cfg(feature = "dim3")]
+            let angular_part = (inv_inertia_sqrt * ang).norm_squared();
+            inv_effective_mass += lin.norm_squared() * rb.mass_properties().inv_mass + angular_part;
+            rows.push((handle, lin, ang));
         }
 
-        if let (Some(target), Some(source)) = (target, context.entity2body.get(&joint.parent)) {
-            let handle =
-                context
-                    .impulse_joints
-                    .insert(*source, target, joint.data.into_rapier(scale), true);
-            commands
-                .entity(entity)
-                .insert(RapierImpulseJointHandle(handle));
-            context.entity2impulse_joint.insert(entity, handle);
+        // Lets the slack rope become taut during the next step, or pulls the bodies back
+        // if it is already stretched.
+        let error = rope_length - pulley.length / scale;
+        let bias = if error < 0.0 {
+            error / dt
+        } else {
+            erp * error / dt
+        };
+
+        if inv_effective_mass > 0.0 {
+            constraints.push((rows, inv_effective_mass, bias, 0.0));
         }
     }
 
-    for (entity, joint) in multibody_joints.iter() {
-        let target = context.entity2body.get(&entity);
+    for _ in 0..context.integration_parameters.max_velocity_iterations {
+        for (rows, inv_effective_mass, bias, total_impulse) in &mut constraints {
+            let mut velocity_error = *bias;
 
-        if let (Some(target), Some(source)) = (target, context.entity2body.get(&joint.parent)) {
-            if let Some(handle) = context.multibody_joints.insert(
-                *source,
-                *target,
-                joint.data.into_rapier(scale),
-                true,
-            ) {
-                commands
-                    .entity(entity)
-                    .insert(RapierMultibodyJointHandle(handle));
-                context.entity2multibody_joint.insert(entity, handle);
-            } else {
-                error!("Failed to create multibody joint: loop detected.")
+            for (handle, lin, ang) in rows.iter() {
+                let rb = &context.bodies[*handle];
+                #[cfg(feature = "dim2")]
+                {
+                    velocity_error += lin.dot(rb.linvel()) + ang * rb.angvel();
+                }
+                #[cfg(feature = "dim3")]
+                {
+                    velocity_error += lin.dot(rb.linvel()) + ang.dot(rb.angvel());
+                }
+            }
+
+            // The rope can only pull, so the accumulated impulse is never positive.
+            let new_total_impulse =
+                (*total_impulse - velocity_error / *inv_effective_mass).min(0.0);
+            let impulse = new_total_impulse - *total_impulse;
+            *total_impulse = new_total_impulse;
+
+            if impulse == 0.0 {
+                continue;
+            }
+
+            for (handle, lin, ang) in rows.iter() {
+                let rb = &mut context.bodies[*handle];
+                let wake_up = rb.is_sleeping();
+                rb.apply_impulse(lin * impulse, wake_up);
+                rb.apply_torque_impulse(ang * impulse, wake_up);
             }
         }
     }
 }
 
-/// System responsible for removing from Rapier the rigid-bodies/colliders/joints which had
-/// their related `bevy_rapier` components removed by the user (through component removal or
-/// despawn).
-pub fn sync_removals(
-    mut commands: Commands,
+/// System responsible for gathering the [`RollingFriction`]s (and `SpinFriction`s in 3D) of the
+/// colliders, and the [`AppliedMaterial`]s used by the rolling resistance of the
+/// [`PhysicsMaterials`].
+///
+/// Rapier’s contact modification cannot express torques, so the resistance is applied after
+/// each simulation step, as angular impulses proportional to the normal impulses computed by
+/// this step.
+pub fn update_rolling_friction(
     mut context: ResMut<RapierContext>,
-    removed_bodies: RemovedComponents<RapierRigidBodyHandle>,
-    removed_colliders: RemovedComponents<RapierColliderHandle>,
-    removed_impulse_joints: RemovedComponents<RapierImpulseJointHandle>,
-    removed_multibody_joints: RemovedComponents<RapierMultibodyJointHandle>,
-    orphan_bodies: Query<Entity, (With<RapierRigidBodyHandle>, Without<RigidBody>)>,
-    orphan_colliders: Query<Entity, (With<RapierColliderHandle>, Without<Collider>)>,
-    orphan_impulse_joints: Query<Entity, (With<RapierImpulseJointHandle>, Without<ImpulseJoint>)>,
-    orphan_multibody_joints: Query<
-        Entity,
-        (With<RapierMultibodyJointHandle>, Without<MultibodyJoint>),
-    >,
-
-    removed_sensors: RemovedComponents<Sensor>,
+    rolling_frictions: Query<(Entity, &RollingFriction)>,
+    #[cfg(feature = "dim3")] spin_frictions: Query<(Entity, &SpinFriction)>,
+    physics_materials: Option<Res<PhysicsMaterials>>,
+    applied_materials: Query<(Entity, &AppliedMaterial)>,
 ) {
-    /*
-     * Rigid-bodies removal detection.
-     */
-    let context = &mut *context;
-    for entity in removed_bodies.iter() {
-        if let Some(handle) = context.entity2body.remove(&entity) {
-            let _ = context.last_body_transform_set.remove(&handle);
-            context.bodies.remove(
-                handle,
-                &mut context.islands,
-                &mut context.colliders,
-                &mut context.impulse_joints,
-                &mut context.multibody_joints,
-                false,
+    let resistances = &mut context.rolling_resistances;
+
+    resistances.rolling_frictions.clear();
+    resistances
+        .rolling_frictions
+        .extend(rolling_frictions.iter().map(|(e, f)| (e, *f)));
+    #[cfg(feature = "dim3")]
+    {
+        resistances.spin_frictions.clear();
+        resistances
+            .spin_frictions
+            .extend(spin_frictions.iter().map(|(e, f)| (e, *f)));
+    }
+
+    resistances.materials.clear();
+    match physics_materials {
+        Some(physics_materials) => {
+            if physics_materials.is_changed() || resistances.physics_materials.is_none() {
+                resistances.physics_materials = Some(physics_materials.clone());
+            }
+            resistances.materials.extend(
+                applied_materials
+                    .iter()
+                    .map(|(e, material)| (e, material.0.clone_weak())),
             );
         }
+        None => resistances.physics_materials = None,
     }
+}
 
+/// System responsible for updating the collision event filters of the colliders with a
+/// [`CollisionEventFilter`].
+pub fn update_collision_event_filters(
+    mut context: ResMut<RapierContext>,
+    filters: Query<
+        (Entity, &CollisionEventFilter),
+        Or<(Changed<CollisionEventFilter>, Without<RapierColliderHandle>)>,
+    >,
+    removed_filters: RemovedComponents<CollisionEventFilter>,
+) {
     let context = &mut *context;
-    for entity in orphan_bodies.iter() {
-        if let Some(handle) = context.entity2body.remove(&entity) {
-            let _ = context.last_body_transform_set.remove(&handle);
-            context.bodies.remove(
-                handle,
-                &mut context.islands,
-                &mut context.colliders,
-                &mut context.impulse_joints,
-                &mut context.multibody_joints,
-                false,
-            );
+
+    for (entity, filter) in filters.iter() {
+        if let Some(handle) = context.entity2collider.get(&entity) {
+            context.collision_event_filters.insert(*handle, filter.0);
         }
-        commands.entity(entity).remove::<RapierRigidBodyHandle>();
     }
 
-    /*
-     * Collider removal detection.
-     */
-    for entity in removed_colliders.iter() {
-        if let Some(handle) = context.entity2collider.remove(&entity) {
-            context
-                .colliders
-                .remove(handle, &mut context.islands, &mut context.bodies, true);
-            context.deleted_colliders.insert(handle, entity);
+    for entity in removed_filters.iter() {
+        if let Some(handle) = context.entity2collider.get(&entity) {
+            context.collision_event_filters.remove(handle);
         }
     }
+}
 
-    for entity in orphan_colliders.iter() {
-        if let Some(handle) = context.entity2collider.remove(&entity) {
-            context
-                .colliders
-                .remove(handle, &mut context.islands, &mut context.bodies, true);
-            context.deleted_colliders.insert(handle, entity);
+/// System responsible for enabling the contact modification of the colliders with a
+/// [`OneWayCollider`], whichever their [`ActiveHooks`] are.
+pub fn update_one_way_colliders(
+    mut context: ResMut<RapierContext>,
+    one_way_colliders: Query<
+        (Entity, Option<&ActiveHooks>),
+        (
+            With<OneWayCollider>,
+            Or<(
+                Changed<OneWayCollider>,
+                Changed<ActiveHooks>,
+                Without<RapierColliderHandle>,
+            )>,
+        ),
+    >,
+    removed_one_way_colliders: RemovedComponents<OneWayCollider>,
+    active_hooks: Query<&ActiveHooks>,
+) {
+    let context = &mut *context;
+    let mut set_active_hooks = |entity: Entity, hooks: ActiveHooks| {
+        if let Some(co) = context
+            .entity2collider
+            .get(&entity)
+            .and_then(|handle| context.colliders.get_mut(*handle))
+        {
+            co.set_active_hooks(hooks.into());
         }
-        commands.entity(entity).remove::<RapierColliderHandle>();
+    };
+
+    for (entity, hooks) in one_way_colliders.iter() {
+        let hooks = hooks.copied().unwrap_or_default() | ActiveHooks::MODIFY_SOLVER_CONTACTS;
+        set_active_hooks(entity, hooks);
     }
 
-    /*
-     * Impulse joint removal detection.
-     */
-    for entity in removed_impulse_joints.iter() {
-        if let Some(handle) = context.entity2impulse_joint.remove(&entity) {
-            context.impulse_joints.remove(handle, true);
-        }
+    for entity in removed_one_way_colliders.iter() {
+        let hooks = active_hooks.get(entity).copied().unwrap_or_default();
+        set_active_hooks(entity, hooks);
     }
+}
 
-    for entity in orphan_impulse_joints.iter() {
-        if let Some(handle) = context.entity2impulse_joint.remove(&entity) {
-            context.impulse_joints.remove(handle, true);
-        }
-        commands.entity(entity).remove::<RapierImpulseJointHandle>();
+/// System responsible for advancing the physics simulation, and updating the internal state
+/// for scene queries.
+pub fn step_simulation<PhysicsHooksData: 'static + WorldQuery + Send + Sync>(
+    mut context: ResMut<RapierContext>,
+    config: Res<RapierConfiguration>,
+    hooks: Res<PhysicsHooksWithQueryResource<PhysicsHooksData>>,
+    (time, mut sim_to_render_time): (Res<Time>, ResMut<SimulationToRenderTime>),
+    collision_events: EventWriter<CollisionEvent>,
+    contact_force_events: EventWriter<ContactForceEvent>,
+    mut collision_event_details: EventWriter<CollisionEventDetails>,
+    mut sensor_events: EventWriter<SensorEvent>,
+    mut ccd_impacts: EventWriter<CcdImpact>,
+    physics_materials: Option<Res<PhysicsMaterials>>,
+    hooks_data: Query<PhysicsHooksData>,
+    one_way_colliders: Query<&'static OneWayCollider>,
+    interpolation_query: Query<(&RapierRigidBodyHandle, &mut TransformInterpolation)>,
+) {
+    let context = &mut *context;
+
+    if config.physics_pipeline_active {
+        let hooks_instance = PhysicsHooksWithQueryInstance {
+            user_data: hooks_data,
+            one_way_colliders,
+            hooks: &*hooks.0,
+            physics_scale: context.physics_scale,
+            physics_materials: physics_materials.as_deref(),
+        };
+
+        context.step_simulation(
+            config.gravity,
+            config.timestep_mode,
+            Some((collision_events, contact_force_events)),
+            &hooks_instance,
+            &time,
+            &mut sim_to_render_time,
+            Some(interpolation_query),
+        );
+        context.deleted_colliders.clear();
+        context.deleted_sensors.clear();
+        collision_event_details.send_batch(context.collision_event_details.drain(..));
+        sensor_events.send_batch(context.sensor_events.drain(..));
+        ccd_impacts.send_batch(context.ccd_impacts.drain(..));
+    } else {
+        context.propagate_modified_body_positions_to_colliders();
     }
 
-    /*
-     * Multibody joint removal detection.
-     */
-    for entity in removed_multibody_joints.iter() {
-        if let Some(handle) = context.entity2multibody_joint.remove(&entity) {
-            context.multibody_joints.remove(handle, true);
-        }
+    if config.query_pipeline_active && config.query_pipeline_update != QueryPipelineUpdate::Manual {
+        context.update_query_pipeline();
     }
+}
 
-    for entity in orphan_multibody_joints.iter() {
-        if let Some(handle) = context.entity2multibody_joint.remove(&entity) {
-            context.multibody_joints.remove(handle, true);
-        }
-        commands
-            .entity(entity)
-            .remove::<RapierMultibodyJointHandle>();
+/// System responsible for updating the query pipeline before the simulation step, with
+/// [`QueryPipelineUpdate::AfterSyncAndStep`].
+pub fn update_query_pipeline_after_sync(
+    config: Res<RapierConfiguration>,
+    mut context: ResMut<RapierContext>,
+) {
+    if config.query_pipeline_active
+        && config.query_pipeline_update == QueryPipelineUpdate::AfterSyncAndStep
+    {
+        context.update_query_pipeline_now();
     }
+}
 
-    /*
-     * Sensor marker component removal detection.
-     */
-    for entity in removed_sensors.iter() {
-        if let Some(handle) = context.entity2collider.get(&entity) {
-            if let Some(co) = context.colliders.get_mut(*handle) {
-                co.set_sensor(false);
+/// System responsible for applying the [`ActivePhysicsConfig`] to the physics resources when it
+/// is loaded or modified.
+#[cfg(feature = "serde-serialize")]
+pub fn apply_physics_config(
+    mut events: EventReader<AssetEvent<PhysicsConfigAsset>>,
+    assets: Res<Assets<PhysicsConfigAsset>>,
+    active: Option<Res<ActivePhysicsConfig>>,
+    mut config: ResMut<RapierConfiguration>,
+    mut context: ResMut<RapierContext>,
+    #[cfg(feature = "debug-render")] debug_render: Option<
+        ResMut<crate::render::DebugRenderContext>,
+    >,
+) {
+    let active = match active {
+        Some(active) => active,
+        None => {
+            events.clear();
+            return;
+        }
+    };
+
+    let modified = events.iter().any(|event| match event {
+        AssetEvent::Created { handle } | AssetEvent::Modified { handle } => *handle == active.0,
+        AssetEvent::Removed { .. } => false,
+    });
+
+    if modified || active.is_changed() {
+        if let Some(asset) = assets.get(&active.0) {
+            asset.apply(&mut config, &mut context.integration_parameters);
+            #[cfg(feature = "debug-render")]
+            if let Some(mut debug_render) = debug_render {
+                asset.apply_debug_render(&mut debug_render);
             }
         }
     }
-
-    // TODO: update mass props after collider removal.
-    // TODO: what about removing forces?
 }
 
-/// Adds entity to [`CollidingEntities`] on starting collision and removes from it when the
-/// collision ends.
-pub fn update_colliding_entities(
-    mut collision_events: EventReader<CollisionEvent>,
-    mut colliding_entities: Query<&mut CollidingEntities>,
-) {
-    for event in collision_events.iter() {
-        match event.to_owned() {
-            CollisionEvent::Started(entity1, entity2, _) => {
-                if let Ok(mut entities) = colliding_entities.get_mut(entity1) {
-                    entities.0.insert(entity2);
+/// System responsible for spawning the scenes of the [`PhysicsPrefab`]s once they are loaded,
+/// and setting the global transforms of their entities.
+pub fn spawn_physics_prefabs(world: &mut World) {
+    let prefabs: Vec<_> = world
+        .query::<(Entity, &PhysicsPrefab)>()
+        .iter(world)
+        .map(|(entity, prefab)| (entity, prefab.0.clone()))
+        .collect();
+    if prefabs.is_empty() {
+        return;
+    }
+
+    world.resource_scope(|world, mut spawner: Mut<SceneSpawner>| {
+        for (prefab, scene) in prefabs {
+            if !world.resource::<Assets<Scene>>().contains(&scene) {
+                continue;
+            }
+
+            let entities: Vec<_> = match spawner.spawn_sync(world, scene) {
+                Ok(instance) => spawner
+                    .iter_instance_entities(instance)
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                Err(err) => {
+                    error!("Unable to spawn the physics prefab: {}", err);
+                    Vec::new()
                 }
-                if let Ok(mut entities) = colliding_entities.get_mut(entity2) {
-                    entities.0.insert(entity1);
+            };
+            for entity in &entities {
+                if !world.entity(*entity).contains::<Parent>() {
+                    world.entity_mut(prefab).push_children(&[*entity]);
                 }
             }
-            CollisionEvent::Stopped(entity1, entity2, _) => {
-                if let Ok(mut entities) = colliding_entities.get_mut(entity1) {
-                    entities.0.remove(&entity2);
+
+            // Propagate the transforms, which would otherwise be propagated at the end of the
+            // frame, after the physics stages.
+            let parent_transform = world
+                .get::<Parent>(prefab)
+                .and_then(|parent| world.get::<GlobalTransform>(parent.get()))
+                .copied()
+                .unwrap_or_default();
+            let mut stack = vec![(prefab, parent_transform)];
+            while let Some((entity, parent_transform)) = stack.pop() {
+                let global_transform = match world.get::<Transform>(entity) {
+                    Some(transform) => parent_transform.mul_transform(*transform),
+                    None => parent_transform,
+                };
+                if let Some(mut current) = world.get_mut::<GlobalTransform>(entity) {
+                    *current = global_transform;
                 }
-                if let Ok(mut entities) = colliding_entities.get_mut(entity2) {
-                    entities.0.remove(&entity1);
+                if let Some(children) = world.get::<Children>(entity) {
+                    stack.extend(children.iter().map(|child| (*child, global_transform)));
                 }
             }
+
+            let mut prefab = world.entity_mut(prefab);
+            prefab.remove::<PhysicsPrefab>();
+            prefab.insert(PhysicsPrefabInstance { entities });
         }
+    });
+}
+
+/// System responsible for recording the checksum of the physics world in the
+/// [`LockstepChecksums`] after each simulation step.
+pub fn record_lockstep_checksum(
+    context: Res<RapierContext>,
+    mut checksums: ResMut<LockstepChecksums>,
+) {
+    let step = context.step_index();
+    if step > 0 && checksums.latest().map(|(last, _)| last) != Some(step) {
+        checksums.push(step, context.checksum());
     }
 }
 
-/// System responsible for applying the character controller translation to the underlying
-/// collider.
-pub fn update_character_controls(
-    mut commands: Commands,
-    config: Res<RapierConfiguration>,
+/// System lending the pipeline of the [`DebugStreamRecorder`] to the physics world while it
+/// is recording, for the debug-render to be recorded after each simulation step.
+#[cfg(feature = "debug-stream")]
+pub fn prepare_debug_stream(
     mut context: ResMut<RapierContext>,
-    mut character_controllers: Query<(
-        Entity,
-        &mut KinematicCharacterController,
-        Option<&mut KinematicCharacterControllerOutput>,
-        Option<&RapierColliderHandle>,
-        Option<&RapierRigidBodyHandle>,
-        Option<&GlobalTransform>,
-    )>,
-    mut transforms: Query<&mut Transform>,
+    recorder: Option<ResMut<DebugStreamRecorder>>,
 ) {
-    let physics_scale = context.physics_scale;
-    let context = &mut *context;
-    for (entity, mut controller, output, collider_handle, body_handle, glob_transform) in
-        character_controllers.iter_mut()
-    {
-        if let (Some(raw_controller), Some(translation)) =
-            (controller.to_raw(physics_scale), controller.translation)
-        {
-            let scaled_custom_shape =
-                controller
-                    .custom_shape
-                    .as_ref()
-                    .map(|(custom_shape, tra, rot)| {
-                        // TODO: avoid the systematic scale somehow?
-                        let mut scaled_shape = custom_shape.clone();
-                        scaled_shape.set_scale(
-                            custom_shape.scale / physics_scale,
-                            config.scaled_shape_subdivision,
-                        );
+    if let Some(mut recorder) = recorder {
+        if recorder.is_recording() {
+            context.debug_stream_pipeline = Some(std::mem::take(&mut recorder.pipeline));
+        }
+    }
+}
 
-                        (scaled_shape, *tra / physics_scale, *rot)
-                    });
+/// System responsible for writing the debug-render of the physics world recorded after each
+/// simulation step into the stream of the [`DebugStreamRecorder`].
+#[cfg(feature = "debug-stream")]
+pub fn record_debug_stream(
+    mut context: ResMut<RapierContext>,
+    mut recorder: ResMut<DebugStreamRecorder>,
+) {
+    if let Some(pipeline) = context.debug_stream_pipeline.take() {
+        recorder.pipeline = pipeline;
+    }
+    let frames = std::mem::take(&mut context.debug_stream_frames);
+    recorder.write_frames(frames);
+}
 
-            let parent_rigid_body = body_handle.map(|h| h.0).or_else(|| {
-                collider_handle
-                    .and_then(|h| context.colliders.get(h.0))
-                    .and_then(|c| c.parent())
-            });
-            let entity_to_move = parent_rigid_body
-                .and_then(|rb| context.rigid_body_entity(rb))
-                .unwrap_or(entity);
+/// System responsible for recording, with the [`PhysicsRecorder`], the changes made to the
+/// physics world before the simulation step.
+pub fn record_physics_inputs(
+    context: Res<RapierContext>,
+    mut recorder: ResMut<PhysicsRecorder>,
+    changed_forces: Query<(Entity, &ExternalForce), Changed<ExternalForce>>,
+) {
+    if !recorder.is_recording() {
+        return;
+    }
 
-            let (character_shape, character_pos) = if let Some((scaled_shape, tra, rot)) =
-                &scaled_custom_shape
-            {
-                let mut shape_pos: Isometry<Real> = (*tra, *rot).into();
+    let scale = context.physics_scale;
+    recorder.record_inputs(
+        &context,
+        changed_forces.iter().map(|(entity, forces)| {
+            #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
+            let torque = forces.torque.into();
+            (entity, (forces.force / scale).into(), torque)
+        }),
+    );
+}
 
-                if let Some(body) = body_handle.and_then(|h| context.bodies.get(h.0)) {
-                    shape_pos = body.position() * shape_pos
-                } else if let Some(gtransform) = glob_transform {
-                    shape_pos =
-                        transform_to_iso(&gtransform.compute_transform(), physics_scale) * shape_pos
+/// System responsible for recording, with the [`PhysicsRecorder`], the end of the update of the
+/// physics world after the simulation step.
+pub fn record_physics_step(
+    context: Res<RapierContext>,
+    config: Res<RapierConfiguration>,
+    (time, sim_to_render_time): (Res<Time>, Res<SimulationToRenderTime>),
+    mut recorder: ResMut<PhysicsRecorder>,
+) {
+    if config.physics_pipeline_active && recorder.is_recording() {
+        recorder.record_step(
+            &context,
+            config.gravity,
+            config.timestep_mode,
+            time.delta(),
+            &sim_to_render_time,
+        );
+    }
+}
+
+/// NOTE: This currently does nothing in 2D.
+#[cfg(feature = "dim2")]
+pub fn init_async_colliders() {}
+
+/// System responsible for creating `Collider` components from `AsyncCollider` components if the
+/// corresponding mesh has become available.
+#[cfg(feature = "dim3")]
+pub fn init_async_colliders(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    async_colliders: Query<(Entity, &AsyncCollider)>,
+) {
+    for (entity, async_collider) in async_colliders.iter() {
+        if let Some(mesh) = meshes.get(&async_collider.handle) {
+            match Collider::from_bevy_mesh(mesh, &async_collider.shape) {
+                Some(collider) => {
+                    commands
+                        .entity(entity)
+                        .insert(collider)
+                        .remove::<AsyncCollider>();
                 }
+                None => error!("Unable to generate collider from mesh {:?}", mesh),
+            }
+        }
+    }
+}
 
-                (&*scaled_shape.raw, shape_pos)
-            } else if let Some(collider) = collider_handle.and_then(|h| context.colliders.get(h.0))
-            {
-                (collider.shape(), *collider.position())
-            } else {
-                continue;
-            };
+/// System responsible for creating `Collider` components from `AsyncColliderFromAsset` components
+/// once their `ColliderAsset` has been loaded.
+pub fn init_colliders_from_assets(
+    mut commands: Commands,
+    assets: Option<Res<Assets<ColliderAsset>>>,
+    async_colliders: Query<(Entity, &AsyncColliderFromAsset)>,
+) {
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
 
-            let exclude_collider = collider_handle.map(|h| h.0);
+    for (entity, async_collider) in async_colliders.iter() {
+        if let Some(asset) = assets.get(&async_collider.0) {
+            commands
+                .entity(entity)
+                .insert(asset.collider.clone())
+                .remove::<AsyncColliderFromAsset>();
+        }
+    }
+}
+
+/// System spawning the colliders of the [`StaticWorld`]s built in the background as children
+/// of their entity.
+pub fn spawn_static_worlds(
+    mut commands: Commands,
+    mut static_worlds: Query<(Entity, &mut StaticWorld)>,
+) {
+    for (entity, mut static_world) in static_worlds.iter_mut() {
+        if let Some(built) = static_world.take_built() {
+            let colliders: Vec<_> = built
+                .into_iter()
+                .map(|(transform, collider)| {
+                    commands
+                        .spawn()
+                        .insert_bundle(TransformBundle::from(transform))
+                        .insert(collider)
+                        .id()
+                })
+                .collect();
+            commands.entity(entity).push_children(&colliders);
+            static_world.set_colliders(colliders);
+        }
+    }
+}
+
+/// System loading the chunks of the [`TerrainStreamer`]s around the entities with a
+/// [`TerrainFocus`], and unloading the far ones.
+pub fn stream_terrains(
+    mut commands: Commands,
+    focuses: Query<&GlobalTransform, With<TerrainFocus>>,
+    mut terrains: Query<(Entity, &GlobalTransform, &mut TerrainStreamer)>,
+) {
+    for (entity, global_transform, mut streamer) in terrains.iter_mut() {
+        let world_to_terrain = global_transform.compute_matrix().inverse();
+        let local_focuses: Vec<Vect> = focuses
+            .iter()
+            .map(|focus| {
+                let point = world_to_terrain.transform_point3(focus.translation());
+                #[cfg(feature = "dim2")]
+                return point.truncate();
+                #[cfg(feature = "dim3")]
+                return point;
+            })
+            .collect();
+        streamer.stream(&mut commands, entity, &local_focuses);
+    }
+}
+
+/// System responsible for creating `Collider` components from `AsyncSceneCollider` components if the
+/// corresponding scene has become available.
+#[cfg(feature = "dim3")]
+pub fn init_async_scene_colliders(
+    mut commands: Commands,
+    meshes: Res<Assets<Mesh>>,
+    scenes: Res<Assets<Scene>>,
+    async_colliders: Query<(Entity, &AsyncSceneCollider)>,
+    children: Query<&Children>,
+    mesh_handles: Query<(&Name, &Handle<Mesh>)>,
+) {
+    for (entity, async_collider) in async_colliders.iter() {
+        if scenes.get(&async_collider.handle).is_some() {
+            traverse_descendants(entity, &children, &mut |child| {
+                if let Ok((name, handle)) = mesh_handles.get(child) {
+                    let shape = async_collider
+                        .named_shapes
+                        .get(name.as_str())
+                        .unwrap_or(&async_collider.shape);
+                    if let Some(shape) = shape {
+                        let mesh = meshes.get(handle).unwrap(); // NOTE: Mesh is already loaded
+                        match Collider::from_bevy_mesh(mesh, shape) {
+                            Some(collider) => {
+                                commands.entity(child).insert(collider);
+                            }
+                            None => error!(
+                                "Unable to generate collider from mesh {:?} with name {}",
+                                mesh, name
+                            ),
+                        }
+                    }
+                }
+            });
+
+            commands.entity(entity).remove::<AsyncSceneCollider>();
+        }
+    }
+}
+
+/// Iterates over all descendants of the `entity` and applies `f`.
+fn traverse_descendants(entity: Entity, children: &Query<&Children>, f: &mut impl FnMut(Entity)) {
+    if let Ok(entity_children) = children.get(entity) {
+        for child in entity_children.iter().copied() {
+            f(child);
+            traverse_descendants(child, children, f);
+        }
+    }
+}
+
+/// System responsible for turning the bones of the skinned meshes with a new [`Ragdoll`]
+/// into rigid-bodies attached by joints.
+#[cfg(feature = "dim3")]
+pub fn init_ragdolls(
+    mut commands: Commands,
+    ragdolls: Query<(&Ragdoll, &SkinnedMesh), Added<Ragdoll>>,
+    names: Query<&Name>,
+    children: Query<&Children>,
+    parents: Query<&Parent>,
+    transforms: Query<(&Transform, &GlobalTransform)>,
+) {
+    for (ragdoll, skinned_mesh) in ragdolls.iter() {
+        let configs: HashMap<Entity, &RagdollBoneConfig> = skinned_mesh
+            .joints
+            .iter()
+            .filter_map(|bone| {
+                let name = names.get(*bone).ok()?;
+                Some((*bone, ragdoll.bones.get(name.as_str())?))
+            })
+            .collect();
+
+        for (&bone, config) in configs.iter() {
+            let (_, global_transform) = match transforms.get(bone) {
+                Ok(transforms) => transforms,
+                Err(_) => continue,
+            };
+
+            // The bone extends toward its first child bone.
+            let child_offset = children
+                .get(bone)
+                .ok()
+                .and_then(|bone_children| {
+                    bone_children
+                        .iter()
+                        .find(|child| skinned_mesh.joints.contains(child))
+                })
+                .and_then(|child| transforms.get(*child).ok())
+                .map(|(child_transform, _)| child_transform.translation)
+                .filter(|offset| *offset != Vect::ZERO);
+            let dir = child_offset.map(Vect::normalize).unwrap_or(Vect::Y);
+            let length = config
+                .length
+                .or_else(|| child_offset.map(Vect::length))
+                .unwrap_or(0.0);
+
+            let collider = if length > 0.0 {
+                Collider::capsule(Vect::ZERO, dir * length, config.radius)
+            } else {
+                Collider::ball(config.radius)
+            };
+            let rigid_body = match ragdoll.mode {
+                RagdollMode::Kinematic => RigidBody::KinematicPositionBased,
+                RagdollMode::Dynamic => RigidBody::Dynamic,
+            };
+
+            let mut bone_commands = commands.entity(bone);
+            bone_commands
+                .insert(rigid_body)
+                .insert(collider)
+                .insert(RagdollBone { mode: ragdoll.mode });
+
+            // Attach the bone to its closest ancestor that is part of the ragdoll.
+            let mut ancestor = parents.get(bone).ok().map(|parent| parent.get());
+            while let Some(entity) = ancestor {
+                if configs.contains_key(&entity) {
+                    break;
+                }
+                ancestor = parents.get(entity).ok().map(|parent| parent.get());
+            }
+
+            if let Some((parent_bone, (_, parent_global_transform))) =
+                ancestor.and_then(|entity| Some((entity, transforms.get(entity).ok()?)))
+            {
+                let (_, rotation1, translation1) =
+                    parent_global_transform.to_scale_rotation_translation();
+                let (_, rotation2, translation2) = global_transform.to_scale_rotation_translation();
+                let joint = config.joint.build(
+                    rotation1.inverse() * rotation2,
+                    rotation1.inverse() * (translation2 - translation1),
+                    dir,
+                );
+                bone_commands.insert(ImpulseJoint::new(parent_bone, joint));
+            }
+        }
+    }
+}
+
+/// System responsible for applying the mode of the [`Ragdoll`]s to all their bones.
+#[cfg(feature = "dim3")]
+pub fn apply_ragdoll_modes(
+    ragdolls: Query<(&Ragdoll, &SkinnedMesh), Changed<Ragdoll>>,
+    mut bones: Query<&mut RagdollBone>,
+) {
+    for (ragdoll, skinned_mesh) in ragdolls.iter() {
+        for bone in &skinned_mesh.joints {
+            if let Ok(mut bone) = bones.get_mut(*bone) {
+                if bone.mode != ragdoll.mode {
+                    bone.mode = ragdoll.mode;
+                }
+            }
+        }
+    }
+}
+
+/// System responsible for applying the mode of the [`RagdollBone`]s to their rigid-bodies.
+#[cfg(feature = "dim3")]
+pub fn apply_ragdoll_bone_modes(
+    mut bones: Query<(&RagdollBone, &mut RigidBody), Changed<RagdollBone>>,
+) {
+    for (bone, mut rigid_body) in bones.iter_mut() {
+        let new_rigid_body = match bone.mode {
+            RagdollMode::Kinematic => RigidBody::KinematicPositionBased,
+            RagdollMode::Dynamic => RigidBody::Dynamic,
+        };
+
+        if *rigid_body != new_rigid_body {
+            *rigid_body = new_rigid_body;
+        }
+    }
+}
+
+// Attaches an end of a rope through an impulse joint carried by a child of its end segment.
+#[cfg(feature = "dim3")]
+fn spawn_rope_attachment(
+    commands: &mut Commands,
+    rope: &Rope,
+    attachment: RopeAttachment,
+    segment: Entity,
+    start: bool,
+) -> Entity {
+    let joint = commands
+        .spawn()
+        .insert_bundle(TransformBundle::default())
+        .insert(ImpulseJoint::new(
+            attachment.entity,
+            rope.attachment_joint(attachment, start),
+        ))
+        .id();
+    commands.entity(segment).add_child(joint);
+    joint
+}
+
+/// System responsible for spawning the segments of the new [`Rope`]s.
+#[cfg(feature = "dim3")]
+pub fn init_ropes(
+    mut commands: Commands,
+    ropes: Query<(Entity, &Rope, &GlobalTransform), Without<RopeSegments>>,
+) {
+    for (entity, rope, global_transform) in ropes.iter() {
+        let (_, rotation, translation) = global_transform.to_scale_rotation_translation();
+        let segment_length = rope.segment_length();
+        let joint = rope.segment_joint();
+        let mut segments = Vec::with_capacity(rope.segments);
+
+        for i in 0..rope.segments {
+            let center = translation + rotation * Vect::Y * -(segment_length * (i as Real + 0.5));
+            let mut segment = commands.spawn();
+            segment
+                .insert_bundle(TransformBundle::from(
+                    Transform::from_translation(center).with_rotation(rotation),
+                ))
+                .insert(RigidBody::Dynamic)
+                .insert(rope.segment_collider())
+                .insert(RopeSegment {
+                    rope: entity,
+                    index: i,
+                });
+
+            if let Some(previous) = segments.last().copied() {
+                if rope.multibody {
+                    segment.insert(MultibodyJoint::new(previous, joint));
+                } else {
+                    segment.insert(ImpulseJoint::new(previous, joint));
+                }
+            }
+
+            segments.push(segment.id());
+        }
+
+        let start = rope
+            .start
+            .zip(segments.first().copied())
+            .map(|(attachment, segment)| {
+                let joint = spawn_rope_attachment(&mut commands, rope, attachment, segment, true);
+                (attachment, joint)
+            });
+        let end = rope
+            .end
+            .zip(segments.last().copied())
+            .map(|(attachment, segment)| {
+                let joint = spawn_rope_attachment(&mut commands, rope, attachment, segment, false);
+                (attachment, joint)
+            });
+
+        commands.entity(entity).insert(RopeSegments {
+            segments,
+            length: rope.length,
+            start,
+            end,
+        });
+    }
+}
+
+/// System responsible for applying the changes of length and attachments of the [`Rope`]s
+/// to their segments.
+#[cfg(feature = "dim3")]
+pub fn apply_rope_changes(
+    mut commands: Commands,
+    mut ropes: Query<(&Rope, &mut RopeSegments), Changed<Rope>>,
+    mut colliders: Query<&mut Collider, With<RopeSegment>>,
+    mut impulse_joints: Query<&mut ImpulseJoint>,
+    mut multibody_joints: Query<&mut MultibodyJoint, With<RopeSegment>>,
+) {
+    for (rope, mut state) in ropes.iter_mut() {
+        let state = &mut *state;
+
+        if state.length != rope.length {
+            state.length = rope.length;
+            let joint = rope.segment_joint();
+
+            for (i, segment) in state.segments.iter().enumerate() {
+                if let Ok(mut collider) = colliders.get_mut(*segment) {
+                    *collider = rope.segment_collider();
+                }
+
+                if i > 0 {
+                    if let Ok(mut segment_joint) = impulse_joints.get_mut(*segment) {
+                        segment_joint.data = joint;
+                    }
+                    if let Ok(mut segment_joint) = multibody_joints.get_mut(*segment) {
+                        segment_joint.data = joint;
+                    }
+                }
+            }
+
+            for (attachment, joint, start) in [
+                state
+                    .start
+                    .map(|(attachment, joint)| (attachment, joint, true)),
+                state
+                    .end
+                    .map(|(attachment, joint)| (attachment, joint, false)),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if let Ok(mut attachment_joint) = impulse_joints.get_mut(joint) {
+                    attachment_joint.data = rope.attachment_joint(attachment, start);
+                }
+            }
+        }
+
+        if state.start.map(|(attachment, _)| attachment) != rope.start {
+            if let Some((_, joint)) = state.start.take() {
+                commands.entity(joint).despawn_recursive();
+            }
+            state.start =
+                rope.start
+                    .zip(state.segments.first().copied())
+                    .map(|(attachment, segment)| {
+                        let joint =
+                            spawn_rope_attachment(&mut commands, rope, attachment, segment, true);
+                        (attachment, joint)
+                    });
+        }
+
+        if state.end.map(|(attachment, _)| attachment) != rope.end {
+            if let Some((_, joint)) = state.end.take() {
+                commands.entity(joint).despawn_recursive();
+            }
+            state.end =
+                rope.end
+                    .zip(state.segments.last().copied())
+                    .map(|(attachment, segment)| {
+                        let joint =
+                            spawn_rope_attachment(&mut commands, rope, attachment, segment, false);
+                        (attachment, joint)
+                    });
+        }
+    }
+}
+
+/// System responsible for regenerating the meshes of the [`Rope`]s with a [`RopeMesh`].
+#[cfg(feature = "dim3")]
+pub fn update_rope_meshes(
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    ropes: Query<(
+        &Rope,
+        &RopeSegments,
+        &RopeMesh,
+        &Handle<Mesh>,
+        &GlobalTransform,
+    )>,
+    segments: Query<&Transform, With<RopeSegment>>,
+) {
+    let mut meshes = match meshes {
+        Some(meshes) => meshes,
+        None => return,
+    };
+
+    for (rope, state, rope_mesh, handle, global_transform) in ropes.iter() {
+        let half_length = rope.segment_length() / 2.0;
+        let world_to_rope = global_transform.compute_matrix().inverse();
+        let transforms: Vec<_> = state
+            .segments
+            .iter()
+            .filter_map(|segment| segments.get(*segment).ok())
+            .collect();
+
+        // The tube goes through the top of the first segment, then the bottom of each segment.
+        let points: Vec<_> = transforms
+            .first()
+            .map(|transform| transform.mul_vec3(Vect::Y * half_length))
+            .into_iter()
+            .chain(
+                transforms
+                    .iter()
+                    .map(|transform| transform.mul_vec3(Vect::Y * -half_length)),
+            )
+            .map(|point| world_to_rope.transform_point3(point))
+            .collect();
+
+        meshes.set_untracked(handle, rope_mesh.mesh(&points, rope.radius));
+    }
+}
+
+/// System responsible for creating new Rapier colliders from the related `bevy_rapier` components.
+pub fn init_colliders(
+    mut commands: Commands,
+    config: Res<RapierConfiguration>,
+    mut context: ResMut<RapierContext>,
+    colliders: Query<ColliderComponents, Without<RapierColliderHandle>>,
+    mut rigid_body_mprops: Query<&mut ReadMassProperties>,
+    parent_query: Query<(&Parent, Option<&Transform>)>,
+) {
+    let context = &mut *context;
+    let physics_scale = context.physics_scale;
+
+    for (
+        entity,
+        shape,
+        sensor,
+        mprops,
+        active_events,
+        active_hooks,
+        active_collision_types,
+        friction,
+        restitution,
+        collision_groups,
+        solver_groups,
+        contact_force_event_threshold,
+    ) in colliders.iter()
+    {
+        let mut scaled_shape = shape.clone();
+        scaled_shape.set_scale(shape.scale / physics_scale, config.scaled_shape_subdivision);
+        let mut builder = ColliderBuilder::new(scaled_shape.raw.clone());
+
+        builder = builder.sensor(sensor.is_some());
+
+        if let Some(mprops) = mprops {
+            builder = match mprops {
+                ColliderMassProperties::Density(density) => builder.density(*density),
+                ColliderMassProperties::Mass(mass) => builder.mass(*mass),
+                ColliderMassProperties::MassProperties(mprops) => {
+                    builder.mass_properties(mprops.into_rapier(physics_scale))
+                }
+            };
+        }
+
+        if let Some(active_events) = active_events {
+            builder = builder.active_events((*active_events).into());
+        }
+
+        if let Some(active_hooks) = active_hooks {
+            builder = builder.active_hooks((*active_hooks).into());
+        }
+
+        if let Some(active_collision_types) = active_collision_types {
+            builder = builder.active_collision_types((*active_collision_types).into());
+        }
+
+        if let Some(friction) = friction {
+            builder = builder
+                .friction(friction.coefficient)
+                .friction_combine_rule(friction.combine_rule.into());
+        }
+
+        if let Some(restitution) = restitution {
+            builder = builder
+                .restitution(restitution.coefficient)
+                .restitution_combine_rule(restitution.combine_rule.into());
+        }
+
+        if let Some(collision_groups) = collision_groups {
+            builder = builder.collision_groups((*collision_groups).into());
+        }
+
+        if let Some(solver_groups) = solver_groups {
+            builder = builder.solver_groups((*solver_groups).into());
+        }
+
+        if let Some(threshold) = contact_force_event_threshold {
+            builder = builder.contact_force_event_threshold(threshold.0);
+        }
+
+        let mut body_entity = entity;
+        let mut body_handle = context.entity2body.get(&body_entity).copied();
+        let mut child_transform = Transform::default();
+        while body_handle.is_none() {
+            if let Ok((parent_entity, transform)) = parent_query.get(body_entity) {
+                if let Some(transform) = transform {
+                    child_transform = *transform * child_transform;
+                }
+                body_entity = parent_entity.get();
+            } else {
+                break;
+            }
+
+            body_handle = context.entity2body.get(&body_entity).copied();
+        }
+
+        builder = builder.position(utils::transform_to_iso(&child_transform, physics_scale));
+        builder = builder.user_data(entity.to_bits() as u128);
+
+        let handle = if let Some(body_handle) = body_handle {
+            let handle =
+                context
+                    .colliders
+                    .insert_with_parent(builder, body_handle, &mut context.bodies);
+            if let Ok(mut mprops) = rigid_body_mprops.get_mut(body_entity) {
+                // Inserting the collider changed the rigid-body’s mass properties.
+                // Read them back from the engine.
+                if let Some(parent_body) = context.bodies.get(body_handle) {
+                    mprops.0 =
+                        MassProperties::from_rapier(*parent_body.mass_properties(), physics_scale);
+                }
+            }
+            handle
+        } else {
+            context.colliders.insert(builder)
+        };
+
+        let active_events = active_events.copied().unwrap_or_default();
+        if active_events.contains(ActiveEvents::COLLISION_EVENT_CONTACTS) {
+            context.collision_event_contacts.insert(handle);
+        }
+        if active_events.contains(ActiveEvents::CCD_IMPACTS) {
+            context.ccd_impact_colliders.insert(handle);
+        }
+
+        commands.entity(entity).insert(RapierColliderHandle(handle));
+        context.entity2collider.insert(entity, handle);
+    }
+}
+
+/// System responsible for creating new Rapier rigid-bodies from the related `bevy_rapier` components.
+pub fn init_rigid_bodies(
+    mut commands: Commands,
+    mut context: ResMut<RapierContext>,
+    rigid_bodies: Query<RigidBodyComponents, Without<RapierRigidBodyHandle>>,
+) {
+    let physics_scale = context.physics_scale;
+
+    for (
+        entity,
+        rb,
+        transform,
+        vel,
+        additional_mass_props,
+        _mass_props,
+        locked_axes,
+        force,
+        gravity_scale,
+        ccd,
+        dominance,
+        sleep,
+        damping,
+    ) in rigid_bodies.iter()
+    {
+        let mut builder = RigidBodyBuilder::new((*rb).into());
+        if let Some(transform) = transform {
+            builder = builder.position(utils::transform_to_iso(
+                &transform.compute_transform(),
+                physics_scale,
+            ));
+        }
+
+        #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
+        if let Some(vel) = vel {
+            builder = builder
+                .linvel((vel.linvel / physics_scale).into())
+                .angvel(vel.angvel.into());
+        }
+
+        if let Some(locked_axes) = locked_axes {
+            builder = builder.locked_axes((*locked_axes).into())
+        }
+
+        if let Some(gravity_scale) = gravity_scale {
+            builder = builder.gravity_scale(gravity_scale.0);
+        }
+
+        if let Some(ccd) = ccd {
+            builder = builder.ccd_enabled(ccd.enabled)
+        }
+
+        if let Some(dominance) = dominance {
+            builder = builder.dominance_group(dominance.groups)
+        }
+
+        if let Some(sleep) = sleep {
+            builder = builder.sleeping(sleep.sleeping);
+        }
+
+        if let Some(damping) = damping {
+            builder = builder
+                .linear_damping(damping.linear_damping)
+                .angular_damping(damping.angular_damping);
+        }
+
+        if let Some(mprops) = additional_mass_props {
+            builder = match mprops {
+                AdditionalMassProperties::MassProperties(mprops) => {
+                    builder.additional_mass_properties(mprops.into_rapier(physics_scale))
+                }
+                AdditionalMassProperties::Mass(mass) => builder.additional_mass(*mass),
+            };
+        }
+
+        builder = builder.user_data(entity.to_bits() as u128);
+
+        let mut rb = builder.build();
+
+        #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
+        if let Some(force) = force {
+            rb.add_force((force.force / physics_scale).into(), false);
+            rb.add_torque(force.torque.into(), false);
+        }
+
+        // NOTE: we can’t apply impulses yet at this point because
+        //       the rigid-body’s mass isn’t up-to-date yet (its
+        //       attached colliders, if any, haven’t been created yet).
+
+        if let Some(sleep) = sleep {
+            let activation = rb.activation_mut();
+            activation.linear_threshold = sleep.linear_threshold;
+            activation.angular_threshold = sleep.angular_threshold;
+        }
+
+        let handle = context.bodies.insert(rb);
+        commands
+            .entity(entity)
+            .insert(RapierRigidBodyHandle(handle));
+        context.entity2body.insert(entity, handle);
+
+        if let Some(transform) = transform {
+            context.last_body_transform_set.insert(handle, *transform);
+        }
+    }
+}
+
+/// This applies the initial impulse given to a rigid-body when it is created.
+///
+/// This cannot be done inside `init_rigid_bodies` because impulses require the rigid-body
+/// mass to be available, which it was not because colliders were not created yet. As a
+/// result, we run this system after the collider creation.
+pub fn apply_initial_rigid_body_impulses(
+    mut context: ResMut<RapierContext>,
+    // We can’t use RapierRigidBodyHandle yet because its creation command hasn’t been
+    // executed yet.
+    mut init_impulses: Query<(Entity, &mut ExternalImpulse), Without<RapierRigidBodyHandle>>,
+) {
+    let context = &mut *context;
+    let scale = context.physics_scale;
+
+    for (entity, mut impulse) in init_impulses.iter_mut() {
+        let bodies = &mut context.bodies;
+        if let Some(rb) = context
+            .entity2body
+            .get(&entity)
+            .and_then(|h| bodies.get_mut(*h))
+        {
+            // Make sure the mass-properties are computed.
+            rb.recompute_mass_properties_from_colliders(&context.colliders);
+            // Apply the impulse.
+            rb.apply_impulse((impulse.impulse / scale).into(), false);
+
+            #[allow(clippy::useless_conversion)] // Need to convert if dim3 enabled
+            rb.apply_torque_impulse(impulse.torque_impulse.into(), false);
+
+            impulse.reset();
+        }
+    }
+}
+
+/// System responsible for replacing the global gravity of rigid-bodies with a [`RadialGravity`]
+/// by a force pulling them toward their attractor.
+pub fn apply_radial_gravity(
+    mut context: ResMut<RapierContext>,
+    radial_gravities: Query<(Entity, &RadialGravity, ChangeTrackers<RadialGravity>)>,
+    removed_radial_gravities: RemovedComponents<RadialGravity>,
+    gravity_scales: Query<&GravityScale>,
+    global_transforms: Query<(&GlobalTransform, ChangeTrackers<GlobalTransform>)>,
+) {
+    let context = &mut *context;
+    let scale = context.physics_scale;
+
+    for entity in removed_radial_gravities.iter() {
+        if let Some(handle) = context.entity2body.get(&entity) {
+            if let Some(rb) = context.bodies.get_mut(*handle) {
+                if let Some(prev_force) = context.radial_gravity_forces.remove(handle) {
+                    rb.add_force((-prev_force).into(), true);
+                }
+
+                let gravity_scale = gravity_scales.get(entity).map(|s| s.0).unwrap_or(1.0);
+                rb.set_gravity_scale(gravity_scale, true);
+            }
+        }
+    }
+
+    for (entity, gravity, change_tracker) in radial_gravities.iter() {
+        let handle = match context.entity2body.get(&entity) {
+            Some(handle) => *handle,
+            None => continue,
+        };
+        let rb = match context.bodies.get_mut(handle) {
+            Some(rb) => rb,
+            None => continue,
+        };
+        let center = global_transforms.get(gravity.center);
+        // Wake the rigid-body up if its attractor moved.
+        let wake_up = change_tracker.is_changed()
+            || matches!(&center, Ok((_, center_tracker)) if center_tracker.is_changed());
+
+        if rb.gravity_scale() != 0.0 {
+            rb.set_gravity_scale(0.0, wake_up);
+        }
+
+        let center = center.map(|(t, _)| t.translation() / scale);
+        let world_com = rb.position() * rb.mass_properties().local_com;
+        #[cfg(feature = "dim2")]
+        let dir = center.map(|c| c.xy() - Vect::from(world_com));
+        #[cfg(feature = "dim3")]
+        let dir = center.map(|c| c - Vect::from(world_com));
+
+        let new_force = dir
+            .ok()
+            .and_then(|dir| dir.try_normalize())
+            .map(|dir| dir * (rb.mass() * gravity.strength / scale))
+            .unwrap_or(Vect::ZERO);
+        let prev_force = context
+            .radial_gravity_forces
+            .insert(handle, new_force)
+            .unwrap_or(Vect::ZERO);
+
+        if new_force != prev_force {
+            rb.add_force((new_force - prev_force).into(), wake_up);
+        }
+    }
+}
+
+/// System responsible for welding and unwelding rigid-bodies according to their [`Weld`] component.
+pub fn apply_welds(
+    mut context: ResMut<RapierContext>,
+    welds: Query<(Entity, &RapierRigidBodyHandle, &Weld)>,
+) {
+    let context = &mut *context;
+
+    // Split the rigid-bodies that are no longer welded to the same target.
+    let to_unweld: Vec<_> = context
+        .welded_bodies
+        .iter()
+        .filter(|(entity, welded)| {
+            welds.get(**entity).ok().map(|(_, _, weld)| weld.0) != Some(welded.target)
+                || !context.bodies.contains(welded.target_handle)
+        })
+        .map(|(entity, _)| *entity)
+        .collect();
+
+    for entity in to_unweld {
+        split_welded_body(context, entity);
+    }
+
+    for (entity, handle, weld) in welds.iter() {
+        if context.welded_bodies.contains_key(&entity) {
+            continue;
+        }
+
+        // Weld onto the compound the target is part of, if it is itself welded.
+        let mut target = weld.0;
+        while let Some(welded) = context.welded_bodies.get(&target) {
+            target = welded.target;
+        }
+
+        let target_handle = match context.entity2body.get(&target) {
+            Some(target_handle) if target != entity => *target_handle,
+            _ => continue,
+        };
+
+        if let Some(welded) = weld_body(context, handle.0, weld.0, target_handle) {
+            context.welded_bodies.insert(entity, welded);
+        }
+    }
+
+    // The colliders added to a welded entity after it was welded are attached to its own
+    // rigid-body, move them to the compound too.
+    let welded_entities: Vec<_> = context.welded_bodies.keys().copied().collect();
+    for entity in welded_entities {
+        let late_colliders = match context
+            .entity2body
+            .get(&entity)
+            .and_then(|h| context.bodies.get(*h))
+        {
+            Some(rb) if !rb.colliders().is_empty() => rb.colliders().to_vec(),
+            _ => continue,
+        };
+
+        let welded = context.welded_bodies.get_mut(&entity).unwrap();
+        attach_colliders(
+            &mut context.colliders,
+            &mut context.bodies,
+            &late_colliders,
+            welded.relative_pos,
+            welded.target_handle,
+        );
+        welded.colliders.extend(late_colliders);
+
+        for handle in [context.entity2body[&entity], welded.target_handle] {
+            if let Some(rb) = context.bodies.get_mut(handle) {
+                rb.recompute_mass_properties_from_colliders(&context.colliders);
+                rb.wake_up(true);
+            }
+        }
+    }
+}
+
+// Splits a welded rigid-body from its compound, along with the rigid-bodies welded onto it
+// which are welded again onto it by `apply_welds`.
+fn split_welded_body(context: &mut RapierContext, entity: Entity) {
+    let dependents: Vec<_> = context
+        .welded_bodies
+        .iter()
+        .filter(|(_, welded)| welded.target == entity)
+        .map(|(entity, _)| *entity)
+        .collect();
+    for dependent in dependents {
+        split_welded_body(context, dependent);
+    }
+
+    if let Some(welded) = context.welded_bodies.remove(&entity) {
+        match context.entity2body.get(&entity) {
+            Some(handle) => unweld_body(context, *handle, welded),
+            None => {
+                // The welded rigid-body no longer exists, give its colliders back to
+                // the entity owning them, if any.
+                for handle in welded.colliders {
+                    let parent = context
+                        .collider_entity(handle)
+                        .and_then(|e| context.entity2body.get(&e).copied());
+                    context
+                        .colliders
+                        .set_parent(handle, parent, &mut context.bodies);
+                }
+            }
+        }
+    }
+}
+
+// Moves the colliders to the rigid-body `target_handle`, `relative_pos` being the position
+// of their current rigid-body relative to it.
+fn attach_colliders(
+    colliders: &mut ColliderSet,
+    bodies: &mut RigidBodySet,
+    handles: &[ColliderHandle],
+    relative_pos: Isometry<Real>,
+    target_handle: RigidBodyHandle,
+) {
+    for collider in handles {
+        if let Some(co) = colliders.get_mut(*collider) {
+            let local_pos = co.position_wrt_parent().copied().unwrap_or_default();
+            co.set_position_wrt_parent(relative_pos * local_pos);
+        }
+        colliders.set_parent(*collider, Some(target_handle), bodies);
+    }
+}
+
+fn weld_body(
+    context: &mut RapierContext,
+    handle: RigidBodyHandle,
+    target: Entity,
+    target_handle: RigidBodyHandle,
+) -> Option<WeldedBody> {
+    let (target_pos, target_mass, target_linvel) = context
+        .bodies
+        .get(target_handle)
+        .map(|rb| (*rb.position(), rb.mass(), *rb.linvel()))?;
+    let rb = context.bodies.get_mut(handle)?;
+    let relative_pos = target_pos.inv_mul(rb.position());
+    let all_colliders = rb.colliders().to_vec();
+    let body_type = rb.body_type();
+    let (mass, linvel) = (rb.mass(), *rb.linvel());
+    rb.set_body_type(RigidBodyType::KinematicVelocityBased);
+
+    // The rigid-bodies welded onto this one become part of the new compound, along with
+    // their colliders.
+    let mut carried_colliders = vec![];
+    for welded in context.welded_bodies.values_mut() {
+        if welded.target_handle == handle {
+            welded.target_handle = target_handle;
+            welded.relative_pos = relative_pos * welded.relative_pos;
+            carried_colliders.extend(welded.colliders.iter().copied());
+        }
+    }
+
+    attach_colliders(
+        &mut context.colliders,
+        &mut context.bodies,
+        &all_colliders,
+        relative_pos,
+        target_handle,
+    );
+
+    if let Some(target_rb) = context.bodies.get_mut(target_handle) {
+        target_rb.recompute_mass_properties_from_colliders(&context.colliders);
+
+        // Preserve the linear momentum of the compound.
+        if body_type == RigidBodyType::Dynamic && target_rb.is_dynamic() && mass + target_mass > 0.0
+        {
+            let new_linvel = (target_linvel * target_mass + linvel * mass) / (mass + target_mass);
+            target_rb.set_linvel(new_linvel, true);
+        }
+        target_rb.wake_up(true);
+    }
+
+    if let Some(rb) = context.bodies.get_mut(handle) {
+        rb.recompute_mass_properties_from_colliders(&context.colliders);
+    }
+
+    Some(WeldedBody {
+        target,
+        target_handle,
+        relative_pos,
+        colliders: all_colliders
+            .into_iter()
+            .filter(|collider| !carried_colliders.contains(collider))
+            .collect(),
+        body_type,
+    })
+}
+
+fn unweld_body(context: &mut RapierContext, handle: RigidBodyHandle, welded: WeldedBody) {
+    let pos = match context.bodies.get(handle) {
+        Some(rb) => *rb.position(),
+        None => return,
+    };
+
+    for collider in &welded.colliders {
+        let world_pos = match context.colliders.get(*collider) {
+            Some(co) => *co.position(),
+            None => continue,
+        };
+        context
+            .colliders
+            .set_parent(*collider, Some(handle), &mut context.bodies);
+        if let Some(co) = context.colliders.get_mut(*collider) {
+            co.set_position_wrt_parent(pos.inv_mul(&world_pos));
+        }
+    }
+
+    let target_vels = context
+        .bodies
+        .get_mut(welded.target_handle)
+        .map(|target_rb| {
+            target_rb.recompute_mass_properties_from_colliders(&context.colliders);
+            target_rb.wake_up(true);
+            (
+                target_rb.velocity_at_point(&pos.translation.vector.into()),
+                rigid_body_angvel(target_rb),
+            )
+        });
+
+    if let Some(rb) = context.bodies.get_mut(handle) {
+        rb.set_body_type(welded.body_type);
+        rb.recompute_mass_properties_from_colliders(&context.colliders);
+        if let Some((linvel, angvel)) = target_vels {
+            if welded.body_type != RigidBodyType::Fixed {
+                rb.set_linvel(linvel, true);
+                rb.set_angvel(angvel, true);
+            }
+        }
+        rb.wake_up(true);
+    }
+}
+
+#[cfg(feature = "dim2")]
+fn rigid_body_angvel(rb: &rapier::dynamics::RigidBody) -> AngVector<Real> {
+    rb.angvel()
+}
+
+#[cfg(feature = "dim3")]
+fn rigid_body_angvel(rb: &rapier::dynamics::RigidBody) -> AngVector<Real> {
+    *rb.angvel()
+}
+
+/// System responsible for moving the rigid-bodies welded by a [`Weld`] along with
+/// the rigid-body they are welded onto.
+pub fn sync_welded_bodies(mut context: ResMut<RapierContext>) {
+    let context = &mut *context;
+
+    for (entity, welded) in &context.welded_bodies {
+        let (target_pos, linvel, angvel) = match context.bodies.get(welded.target_handle) {
+            Some(target_rb) => {
+                let pos = *target_rb.position() * welded.relative_pos;
+                (
+                    pos,
+                    target_rb.velocity_at_point(&pos.translation.vector.into()),
+                    rigid_body_angvel(target_rb),
+                )
+            }
+            None => continue,
+        };
+
+        if let Some(rb) = context
+            .entity2body
+            .get(entity)
+            .and_then(|h| context.bodies.get_mut(*h))
+        {
+            rb.set_position(target_pos, false);
+            rb.set_linvel(linvel, false);
+            rb.set_angvel(angvel, false);
+        }
+    }
+}
+
+/// System responsible for associating the entities again with the rigid-bodies, colliders and
+/// joints of a [`PhysicsSnapshot`] restored with [`RapierContext::restore`].
+///
+/// The handle components of the entities are updated, and the rigid-bodies, colliders and
+/// joints of the entities that no longer exist are removed.
+pub fn sync_restored_snapshot(
+    mut commands: Commands,
+    mut context: ResMut<RapierContext>,
+    entities: &Entities,
+    bodies: Query<(Entity, &RapierRigidBodyHandle)>,
+    colliders: Query<(Entity, &RapierColliderHandle)>,
+    impulse_joints: Query<(Entity, &RapierImpulseJointHandle)>,
+    multibody_joints: Query<(Entity, &RapierMultibodyJointHandle)>,
+) {
+    if !context.restored {
+        return;
+    }
+
+    let context = &mut *context;
+    context.restored = false;
+
+    // Updates the handle components of the entities, returning the handles of the entities
+    // that no longer exist.
+    fn sync_handles<C: Component, H: Copy + PartialEq>(
+        commands: &mut Commands,
+        entities: &Entities,
+        handles: &HashMap<Entity, H>,
+        components: &Query<(Entity, &C)>,
+        handle: impl Fn(&C) -> H,
+        component: impl Fn(H) -> C,
+    ) -> Vec<H> {
+        for (entity, current) in components.iter() {
+            match handles.get(&entity) {
+                Some(h) if *h != handle(current) => {
+                    commands.entity(entity).insert(component(*h));
+                }
+                Some(_) => {}
+                None => {
+                    commands.entity(entity).remove::<C>();
+                }
+            }
+        }
+
+        let mut dead = vec![];
+        for (entity, h) in handles {
+            if !entities.contains(*entity) {
+                dead.push(*h);
+            } else if !components.contains(*entity) {
+                commands.entity(*entity).insert(component(*h));
+            }
+        }
+        dead
+    }
+
+    let dead_impulse_joints = sync_handles(
+        &mut commands,
+        entities,
+        &context.entity2impulse_joint,
+        &impulse_joints,
+        |h| h.0,
+        RapierImpulseJointHandle,
+    );
+    let dead_multibody_joints = sync_handles(
+        &mut commands,
+        entities,
+        &context.entity2multibody_joint,
+        &multibody_joints,
+        |h| h.0,
+        RapierMultibodyJointHandle,
+    );
+    let dead_colliders = sync_handles(
+        &mut commands,
+        entities,
+        &context.entity2collider,
+        &colliders,
+        |h| h.0,
+        RapierColliderHandle,
+    );
+    let dead_bodies = sync_handles(
+        &mut commands,
+        entities,
+        &context.entity2body,
+        &bodies,
+        |h| h.0,
+        RapierRigidBodyHandle,
+    );
+
+    for handle in dead_impulse_joints {
+        context.impulse_joints.remove(handle, true);
+    }
+    for handle in dead_multibody_joints {
+        context.multibody_joints.remove(handle, true);
+    }
+    for handle in dead_colliders {
+        context
+            .colliders
+            .remove(handle, &mut context.islands, &mut context.bodies, true);
+    }
+    for handle in dead_bodies {
+        context.bodies.remove(
+            handle,
+            &mut context.islands,
+            &mut context.colliders,
+            &mut context.impulse_joints,
+            &mut context.multibody_joints,
+            true,
+        );
+    }
+
+    context
+        .entity2body
+        .retain(|_, h| context.bodies.contains(*h));
+    context
+        .entity2collider
+        .retain(|_, h| context.colliders.contains(*h));
+    context
+        .entity2impulse_joint
+        .retain(|_, h| context.impulse_joints.contains(*h));
+    context
+        .entity2multibody_joint
+        .retain(|_, h| context.multibody_joints.get(*h).is_some());
+}
+
+/// System responsible for restoring the physics world from the [`PhysicsRollbackState`] loaded
+/// by GGRS during a rollback.
+///
+/// The physics world is restored if the state was taken at another simulation step than the
+/// current one.
+#[cfg(feature = "ggrs")]
+pub fn load_rollback_state(
+    mut context: ResMut<RapierContext>,
+    state: Res<PhysicsRollbackState>,
+    mut velocities: Query<(Entity, &mut Velocity)>,
+) {
+    if state.snapshot.step_index == context.step_index {
+        return;
+    }
+
+    context.restore(state.snapshot.clone());
+
+    // The velocities are written back like after a simulation step, so that the velocities of
+    // a later step aren’t applied to the restored rigid-bodies.
+    let scale = context.physics_scale;
+    for (entity, mut velocity) in velocities.iter_mut() {
+        if let Some(rb) = context
+            .entity2body
+            .get(&entity)
+            .and_then(|handle| context.bodies.get(*handle))
+        {
+            let new_vel = body_velocity(rb, scale);
+            if *velocity != new_vel {
+                *velocity = new_vel;
+            }
+        }
+    }
+}
+
+/// System responsible for saving the physics world into the [`PhysicsRollbackState`] after
+/// each simulation step.
+#[cfg(feature = "ggrs")]
+pub fn save_rollback_state(context: Res<RapierContext>, mut state: ResMut<PhysicsRollbackState>) {
+    if state.snapshot.step_index != context.step_index {
+        *state = context.snapshot().into();
+    }
+}
+
+/// System responsible for creating new Rapier joints from the related `bevy_rapier` components.
+pub fn init_joints(
+    mut commands: Commands,
+    mut context: ResMut<RapierContext>,
+    impulse_joints: Query<
+        (Entity, &ImpulseJoint, Option<&JointEnabled>),
+        Without<RapierImpulseJointHandle>,
+    >,
+    multibody_joints: Query<(Entity, &MultibodyJoint), Without<RapierMultibodyJointHandle>>,
+    parent_query: Query<&Parent>,
+) {
+    let context = &mut *context;
+    let scale = context.physics_scale;
+
+    for (entity, joint, enabled) in impulse_joints.iter() {
+        let mut target = None;
+        let mut body_entity = entity;
+        while target.is_none() {
+            target = context.entity2body.get(&body_entity).copied();
+            if let Ok(parent_entity) = parent_query.get(body_entity) {
+                body_entity = parent_entity.get();
+            } else {
+                break;
+            }
+        }
+
+        let source = match joint.parent {
+            JointParent::World => Some(context.world_body()),
+            JointParent::Entity(parent) => context.entity2body.get(&parent).copied(),
+        };
+
+        if let (Some(target), Some(source)) = (target, source) {
+            let handle = context.impulse_joints.insert(
+                source,
+                target,
+                impulse_joint_data(joint, enabled, scale),
+                true,
+            );
+            commands
+                .entity(entity)
+                .insert(RapierImpulseJointHandle(handle));
+            context.entity2impulse_joint.insert(entity, handle);
+        }
+    }
+
+    for (entity, joint) in multibody_joints.iter() {
+        let target = context.entity2body.get(&entity);
+
+        if let (Some(target), Some(source)) = (target, context.entity2body.get(&joint.parent)) {
+            if let Some(handle) = context.multibody_joints.insert(
+                *source,
+                *target,
+                multibody_joint_data(joint, scale),
+                true,
+            ) {
+                set_multibody_joint_damping(&mut context.multibody_joints, handle, joint);
+                commands
+                    .entity(entity)
+                    .insert(RapierMultibodyJointHandle(handle));
+                context.entity2multibody_joint.insert(entity, handle);
+            } else {
+                error!("Failed to create multibody joint: loop detected.")
+            }
+        }
+    }
+}
+
+/// System responsible for turning the [`PendingJoint`]s into joints once the entity they refer
+/// to is found.
+pub fn resolve_pending_joints(
+    mut commands: Commands,
+    pending_joints: Query<(Entity, &PendingJoint)>,
+    parents: Query<&Parent>,
+    children: Query<&Children>,
+    names: Query<&Name>,
+) {
+    let has_name = |entity: Entity, name: &str| {
+        names
+            .get(entity)
+            .map(|entity_name| entity_name.as_str() == name)
+            .unwrap_or(false)
+    };
+
+    for (entity, pending_joint) in pending_joints.iter() {
+        let mut root = entity;
+        while let Ok(parent) = parents.get(root) {
+            root = parent.get();
+        }
+
+        let mut candidates = vec![root];
+        traverse_descendants(root, &children, &mut |child| candidates.push(child));
+
+        let other = candidates
+            .into_iter()
+            .find_map(|candidate| match &pending_joint.other {
+                EntityPathOrName::Name(name) => has_name(candidate, name).then_some(candidate),
+                EntityPathOrName::Path(path) => {
+                    let (first, rest) = path.split_first()?;
+                    if !has_name(candidate, first) {
+                        return None;
+                    }
+
+                    rest.iter().try_fold(candidate, |current, name| {
+                        children
+                            .get(current)
+                            .ok()?
+                            .iter()
+                            .copied()
+                            .find(|child| has_name(*child, name))
+                    })
+                }
+            });
+
+        if let Some(other) = other {
+            let mut entity_commands = commands.entity(entity);
+            entity_commands.remove::<PendingJoint>();
+
+            if pending_joint.multibody {
+                entity_commands.insert(MultibodyJoint::new(other, pending_joint.data));
+            } else {
+                entity_commands.insert(ImpulseJoint::new(other, pending_joint.data));
+            }
+        }
+    }
+}
+
+/// System responsible for removing from Rapier the rigid-bodies/colliders/joints which had
+/// their related `bevy_rapier` components removed by the user (through component removal or
+/// despawn).
+pub fn sync_removals(
+    mut commands: Commands,
+    mut context: ResMut<RapierContext>,
+    removed_bodies: RemovedComponents<RapierRigidBodyHandle>,
+    removed_colliders: RemovedComponents<RapierColliderHandle>,
+    removed_impulse_joints: RemovedComponents<RapierImpulseJointHandle>,
+    removed_multibody_joints: RemovedComponents<RapierMultibodyJointHandle>,
+    orphan_bodies: Query<Entity, (With<RapierRigidBodyHandle>, Without<RigidBody>)>,
+    orphan_colliders: Query<Entity, (With<RapierColliderHandle>, Without<Collider>)>,
+    orphan_impulse_joints: Query<Entity, (With<RapierImpulseJointHandle>, Without<ImpulseJoint>)>,
+    orphan_multibody_joints: Query<
+        Entity,
+        (With<RapierMultibodyJointHandle>, Without<MultibodyJoint>),
+    >,
+
+    removed_sensors: RemovedComponents<Sensor>,
+    (mut body_removed, mut collider_removed, mut joint_removed): (
+        EventWriter<BodyRemoved>,
+        EventWriter<ColliderRemoved>,
+        EventWriter<JointRemoved>,
+    ),
+) {
+    /*
+     * Rigid-bodies removal detection.
+     */
+    let context = &mut *context;
+    for entity in removed_bodies.iter() {
+        if let Some(handle) = context.entity2body.remove(&entity) {
+            let _ = context.last_body_transform_set.remove(&handle);
+            let _ = context.radial_gravity_forces.remove(&handle);
+            let _ = context.kinematic_position_histories.remove(&handle);
+            if let Some(body) = context.bodies.remove(
+                handle,
+                &mut context.islands,
+                &mut context.colliders,
+                &mut context.impulse_joints,
+                &mut context.multibody_joints,
+                false,
+            ) {
+                body_removed.send(BodyRemoved {
+                    entity,
+                    transform: utils::iso_to_transform(body.position(), context.physics_scale),
+                });
+            }
+        }
+    }
+
+    let context = &mut *context;
+    for entity in orphan_bodies.iter() {
+        if let Some(handle) = context.entity2body.remove(&entity) {
+            let _ = context.last_body_transform_set.remove(&handle);
+            let _ = context.radial_gravity_forces.remove(&handle);
+            let _ = context.kinematic_position_histories.remove(&handle);
+            if let Some(body) = context.bodies.remove(
+                handle,
+                &mut context.islands,
+                &mut context.colliders,
+                &mut context.impulse_joints,
+                &mut context.multibody_joints,
+                false,
+            ) {
+                body_removed.send(BodyRemoved {
+                    entity,
+                    transform: utils::iso_to_transform(body.position(), context.physics_scale),
+                });
+            }
+        }
+        commands.entity(entity).remove::<RapierRigidBodyHandle>();
+    }
+
+    /*
+     * Collider removal detection.
+     */
+    for entity in removed_colliders.iter() {
+        if let Some(handle) = context.entity2collider.remove(&entity) {
+            let collider =
+                context
+                    .colliders
+                    .remove(handle, &mut context.islands, &mut context.bodies, true);
+            if let Some(co) = collider {
+                if co.is_sensor() {
+                    context.deleted_sensors.insert(handle);
+                }
+                collider_removed.send(ColliderRemoved {
+                    entity,
+                    transform: utils::iso_to_transform(co.position(), context.physics_scale),
+                });
+            }
+            context.deleted_colliders.insert(handle, entity);
+            context.collision_event_contacts.remove(&handle);
+            context.ccd_impact_colliders.remove(&handle);
+            context.collision_event_filters.remove(&handle);
+        }
+    }
+
+    for entity in orphan_colliders.iter() {
+        if let Some(handle) = context.entity2collider.remove(&entity) {
+            let collider =
+                context
+                    .colliders
+                    .remove(handle, &mut context.islands, &mut context.bodies, true);
+            if let Some(co) = collider {
+                if co.is_sensor() {
+                    context.deleted_sensors.insert(handle);
+                }
+                collider_removed.send(ColliderRemoved {
+                    entity,
+                    transform: utils::iso_to_transform(co.position(), context.physics_scale),
+                });
+            }
+            context.deleted_colliders.insert(handle, entity);
+            context.collision_event_contacts.remove(&handle);
+            context.ccd_impact_colliders.remove(&handle);
+            context.collision_event_filters.remove(&handle);
+        }
+        commands.entity(entity).remove::<RapierColliderHandle>();
+    }
+
+    /*
+     * Impulse joint removal detection.
+     */
+    for entity in removed_impulse_joints.iter() {
+        if let Some(handle) = context.entity2impulse_joint.remove(&entity) {
+            context.impulse_joints.remove(handle, true);
+            joint_removed.send(JointRemoved { entity });
+        }
+    }
+
+    for entity in orphan_impulse_joints.iter() {
+        if let Some(handle) = context.entity2impulse_joint.remove(&entity) {
+            context.impulse_joints.remove(handle, true);
+            joint_removed.send(JointRemoved { entity });
+        }
+        commands.entity(entity).remove::<RapierImpulseJointHandle>();
+    }
+
+    /*
+     * Multibody joint removal detection.
+     */
+    for entity in removed_multibody_joints.iter() {
+        if let Some(handle) = context.entity2multibody_joint.remove(&entity) {
+            context.multibody_joints.remove(handle, true);
+            joint_removed.send(JointRemoved { entity });
+        }
+    }
+
+    for entity in orphan_multibody_joints.iter() {
+        if let Some(handle) = context.entity2multibody_joint.remove(&entity) {
+            context.multibody_joints.remove(handle, true);
+            joint_removed.send(JointRemoved { entity });
+        }
+        commands
+            .entity(entity)
+            .remove::<RapierMultibodyJointHandle>();
+    }
+
+    /*
+     * Sensor marker component removal detection.
+     */
+    for entity in removed_sensors.iter() {
+        if let Some(handle) = context.entity2collider.get(&entity) {
+            if let Some(co) = context.colliders.get_mut(*handle) {
+                co.set_sensor(false);
+            }
+        }
+    }
+
+    // TODO: update mass props after collider removal.
+    // TODO: what about removing forces?
+}
+
+/// The world-space transform of an entity, taken from its rigid-body if it has one.
+fn physics_transform(
+    context: &RapierContext,
+    entity: Entity,
+    global_transform: Option<&GlobalTransform>,
+) -> Transform {
+    context
+        .entity2body()
+        .get(&entity)
+        .and_then(|handle| context.bodies.get(*handle))
+        .map(|body| utils::iso_to_transform(body.position(), context.physics_scale()))
+        .or_else(|| global_transform.map(|transform| transform.compute_transform()))
+        .unwrap_or_default()
+}
+
+/// System responsible for casting the rays of the [`RayCaster`]s after each simulation step, and
+/// writing their hits into the [`RayHits`] components.
+pub fn update_ray_casters(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    mut casters: Query<(
+        Entity,
+        &RayCaster,
+        Option<&GlobalTransform>,
+        Option<&mut RayHits>,
+    )>,
+) {
+    for (entity, caster, global_transform, hits) in casters.iter_mut() {
+        let transform = physics_transform(&context, entity, global_transform);
+        #[cfg(feature = "dim2")]
+        let (origin, direction) = (
+            transform.mul_vec3(caster.origin.extend(0.0)).truncate(),
+            (transform.rotation * caster.direction.extend(0.0)).truncate(),
+        );
+        #[cfg(feature = "dim3")]
+        let (origin, direction) = (
+            transform.mul_vec3(caster.origin),
+            transform.rotation * caster.direction,
+        );
+
+        let filter = crate::pipeline::QueryFilter {
+            flags: caster.filter_flags,
+            groups: caster.filter_groups,
+            exclude_collider: Some(entity),
+            exclude_rigid_body: context.collider_parent(entity).or(Some(entity)),
+            predicate: None,
+            entities: None,
+        };
+
+        let new_hits = match caster.mode {
+            RayCastMode::First => context
+                .cast_ray_and_get_normal(origin, direction, caster.max_toi, caster.solid, filter)
+                .into_iter()
+                .collect(),
+            RayCastMode::All { max_hits } => context.cast_ray_all(
+                origin,
+                direction,
+                caster.max_toi,
+                caster.solid,
+                filter,
+                max_hits,
+            ),
+        };
+
+        if let Some(mut hits) = hits {
+            // NOTE: we write the new value only if there was an actual change, in order to not
+            //       trigger bevy’s change tracking when the values didn’t change.
+            if hits.0 != new_hits {
+                hits.0 = new_hits;
+            }
+        } else {
+            commands.entity(entity).insert(RayHits(new_hits));
+        }
+    }
+}
+
+/// System responsible for casting the shapes of the [`ShapeCaster`]s after each simulation step,
+/// and writing their hits into the [`ShapeHits`] components.
+pub fn update_shape_casters(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    mut casters: Query<(
+        Entity,
+        &ShapeCaster,
+        Option<&GlobalTransform>,
+        Option<&mut ShapeHits>,
+    )>,
+) {
+    for (entity, caster, global_transform, hits) in casters.iter_mut() {
+        let transform = physics_transform(&context, entity, global_transform);
+        #[cfg(feature = "dim2")]
+        let (shape_pos, shape_rot, direction) = (
+            transform.mul_vec3(caster.offset.extend(0.0)).truncate(),
+            transform.rotation.to_scaled_axis().z + caster.rotation,
+            (transform.rotation * caster.direction.extend(0.0)).truncate(),
+        );
+        #[cfg(feature = "dim3")]
+        let (shape_pos, shape_rot, direction) = (
+            transform.mul_vec3(caster.offset),
+            transform.rotation * caster.rotation,
+            transform.rotation * caster.direction,
+        );
+        let direction = direction.normalize_or_zero();
+
+        let filter = crate::pipeline::QueryFilter {
+            flags: caster.filter_flags,
+            groups: caster.filter_groups,
+            exclude_collider: Some(entity),
+            exclude_rigid_body: context.collider_parent(entity).or(Some(entity)),
+            predicate: None,
+            entities: None,
+        };
+
+        let new_hits = match caster.mode {
+            ShapeCastMode::First => context
+                .cast_shape(
+                    shape_pos,
+                    shape_rot,
+                    direction,
+                    &caster.shape,
+                    caster.max_distance,
+                    filter,
+                )
+                .into_iter()
+                .collect(),
+            ShapeCastMode::All { max_hits } => context.cast_shape_all(
+                shape_pos,
+                shape_rot,
+                direction,
+                &caster.shape,
+                caster.max_distance,
+                filter,
+                max_hits,
+            ),
+        };
+
+        if let Some(mut hits) = hits {
+            // NOTE: we write the new value only if there was an actual change, in order to not
+            //       trigger bevy’s change tracking when the values didn’t change.
+            if hits.0 != new_hits {
+                hits.0 = new_hits;
+            }
+        } else {
+            commands.entity(entity).insert(ShapeHits(new_hits));
+        }
+    }
+}
+
+/// System responsible for resolving up to [`AsyncQueryQueue::budget`] of the queries of the
+/// [`AsyncQueryQueue`] after each simulation step, and emitting their [`AsyncQueryResult`]s.
+pub fn resolve_async_queries(
+    context: Res<RapierContext>,
+    mut queue: ResMut<AsyncQueryQueue>,
+    mut results: EventWriter<AsyncQueryResult>,
+) {
+    let count = queue.budget.min(queue.pending.len());
+    if count == 0 {
+        return;
+    }
+
+    let queries: Vec<_> = queue.pending.drain(..count).collect();
+    let resolve = |(id, query): &(AsyncQueryId, AsyncQuery)| AsyncQueryResult {
+        id: *id,
+        requester: query.requester,
+        hit: resolve_async_query(&context, query),
+    };
+
+    if queue.parallel {
+        let resolved = queries.par_splat_map(ComputeTaskPool::get(), None, |chunk| {
+            chunk.iter().map(resolve).collect::<Vec<_>>()
+        });
+        results.send_batch(resolved.into_iter().flatten());
+    } else {
+        results.send_batch(queries.iter().map(resolve));
+    }
+}
+
+fn resolve_async_query(context: &RapierContext, query: &AsyncQuery) -> AsyncQueryHit {
+    let filter = crate::pipeline::QueryFilter {
+        flags: query.filter_flags,
+        groups: query.filter_groups,
+        exclude_collider: query.exclude_collider,
+        exclude_rigid_body: query.exclude_rigid_body,
+        predicate: None,
+        entities: None,
+    };
+
+    match &query.kind {
+        AsyncQueryKind::Ray {
+            origin,
+            direction,
+            max_toi,
+            solid,
+        } => AsyncQueryHit::Ray(
+            context.cast_ray_and_get_normal(*origin, *direction, *max_toi, *solid, filter),
+        ),
+        AsyncQueryKind::Shape {
+            shape,
+            position,
+            rotation,
+            velocity,
+            max_toi,
+        } => AsyncQueryHit::Shape(
+            context.cast_shape(*position, *rotation, *velocity, shape, *max_toi, filter),
+        ),
+    }
+}
+
+/// Adds entity to [`CollidingEntities`] on starting collision and removes from it when the
+/// collision ends.
+pub fn update_colliding_entities(
+    mut collision_events: EventReader<CollisionEvent>,
+    mut colliding_entities: Query<&mut CollidingEntities>,
+) {
+    for event in collision_events.iter() {
+        match *event {
+            CollisionEvent::Started(entity1, entity2, _) => {
+                if let Ok(mut entities) = colliding_entities.get_mut(entity1) {
+                    entities.0.insert(entity2);
+                }
+                if let Ok(mut entities) = colliding_entities.get_mut(entity2) {
+                    entities.0.insert(entity1);
+                }
+            }
+            CollisionEvent::Stopped(entity1, entity2, _) => {
+                if let Ok(mut entities) = colliding_entities.get_mut(entity1) {
+                    entities.0.remove(&entity2);
+                }
+                if let Ok(mut entities) = colliding_entities.get_mut(entity2) {
+                    entities.0.remove(&entity1);
+                }
+            }
+        }
+    }
+}
+
+/// System responsible for rebuilding the [`ContactReport`] after each simulation step, if the
+/// resource exists.
+pub fn update_contact_report(
+    context: Res<RapierContext>,
+    contact_report: Option<ResMut<ContactReport>>,
+) {
+    if let Some(mut contact_report) = contact_report {
+        contact_report.rebuild(&context);
+    }
+}
+
+/// System responsible for updating the [`StandingOn`] components of the rigid-bodies with
+/// [`TrackStanding`], and the [`Carrying`] components of the entities they stand on, after
+/// each simulation step.
+#[allow(clippy::too_many_arguments)]
+pub fn update_standing_on(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    trackers: Query<(Entity, &TrackStanding, Option<&StandingOn>)>,
+    mut carriers: Query<(Entity, &mut Carrying)>,
+    removed_trackers: RemovedComponents<TrackStanding>,
+    // For how long each rigid-body has lost the contact with the entity it stands on.
+    mut lost_for: Local<HashMap<Entity, f32>>,
+) {
+    let dt = time.delta_seconds();
+    let up = (-config.gravity).try_normalize().unwrap_or(Vect::Y);
+    let mut carried: HashMap<Entity, Vec<Entity>> = HashMap::new();
+
+    for (entity, settings, standing_on) in trackers.iter() {
+        // The smallest slope angle of the contacts with each supporting entity.
+        let mut slopes: HashMap<Entity, Real> = HashMap::new();
+        let colliders = context
+            .entity2body
+            .get(&entity)
+            .and_then(|handle| context.bodies.get(*handle))
+            .map(|rb| rb.colliders())
+            .unwrap_or(&[]);
+
+        for collider in colliders.iter().filter_map(|h| context.collider_entity(*h)) {
+            for pair in context.contacts_with(collider) {
+                if !pair.has_any_active_contacts() {
+                    continue;
+                }
+
+                let is_collider1 = pair.collider1() == collider;
+                for manifold in pair.manifolds() {
+                    if manifold.num_points() == 0 {
+                        continue;
+                    }
+
+                    // The normal of the supporting surface, pointing toward this rigid-body.
+                    let (normal, support) = if is_collider1 {
+                        (-manifold.normal(), manifold.rigid_body2())
+                    } else {
+                        (manifold.normal(), manifold.rigid_body1())
+                    };
+                    let support = support.unwrap_or(if is_collider1 {
+                        pair.collider2()
+                    } else {
+                        pair.collider1()
+                    });
+                    let slope = normal.dot(up).clamp(-1.0, 1.0).acos();
+                    let min_slope = slopes.entry(support).or_insert(slope);
+                    *min_slope = min_slope.min(slope);
+                }
+            }
+        }
+
+        let best = slopes
+            .iter()
+            .filter(|(support, _)| **support != entity)
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .map(|(support, slope)| (*support, *slope));
+        let current = standing_on.map(|standing_on| standing_on.0);
+        let current_slope = current.and_then(|support| slopes.get(&support).copied());
+
+        let new_support = match (current, current_slope, best) {
+            (Some(support), Some(slope), _) if slope <= settings.release_slope_angle => {
+                lost_for.remove(&entity);
+                Some(support)
+            }
+            (_, _, Some((support, slope))) if slope <= settings.max_slope_angle => {
+                lost_for.remove(&entity);
+                Some(support)
+            }
+            (Some(support), ..) => {
+                let lost = lost_for.entry(entity).or_default();
+                *lost += dt;
+                if *lost <= settings.release_delay {
+                    Some(support)
+                } else {
+                    lost_for.remove(&entity);
+                    None
+                }
+            }
+            (None, ..) => None,
+        };
+
+        if new_support != current {
+            match new_support {
+                Some(support) => commands.entity(entity).insert(StandingOn(support)),
+                None => commands.entity(entity).remove::<StandingOn>(),
+            };
+        }
+        if let Some(support) = new_support {
+            carried.entry(support).or_default().push(entity);
+        }
+    }
+
+    for entity in removed_trackers.iter() {
+        lost_for.remove(&entity);
+        commands.entity(entity).remove::<StandingOn>();
+    }
+
+    for (entity, mut carrying) in carriers.iter_mut() {
+        match carried.remove(&entity) {
+            Some(mut entities) => {
+                entities.sort();
+                if carrying.0 != entities {
+                    carrying.0 = entities;
+                }
+            }
+            None => {
+                commands.entity(entity).remove::<Carrying>();
+            }
+        }
+    }
+
+    for (entity, mut entities) in carried {
+        entities.sort();
+        commands.entity(entity).insert(Carrying(entities));
+    }
+}
+
+/// System responsible for updating the [`SensorIntersections`] of the sensors with the
+/// [`TrackIntersections`] component after each simulation step.
+pub fn update_sensor_intersections(
+    mut commands: Commands,
+    context: Res<RapierContext>,
+    mut sensors: Query<(Entity, Option<&mut SensorIntersections>), With<TrackIntersections>>,
+) {
+    for (entity, intersections) in sensors.iter_mut() {
+        let new_intersections = context
+            .intersections_with(entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(entity1, entity2, _)| if entity1 == entity { entity2 } else { entity1 })
+            .collect();
+
+        if let Some(mut intersections) = intersections {
+            // NOTE: we write the new value only if there was an actual change, in order to not
+            //       trigger bevy’s change tracking when the values didn’t change.
+            if intersections.0 != new_intersections {
+                intersections.0 = new_intersections;
+            }
+        } else {
+            commands
+                .entity(entity)
+                .insert(SensorIntersections(new_intersections));
+        }
+    }
+}
+
+/// System responsible for emitting the [`TriggerEvent`]s of the [`TriggerZone`]s after each
+/// simulation step.
+pub fn update_trigger_zones(
+    context: Res<RapierContext>,
+    time: Res<Time>,
+    zones: Query<(Entity, &TriggerZone)>,
+    mut trigger_events: EventWriter<TriggerEvent>,
+    // The time spent inside each zone by the colliders, and whether they entered it.
+    mut occupants: Local<HashMap<(Entity, Entity), (f32, bool)>>,
+) {
+    let dt = time.delta_seconds();
+    let mut inside = HashSet::new();
+
+    for (zone, settings) in zones.iter() {
+        for (entity1, entity2, intersecting) in context.intersections_with(zone) {
+            let entity = if entity1 == zone { entity2 } else { entity1 };
+            let memberships = context
+                .entity2collider
+                .get(&entity)
+                .and_then(|handle| context.colliders.get(*handle))
+                .map(|co| {
+                    crate::geometry::Group::from_bits_truncate(
+                        co.collision_groups().memberships.bits(),
+                    )
+                });
+            if !intersecting || !memberships.unwrap_or_default().intersects(settings.filter) {
+                continue;
+            }
+
+            inside.insert((zone, entity));
+            let (time_inside, entered) = occupants.entry((zone, entity)).or_default();
+            *time_inside += dt;
+
+            if *entered {
+                trigger_events.send(TriggerEvent::Staying {
+                    zone,
+                    entity,
+                    time_inside: *time_inside,
+                });
+            } else if *time_inside >= settings.dwell_time {
+                *entered = true;
+                trigger_events.send(TriggerEvent::Entered { zone, entity });
+            }
+        }
+    }
+
+    occupants.retain(|&(zone, entity), &mut (time_inside, entered)| {
+        if inside.contains(&(zone, entity)) {
+            return true;
+        }
+
+        if entered {
+            trigger_events.send(TriggerEvent::Exited {
+                zone,
+                entity,
+                time_inside,
+            });
+        }
+        false
+    });
+}
+
+/// System responsible for resizing the capsules of the character controllers to their
+/// [`KinematicCharacterController::target_height`].
+pub fn resize_character_controllers(
+    config: Res<RapierConfiguration>,
+    context: Res<RapierContext>,
+    mut character_controllers: Query<(
+        Entity,
+        &mut KinematicCharacterController,
+        Option<&mut KinematicCharacterControllerOutput>,
+        Option<&mut Collider>,
+        Option<&RapierRigidBodyHandle>,
+        Option<&GlobalTransform>,
+    )>,
+) {
+    for (entity, mut controller, output, collider, body_handle, transform) in
+        character_controllers.iter_mut()
+    {
+        let (height, transform) = match (controller.target_height, transform) {
+            (Some(height), Some(transform)) => (height, transform.compute_transform()),
+            _ => continue,
+        };
+
+        let (shape, shape_pos) = if let Some((shape, tra, rot)) = &controller.custom_shape {
+            let local_pos: Isometry<Real> = (*tra, *rot).into();
+            (shape, transform_to_iso(&transform, 1.0) * local_pos)
+        } else if let Some(collider) = &collider {
+            (&**collider, transform_to_iso(&transform, 1.0))
+        } else {
+            continue;
+        };
+        let capsule = match shape.as_unscaled_typed_shape() {
+            ColliderView::Capsule(capsule) => capsule,
+            _ => continue,
+        };
+
+        // Keep the endpoint of the capsule the lowest along the `up` vector in place.
+        let local_up: Vect = (shape_pos.rotation.inverse() * Vector::from(controller.up)).into();
+        let (a, b, radius) = (
+            capsule.segment().a(),
+            capsule.segment().b(),
+            capsule.radius(),
+        );
+        let (lower, upper) = if a.dot(local_up) <= b.dot(local_up) {
+            (a, b)
+        } else {
+            (b, a)
+        };
+        let dir = (upper - lower).try_normalize().unwrap_or(local_up);
+        let new_upper = lower + dir * (height - radius * 2.0).max(0.0);
+        if new_upper.abs_diff_eq(upper, 1.0e-5) {
+            if let Some(mut output) = output {
+                output.resize_blocked = false;
+            }
+            continue;
+        }
+
+        let mut resized = Collider::capsule(lower, new_upper, radius);
+        resized.set_scale(shape.scale(), config.scaled_shape_subdivision);
+
+        let blocked = (new_upper - lower).length() > (upper - lower).length() && {
+            let body_entity = body_handle
+                .map(|_| entity)
+                .or_else(|| context.collider_parent(entity));
+            let filter = crate::pipeline::QueryFilter {
+                flags: controller.filter_flags,
+                groups: controller.filter_groups,
+                exclude_collider: Some(entity),
+                exclude_rigid_body: body_entity,
+                predicate: None,
+                entities: None,
+            };
+            // The character may slightly penetrate the ground, so overlaps smaller than its offset
+            // are ignored by shrinking the tested capsule.
+            let skin = match controller.offset {
+                CharacterLength::Absolute(offset) => offset,
+                CharacterLength::Relative(offset) => offset * height,
+            };
+            let skin_radius = (radius - skin).max(0.0);
+            let mut tested = Collider::capsule(lower, new_upper, skin_radius);
+            tested.set_scale(shape.scale(), config.scaled_shape_subdivision);
+            let (pos, rot) = (shape_pos * Translation::from(Vector::from(local_up * skin))).into();
+            context
+                .intersection_with_shape(pos, rot, &tested, filter)
+                .is_some()
+        };
+
+        if !blocked {
+            if let Some((shape, ..)) = &mut controller.custom_shape {
+                *shape = resized;
+            } else if let Some(mut collider) = collider {
+                *collider = resized;
+            }
+        }
+
+        if let Some(mut output) = output {
+            output.resize_blocked = blocked;
+        }
+    }
+}
+
+/// Merges the shapes of colliders attached to the same rigid-body into a single shape, positioned
+/// relative to the rigid-body.
+///
+/// Compound shapes are flattened, since they can’t be nested.
+fn merge_collider_shapes(
+    colliders: &[&rapier::geometry::Collider],
+) -> Option<(SharedShape, Isometry<Real>)> {
+    let mut parts = vec![];
+    for collider in colliders {
+        let pos = collider
+            .position_wrt_parent()
+            .copied()
+            .unwrap_or_else(Isometry::identity);
+        if let Some(compound) = collider.shape().as_compound() {
+            parts.extend(
+                compound
+                    .shapes()
+                    .iter()
+                    .map(|(part_pos, part)| (pos * part_pos, part.clone())),
+            );
+        } else {
+            parts.push((pos, collider.shared_shape().clone()));
+        }
+    }
+
+    match parts.len() {
+        0 => None,
+        1 => parts.pop().map(|(pos, shape)| (shape, pos)),
+        _ => Some((SharedShape::compound(parts), Isometry::identity())),
+    }
+}
+
+/// System responsible for restoring the [`Transform`] of the interpolated characters to their
+/// last position computed by the character controller, before they are moved again.
+pub fn restore_character_transforms(
+    mut interpolations: Query<(&mut Transform, &mut CharacterInterpolation)>,
+) {
+    for (mut transform, mut interpolation) in interpolations.iter_mut() {
+        if let Some(end) = interpolation.end {
+            if interpolation.current == Some(*transform) {
+                *transform = end;
+            } else {
+                // The transform was modified by the user, reset the interpolation so we don’t
+                // overwrite the user’s input.
+                interpolation.start = None;
+                interpolation.end = None;
+                interpolation.current = None;
+            }
+        }
+    }
+}
+
+/// System responsible for applying the character controller translation to the underlying
+/// collider.
+pub fn update_character_controls(
+    mut commands: Commands,
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    sim_to_render_time: Res<SimulationToRenderTime>,
+    mut context: ResMut<RapierContext>,
+    mut character_controllers: Query<(
+        Entity,
+        &mut KinematicCharacterController,
+        Option<&mut KinematicCharacterControllerOutput>,
+        Option<&RapierColliderHandle>,
+        Option<&RapierRigidBodyHandle>,
+        Option<&GlobalTransform>,
+    )>,
+    mut transforms: Query<&mut Transform>,
+    mut interpolations: Query<&mut CharacterInterpolation>,
+    platforms: Query<(&RapierColliderHandle, &OneWayPlatform)>,
+    fluids: Query<(&RapierColliderHandle, &FluidVolume)>,
+    mut collision_events: EventWriter<CharacterCollisionEvent>,
+    mut grounded_events: EventWriter<GroundedChanged>,
+    mut steep_slope_events: EventWriter<SteepSlopeChanged>,
+) {
+    let physics_scale = context.physics_scale;
+    let dt = simulation_step_dt(config.timestep_mode, &time);
+    // With an interpolated timestep, the interpolated characters are only moved on the frames
+    // where the simulation advances.
+    let interpolated = matches!(config.timestep_mode, TimestepMode::Interpolated { .. });
+    let simulation_advances = sim_to_render_time.diff + time.delta_seconds() > 0.0;
+    let context = &mut *context;
+    context.one_way_platforms = platforms
+        .iter()
+        .map(|(handle, platform)| (handle.0, platform.direction.into()))
+        .collect();
+    context.fluid_volumes = fluids
+        .iter()
+        .map(|(handle, fluid)| (handle.0, *fluid))
+        .collect();
+    for (entity, mut controller, output, collider_handle, body_handle, glob_transform) in
+        character_controllers.iter_mut()
+    {
+        // Characters with a built-in vertical movement are moved every frame.
+        let translation = controller
+            .translation
+            .or_else(|| controller.vertical_movement.map(|_| Vect::ZERO));
+        if let (Some(raw_controller), Some(translation)) =
+            (controller.to_raw(physics_scale), translation)
+        {
+            let scaled_custom_shape =
+                controller
+                    .custom_shape
+                    .as_ref()
+                    .map(|(custom_shape, tra, rot)| {
+                        // TODO: avoid the systematic scale somehow?
+                        let mut scaled_shape = custom_shape.clone();
+                        scaled_shape.set_scale(
+                            custom_shape.scale / physics_scale,
+                            config.scaled_shape_subdivision,
+                        );
+
+                        (scaled_shape, *tra / physics_scale, *rot)
+                    });
+
+            let parent_rigid_body = body_handle.map(|h| h.0).or_else(|| {
+                collider_handle
+                    .and_then(|h| context.colliders.get(h.0))
+                    .and_then(|c| c.parent())
+            });
+            let entity_to_move = parent_rigid_body
+                .and_then(|rb| context.rigid_body_entity(rb))
+                .unwrap_or(entity);
+
+            if interpolated && !simulation_advances && interpolations.contains(entity_to_move) {
+                continue;
+            }
+
+            let (character_shape, character_pos) = if let Some((scaled_shape, tra, rot)) =
+                &scaled_custom_shape
+            {
+                let mut shape_pos: Isometry<Real> = (*tra, *rot).into();
+
+                if let Some(body) = body_handle.and_then(|h| context.bodies.get(h.0)) {
+                    shape_pos = body.position() * shape_pos
+                } else if let Some(gtransform) = glob_transform {
+                    shape_pos =
+                        transform_to_iso(&gtransform.compute_transform(), physics_scale) * shape_pos
+                }
+
+                (scaled_shape.raw.clone(), shape_pos)
+            } else if let Some((shape, shape_pos)) = parent_rigid_body
+                .and_then(|h| context.bodies.get(h))
+                .and_then(|body| {
+                    // Characters made of several colliders, or whose collider isn’t attached to
+                    // the same entity, are moved as a whole.
+                    let solids: Vec<_> = body
+                        .colliders()
+                        .iter()
+                        .filter_map(|h| context.colliders.get(*h))
+                        .filter(|collider| !collider.is_sensor())
+                        .collect();
+                    if collider_handle.is_some() && solids.len() <= 1 {
+                        return None;
+                    }
+
+                    merge_collider_shapes(&solids)
+                        .map(|(shape, shape_pos)| (shape, body.position() * shape_pos))
+                })
+            {
+                (shape, shape_pos)
+            } else if let Some(collider) = collider_handle.and_then(|h| context.colliders.get(h.0))
+            {
+                (collider.shared_shape().clone(), *collider.position())
+            } else {
+                continue;
+            };
+
+            let exclude_collider = collider_handle.map(|h| h.0);
+
+            let character_mass = controller
+                .custom_mass
+                .or_else(|| {
+                    parent_rigid_body
+                        .and_then(|h| context.bodies.get(h))
+                        .map(|rb| rb.mass())
+                })
+                .unwrap_or(0.0);
+
+            let mut filter = QueryFilter {
+                flags: controller.filter_flags,
+                groups: controller.filter_groups,
+                exclude_collider: None,
+                exclude_rigid_body: None,
+                predicate: None,
+            };
+
+            if let Some(parent) = parent_rigid_body {
+                filter = filter.exclude_rigid_body(parent);
+            } else if let Some(excl_co) = exclude_collider {
+                filter = filter.exclude_collider(excl_co)
+            };
+
+            let up_vect: Vect = (*raw_controller.up).into();
+            let (grounded, on_steep_slope) = output
+                .as_ref()
+                .map(|output| (output.grounded, output.on_steep_slope))
+                .unwrap_or((false, false));
+            let new_output = context.move_character_raw(
+                &controller,
+                raw_controller,
+                translation,
+                &*character_shape,
+                character_pos,
+                character_mass,
+                output.as_deref(),
+                config.gravity,
+                dt,
+                filter,
+                controller.apply_impulse_to_dynamic_bodies,
+            );
+
+            if let Ok(mut transform) = transforms.get_mut(entity_to_move) {
+                let previous_transform = *transform;
+                // TODO: take the parent’s GlobalTransform rotation into account?
+                transform.translation.x += new_output.effective_translation.x;
+                transform.translation.y += new_output.effective_translation.y;
+                #[cfg(feature = "dim3")]
+                {
+                    transform.translation.z += new_output.effective_translation.z;
+                }
+
+                if new_output.inherited_angle != 0.0 {
+                    #[cfg(feature = "dim2")]
+                    let platform_rotation = Quat::from_rotation_z(new_output.inherited_angle);
+                    #[cfg(feature = "dim3")]
+                    let platform_rotation =
+                        Quat::from_axis_angle(up_vect, new_output.inherited_angle);
+                    transform.rotation = platform_rotation * transform.rotation;
+                }
+
+                if interpolated {
+                    if let Ok(mut interpolation) = interpolations.get_mut(entity_to_move) {
+                        interpolation.start = Some(previous_transform);
+                        interpolation.end = Some(*transform);
+                    }
+                }
+            }
+
+            let max_slope_cos = controller.max_slope_climb_angle.cos();
+            for collision in &new_output.collisions {
+                // The first normal is the normal of the collider hit.
+                let surface = match collision.toi.normal1.dot(up_vect) {
+                    cos if cos >= max_slope_cos => CharacterSurface::Ground,
+                    cos if cos <= -max_slope_cos => CharacterSurface::Ceiling,
+                    _ => CharacterSurface::Wall,
+                };
+                collision_events.send(CharacterCollisionEvent::Hit {
+                    character: entity,
+                    collision: *collision,
+                    surface,
+                });
+            }
+
+            let previously_touching = output
+                .as_ref()
+                .map(|output| &output.touching[..])
+                .unwrap_or_default();
+            for collider in &new_output.touching {
+                if previously_touching.binary_search(collider).is_err() {
+                    collision_events.send(CharacterCollisionEvent::Started(entity, *collider));
+                }
+            }
+            for collider in previously_touching {
+                if new_output.touching.binary_search(collider).is_err() {
+                    collision_events.send(CharacterCollisionEvent::Stopped(entity, *collider));
+                }
+            }
+
+            if new_output.grounded != grounded {
+                grounded_events.send(GroundedChanged(entity, new_output.grounded));
+            }
+
+            if new_output.on_steep_slope != on_steep_slope {
+                steep_slope_events.send(SteepSlopeChanged(entity, new_output.on_steep_slope));
+            }
+
+            if let Some(mut output) = output {
+                *output = new_output;
+            } else {
+                commands.entity(entity).insert(new_output);
+            }
+
+            controller.translation = None;
+            controller.jump = false;
+            controller.drop_through = false;
+        }
+    }
+}
+
+/// System responsible for interpolating the [`Transform`] of the characters with a
+/// [`CharacterInterpolation`] between their last two positions.
+pub fn interpolate_character_transforms(
+    config: Res<RapierConfiguration>,
+    sim_to_render_time: Res<SimulationToRenderTime>,
+    mut interpolations: Query<(&mut Transform, &mut CharacterInterpolation)>,
+) {
+    if let TimestepMode::Interpolated { dt, .. } = config.timestep_mode {
+        for (mut transform, mut interpolation) in interpolations.iter_mut() {
+            if let Some(interpolated) =
+                interpolation.lerp_slerp((dt + sim_to_render_time.diff) / dt)
+            {
+                *transform = interpolated;
+                interpolation.current = Some(interpolated);
+            }
+        }
+    }
+}
+
+/// System responsible for accelerating the rigid-bodies of the [`DynamicCharacterController`]s
+/// toward their desired velocity.
+pub fn update_dynamic_character_controls(
+    mut commands: Commands,
+    config: Res<RapierConfiguration>,
+    time: Res<Time>,
+    mut context: ResMut<RapierContext>,
+    mut character_controllers: Query<(
+        Entity,
+        &mut DynamicCharacterController,
+        Option<&mut DynamicCharacterControllerOutput>,
+        &RapierRigidBodyHandle,
+    )>,
+) {
+    let context = &mut *context;
+    let physics_scale = context.physics_scale;
+    let dt = simulation_step_dt(config.timestep_mode, &time);
+    let gravity: Vector<Real> = (config.gravity / physics_scale).into();
+
+    for (entity, mut controller, output, handle) in character_controllers.iter_mut() {
+        let jump = std::mem::take(&mut controller.jump);
+        let up = match UnitVector::try_new(controller.up.into(), 1.0e-5) {
+            Some(up) => up,
+            None => continue,
+        };
+        let body = match context.bodies.get(handle.0) {
+            Some(body) => body,
+            None => continue,
+        };
+        let filter = QueryFilter {
+            flags: controller.filter_flags,
+            groups: controller.filter_groups,
+            exclude_collider: None,
+            exclude_rigid_body: Some(handle.0),
+            predicate: None,
+        };
+
+        // Find the closest ground right below the colliders of the character. The shapes are
+        // cast from slightly above so the normal of a touching ground is still computed.
+        let ground_distance = controller.ground_distance / physics_scale;
+        let skin = Translation::from(*up * ground_distance);
+        let mut ground: Option<(ColliderHandle, TOI)> = None;
+        for collider in body.colliders() {
+            let collider = match context.colliders.get(*collider) {
+                Some(collider) => collider,
+                None => continue,
+            };
+            if let Some((hit, toi)) = context.query_pipeline.cast_shape(
+                &context.bodies,
+                &context.colliders,
+                &(skin * collider.position()),
+                &-*up,
+                collider.shape(),
+                ground_distance * 2.0,
+                true,
+                filter,
+            ) {
+                if ground
+                    .map(|(_, closest)| toi.toi < closest.toi)
+                    .unwrap_or(true)
+                {
+                    ground = Some((hit, toi));
+                }
+            }
+        }
+        let ground = ground
+            .filter(|(_, toi)| toi.normal1.dot(&up) >= controller.max_ground_angle.cos() - 1.0e-5);
+        let ground_normal = ground.map(|(_, toi)| *toi.normal1).unwrap_or(*up);
+
+        let linvel = *body.linvel();
+        let desired: Vector<Real> = (controller.desired_velocity / physics_scale).into();
+        let desired = desired - *up * desired.dot(&up);
+        let mut delta_vel = if ground.is_some() {
+            // Move along the ground, keeping the desired velocity orthogonal to `up`.
+            let target = desired - *up * (desired.dot(&ground_normal) / ground_normal.dot(&up));
+            let current = linvel - ground_normal * linvel.dot(&ground_normal);
+            let max_delta = controller.max_acceleration / physics_scale * dt;
+            // Cancel the gravity pulling the character down the slope.
+            let gravity_along_ground = gravity - ground_normal * gravity.dot(&ground_normal);
+            (target - current).cap_magnitude(max_delta) - gravity_along_ground * dt
+        } else {
+            let current = linvel - *up * linvel.dot(&up);
+            let max_delta = controller.air_acceleration / physics_scale * dt;
+            (desired - current).cap_magnitude(max_delta)
+        };
+
+        if jump && ground.is_some() {
+            delta_vel +=
+                *up * (controller.jump_speed / physics_scale - (linvel + delta_vel).dot(&up));
+        }
+
+        if delta_vel != Vector::zeros() {
+            if let Some(body) = context.bodies.get_mut(handle.0) {
+                body.set_linvel(linvel + delta_vel, true);
+            }
+        }
+
+        let grounded = ground.is_some();
+        let ground = ground.and_then(|(handle, _)| context.collider_entity(handle));
+        if let Some(mut output) = output {
+            output.grounded = grounded;
+            output.ground = ground;
+            output.ground_normal = ground_normal.into();
+        } else {
+            commands
+                .entity(entity)
+                .insert(DynamicCharacterControllerOutput {
+                    grounded,
+                    ground,
+                    ground_normal: ground_normal.into(),
+                });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "dim3")]
+    use bevy::prelude::shape::{Capsule, Cube};
+    use bevy::{
+        asset::AssetPlugin,
+        core::CorePlugin,
+        ecs::{entity::EntityMap, event::Events, system::CommandQueue},
+        reflect::TypeRegistryArc,
+        render::{primitives::Aabb, settings::WgpuSettings, RenderPlugin},
+        scene::{DynamicScene, ScenePlugin},
+        time::TimePlugin,
+        window::WindowPlugin,
+    };
+    use std::f32::consts::PI;
+
+    use super::*;
+    use crate::control::{
+        CharacterInterpolation, CharacterLedgeDetection, CharacterPushing,
+        CharacterVerticalMovement, CharacterWallDetection, PlatformInheritance, SteepSlopeBehavior,
+    };
+    use crate::dynamics::{
+        CoefficientCombineRule, FixedJointBuilder, GenericJoint, JointSoftness, MirrorPlane,
+        MirrorRigCommandsExt, MultibodyChainBuilder, PrismaticJointBuilder, PulleyJointBuilder,
+        QuantizedBodyState, RevoluteJointBuilder, RigidBodyCommandsExt, RopeJointBuilder,
+        SpringJointBuilder, StateQuantization,
+    };
+    use crate::geometry::{
+        ClosestPoints, FrictionOverrides, HeightfieldCell, LineOfSight, Region, SurfaceVelocity,
+        TriangleHit, VisionCone,
+    };
+    use crate::math::{Real, Rot};
+    use crate::pipeline::{ContactModifiers, ContactModifiersData, QueryFilter};
+    use crate::plugin::{
+        NoUserData, PhysicsInput, PhysicsStages, RapierPhysicsPlugin, RapierQuery,
+    };
+    #[cfg(feature = "dim3")]
+    use crate::prelude::ComputedColliderShape;
+
+    #[test]
+    fn colliding_entities_updates() {
+        let mut app = App::new();
+        app.add_event::<CollisionEvent>()
+            .add_system(update_colliding_entities);
+
+        let entity1 = app.world.spawn().insert(CollidingEntities::default()).id();
+        let entity2 = app.world.spawn().insert(CollidingEntities::default()).id();
+
+        let mut collision_events = app
+            .world
+            .get_resource_mut::<Events<CollisionEvent>>()
+            .unwrap();
+        collision_events.send(CollisionEvent::Started(
+            entity1,
+            entity2,
+            CollisionEventFlags::SENSOR,
+        ));
+
+        app.update();
+
+        let colliding_entities1 = app
+            .world
+            .entity(entity1)
+            .get::<CollidingEntities>()
+            .unwrap();
+        assert_eq!(
+            colliding_entities1.len(),
+            1,
+            "There should be one colliding entity"
+        );
+        assert_eq!(
+            colliding_entities1.iter().next().unwrap(),
+            entity2,
+            "Colliding entity should be equal to the second entity"
+        );
+
+        let colliding_entities2 = app
+            .world
+            .entity(entity2)
+            .get::<CollidingEntities>()
+            .unwrap();
+        assert_eq!(
+            colliding_entities2.len(),
+            1,
+            "There should be one colliding entity"
+        );
+        assert_eq!(
+            colliding_entities2.iter().next().unwrap(),
+            entity1,
+            "Colliding entity should be equal to the first entity"
+        );
+
+        let mut collision_events = app
+            .world
+            .get_resource_mut::<Events<CollisionEvent>>()
+            .unwrap();
+        collision_events.send(CollisionEvent::Stopped(
+            entity1,
+            entity2,
+            CollisionEventFlags::SENSOR,
+        ));
+
+        app.update();
+
+        let colliding_entities1 = app
+            .world
+            .entity(entity1)
+            .get::<CollidingEntities>()
+            .unwrap();
+        assert!(
+            colliding_entities1.is_empty(),
+            "Colliding entity should be removed from the CollidingEntities component when the collision ends"
+        );
+
+        let colliding_entities2 = app
+            .world
+            .entity(entity2)
+            .get::<CollidingEntities>()
+            .unwrap();
+        assert!(
+            colliding_entities2.is_empty(),
+            "Colliding entity should be removed from the CollidingEntities component when the collision ends"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn async_collider_initializes() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_system(init_async_colliders);
+
+        let mut meshes = app.world.resource_mut::<Assets<Mesh>>();
+        let cube = meshes.add(Cube::default().into());
+
+        let entity = app
+            .world
+            .spawn()
+            .insert(AsyncCollider {
+                handle: cube,
+                shape: ComputedColliderShape::TriMesh,
+            })
+            .id();
+
+        app.update();
+
+        let entity = app.world.entity(entity);
+        assert!(
+            entity.get::<Collider>().is_some(),
+            "Collider component should be added"
+        );
+        assert!(
+            entity.get::<AsyncCollider>().is_none(),
+            "AsyncCollider component should be removed after Collider component creation"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn async_scene_collider_initializes() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_system(init_async_scene_colliders);
+
+        let mut meshes = app.world.resource_mut::<Assets<Mesh>>();
+        let cube_handle = meshes.add(Cube::default().into());
+        let capsule_handle = meshes.add(Capsule::default().into());
+        let cube = app
+            .world
+            .spawn()
+            .insert(Name::new("Cube"))
+            .insert(cube_handle)
+            .id();
+        let capsule = app
+            .world
+            .spawn()
+            .insert(Name::new("Capsule"))
+            .insert(capsule_handle)
+            .id();
+
+        let mut scenes = app.world.resource_mut::<Assets<Scene>>();
+        let scene = scenes.add(Scene::new(World::new()));
+
+        let mut named_shapes = bevy::utils::HashMap::new();
+        named_shapes.insert("Capsule".to_string(), None);
+        let parent = app
+            .world
+            .spawn()
+            .insert(AsyncSceneCollider {
+                handle: scene,
+                shape: Some(ComputedColliderShape::TriMesh),
+                named_shapes,
+            })
+            .push_children(&[cube, capsule])
+            .id();
+
+        app.update();
+
+        assert!(
+            app.world.entity(cube).get::<Collider>().is_some(),
+            "Collider component should be added for cube"
+        );
+        assert!(
+            app.world.entity(capsule).get::<Collider>().is_none(),
+            "Collider component shouldn't be added for capsule"
+        );
+        assert!(
+            app.world.entity(parent).get::<AsyncCollider>().is_none(),
+            "AsyncSceneCollider component should be removed after Collider components creation"
+        );
+    }
+
+    #[test]
+    fn transform_propagation() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let zero = (Transform::default(), Transform::default());
+
+        let different = (
+            Transform {
+                translation: Vec3::X * 10.0,
+                rotation: Quat::from_rotation_x(PI),
+                ..Default::default()
+            },
+            Transform {
+                translation: Vec3::Y * 10.0,
+                rotation: Quat::from_rotation_x(PI),
+                ..Default::default()
+            },
+        );
+
+        let same = (different.0, different.0);
+
+        for (child_transform, parent_transform) in [zero, same, different] {
+            let child = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(child_transform))
+                .insert(RigidBody::Fixed)
+                .insert(Collider::ball(1.0))
+                .id();
+
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(parent_transform))
+                .push_children(&[child]);
+
+            app.update();
+
+            let child_transform = app.world.entity(child).get::<GlobalTransform>().unwrap();
+            let context = app.world.resource::<RapierContext>();
+            let child_handle = context.entity2body[&child];
+            let child_body = context.bodies.get(child_handle).unwrap();
+            let body_transform =
+                utils::iso_to_transform(child_body.position(), context.physics_scale);
+            assert_eq!(
+                GlobalTransform::from(body_transform),
+                *child_transform,
+                "Collider transform should have have global rotation and translation"
+            );
+        }
+    }
+
+    #[test]
+    fn transform_propagation2() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let zero = (Transform::default(), Transform::default());
+
+        let different = (
+            Transform {
+                translation: Vec3::X * 10.0,
+                // NOTE: in 2D the test will fail if the rotation is wrt. an axis
+                //       other than Z because 2D physics objects can’t rotate wrt.
+                //       other axes.
+                rotation: Quat::from_rotation_z(PI),
+                ..Default::default()
+            },
+            Transform {
+                translation: Vec3::Y * 10.0,
+                rotation: Quat::from_rotation_z(PI),
+                ..Default::default()
+            },
+        );
+
+        let same = (different.0, different.0);
+
+        for (child_transform, parent_transform) in [zero, same, different] {
+            let child = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(child_transform))
+                .insert(Collider::ball(1.0))
+                .id();
+
+            let parent = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(parent_transform))
+                .insert(RigidBody::Fixed)
+                .push_children(&[child])
+                .id();
+
+            app.update();
+
+            let child_transform = app
+                .world
+                .entity(child)
+                .get::<GlobalTransform>()
+                .unwrap()
+                .compute_transform();
+            let context = app.world.resource::<RapierContext>();
+            let parent_handle = context.entity2body[&parent];
+            let parent_body = context.bodies.get(parent_handle).unwrap();
+            let child_collider_handle = parent_body.colliders()[0];
+            let child_collider = context.colliders.get(child_collider_handle).unwrap();
+            let body_transform =
+                utils::iso_to_transform(child_collider.position(), context.physics_scale);
+            approx::assert_relative_eq!(
+                body_transform.translation,
+                child_transform.translation,
+                epsilon = 1.0e-5
+            );
+
+            // Adjust signs to account for the quaternion’s double covering.
+            let comparison_child_rotation =
+                if body_transform.rotation.w * child_transform.rotation.w < 0.0 {
+                    -child_transform.rotation
+                } else {
+                    child_transform.rotation
+                };
+
+            approx::assert_relative_eq!(
+                body_transform.rotation,
+                comparison_child_rotation,
+                epsilon = 1.0e-5
+            );
+            approx::assert_relative_eq!(body_transform.scale, child_transform.scale,);
+        }
+    }
+
+    #[test]
+    fn rigid_body_commands_keep_or_stop_the_velocity() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let entity = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::KinematicVelocityBased)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::linear(Vect::X * 2.0))
+            .id();
+        app.update();
+
+        let body_velocity = |app: &App| {
+            let context = app.world.resource::<RapierContext>();
+            let handle = context.entity2body()[&entity];
+            context.bodies[handle].linvel().x
+        };
+
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &app.world)
+            .entity(entity)
+            .make_dynamic();
+        queue.apply(&mut app.world);
+
+        assert_eq!(
+            app.world.get::<RigidBody>(entity),
+            Some(&RigidBody::Dynamic)
+        );
+        approx::assert_relative_eq!(body_velocity(&app), 2.0);
+        for _ in 0..10 {
+            app.update();
+        }
+        let velocity = app.world.get::<Velocity>(entity).unwrap();
+        approx::assert_relative_eq!(velocity.linvel.x, 2.0, epsilon = 1.0e-4);
+
+        Commands::new(&mut queue, &app.world)
+            .entity(entity)
+            .make_fixed();
+        queue.apply(&mut app.world);
+
+        assert_eq!(app.world.get::<RigidBody>(entity), Some(&RigidBody::Fixed));
+        assert_eq!(body_velocity(&app), 0.0);
+        assert_eq!(
+            app.world.get::<Velocity>(entity).unwrap().linvel,
+            Vect::ZERO
+        );
+
+        // The command of a rigid-body despawned before it is applied does nothing.
+        Commands::new(&mut queue, &app.world)
+            .entity(entity)
+            .make_dynamic();
+        app.world.despawn(entity);
+        queue.apply(&mut app.world);
+        app.update();
+    }
+
+    #[test]
+    fn rigid_body_commands_wake_up_the_neighbors() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let (ground_shape, box_shape) = (Collider::cuboid(10.0, 0.5), Collider::cuboid(0.5, 0.5));
+        #[cfg(feature = "dim3")]
+        let (ground_shape, box_shape) = (
+            Collider::cuboid(10.0, 0.5, 10.0),
+            Collider::cuboid(0.5, 0.5, 0.5),
+        );
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(ground_shape)
+            .id();
+        let resting = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(box_shape)
+            .id();
+
+        let is_sleeping = |app: &App| {
+            let context = app.world.resource::<RapierContext>();
+            let handle = context.entity2body()[&resting];
+            context.bodies[handle].is_sleeping()
+        };
+
+        for _ in 0..300 {
+            app.update();
+        }
+        assert!(is_sleeping(&app));
+
+        let mut queue = CommandQueue::default();
+        Commands::new(&mut queue, &app.world)
+            .entity(ground)
+            .make_kinematic();
+        queue.apply(&mut app.world);
+
+        assert!(!is_sleeping(&app));
+    }
+
+    #[test]
+    fn radial_gravity_accelerates_toward_a_moving_center() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let dt = 1.0 / 60.0;
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed { dt, substeps: 1 };
+
+        let center = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(100.0, 0.0, 0.0)))
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::default())
+            .insert(RadialGravity::new(center, 5.0))
+            .id();
+        app.update();
+        app.update();
+
+        let acceleration = |app: &mut App| {
+            let before = app.world.get::<Velocity>(body).unwrap().linvel;
+            app.update();
+            (app.world.get::<Velocity>(body).unwrap().linvel - before) / dt
+        };
+
+        // The global gravity no longer applies.
+        let accel = acceleration(&mut app);
+        approx::assert_relative_eq!(accel.x, 5.0, epsilon = 1.0e-2);
+        approx::assert_relative_eq!(accel.y, 0.0, epsilon = 1.0e-2);
+
+        app.world.get_mut::<Transform>(center).unwrap().translation = Vec3::new(0.0, -100.0, 0.0);
+        app.update();
+        let accel = acceleration(&mut app);
+        approx::assert_relative_eq!(accel.x, 0.0, epsilon = 1.0e-2);
+        approx::assert_relative_eq!(accel.y, -5.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn radial_gravity_wakes_up_bodies_when_the_center_moves() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let ground = Collider::cuboid(10.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let ground = Collider::cuboid(10.0, 0.5, 10.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(ground);
+
+        // The rigid-body rests on the ground, attracted by a center below it.
+        let center = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -10.0, 0.0)))
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(RadialGravity::new(center, 9.81))
+            .id();
+
+        let is_sleeping = |app: &App| {
+            let context = app.world.resource::<RapierContext>();
+            context.bodies[context.entity2body()[&body]].is_sleeping()
+        };
+        for _ in 0..300 {
+            app.update();
+        }
+        assert!(is_sleeping(&app));
+
+        app.world.get_mut::<Transform>(center).unwrap().translation = Vec3::new(0.0, 10.0, 0.0);
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(!is_sleeping(&app));
+        assert!(app.world.get::<Transform>(body).unwrap().translation.y > 1.5);
+    }
+
+    #[test]
+    fn removing_radial_gravity_restores_the_global_gravity() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let dt = 1.0 / 60.0;
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed { dt, substeps: 1 };
+
+        let center = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(100.0, 0.0, 0.0)))
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::default())
+            .insert(GravityScale(0.5))
+            .insert(RadialGravity::new(center, 5.0))
+            .id();
+        for _ in 0..5 {
+            app.update();
+        }
+
+        app.world.entity_mut(body).remove::<RadialGravity>();
+        app.update();
+
+        let before = app.world.get::<Velocity>(body).unwrap().linvel;
+        app.update();
+        let accel = (app.world.get::<Velocity>(body).unwrap().linvel - before) / dt;
+        let gravity = app.world.resource::<RapierConfiguration>().gravity;
+        approx::assert_relative_eq!(accel, gravity * 0.5, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn sleeping_bodies_time_out_once() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .insert_resource(Time::default())
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let (ground_shape, box_shape) = (Collider::cuboid(10.0, 0.5), Collider::cuboid(0.5, 0.5));
+        #[cfg(feature = "dim3")]
+        let (ground_shape, box_shape) = (
+            Collider::cuboid(10.0, 0.5, 10.0),
+            Collider::cuboid(0.5, 0.5, 0.5),
+        );
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(ground_shape);
+        let mut spawn_box = |x: f32, despawn: DespawnWhenSleeping| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 1.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(box_shape.clone())
+                .insert(despawn)
+                .id()
+        };
+        let despawned = spawn_box(-3.0, DespawnWhenSleeping::new(Duration::from_secs(1)));
+        let kept = spawn_box(3.0, DespawnWhenSleeping::send_event(Duration::from_secs(1)));
+
+        // Run for 10 s, long enough for the boxes to fall asleep and time out.
+        let start = std::time::Instant::now();
+        let mut reader = app
+            .world
+            .resource::<Events<SleepTimeoutEvent>>()
+            .get_reader();
+        let mut events = vec![];
+        for i in 1..=600 {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(i as f32 / 60.0));
+            app.update();
+            events.extend(
+                reader
+                    .iter(app.world.resource::<Events<SleepTimeoutEvent>>())
+                    .map(|event| event.entity),
+            );
+        }
+
+        events.sort();
+        let mut expected = vec![despawned, kept];
+        expected.sort();
+        assert_eq!(events, expected);
+        assert!(app.world.get_entity(despawned).is_none());
+        let despawn = app.world.get::<DespawnWhenSleeping>(kept).unwrap();
+        assert!(despawn.asleep_for >= despawn.after);
+    }
+
+    #[test]
+    fn sleeping_body_timer_resets_on_wake_up() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .insert_resource(Time::default())
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let (ground_shape, box_shape) = (Collider::cuboid(10.0, 0.5), Collider::cuboid(0.5, 0.5));
+        #[cfg(feature = "dim3")]
+        let (ground_shape, box_shape) = (
+            Collider::cuboid(10.0, 0.5, 10.0),
+            Collider::cuboid(0.5, 0.5, 0.5),
+        );
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(ground_shape);
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(box_shape)
+            .insert(DespawnWhenSleeping::send_event(Duration::from_secs(60)))
+            .id();
+
+        let start = std::time::Instant::now();
+        let mut frame = 0;
+        let mut run = |app: &mut App, frames| {
+            for _ in 0..frames {
+                frame += 1;
+                app.world
+                    .resource_mut::<Time>()
+                    .update_with_instant(start + Duration::from_secs_f32(frame as f32 / 60.0));
+                app.update();
+            }
+        };
+        let asleep_for = |app: &App| {
+            app.world
+                .get::<DespawnWhenSleeping>(body)
+                .unwrap()
+                .asleep_for
+        };
+
+        run(&mut app, 300);
+        assert!(asleep_for(&app) > Duration::from_secs(1));
+
+        let mut context = app.world.resource_mut::<RapierContext>();
+        let handle = context.entity2body()[&body];
+        context.bodies[handle].wake_up(true);
+        run(&mut app, 1);
+        assert_eq!(asleep_for(&app), Duration::ZERO);
+    }
+
+    #[test]
+    fn world_bounds_handle_each_exit_once() {
+        let new_app = |action| {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+                .insert_resource(PhysicsWorldBounds {
+                    aabb: Aabb::from_min_max(Vec3::splat(-5.0), Vec3::splat(5.0)),
+                    action,
+                });
+
+            let mut config = app.world.resource_mut::<RapierConfiguration>();
+            config.gravity = Vect::ZERO;
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 1,
+            };
+
+            // The rigid-body moves by one unit per update.
+            let entity = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::default())
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(Velocity::linear(Vect::X * 60.0))
+                .id();
+            (app, entity)
+        };
+        let run = |app: &mut App, updates| {
+            let mut reader = app.world.resource::<Events<OutOfBounds>>().get_reader();
+            let mut events = vec![];
+            for _ in 0..updates {
+                app.update();
+                events.extend(
+                    reader
+                        .iter(app.world.resource::<Events<OutOfBounds>>())
+                        .copied(),
+                );
+            }
+            events
+        };
+
+        let (mut app, entity) = new_app(OutOfBoundsAction::Despawn);
+        assert_eq!(run(&mut app, 20), [OutOfBounds(entity)]);
+        assert!(app.world.get_entity(entity).is_none());
+
+        let (mut app, entity) = new_app(OutOfBoundsAction::Clamp);
+        assert_eq!(run(&mut app, 20), [OutOfBounds(entity)]);
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        approx::assert_relative_eq!(transform.translation.x, 5.0, epsilon = 1.0e-3);
+        let velocity = app.world.get::<Velocity>(entity).unwrap();
+        approx::assert_relative_eq!(velocity.linvel.x, 0.0);
+
+        let (mut app, entity) = new_app(OutOfBoundsAction::SendEvent);
+        assert_eq!(run(&mut app, 20), [OutOfBounds(entity)]);
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        assert!(transform.translation.x > 15.0);
+
+        // The rigid-body is reported again once it comes back and leaves on the other side.
+        app.world.get_mut::<Velocity>(entity).unwrap().linvel = -Vect::X * 60.0;
+        assert_eq!(run(&mut app, 30), [OutOfBounds(entity)]);
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        assert!(transform.translation.x < -5.0);
+    }
+
+    #[test]
+    fn world_bounds_handle_non_finite_bodies() {
+        let new_app = |action| {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+                .insert_resource(PhysicsWorldBounds {
+                    aabb: Aabb::from_min_max(Vec3::new(0.0, 0.0, -5.0), Vec3::splat(10.0)),
+                    action,
+                });
+
+            let mut config = app.world.resource_mut::<RapierConfiguration>();
+            config.gravity = Vect::ZERO;
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 1,
+            };
+
+            let entity = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 1.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(Velocity::default())
+                .id();
+            app.update();
+
+            let mut context = app.world.resource_mut::<RapierContext>();
+            let handle = context.entity2body()[&entity];
+            context.bodies[handle].set_linvel(Vect::splat(Real::NAN).into(), true);
+            (app, entity)
+        };
+        let run = |app: &mut App, updates| {
+            let mut reader = app.world.resource::<Events<OutOfBounds>>().get_reader();
+            let mut events = vec![];
+            for _ in 0..updates {
+                app.update();
+                events.extend(
+                    reader
+                        .iter(app.world.resource::<Events<OutOfBounds>>())
+                        .copied(),
+                );
+            }
+            events
+        };
+
+        // The rigid-body is moved to the center of the bounds and stopped.
+        let (mut app, entity) = new_app(OutOfBoundsAction::Clamp);
+        assert_eq!(run(&mut app, 10), [OutOfBounds(entity)]);
+        let context = app.world.resource::<RapierContext>();
+        let rb = &context.bodies[context.entity2body()[&entity]];
+        #[cfg(feature = "dim2")]
+        assert_eq!(Vect::from(*rb.translation()), Vect::splat(5.0));
+        #[cfg(feature = "dim3")]
+        assert_eq!(Vect::from(*rb.translation()), Vect::new(5.0, 5.0, 2.5));
+        assert_eq!(Vect::from(*rb.linvel()), Vect::ZERO);
+
+        let (mut app, entity) = new_app(OutOfBoundsAction::SendEvent);
+        assert_eq!(run(&mut app, 10), [OutOfBounds(entity)]);
+
+        let (mut app, entity) = new_app(OutOfBoundsAction::Despawn);
+        assert_eq!(run(&mut app, 10), [OutOfBounds(entity)]);
+        assert!(app.world.get_entity(entity).is_none());
+    }
+
+    #[test]
+    fn kinematic_velocity_estimation_averages_displacements() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let dt = 1.0 / 60.0;
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed { dt, substeps: 1 };
+
+        let entity = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::ball(1.0))
+            .insert(Velocity::default())
+            .insert(KinematicVelocityEstimation { window: 2 })
+            .id();
+        app.update();
+
+        // Move by one unit every other update.
+        for i in 0..10 {
+            if i % 2 == 0 {
+                let mut transform = app.world.get_mut::<Transform>(entity).unwrap();
+                transform.translation.x += 1.0;
+            }
+
+            app.update();
+
+            // The rigid-body reaches its target right away, only its velocity is smoothed.
+            let context = app.world.resource::<RapierContext>();
+            let handle = context.entity2body()[&entity];
+            let x = context.bodies[handle].translation().x;
+            approx::assert_relative_eq!(x, (i / 2 + 1) as f32, epsilon = 1.0e-4);
+
+            if i >= 2 {
+                let velocity = app.world.get::<Velocity>(entity).unwrap();
+                approx::assert_relative_eq!(velocity.linvel.x, 0.5 / dt, epsilon = 1.0e-2);
+            }
+        }
+
+        let transform = app.world.get::<Transform>(entity).unwrap();
+        approx::assert_relative_eq!(transform.translation.x, 5.0, epsilon = 1.0e-4);
+    }
+
+    #[test]
+    fn rolling_friction_stops_rolling_balls() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let ground = Collider::cuboid(100.0, 1.0);
+        #[cfg(feature = "dim3")]
+        let ground = Collider::cuboid(100.0, 1.0, 100.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(ground);
+
+        let mut spawn_ball = |x: f32, rolling_friction: f32| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 2.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(1.0))
+                .insert(Velocity::linear(Vect::X * 2.0))
+                .insert(RollingFriction::new(rolling_friction))
+                .id()
+        };
+        let coasting = spawn_ball(-50.0, 0.0);
+        let braking = spawn_ball(0.0, 0.1);
+
+        for _ in 0..300 {
+            app.update();
+        }
+
+        let speed = |entity| app.world.get::<Velocity>(entity).unwrap().linvel.length();
+        assert!(
+            speed(coasting) > 1.0,
+            "A ball without rolling friction keeps rolling"
+        );
+        assert!(
+            speed(braking) < 1.0e-2,
+            "A ball with rolling friction stops rolling"
+        );
+    }
+
+    #[test]
+    fn rolling_friction_is_applied_after_each_substep() {
+        // The speed of a braking ball after a second, simulated in frames of `substeps` steps.
+        let speed_after_a_second = |substeps: usize| {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+            app.world
+                .resource_mut::<RapierConfiguration>()
+                .timestep_mode = TimestepMode::Fixed {
+                dt: substeps as f32 / 60.0,
+                substeps,
+            };
+
+            #[cfg(feature = "dim2")]
+            let ground = Collider::cuboid(100.0, 1.0);
+            #[cfg(feature = "dim3")]
+            let ground = Collider::cuboid(100.0, 1.0, 100.0);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::default())
+                .insert(RigidBody::Fixed)
+                .insert(ground);
+            let ball = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(1.0))
+                .insert(Velocity::linear(Vect::X * 2.0))
+                .insert(RollingFriction::new(0.01))
+                .id();
+
+            for _ in 0..60 / substeps {
+                app.update();
+            }
+            app.world.get::<Velocity>(ball).unwrap().linvel.length()
+        };
+
+        let speed = speed_after_a_second(1);
+        assert!(speed < 1.8, "The ball brakes");
+        approx::assert_relative_eq!(speed_after_a_second(4), speed, epsilon = 2.0e-2);
+    }
+
+    #[test]
+    fn welded_bodies_move_together() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let mut spawn_ball = |x: f32, linvel: Vect| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(1.0))
+                .insert(Velocity::linear(linvel))
+                .id()
+        };
+        let target = spawn_ball(0.0, Vect::X);
+        let part = spawn_ball(3.0, -Vect::X);
+        app.update();
+
+        let translation =
+            |app: &App, entity| app.world.get::<Transform>(entity).unwrap().translation;
+        let offset = translation(&app, part) - translation(&app, target);
+
+        app.world.entity_mut(part).insert(Weld(target));
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let context = app.world.resource::<RapierContext>();
+        let target_rb = context.bodies.get(context.entity2body[&target]).unwrap();
+        assert_eq!(
+            target_rb.colliders().len(),
+            2,
+            "The colliders should be welded"
+        );
+        approx::assert_relative_eq!(target_rb.linvel().x, 0.0, epsilon = 1.0e-4);
+        approx::assert_relative_eq!(
+            translation(&app, part) - translation(&app, target),
+            offset,
+            epsilon = 1.0e-3
+        );
+
+        app.world.entity_mut(part).remove::<Weld>();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let part_rb = context.bodies.get(context.entity2body[&part]).unwrap();
+        assert!(
+            part_rb.is_dynamic(),
+            "The rigid-body type should be restored"
+        );
+        assert_eq!(
+            part_rb.colliders().len(),
+            1,
+            "The collider should be given back"
+        );
+    }
+
+    #[test]
+    fn chained_welds_form_a_single_compound() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let mut spawn_ball = |x: f32| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(1.0))
+                .insert(Velocity::linear(Vect::Y))
+                .id()
+        };
+        let root = spawn_ball(0.0);
+        let middle = spawn_ball(3.0);
+        let leaf = spawn_ball(6.0);
+        app.update();
+
+        let num_colliders = |app: &App, entity| {
+            let context = app.world.resource::<RapierContext>();
+            context.bodies[context.entity2body[&entity]]
+                .colliders()
+                .len()
+        };
+        let x = |app: &App, entity| app.world.get::<Transform>(entity).unwrap().translation.x;
+
+        // The leaf is welded before its target is welded onto the root.
+        app.world.entity_mut(leaf).insert(Weld(middle));
+        app.update();
+        app.world.entity_mut(middle).insert(Weld(root));
+        app.update();
+
+        assert_eq!(num_colliders(&app, root), 3);
+        assert_eq!(num_colliders(&app, middle), 0);
+        assert_eq!(num_colliders(&app, leaf), 0);
+
+        // A collider added to a welded entity is moved to the compound.
+        let late = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+        app.world.entity_mut(leaf).push_children(&[late]);
+        for _ in 0..30 {
+            app.update();
+        }
+
+        assert_eq!(num_colliders(&app, root), 4);
+        assert_eq!(num_colliders(&app, leaf), 0);
+        approx::assert_relative_eq!(x(&app, middle) - x(&app, root), 3.0, epsilon = 1.0e-3);
+        approx::assert_relative_eq!(x(&app, leaf) - x(&app, root), 6.0, epsilon = 1.0e-3);
+
+        // The leaf stays welded onto the middle once it is split from the root.
+        app.world.entity_mut(middle).remove::<Weld>();
+        app.update();
+
+        assert_eq!(num_colliders(&app, root), 1);
+        assert_eq!(num_colliders(&app, middle), 3);
+        assert_eq!(num_colliders(&app, leaf), 0);
+
+        app.world.entity_mut(leaf).remove::<Weld>();
+        app.update();
+
+        assert_eq!(num_colliders(&app, middle), 1);
+        assert_eq!(num_colliders(&app, leaf), 2);
+    }
+
+    #[test]
+    fn rope_joint_limits_max_distance() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.1))
+            .insert(ImpulseJoint::new(anchor, RopeJointBuilder::new(2.0)))
+            .id();
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        let translation = app.world.get::<Transform>(ball).unwrap().translation;
+        assert!(
+            translation.y < -1.0,
+            "The ball should fall while the rope is slack"
+        );
+        approx::assert_relative_eq!(translation.length(), 2.0, epsilon = 5.0e-2);
+    }
+
+    #[test]
+    fn spring_joint_settles_at_tunable_rest_length() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -3.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.1))
+            .insert(ImpulseJoint::new(
+                anchor,
+                SpringJointBuilder::new(1.0, 50.0, 10.0),
+            ))
+            .id();
+
+        let distance = |app: &App| {
+            app.world
+                .get::<Transform>(ball)
+                .unwrap()
+                .translation
+                .length()
+        };
+        for _ in 0..180 {
+            app.update();
+        }
+        approx::assert_relative_eq!(distance(&app), 1.0, epsilon = 1.0e-2);
+
+        app.world.entity_mut(ball).insert(JointSpring {
+            rest_length: 2.0,
+            stiffness: 50.0,
+            damping: 10.0,
+        });
+        for _ in 0..180 {
+            app.update();
+        }
+        approx::assert_relative_eq!(distance(&app), 2.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    fn rope_joints_are_aimed_before_each_substep() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 15.0,
+            substeps: 8,
+        };
+
+        // The ball whirls around the anchor at the end of the taut rope, covering a large
+        // angle during the substeps of each frame.
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.1))
+            .insert(Velocity::linear(Vect::Y * 10.0))
+            .insert(ImpulseJoint::new(anchor, RopeJointBuilder::new(2.0)))
+            .insert(RampedJointMotor::new(JointAxis::AngX, 0.0, 1.0, 1.0))
+            .id();
+
+        for _ in 0..30 {
+            app.update();
+            let translation = app.world.get::<Transform>(ball).unwrap().translation;
+            approx::assert_relative_eq!(translation.length(), 2.0, epsilon = 5.0e-2);
+        }
+
+        // Only the `X` axis of the Rapier joint is driven by the rope.
+        let context = app.world.resource::<RapierContext>();
+        let handle = app.world.get::<RapierImpulseJointHandle>(ball).unwrap().0;
+        let raw_joint = context.impulse_joints.get(handle).unwrap();
+        assert!(raw_joint.data.motor_axes.contains(JointAxesMask::ANG_X));
+    }
+
+    #[test]
+    fn spring_joints_are_aimed_before_each_substep() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 15.0,
+            substeps: 8,
+        };
+
+        // The ball whirls around the anchor, held at the rest length by a stiff spring.
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.1))
+            .insert(Velocity::linear(Vect::Y * 10.0))
+            .insert(ImpulseJoint::new(
+                anchor,
+                SpringJointBuilder::new(2.0, 10_000.0, 100.0),
+            ))
+            .insert(RampedJointMotor::new(JointAxis::AngX, 0.0, 1.0, 1.0))
+            .id();
+
+        for _ in 0..30 {
+            app.update();
+            let translation = app.world.get::<Transform>(ball).unwrap().translation;
+            approx::assert_relative_eq!(translation.length(), 2.0, epsilon = 5.0e-2);
+        }
+
+        // Tuning the spring only changes the `X` axis of the Rapier joint.
+        app.world.entity_mut(ball).insert(JointSpring {
+            rest_length: 1.5,
+            stiffness: 5_000.0,
+            damping: 50.0,
+        });
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let handle = app.world.get::<RapierImpulseJointHandle>(ball).unwrap().0;
+        let raw_joint = context.impulse_joints.get(handle).unwrap();
+        let motor = raw_joint.data.motors[JointAxis::X as usize];
+        assert_eq!(
+            (motor.target_pos, motor.stiffness, motor.damping),
+            (1.5, 5_000.0, 50.0)
+        );
+        assert!(raw_joint.data.motor_axes.contains(JointAxesMask::ANG_X));
+    }
+
+    #[test]
+    fn breakable_joints_break_above_threshold() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let mut spawn_hanging_ball = |x: f32, break_force: f32| {
+            let joint = FixedJointBuilder::new()
+                .local_anchor1(Vect::X * x)
+                .local_anchor2(Vect::Y * 2.0);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, -2.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(ImpulseJoint::new(anchor, joint))
+                .insert(BreakableJoint::new(break_force, Real::INFINITY))
+                .id()
+        };
+        let weak = spawn_hanging_ball(-2.0, 1.0);
+        let strong = spawn_hanging_ball(2.0, 1.0e6);
+        app.world
+            .entity_mut(strong)
+            .insert(ReadJointForces::default());
+
+        let mut reader = app.world.resource::<Events<JointBroken>>().get_reader();
+        let mut broken = vec![];
+        for _ in 0..30 {
+            app.update();
+            let events = app.world.resource::<Events<JointBroken>>();
+            broken.extend(reader.iter(events).map(|event| event.entity));
+        }
+
+        assert_eq!(broken, vec![weak]);
+        assert!(app.world.get::<ImpulseJoint>(weak).is_none());
+        assert!(app.world.get::<ImpulseJoint>(strong).is_some());
+
+        let translation =
+            |app: &App, entity| app.world.get::<Transform>(entity).unwrap().translation;
+        assert!(
+            translation(&app, weak).y < -2.5,
+            "The weak ball should fall"
+        );
+        approx::assert_relative_eq!(translation(&app, strong).y, -2.0, epsilon = 1.0e-1);
+
+        // The strong joint holds the weight of its ball.
+        let context = app.world.resource::<RapierContext>();
+        let weight = context.bodies[context.entity2body[&strong]].mass()
+            * app.world.resource::<RapierConfiguration>().gravity;
+        let forces = app.world.get::<ReadJointForces>(strong).unwrap();
+        approx::assert_relative_eq!(forces.force, -weight, epsilon = 1.0e-1 * weight.length());
+    }
+
+    #[test]
+    fn breakable_joints_break_under_the_load_of_their_limits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 4,
+        };
+        let gravity = app.world.resource::<RapierConfiguration>().gravity.length();
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        // Horizontal pendulums of unit mass, resting against the lower limit of their hinge,
+        // which holds the torque of their weight.
+        let weight_torque = 2.0 * gravity;
+        let mut spawn_pendulum = |y: f32, break_torque: f32| {
+            #[cfg(feature = "dim2")]
+            let joint = RevoluteJointBuilder::new();
+            #[cfg(feature = "dim3")]
+            let joint = RevoluteJointBuilder::new(Vect::Z);
+            let joint = joint
+                .local_anchor1(Vect::Y * y)
+                .local_anchor2(Vect::X * -2.0)
+                .limits([0.0, 0.5]);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(ColliderMassProperties::Mass(1.0))
+                .insert(ImpulseJoint::new(anchor, joint))
+                .insert(BreakableJoint::new(Real::INFINITY, break_torque))
+                .id()
+        };
+        let weak = spawn_pendulum(-5.0, 0.5 * weight_torque);
+        let strong = spawn_pendulum(5.0, 2.0 * weight_torque);
+
+        let mut reader = app.world.resource::<Events<JointBroken>>().get_reader();
+        let mut broken = vec![];
+        for _ in 0..60 {
+            app.update();
+            let events = app.world.resource::<Events<JointBroken>>();
+            broken.extend(reader.iter(events).map(|event| event.entity));
+        }
+
+        assert_eq!(broken, vec![weak]);
+        assert!(app.world.get::<ImpulseJoint>(weak).is_none());
+        assert!(app.world.get::<ImpulseJoint>(strong).is_some());
+    }
+
+    #[test]
+    fn read_joint_forces_include_the_motors_with_substeps() {
+        for substeps in [1, 4] {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+            app.world
+                .resource_mut::<RapierConfiguration>()
+                .timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps,
+            };
+
+            // A horizontal pendulum held in place by the motor of its hinge.
+            #[cfg(feature = "dim2")]
+            let joint = RevoluteJointBuilder::new();
+            #[cfg(feature = "dim3")]
+            let joint = RevoluteJointBuilder::new(Vect::Z);
+            let joint = joint
+                .local_anchor2(Vect::X * -2.0)
+                .motor_position(0.0, 1.0e4, 1.0e3);
+            let anchor = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::default())
+                .insert(RigidBody::Fixed)
+                .id();
+            let pendulum = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(ImpulseJoint::new(anchor, joint))
+                .insert(ReadJointForces::default())
+                .id();
+
+            for _ in 0..60 {
+                app.update();
+            }
+
+            let context = app.world.resource::<RapierContext>();
+            let weight = context.bodies[context.entity2body[&pendulum]].mass()
+                * app.world.resource::<RapierConfiguration>().gravity;
+            let forces = app.world.get::<ReadJointForces>(pendulum).unwrap();
+            #[cfg(feature = "dim2")]
+            let motor_torque = forces.local_torque;
+            #[cfg(feature = "dim3")]
+            let motor_torque = forces.local_torque.x;
+
+            // The motor balances the torque of the weight around the hinge.
+            let lever_arm = app.world.get::<Transform>(pendulum).unwrap().translation.x;
+            let weight_torque = lever_arm * weight.length();
+            approx::assert_relative_eq!(
+                motor_torque.abs(),
+                weight_torque,
+                epsilon = 5.0e-2 * weight_torque
+            );
+        }
+    }
+
+    #[test]
+    fn disabled_joints_keep_their_handle() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::new(
+                anchor,
+                FixedJointBuilder::new().local_anchor2(Vect::Y * 2.0),
+            ))
+            .id();
+
+        for _ in 0..10 {
+            app.update();
+        }
+        let handle = app.world.get::<RapierImpulseJointHandle>(ball).unwrap().0;
+        let translation = |app: &App| app.world.get::<Transform>(ball).unwrap().translation;
+        approx::assert_relative_eq!(translation(&app).y, -2.0, epsilon = 1.0e-1);
+
+        app.world.entity_mut(ball).insert(JointEnabled(false));
+        for _ in 0..30 {
+            app.update();
+        }
+        assert!(translation(&app).y < -2.5, "The ball should fall");
+        assert_eq!(
+            app.world.get::<RapierImpulseJointHandle>(ball).unwrap().0,
+            handle,
+            "The joint shouldn’t be recreated"
+        );
+
+        app.world.entity_mut(ball).remove::<JointEnabled>();
+        app.update();
+        let context = app.world.resource::<RapierContext>();
+        assert_eq!(
+            context.impulse_joints.get(handle).unwrap().data.locked_axes,
+            JointAxesMask::LOCKED_FIXED_AXES,
+            "The joint should be re-enabled"
+        );
+    }
+
+    #[test]
+    fn multibody_chain_builder_links_the_chain() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let joint = FixedJointBuilder::new()
+            .local_anchor1(Vect::Y * -0.5)
+            .local_anchor2(Vect::Y * 0.5);
+        let mut queue = CommandQueue::default();
+        let links = MultibodyChainBuilder::new(4, Collider::ball(0.25), joint)
+            .with_link(|i, link| {
+                if i == 0 {
+                    link.insert(RigidBody::Fixed);
+                }
+            })
+            .spawn(&mut Commands::new(&mut queue, &app.world));
+        queue.apply(&mut app.world);
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        assert_eq!(links.len(), 4);
+        assert!(app.world.get::<MultibodyJoint>(links[0]).is_none());
+        for pair in links.windows(2) {
+            assert_eq!(
+                app.world.get::<MultibodyJoint>(pair[1]).unwrap().parent,
+                pair[0]
+            );
+        }
+
+        let context = app.world.resource::<RapierContext>();
+        let link_handle = app
+            .world
+            .get::<RapierMultibodyJointHandle>(links[3])
+            .unwrap()
+            .0;
+        let (multibody, _) = context.multibody_joints.get(link_handle).unwrap();
+        assert_eq!(multibody.num_links(), 4);
+
+        let translation = app.world.get::<Transform>(links[3]).unwrap().translation;
+        approx::assert_relative_eq!(translation.y, -3.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn ragdoll_bones_are_jointed_and_switch_modes() {
+        use crate::dynamics::{Ragdoll, RagdollBoneConfig, RagdollJoint, RagdollMode};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut spawn_bone = |name: &str, y: f32| {
+            app.world
+                .spawn()
+                .insert(Name::new(name.to_string()))
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .id()
+        };
+        let hips = spawn_bone("hips", 1.0);
+        let spine = spawn_bone("spine", 0.5);
+        let neck = spawn_bone("neck", 0.25);
+        let head = spawn_bone("head", 0.25);
+        app.world.entity_mut(hips).push_children(&[spine]);
+        app.world.entity_mut(spine).push_children(&[neck]);
+        app.world.entity_mut(neck).push_children(&[head]);
+
+        let skinned_mesh = app
+            .world
+            .spawn()
+            .insert(SkinnedMesh {
+                inverse_bindposes: Default::default(),
+                joints: vec![hips, spine, neck, head],
+            })
+            .id();
+        app.update();
+
+        let ragdoll = Ragdoll::new(RagdollMode::Kinematic)
+            .with_bone("hips", RagdollBoneConfig::new(0.1))
+            .with_bone("spine", RagdollBoneConfig::new(0.1))
+            .with_bone(
+                "head",
+                RagdollBoneConfig::new(0.1).joint(RagdollJoint::Revolute {
+                    axis: Vect::X,
+                    limits: [-0.5, 0.5],
+                }),
+            );
+        app.world.entity_mut(skinned_mesh).insert(ragdoll);
+        app.update();
+        app.update();
+
+        assert!(app.world.get::<ImpulseJoint>(hips).is_none());
+        assert_eq!(
+            app.world.get::<ImpulseJoint>(spine).unwrap().parent,
+            JointParent::Entity(hips)
+        );
+        assert!(app.world.get::<RigidBody>(neck).is_none());
+        // The neck isn’t part of the ragdoll, so the head is attached to the spine.
+        let head_joint = app.world.get::<ImpulseJoint>(head).unwrap();
+        assert_eq!(head_joint.parent, JointParent::Entity(spine));
+        approx::assert_relative_eq!(head_joint.data.local_anchor1(), Vect::Y * 0.5);
+        assert_eq!(
+            *app.world.get::<RigidBody>(head).unwrap(),
+            RigidBody::KinematicPositionBased
+        );
+
+        app.world.get_mut::<Ragdoll>(skinned_mesh).unwrap().mode = RagdollMode::Dynamic;
+        app.update();
+        for bone in [hips, spine, head] {
+            assert_eq!(
+                *app.world.get::<RigidBody>(bone).unwrap(),
+                RigidBody::Dynamic
+            );
+        }
+    }
+
+    #[test]
+    fn connect_in_place_keeps_the_current_pose() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let transform1 =
+            Transform::from_xyz(1.0, 2.0, 0.0).with_rotation(Quat::from_rotation_z(0.5));
+        let transform2 =
+            Transform::from_xyz(3.0, 1.0, 0.0).with_rotation(Quat::from_rotation_z(-1.2));
+        let joint = FixedJointBuilder::connect_in_place(
+            &GlobalTransform::from(transform1),
+            &GlobalTransform::from(transform2),
+            Vect::ZERO,
+        );
+
+        let body1 = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(transform1))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        let body2 = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(transform2))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::new(body1, joint))
+            .id();
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        for (body, expected) in [(body1, transform1), (body2, transform2)] {
+            let transform = app.world.get::<Transform>(body).unwrap();
+            approx::assert_relative_eq!(
+                transform.translation,
+                expected.translation,
+                epsilon = 1.0e-3
+            );
+            approx::assert_relative_eq!(transform.rotation, expected.rotation, epsilon = 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn joint_couplings_drive_gears_and_racks() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let mut spawn_gear = |x: Real, angvel: Real| {
+            #[cfg(feature = "dim2")]
+            let joint = RevoluteJointBuilder::new();
+            #[cfg(feature = "dim3")]
+            let joint = RevoluteJointBuilder::new(Vect::Z);
+            #[cfg(feature = "dim2")]
+            let velocity = Velocity::angular(angvel);
+            #[cfg(feature = "dim3")]
+            let velocity = Velocity::angular(Vect::Z * angvel);
+
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(velocity)
+                .insert(ImpulseJoint::new(ground, joint.local_anchor1(Vect::X * x)))
+                .id()
+        };
+        let gear1 = spawn_gear(-1.0, 2.0);
+        let gear2 = spawn_gear(1.0, 0.0);
+        let rack = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -3.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::default())
+            .insert(ImpulseJoint::new(
+                ground,
+                PrismaticJointBuilder::new(Vect::X).local_anchor1(Vect::Y * -3.0),
+            ))
+            .id();
+
+        app.world
+            .spawn()
+            .insert(JointCoupling::gear(gear1, 10, gear2, 20));
+        app.world
+            .spawn()
+            .insert(JointCoupling::rack_and_pinion(gear2, rack, 0.5));
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        #[cfg(feature = "dim2")]
+        let angvel = |entity| app.world.get::<Velocity>(entity).unwrap().angvel;
+        #[cfg(feature = "dim3")]
+        let angvel = |entity| app.world.get::<Velocity>(entity).unwrap().angvel.z;
+        let rack_linvel = app.world.get::<Velocity>(rack).unwrap().linvel;
+
+        assert!(angvel(gear1) > 0.1, "The first gear should still rotate");
+        approx::assert_relative_eq!(angvel(gear2), -0.5 * angvel(gear1), epsilon = 1.0e-3);
+        approx::assert_relative_eq!(rack_linvel.x, 0.5 * angvel(gear2), epsilon = 1.0e-3);
+    }
+
+    #[test]
+    fn pulley_joints_keep_the_rope_length() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground_anchor1 = Vec3::new(-2.0, 5.0, 0.0);
+        let ground_anchor2 = Vec3::new(2.0, 5.0, 0.0);
+        #[cfg(feature = "dim2")]
+        let (ground_anchor1, ground_anchor2) =
+            (ground_anchor1.truncate(), ground_anchor2.truncate());
+
+        let heavy = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-2.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ColliderMassProperties::Density(3.0))
+            .id();
+        let light = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(PulleyJoint::new(
+                heavy,
+                PulleyJointBuilder::new(ground_anchor1, ground_anchor2, 10.0),
+            ))
+            .id();
+
+        for _ in 0..20 {
+            app.update();
+        }
+
+        let translation = |entity| {
+            let translation = app.world.get::<Transform>(entity).unwrap().translation;
+            #[cfg(feature = "dim2")]
+            return translation.truncate();
+            #[cfg(feature = "dim3")]
+            return translation;
+        };
+        assert!(translation(heavy).y < -0.1, "The heavy body should go down");
+        assert!(translation(light).y > 0.1, "The light body should go up");
+        approx::assert_relative_eq!(
+            (translation(heavy) - ground_anchor1).length()
+                + (translation(light) - ground_anchor2).length(),
+            10.0,
+            epsilon = 0.1
+        );
+    }
+
+    #[test]
+    fn joint_limit_events_are_emitted_above_threshold() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = Vect::ZERO;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let mut spawn_slider = |y: Real, linvel: Real| {
+            let joint = PrismaticJointBuilder::new(Vect::X)
+                .local_anchor1(Vect::Y * y)
+                .limits([-0.5, 0.5]);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(Velocity::linear(Vect::X * linvel))
+                .insert(ImpulseJoint::new(ground, joint))
+                .insert(JointLimitEventThreshold(1.0))
+                .id()
+        };
+        let fast_right = spawn_slider(0.0, 5.0);
+        let fast_left = spawn_slider(2.0, -3.0);
+        spawn_slider(4.0, 0.5);
+
+        let mut reader = app
+            .world
+            .resource::<Events<JointLimitReached>>()
+            .get_reader();
+        let mut reached = vec![];
+        for _ in 0..60 {
+            app.update();
+            let events = app.world.resource::<Events<JointLimitReached>>();
+            reached.extend(reader.iter(events).copied());
+        }
+
+        assert_eq!(reached.len(), 2, "{:?}", reached);
+        let right = reached.iter().find(|e| e.entity == fast_right).unwrap();
+        assert_eq!(right.axis, JointAxis::X);
+        assert_eq!(right.side, JointLimitSide::Max);
+        approx::assert_relative_eq!(right.velocity, 5.0, epsilon = 0.1);
+        let left = reached.iter().find(|e| e.entity == fast_left).unwrap();
+        assert_eq!(left.axis, JointAxis::X);
+        assert_eq!(left.side, JointLimitSide::Min);
+        approx::assert_relative_eq!(left.velocity, 3.0, epsilon = 0.1);
+    }
+
+    #[test]
+    fn joint_axis_descriptions_round_trip() {
+        use crate::dynamics::{
+            GenericJointBuilder, JointAxisDescription, JointAxisMotion, JointAxisMotor,
+        };
+
+        #[cfg(feature = "dim2")]
+        let axes = [JointAxis::X, JointAxis::Y, JointAxis::AngX];
+        #[cfg(feature = "dim3")]
+        let axes = [
+            JointAxis::X,
+            JointAxis::Y,
+            JointAxis::Z,
+            JointAxis::AngX,
+            JointAxis::AngY,
+            JointAxis::AngZ,
+        ];
+        let motor = JointAxisMotor {
+            target_pos: 0.5,
+            target_vel: 1.5,
+            stiffness: 20.0,
+            damping: 2.0,
+            max_force: 50.0,
+        };
+        let descriptions = [
+            JointAxisDescription::LOCKED,
+            JointAxisDescription::limited(-1.0, 2.0),
+            JointAxisDescription::FREE.with_motor(motor),
+            JointAxisDescription::limited(-1.0, 2.0).with_motor(motor),
+        ];
+
+        for axis in axes {
+            for description in descriptions {
+                let joint = GenericJointBuilder::new(JointAxesMask::empty())
+                    .axis(axis, description)
+                    .build();
+                assert_eq!(joint.axis(axis), description, "{:?}", axis);
+                for other in axes.into_iter().filter(|other| *other != axis) {
+                    assert_eq!(joint.axis(other), JointAxisDescription::FREE);
+                }
+
+                // Setting an axis replaces its previous lock, limits and motor.
+                let mut joint = joint;
+                for other in descriptions {
+                    joint.set_axis(axis, other);
+                    assert_eq!(joint.axis(axis), other, "{:?}", axis);
+                }
+            }
+
+            let joint = GenericJointBuilder::new(JointAxesMask::empty())
+                .axis(
+                    axis,
+                    JointAxisDescription::limited(-1.0, 2.0).with_motor(motor),
+                )
+                .lock(axis)
+                .build();
+            assert_eq!(joint.axis(axis).motion, JointAxisMotion::Locked);
+            assert_eq!(joint.axis(axis).motor, None);
+
+            let joint = GenericJointBuilder::new(JointAxesMask::all())
+                .free(axis)
+                .build();
+            assert_eq!(joint.axis(axis), JointAxisDescription::FREE);
+        }
+    }
+
+    #[test]
+    fn changing_joint_motors_wakes_up_the_bodies() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        // A pendulum hanging at rest below its anchor.
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJointBuilder::new();
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJointBuilder::new(Vect::Z);
+        let joint = joint.local_anchor2(Vect::Y);
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.25))
+            .insert(ImpulseJoint::new(anchor, joint))
+            .id();
+
+        let is_sleeping = |app: &App| {
+            let context = app.world.resource::<RapierContext>();
+            context.bodies[context.entity2body()[&body]].is_sleeping()
+        };
+        for _ in 0..300 {
+            app.update();
+        }
+        assert!(is_sleeping(&app));
+
+        app.world
+            .get_mut::<ImpulseJoint>(body)
+            .unwrap()
+            .data
+            .set_motor_velocity(JointAxis::AngX, 2.0, 100.0);
+        app.update();
+        assert!(!is_sleeping(&app));
+
+        for _ in 0..30 {
+            app.update();
+        }
+        let translation = app.world.get::<Transform>(body).unwrap().translation;
+        assert!(translation.x.abs() > 1.0e-2);
+    }
+
+    #[test]
+    fn joints_survive_dynamic_scene_round_trip() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJointBuilder::new();
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJointBuilder::new(Vect::Z);
+        let joint = joint.local_anchor1(Vect::X).limits([-1.0, 1.0]);
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(ImpulseJoint::new(anchor, joint))
+            .id();
+
+        let type_registry = app.world.resource::<TypeRegistryArc>().clone();
+        let scene = DynamicScene::from_world(&app.world, &type_registry);
+        let mut entity_map = EntityMap::default();
+        scene
+            .write_to_world(&mut app.world, &mut entity_map)
+            .unwrap();
+
+        let anchor_copy = entity_map.get(anchor).unwrap();
+        let body_copy = entity_map.get(body).unwrap();
+        let joint_copy = *app.world.get::<ImpulseJoint>(body_copy).unwrap();
+        assert_eq!(joint_copy.parent, JointParent::Entity(anchor_copy));
+        assert_eq!(joint_copy.data, GenericJoint::from(joint));
+
+        app.update();
+        assert!(app.world.get::<RapierImpulseJointHandle>(body).is_some());
+        assert!(app
+            .world
+            .get::<RapierImpulseJointHandle>(body_copy)
+            .is_some());
+    }
+
+    #[test]
+    fn soft_joints_sag_under_gravity() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        let gravity = config.gravity.y;
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let softness = JointSoftness::new(1.0, 1.0);
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::new(
+                anchor,
+                FixedJointBuilder::new()
+                    .local_anchor1(Vect::Y * -1.0)
+                    .softness(softness),
+            ))
+            .id();
+
+        let sag = |app: &mut App| {
+            for _ in 0..240 {
+                app.update();
+            }
+            -1.0 - app.world.get::<Transform>(ball).unwrap().translation.y
+        };
+
+        // The acceleration-based spring balances the gravity.
+        let (stiffness, _) = softness.stiffness_and_damping();
+        approx::assert_relative_eq!(sag(&mut app), -gravity / stiffness, max_relative = 0.05);
+
+        let stiff = JointSoftness::new(20.0, 1.0);
+        app.world.entity_mut(ball).insert(stiff);
+        let (stiffness, _) = stiff.stiffness_and_damping();
+        approx::assert_relative_eq!(sag(&mut app), -gravity / stiffness, max_relative = 0.05);
+    }
+
+    #[test]
+    fn multibody_joint_damping_and_friction_slow_links_down() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJointBuilder::new();
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJointBuilder::new(Vect::Z);
+        let joint = joint.local_anchor2(Vect::X * -1.0);
+
+        // Horizontal pendulums, spaced apart so that they don’t collide.
+        let mut spawn_pendulum = |x: f32, configure: fn(MultibodyJoint) -> MultibodyJoint| {
+            let root = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(RigidBody::Fixed)
+                .id();
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                    x + 1.0,
+                    0.0,
+                    0.0,
+                )))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.25))
+                .insert(configure(MultibodyJoint::new(root, joint)))
+                .id()
+        };
+        let free = spawn_pendulum(0.0, |joint| joint);
+        let damped = spawn_pendulum(5.0, |joint| joint.with_damping(5.0));
+        let stuck = spawn_pendulum(10.0, |joint| joint.with_friction(10.0));
+
+        for _ in 0..20 {
+            app.update();
+        }
+
+        let height = |app: &App, entity| app.world.get::<Transform>(entity).unwrap().translation.y;
+        assert!(height(&app, free) < -0.3);
+        assert!(height(&app, damped) > height(&app, free) + 0.2);
+        approx::assert_relative_eq!(height(&app, stuck), 0.0, epsilon = 1.0e-2);
+
+        // The friction can be changed at runtime.
+        app.world.get_mut::<MultibodyJoint>(stuck).unwrap().friction = 0.0;
+        for _ in 0..20 {
+            app.update();
+        }
+
+        assert!(height(&app, stuck) < -0.3);
+    }
+
+    #[test]
+    fn pending_joints_are_resolved_by_name() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut spawn_node = |name: &str, translation: Vec3| {
+            app.world
+                .spawn()
+                .insert(Name::new(name.to_string()))
+                .insert_bundle(TransformBundle::from(Transform::from_translation(
+                    translation,
+                )))
+                .id()
+        };
+        let base = spawn_node("Base", Vec3::ZERO);
+        let left = spawn_node("Left", Vec3::X);
+        let arm = spawn_node("Arm", Vec3::ZERO);
+        let hand = spawn_node("Hand", Vec3::Y * 2.0);
+        let ball = spawn_node("Ball", Vec3::Y);
+        let lost = spawn_node("Lost", Vec3::Y * 3.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .push_children(&[base, left, arm, ball, lost]);
+        app.world.entity_mut(arm).push_children(&[hand]);
+
+        app.world.entity_mut(base).insert(RigidBody::Fixed);
+        app.world.entity_mut(hand).insert(RigidBody::Fixed);
+        for body in [left, ball, lost] {
+            app.world
+                .entity_mut(body)
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.25));
+        }
+        app.world.entity_mut(left).insert(PendingJoint::new(
+            "Base",
+            FixedJointBuilder::new().local_anchor1(Vect::X),
+        ));
+        app.world.entity_mut(ball).insert(
+            PendingJoint::new(
+                "Arm/Hand",
+                FixedJointBuilder::new().local_anchor1(Vect::Y * -1.0),
+            )
+            .multibody(),
+        );
+        app.world
+            .entity_mut(lost)
+            .insert(PendingJoint::new("Missing", FixedJointBuilder::new()));
+
+        for _ in 0..10 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.get::<ImpulseJoint>(left).unwrap().parent,
+            JointParent::Entity(base)
+        );
+        assert!(app.world.get::<PendingJoint>(left).is_none());
+        assert_eq!(app.world.get::<MultibodyJoint>(ball).unwrap().parent, hand);
+        assert!(app.world.get::<PendingJoint>(ball).is_none());
+        assert!(app.world.get::<PendingJoint>(lost).is_some());
+        assert!(app.world.get::<ImpulseJoint>(lost).is_none());
+
+        let translation = app
+            .world
+            .get::<GlobalTransform>(left)
+            .unwrap()
+            .translation();
+        approx::assert_relative_eq!(translation, Vec3::X, epsilon = 1.0e-2);
+        assert!(app.world.get::<Transform>(lost).unwrap().translation.y < 3.0);
+    }
+
+    #[test]
+    fn joints_anchored_to_the_world_pin_bodies() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let pin = Vec3::new(1.0, 2.0, 0.0);
+        #[cfg(feature = "dim2")]
+        let anchor = pin.truncate();
+        #[cfg(feature = "dim3")]
+        let anchor = pin;
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_translation(pin)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::anchored_to_world(
+                FixedJointBuilder::new().local_anchor1(anchor),
+            ))
+            .id();
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        assert!(app
+            .world
+            .get::<ImpulseJoint>(ball)
+            .unwrap()
+            .is_anchored_to_world());
+        let translation = app.world.get::<Transform>(ball).unwrap().translation;
+        approx::assert_relative_eq!(translation, pin, epsilon = 1.0e-2);
+
+        // The placeholder parent of the reflected joints isn’t the world.
+        assert!(!ImpulseJoint::from_world(&mut app.world).is_anchored_to_world());
+    }
+
+    #[test]
+    fn ramped_joint_motors_follow_a_trapezoidal_profile() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let dt = 1.0 / 60.0;
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed { dt, substeps: 1 };
+        config.gravity = Vect::ZERO;
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let (max_velocity, max_acceleration) = (1.0, 2.0);
+        let slider = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::new(
+                anchor,
+                PrismaticJointBuilder::new(Vect::X),
+            ))
+            .insert(RampedJointMotor::new(
+                JointAxis::X,
+                2.0,
+                max_velocity,
+                max_acceleration,
+            ))
+            .id();
+
+        let mut position = 0.0;
+        let mut velocity = 0.0;
+        for _ in 0..210 {
+            app.update();
+            let new_position = app.world.get::<Transform>(slider).unwrap().translation.x;
+            let new_velocity = (new_position - position) / dt;
+            assert!(new_velocity.abs() <= max_velocity + 1.0e-2);
+            assert!((new_velocity - velocity).abs() <= max_acceleration * dt + 1.0e-2);
+            assert!(new_position <= 2.0 + 1.0e-2);
+            position = new_position;
+            velocity = new_velocity;
+        }
+
+        approx::assert_relative_eq!(position, 2.0, epsilon = 1.0e-2);
+    }
+
+    #[test]
+    #[cfg(feature = "dim3")]
+    fn ropes_hang_winch_and_detach() {
+        use crate::dynamics::{Rope, RopeMesh, RopeSegments};
+        use bevy::render::mesh::{PrimitiveTopology, VertexAttributeValues};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let mesh = app
+            .world
+            .resource_mut::<Assets<Mesh>>()
+            .add(Mesh::new(PrimitiveTopology::TriangleList));
+        let rope = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Rope::new(8, 2.0, 0.05).attach_start(anchor, Vect::ZERO))
+            .insert(RopeMesh::default())
+            .insert(mesh.clone())
+            .id();
+
+        let rope_end = |app: &mut App| {
+            let last = *app
+                .world
+                .get::<RopeSegments>(rope)
+                .unwrap()
+                .segments
+                .last()
+                .unwrap();
+            let half_length = app.world.get::<Rope>(rope).unwrap().segment_length() / 2.0;
+            let transform = app.world.get::<Transform>(last).unwrap();
+            transform.mul_vec3(Vect::Y * -half_length)
+        };
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.get::<RopeSegments>(rope).unwrap().segments.len(),
+            8
+        );
+        approx::assert_relative_eq!(rope_end(&mut app), Vect::Y * -2.0, epsilon = 0.05);
+        let meshes = app.world.resource::<Assets<Mesh>>();
+        match meshes
+            .get(&mesh)
+            .unwrap()
+            .attribute(Mesh::ATTRIBUTE_POSITION)
+        {
+            Some(VertexAttributeValues::Float32x3(positions)) => {
+                assert_eq!(positions.len(), 9 * 9)
+            }
+            _ => panic!("The rope mesh has no positions."),
+        }
+
+        // Winch the rope in progressively.
+        for _ in 0..100 {
+            app.world.get_mut::<Rope>(rope).unwrap().length -= 0.01;
+            app.update();
+        }
+        for _ in 0..120 {
+            app.update();
+        }
+
+        approx::assert_relative_eq!(rope_end(&mut app), Vect::Y * -1.0, epsilon = 0.05);
+
+        app.world.get_mut::<Rope>(rope).unwrap().start = None;
+        for _ in 0..30 {
+            app.update();
+        }
+
+        assert!(rope_end(&mut app).y < -2.0);
+    }
+
+    #[test]
+    fn joint_rigs_are_mirrored() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let joint = RevoluteJointBuilder::new();
+        #[cfg(feature = "dim3")]
+        let joint = RevoluteJointBuilder::new(Vect::Z);
+        let torso = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let left_arm = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.25))
+            .insert(Name::new("LeftArm"))
+            .insert(ImpulseJoint::new(
+                torso,
+                joint
+                    .local_anchor1(Vect::X * 0.5)
+                    .local_anchor2(Vect::X * -0.5)
+                    .limits([-0.5, 0.0]),
+            ))
+            .id();
+
+        let mut queue = CommandQueue::default();
+        let right_arm = Commands::new(&mut queue, &app.world).spawn_mirrored_rig(
+            &[left_arm],
+            MirrorPlane::new(Vect::ZERO, Vect::X),
+            Some(("Left", "Right")),
+        )[0];
+        queue.apply(&mut app.world);
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        assert_eq!(
+            app.world.get::<Name>(right_arm).unwrap().as_str(),
+            "RightArm"
+        );
+        assert_eq!(
+            app.world.get::<ImpulseJoint>(right_arm).unwrap().parent,
+            JointParent::Entity(torso)
+        );
+        assert!(app.world.get::<Collider>(right_arm).is_some());
+
+        let left = *app.world.get::<Transform>(left_arm).unwrap();
+        let right = *app.world.get::<Transform>(right_arm).unwrap();
+        // The left arm hangs down to its limit, and the right arm to its mirrored limit.
+        assert!(left.translation.y < -0.1);
+        approx::assert_relative_eq!(
+            right.translation,
+            left.translation * Vec3::new(-1.0, 1.0, 1.0),
+            epsilon = 1.0e-3
+        );
+    }
+
+    #[test]
+    fn character_controllers_follow_moving_platforms() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let platform_collider = Collider::cuboid(10.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let platform_collider = Collider::cuboid(10.0, 0.5, 10.0);
+        let platform = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(platform_collider)
+            .id();
+        let mut spawn_character = |x: f32, platform_inheritance| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 1.05, 0.0)))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Collider::ball(0.5))
+                .insert(KinematicCharacterController {
+                    platform_inheritance,
+                    ..default()
+                })
+                .id()
+        };
+        let follower = spawn_character(2.0, PlatformInheritance::FULL);
+        let slider = spawn_character(-2.0, PlatformInheritance::NONE);
+
+        for _ in 0..60 {
+            let mut transform = app.world.get_mut::<Transform>(platform).unwrap();
+            transform.translation.x += 0.02;
+            #[cfg(feature = "dim3")]
+            transform.rotate_y(0.01);
+            for character in [follower, slider] {
+                app.world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap()
+                    .translation = Some(Vect::Y * -0.1);
+            }
+            app.update();
+        }
+
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(follower)
+            .unwrap();
+        assert!(output.grounded);
+        assert_eq!(output.ground, Some(platform));
+
+        // The follower lags one step behind the platform.
+        let platform = *app.world.get::<Transform>(platform).unwrap();
+        let follower = *app.world.get::<Transform>(follower).unwrap();
+        let local_follower = platform.compute_matrix().inverse() * follower.compute_matrix();
+        approx::assert_relative_eq!(local_follower.w_axis.x, 2.0, epsilon = 0.05);
+        #[cfg(feature = "dim3")]
+        {
+            let (_, angle) = follower.rotation.to_axis_angle();
+            approx::assert_relative_eq!(angle, 0.59, epsilon = 0.02);
+        }
+
+        let slider = app.world.get::<Transform>(slider).unwrap();
+        approx::assert_relative_eq!(slider.translation.x, -2.0, epsilon = 0.05);
+    }
+
+    #[test]
+    fn character_controllers_fall_and_jump() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let ground_collider = Collider::cuboid(10.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let ground_collider = Collider::cuboid(10.0, 0.5, 10.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(ground_collider);
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 3.0, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::ball(0.5))
+            .insert(KinematicCharacterController {
+                vertical_movement: Some(CharacterVerticalMovement {
+                    jump_speed: 5.0,
+                    ..default()
+                }),
+                ..default()
+            })
+            .id();
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(character)
+            .unwrap();
+        assert!(output.grounded);
+        assert_eq!(output.vertical_velocity, 0.0);
+        let rest_height = app.world.get::<Transform>(character).unwrap().translation.y;
+        approx::assert_relative_eq!(rest_height, 1.0, epsilon = 0.05);
+
+        app.world
+            .get_mut::<KinematicCharacterController>(character)
+            .unwrap()
+            .jump = true;
+        let mut max_height = rest_height;
+        for i in 0..90 {
+            // Jumping again in the air has no effect.
+            if i == 45 {
+                app.world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap()
+                    .jump = true;
+            }
+            app.update();
+            max_height =
+                max_height.max(app.world.get::<Transform>(character).unwrap().translation.y);
+        }
+
+        // The apex of the jump is at `jump_speed² / (2 * gravity)`.
+        approx::assert_relative_eq!(max_height - rest_height, 1.27, epsilon = 0.1);
+        let height = app.world.get::<Transform>(character).unwrap().translation.y;
+        approx::assert_relative_eq!(height, rest_height, epsilon = 0.05);
+    }
+
+    #[test]
+    fn character_controllers_emit_collision_events() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let (ground_collider, wall_collider) =
+            (Collider::cuboid(10.0, 0.5), Collider::cuboid(0.5, 5.0));
+        #[cfg(feature = "dim3")]
+        let (ground_collider, wall_collider) = (
+            Collider::cuboid(10.0, 0.5, 10.0),
+            Collider::cuboid(0.5, 5.0, 10.0),
+        );
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(ground_collider)
+            .id();
+        let wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(3.0, 5.0, 0.0)))
+            .insert(wall_collider)
+            .id();
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::ball(0.5))
+            .insert(KinematicCharacterController {
+                vertical_movement: Some(CharacterVerticalMovement::default()),
+                ..default()
+            })
+            .id();
+
+        let mut collision_reader = app
+            .world
+            .resource::<Events<CharacterCollisionEvent>>()
+            .get_reader();
+        let mut grounded_reader = app.world.resource::<Events<GroundedChanged>>().get_reader();
+        let mut run = |app: &mut App, frames, translation: Vect, jump| {
+            let mut hits = vec![];
+            let mut touches = vec![];
+            let mut grounded = vec![];
+            for _ in 0..frames {
+                let mut controller = app
+                    .world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap();
+                controller.translation = Some(translation);
+                controller.jump = jump;
+                app.update();
+
+                let events = app.world.resource::<Events<CharacterCollisionEvent>>();
+                for event in collision_reader.iter(events) {
+                    match *event {
+                        CharacterCollisionEvent::Hit {
+                            collision, surface, ..
+                        } => hits.push((collision.entity, surface)),
+                        touch => touches.push(touch),
+                    }
+                }
+                let events = app.world.resource::<Events<GroundedChanged>>();
+                grounded.extend(grounded_reader.iter(events).copied());
+            }
+            (hits, touches, grounded)
+        };
+
+        // Land on the ground.
+        let (hits, touches, grounded) = run(&mut app, 60, Vect::ZERO, false);
+        assert!(hits.contains(&(ground, CharacterSurface::Ground)));
+        assert_eq!(
+            touches,
+            [CharacterCollisionEvent::Started(character, ground)]
+        );
+        assert_eq!(grounded, [GroundedChanged(character, true)]);
+
+        // Walk against the wall, then away from it.
+        let (hits, touches, grounded) = run(&mut app, 40, Vect::X * 0.1, false);
+        assert!(hits.contains(&(wall, CharacterSurface::Wall)));
+        assert_eq!(touches, [CharacterCollisionEvent::Started(character, wall)]);
+        assert!(grounded.is_empty());
+        let (_, touches, _) = run(&mut app, 10, Vect::X * -0.1, false);
+        assert_eq!(touches, [CharacterCollisionEvent::Stopped(character, wall)]);
+
+        // Jump.
+        let (_, touches, grounded) = run(&mut app, 10, Vect::ZERO, true);
+        assert_eq!(
+            touches,
+            [CharacterCollisionEvent::Stopped(character, ground)]
+        );
+        assert_eq!(grounded, [GroundedChanged(character, false)]);
+    }
+
+    #[test]
+    fn character_controllers_crouch_and_stand_up() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[cfg(feature = "dim2")]
+        let (ground_collider, ceiling_collider) =
+            (Collider::cuboid(20.0, 0.5), Collider::cuboid(2.0, 0.5));
+        #[cfg(feature = "dim3")]
+        let (ground_collider, ceiling_collider) = (
+            Collider::cuboid(20.0, 0.5, 20.0),
+            Collider::cuboid(2.0, 0.5, 20.0),
+        );
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(ground_collider);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(4.0, 2.0, 0.0)))
+            .insert(ceiling_collider);
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.1, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::capsule_y(0.5, 0.5))
+            .insert(KinematicCharacterController::default())
+            .id();
+
+        let run = |app: &mut App, frames, translation: Vect| {
+            for _ in 0..frames {
+                app.world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap()
+                    .translation = Some(translation);
+                app.update();
+            }
+        };
+        let set_target_height = |app: &mut App, height| {
+            app.world
+                .get_mut::<KinematicCharacterController>(character)
+                .unwrap()
+                .target_height = Some(height);
+        };
+        let segment_height = |app: &App| {
+            app.world
+                .get::<Collider>(character)
+                .unwrap()
+                .as_capsule()
+                .unwrap()
+                .height()
+        };
+
+        // Land, crouch, then walk under the ceiling.
+        run(&mut app, 30, Vect::Y * -0.1);
+        set_target_height(&mut app, 1.2);
+        run(&mut app, 40, Vect::X * 0.1);
+        approx::assert_relative_eq!(segment_height(&app), 0.2, epsilon = 1.0e-5);
+        let translation = app.world.get::<Transform>(character).unwrap().translation;
+        assert!(translation.x > 3.0 && translation.x < 5.0);
+
+        // Standing up is blocked by the ceiling.
+        set_target_height(&mut app, 2.0);
+        run(&mut app, 5, Vect::ZERO);
+        approx::assert_relative_eq!(segment_height(&app), 0.2, epsilon = 1.0e-5);
+        assert!(
+            app.world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap()
+                .resize_blocked
+        );
+
+        // Walk out from under the ceiling, then stand up.
+        run(&mut app, 40, Vect::X * 0.1);
+        approx::assert_relative_eq!(segment_height(&app), 1.0);
+        assert!(
+            !app.world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap()
+                .resize_blocked
+        );
+        let collider = app.world.get::<Collider>(character).unwrap();
+        approx::assert_relative_eq!(collider.as_capsule().unwrap().segment().a().y, -0.5);
+    }
+
+    #[test]
+    fn dynamic_character_controllers_walk_stand_on_slopes_and_jump() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        // A flat ground, and a slope of 30 degrees further along the X axis.
+        #[cfg(feature = "dim2")]
+        let ground_collider = Collider::cuboid(5.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let ground_collider = Collider::cuboid(5.0, 0.5, 5.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(ground_collider.clone());
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(
+                Transform::from_xyz(20.0, -0.5, 0.0)
+                    .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_6)),
+            ))
+            .insert(ground_collider);
+
+        let spawn_character = |app: &mut App, x: f32| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 1.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Velocity::default())
+                .insert(LockedAxes::ROTATION_LOCKED)
+                .insert(Collider::capsule_y(0.5, 0.5))
+                .insert(Friction {
+                    coefficient: 0.0,
+                    combine_rule: CoefficientCombineRule::Min,
+                })
+                .insert(DynamicCharacterController {
+                    desired_velocity: Vect::X * 2.0,
+                    ..default()
+                })
+                .id()
+        };
+        let walker = spawn_character(&mut app, -3.0);
+        let idle = spawn_character(&mut app, 20.0);
+        app.world
+            .get_mut::<DynamicCharacterController>(idle)
+            .unwrap()
+            .desired_velocity = Vect::ZERO;
+
+        for _ in 0..120 {
+            app.update();
+        }
+
+        let output = app
+            .world
+            .get::<DynamicCharacterControllerOutput>(walker)
+            .unwrap();
+        assert!(output.grounded);
+        approx::assert_relative_eq!(output.ground_normal.y, 1.0, epsilon = 1.0e-3);
+        let velocity = app.world.get::<Velocity>(walker).unwrap().linvel;
+        approx::assert_relative_eq!(velocity.x, 2.0, epsilon = 1.0e-3);
+
+        // The idle character doesn’t slide down the slope.
+        let output = app
+            .world
+            .get::<DynamicCharacterControllerOutput>(idle)
+            .unwrap();
+        assert!(output.grounded);
+        approx::assert_relative_eq!(
+            output.ground_normal.y,
+            std::f32::consts::FRAC_PI_6.cos(),
+            epsilon = 1.0e-3
+        );
+        let position = app.world.get::<Transform>(idle).unwrap().translation;
+        for _ in 0..60 {
+            app.update();
+        }
+        let new_position = app.world.get::<Transform>(idle).unwrap().translation;
+        assert!((new_position - position).length() < 0.01);
+
+        // Stop, jump, then land.
+        let mut controller = app
+            .world
+            .get_mut::<DynamicCharacterController>(walker)
+            .unwrap();
+        controller.desired_velocity = Vect::ZERO;
+        controller.jump = true;
+        app.update();
+        app.update();
+        let velocity = app.world.get::<Velocity>(walker).unwrap().linvel;
+        assert!(velocity.y > 4.0);
+        assert!(
+            !app.world
+                .get::<DynamicCharacterControllerOutput>(walker)
+                .unwrap()
+                .grounded
+        );
+        assert!(
+            !app.world
+                .get::<DynamicCharacterController>(walker)
+                .unwrap()
+                .jump
+        );
+
+        for _ in 0..120 {
+            app.update();
+        }
+        assert!(
+            app.world
+                .get::<DynamicCharacterControllerOutput>(walker)
+                .unwrap()
+                .grounded
+        );
+    }
+
+    #[test]
+    fn character_controllers_push_bodies_depending_on_their_mass() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::ZERO;
+
+        // Each character walks toward a box in its own lane.
+        let mut spawn_lane = |y: f32, mass: Real, collision_groups: CollisionGroups, groups| {
+            #[cfg(feature = "dim2")]
+            let box_collider = Collider::cuboid(0.5, 0.5);
+            #[cfg(feature = "dim3")]
+            let box_collider = Collider::cuboid(0.5, 0.5, 0.5);
+            let body = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(box_collider)
+                .insert(ColliderMassProperties::Mass(mass))
+                .insert(collision_groups)
+                .insert(Velocity::default())
+                .id();
+            let character = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Collider::ball(0.5))
+                .insert(KinematicCharacterController {
+                    custom_mass: Some(80.0),
+                    pushing: CharacterPushing {
+                        max_force: 2_000.0,
+                        groups,
+                    },
+                    ..default()
+                })
+                .id();
+            (character, body)
+        };
+        let (crate_pusher, light_crate) = spawn_lane(0.0, 10.0, CollisionGroups::default(), None);
+        let (_, car) = spawn_lane(10.0, 10_000.0, CollisionGroups::default(), None);
+        let (_, filtered_crate) = spawn_lane(
+            20.0,
+            10.0,
+            CollisionGroups::new(crate::geometry::Group::GROUP_2, crate::geometry::Group::ALL),
+            Some(InteractionGroups::new(Group::GROUP_1, Group::GROUP_1)),
+        );
+
+        let mut pushed = false;
+        for _ in 0..30 {
+            let mut controllers = app.world.query::<&mut KinematicCharacterController>();
+            for mut controller in controllers.iter_mut(&mut app.world) {
+                controller.translation = Some(Vect::X * 0.1);
+            }
+            app.update();
+            pushed |= app
+                .world
+                .get::<KinematicCharacterControllerOutput>(crate_pusher)
+                .map(|output| output.push_impulse.x > 0.0)
+                .unwrap_or(false);
+        }
+        assert!(pushed);
+
+        let speed = |app: &App, entity| app.world.get::<Velocity>(entity).unwrap().linvel.x;
+        assert!(speed(&app, light_crate) > 3.0);
+        // The force limit changes the speed of the car by at most 0.2 per second.
+        assert!(speed(&app, car) > 0.0);
+        assert!(speed(&app, car) <= 0.2 * 0.5 + 1.0e-4);
+        assert_eq!(speed(&app, filtered_crate), 0.0);
+    }
+
+    #[test]
+    fn character_controllers_snap_to_ground_while_descending_stairs() {
+        // Returns whether the character stayed grounded while walking down the stairs, and its
+        // last ground and ground normal.
+        let walk_down_stairs = |snap: Option<CharacterLength>| {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+            let mut config = app.world.resource_mut::<RapierConfiguration>();
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 1,
+            };
+
+            // Steps of 1 unit wide and 0.2 unit high.
+            for i in 0..5 {
+                #[cfg(feature = "dim2")]
+                let step_collider = Collider::cuboid(0.5, 0.5);
+                #[cfg(feature = "dim3")]
+                let step_collider = Collider::cuboid(0.5, 0.5, 2.0);
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                        i as f32,
+                        -0.5 - 0.2 * i as f32,
+                        0.0,
+                    )))
+                    .insert(step_collider);
+            }
+            let character = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.52, 0.0)))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Collider::capsule_y(0.1, 0.4))
+                .insert(KinematicCharacterController {
+                    snap_to_ground: None,
+                    snap_to_ground_while_descending: snap,
+                    ..default()
+                })
+                .id();
+
+            let mut stayed_grounded = true;
+            for i in 0..70 {
+                let mut controller = app
+                    .world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap();
+                controller.translation = Some(Vect::X * 0.05 - Vect::Y * 0.001);
+                app.update();
+                // Skip the first frames, until the character lands.
+                if i > 5 {
+                    stayed_grounded &= app
+                        .world
+                        .get::<KinematicCharacterControllerOutput>(character)
+                        .unwrap()
+                        .grounded;
+                }
+            }
+
+            let output = app
+                .world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap();
+            let translation = app.world.get::<Transform>(character).unwrap().translation;
+            assert!(translation.x > 3.0);
+            (stayed_grounded, output.ground, output.ground_normal)
+        };
+
+        let (stayed_grounded, ground, _) = walk_down_stairs(None);
+        assert!(!stayed_grounded);
+        assert!(ground.is_none());
+        let (stayed_grounded, ground, ground_normal) =
+            walk_down_stairs(Some(CharacterLength::Absolute(0.3)));
+        assert!(stayed_grounded);
+        assert!(ground.is_some());
+        approx::assert_relative_eq!(ground_normal.y, 1.0, epsilon = 1.0e-3);
+    }
+
+    #[test]
+    fn character_controllers_pass_through_one_way_platforms() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let (ground_collider, platform_collider) =
+            (Collider::cuboid(5.0, 0.5), Collider::cuboid(2.0, 0.1));
+        #[cfg(feature = "dim3")]
+        let (ground_collider, platform_collider) = (
+            Collider::cuboid(5.0, 0.5, 5.0),
+            Collider::cuboid(2.0, 0.1, 2.0),
+        );
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(ground_collider)
+            .id();
+        let platform = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(platform_collider)
+            .insert(OneWayPlatform::default())
+            .id();
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.1, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::capsule_y(0.5, 0.5))
+            .insert(KinematicCharacterController {
+                vertical_movement: Some(CharacterVerticalMovement {
+                    jump_speed: 8.0,
+                    ..default()
+                }),
+                ..default()
+            })
+            .id();
+
+        let run = |app: &mut App, frames| {
+            for _ in 0..frames {
+                app.update();
+            }
+            let output = app
+                .world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap();
+            let y = app.world.get::<Transform>(character).unwrap().translation.y;
+            (output.grounded, output.ground, y)
+        };
+
+        let (grounded, ground_entity, _) = run(&mut app, 30);
+        assert!(grounded);
+        assert_eq!(ground_entity, Some(ground));
+
+        // Jump through the platform from below, and land on it.
+        app.world
+            .get_mut::<KinematicCharacterController>(character)
+            .unwrap()
+            .jump = true;
+        let (grounded, ground_entity, y) = run(&mut app, 120);
+        assert!(grounded);
+        assert_eq!(ground_entity, Some(platform));
+        assert!(y > 3.0);
+
+        // Drop through the platform.
+        app.world
+            .get_mut::<KinematicCharacterController>(character)
+            .unwrap()
+            .drop_through = true;
+        let (grounded, ground_entity, y) = run(&mut app, 120);
+        assert!(grounded);
+        assert_eq!(ground_entity, Some(ground));
+        assert!(y < 1.1);
+    }
+
+    #[test]
+    fn character_controllers_detect_grabbable_ledges() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let (low_wall_collider, high_wall_collider) =
+            (Collider::cuboid(0.5, 1.05), Collider::cuboid(0.5, 1.5));
+        #[cfg(feature = "dim3")]
+        let (low_wall_collider, high_wall_collider) = (
+            Collider::cuboid(0.5, 1.05, 2.0),
+            Collider::cuboid(0.5, 1.5, 2.0),
+        );
+        let low_wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 1.05, 0.0)))
+            .insert(low_wall_collider)
+            .id();
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-2.0, 1.5, 0.0)))
+            .insert(high_wall_collider);
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::capsule_y(0.5, 0.5))
+            .insert(KinematicCharacterController {
+                ledge_detection: Some(CharacterLedgeDetection::default()),
+                ..default()
+            })
+            .id();
+
+        let run = |app: &mut App, frames, translation: Vect| {
+            for _ in 0..frames {
+                app.world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap()
+                    .translation = Some(translation);
+                app.update();
+            }
+            app.world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap()
+                .ledge
+        };
+
+        // Far from the walls, there is no ledge.
+        assert_eq!(run(&mut app, 2, Vect::X * 0.01), None);
+
+        // Walk toward the low wall: its top is within reach.
+        let ledge = run(&mut app, 60, Vect::X * 0.05).unwrap();
+        assert_eq!(ledge.entity, low_wall);
+        assert!((ledge.grab_point.x - 1.5).abs() < 1.0e-3);
+        assert!((ledge.grab_point.y - 2.1).abs() < 1.0e-3);
+        assert!((ledge.top_normal - Vect::Y).length() < 1.0e-3);
+        assert!((ledge.wall_normal + Vect::X).length() < 1.0e-3);
+
+        // Walk toward the high wall: its top is out of reach.
+        assert_eq!(run(&mut app, 120, Vect::X * -0.05), None);
+    }
+
+    #[test]
+    fn character_controllers_swim_in_fluid_volumes() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let (ground_collider, water_collider) =
+            (Collider::cuboid(10.0, 0.5), Collider::cuboid(3.0, 1.5));
+        #[cfg(feature = "dim3")]
+        let (ground_collider, water_collider) = (
+            Collider::cuboid(10.0, 0.5, 10.0),
+            Collider::cuboid(3.0, 1.5, 3.0),
+        );
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(ground_collider);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, 0.0)))
+            .insert(water_collider)
+            .insert(Sensor)
+            .insert(FluidVolume {
+                density: 1.2,
+                ..default()
+            });
+        let spawn_character = |app: &mut App, x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 1.0, 0.0)))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Collider::capsule_y(0.5, 0.5))
+                .insert(KinematicCharacterController {
+                    vertical_movement: Some(CharacterVerticalMovement::default()),
+                    ..default()
+                })
+                .id()
+        };
+        let swimmer = spawn_character(&mut app, 0.0);
+        let walker = spawn_character(&mut app, 6.0);
+
+        for _ in 0..300 {
+            app.update();
+        }
+
+        // The swimmer floats where its buoyancy balances its gravity.
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(swimmer)
+            .unwrap();
+        assert!(output.swimming);
+        assert!(!output.grounded);
+        assert!((output.submersion - 1.0 / 1.2).abs() < 0.02);
+        let y = app.world.get::<Transform>(swimmer).unwrap().translation.y;
+        assert!((y - 2.33).abs() < 0.05);
+
+        // The walker, outside of the water, stands on the ground.
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(walker)
+            .unwrap();
+        assert!(!output.swimming);
+        assert!(output.grounded);
+        assert_eq!(output.submersion, 0.0);
+    }
+
+    #[test]
+    fn character_controllers_can_be_moved_manually() {
+        let make_app = || {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+            let mut config = app.world.resource_mut::<RapierConfiguration>();
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 1,
+            };
+            config.gravity = Vect::Y * -9.81;
+
+            #[cfg(feature = "dim2")]
+            let (ground_collider, wall_collider) =
+                (Collider::cuboid(10.0, 0.5), Collider::cuboid(0.5, 2.0));
+            #[cfg(feature = "dim3")]
+            let (ground_collider, wall_collider) = (
+                Collider::cuboid(10.0, 0.5, 10.0),
+                Collider::cuboid(0.5, 2.0, 10.0),
+            );
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+                .insert(ground_collider);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(3.0, 2.0, 0.0)))
+                .insert(wall_collider);
+
+            // Let the colliders reach their initial positions in the query pipeline.
+            app.update();
+            app.update();
+            app
+        };
+        let controller = KinematicCharacterController {
+            vertical_movement: Some(CharacterVerticalMovement::default()),
+            ..default()
+        };
+        let shape = Collider::capsule_y(0.5, 0.5);
+
+        // Moved by the built-in system, once its collider reached its initial position.
+        let mut app = make_app();
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, 0.0)))
+            .insert(shape.clone())
+            .id();
+        app.update();
+        app.update();
+        app.world.entity_mut(character).insert(controller.clone());
+
+        // Moved manually, starting from the same state.
+        let mut manual_app = make_app();
+        let start = app.world.get::<Transform>(character).unwrap().translation;
+        #[cfg(feature = "dim2")]
+        let mut manual_position = start.truncate();
+        #[cfg(feature = "dim3")]
+        let mut manual_position = start;
+        let mut manual_output = None;
+        let config = *manual_app.world.resource::<RapierConfiguration>();
+
+        for i in 0..120 {
+            let jump = i == 60;
+            let translation = Vect::X * 0.05;
+
+            let mut system_controller = app
+                .world
+                .get_mut::<KinematicCharacterController>(character)
+                .unwrap();
+            system_controller.translation = Some(translation);
+            system_controller.jump = jump;
+            app.update();
+
+            let mut context = manual_app.world.resource_mut::<RapierContext>();
+            let output = context
+                .move_character(
+                    &KinematicCharacterController {
+                        jump,
+                        ..controller.clone()
+                    },
+                    translation,
+                    &shape,
+                    manual_position,
+                    Rot::default(),
+                    manual_output.as_ref(),
+                    &config,
+                    1.0 / 60.0,
+                    crate::pipeline::QueryFilter::default(),
+                    true,
+                )
+                .unwrap();
+            manual_position += output.effective_translation;
+            manual_output = Some(output);
+
+            let system_output = app
+                .world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap();
+            let manual_output = manual_output.as_ref().unwrap();
+            assert_eq!(system_output.grounded, manual_output.grounded);
+            assert_eq!(
+                system_output.effective_translation,
+                manual_output.effective_translation
+            );
+        }
+
+        // The character walked to the wall, jumped, and landed.
+        assert!(manual_position.x > 1.9 && manual_position.x < 2.0);
+        assert!(manual_output.unwrap().grounded);
+    }
+
+    #[test]
+    fn moving_characters_manually_only_pushes_bodies_if_requested() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world.resource_mut::<RapierConfiguration>().gravity = Vect::ZERO;
+
+        #[cfg(feature = "dim2")]
+        let box_collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let box_collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.5, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(box_collider)
+            .id();
+        app.update();
+        app.update();
+
+        let controller = KinematicCharacterController {
+            custom_mass: Some(80.0),
+            ..default()
+        };
+        let config = *app.world.resource::<RapierConfiguration>();
+        let handle = app.world.get::<RapierRigidBodyHandle>(body).unwrap().0;
+        let move_character = |app: &mut App, push_dynamic_bodies| {
+            let mut context = app.world.resource_mut::<RapierContext>();
+            let output = context
+                .move_character(
+                    &controller,
+                    Vect::X * 0.5,
+                    &Collider::ball(0.5),
+                    Vect::ZERO,
+                    Rot::default(),
+                    None,
+                    &config,
+                    1.0 / 60.0,
+                    crate::pipeline::QueryFilter::default(),
+                    push_dynamic_bodies,
+                )
+                .unwrap();
+            let linvel: Vect = (*context.bodies.get(handle).unwrap().linvel()).into();
+            (output.push_impulse, linvel)
+        };
+
+        let (push_impulse, linvel) = move_character(&mut app, false);
+        assert_eq!(push_impulse, Vect::ZERO);
+        assert_eq!(linvel, Vect::ZERO);
+
+        let (push_impulse, linvel) = move_character(&mut app, true);
+        assert!(push_impulse.x > 0.0);
+        assert!(linvel.x > 0.0);
+    }
+
+    #[test]
+    fn character_controllers_move_all_the_solid_colliders_of_their_body() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        #[cfg(feature = "dim2")]
+        let (wall_collider, shield_collider, sword_collider) = (
+            Collider::cuboid(0.5, 2.0),
+            Collider::cuboid(0.2, 0.2),
+            Collider::cuboid(0.5, 0.1),
+        );
+        #[cfg(feature = "dim3")]
+        let (wall_collider, shield_collider, sword_collider) = (
+            Collider::cuboid(0.5, 2.0, 10.0),
+            Collider::cuboid(0.2, 0.2, 0.2),
+            Collider::cuboid(0.5, 0.1, 0.1),
+        );
+        for x in [-3.0, 3.0] {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 2.0, 0.0)))
+                .insert(wall_collider.clone());
+        }
+
+        // A capsule with a solid shield in front of it, and a sword hitbox behind it.
+        let capsule = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::capsule_y(0.5, 0.5))
+            .id();
+        let shield = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.8, 0.0, 0.0)))
+            .insert(shield_collider)
+            .id();
+        let sword = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-1.5, 0.0, 0.0)))
+            .insert(sword_collider)
+            .insert(Sensor)
+            .id();
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(KinematicCharacterController::default())
+            .push_children(&[capsule, shield, sword])
+            .id();
+
+        let run = |app: &mut App, frames, translation: Vect| {
+            for _ in 0..frames {
+                app.world
+                    .get_mut::<KinematicCharacterController>(character)
+                    .unwrap()
+                    .translation = Some(translation);
+                app.update();
+            }
+            app.world.get::<Transform>(character).unwrap().translation.x
+        };
+
+        // The shield hits the wall before the capsule.
+        let x = run(&mut app, 120, Vect::X * 0.05);
+        assert!(x > 1.4 && x < 1.5);
+
+        // The sword is a sensor, which doesn’t stop the character.
+        let x = run(&mut app, 180, Vect::X * -0.05);
+        assert!(x > -2.0 && x < -1.9);
+    }
+
+    #[test]
+    fn character_controllers_slide_down_steep_slopes() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::Y * -9.81;
+
+        // Ramps of 60 degrees, going up along +X.
+        let angle = PI / 3.0;
+        #[cfg(feature = "dim2")]
+        let (ramp_collider, ramp_rotation) =
+            (Collider::cuboid(10.0, 0.5), Quat::from_rotation_z(angle));
+        #[cfg(feature = "dim3")]
+        let (ramp_collider, ramp_rotation) = (
+            Collider::cuboid(10.0, 0.5, 2.0),
+            Quat::from_rotation_z(angle),
+        );
+        let normal = ramp_rotation * Vec3::Y;
+
+        let spawn_character = |app: &mut App, x, behavior| {
+            let ramp_center = Vec3::new(x, 0.0, 0.0);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(
+                    Transform::from_translation(ramp_center).with_rotation(ramp_rotation),
+                ))
+                .insert(ramp_collider.clone());
+            let surface = ramp_center + normal * 0.5;
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_translation(
+                    surface + normal * 0.55 + Vec3::Y * 0.5,
+                )))
+                .insert(RigidBody::KinematicPositionBased)
+                .insert(Collider::capsule_y(0.5, 0.5))
+                .insert(KinematicCharacterController {
+                    vertical_movement: Some(CharacterVerticalMovement::default()),
+                    steep_slope_behavior: behavior,
+                    ..default()
+                })
+                .id()
+        };
+        let blocked = spawn_character(&mut app, 0.0, SteepSlopeBehavior::Block);
+        let slider = spawn_character(
+            &mut app,
+            30.0,
+            SteepSlopeBehavior::Slide { acceleration: 20.0 },
+        );
+        let start =
+            |app: &App, character| app.world.get::<Transform>(character).unwrap().translation;
+        let (blocked_start, slider_start) = (start(&app, blocked), start(&app, slider));
+
+        let mut reader = app
+            .world
+            .resource::<Events<SteepSlopeChanged>>()
+            .get_reader();
+        let mut events = vec![];
+        for _ in 0..60 {
+            app.update();
+            let steep_slope_events = app.world.resource::<Events<SteepSlopeChanged>>();
+            events.extend(reader.iter(steep_slope_events).copied());
+        }
+
+        for character in [blocked, slider] {
+            let output = app
+                .world
+                .get::<KinematicCharacterControllerOutput>(character)
+                .unwrap();
+            assert!(output.on_steep_slope);
+            assert!((output.slope_angle - angle).abs() < 1.0e-3);
+            assert!(events.contains(&SteepSlopeChanged(character, true)));
+        }
+
+        // The sliding character accelerated down the slope, unlike the blocked one.
+        let slid = |app: &App, character, start: Vec3| {
+            start.x - app.world.get::<Transform>(character).unwrap().translation.x
+        };
+        assert!(slid(&app, blocked, blocked_start) < 0.2);
+        assert!(slid(&app, slider, slider_start) > 0.5);
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(slider)
+            .unwrap();
+        assert!(output.slide_velocity.x < -5.0);
+    }
+
+    #[test]
+    fn character_controllers_detect_nearby_walls() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::ZERO;
+
+        #[cfg(feature = "dim2")]
+        let (wall_collider, translation) = (Collider::cuboid(0.5, 5.0), Vect::Y * 0.01);
+        #[cfg(feature = "dim3")]
+        let (wall_collider, translation) = (Collider::cuboid(0.5, 5.0, 5.0), Vect::Z * -0.01);
+        let near_wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.8, 0.0, 0.0)))
+            .insert(wall_collider.clone())
+            .id();
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-5.0, 0.0, 0.0)))
+            .insert(wall_collider);
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::capsule_y(0.5, 0.5))
+            .insert(KinematicCharacterController {
+                wall_detection: Some(CharacterWallDetection { distance: 1.0 }),
+                ..default()
+            })
+            .id();
+
+        for _ in 0..3 {
+            app.world
+                .get_mut::<KinematicCharacterController>(character)
+                .unwrap()
+                .translation = Some(translation);
+            app.update();
+        }
+
+        // The wall on the right is within reach, the one on the left is too far.
+        let output = app
+            .world
+            .get::<KinematicCharacterControllerOutput>(character)
+            .unwrap();
+        assert_eq!(output.left_wall, None);
+        let wall = output.right_wall.unwrap();
+        assert_eq!(wall.entity, near_wall);
+        assert!((wall.normal + Vect::X).length() < 1.0e-3);
+        assert!((wall.distance - 0.8).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn character_controllers_are_interpolated_between_simulation_steps() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .insert_resource(Time::default())
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.timestep_mode = TimestepMode::Interpolated {
+            dt: 1.0 / 30.0,
+            time_scale: 1.0,
+            substeps: 1,
+        };
+        config.gravity = Vect::ZERO;
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::ball(0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::capsule_y(0.5, 0.5);
+        let character = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(collider)
+            .insert(KinematicCharacterController::default())
+            .insert(CharacterInterpolation::default())
+            .id();
+
+        // Render at 60 FPS, i.e., the simulation advances every other frame.
+        let start = std::time::Instant::now();
+        let mut positions = vec![];
+        for i in 1..=8 {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(i as f32 / 60.0));
+            app.world
+                .get_mut::<KinematicCharacterController>(character)
+                .unwrap()
+                .translation = Some(Vect::X * 0.1);
+            app.update();
+            positions.push(app.world.get::<Transform>(character).unwrap().translation.x);
+        }
+
+        // The character moves by 0.1 per simulation step, which is interpolated over two frames.
+        for (previous, next) in positions[2..].iter().zip(&positions[3..]) {
+            assert!((next - previous - 0.05).abs() < 1.0e-3);
+        }
+    }
+
+    #[test]
+    fn rapier_query_filters_colliders_with_its_query_filter() {
+        #[derive(Component)]
+        struct Target;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let obstacle = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+            .insert(collider.clone())
+            .id();
+        let target = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(4.0, 0.0, 0.0)))
+            .insert(collider)
+            .insert(Target)
+            .id();
+        app.update();
+        app.update();
+
+        let mut state =
+            bevy::ecs::system::SystemState::<(RapierQuery, RapierQuery<With<Target>>)>::new(
+                &mut app.world,
+            );
+        let (all, targets) = state.get_mut(&mut app.world);
+
+        let hit = all.cast_ray(Vect::ZERO, Vect::X, Real::MAX, true, QueryFilter::default());
+        assert_eq!(hit, Some((obstacle, 1.5)));
+        let hit = targets.cast_ray(Vect::ZERO, Vect::X, Real::MAX, true, QueryFilter::default());
+        assert_eq!(hit, Some((target, 3.5)));
+
+        // The predicate of the filter is still applied.
+        let predicate = |entity| entity != target;
+        let filter = QueryFilter::default().predicate(&predicate);
+        assert_eq!(
+            targets.cast_ray(Vect::ZERO, Vect::X, Real::MAX, true, filter),
+            None
+        );
+
+        let mut found = vec![];
+        targets.intersections_with_point(Vect::X * 4.0, QueryFilter::default(), |entity| {
+            found.push(entity);
+            true
+        });
+        assert_eq!(found, vec![target]);
+    }
+
+    #[test]
+    fn cast_ray_all_returns_sorted_hits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let walls: Vec<_> = [6.0, 2.0, 4.0, 8.0]
+            .into_iter()
+            .map(|x| {
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                    .insert(collider.clone())
+                    .id()
+            })
+            .collect();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let hits =
+            context.cast_ray_all(Vect::ZERO, Vect::X, 7.0, true, QueryFilter::default(), None);
+        let entities: Vec<_> = hits.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(entities, vec![walls[1], walls[2], walls[0]]);
+        assert!((hits[2].1.toi - 5.5).abs() < 1.0e-5);
+
+        let hits = context.cast_ray_all(
+            Vect::ZERO,
+            Vect::X,
+            Real::MAX,
+            true,
+            QueryFilter::default(),
+            Some(2),
+        );
+        let entities: Vec<_> = hits.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(entities, vec![walls[1], walls[2]]);
+    }
+
+    #[test]
+    fn cast_shape_all_returns_sorted_hits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        // The ray cast from the center of the shape misses the wall above it, but the shape doesn’t.
+        let walls: Vec<_> = [(6.0, 0.0), (2.0, 0.0), (4.0, 0.8), (8.0, 0.0)]
+            .into_iter()
+            .map(|(x, y)| {
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(x, y, 0.0)))
+                    .insert(collider.clone())
+                    .id()
+            })
+            .collect();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let hits = context.cast_shape_all(
+            Vect::ZERO,
+            Rot::default(),
+            Vect::X,
+            &Collider::ball(0.5),
+            6.5,
+            QueryFilter::default(),
+            None,
+        );
+        let entities: Vec<_> = hits.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(entities, vec![walls[1], walls[2], walls[0]]);
+        assert!((hits[0].1.toi - 1.0).abs() < 1.0e-3);
+        assert!((hits[0].1.normal1 + Vect::X).length() < 1.0e-3);
+        assert!((hits[2].1.toi - 5.0).abs() < 1.0e-3);
+
+        let hits = context.cast_shape_all(
+            Vect::ZERO,
+            Rot::default(),
+            Vect::X,
+            &Collider::ball(0.5),
+            Real::MAX,
+            QueryFilter::default(),
+            Some(1),
+        );
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, walls[1]);
+    }
+
+    #[test]
+    fn cast_ray_from_screen_picks_the_collider_under_the_cursor() {
+        use bevy::render::camera::CameraProjection;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(1.0, 1.0);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(1.0, 1.0, 1.0);
+        let target = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(3.0, 0.0, 0.0)))
+            .insert(collider)
+            .id();
+        app.update();
+        app.update();
+
+        // A 20x20 orthographic camera looking toward -Z.
+        let mut projection = OrthographicProjection::default();
+        projection.update(20.0, 20.0);
+        let camera_transform = Transform::from_xyz(0.0, 0.0, 10.0);
+        let ndc_to_world =
+            camera_transform.compute_matrix() * projection.get_projection_matrix().inverse();
+
+        let context = app.world.resource::<RapierContext>();
+        let (entity, hit) = context
+            .cast_ray_from_ndc(ndc_to_world, Vec2::new(0.3, 0.05), QueryFilter::default())
+            .unwrap();
+        assert_eq!(entity, target);
+        #[cfg(feature = "dim2")]
+        assert!((hit.point - Vect::new(3.0, 0.5)).length() < 1.0e-3);
+        #[cfg(feature = "dim3")]
+        {
+            assert!((hit.point - Vect::new(3.0, 0.5, 1.0)).length() < 1.0e-3);
+            assert!((hit.normal - Vect::Z).length() < 1.0e-3);
+        }
+
+        assert_eq!(
+            context.cast_ray_from_ndc(ndc_to_world, Vec2::new(-0.3, 0.05), QueryFilter::default()),
+            None
+        );
+    }
+
+    #[test]
+    fn query_filters_include_the_entities_matching_a_query() {
+        #[derive(Component)]
+        struct Enemy;
+        #[derive(Component)]
+        struct Dead;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 0.0, 0.0)))
+            .insert(collider.clone());
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(4.0, 0.0, 0.0)))
+            .insert(collider.clone())
+            .insert(Enemy)
+            .insert(Dead);
+        let enemy = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(6.0, 0.0, 0.0)))
+            .insert(collider)
+            .insert(Enemy)
+            .id();
+        app.update();
+        app.update();
+
+        let mut state = bevy::ecs::system::SystemState::<(
+            Res<RapierContext>,
+            Query<(), (With<Enemy>, Without<Dead>)>,
+        )>::new(&mut app.world);
+        let (context, living_enemies) = state.get(&app.world);
+
+        let filter = QueryFilter::default().with_query(&living_enemies);
+        let hit = context.cast_ray(Vect::ZERO, Vect::X, Real::MAX, true, filter);
+        assert_eq!(hit, Some((enemy, 5.5)));
+
+        let mut hits = vec![];
+        context.intersections_with_ray(Vect::ZERO, Vect::X, Real::MAX, true, filter, |e, _| {
+            hits.push(e);
+            true
+        });
+        assert_eq!(hits, vec![enemy]);
+    }
+
+    #[test]
+    fn distance_queries_between_colliders() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let [a, b, c] = [0.0, 3.0, 3.5].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(collider.clone())
+                .id()
+        });
+        let no_collider = app.world.spawn().id();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        assert!((context.distance_to_collider(Vect::Y * 2.0, a).unwrap() - 1.5).abs() < 1.0e-5);
+        assert_eq!(context.distance_to_collider(Vect::ZERO, a), Some(0.0));
+        assert_eq!(context.distance_to_collider(Vect::ZERO, no_collider), None);
+
+        let distance = context
+            .shape_distance_to_collider(Vect::Y * 3.0, Rot::default(), &Collider::ball(0.5), a)
+            .unwrap();
+        assert!((distance - 2.0).abs() < 1.0e-5);
+
+        assert!((context.distance_between(a, b).unwrap() - 2.0).abs() < 1.0e-5);
+        assert_eq!(context.distance_between(b, c), Some(0.0));
+
+        match context.closest_points_between(a, b, 5.0) {
+            Some(ClosestPoints::WithinMargin(p1, p2)) => {
+                assert!((p1.x - 0.5).abs() < 1.0e-5);
+                assert!((p2.x - 2.5).abs() < 1.0e-5);
+            }
+            other => panic!("Unexpected closest points: {:?}", other),
+        }
+        assert_eq!(
+            context.closest_points_between(a, b, 1.0),
+            Some(ClosestPoints::Disjoint)
+        );
+        assert_eq!(
+            context.closest_points_between(b, c, 1.0),
+            Some(ClosestPoints::Intersecting)
+        );
+    }
+
+    #[test]
+    fn ray_casters_report_their_hits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let [ground, block] = [0.0, 2.0].map(|y| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .insert(collider.clone())
+                .id()
+        });
+        // The caster is upside down, and its own collider is ignored.
+        let caster = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(
+                Transform::from_xyz(0.0, 5.0, 0.0).with_rotation(Quat::from_rotation_z(PI)),
+            ))
+            .insert(Collider::ball(0.2))
+            .insert(RayCaster {
+                direction: Vect::Y,
+                ..default()
+            })
+            .id();
+        app.update();
+        app.update();
+
+        let hits = app.world.get::<RayHits>(caster).unwrap();
+        assert_eq!(hits.0.len(), 1);
+        let (entity, hit) = hits.first().unwrap();
+        assert_eq!(*entity, block);
+        assert!((hit.toi - 2.5).abs() < 1.0e-3);
+        assert!((hit.normal - Vect::Y).length() < 1.0e-3);
+
+        app.world.get_mut::<RayCaster>(caster).unwrap().mode = RayCastMode::All { max_hits: None };
+        app.update();
+
+        let hits = app.world.get::<RayHits>(caster).unwrap();
+        let entities: Vec<_> = hits.0.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(entities, vec![block, ground]);
+    }
+
+    #[test]
+    fn shape_casters_report_their_hits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        // The center of the shape passes next to the block, but the shape doesn’t.
+        let [ground, block] = [(0.0, 0.0), (0.9, 2.0)].map(|(x, y)| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, y, 0.0)))
+                .insert(collider.clone())
+                .id()
+        });
+        let caster = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 4.0, 0.0)))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::ball(0.2))
+            .insert(ShapeCaster {
+                offset: Vect::Y,
+                ..ShapeCaster::new(Collider::ball(0.5), -Vect::Y, 10.0)
+            })
+            .id();
+        app.update();
+        app.update();
+
+        let hits = app.world.get::<ShapeHits>(caster).unwrap();
+        assert_eq!(hits.0.len(), 1);
+        let (entity, hit) = hits.first().unwrap();
+        assert_eq!(*entity, block);
+        assert!(hit.toi > 1.5 && hit.toi < 2.5);
+
+        app.world.get_mut::<ShapeCaster>(caster).unwrap().mode =
+            ShapeCastMode::All { max_hits: None };
+        app.update();
+
+        let hits = app.world.get::<ShapeHits>(caster).unwrap();
+        let entities: Vec<_> = hits.0.iter().map(|(entity, _)| *entity).collect();
+        assert_eq!(entities, vec![block, ground]);
+        assert!((hits.0[1].1.toi - 4.0).abs() < 1.0e-3);
+    }
+
+    #[test]
+    fn cast_rays_batch_returns_the_hits_in_order() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let blocks: Vec<_> = (0..10)
+            .map(|i| {
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                        i as f32 * 2.0,
+                        5.0,
+                        0.0,
+                    )))
+                    .insert(collider.clone())
+                    .id()
+            })
+            .collect();
+        app.update();
+        app.update();
+
+        // Rays toward each block, with a ray missing all of them in between.
+        let rays: Vec<_> = (0..20).map(|i| (Vect::X * i as f32, Vect::Y)).collect();
+        let context = app.world.resource::<RapierContext>();
+        let hits = context.cast_rays_batch(&rays, Real::MAX, true, QueryFilter::default());
+        assert_eq!(hits.len(), rays.len());
+        for (i, hit) in hits.iter().enumerate() {
+            if i % 2 == 0 {
+                let (entity, intersection) = hit.unwrap();
+                assert_eq!(entity, blocks[i / 2]);
+                assert!((intersection.toi - 4.5).abs() < 1.0e-3);
+            } else {
+                assert_eq!(*hit, None);
+            }
+        }
+
+        // The predicate is still applied.
+        let predicate = |entity| entity != blocks[0];
+        let filter = QueryFilter::default().predicate(&predicate);
+        let hits = context.cast_rays_batch(&rays[..3], Real::MAX, true, filter);
+        assert_eq!(hits[0], None);
+        assert_eq!(hits[2].unwrap().0, blocks[1]);
+    }
+
+    #[test]
+    fn contact_pair_data_between_two_colliders() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let ground_collider = Collider::cuboid(5.0, 0.5);
+        #[cfg(feature = "dim3")]
+        let ground_collider = Collider::cuboid(5.0, 0.5, 5.0);
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(ground_collider)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        let sensor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, 0.0)))
+            .insert(Collider::ball(0.5))
+            .insert(Sensor)
+            .id();
+        let far = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(20.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+
+        for _ in 0..5 {
+            app.update();
+        }
+
+        let context = app.world.resource::<RapierContext>();
+        let data = context.contact_pair_data(ground, ball).unwrap();
+        assert_eq!(context.contact_pair_data(ball, ground), Some(data.clone()));
+        let (manifold, contact) = data.find_deepest_contact().unwrap();
+        let ground_normal = if data.collider1 == ground {
+            manifold.normal
+        } else {
+            -manifold.normal
+        };
+        let ground_point = if data.collider1 == ground {
+            contact.point1
+        } else {
+            contact.point2
+        };
+        assert!((ground_normal - Vect::Y).length() < 1.0e-3);
+        assert!((ground_point.y - 0.5).abs() < 1.0e-2);
+        assert!(contact.dist < 1.0e-2);
+        assert!(data
+            .manifolds
+            .iter()
+            .flat_map(|manifold| &manifold.points)
+            .any(|point| point.impulse > 0.0));
+        assert!(data.manifolds.iter().all(
+            |manifold| manifold.rigid_body1 == Some(ball) || manifold.rigid_body2 == Some(ball)
+        ));
+
+        assert_eq!(context.contact_pair_data(ground, far), None);
+        assert_eq!(
+            context.contact_pair_data(ground, Entity::from_raw(1000)),
+            None
+        );
+        assert_eq!(context.intersection_pair(sensor, ball), Some(true));
+        assert_ne!(context.intersection_pair(sensor, far), Some(true));
+    }
+
+    #[test]
+    fn region_queries_report_the_colliders_in_the_region() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let [a, b, c] = [0.0, 3.0, 6.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(Collider::ball(0.5))
+                .id()
+        });
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let region = Region::new(Vect::X * 4.0 + Vect::Y, -Vect::ONE);
+        let mut hits: Vec<_> = context
+            .colliders_with_aabb_intersecting(region, QueryFilter::default())
+            .collect();
+        hits.sort();
+        assert_eq!(hits, vec![a, b]);
+
+        let filter = QueryFilter {
+            exclude_collider: Some(a),
+            ..default()
+        };
+        let hits: Vec<_> = context
+            .colliders_with_aabb_intersecting(region, filter)
+            .collect();
+        assert_eq!(hits, vec![b]);
+
+        let aabb = Aabb::from_min_max(Vec3::new(5.0, -1.0, -1.0), Vec3::new(7.0, 1.0, 1.0));
+        let hits: Vec<_> = context
+            .colliders_with_aabb_intersecting(aabb, QueryFilter::default())
+            .collect();
+        assert_eq!(hits, vec![c]);
+
+        #[cfg(all(feature = "dim2", feature = "debug-render"))]
+        {
+            let rect = bevy::sprite::Rect {
+                min: Vec2::new(2.0, -1.0),
+                max: Vec2::new(4.0, 1.0),
+            };
+            let hits: Vec<_> = context
+                .colliders_with_aabb_intersecting(rect, QueryFilter::default())
+                .collect();
+            assert_eq!(hits, vec![b]);
+        }
+    }
+
+    #[test]
+    fn triangle_hits_are_mapped_to_the_mesh_triangles() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let (vertices, indices) = (
+            vec![
+                Vect::new(-1.0, -1.0),
+                Vect::new(1.0, -1.0),
+                Vect::new(1.0, 1.0),
+                Vect::new(-1.0, 1.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        );
+        #[cfg(feature = "dim3")]
+        let (vertices, indices) = (
+            vec![
+                Vect::new(-1.0, 0.0, -1.0),
+                Vect::new(1.0, 0.0, -1.0),
+                Vect::new(1.0, 0.0, 1.0),
+                Vect::new(-1.0, 0.0, 1.0),
+            ],
+            vec![[0, 2, 1], [0, 3, 2]],
+        );
+        let mesh = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(10.0, 0.0, 0.0)))
+            .insert(Collider::trimesh(vertices.clone(), indices.clone()))
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let interpolate = |hit: TriangleHit| {
+            let [a, b, c] = indices[hit.triangle as usize].map(|i| vertices[i as usize]);
+            a * hit.barycentric[0] + b * hit.barycentric[1] + c * hit.barycentric[2]
+        };
+
+        #[cfg(feature = "dim2")]
+        let (origin, expected_triangle) = (Vect::new(10.5, 5.0), 1);
+        #[cfg(feature = "dim3")]
+        let (origin, expected_triangle) = (Vect::new(10.5, 5.0, -0.5), 0);
+        let (entity, inter) = context
+            .cast_ray_and_get_normal(origin, -Vect::Y, Real::MAX, true, QueryFilter::default())
+            .unwrap();
+        assert_eq!(entity, mesh);
+        let hit = context
+            .triangle_hit(mesh, inter.point, inter.feature)
+            .unwrap();
+        assert_eq!(hit.triangle, expected_triangle);
+        assert!(!hit.back_face);
+        let local_point = inter.point - Vect::X * 10.0;
+        assert!((interpolate(hit) - local_point).length() < 1.0e-4);
+
+        #[cfg(feature = "dim3")]
+        {
+            let (_, inter) = context
+                .cast_ray_and_get_normal(
+                    Vect::new(9.5, -5.0, 0.5),
+                    Vect::Y,
+                    Real::MAX,
+                    true,
+                    QueryFilter::default(),
+                )
+                .unwrap();
+            let hit = context
+                .triangle_hit(mesh, inter.point, inter.feature)
+                .unwrap();
+            assert_eq!(hit.triangle, 1);
+            assert!(hit.back_face);
+        }
+
+        #[cfg(feature = "dim2")]
+        let point = Vect::new(10.5, -1.5);
+        #[cfg(feature = "dim3")]
+        let point = Vect::new(9.5, 0.3, 0.5);
+        let hit = context.closest_triangle(mesh, point).unwrap();
+        #[cfg(feature = "dim2")]
+        assert_eq!(hit.triangle, 0);
+        #[cfg(feature = "dim3")]
+        assert_eq!(hit.triangle, 1);
+        assert!(!hit.back_face);
+
+        assert_eq!(context.closest_triangle(ball, Vect::ZERO), None);
+    }
+
+    #[test]
+    fn nearest_colliders_are_sorted_by_distance() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let [a, b, c, d] = [2.0, 5.0, 90.0, -3.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(Collider::ball(0.5))
+                .id()
+        });
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let nearest = context.nearest_colliders(Vect::ZERO, 3, QueryFilter::default());
+        assert_eq!(
+            nearest.iter().map(|hit| hit.0).collect::<Vec<_>>(),
+            vec![a, d, b]
+        );
+        assert!((nearest[0].1 - 1.5).abs() < 1.0e-5);
+        assert!((nearest[0].2 - Vect::X * 1.5).length() < 1.0e-5);
+        assert!((nearest[2].1 - 4.5).abs() < 1.0e-5);
+
+        let nearest = context.nearest_colliders(Vect::ZERO, 10, QueryFilter::default());
+        assert_eq!(
+            nearest.iter().map(|hit| hit.0).collect::<Vec<_>>(),
+            vec![a, d, b, c]
+        );
+
+        let filter = QueryFilter {
+            exclude_collider: Some(a),
+            ..default()
+        };
+        let nearest = context.nearest_colliders(Vect::X * 5.2, 1, filter);
+        assert_eq!(nearest, vec![(b, 0.0, Vect::X * 5.2)]);
+        assert!(context
+            .nearest_colliders(Vect::ZERO, 0, QueryFilter::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn cast_shape_along_path_reports_the_first_blocking_collider() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 0.5);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 0.5, 0.5);
+        let obstacle = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 3.0, 0.0)))
+            .insert(collider)
+            .id();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let shape = Collider::ball(0.25);
+        let path = [Vect::ZERO, Vect::X * 5.0, Vect::X * 5.0 + Vect::Y * 5.0];
+        let hit = context
+            .cast_shape_along_path(&path, Rot::default(), &shape, QueryFilter::default())
+            .unwrap();
+        assert_eq!(hit.entity, obstacle);
+        assert_eq!(hit.segment, 1);
+        assert!((hit.distance - 7.25).abs() < 1.0e-3);
+        assert!((hit.position - (Vect::X * 5.0 + Vect::Y * 2.25)).length() < 1.0e-3);
+
+        let clear_path = [Vect::ZERO, Vect::X * 3.0, Vect::X * 3.0 + Vect::Y * 5.0];
+        assert_eq!(
+            context.cast_shape_along_path(
+                &clear_path,
+                Rot::default(),
+                &shape,
+                QueryFilter::default()
+            ),
+            None
+        );
+
+        let filter = QueryFilter {
+            exclude_collider: Some(obstacle),
+            ..default()
+        };
+        assert_eq!(
+            context.cast_shape_along_path(&path, Rot::default(), &shape, filter),
+            None
+        );
+    }
+
+    #[test]
+    fn line_of_sight_ignores_the_entities_themselves() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let guard = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .with_children(|children| {
+                children
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(0.5, 0.0, 0.0)))
+                    .insert(Collider::ball(1.0));
+            })
+            .id();
+        let target = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(10.0, 0.0, 0.0)))
+            .insert(Collider::ball(1.0))
+            .id();
+        #[cfg(feature = "dim2")]
+        let wall_collider = Collider::cuboid(0.5, 1.0);
+        #[cfg(feature = "dim3")]
+        let wall_collider = Collider::cuboid(0.5, 1.0, 5.0);
+        let wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .insert(wall_collider)
+            .id();
+        let nothing = app.world.spawn().id();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let filter = QueryFilter::default();
+        assert_eq!(
+            context.line_of_sight(guard, Vect::ZERO, target, Vect::ZERO, filter),
+            Some(LineOfSight::Blocked(wall))
+        );
+        assert_eq!(
+            context.line_of_sight(guard, Vect::Y * 3.0, target, Vect::Y * 3.0, filter),
+            Some(LineOfSight::Clear)
+        );
+
+        let without_wall = QueryFilter {
+            exclude_collider: Some(wall),
+            ..default()
+        };
+        assert!(context
+            .line_of_sight(guard, Vect::ZERO, target, Vect::ZERO, without_wall)
+            .unwrap()
+            .is_clear());
+        assert_eq!(
+            context.line_of_sight(guard, Vect::ZERO, nothing, Vect::ZERO, filter),
+            None
+        );
+    }
+
+    #[test]
+    fn sensor_intersections_track_the_entities_inside_sensors() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let sensor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::ball(1.0))
+            .insert(Sensor)
+            .insert(TrackIntersections)
+            .id();
+        let untracked = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::ball(1.0))
+            .insert(Sensor)
+            .id();
+        let [inside, outside] = [0.5, 5.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(GravityScale(0.0))
+                .insert(Collider::ball(0.25))
+                .id()
+        });
+        app.update();
+        app.update();
+        app.update();
+
+        let intersections = app.world.get::<SensorIntersections>(sensor).unwrap();
+        assert_eq!(intersections.iter().collect::<Vec<_>>(), vec![inside]);
+        assert!(!intersections.contains(outside));
+        assert!(app.world.get::<SensorIntersections>(untracked).is_none());
+
+        app.world
+            .get_mut::<Transform>(inside)
+            .unwrap()
+            .translation
+            .x = 10.0;
+        app.update();
+        app.update();
+
+        assert!(app
+            .world
+            .get::<SensorIntersections>(sensor)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn visible_entities_are_in_the_cone_and_not_occluded() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut spawn = |translation: Vect, collider: Collider| {
+            #[cfg(feature = "dim2")]
+            let translation = translation.extend(0.0);
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_translation(
+                    translation,
+                )))
+                .insert(collider)
+                .id()
+        };
+        let viewer = spawn(Vect::ZERO, Collider::ball(0.5));
+        #[cfg(feature = "dim2")]
+        let wall = spawn(Vect::X * 6.5, Collider::cuboid(0.2, 0.5));
+        #[cfg(feature = "dim3")]
+        let wall = spawn(Vect::X * 6.5, Collider::cuboid(0.2, 0.5, 0.5));
+        let ahead = spawn(Vect::X * 5.0, Collider::ball(0.25));
+        let aside = spawn(Vect::X * 3.0 + Vect::Y, Collider::ball(0.25));
+        let _above = spawn(Vect::Y * 5.0, Collider::ball(0.25));
+        let _too_far = spawn(Vect::X * 15.0, Collider::ball(0.25));
+        let _behind_wall = spawn(Vect::X * 8.0, Collider::ball(0.25));
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        let cone = VisionCone {
+            origin: Vect::ZERO,
+            direction: Vect::X,
+            half_angle: PI / 4.0,
+            range: 10.0,
+        };
+        let not_wall = |entity| entity != wall;
+        let filter = QueryFilter {
+            exclude_collider: Some(viewer),
+            ..default()
+        };
+        let visible = context.visible_entities(cone, filter.predicate(&not_wall), filter);
+        assert_eq!(
+            visible.iter().map(|hit| hit.0).collect::<Vec<_>>(),
+            vec![aside, ahead]
+        );
+        assert!((visible[1].1 - 5.0).abs() < 1.0e-5);
+
+        let narrow_cone = VisionCone {
+            half_angle: 0.1,
+            ..cone
+        };
+        let visible = context.visible_entities(narrow_cone, filter.predicate(&not_wall), filter);
+        assert_eq!(
+            visible.iter().map(|hit| hit.0).collect::<Vec<_>>(),
+            vec![ahead]
+        );
+    }
+
+    #[test]
+    fn async_queries_are_resolved_within_the_budget() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let collider = Collider::cuboid(0.5, 5.0);
+        #[cfg(feature = "dim3")]
+        let collider = Collider::cuboid(0.5, 5.0, 5.0);
+        let wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .insert(collider)
+            .id();
+        let agent = app.world.spawn().id();
+        app.update();
+        app.update();
+
+        let ids = {
+            let mut queue = app.world.resource_mut::<AsyncQueryQueue>();
+            queue.budget = 2;
+            vec![
+                queue.push(AsyncQuery::ray(agent, Vect::ZERO, Vect::X, Real::MAX, true)),
+                queue.push(AsyncQuery::ray(
+                    agent,
+                    Vect::ZERO,
+                    -Vect::X,
+                    Real::MAX,
+                    true,
+                )),
+                queue.push(AsyncQuery::shape(
+                    agent,
+                    Collider::ball(0.5),
+                    Vect::ZERO,
+                    Rot::default(),
+                    Vect::X,
+                    Real::MAX,
+                )),
+                queue.push(
+                    AsyncQuery::ray(agent, Vect::X * 5.0, Vect::X, Real::MAX, true).excluding(wall),
+                ),
+            ]
+        };
+
+        let mut reader = app
+            .world
+            .resource::<Events<AsyncQueryResult>>()
+            .get_reader();
+        app.update();
+        let results: Vec<_> = reader
+            .iter(app.world.resource::<Events<AsyncQueryResult>>())
+            .copied()
+            .collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(app.world.resource::<AsyncQueryQueue>().len(), 2);
+        assert_eq!(results[0].id, ids[0]);
+        assert_eq!(results[0].requester, agent);
+        match results[0].hit {
+            AsyncQueryHit::Ray(Some((entity, inter))) => {
+                assert_eq!(entity, wall);
+                assert!((inter.toi - 4.5).abs() < 1.0e-5);
+            }
+            other => panic!("Unexpected hit: {:?}", other),
+        }
+        assert_eq!(results[1].hit, AsyncQueryHit::Ray(None));
+
+        app.world.resource_mut::<AsyncQueryQueue>().parallel = true;
+        app.update();
+        let results: Vec<_> = reader
+            .iter(app.world.resource::<Events<AsyncQueryResult>>())
+            .copied()
+            .collect();
+        assert_eq!(
+            results.iter().map(|result| result.id).collect::<Vec<_>>(),
+            ids[2..]
+        );
+        match results[0].hit {
+            AsyncQueryHit::Shape(Some((entity, toi))) => {
+                assert_eq!(entity, wall);
+                assert!((toi.toi - 4.0).abs() < 1.0e-3);
+            }
+            other => panic!("Unexpected hit: {:?}", other),
+        }
+        assert_eq!(results[1].hit, AsyncQueryHit::Ray(None));
+        assert!(app.world.resource::<AsyncQueryQueue>().is_empty());
+    }
+
+    #[test]
+    fn query_pipeline_update_policies() {
+        #[derive(Default)]
+        struct HitBeforeStep(Option<Entity>);
+
+        fn cast_before_step(context: Res<RapierContext>, mut hit: ResMut<HitBeforeStep>) {
+            hit.0 = context
+                .cast_ray(Vect::ZERO, Vect::X, 10.0, true, QueryFilter::default())
+                .map(|(entity, _)| entity);
+        }
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .init_resource::<HitBeforeStep>()
+            .add_system_to_stage(
+                PhysicsStages::StepSimulation,
+                cast_before_step
+                    .after(update_query_pipeline_after_sync)
+                    .before(step_simulation::<()>),
+            );
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .query_pipeline_update = QueryPipelineUpdate::Manual;
+
+        let entity = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let ray = |app: &App| {
+            app.world
+                .resource::<RapierContext>()
+                .cast_ray(Vect::ZERO, Vect::X, 10.0, true, QueryFilter::default())
+                .map(|(entity, _)| entity)
+        };
+        assert_eq!(ray(&app), None);
+        app.world
+            .resource_mut::<RapierContext>()
+            .update_query_pipeline_now();
+        assert_eq!(ray(&app), Some(entity));
+
+        // A collider spawned during the frame is only found before the simulation step after
+        // its synchronization with `AfterSyncAndStep`.
+        app.world.entity_mut(entity).despawn();
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .query_pipeline_update = QueryPipelineUpdate::AfterSyncAndStep;
+        app.update();
+        assert_eq!(ray(&app), None);
+
+        let entity = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .insert(GlobalTransform::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .id();
+        app.update();
+        assert_eq!(app.world.resource::<HitBeforeStep>().0, Some(entity));
+        assert_eq!(ray(&app), Some(entity));
+    }
+
+    #[test]
+    fn heightfield_cells_are_reported_at_the_hits() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        #[cfg(feature = "dim2")]
+        let heightfield = Collider::heightfield(vec![0.0; 4], Vect::new(3.0, 1.0));
+        #[cfg(feature = "dim3")]
+        let heightfield = Collider::heightfield(vec![0.0; 9], 3, 3, Vect::new(2.0, 1.0, 2.0));
+        let terrain = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(heightfield)
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(10.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let context = app.world.resource::<RapierContext>();
+        #[cfg(feature = "dim2")]
+        let origin = Vect::new(0.2, 5.0);
+        #[cfg(feature = "dim3")]
+        let origin = Vect::new(0.5, 5.0, -0.5);
+        let (entity, inter) = context
+            .cast_ray_and_get_normal(origin, -Vect::Y, 10.0, true, QueryFilter::default())
+            .unwrap();
+        assert_eq!(entity, terrain);
+
+        let cell = context.heightfield_cell(terrain, inter.point).unwrap();
+        #[cfg(feature = "dim2")]
+        assert_eq!(
+            cell,
+            HeightfieldCell {
+                index: 1,
+                heights: [1, 2],
+            }
+        );
+        #[cfg(feature = "dim3")]
+        assert_eq!(
+            cell,
+            HeightfieldCell {
+                row: 0,
+                column: 1,
+                heights: [3, 4, 6, 7],
+            }
+        );
+
+        assert_eq!(context.heightfield_cell(terrain, Vect::X * 5.0), None);
+        assert_eq!(context.heightfield_cell(ball, Vect::X * 10.0), None);
+    }
+
+    #[test]
+    fn contact_force_event_thresholds_apply_to_pairs_and_groups() {
+        use crate::geometry::Group;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .id();
+        let [ball1, ball2] = [(-2.0, Group::GROUP_1), (2.0, Group::GROUP_2)].map(|(x, group)| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 2.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(CollisionGroups::new(group, Group::ALL))
+                .insert(ActiveEvents::CONTACT_FORCE_EVENTS)
+                .insert(ContactForceEventThreshold(Real::MAX))
+                .id()
+        });
+
+        {
+            let mut context = app.world.resource_mut::<RapierContext>();
+            let thresholds = &mut context.contact_force_event_thresholds;
+            thresholds.set_groups(Group::GROUP_1, Group::GROUP_2, 0.0);
+            thresholds.set_pair(ball2, ground, Real::MAX);
+            assert_eq!(thresholds.groups(Group::GROUP_2, Group::GROUP_1), Some(0.0));
+            assert_eq!(thresholds.pair(ground, ball2), Some(Real::MAX));
+        }
+
+        let mut reader = app
+            .world
+            .resource::<Events<ContactForceEvent>>()
+            .get_reader();
+        let mut events = vec![];
+        for _ in 0..60 {
+            app.update();
+            events.extend(
+                reader
+                    .iter(app.world.resource::<Events<ContactForceEvent>>())
+                    .cloned(),
+            );
+        }
+
+        assert!(!events.is_empty());
+        assert!(events.iter().all(|event| {
+            let pair = [event.collider1, event.collider2];
+            pair.contains(&ball1) && pair.contains(&ground)
+        }));
+
+        // The first event is the impact of the falling ball, about 1.5 below its start.
+        let impact = &events[0];
+        assert!(!impact.contact_points.is_empty());
+        assert!((impact.max_force_point.y).abs() < 0.1);
+        assert!((impact.max_force_point.x + 2.0).abs() < 0.1);
+        assert!(impact.relative_velocity.length() > 4.0);
+        assert!(impact.relative_velocity.y.abs() > 4.0);
+
+        // The thresholds of the colliders lowered during the steps are restored.
+        let context = app.world.resource::<RapierContext>();
+        let handle = context.entity2collider()[&ball1];
+        assert_eq!(
+            context.colliders[handle].contact_force_event_threshold(),
+            Real::MAX
+        );
+    }
+
+    #[test]
+    fn collision_events_include_contacts_when_requested() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .id();
+        let [ball1, ball2] = [
+            (
+                -2.0,
+                ActiveEvents::COLLISION_EVENTS | ActiveEvents::COLLISION_EVENT_CONTACTS,
+            ),
+            (2.0, ActiveEvents::COLLISION_EVENTS),
+        ]
+        .map(|(x, events)| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 2.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(events)
+                .id()
+        });
+
+        let mut reader = app
+            .world
+            .resource::<Events<CollisionEventDetails>>()
+            .get_reader();
+        let mut event_reader = app.world.resource::<Events<CollisionEvent>>().get_reader();
+        let mut started = vec![];
+        for _ in 0..60 {
+            app.update();
+            let events: Vec<_> = event_reader
+                .iter(app.world.resource::<Events<CollisionEvent>>())
+                .copied()
+                .collect();
+            let details: Vec<_> = reader
+                .iter(app.world.resource::<Events<CollisionEventDetails>>())
+                .collect();
+            assert!(details.iter().map(|details| details.event).eq(events));
+
+            for details in details {
+                if let CollisionEvent::Started(e1, e2, ..) = details.event {
+                    let ball = if e1 == ground { e2 } else { e1 };
+                    started.push((ball, details.contact.clone()));
+                }
+            }
+        }
+
+        assert_eq!(started.len(), 2);
+        let contact1 = started
+            .iter()
+            .find(|(ball, _)| *ball == ball1)
+            .and_then(|(_, contact)| contact.clone())
+            .unwrap();
+        assert!(started
+            .iter()
+            .any(|(ball, contact)| *ball == ball2 && contact.is_none()));
+
+        // The ball hits the ground at about 5.4 after falling for 1.5.
+        assert!(!contact1.manifold.points.is_empty());
+        assert!(contact1.manifold.normal.y.abs() > 0.99);
+        assert!(contact1.penetration >= 0.0);
+        assert!(contact1.relative_velocity.y.abs() > 4.0);
+        assert!(contact1
+            .manifold
+            .points
+            .iter()
+            .all(|point| (point.point1.x + 2.0).abs() < 0.1));
+    }
+
+    #[test]
+    fn contact_modifiers_apply_surface_velocities_and_friction_overrides() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<ContactModifiersData>::default())
+            .insert_resource(PhysicsHooksWithQueryResource::<ContactModifiersData>(
+                Box::new(ContactModifiers),
+            ));
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let [conveyor, ground] = [-10.0, 10.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, -0.5, 0.0)))
+                .insert(RigidBody::Fixed)
+                .insert(Collider::cuboid(
+                    5.0,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    5.0,
+                ))
+                .insert(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+                .id()
+        });
+        app.world
+            .entity_mut(conveyor)
+            .insert(SurfaceVelocity(Vect::X * 2.0));
+
+        let [carried, sliding, braking] = [-10.0, 8.0, 12.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.5, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::cuboid(
+                    0.5,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    0.5,
+                ))
+                .insert(Velocity::default())
+                .id()
+        });
+        for entity in [sliding, braking] {
+            app.world
+                .entity_mut(entity)
+                .insert(Velocity::linear(Vect::X * 3.0));
+        }
+        app.world
+            .entity_mut(sliding)
+            .insert(FrictionOverrides::default().with(ground, 0.0));
+
+        for _ in 0..30 {
+            app.update();
+        }
+
+        let linvel = |entity| app.world.get::<Velocity>(entity).unwrap().linvel;
+        assert!((linvel(carried).x - 2.0).abs() < 0.2);
+        assert!((linvel(sliding).x - 3.0).abs() < 0.1);
+        assert!(linvel(braking).x < 1.5);
+    }
+
+    #[test]
+    fn collision_event_router_emits_ordered_typed_events() {
+        use crate::pipeline::{CollisionEventRouter, CollisionStarted, CollisionStopped};
+
+        #[derive(Component)]
+        struct Player;
+        #[derive(Component)]
+        struct Hazard;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_plugin(CollisionEventRouter::<Player, Hazard>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let hazard = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .insert(Hazard)
+            .id();
+        // The player marker is on the rigid-body, the collider is attached as a child.
+        let player_collider = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .id();
+        let player = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-2.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Player)
+            .push_children(&[player_collider])
+            .id();
+        // Unmarked colliders are not routed.
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(2.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS);
+
+        let mut started_reader = app
+            .world
+            .resource::<Events<CollisionStarted<Player, Hazard>>>()
+            .get_reader();
+        let mut stopped_reader = app
+            .world
+            .resource::<Events<CollisionStopped<Player, Hazard>>>()
+            .get_reader();
+        let mut started = vec![];
+        for _ in 0..60 {
+            app.update();
+            started.extend(
+                started_reader
+                    .iter(
+                        app.world
+                            .resource::<Events<CollisionStarted<Player, Hazard>>>(),
+                    )
+                    .copied(),
+            );
+        }
+        assert_eq!(started, vec![CollisionStarted::new(player, hazard)]);
+
+        app.world.despawn(player_collider);
+        app.update();
+        app.update();
+        let stopped: Vec<_> = stopped_reader
+            .iter(
+                app.world
+                    .resource::<Events<CollisionStopped<Player, Hazard>>>(),
+            )
+            .copied()
+            .collect();
+        assert_eq!(stopped, vec![CollisionStopped::new(player, hazard)]);
+    }
+
+    #[test]
+    fn sensor_events_report_the_sensor_first() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let [sensor, resting_sensor] = [0.0, 5.0].map(|x| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.0, 0.0)))
+                .insert(Collider::cuboid(
+                    1.0,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    1.0,
+                ))
+                .insert(Sensor)
+                .id()
+        });
+        // The ball falls through the first sensor, the other collider rests in the second one.
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .id();
+        let resting = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 0.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(GravityScale(0.0))
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .id();
+
+        let mut reader = app.world.resource::<Events<SensorEvent>>().get_reader();
+        let mut events = vec![];
+        for _ in 0..60 {
+            app.update();
+            events.extend(
+                reader
+                    .iter(app.world.resource::<Events<SensorEvent>>())
+                    .copied(),
+            );
+        }
+
+        assert_eq!(events.len(), 3);
+        assert!(events.contains(&SensorEvent::Entered {
+            sensor: resting_sensor,
+            other: resting,
+        }));
+        let entered = events
+            .iter()
+            .position(|e| {
+                *e == SensorEvent::Entered {
+                    sensor,
+                    other: ball,
+                }
+            })
+            .unwrap();
+        let exited = events
+            .iter()
+            .position(|e| {
+                *e == SensorEvent::Exited {
+                    sensor,
+                    other: ball,
+                }
+            })
+            .unwrap();
+        assert!(entered < exited);
+
+        // Exit events are reported for removed sensors.
+        app.world.despawn(resting_sensor);
+        app.update();
+        app.update();
+        let events: Vec<_> = reader
+            .iter(app.world.resource::<Events<SensorEvent>>())
+            .copied()
+            .collect();
+        assert_eq!(
+            events,
+            vec![SensorEvent::Exited {
+                sensor: resting_sensor,
+                other: resting,
+            }]
+        );
+    }
+
+    #[test]
+    fn collision_events_are_buffered_per_step() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        {
+            let mut config = app.world.resource_mut::<RapierConfiguration>();
+            config.gravity = Vect::ZERO;
+            config.timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 8,
+            };
+        }
+
+        let sensor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::cuboid(
+                10.0,
+                0.1,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .insert(Sensor)
+            .id();
+        // The ball crosses the thin sensor during the substeps of a single frame.
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.6, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.1))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(Velocity::linear(Vect::new(
+                0.0,
+                -60.0,
+                #[cfg(feature = "dim3")]
+                0.0,
+            )))
+            .id();
+
+        let mut reader = app
+            .world
+            .resource::<Events<CollisionEventDetails>>()
+            .get_reader();
+        let mut frame_events = vec![];
+        for _ in 0..4 {
+            app.update();
+            let events: Vec<_> = reader
+                .iter(app.world.resource::<Events<CollisionEventDetails>>())
+                .map(|details| (details.event, details.step))
+                .collect();
+            if !events.is_empty() {
+                frame_events.push(events);
+            }
+        }
+
+        assert_eq!(app.world.resource::<RapierContext>().step_index(), 4 * 8);
+        assert_eq!(frame_events.len(), 1);
+        let events = &frame_events[0];
+        assert_eq!(events.len(), 2);
+        let (started, stopped) = match (events[0], events[1]) {
+            (
+                (CollisionEvent::Started(s1, s2, _), started),
+                (CollisionEvent::Stopped(e1, e2, _), stopped),
+            ) => {
+                assert!([s1, s2].contains(&sensor) && [s1, s2].contains(&ball));
+                assert!([e1, e2].contains(&sensor) && [e1, e2].contains(&ball));
+                (started, stopped)
+            }
+            _ => panic!("unexpected collision events: {:?}", events),
+        };
+        assert!(started < stopped);
+        assert!(stopped < 4 * 8);
+    }
+
+    #[test]
+    fn impact_events_are_debounced() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                100.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                100.0,
+            ))
+            .insert(Friction::coefficient(0.0))
+            .insert(ImpactMaterial(1))
+            .id();
+        // The box lands on the ground, then slides along it.
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Velocity::linear(Vect::new(
+                5.0,
+                0.0,
+                #[cfg(feature = "dim3")]
+                0.0,
+            )))
+            // No cooldown: the impacts are debounced by the approach speed alone.
+            .insert(ImpactEvents {
+                cooldown: 0.0,
+                ..default()
+            })
+            .insert(ImpactMaterial(2))
+            .id();
+        let collider = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::cuboid(
+                0.5,
+                0.5,
+                #[cfg(feature = "dim3")]
+                0.5,
+            ))
+            .insert(Friction::coefficient(0.0))
+            .insert(ActiveEvents::CONTACT_FORCE_EVENTS)
+            .id();
+        app.world.entity_mut(body).push_children(&[collider]);
+
+        let mut reader = app.world.resource::<Events<ImpactEvent>>().get_reader();
+        let mut impacts = vec![];
+        for _ in 0..120 {
+            app.update();
+            impacts.extend(
+                reader
+                    .iter(app.world.resource::<Events<ImpactEvent>>())
+                    .copied(),
+            );
+        }
+
+        // The box hits the ground at about 5.4, and contact forces are applied while it slides.
+        assert_eq!(impacts.len(), 1);
+        let impact = impacts[0];
+        let (material_ground, material_box) = if impact.entities == [ground, collider] {
+            (impact.material_pair[0], impact.material_pair[1])
+        } else {
+            assert_eq!(impact.entities, [collider, ground]);
+            (impact.material_pair[1], impact.material_pair[0])
+        };
+        assert_eq!((material_ground, material_box), (1, 2));
+        assert!(impact.normal.y.abs() > 0.99);
+        assert!(impact.relative_speed > 4.0);
+        assert!(impact.impulse > 0.0);
+        assert!(impact.point.y.abs() < 0.1);
+    }
+
+    #[test]
+    fn removal_events_report_the_last_known_pose() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let anchor = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 2.0, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::new(anchor, FixedJointBuilder::new()))
+            .id();
+        let collider = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(-3.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
+
+        let mut body_reader = app.world.resource::<Events<BodyRemoved>>().get_reader();
+        let mut collider_reader = app.world.resource::<Events<ColliderRemoved>>().get_reader();
+        let mut joint_reader = app.world.resource::<Events<JointRemoved>>().get_reader();
+
+        app.world.despawn(body);
+        app.world.entity_mut(collider).remove::<Collider>();
+        app.update();
+
+        let bodies: Vec<_> = body_reader
+            .iter(app.world.resource::<Events<BodyRemoved>>())
+            .copied()
+            .collect();
+        assert_eq!(bodies.len(), 1);
+        assert_eq!(bodies[0].entity, body);
+        assert!(bodies[0]
+            .transform
+            .translation
+            .abs_diff_eq(Vec3::new(1.0, 2.0, 0.0), 1.0e-5));
+
+        let colliders: Vec<_> = collider_reader
+            .iter(app.world.resource::<Events<ColliderRemoved>>())
+            .copied()
+            .collect();
+        assert_eq!(colliders.len(), 2);
+        for event in colliders {
+            let expected = if event.entity == body {
+                Vec3::new(1.0, 2.0, 0.0)
+            } else {
+                assert_eq!(event.entity, collider);
+                Vec3::new(-3.0, 0.0, 0.0)
+            };
+            assert!(event.transform.translation.abs_diff_eq(expected, 1.0e-5));
+        }
+
+        let joints: Vec<_> = joint_reader
+            .iter(app.world.resource::<Events<JointRemoved>>())
+            .copied()
+            .collect();
+        assert_eq!(joints, vec![JointRemoved { entity: body }]);
+
+        // Nothing is reported once the objects are gone.
+        app.update();
+        assert_eq!(
+            body_reader
+                .iter(app.world.resource::<Events<BodyRemoved>>())
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn trigger_zones_report_enter_stay_and_exit() {
+        use crate::geometry::Group;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .insert_resource(Time::default())
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world.resource_mut::<RapierConfiguration>().gravity = Vect::ZERO;
+
+        let zone = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::cuboid(
+                1.0,
+                1.0,
+                #[cfg(feature = "dim3")]
+                1.0,
+            ))
+            .insert(Sensor)
+            .insert(TriggerZone {
+                filter: Group::GROUP_1,
+                dwell_time: 0.25,
+            })
+            .id();
+        let [visitor, ignored] = [Group::GROUP_1, Group::GROUP_2].map(|memberships| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::default())
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.1))
+                .insert(CollisionGroups::new(memberships, Group::ALL))
+                .id()
+        });
+
+        // Each frame lasts 0.1 second.
+        let start = std::time::Instant::now();
+        let mut reader = app.world.resource::<Events<TriggerEvent>>().get_reader();
+        let mut events = vec![];
+        for i in 1..=10 {
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(i as f32 * 0.1));
+            if i == 8 {
+                app.world
+                    .get_mut::<Transform>(visitor)
+                    .unwrap()
+                    .translation
+                    .x = 5.0;
+            }
+            app.update();
+            events.extend(
+                reader
+                    .iter(app.world.resource::<Events<TriggerEvent>>())
+                    .copied(),
+            );
+        }
+
+        assert!(events.iter().all(|event| match event {
+            TriggerEvent::Entered { zone: z, entity }
+            | TriggerEvent::Staying {
+                zone: z, entity, ..
+            }
+            | TriggerEvent::Exited {
+                zone: z, entity, ..
+            } => *z == zone && *entity != ignored,
+        }));
+        let entered = events
+            .iter()
+            .position(|event| {
+                *event
+                    == TriggerEvent::Entered {
+                        zone,
+                        entity: visitor,
+                    }
+            })
+            .unwrap();
+        let staying: Vec<_> = events[entered + 1..events.len() - 1]
+            .iter()
+            .map(|event| match event {
+                TriggerEvent::Staying { time_inside, .. } => *time_inside,
+                _ => panic!("unexpected trigger event: {:?}", event),
+            })
+            .collect();
+        assert!(!staying.is_empty());
+        assert!(staying[0] > 0.25);
+        assert!(staying.windows(2).all(|w| w[1] > w[0]));
+        match events.last().unwrap() {
+            TriggerEvent::Exited {
+                zone: z,
+                entity,
+                time_inside,
+            } => {
+                assert_eq!((*z, *entity), (zone, visitor));
+                assert!(*time_inside >= *staying.last().unwrap());
+            }
+            event => panic!("unexpected trigger event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn contact_pair_storage_lives_as_long_as_the_contact() {
+        #[derive(Debug, PartialEq)]
+        struct GrindingDamage(u32);
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world.resource_mut::<RapierConfiguration>().gravity = Vect::ZERO;
+
+        let [ground, ball, far] = [(0.0, -0.5), (0.0, 0.45), (5.0, 0.45)].map(|(x, y)| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::cuboid(
+                    0.5,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    0.5,
+                ))
+                .id()
+        });
+        app.update();
+        app.update();
+
+        for _ in 0..3 {
+            app.world
+                .resource_mut::<RapierContext>()
+                .contact_pair_storage
+                .get_or_insert_with(ground, ball, || GrindingDamage(0))
+                .0 += 1;
+            app.update();
+        }
+
+        {
+            let mut context = app.world.resource_mut::<RapierContext>();
+            let storage = &mut context.contact_pair_storage;
+            assert_eq!(storage.get(ball, ground), Some(&GrindingDamage(3)));
+            assert_eq!(storage.iter::<GrindingDamage>().count(), 1);
+            // The colliders aren’t touching: the data is removed after the next step.
+            assert!(storage.insert(ground, far, GrindingDamage(1)).is_none());
+            assert_eq!(storage.len(), 2);
+        }
+        app.update();
+        assert_eq!(
+            app.world
+                .resource::<RapierContext>()
+                .contact_pair_storage
+                .len(),
+            1
+        );
+
+        // Once the contact ends, the data is removed.
+        app.world.get_mut::<Transform>(ball).unwrap().translation.y = 10.0;
+        app.update();
+        assert!(app
+            .world
+            .resource::<RapierContext>()
+            .contact_pair_storage
+            .is_empty());
+    }
+
+    #[test]
+    fn invalid_states_are_detected_and_frozen() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .insert_resource(InvalidStateDetection {
+                freeze_bodies: true,
+                ..default()
+            })
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        let healthy = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(5.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
+        let valid_translation = app.world.get::<Transform>(body).unwrap().translation;
+
+        app.world.entity_mut(body).insert(ExternalForce {
+            force: Vect::splat(Real::INFINITY),
+            ..default()
+        });
+        let mut reader = app
+            .world
+            .resource::<Events<PhysicsInvalidState>>()
+            .get_reader();
+        app.update();
+        app.update();
+
+        let events: Vec<_> = reader
+            .iter(app.world.resource::<Events<PhysicsInvalidState>>())
+            .copied()
+            .collect();
+        assert!(!events.is_empty());
+        assert!(events
+            .iter()
+            .all(|event| event.entity == body && event.cause == InvalidStateCause::HugeForce));
+        assert!(events
+            .iter()
+            .any(|event| event.field == InvalidStateField::LinearVelocity));
+
+        // The body is frozen at its last valid position.
+        assert_eq!(app.world.get::<RigidBody>(body), Some(&RigidBody::Fixed));
+        let translation = app.world.get::<Transform>(body).unwrap().translation;
+        assert!(translation.is_finite());
+        assert!(translation.abs_diff_eq(valid_translation, 0.5));
+        assert_eq!(
+            app.world.get::<RigidBody>(healthy),
+            Some(&RigidBody::Dynamic)
+        );
+    }
+
+    #[test]
+    fn ccd_impacts_are_reported_at_the_impact_location() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let wall = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(10.0, 0.0, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                0.05,
+                5.0,
+                #[cfg(feature = "dim3")]
+                5.0,
+            ))
+            .id();
+        // The projectiles move 20 units per step, much more than the thickness of the wall.
+        let mut spawn_projectile = |y: Real, active_events: ActiveEvents| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(GravityScale(0.0))
+                .insert(Ccd::enabled())
+                .insert(Collider::ball(0.1))
+                .insert(active_events)
+                .insert(Velocity::linear(Vect::X * 1200.0))
+                .id()
+        };
+        let projectile = spawn_projectile(0.0, ActiveEvents::CCD_IMPACTS);
+        let silent_projectile = spawn_projectile(2.0, ActiveEvents::empty());
+
+        let mut reader = app.world.resource::<Events<CcdImpact>>().get_reader();
+        let mut impacts = vec![];
+        for _ in 0..5 {
+            app.update();
+            impacts.extend(
+                reader
+                    .iter(app.world.resource::<Events<CcdImpact>>())
+                    .copied(),
+            );
+        }
+
+        let x = |entity| app.world.get::<Transform>(entity).unwrap().translation.x;
+
+        // The CCD of Rapier doesn’t stop the projectiles with the `parallel` feature.
+        if cfg!(feature = "parallel") {
+            assert!(impacts.is_empty());
+            assert!(x(projectile) > 10.0 && x(silent_projectile) > 10.0);
+            return;
+        }
+
+        assert_eq!(impacts.len(), 1);
+        let impact = impacts[0];
+        assert_eq!((impact.entity, impact.other), (projectile, wall));
+        assert!(impact.toi >= 0.0 && impact.toi <= 1.0 / 60.0);
+        assert!((impact.point.x - 9.95).abs() < 1.0e-2);
+        assert!((impact.normal - -Vect::X).length() < 1.0e-3);
+        assert!(x(projectile) < 10.0);
+        // Without `ActiveEvents::CCD_IMPACTS`, the projectile is stopped silently.
+        assert!(x(silent_projectile) < 10.0);
+    }
+
+    #[test]
+    fn contact_report_sums_the_impulses_of_touching_colliders() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .init_resource::<ContactReport>();
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .id();
+        let crate_ = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.5, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::cuboid(
+                0.5,
+                0.5,
+                #[cfg(feature = "dim3")]
+                0.5,
+            ))
+            .insert(ColliderMassProperties::Mass(2.0))
+            .id();
+
+        let floating = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 5.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .id();
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        let gravity = app.world.resource::<RapierConfiguration>().gravity.length();
+        let report = app.world.resource::<ContactReport>();
+        assert!(report.contacts(floating).is_empty());
+        let under_crate = report.contacts(crate_);
+        assert_eq!(under_crate.len(), 1);
+        assert_eq!(under_crate[0].entity, ground);
+        assert!((under_crate[0].normal - -Vect::Y).length() < 1.0e-3);
+
+        // The ground carries the weight of the crate.
+        let on_ground = report.contacts(ground);
+        assert_eq!(on_ground.len(), 1);
+        assert_eq!(on_ground[0].entity, crate_);
+        assert!((on_ground[0].normal - Vect::Y).length() < 1.0e-3);
+        let weight_impulse = 2.0 * gravity / 60.0;
+        assert!(
+            (report.total_normal_impulse(ground) - weight_impulse).abs() < 0.05 * weight_impulse
+        );
+    }
+
+    #[test]
+    fn physics_materials_override_the_response_of_material_pairs() {
+        use crate::geometry::{AppliedMaterial, MaterialPairResponse, PhysicsMaterial};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<ContactModifiersData>::default())
+            .insert_resource(PhysicsHooksWithQueryResource::<ContactModifiersData>(
+                Box::new(ContactModifiers),
+            ));
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let mut materials = app.world.resource_mut::<Assets<PhysicsMaterial>>();
+        let ice = materials.add(PhysicsMaterial::new("ice"));
+        let rubber = materials.add(PhysicsMaterial::new("rubber"));
+        app.insert_resource(
+            PhysicsMaterials::default()
+                .with(
+                    &ice,
+                    &ice,
+                    MaterialPairResponse::default().with_friction(0.0),
+                )
+                .with(
+                    &rubber,
+                    &ice,
+                    MaterialPairResponse::default().with_restitution(1.0),
+                ),
+        );
+
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                20.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                20.0,
+            ))
+            .insert(ActiveHooks::MODIFY_SOLVER_CONTACTS)
+            .insert(AppliedMaterial(ice.clone()));
+
+        // The material of the sliding crates is attached to their rigid-bodies.
+        let [sliding, braking] =
+            [(-10.0, ice.clone()), (-5.0, rubber.clone())].map(|(x, material)| {
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(x, 0.5, 0.0)))
+                    .insert(RigidBody::Dynamic)
+                    .insert(AppliedMaterial(material))
+                    .insert(Velocity::linear(Vect::X * 3.0))
+                    .with_children(|children| {
+                        children.spawn().insert(Collider::cuboid(
+                            0.5,
+                            0.5,
+                            #[cfg(feature = "dim3")]
+                            0.5,
+                        ));
+                    })
+                    .id()
+            });
+        let bouncing = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(10.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(AppliedMaterial(rubber))
+            .insert(Velocity::default())
+            .id();
+
+        let mut max_rebound: f32 = 0.0;
+        for _ in 0..40 {
+            app.update();
+            max_rebound = max_rebound.max(app.world.get::<Velocity>(bouncing).unwrap().linvel.y);
+        }
+
+        let linvel = |entity| app.world.get::<Velocity>(entity).unwrap().linvel;
+        assert!((linvel(sliding).x - 3.0).abs() < 0.1);
+        assert!(linvel(braking).x < 1.5);
+        assert!(max_rebound > 1.0);
+    }
+
+    #[test]
+    fn collision_event_filters_restrict_the_reported_counterparts() {
+        use crate::geometry::Group;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .insert(CollisionGroups::new(Group::GROUP_1, Group::ALL))
+            .id();
+        let pickup = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.5, 0.0)))
+            .insert(Collider::ball(0.5))
+            .insert(Sensor)
+            .insert(CollisionGroups::new(Group::GROUP_2, Group::ALL))
+            .id();
+        let player = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.5, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .insert(CollisionEventFilter(Group::GROUP_2 | Group::GROUP_3))
+            .id();
+
+        let mut reader = app.world.resource::<Events<CollisionEvent>>().get_reader();
+        let mut started = vec![];
+        for _ in 0..10 {
+            app.update();
+            for event in reader.iter(app.world.resource::<Events<CollisionEvent>>()) {
+                if let CollisionEvent::Started(e1, e2, ..) = event {
+                    started.push([*e1, *e2]);
+                }
+            }
+        }
+
+        assert!(app
+            .world
+            .resource::<RapierContext>()
+            .contact_pair(player, ground)
+            .is_some());
+        assert_eq!(started.len(), 1);
+        assert!(started[0].contains(&player) && started[0].contains(&pickup));
+
+        // Without the filter, the contacts with the ground are reported too.
+        let mut player_mut = app.world.entity_mut(player);
+        player_mut.remove::<CollisionEventFilter>();
+        player_mut.insert(Transform::from_xyz(0.0, 3.0, 0.0));
+        for _ in 0..60 {
+            app.update();
+            for event in reader.iter(app.world.resource::<Events<CollisionEvent>>()) {
+                if let CollisionEvent::Started(e1, e2, ..) = event {
+                    started.push([*e1, *e2]);
+                }
+            }
+        }
+        assert!(started
+            .iter()
+            .any(|pair| pair.contains(&player) && pair.contains(&ground)));
+    }
+
+    #[test]
+    fn standing_on_and_carrying_follow_the_supporting_contacts() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .insert_resource(Time::default())
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, -0.5, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .id();
+        let crate_ = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.5, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::cuboid(
+                0.5,
+                0.5,
+                #[cfg(feature = "dim3")]
+                0.5,
+            ))
+            .insert(TrackStanding::default())
+            .id();
+        let player = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(TrackStanding::default())
+            .id();
+
+        // Each frame lasts 1/60 second.
+        let start = std::time::Instant::now();
+        let mut frame = 0;
+        let mut update = |app: &mut App| {
+            frame += 1;
+            app.world
+                .resource_mut::<Time>()
+                .update_with_instant(start + Duration::from_secs_f32(frame as f32 / 60.0));
+            app.update();
+        };
+
+        for _ in 0..30 {
+            update(&mut app);
+        }
+
+        let standing_on = |app: &App, entity| app.world.get::<StandingOn>(entity).copied();
+        let carrying = |app: &App, entity| app.world.get::<Carrying>(entity).cloned();
+        assert_eq!(standing_on(&app, player), Some(StandingOn(crate_)));
+        assert_eq!(standing_on(&app, crate_), Some(StandingOn(ground)));
+        assert_eq!(carrying(&app, ground), Some(Carrying(vec![crate_])));
+        assert_eq!(carrying(&app, crate_), Some(Carrying(vec![player])));
+
+        // The player keeps standing on the crate for a short while after jumping off.
+        app.world
+            .entity_mut(player)
+            .insert(Transform::from_xyz(5.0, 5.0, 0.0));
+        update(&mut app);
+        assert_eq!(standing_on(&app, player), Some(StandingOn(crate_)));
+
+        for _ in 0..10 {
+            update(&mut app);
+        }
+        assert_eq!(standing_on(&app, player), None);
+        assert_eq!(carrying(&app, crate_), None);
+        assert_eq!(carrying(&app, ground), Some(Carrying(vec![crate_])));
+    }
+
+    #[test]
+    fn collision_observers_are_invoked_with_their_entity_events() {
+        use crate::pipeline::CollisionObserver;
+        use std::sync::{Arc, Mutex};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        #[derive(Component)]
+        struct PickedUp;
+
+        // The pickup is collected by the first collider touching it.
+        let pickup = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 0.0, 0.0)))
+            .insert(Collider::ball(0.5))
+            .insert(Sensor)
+            .insert(CollisionObserver::on_started(|commands, collision| {
+                commands.entity(collision.observer).insert(PickedUp);
+            }))
+            .id();
+
+        // The observer of the falling body sees the events of its child collider.
+        let observed = Arc::new(Mutex::new(vec![]));
+        let body_observed = observed.clone();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(CollisionObserver::on_stopped(move |_, collision| {
+                body_observed
+                    .lock()
+                    .unwrap()
+                    .push((collision.observer, collision.other));
+            }))
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(Collider::ball(0.5))
+            .insert(ActiveEvents::COLLISION_EVENTS)
+            .id();
+        app.world.entity_mut(body).push_children(&[ball]);
+
+        for _ in 0..60 {
+            app.update();
+        }
+
+        assert!(app.world.get::<PickedUp>(pickup).is_some());
+        assert!(app.world.get::<PickedUp>(body).is_none());
+        assert_eq!(*observed.lock().unwrap(), vec![(body, pickup)]);
+    }
+
+    #[test]
+    fn one_way_colliders_are_crossed_from_one_side_only() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = -Vect::Y * 9.81;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.1,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .insert(OneWayCollider::default());
+
+        // The first ball is thrown up through the platform, the second one falls onto it.
+        let [thrown, falling] = [(-3.0, -2.0, 12.0), (3.0, 2.0, 0.0)].map(|(x, y, speed)| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(Velocity::linear(Vect::Y * speed))
+                .id()
+        });
+
+        for _ in 0..240 {
+            app.update();
+        }
+
+        for ball in [thrown, falling] {
+            let y = app.world.get::<Transform>(ball).unwrap().translation.y;
+            assert!((y - 0.6).abs() < 0.1, "{}", y);
+        }
+    }
+
+    #[test]
+    fn island_events_report_created_merged_and_sleeping_islands() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .init_resource::<IslandTracker>();
+        let mut config = app.world.resource_mut::<RapierConfiguration>();
+        config.gravity = -Vect::Y * 9.81;
+        config.timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.1,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ));
+
+        let mut spawn_box = |y| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::cuboid(
+                    0.5,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    0.5,
+                ))
+                .id()
+        };
+        let bottom = spawn_box(0.6);
+        let top = spawn_box(1.8);
+        let mut pile = vec![bottom, top];
+        pile.sort();
+
+        let mut reader = app.world.resource::<Events<IslandEvent>>().get_reader();
+        let mut events = vec![];
+        for frame in 0..300 {
+            // Put the pile to sleep once it has settled, and pause the simulation so the
+            // colliders moved by the last step don’t wake it up again.
+            if frame == 240 {
+                app.world
+                    .resource_mut::<RapierConfiguration>()
+                    .physics_pipeline_active = false;
+                for entity in [bottom, top] {
+                    app.world.entity_mut(entity).insert(Sleeping {
+                        sleeping: true,
+                        ..default()
+                    });
+                }
+            }
+
+            app.update();
+            let island_events = app.world.resource::<Events<IslandEvent>>();
+            events.extend(reader.iter(island_events).cloned());
+        }
+
+        assert_eq!(
+            events,
+            vec![
+                IslandEvent::Created {
+                    bodies: vec![bottom]
+                },
+                IslandEvent::Created { bodies: vec![top] },
+                IslandEvent::Merged {
+                    bodies: pile.clone()
+                },
+                IslandEvent::Sleeping { bodies: pile },
+            ]
+        );
+    }
+
+    #[test]
+    fn island_events_are_only_sent_with_the_island_tracker() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+
+        let mut reader = app.world.resource::<Events<IslandEvent>>().get_reader();
+        let mut events = vec![];
+        for _ in 0..3 {
+            app.update();
+            let island_events = app.world.resource::<Events<IslandEvent>>();
+            events.extend(reader.iter(island_events).cloned());
+        }
+        assert!(events.is_empty());
+
+        app.init_resource::<IslandTracker>();
+        app.update();
+        let island_events = app.world.resource::<Events<IslandEvent>>();
+        events.extend(reader.iter(island_events).cloned());
+        assert_eq!(events, vec![IslandEvent::Created { bodies: vec![body] }]);
+    }
+
+    #[test]
+    fn restored_snapshots_continue_like_the_original_simulation() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.1,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ));
+
+        let mut spawn_box = |x, y| {
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(x, y, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::cuboid(
+                    0.5,
+                    0.5,
+                    #[cfg(feature = "dim3")]
+                    0.5,
+                ))
+                .id()
+        };
+        let bottom = spawn_box(0.0, 1.0);
+        let top = spawn_box(0.2, 3.0);
+        let removed = spawn_box(5.0, 2.0);
+
+        for _ in 0..30 {
+            app.update();
+        }
+        let snapshot = app.world.resource::<RapierContext>().snapshot();
+
+        let mut positions = vec![];
+        for _ in 0..30 {
+            app.update();
+            let context = app.world.resource::<RapierContext>();
+            positions
+                .push([bottom, top].map(|e| *context.bodies[context.entity2body[&e]].position()));
+        }
+
+        app.world.despawn(removed);
+        app.world.resource_mut::<RapierContext>().restore(snapshot);
+
+        for expected in positions {
+            app.update();
+            let context = app.world.resource::<RapierContext>();
+            for (entity, expected) in [bottom, top].into_iter().zip(expected) {
+                let restored = context.bodies[context.entity2body[&entity]].position();
+                approx::assert_relative_eq!(
+                    restored.translation.vector,
+                    expected.translation.vector,
+                    epsilon = 1.0e-4
+                );
+            }
+        }
+
+        let context = app.world.resource::<RapierContext>();
+        assert!(!context.entity2body.contains_key(&removed));
+        assert_eq!(context.bodies.len(), 3);
+        assert_eq!(context.colliders.len(), 3);
+        for entity in [bottom, top] {
+            assert_eq!(
+                app.world.get::<RapierRigidBodyHandle>(entity).unwrap().0,
+                context.entity2body[&entity]
+            );
+        }
+    }
+
+    #[test]
+    fn checksums_hash_the_whole_step_index() {
+        use crate::plugin::snapshot::checksum;
+
+        let bodies = RigidBodySet::new();
+        assert_ne!(checksum(1, &bodies), checksum(1 + (1 << 32), &bodies));
+    }
+
+    #[test]
+    fn restored_snapshots_replay_with_the_same_checksums() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default().with_physics_scale(100.0));
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                1000.0,
+                10.0,
+                #[cfg(feature = "dim3")]
+                1000.0,
+            ));
+        #[cfg(feature = "dim2")]
+        let angvel = 2.0;
+        #[cfg(feature = "dim3")]
+        let angvel = Vect::Z * 2.0;
+        for i in 0..3 {
+            let transform = Transform::from_xyz(30.0 * i as f32, 100.0 + 110.0 * i as f32, 0.0)
+                .with_rotation(Quat::from_rotation_z(0.3 * i as f32));
+            app.world
+                .spawn()
+                .insert_bundle(TransformBundle::from(transform))
+                .insert(RigidBody::Dynamic)
+                .insert(Velocity::angular(angvel))
+                .insert(Collider::cuboid(
+                    50.0,
+                    50.0,
+                    #[cfg(feature = "dim3")]
+                    50.0,
+                ));
+        }
+
+        for _ in 0..20 {
+            app.update();
+        }
+        let snapshot = app.world.resource::<RapierContext>().snapshot();
+
+        let mut checksums = vec![];
+        for _ in 0..40 {
+            app.update();
+            checksums.push(app.world.resource::<RapierContext>().snapshot().checksum());
+        }
+
+        // The `Velocity` components hold the velocities of the last step, which must not be
+        // applied to the restored rigid-bodies.
+        let mut context = app.world.resource_mut::<RapierContext>();
+        context.restore(snapshot);
+        let scale = context.physics_scale();
+        let velocities: Vec<_> = context
+            .entity2body
+            .iter()
+            .map(|(entity, handle)| (*entity, body_velocity(&context.bodies[*handle], scale)))
+            .collect();
+        for (entity, velocity) in velocities {
+            if let Some(mut current) = app.world.get_mut::<Velocity>(entity) {
+                *current = velocity;
+            }
+        }
+
+        for expected in checksums {
+            app.update();
+            assert_eq!(
+                app.world.resource::<RapierContext>().snapshot().checksum(),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn snapshot_diffs_report_the_divergences() {
+        use crate::plugin::DiffThresholds;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
+
+        let ground = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                10.0,
+                0.5,
+                #[cfg(feature = "dim3")]
+                10.0,
+            ))
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.5, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+
+        app.update();
+        let before = app.world.resource::<RapierContext>().snapshot();
+        let diff = before.diff(&before.clone(), &DiffThresholds::default());
+        assert!(diff.is_empty());
+        assert_eq!(diff.to_string(), "no divergence at step 1\n");
+
+        for _ in 0..60 {
+            app.update();
+        }
+        let after = app.world.resource::<RapierContext>().snapshot();
+        let diff = before.diff(&after, &DiffThresholds::default());
+        assert!(!diff.is_empty());
+        assert_eq!(diff.bodies.len(), 1);
+        assert_eq!(diff.bodies[0].entity, body);
+        assert!(diff.bodies[0].translation > 0.4, "{}", diff);
+        assert_eq!(diff.contact_pairs.len(), 1);
+        let pair = diff.contact_pairs[0];
+        assert_eq!((pair.collider1, pair.collider2), (ground, body));
+        assert_eq!(pair.contacts.0, 0);
+        assert!(pair.contacts.1 > 0);
+        assert!(diff.to_string().contains("contact pair"));
+
+        let thresholds = DiffThresholds {
+            translation: 1.0,
+            rotation: 1.0,
+            linvel: 10.0,
+            angvel: 10.0,
+        };
+        assert!(before.diff(&after, &thresholds).bodies.is_empty());
+
+        app.world.despawn(body);
+        app.update();
+        let despawned = app.world.resource::<RapierContext>().snapshot();
+        let diff = after.diff(&despawned, &DiffThresholds::default());
+        assert_eq!(diff.only_in_self, vec![body]);
+        assert!(diff.only_in_other.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "serde-serialize")]
+    fn versioned_snapshots_are_migrated_or_rejected() {
+        use crate::plugin::{
+            PhysicsSnapshot, SnapshotError, SnapshotHeader, SnapshotMigrations,
+            SNAPSHOT_FORMAT_VERSION, SNAPSHOT_MAGIC,
+        };
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 5.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5));
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let snapshot = app.world.resource::<RapierContext>().snapshot();
+        let bytes = snapshot.to_bytes().unwrap();
+        let (header, payload) = PhysicsSnapshot::read_header(&bytes).unwrap();
+        assert_eq!(header, SnapshotHeader::current());
+        let loaded = PhysicsSnapshot::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.checksum(), snapshot.checksum());
+
+        assert!(matches!(
+            PhysicsSnapshot::from_bytes(&bytes[4..]),
+            Err(SnapshotError::NotASnapshot)
+        ));
+
+        let with_header = |header: &SnapshotHeader, payload: &[u8]| {
+            let mut bytes = SNAPSHOT_MAGIC.to_vec();
+            bincode::serialize_into(&mut bytes, header).unwrap();
+            bytes.extend_from_slice(payload);
+            bytes
+        };
+        let newer = SnapshotHeader {
+            format_version: SNAPSHOT_FORMAT_VERSION + 1,
+            ..SnapshotHeader::current()
+        };
+        assert!(matches!(
+            PhysicsSnapshot::from_bytes(&with_header(&newer, payload)),
+            Err(SnapshotError::UnsupportedVersion { .. })
+        ));
+
+        // An older format, whose payload had an extra leading byte.
+        let older = SnapshotHeader {
+            format_version: SNAPSHOT_FORMAT_VERSION - 1,
+            ..SnapshotHeader::current()
+        };
+        let old_bytes = with_header(&older, &[&[42], payload].concat());
+        assert!(matches!(
+            PhysicsSnapshot::from_bytes(&old_bytes),
+            Err(SnapshotError::MissingMigration { .. })
+        ));
+        let migrations = SnapshotMigrations::default()
+            .with_migration(SNAPSHOT_FORMAT_VERSION - 1, |bytes| Ok(bytes[1..].to_vec()));
+        let migrated =
+            PhysicsSnapshot::from_bytes_with_migrations(&old_bytes, &migrations).unwrap();
+        assert_eq!(migrated.checksum(), snapshot.checksum());
+    }
+
+    #[test]
+    fn physics_prefabs_are_spawned_at_their_final_position() {
+        use crate::plugin::{PhysicsPrefabBundle, PhysicsPrefabInstance};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let mut scene = World::new();
+        let base = scene
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::ball(0.5))
+            .id();
+        let arm = scene
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 1.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.25))
+            .insert(ImpulseJoint::new(
+                base,
+                FixedJointBuilder::new().local_anchor2(Vect::Y),
+            ))
+            .id();
+        scene.entity_mut(base).push_children(&[arm]);
+        let scene = app
+            .world
+            .resource_mut::<Assets<Scene>>()
+            .add(Scene::new(scene));
+
+        let prefab = app
+            .world
+            .spawn()
+            .insert_bundle(PhysicsPrefabBundle::new(
+                scene,
+                Transform::from_xyz(10.0, 0.0, 0.0),
+            ))
+            .id();
+        app.update();
+
+        let entities = &app
+            .world
+            .get::<PhysicsPrefabInstance>(prefab)
+            .unwrap()
+            .entities;
+        assert_eq!(entities.len(), 2);
+        let context = app.world.resource::<RapierContext>();
+        let mut positions: Vec<_> = entities
+            .iter()
+            .map(|entity| {
+                let handle = context.entity2body()[entity];
+                let translation = context.bodies[handle].translation();
+                (translation.x, translation.y)
+            })
+            .collect();
+        positions.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(positions, vec![(10.0, 2.0), (10.0, 3.0)]);
+        assert_eq!(context.impulse_joints.len(), 1);
+    }
+
+    #[test]
+    fn saveable_physics_extracts_the_simulation_state() {
+        use crate::plugin::SaveablePhysics;
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 3.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(DespawnWhenSleeping::default())
+            .id();
+        app.update();
+        app.update();
 
-            let character_mass = controller
-                .custom_mass
-                .or_else(|| {
-                    parent_rigid_body
-                        .and_then(|h| context.bodies.get(h))
-                        .map(|rb| rb.mass())
-                })
-                .unwrap_or(0.0);
+        let velocity = {
+            let context = app.world.resource::<RapierContext>();
+            let handle = context.entity2body()[&body];
+            body_velocity(&context.bodies[handle], 1.0)
+        };
+        assert!(velocity.linvel.y < 0.0);
 
-            let mut filter = QueryFilter {
-                flags: controller.filter_flags,
-                groups: controller.filter_groups,
-                exclude_collider: None,
-                exclude_rigid_body: None,
-                predicate: None,
-            };
+        let scene = app
+            .world
+            .resource::<SaveablePhysics>()
+            .extract_scene(&app.world);
+        let mut entity_map = EntityMap::default();
+        scene
+            .write_to_world(&mut app.world, &mut entity_map)
+            .unwrap();
 
-            if let Some(parent) = parent_rigid_body {
-                filter = filter.exclude_rigid_body(parent);
-            } else if let Some(excl_co) = exclude_collider {
-                filter = filter.exclude_collider(excl_co)
-            };
+        let copy = entity_map.get(body).unwrap();
+        assert!(app.world.get::<Collider>(copy).is_some());
+        assert!(app.world.get::<DespawnWhenSleeping>(copy).is_none());
+        assert_eq!(app.world.get::<Velocity>(copy), Some(&velocity));
+        assert!(app.world.get::<ExternalForce>(copy).is_none());
+        assert!(!app.world.get::<Sleeping>(copy).unwrap().sleeping);
+    }
 
-            let collisions = &mut context.character_collisions_collector;
-            collisions.clear();
+    #[test]
+    #[cfg(feature = "debug-stream")]
+    fn debug_stream_records_each_simulation_step() {
+        use crate::plugin::{
+            DebugStreamReader, DebugStreamRecorder, DebugStreamRecorderPlugin, DebugStreamWriter,
+        };
+        use std::io::{Cursor, Write};
+        use std::sync::{Arc, Mutex};
 
-            let movement = raw_controller.move_shape(
-                context.integration_parameters.dt,
-                &context.bodies,
-                &context.colliders,
-                &context.query_pipeline,
-                character_shape,
-                &character_pos,
-                (translation / physics_scale).into(),
-                filter,
-                |c| collisions.push(c),
-            );
+        #[derive(Clone, Default)]
+        struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
 
-            if controller.apply_impulse_to_dynamic_bodies {
-                for collision in &*collisions {
-                    raw_controller.solve_character_collision_impulses(
-                        context.integration_parameters.dt,
-                        &mut context.bodies,
-                        &context.colliders,
-                        &context.query_pipeline,
-                        character_shape,
-                        character_mass,
-                        collision,
-                        filter,
-                    )
-                }
+        impl Write for SharedBuffer {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
             }
 
-            if let Ok(mut transform) = transforms.get_mut(entity_to_move) {
-                // TODO: take the parent’s GlobalTransform rotation into account?
-                transform.translation.x += movement.translation.x * physics_scale;
-                transform.translation.y += movement.translation.y * physics_scale;
-                #[cfg(feature = "dim3")]
-                {
-                    transform.translation.z += movement.translation.z * physics_scale;
-                }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
             }
+        }
 
-            let converted_collisions = context
-                .character_collisions_collector
-                .iter()
-                .filter_map(|c| CharacterCollision::from_raw(context, c));
-
-            if let Some(mut output) = output {
-                output.desired_translation = controller.translation.unwrap(); // Already takes the physics_scale into account.
-                output.effective_translation = (movement.translation * physics_scale).into();
-                output.grounded = movement.grounded;
-                output.collisions.clear();
-                output.collisions.extend(converted_collisions);
-            } else {
-                commands
-                    .entity(entity)
-                    .insert(KinematicCharacterControllerOutput {
-                        desired_translation: controller.translation.unwrap(), // Already takes the physics_scale into account.
-                        effective_translation: (movement.translation * physics_scale).into(),
-                        grounded: movement.grounded,
-                        collisions: converted_collisions.collect(),
-                    });
-            }
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .add_plugin(DebugStreamRecorderPlugin::default());
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 2,
+        };
 
-            controller.translation = None;
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 3.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        let buffer = SharedBuffer::default();
+        app.world
+            .resource_mut::<DebugStreamRecorder>()
+            .start(DebugStreamWriter::new(buffer.clone()));
+        for _ in 0..3 {
+            app.update();
         }
+
+        let bytes = buffer.0.lock().unwrap().clone();
+        let frames: Vec<_> = DebugStreamReader::new(Cursor::new(bytes))
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+        // Each substep is recorded, with the pose of the falling body after it.
+        assert_eq!(
+            frames.iter().map(|frame| frame.step).collect::<Vec<_>>(),
+            vec![1, 2, 3, 4, 5, 6]
+        );
+        let heights: Vec<_> = frames
+            .iter()
+            .map(|frame| frame.body(body).unwrap().translation.y)
+            .collect();
+        assert!(heights.windows(2).all(|pair| pair[1] < pair[0]));
+        let last = frames.last().unwrap();
+        assert!(!last.lines.is_empty());
+        assert_eq!(
+            last.body(body).unwrap().translation,
+            app.world.get::<Transform>(body).unwrap().translation
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    #[cfg(feature = "dim3")]
-    use bevy::prelude::shape::{Capsule, Cube};
-    use bevy::{
-        asset::AssetPlugin,
-        core::CorePlugin,
-        ecs::event::Events,
-        render::{settings::WgpuSettings, RenderPlugin},
-        scene::ScenePlugin,
-        time::TimePlugin,
-        window::WindowPlugin,
-    };
-    use std::f32::consts::PI;
+    #[test]
+    fn parallel_writeback_matches_the_physics_world() {
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
 
-    use super::*;
-    use crate::plugin::{NoUserData, RapierPhysicsPlugin};
-    #[cfg(feature = "dim3")]
-    use crate::prelude::ComputedColliderShape;
+        let parents: Vec<_> = (0..4)
+            .map(|i| {
+                let transform = Transform::from_xyz(i as f32 * 3.0, 1.0, 0.0)
+                    .with_rotation(Quat::from_rotation_z(i as f32 * 0.3));
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(transform))
+                    .id()
+            })
+            .collect();
+
+        // Enough rigid-bodies to be written back by several batches, half of them with a parent.
+        let bodies: Vec<_> = (0..PARALLEL_BATCH_SIZE * 2 + 100)
+            .map(|i| {
+                let body = app
+                    .world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                        i as f32, 0.0, 0.0,
+                    )))
+                    .insert(RigidBody::Dynamic)
+                    .insert(Velocity::linear(Vect::X))
+                    .id();
+                if i % 2 == 0 {
+                    app.world
+                        .entity_mut(parents[i % parents.len()])
+                        .push_children(&[body]);
+                }
+                body
+            })
+            .collect();
+
+        for _ in 0..3 {
+            app.update();
+        }
+
+        let context = app.world.resource::<RapierContext>();
+        for body in bodies {
+            let handle = context.entity2body()[&body];
+            let rb = &context.bodies[handle];
+            let global_transform = app.world.get::<GlobalTransform>(body).unwrap();
+            let expected = utils::iso_to_transform(rb.position(), context.physics_scale);
+
+            assert!(global_transform
+                .translation()
+                .abs_diff_eq(expected.translation, 1.0e-3));
+            // The global transform predicted by the writeback is the propagated one.
+            assert_eq!(
+                context.last_body_transform_set.get(&handle),
+                Some(global_transform)
+            );
+            assert_eq!(
+                app.world.get::<Velocity>(body),
+                Some(&body_velocity(rb, context.physics_scale))
+            );
+        }
+    }
 
     #[test]
-    fn colliding_entities_updates() {
-        let mut app = App::new();
-        app.add_event::<CollisionEvent>()
-            .add_system(update_colliding_entities);
+    fn writeback_only_changes_the_moved_transforms() {
+        #[derive(Default)]
+        struct ChangedTransforms(Vec<Entity>);
+
+        fn record_changed_transforms(
+            mut changed: ResMut<ChangedTransforms>,
+            transforms: Query<Entity, Changed<Transform>>,
+        ) {
+            changed.0 = transforms.iter().collect();
+        }
 
-        let entity1 = app.world.spawn().insert(CollidingEntities::default()).id();
-        let entity2 = app.world.spawn().insert(CollidingEntities::default()).id();
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+            .init_resource::<ChangedTransforms>()
+            .add_system_to_stage(CoreStage::Last, record_changed_transforms);
 
-        let mut collision_events = app
+        let ground = app
             .world
-            .get_resource_mut::<Events<CollisionEvent>>()
-            .unwrap();
-        collision_events.send(CollisionEvent::Started(
-            entity1,
-            entity2,
-            CollisionEventFlags::SENSOR,
-        ));
-
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_scale(Vec3::splat(
+                2.0,
+            ))))
+            .insert(RigidBody::Fixed)
+            .insert(Collider::ball(1.0))
+            .id();
+        let ball = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 10.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .id();
+        app.update();
+        app.update();
         app.update();
 
-        let colliding_entities1 = app
-            .world
-            .entity(entity1)
-            .get::<CollidingEntities>()
-            .unwrap();
-        assert_eq!(
-            colliding_entities1.len(),
-            1,
-            "There should be one colliding entity"
-        );
+        let changed = &app.world.resource::<ChangedTransforms>().0;
+        assert!(changed.contains(&ball));
+        assert!(!changed.contains(&ground));
         assert_eq!(
-            colliding_entities1.iter().next().unwrap(),
-            entity2,
-            "Colliding entity should be equal to the second entity"
+            app.world.get::<Collider>(ground).unwrap().scale(),
+            Vect::splat(2.0)
         );
+    }
 
-        let colliding_entities2 = app
+    #[test]
+    fn static_world_merges_the_shapes_of_each_chunk() {
+        use crate::geometry::{StaticWorld, StaticWorldBuilder};
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let triangle = Collider::trimesh(vec![Vect::ZERO, Vect::X, Vect::Y], vec![[0, 1, 2]]);
+        let mut builder = StaticWorldBuilder::new().chunk_size(10.0);
+        let mut scaled_ball = Collider::ball(0.5);
+        scaled_ball.set_scale(Vect::splat(2.0), 10);
+        builder
+            .add(Transform::from_xyz(1.0, 0.0, 0.0), &Collider::ball(0.5))
+            .add(
+                Transform::from_xyz(2.0, 0.0, 0.0).with_scale(Vec3::splat(1.5)),
+                &scaled_ball,
+            )
+            .add(Transform::from_xyz(3.0, 0.0, 0.0), &triangle)
+            .add(Transform::from_xyz(3.0, 0.0, 0.0), &triangle)
+            .add(Transform::from_xyz(12.0, 0.0, 0.0), &Collider::ball(0.5));
+        assert_eq!(builder.len(), 5);
+
+        let built = builder.build();
+        assert_eq!(built.len(), 3);
+        let parts = built[0].1.as_compound().unwrap().raw.shapes();
+        assert_eq!(parts.len(), 2);
+        // The scale of the collider and of its transform are both applied.
+        assert_eq!(parts[1].1.as_ball().unwrap().radius, 1.5);
+        assert_eq!(built[1].1.as_trimesh().unwrap().raw.indices().len(), 2);
+        assert_eq!(built[2].0.translation.x, 10.0);
+
+        let mut queue = CommandQueue::default();
+        let entity = builder.spawn(&mut Commands::new(&mut queue, &app.world));
+        queue.apply(&mut app.world);
+
+        for _ in 0..1000 {
+            app.update();
+            if app.world.get::<StaticWorld>(entity).unwrap().is_built() {
+                break;
+            }
+        }
+        app.update();
+
+        let static_world = app.world.get::<StaticWorld>(entity).unwrap();
+        assert!(static_world.is_built());
+        assert_eq!(static_world.colliders().len(), 3);
+        for collider in static_world.colliders() {
+            assert!(app.world.get::<RapierColliderHandle>(*collider).is_some());
+        }
+        assert_eq!(app.world.resource::<RapierContext>().colliders.len(), 3);
+    }
+
+    #[test]
+    fn terrain_streamer_loads_the_chunks_around_the_focuses() {
+        use crate::geometry::{TerrainFocus, TerrainStreamer};
+
+        fn stream_to(app: &mut App, focus: Entity, terrain: Entity, x: Real) -> usize {
+            app.world.get_mut::<Transform>(focus).unwrap().translation.x = x;
+            for _ in 0..1000 {
+                app.update();
+                let streamer = app.world.get::<TerrainStreamer>(terrain).unwrap();
+                if streamer.pending_chunks().next().is_none() {
+                    break;
+                }
+            }
+            app.update();
+
+            let streamer = app.world.get::<TerrainStreamer>(terrain).unwrap();
+            assert!(streamer
+                .loaded_chunks()
+                .all(|(_, collider)| app.world.get::<Collider>(collider.unwrap()).is_some()));
+            streamer.loaded_chunks().count()
+        }
+
+        let mut app = App::new();
+        app.add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+
+        let focus = app
             .world
-            .entity(entity2)
-            .get::<CollidingEntities>()
-            .unwrap();
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(TerrainFocus)
+            .id();
+        let terrain = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(
+                TerrainStreamer::new(10.0, 5.0, |_| Some(Collider::ball(0.5)))
+                    .with_unload_radius(15.0),
+            )
+            .id();
+
+        // The chunks are loaded by rows along `x`, two rows along `z` in 3D.
+        #[cfg(feature = "dim2")]
+        let rows = 1;
+        #[cfg(feature = "dim3")]
+        let rows = 2;
+
+        assert_eq!(stream_to(&mut app, focus, terrain, 0.0), 2 * rows);
+        // The chunk behind the focus is kept, being closer than the unload radius.
+        assert_eq!(stream_to(&mut app, focus, terrain, 12.0), 3 * rows);
+        assert_eq!(stream_to(&mut app, focus, terrain, 30.0), 3 * rows);
+
+        let streamer = app.world.get::<TerrainStreamer>(terrain).unwrap();
+        let mut chunks: Vec<_> = streamer.loaded_chunks().map(|(chunk, _)| chunk.x).collect();
+        chunks.sort_unstable();
+        chunks.dedup();
+        assert_eq!(chunks, vec![1, 2, 3]);
         assert_eq!(
-            colliding_entities2.len(),
-            1,
-            "There should be one colliding entity"
+            app.world.resource::<RapierContext>().colliders.len(),
+            3 * rows
         );
+    }
+
+    #[test]
+    fn lockstep_checksums_find_the_diverged_step() {
+        use crate::plugin::{LockstepChecksumPlugin, LockstepChecksums};
+
+        fn peer() -> (App, Entity) {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default())
+                .add_plugin(LockstepChecksumPlugin::with_capacity(8));
+            app.world
+                .resource_mut::<RapierConfiguration>()
+                .timestep_mode = TimestepMode::Fixed {
+                dt: 1.0 / 60.0,
+                substeps: 1,
+            };
+            let body = app
+                .world
+                .spawn()
+                .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 5.0, 0.0)))
+                .insert(RigidBody::Dynamic)
+                .insert(Collider::ball(0.5))
+                .insert(Velocity::default())
+                .id();
+            (app, body)
+        }
+
+        let (mut local, _) = peer();
+        let (mut remote, body) = peer();
+        for _ in 0..10 {
+            local.update();
+            remote.update();
+        }
+
+        let context = local.world.resource::<RapierContext>();
+        assert_eq!(context.checksum(), context.snapshot().checksum());
+        let checksums = local.world.resource::<LockstepChecksums>();
+        assert_eq!(checksums.iter().count(), 8);
+        assert_eq!(checksums.latest().unwrap().0, 10);
+        assert_eq!(checksums.get(2), None);
+        let remote_checksums = remote.world.resource::<LockstepChecksums>();
+        assert_eq!(checksums, remote_checksums);
+        assert_eq!(checksums.first_divergence(remote_checksums.iter()), None);
+
+        remote.world.get_mut::<Velocity>(body).unwrap().linvel = Vect::X;
+        for _ in 0..3 {
+            local.update();
+            remote.update();
+        }
+
+        let checksums = local.world.resource::<LockstepChecksums>();
+        let remote_checksums = remote.world.resource::<LockstepChecksums>();
+        let (step, checksum) = remote_checksums.latest().unwrap();
+        assert_eq!(checksums.matches(step, checksum), Some(false));
+        assert_eq!(checksums.matches(step + 1, checksum), None);
         assert_eq!(
-            colliding_entities2.iter().next().unwrap(),
-            entity1,
-            "Colliding entity should be equal to the first entity"
+            checksums.first_divergence(remote_checksums.iter()),
+            Some(11)
         );
+    }
 
-        let mut collision_events = app
+    #[test]
+    fn raw_rapier_worlds_are_exported_and_spawned() {
+        fn app() -> App {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default().with_physics_scale(10.0));
+            app
+        }
+
+        let mut source = app();
+        source
             .world
-            .get_resource_mut::<Events<CollisionEvent>>()
-            .unwrap();
-        collision_events.send(CollisionEvent::Stopped(
-            entity1,
-            entity2,
-            CollisionEventFlags::SENSOR,
-        ));
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                100.0,
+                5.0,
+                #[cfg(feature = "dim3")]
+                100.0,
+            ));
+        let anchor = source
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 50.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(5.0))
+            .insert(Friction::coefficient(0.2))
+            .id();
+        source
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 70.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(5.0))
+            .insert(ImpulseJoint::new(
+                anchor,
+                FixedJointBuilder::new().local_anchor1(Vect::Y * 20.0),
+            ));
+        for _ in 0..5 {
+            source.update();
+        }
 
-        app.update();
+        let raw = source
+            .world
+            .resource::<RapierContext>()
+            .export_raw_world(source.world.resource::<RapierConfiguration>());
+        #[cfg(feature = "serde-serialize")]
+        let raw = crate::plugin::RawRapierWorld::from_bytes(&raw.to_bytes().unwrap()).unwrap();
+        assert_eq!(raw.bodies.len(), 3);
+        assert_eq!(raw.colliders.len(), 3);
+
+        let mut target = app();
+        let mut queue = CommandQueue::default();
+        let entities = raw.spawn(&mut Commands::new(&mut queue, &target.world), 10.0);
+        queue.apply(&mut target.world);
+        target.update();
+
+        let context = target.world.resource::<RapierContext>();
+        assert_eq!(context.bodies.len(), 3);
+        assert_eq!(context.colliders.len(), 3);
+        assert_eq!(context.impulse_joints.len(), 1);
+        for (handle, rb) in raw.bodies.iter() {
+            let entity = entities.bodies[&handle];
+            let spawned = &context.bodies[context.entity2body()[&entity]];
+            assert_eq!(spawned.body_type(), rb.body_type());
+            assert!((spawned.translation() - rb.translation()).norm() < 1.0e-5);
+            assert!((spawned.linvel() - rb.linvel()).norm() < 1.0e-5);
+        }
+        for (handle, co) in raw.colliders.iter() {
+            let spawned =
+                &context.colliders[context.entity2collider()[&entities.colliders[&handle]]];
+            assert_eq!(spawned.friction(), co.friction());
+            assert!(
+                (spawned.shape().compute_local_aabb().extents()
+                    - co.shape().compute_local_aabb().extents())
+                .norm()
+                    < 1.0e-5
+            );
+        }
+    }
 
-        let colliding_entities1 = app
+    #[test]
+    fn raw_rapier_worlds_keep_the_joints_anchored_to_the_world() {
+        fn app() -> App {
+            let mut app = App::new();
+            app.add_plugin(HeadlessRenderPlugin)
+                .add_plugin(TransformPlugin)
+                .add_plugin(TimePlugin)
+                .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+            app
+        }
+
+        let mut source = app();
+        source
             .world
-            .entity(entity1)
-            .get::<CollidingEntities>()
-            .unwrap();
-        assert!(
-            colliding_entities1.is_empty(),
-            "Colliding entity should be removed from the CollidingEntities component when the collision ends"
-        );
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(ImpulseJoint::anchored_to_world(
+                FixedJointBuilder::new().local_anchor1(Vect::Y * 2.0),
+            ));
+        source.update();
 
-        let colliding_entities2 = app
+        let raw = source
             .world
-            .entity(entity2)
-            .get::<CollidingEntities>()
-            .unwrap();
-        assert!(
-            colliding_entities2.is_empty(),
-            "Colliding entity should be removed from the CollidingEntities component when the collision ends"
+            .resource::<RapierContext>()
+            .export_raw_world(source.world.resource::<RapierConfiguration>());
+        let mut target = app();
+        let mut queue = CommandQueue::default();
+        let entities = raw.spawn(&mut Commands::new(&mut queue, &target.world), 1.0);
+        queue.apply(&mut target.world);
+        target.update();
+
+        // The fixed rigid-body of the world isn’t spawned as an entity.
+        assert_eq!(entities.bodies.len(), 1);
+        let mut joints = target.world.query::<&ImpulseJoint>();
+        let joints: Vec<_> = joints.iter(&target.world).collect();
+        assert_eq!(joints.len(), 1);
+        assert_eq!(joints[0].parent, JointParent::World);
+        assert_eq!(
+            target
+                .world
+                .resource::<RapierContext>()
+                .impulse_joints
+                .len(),
+            1
         );
     }
 
     #[test]
-    #[cfg(feature = "dim3")]
-    fn async_collider_initializes() {
+    #[cfg(feature = "serde-serialize")]
+    fn physics_config_assets_are_applied_on_change() {
+        use crate::plugin::{ActivePhysicsConfig, PhysicsConfigAsset};
+
         let mut app = App::new();
         app.add_plugin(HeadlessRenderPlugin)
-            .add_system(init_async_colliders);
-
-        let mut meshes = app.world.resource_mut::<Assets<Mesh>>();
-        let cube = meshes.add(Cube::default().into());
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
 
-        let entity = app
+        #[cfg(feature = "dim2")]
+        let source = "(gravity: Some((0.0, -20.0)), integration_parameters: (erp: Some(0.5)))";
+        #[cfg(feature = "dim3")]
+        let source = "(gravity: Some((0.0, -20.0, 0.0)), integration_parameters: (erp: Some(0.5)))";
+        let asset: PhysicsConfigAsset = ron::de::from_str(source).unwrap();
+        let default_damping_ratio = app
             .world
-            .spawn()
-            .insert(AsyncCollider {
-                handle: cube,
-                shape: ComputedColliderShape::TriMesh,
-            })
-            .id();
+            .resource::<RapierContext>()
+            .integration_parameters
+            .damping_ratio;
 
+        let handle = app
+            .world
+            .resource_mut::<Assets<PhysicsConfigAsset>>()
+            .add(asset.clone());
+        app.insert_resource(ActivePhysicsConfig(handle.clone()));
         app.update();
 
-        let entity = app.world.entity(entity);
-        assert!(
-            entity.get::<Collider>().is_some(),
-            "Collider component should be added"
+        assert_eq!(
+            app.world.resource::<RapierConfiguration>().gravity,
+            Vect::Y * -20.0
         );
-        assert!(
-            entity.get::<AsyncCollider>().is_none(),
-            "AsyncCollider component should be removed after Collider component creation"
+        let params = app.world.resource::<RapierContext>().integration_parameters;
+        assert_eq!(params.erp, 0.5);
+        assert_eq!(params.damping_ratio, default_damping_ratio);
+
+        // A modification of the asset, e.g. a hot reload of its file, is applied too.
+        app.world
+            .resource_mut::<Assets<PhysicsConfigAsset>>()
+            .get_mut(&handle)
+            .unwrap()
+            .gravity = Some(Vect::ZERO);
+        app.update();
+        app.update();
+        assert_eq!(
+            app.world.resource::<RapierConfiguration>().gravity,
+            Vect::ZERO
         );
     }
 
     #[test]
-    #[cfg(feature = "dim3")]
-    fn async_scene_collider_initializes() {
+    fn collider_assets_are_attached_once_loaded() {
         let mut app = App::new();
         app.add_plugin(HeadlessRenderPlugin)
-            .add_system(init_async_scene_colliders);
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
 
-        let mut meshes = app.world.resource_mut::<Assets<Mesh>>();
-        let cube_handle = meshes.add(Cube::default().into());
-        let capsule_handle = meshes.add(Capsule::default().into());
-        let cube = app
+        let handle = app
             .world
-            .spawn()
-            .insert(Name::new("Cube"))
-            .insert(cube_handle)
-            .id();
-        let capsule = app
+            .resource::<Assets<ColliderAsset>>()
+            .get_handle(bevy::asset::HandleId::random::<ColliderAsset>());
+        let entity = app
             .world
             .spawn()
-            .insert(Name::new("Capsule"))
-            .insert(capsule_handle)
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(AsyncColliderFromAsset(handle.clone()))
             .id();
 
-        let mut scenes = app.world.resource_mut::<Assets<Scene>>();
-        let scene = scenes.add(Scene::new(World::new()));
+        app.update();
+        assert!(app.world.get::<Collider>(entity).is_none());
+
+        let asset = ColliderAsset::from(Collider::ball(0.5));
+        #[cfg(feature = "serde-serialize")]
+        let asset = ColliderAsset::from_bytes(&asset.to_bytes().unwrap()).unwrap();
+        app.world
+            .resource_mut::<Assets<ColliderAsset>>()
+            .set_untracked(handle, asset);
+        app.update();
+        app.update();
 
-        let mut named_shapes = bevy::utils::HashMap::new();
-        named_shapes.insert("Capsule".to_string(), None);
-        let parent = app
+        assert!(app.world.get::<AsyncColliderFromAsset>(entity).is_none());
+        let collider = app.world.get::<Collider>(entity).unwrap();
+        assert_eq!(collider.as_ball().unwrap().radius(), 0.5);
+        assert!(app
+            .world
+            .resource::<RapierContext>()
+            .entity2collider()
+            .contains_key(&entity));
+    }
+
+    #[test]
+    fn replicated_body_states_are_delta_compressed_and_applied() {
+        let mut server = App::new();
+        server
+            .add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        server.world.resource_mut::<RapierConfiguration>().gravity = Vect::ZERO;
+        let body = server
             .world
             .spawn()
-            .insert(AsyncSceneCollider {
-                handle: scene,
-                shape: Some(ComputedColliderShape::TriMesh),
-                named_shapes,
-            })
-            .push_children(&[cube, capsule])
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(1.0, 2.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::linear(Vect::X * 2.0))
             .id();
+        server.update();
 
-        app.update();
-
-        assert!(
-            app.world.entity(cube).get::<Collider>().is_some(),
-            "Collider component should be added for cube"
+        let quantization = StateQuantization::default();
+        let state = server
+            .world
+            .resource::<RapierContext>()
+            .body_state(body)
+            .unwrap();
+        let quantized = state.quantize(&quantization);
+        let received = quantized.dequantize(&quantization);
+        assert!((received.translation - state.translation).length() <= quantization.position);
+        assert!((received.velocity.linvel - Vect::X * 2.0).length() <= quantization.velocity);
+
+        let mut bytes = vec![];
+        quantized.write_delta(&QuantizedBodyState::default(), &mut bytes);
+        let full_len = bytes.len();
+        quantized.write_delta(&quantized, &mut bytes);
+        assert_eq!(bytes.len(), full_len + 1);
+        let mut reader = &bytes[..];
+        let decoded = QuantizedBodyState::read_delta(&QuantizedBodyState::default(), &mut reader);
+        assert_eq!(decoded, Some(quantized));
+        assert_eq!(
+            QuantizedBodyState::read_delta(&quantized, &mut reader),
+            Some(quantized)
         );
-        assert!(
-            app.world.entity(capsule).get::<Collider>().is_none(),
-            "Collider component shouldn't be added for capsule"
+        assert!(reader.is_empty());
+        assert_eq!(
+            QuantizedBodyState::read_delta(&Default::default(), &mut &bytes[..full_len - 1]),
+            None
         );
-        assert!(
-            app.world.entity(parent).get::<AsyncCollider>().is_none(),
-            "AsyncSceneCollider component should be removed after Collider components creation"
+
+        let mut client = App::new();
+        client
+            .add_plugin(HeadlessRenderPlugin)
+            .add_plugin(TransformPlugin)
+            .add_plugin(TimePlugin)
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
+        client.world.resource_mut::<RapierConfiguration>().gravity = Vect::ZERO;
+        let mut replicated = ReplicatedBody::new(0.5);
+        replicated.receive(received);
+        let body = client
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(Velocity::zero())
+            .insert(replicated)
+            .id();
+
+        for _ in 0..50 {
+            client.update();
+        }
+        assert!(!client
+            .world
+            .get::<ReplicatedBody>(body)
+            .unwrap()
+            .is_correcting());
+        assert_eq!(
+            client.world.get::<Velocity>(body).unwrap().linvel,
+            received.velocity.linvel
         );
     }
 
     #[test]
-    fn transform_propagation() {
+    fn recordings_replay_to_the_same_physics_world() {
         let mut app = App::new();
         app.add_plugin(HeadlessRenderPlugin)
             .add_plugin(TransformPlugin)
             .add_plugin(TimePlugin)
-            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
-
-        let zero = (Transform::default(), Transform::default());
-
-        let different = (
-            Transform {
-                translation: Vec3::X * 10.0,
-                rotation: Quat::from_rotation_x(PI),
-                ..Default::default()
-            },
-            Transform {
-                translation: Vec3::Y * 10.0,
-                rotation: Quat::from_rotation_x(PI),
-                ..Default::default()
-            },
-        );
+            .add_plugin(RapierPhysicsPlugin::<NoUserData>::default().with_physics_scale(100.0));
+        app.world
+            .resource_mut::<RapierConfiguration>()
+            .timestep_mode = TimestepMode::Fixed {
+            dt: 1.0 / 60.0,
+            substeps: 1,
+        };
 
-        let same = (different.0, different.0);
+        app.world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::cuboid(
+                1000.0,
+                10.0,
+                #[cfg(feature = "dim3")]
+                1000.0,
+            ));
+        let boxes: Vec<_> = (0..2)
+            .map(|i| {
+                app.world
+                    .spawn()
+                    .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                        120.0 * i as f32,
+                        100.0,
+                        0.0,
+                    )))
+                    .insert(RigidBody::Dynamic)
+                    .insert(Collider::cuboid(
+                        50.0,
+                        50.0,
+                        #[cfg(feature = "dim3")]
+                        50.0,
+                    ))
+                    .id()
+            })
+            .collect();
+        let platform = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(
+                -300.0, 50.0, 0.0,
+            )))
+            .insert(RigidBody::KinematicPositionBased)
+            .insert(Collider::cuboid(
+                100.0,
+                10.0,
+                #[cfg(feature = "dim3")]
+                100.0,
+            ))
+            .id();
 
-        for (child_transform, parent_transform) in [zero, same, different] {
-            let child = app
-                .world
-                .spawn()
-                .insert_bundle(TransformBundle::from(child_transform))
-                .insert(RigidBody::Fixed)
-                .insert(Collider::ball(1.0))
-                .id();
+        for _ in 0..5 {
+            app.update();
+        }
+        app.world.resource_mut::<PhysicsRecorder>().start();
+        app.update();
 
+        for i in 0..40 {
             app.world
-                .spawn()
-                .insert_bundle(TransformBundle::from(parent_transform))
-                .push_children(&[child]);
-
+                .get_mut::<Transform>(platform)
+                .unwrap()
+                .translation
+                .x += 5.0;
+            match i {
+                5 => {
+                    app.world.entity_mut(boxes[0]).insert(ExternalForce {
+                        force: Vect::X * 5000.0,
+                        ..Default::default()
+                    });
+                }
+                10 => {
+                    app.world.entity_mut(boxes[1]).insert(ExternalImpulse {
+                        impulse: Vect::Y * 1000.0,
+                        ..Default::default()
+                    });
+                }
+                15 => {
+                    app.world
+                        .spawn()
+                        .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 400.0, 0.0)))
+                        .insert(RigidBody::Dynamic)
+                        .insert(Collider::ball(30.0));
+                }
+                25 => {
+                    app.world.despawn(boxes[1]);
+                }
+                _ => {}
+            }
             app.update();
+        }
 
-            let child_transform = app.world.entity(child).get::<GlobalTransform>().unwrap();
-            let context = app.world.resource::<RapierContext>();
-            let child_handle = context.entity2body[&child];
-            let child_body = context.bodies.get(child_handle).unwrap();
-            let body_transform =
-                utils::iso_to_transform(child_body.position(), context.physics_scale);
-            assert_eq!(
-                GlobalTransform::from(body_transform),
-                *child_transform,
-                "Collider transform should have have global rotation and translation"
-            );
+        let recording = app.world.resource_mut::<PhysicsRecorder>().stop().unwrap();
+        assert_eq!(recording.frames.len(), 40);
+        let inputs: Vec<_> = recording
+            .frames
+            .iter()
+            .flat_map(|frame| &frame.inputs)
+            .collect();
+        for kind in [
+            |input: &&PhysicsInput| matches!(input, PhysicsInput::Force { .. }),
+            |input: &&PhysicsInput| matches!(input, PhysicsInput::Impulse { .. }),
+            |input: &&PhysicsInput| matches!(input, PhysicsInput::KinematicTarget { .. }),
+            |input: &&PhysicsInput| matches!(input, PhysicsInput::SpawnRigidBody { .. }),
+            |input: &&PhysicsInput| matches!(input, PhysicsInput::DespawnCollider { .. }),
+        ] {
+            assert!(inputs.iter().any(kind));
         }
+
+        let mut playback = recording.play();
+        playback.run_to_end();
+        assert!(playback.is_finished());
+        assert_eq!(
+            playback.context().snapshot().checksum(),
+            app.world.resource::<RapierContext>().snapshot().checksum()
+        );
     }
 
     #[test]
-    fn transform_propagation2() {
+    fn physics_components_survive_dynamic_scene_round_trip() {
         let mut app = App::new();
         app.add_plugin(HeadlessRenderPlugin)
             .add_plugin(TransformPlugin)
             .add_plugin(TimePlugin)
             .add_plugin(RapierPhysicsPlugin::<NoUserData>::default());
 
-        let zero = (Transform::default(), Transform::default());
-
-        let different = (
-            Transform {
-                translation: Vec3::X * 10.0,
-                // NOTE: in 2D the test will fail if the rotation is wrt. an axis
-                //       other than Z because 2D physics objects can’t rotate wrt.
-                //       other axes.
-                rotation: Quat::from_rotation_z(PI),
-                ..Default::default()
-            },
-            Transform {
-                translation: Vec3::Y * 10.0,
-                rotation: Quat::from_rotation_z(PI),
-                ..Default::default()
-            },
-        );
-
-        let same = (different.0, different.0);
-
-        for (child_transform, parent_transform) in [zero, same, different] {
-            let child = app
-                .world
-                .spawn()
-                .insert_bundle(TransformBundle::from(child_transform))
-                .insert(Collider::ball(1.0))
-                .id();
-
-            let parent = app
-                .world
-                .spawn()
-                .insert_bundle(TransformBundle::from(parent_transform))
-                .insert(RigidBody::Fixed)
-                .push_children(&[child])
-                .id();
+        let center = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::default())
+            .insert(RigidBody::Fixed)
+            .insert(Collider::ball(1.0))
+            .insert(Carrying(vec![]))
+            .id();
+        let body = app
+            .world
+            .spawn()
+            .insert_bundle(TransformBundle::from(Transform::from_xyz(0.0, 3.0, 0.0)))
+            .insert(RigidBody::Dynamic)
+            .insert(Collider::ball(0.5))
+            .insert(RadialGravity::new(center, 9.81))
+            .insert(PulleyJoint::new(
+                center,
+                PulleyJointBuilder::new(Vect::X, -Vect::X, 4.0),
+            ))
+            .insert(FrictionOverrides::default().with(center, 0.1))
+            .insert(KinematicCharacterController {
+                custom_mass: Some(3.0),
+                ..default()
+            })
+            .insert(DynamicCharacterControllerOutput {
+                grounded: true,
+                ground: Some(center),
+                ground_normal: Vect::Y,
+            })
+            .id();
+        app.world.get_mut::<Carrying>(center).unwrap().0.push(body);
 
-            app.update();
+        let type_registry = app.world.resource::<TypeRegistryArc>().clone();
+        let scene = DynamicScene::from_world(&app.world, &type_registry);
+        let mut entity_map = EntityMap::default();
+        scene
+            .write_to_world(&mut app.world, &mut entity_map)
+            .unwrap();
 
-            let child_transform = app
-                .world
-                .entity(child)
-                .get::<GlobalTransform>()
+        let center_copy = entity_map.get(center).unwrap();
+        let body_copy = entity_map.get(body).unwrap();
+        assert_eq!(
+            app.world.get::<Carrying>(center_copy).unwrap().0,
+            vec![body_copy]
+        );
+        let world = &app.world;
+        assert_eq!(
+            world.get::<RadialGravity>(body_copy),
+            Some(&RadialGravity::new(center_copy, 9.81))
+        );
+        assert_eq!(
+            world.get::<PulleyJoint>(body_copy).unwrap().parent,
+            center_copy
+        );
+        assert_eq!(
+            world
+                .get::<FrictionOverrides>(body_copy)
                 .unwrap()
-                .compute_transform();
-            let context = app.world.resource::<RapierContext>();
-            let parent_handle = context.entity2body[&parent];
-            let parent_body = context.bodies.get(parent_handle).unwrap();
-            let child_collider_handle = parent_body.colliders()[0];
-            let child_collider = context.colliders.get(child_collider_handle).unwrap();
-            let body_transform =
-                utils::iso_to_transform(child_collider.position(), context.physics_scale);
-            approx::assert_relative_eq!(
-                body_transform.translation,
-                child_transform.translation,
-                epsilon = 1.0e-5
-            );
-
-            // Adjust signs to account for the quaternion’s double covering.
-            let comparison_child_rotation =
-                if body_transform.rotation.w * child_transform.rotation.w < 0.0 {
-                    -child_transform.rotation
-                } else {
-                    child_transform.rotation
-                };
+                .0
+                .get(&center_copy),
+            Some(&0.1)
+        );
+        assert_eq!(
+            world
+                .get::<DynamicCharacterControllerOutput>(body_copy)
+                .unwrap()
+                .ground,
+            Some(center_copy)
+        );
+        assert_eq!(
+            world
+                .get::<KinematicCharacterController>(body_copy)
+                .unwrap()
+                .custom_mass,
+            Some(3.0)
+        );
+        assert_eq!(
+            world
+                .get::<Collider>(body_copy)
+                .unwrap()
+                .as_ball()
+                .unwrap()
+                .radius(),
+            0.5
+        );
 
-            approx::assert_relative_eq!(
-                body_transform.rotation,
-                comparison_child_rotation,
-                epsilon = 1.0e-5
-            );
-            approx::assert_relative_eq!(body_transform.scale, child_transform.scale,);
-        }
+        app.update();
+        assert!(app.world.get::<RapierRigidBodyHandle>(body_copy).is_some());
+        assert!(app.world.get::<RapierColliderHandle>(body_copy).is_some());
     }
 
     // Allows run tests for systems containing rendering related things without GPU
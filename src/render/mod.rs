@@ -1,20 +1,26 @@
 use crate::plugin::RapierContext;
 use crate::render::lines::DebugLinesConfig;
-use bevy::prelude::*;
+use bevy::{prelude::*, reflect::FromReflect};
 use lines::DebugLines;
 use rapier::math::{Point, Real};
 use rapier::pipeline::{DebugRenderBackend, DebugRenderObject, DebugRenderPipeline};
 pub use rapier::pipeline::{DebugRenderMode, DebugRenderStyle};
 use std::fmt::Debug;
 
+#[cfg(feature = "debug-stream")]
+pub use self::viewer::{DebugStreamViewer, DebugStreamViewerPlugin};
+
 mod lines;
+#[cfg(feature = "debug-stream")]
+mod viewer;
 
 /// The color of a collider when using the debug-renderer.
 ///
 /// Insert this component alongside the collider component to
 /// force to a specific value the color used to render the
 /// collider.
-#[derive(Copy, Clone, Component, PartialEq, Debug)]
+#[derive(Copy, Clone, Component, PartialEq, Debug, Default, Reflect, FromReflect)]
+#[reflect(Component, PartialEq)]
 pub struct ColliderDebugColor(pub Color);
 
 /// Plugin rensponsible for rendering (using lines) what Rapier "sees" when performing
@@ -97,6 +103,7 @@ impl Default for DebugRenderContext {
 impl Plugin for RapierDebugRenderPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(lines::DebugLinesPlugin::always_on_top(self.always_on_top))
+            .register_type::<ColliderDebugColor>()
             .insert_resource(DebugRenderContext {
                 enabled: true,
                 pipeline: DebugRenderPipeline::new(self.style, self.mode),
@@ -0,0 +1,130 @@
+use crate::plugin::{DebugStreamFrame, DebugStreamReader};
+use crate::render::lines::{DebugLines, DebugLinesPlugin};
+use bevy::prelude::*;
+use std::io;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::Mutex;
+
+/// Plugin replaying in a client app the physics debug-render recorded by the
+/// `DebugStreamRecorderPlugin` of another app, e.g. of a headless server, with the
+/// `debug-stream` feature.
+///
+/// The stream to replay is given to the [`DebugStreamViewer`] resource. The lines of its last
+/// received frame are drawn at each update, like the ones of the `RapierDebugRenderPlugin`.
+#[derive(Default)]
+pub struct DebugStreamViewerPlugin {
+    /// If set to `true`, depth-testing will be disabled when rendering,
+    /// meaning that the replayed lines will always appear on top
+    /// of (won’t be occluded by) your own visual assets.
+    pub always_on_top: bool,
+}
+
+impl Plugin for DebugStreamViewerPlugin {
+    fn build(&self, app: &mut App) {
+        // The lines may already be drawn by the `RapierDebugRenderPlugin`.
+        if !app.world.contains_resource::<DebugLines>() {
+            app.add_plugin(DebugLinesPlugin::always_on_top(self.always_on_top));
+        }
+
+        app.init_resource::<DebugStreamViewer>()
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                view_debug_stream.before("draw_lines"),
+            );
+    }
+}
+
+/// Resource holding the stream replayed by the [`DebugStreamViewerPlugin`], and its last
+/// received frame.
+#[derive(Default)]
+pub struct DebugStreamViewer {
+    /// Are the lines of the current frame drawn?
+    pub enabled: bool,
+    receiver: Option<Mutex<Receiver<io::Result<DebugStreamFrame>>>>,
+    frame: Option<DebugStreamFrame>,
+}
+
+impl DebugStreamViewer {
+    /// Starts replaying the given stream, replacing the current one.
+    ///
+    /// The frames are read by a background thread, so that a stream read from a socket doesn’t
+    /// block the app while waiting for the next frame.
+    pub fn connect(&mut self, mut reader: DebugStreamReader) {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || loop {
+            match reader.read_frame() {
+                Ok(Some(frame)) => {
+                    if sender.send(Ok(frame)).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(err) => {
+                    let _ = sender.send(Err(err));
+                    break;
+                }
+            }
+        });
+
+        self.enabled = true;
+        self.receiver = Some(Mutex::new(receiver));
+        self.frame = None;
+    }
+
+    /// Stops replaying the current stream. Its last frame is kept.
+    pub fn disconnect(&mut self) {
+        self.receiver = None;
+    }
+
+    /// Whether a stream is being replayed.
+    pub fn is_connected(&self) -> bool {
+        self.receiver.is_some()
+    }
+
+    /// The last received frame, e.g. to read the poses of its rigid-bodies.
+    pub fn frame(&self) -> Option<&DebugStreamFrame> {
+        self.frame.as_ref()
+    }
+
+    // Receives the frames read since the last update, keeping the last one.
+    fn receive(&mut self) {
+        let receiver = match &mut self.receiver {
+            Some(receiver) => receiver.get_mut().unwrap(),
+            None => return,
+        };
+
+        loop {
+            match receiver.try_recv() {
+                Ok(Ok(frame)) => self.frame = Some(frame),
+                Ok(Err(err)) => {
+                    error!("Unable to read the physics debug stream: {}", err);
+                    self.receiver = None;
+                    return;
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.receiver = None;
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn view_debug_stream(mut viewer: ResMut<DebugStreamViewer>, mut lines: ResMut<DebugLines>) {
+    viewer.receive();
+    if !viewer.enabled {
+        return;
+    }
+
+    if let Some(frame) = &viewer.frame {
+        for line in &frame.lines {
+            #[cfg(feature = "dim2")]
+            let (start, end) = (line.a.extend(0.0), line.b.extend(0.0));
+            #[cfg(feature = "dim3")]
+            let (start, end) = (line.a, line.b);
+            let [r, g, b, a] = line.color;
+            lines.line_colored(start, end, 0.0, Color::rgba_u8(r, g, b, a));
+        }
+    }
+}